@@ -1,5 +1,5 @@
 import type { ValidationChecks, ValidationAcceptor } from 'langium';
-import type { ClIvilizationAstType, City, Model } from './generated/ast.js';
+import type { ClIvilizationAstType, City, CivTrait, MapEvent, Model, PolicyDefaults, Prereq, Puzzle, Region, Regions, Scoring, TechDef, TurnRules, War } from './generated/ast.js';
 import type { ClIvilizationServices } from './clivilization-module.js';
 
 /**
@@ -12,7 +12,27 @@ export function registerValidationChecks(services: ClIvilizationServices) {
         // Validate each city
         City: validator.checkCity,
         // Validate model-wide constraints (unique names)
-        Model: validator.checkModel
+        Model: validator.checkModel,
+        // Validate puzzle objective and target
+        Puzzle: validator.checkPuzzle,
+        // Validate policy slider ranges
+        PolicyDefaults: validator.checkPolicyDefaults,
+        // Validate the spoils of war percentages
+        War: validator.checkWar,
+        // Validate the wonders counted by the scoring
+        Scoring: validator.checkScoring,
+        // Validate the action points allowance
+        TurnRules: validator.checkTurnRules,
+        // Validate the city and percentages of each trait
+        CivTrait: validator.checkCivTrait,
+        // Validate the techs required and unlocked by each tech
+        TechDef: validator.checkTechDef,
+        // Validate the tech named by a prerequisite
+        Prereq: validator.checkPrereq,
+        // Validate that crossings link two regions
+        Regions: validator.checkRegions,
+        // Validate that map events are on the map
+        MapEvent: validator.checkMapEvent
     };
     registry.register(checks, validator);
 }
@@ -183,4 +203,180 @@ export class ClIvilizationValidator {
             }
         }
     }
-}
\ No newline at end of file
+
+    // Puzzle checks: ELIMINATE needs a target, and the target must be a declared city
+    checkPuzzle(puzzle: Puzzle, accept: ValidationAcceptor): void {
+        if (puzzle.objective === 'ELIMINATE' && puzzle.target === undefined) {
+            accept('error', `Puzzle objective 'ELIMINATE' requires a 'target' city.`, { node: puzzle });
+        }
+        if (puzzle.target === undefined) {
+            return;
+        }
+
+        const model = puzzle.$container;
+        const cityNames = new Set<string>();
+        for (const sec of model.sections) {
+            if (sec && Array.isArray((sec as any).cities)) {
+                for (const c of (sec as any).cities) {
+                    if (typeof c?.name === 'string') {
+                        cityNames.add(c.name);
+                    }
+                }
+            }
+        }
+        if (!cityNames.has(puzzle.target)) {
+            accept('error', `Puzzle target '${puzzle.target}' is not defined in [cities] section.`, { node: puzzle, property: 'target' });
+        }
+    }
+
+    // Wonders of the scoring must be defined buildings
+    checkScoring(scoring: Scoring, accept: ValidationAcceptor): void {
+        if (scoring.wonders === undefined) {
+            return;
+        }
+        const buildingNames = new Set<string>();
+        for (const sec of scoring.$container.sections) {
+            if (sec && Array.isArray((sec as any).buildings)) {
+                for (const b of (sec as any).buildings) {
+                    if (typeof b?.name === 'string') {
+                        buildingNames.add(b.name);
+                    }
+                }
+            }
+        }
+        for (const wonder of scoring.wonders.values) {
+            if (!buildingNames.has(wonder)) {
+                accept('error', `Wonder '${wonder}' is not defined in [buildings] section.`, { node: scoring, property: 'wonders' });
+            }
+        }
+    }
+
+    // A turn without any action point could never do anything
+    checkTurnRules(rules: TurnRules, accept: ValidationAcceptor): void {
+        if (rules.action_points === 0) {
+            accept('error', 'Action points must be at least 1.', { node: rules, property: 'action_points' });
+        }
+    }
+
+    // Traits must name a declared city, and their percentages stay between 0 and 100
+    checkCivTrait(civTrait: CivTrait, accept: ValidationAcceptor): void {
+        const model = civTrait.$container.$container;
+        const cityNames = new Set<string>();
+        const buildingNames = new Set<string>();
+        for (const sec of model.sections) {
+            if (sec && Array.isArray((sec as any).cities)) {
+                for (const c of (sec as any).cities) {
+                    if (typeof c?.name === 'string') {
+                        cityNames.add(c.name);
+                    }
+                }
+            }
+            if (sec && Array.isArray((sec as any).buildings)) {
+                for (const b of (sec as any).buildings) {
+                    if (typeof b?.name === 'string') {
+                        buildingNames.add(b.name);
+                    }
+                }
+            }
+        }
+        if (!cityNames.has(civTrait.city)) {
+            accept('error', `Traits of '${civTrait.city}': the city is not defined in [cities] section.`, { node: civTrait, property: 'city' });
+        }
+        for (const property of ['building_discount', 'travel_speed'] as const) {
+            const value = civTrait[property];
+            if (value !== undefined && value > 100) {
+                accept('error', `Trait '${property}' must be between 0 and 100.`, { node: civTrait, property });
+            }
+        }
+        for (const building of civTrait.discounted_buildings?.values ?? []) {
+            if (!buildingNames.has(building)) {
+                accept('error', `Discounted building '${building}' is not defined in [buildings] section.`, { node: civTrait, property: 'discounted_buildings' });
+            }
+        }
+    }
+
+    // Techs must require declared techs and unlock declared buildings or units
+    checkTechDef(tech: TechDef, accept: ValidationAcceptor): void {
+        const model = tech.$container.$container;
+        const techNames = new Set<string>();
+        const unlockable = new Set<string>();
+        for (const sec of model.sections) {
+            for (const t of (sec as any).techs ?? []) {
+                techNames.add(t.name);
+            }
+            for (const def of [...((sec as any).buildings ?? []), ...((sec as any).units ?? [])]) {
+                if (typeof def?.name === 'string') {
+                    unlockable.add(def.name);
+                }
+            }
+        }
+        for (const required of tech.requires?.values ?? []) {
+            if (!techNames.has(required)) {
+                accept('error', `Tech '${tech.name}' requires '${required}', which is not defined in [techs] section.`, { node: tech, property: 'requires' });
+            }
+        }
+        for (const unlocked of tech.unlocks?.values ?? []) {
+            if (!unlockable.has(unlocked)) {
+                accept('error', `Tech '${tech.name}' unlocks '${unlocked}', which is not a defined building or unit.`, { node: tech, property: 'unlocks' });
+            }
+        }
+    }
+
+    // A tech prerequisite must name a declared tech
+    checkPrereq(prereq: Prereq, accept: ValidationAcceptor): void {
+        if (prereq.id_tech === undefined) {
+            return;
+        }
+        let root: any = prereq;
+        while (root && root.$container) {
+            root = root.$container;
+        }
+        const model = root as Model;
+        const declared = model.sections.some(sec => ((sec as any).techs ?? []).some((t: TechDef) => t.name === prereq.id_tech));
+        if (!declared) {
+            accept('error', `Prerequisite tech '${prereq.id_tech}' is not defined in [techs] section.`, { node: prereq, property: 'id_tech' });
+        }
+    }
+
+    // Both ends of a crossing must be inside two different regions
+    checkRegions(regions: Regions, accept: ValidationAcceptor): void {
+        const regionAt = (x: number, y: number): Region | undefined => regions.regions.find(r =>
+            x >= r.x && x < r.x + r.width && y >= r.y && y < r.y + r.height);
+        regions.crossings.forEach((crossing, index) => {
+            const from = regionAt(crossing.from_x, crossing.from_y);
+            const to = regionAt(crossing.to_x, crossing.to_y);
+            if (from === undefined || to === undefined || from === to) {
+                accept('error', `Crossing from ${crossing.from_x},${crossing.from_y} to ${crossing.to_x},${crossing.to_y} does not link two regions.`, { node: regions, property: 'crossings', index });
+            }
+        });
+    }
+
+    // A map event must be centered on a tile of the map declared in [game]
+    checkMapEvent(event: MapEvent, accept: ValidationAcceptor): void {
+        const model = event.$container.$container;
+        const game = model.sections.find(s => s.$type === 'Game') as { map_x: number, map_y: number } | undefined;
+        if (game && (event.x >= game.map_x || event.y >= game.map_y)) {
+            accept('error', `Map event '${event.name}' is outside the ${game.map_x}x${game.map_y} map.`, { node: event, property: event.x >= game.map_x ? 'x' : 'y' });
+        }
+    }
+
+    // Spoils of war are percentages
+    checkWar(war: War, accept: ValidationAcceptor): void {
+        for (const property of ['elimination_share', 'reparations'] as const) {
+            const value = war[property];
+            if (value !== undefined && value > 100) {
+                accept('error', `War '${property}' must be between 0 and 100.`, { node: war, property });
+            }
+        }
+    }
+
+    // Policy sliders are percentages
+    checkPolicyDefaults(policy: PolicyDefaults, accept: ValidationAcceptor): void {
+        for (const property of ['tax', 'military'] as const) {
+            const value = policy[property];
+            if (value !== undefined && value > 100) {
+                accept('error', `Policy '${property}' must be between 0 and 100.`, { node: policy, property });
+            }
+        }
+    }
+}
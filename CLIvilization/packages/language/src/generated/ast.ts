@@ -22,47 +22,165 @@ export type ClIvilizationKeywordNames =
     | ","
     | "="
     | "AI"
+    | "ALLIANCE"
+    | "ATTACK"
+    | "BUILDING"
+    | "CONTINENTS"
+    | "COUNTERINTEL"
+    | "DESERT"
+    | "ELIMINATE"
+    | "END_TURN"
+    | "FOREST"
+    | "IDLE_CONSTRUCTION"
+    | "IDLE_RECRUITMENT"
+    | "INFLUENCE"
+    | "ISLANDS"
+    | "MOUNTAIN"
+    | "NO_INCOME"
+    | "PANGAEA"
+    | "PEACE"
+    | "PLAINS"
     | "PLAYER"
+    | "REFUSE"
     | "RESSOURCE"
+    | "SNOW"
+    | "SURVIVE"
+    | "SWAMP"
     | "UNIT"
+    | "WAIT"
+    | "WAR"
+    | "WATER"
     | "["
     | "[buildings]"
     | "[cities]"
+    | "[diplomacy]"
     | "[game]"
+    | "[map_events]"
+    | "[puzzle]"
+    | "[raiders]"
+    | "[regions]"
+    | "[scoring]"
+    | "[surrender]"
+    | "[techs]"
+    | "[traits]"
+    | "[turn_rules]"
+    | "[tutorial]"
     | "[units]"
     | "[victory_conditions]"
+    | "[war]"
     | "]"
+    | "action_points"
+    | "agenda"
+    | "ai_seed"
     | "amount"
+    | "army_stack"
     | "attack"
+    | "attrition"
+    | "biomes"
     | "blacklist_buildings"
     | "blacklist_units"
+    | "block_end_if"
+    | "build_queue"
     | "build_time"
+    | "building_discount"
+    | "cancel_refund"
+    | "capture"
+    | "city_stack"
     | "color"
     | "cost"
+    | "crossings"
     | "current_turn"
+    | "defense"
+    | "discounted_buildings"
+    | "elimination_share"
+    | "every"
+    | "expect"
+    | "extra_units"
+    | "favor_threshold"
+    | "first_turn"
+    | "fog_of_war"
+    | "forest"
+    | "from_x"
+    | "from_y"
+    | "gate"
+    | "growth"
+    | "height"
     | "id_building"
+    | "id_tech"
     | "id_units"
+    | "income"
+    | "income_bonus"
+    | "label"
     | "level"
+    | "maintenance"
+    | "map_type"
     | "map_x"
     | "map_y"
+    | "max_actions"
+    | "max_builds"
+    | "max_turns"
+    | "military"
     | "nb_slots_buildings"
     | "nb_slots_units"
     | "nb_turns"
     | "nb_units"
+    | "objective"
+    | "off"
+    | "on"
+    | "partner"
+    | "per_building"
+    | "per_resource"
+    | "per_turn"
+    | "per_unit"
+    | "per_wonder"
+    | "persona"
+    | "pillage"
     | "player_type"
+    | "policy"
+    | "power"
     | "prerequisites"
     | "production"
+    | "radius"
+    | "reparations"
+    | "requires"
+    | "research_time"
     | "resources_spent"
+    | "season_length"
     | "seed"
     | "slots"
+    | "snow"
+    | "sortie"
+    | "stack_overflow"
+    | "stance"
     | "starting_buildings"
     | "starting_resources"
     | "starting_units"
+    | "step"
+    | "storage"
+    | "strength"
+    | "supply_range"
+    | "swamp"
+    | "target"
+    | "tax"
+    | "terraform"
+    | "terrain"
+    | "text"
     | "time"
+    | "to_x"
+    | "to_y"
+    | "travel_speed"
+    | "turn"
+    | "turns"
     | "type"
     | "ui_color"
+    | "unlocks"
+    | "upkeep"
+    | "weights"
     | "whitelist_buildings"
     | "whitelist_units"
+    | "width"
+    | "wonders"
+    | "wrap"
     | "x"
     | "y"
     | "{"
@@ -70,25 +188,58 @@ export type ClIvilizationKeywordNames =
 
 export type ClIvilizationTokenNames = ClIvilizationTerminalNames | ClIvilizationKeywordNames;
 
+export interface BiomeThresholds extends langium.AstNode {
+    readonly $container: Game;
+    readonly $type: 'BiomeThresholds';
+    forest?: number;
+    snow?: number;
+    swamp?: number;
+}
+
+export const BiomeThresholds = {
+    $type: 'BiomeThresholds',
+    forest: 'forest',
+    snow: 'snow',
+    swamp: 'swamp'
+} as const;
+
+export function isBiomeThresholds(item: unknown): item is BiomeThresholds {
+    return reflection.isInstance(item, BiomeThresholds.$type);
+}
+
+export type BlockingCondition = 'IDLE_CONSTRUCTION' | 'IDLE_RECRUITMENT' | 'NO_INCOME';
+
+export function isBlockingCondition(item: unknown): item is BlockingCondition {
+    return item === 'NO_INCOME' || item === 'IDLE_CONSTRUCTION' || item === 'IDLE_RECRUITMENT';
+}
+
 export interface BuildingDef extends langium.AstNode {
     readonly $container: BuildingDefArray;
     readonly $type: 'BuildingDef';
     build_time: number;
     cost: number;
+    defense?: number;
+    maintenance?: number;
     name: Value;
     prerequisites: PrereqArray;
     production: Production;
     slots: number;
+    storage?: number;
+    terraform?: Terraform;
 }
 
 export const BuildingDef = {
     $type: 'BuildingDef',
     build_time: 'build_time',
     cost: 'cost',
+    defense: 'defense',
+    maintenance: 'maintenance',
     name: 'name',
     prerequisites: 'prerequisites',
     production: 'production',
-    slots: 'slots'
+    slots: 'slots',
+    storage: 'storage',
+    terraform: 'terraform'
 } as const;
 
 export function isBuildingDef(item: unknown): item is BuildingDef {
@@ -160,6 +311,8 @@ export function isCities(item: unknown): item is Cities {
 export interface City extends langium.AstNode {
     readonly $container: Cities;
     readonly $type: 'City';
+    agenda?: string;
+    ai_seed?: number;
     blacklist_buildings?: ValueArray;
     blacklist_units?: ValueArray;
     buildings: BuildingInstanceArray;
@@ -167,7 +320,9 @@ export interface City extends langium.AstNode {
     name: Value;
     nb_slots_buildings: number;
     nb_slots_units: number;
+    persona?: string;
     player_type: PlayerType;
+    policy?: PolicyDefaults;
     starting_resources: number;
     units: UnitInstanceArray;
     whitelist_buildings?: ValueArray;
@@ -178,6 +333,8 @@ export interface City extends langium.AstNode {
 
 export const City = {
     $type: 'City',
+    agenda: 'agenda',
+    ai_seed: 'ai_seed',
     blacklist_buildings: 'blacklist_buildings',
     blacklist_units: 'blacklist_units',
     buildings: 'buildings',
@@ -185,7 +342,9 @@ export const City = {
     name: 'name',
     nb_slots_buildings: 'nb_slots_buildings',
     nb_slots_units: 'nb_slots_units',
+    persona: 'persona',
     player_type: 'player_type',
+    policy: 'policy',
     starting_resources: 'starting_resources',
     units: 'units',
     whitelist_buildings: 'whitelist_buildings',
@@ -198,23 +357,101 @@ export function isCity(item: unknown): item is City {
     return reflection.isInstance(item, City.$type);
 }
 
+export interface CivTrait extends langium.AstNode {
+    readonly $container: Traits;
+    readonly $type: 'CivTrait';
+    building_discount?: number;
+    city: Value;
+    discounted_buildings?: ValueArray;
+    extra_units?: UnitInstanceArray;
+    income_bonus?: number;
+    label?: Value;
+    travel_speed?: number;
+}
+
+export const CivTrait = {
+    $type: 'CivTrait',
+    building_discount: 'building_discount',
+    city: 'city',
+    discounted_buildings: 'discounted_buildings',
+    extra_units: 'extra_units',
+    income_bonus: 'income_bonus',
+    label: 'label',
+    travel_speed: 'travel_speed'
+} as const;
+
+export function isCivTrait(item: unknown): item is CivTrait {
+    return reflection.isInstance(item, CivTrait.$type);
+}
+
+export interface Crossing extends langium.AstNode {
+    readonly $container: Regions;
+    readonly $type: 'Crossing';
+    from_x: number;
+    from_y: number;
+    to_x: number;
+    to_y: number;
+    turns: number;
+}
+
+export const Crossing = {
+    $type: 'Crossing',
+    from_x: 'from_x',
+    from_y: 'from_y',
+    to_x: 'to_x',
+    to_y: 'to_y',
+    turns: 'turns'
+} as const;
+
+export function isCrossing(item: unknown): item is Crossing {
+    return reflection.isInstance(item, Crossing.$type);
+}
+
 export interface Game extends langium.AstNode {
     readonly $container: Model;
     readonly $type: 'Game';
+    army_stack?: number;
+    attrition?: number;
+    biomes?: BiomeThresholds;
+    build_queue?: number;
+    cancel_refund?: number;
+    capture?: Toggle;
+    city_stack?: number;
     current_turn: number;
+    fog_of_war?: Toggle;
+    map_type?: MapType;
     map_x: number;
     map_y: number;
+    season_length?: number;
     seed?: Value;
+    stack_overflow?: StackOverflow;
+    storage?: number;
+    supply_range?: number;
     ui_color: string;
+    wrap?: Toggle;
 }
 
 export const Game = {
     $type: 'Game',
+    army_stack: 'army_stack',
+    attrition: 'attrition',
+    biomes: 'biomes',
+    build_queue: 'build_queue',
+    cancel_refund: 'cancel_refund',
+    capture: 'capture',
+    city_stack: 'city_stack',
     current_turn: 'current_turn',
+    fog_of_war: 'fog_of_war',
+    map_type: 'map_type',
     map_x: 'map_x',
     map_y: 'map_y',
+    season_length: 'season_length',
     seed: 'seed',
-    ui_color: 'ui_color'
+    stack_overflow: 'stack_overflow',
+    storage: 'storage',
+    supply_range: 'supply_range',
+    ui_color: 'ui_color',
+    wrap: 'wrap'
 } as const;
 
 export function isGame(item: unknown): item is Game {
@@ -235,6 +472,50 @@ export function isIntArray(item: unknown): item is IntArray {
     return reflection.isInstance(item, IntArray.$type);
 }
 
+export interface MapEvent extends langium.AstNode {
+    readonly $container: MapEvents;
+    readonly $type: 'MapEvent';
+    name: Value;
+    terraform: Terraform;
+    turn: number;
+    x: number;
+    y: number;
+}
+
+export const MapEvent = {
+    $type: 'MapEvent',
+    name: 'name',
+    terraform: 'terraform',
+    turn: 'turn',
+    x: 'x',
+    y: 'y'
+} as const;
+
+export function isMapEvent(item: unknown): item is MapEvent {
+    return reflection.isInstance(item, MapEvent.$type);
+}
+
+export interface MapEvents extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'MapEvents';
+    events: Array<MapEvent>;
+}
+
+export const MapEvents = {
+    $type: 'MapEvents',
+    events: 'events'
+} as const;
+
+export function isMapEvents(item: unknown): item is MapEvents {
+    return reflection.isInstance(item, MapEvents.$type);
+}
+
+export type MapType = 'CONTINENTS' | 'ISLANDS' | 'PANGAEA';
+
+export function isMapType(item: unknown): item is MapType {
+    return item === 'CONTINENTS' || item === 'ISLANDS' || item === 'PANGAEA';
+}
+
 export interface Model extends langium.AstNode {
     readonly $type: 'Model';
     sections: Array<Section>;
@@ -255,15 +536,36 @@ export function isPlayerType(item: unknown): item is PlayerType {
     return item === 'PLAYER' || item === 'AI';
 }
 
+export interface PolicyDefaults extends langium.AstNode {
+    readonly $container: City;
+    readonly $type: 'PolicyDefaults';
+    military?: number;
+    sortie?: Toggle;
+    tax?: number;
+}
+
+export const PolicyDefaults = {
+    $type: 'PolicyDefaults',
+    military: 'military',
+    sortie: 'sortie',
+    tax: 'tax'
+} as const;
+
+export function isPolicyDefaults(item: unknown): item is PolicyDefaults {
+    return reflection.isInstance(item, PolicyDefaults.$type);
+}
+
 export interface Prereq extends langium.AstNode {
     readonly $container: PrereqArray;
     readonly $type: 'Prereq';
-    id_building: Value;
+    id_building?: Value;
+    id_tech?: Value;
 }
 
 export const Prereq = {
     $type: 'Prereq',
-    id_building: 'id_building'
+    id_building: 'id_building',
+    id_tech: 'id_tech'
 } as const;
 
 export function isPrereq(item: unknown): item is Prereq {
@@ -271,7 +573,7 @@ export function isPrereq(item: unknown): item is Prereq {
 }
 
 export interface PrereqArray extends langium.AstNode {
-    readonly $container: BuildingDef;
+    readonly $container: BuildingDef | UnitDef;
     readonly $type: 'PrereqArray';
     prereqs: Array<Prereq>;
 }
@@ -308,13 +610,181 @@ export function isProduction(item: unknown): item is Production {
     return reflection.isInstance(item, Production.$type);
 }
 
-export type ProductionType = 'RESSOURCE' | 'UNIT';
+export type ProductionType = 'COUNTERINTEL' | 'INFLUENCE' | 'RESSOURCE' | 'UNIT';
 
 export function isProductionType(item: unknown): item is ProductionType {
-    return item === 'UNIT' || item === 'RESSOURCE';
+    return item === 'UNIT' || item === 'RESSOURCE' || item === 'INFLUENCE' || item === 'COUNTERINTEL';
+}
+
+export interface Puzzle extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'Puzzle';
+    max_actions?: number;
+    max_builds?: number;
+    max_turns?: number;
+    objective: PuzzleObjective;
+    target?: Value;
+}
+
+export const Puzzle = {
+    $type: 'Puzzle',
+    max_actions: 'max_actions',
+    max_builds: 'max_builds',
+    max_turns: 'max_turns',
+    objective: 'objective',
+    target: 'target'
+} as const;
+
+export function isPuzzle(item: unknown): item is Puzzle {
+    return reflection.isInstance(item, Puzzle.$type);
+}
+
+export type PuzzleObjective = 'ELIMINATE' | 'SURVIVE';
+
+export function isPuzzleObjective(item: unknown): item is PuzzleObjective {
+    return item === 'ELIMINATE' || item === 'SURVIVE';
+}
+
+export interface Raiders extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'Raiders';
+    every: number;
+    first_turn?: number;
+    growth?: number;
+    pillage?: number;
+    strength: number;
+}
+
+export const Raiders = {
+    $type: 'Raiders',
+    every: 'every',
+    first_turn: 'first_turn',
+    growth: 'growth',
+    pillage: 'pillage',
+    strength: 'strength'
+} as const;
+
+export function isRaiders(item: unknown): item is Raiders {
+    return reflection.isInstance(item, Raiders.$type);
+}
+
+export interface Region extends langium.AstNode {
+    readonly $container: Regions;
+    readonly $type: 'Region';
+    height: number;
+    name: Value;
+    width: number;
+    x: number;
+    y: number;
+}
+
+export const Region = {
+    $type: 'Region',
+    height: 'height',
+    name: 'name',
+    width: 'width',
+    x: 'x',
+    y: 'y'
+} as const;
+
+export function isRegion(item: unknown): item is Region {
+    return reflection.isInstance(item, Region.$type);
+}
+
+export interface Regions extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'Regions';
+    crossings: Array<Crossing>;
+    regions: Array<Region>;
+}
+
+export const Regions = {
+    $type: 'Regions',
+    crossings: 'crossings',
+    regions: 'regions'
+} as const;
+
+export function isRegions(item: unknown): item is Regions {
+    return reflection.isInstance(item, Regions.$type);
+}
+
+export interface Relation extends langium.AstNode {
+    readonly $container: Relations;
+    readonly $type: 'Relation';
+    city: Value;
+    partner: Value;
+    stance: Stance;
+    turn?: number;
+}
+
+export const Relation = {
+    $type: 'Relation',
+    city: 'city',
+    partner: 'partner',
+    stance: 'stance',
+    turn: 'turn'
+} as const;
+
+export function isRelation(item: unknown): item is Relation {
+    return reflection.isInstance(item, Relation.$type);
+}
+
+export interface Relations extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'Relations';
+    relations: Array<Relation>;
+}
+
+export const Relations = {
+    $type: 'Relations',
+    relations: 'relations'
+} as const;
+
+export function isRelations(item: unknown): item is Relations {
+    return reflection.isInstance(item, Relations.$type);
 }
 
-export type Section = BuildingDefArray | Cities | Game | UnitDefArray | VictoryConditions;
+export interface Scoring extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'Scoring';
+    weights: ScoringWeights;
+    wonders?: ValueArray;
+}
+
+export const Scoring = {
+    $type: 'Scoring',
+    weights: 'weights',
+    wonders: 'wonders'
+} as const;
+
+export function isScoring(item: unknown): item is Scoring {
+    return reflection.isInstance(item, Scoring.$type);
+}
+
+export interface ScoringWeights extends langium.AstNode {
+    readonly $container: Scoring;
+    readonly $type: 'ScoringWeights';
+    per_building?: number;
+    per_resource?: number;
+    per_turn?: number;
+    per_unit?: number;
+    per_wonder?: number;
+}
+
+export const ScoringWeights = {
+    $type: 'ScoringWeights',
+    per_building: 'per_building',
+    per_resource: 'per_resource',
+    per_turn: 'per_turn',
+    per_unit: 'per_unit',
+    per_wonder: 'per_wonder'
+} as const;
+
+export function isScoringWeights(item: unknown): item is ScoringWeights {
+    return reflection.isInstance(item, ScoringWeights.$type);
+}
+
+export type Section = BuildingDefArray | Cities | Game | MapEvents | Puzzle | Raiders | Regions | Relations | Scoring | Surrender | TechDefArray | Traits | TurnRules | Tutorial | UnitDefArray | VictoryConditions | War;
 
 export const Section = {
     $type: 'Section'
@@ -324,17 +794,195 @@ export function isSection(item: unknown): item is Section {
     return reflection.isInstance(item, Section.$type);
 }
 
+export type StackOverflow = 'REFUSE' | 'WAIT';
+
+export function isStackOverflow(item: unknown): item is StackOverflow {
+    return item === 'WAIT' || item === 'REFUSE';
+}
+
+export type Stance = 'ALLIANCE' | 'PEACE' | 'WAR';
+
+export function isStance(item: unknown): item is Stance {
+    return item === 'WAR' || item === 'PEACE' || item === 'ALLIANCE';
+}
+
+export interface Surrender extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'Surrender';
+    income?: number;
+    power?: number;
+    turns: number;
+}
+
+export const Surrender = {
+    $type: 'Surrender',
+    income: 'income',
+    power: 'power',
+    turns: 'turns'
+} as const;
+
+export function isSurrender(item: unknown): item is Surrender {
+    return reflection.isInstance(item, Surrender.$type);
+}
+
+export interface TechDef extends langium.AstNode {
+    readonly $container: TechDefArray;
+    readonly $type: 'TechDef';
+    cost: number;
+    name: Value;
+    requires?: ValueArray;
+    research_time: number;
+    unlocks?: ValueArray;
+}
+
+export const TechDef = {
+    $type: 'TechDef',
+    cost: 'cost',
+    name: 'name',
+    requires: 'requires',
+    research_time: 'research_time',
+    unlocks: 'unlocks'
+} as const;
+
+export function isTechDef(item: unknown): item is TechDef {
+    return reflection.isInstance(item, TechDef.$type);
+}
+
+export interface TechDefArray extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'TechDefArray';
+    techs: Array<TechDef>;
+}
+
+export const TechDefArray = {
+    $type: 'TechDefArray',
+    techs: 'techs'
+} as const;
+
+export function isTechDefArray(item: unknown): item is TechDefArray {
+    return reflection.isInstance(item, TechDefArray.$type);
+}
+
+export interface Terraform extends langium.AstNode {
+    readonly $container: BuildingDef | MapEvent;
+    readonly $type: 'Terraform';
+    radius?: number;
+    terrain: TerrainType;
+}
+
+export const Terraform = {
+    $type: 'Terraform',
+    radius: 'radius',
+    terrain: 'terrain'
+} as const;
+
+export function isTerraform(item: unknown): item is Terraform {
+    return reflection.isInstance(item, Terraform.$type);
+}
+
+export type TerrainType = 'DESERT' | 'FOREST' | 'MOUNTAIN' | 'PLAINS' | 'SNOW' | 'SWAMP' | 'WATER';
+
+export function isTerrainType(item: unknown): item is TerrainType {
+    return item === 'WATER' || item === 'PLAINS' || item === 'DESERT' || item === 'MOUNTAIN' || item === 'FOREST' || item === 'SNOW' || item === 'SWAMP';
+}
+
+export type Toggle = 'off' | 'on';
+
+export function isToggle(item: unknown): item is Toggle {
+    return item === 'on' || item === 'off';
+}
+
+export interface Traits extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'Traits';
+    traits: Array<CivTrait>;
+}
+
+export const Traits = {
+    $type: 'Traits',
+    traits: 'traits'
+} as const;
+
+export function isTraits(item: unknown): item is Traits {
+    return reflection.isInstance(item, Traits.$type);
+}
+
+export interface TurnRules extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'TurnRules';
+    action_points?: number;
+    block_end_if: Array<BlockingCondition>;
+}
+
+export const TurnRules = {
+    $type: 'TurnRules',
+    action_points: 'action_points',
+    block_end_if: 'block_end_if'
+} as const;
+
+export function isTurnRules(item: unknown): item is TurnRules {
+    return reflection.isInstance(item, TurnRules.$type);
+}
+
+export interface Tutorial extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'Tutorial';
+    steps: Array<TutorialStep>;
+}
+
+export const Tutorial = {
+    $type: 'Tutorial',
+    steps: 'steps'
+} as const;
+
+export function isTutorial(item: unknown): item is Tutorial {
+    return reflection.isInstance(item, Tutorial.$type);
+}
+
+export type TutorialGate = 'ATTACK' | 'BUILDING' | 'END_TURN' | 'UNIT';
+
+export function isTutorialGate(item: unknown): item is TutorialGate {
+    return item === 'BUILDING' || item === 'UNIT' || item === 'ATTACK' || item === 'END_TURN';
+}
+
+export interface TutorialStep extends langium.AstNode {
+    readonly $container: Tutorial;
+    readonly $type: 'TutorialStep';
+    expect?: Value;
+    gate: TutorialGate;
+    target?: Value;
+    text: Value;
+}
+
+export const TutorialStep = {
+    $type: 'TutorialStep',
+    expect: 'expect',
+    gate: 'gate',
+    target: 'target',
+    text: 'text'
+} as const;
+
+export function isTutorialStep(item: unknown): item is TutorialStep {
+    return reflection.isInstance(item, TutorialStep.$type);
+}
+
 export interface UnitDef extends langium.AstNode {
     readonly $container: UnitDefArray;
     readonly $type: 'UnitDef';
     attack: number;
+    defense?: number;
     name: Value;
+    prerequisites?: PrereqArray;
+    upkeep?: number;
 }
 
 export const UnitDef = {
     $type: 'UnitDef',
     attack: 'attack',
-    name: 'name'
+    defense: 'defense',
+    name: 'name',
+    prerequisites: 'prerequisites',
+    upkeep: 'upkeep'
 } as const;
 
 export function isUnitDef(item: unknown): item is UnitDef {
@@ -374,7 +1022,7 @@ export function isUnitInstance(item: unknown): item is UnitInstance {
 }
 
 export interface UnitInstanceArray extends langium.AstNode {
-    readonly $container: City;
+    readonly $container: City | CivTrait;
     readonly $type: 'UnitInstanceArray';
     units: Array<UnitInstance>;
 }
@@ -395,7 +1043,7 @@ export function isValue(item: unknown): item is Value {
 }
 
 export interface ValueArray extends langium.AstNode {
-    readonly $container: City;
+    readonly $container: City | CivTrait | Scoring | TechDef;
     readonly $type: 'ValueArray';
     values: Array<Value>;
 }
@@ -412,12 +1060,14 @@ export function isValueArray(item: unknown): item is ValueArray {
 export interface VictoryConditions extends langium.AstNode {
     readonly $container: Model;
     readonly $type: 'VictoryConditions';
+    favor_threshold?: number;
     nb_turns: number;
     resources_spent: number;
 }
 
 export const VictoryConditions = {
     $type: 'VictoryConditions',
+    favor_threshold: 'favor_threshold',
     nb_turns: 'nb_turns',
     resources_spent: 'resources_spent'
 } as const;
@@ -426,30 +1076,85 @@ export function isVictoryConditions(item: unknown): item is VictoryConditions {
     return reflection.isInstance(item, VictoryConditions.$type);
 }
 
+export interface War extends langium.AstNode {
+    readonly $container: Model;
+    readonly $type: 'War';
+    elimination_share: number;
+    reparations?: number;
+}
+
+export const War = {
+    $type: 'War',
+    elimination_share: 'elimination_share',
+    reparations: 'reparations'
+} as const;
+
+export function isWar(item: unknown): item is War {
+    return reflection.isInstance(item, War.$type);
+}
+
 export type ClIvilizationAstType = {
+    BiomeThresholds: BiomeThresholds
     BuildingDef: BuildingDef
     BuildingDefArray: BuildingDefArray
     BuildingInstance: BuildingInstance
     BuildingInstanceArray: BuildingInstanceArray
     Cities: Cities
     City: City
+    CivTrait: CivTrait
+    Crossing: Crossing
     Game: Game
     IntArray: IntArray
+    MapEvent: MapEvent
+    MapEvents: MapEvents
     Model: Model
+    PolicyDefaults: PolicyDefaults
     Prereq: Prereq
     PrereqArray: PrereqArray
     Production: Production
+    Puzzle: Puzzle
+    Raiders: Raiders
+    Region: Region
+    Regions: Regions
+    Relation: Relation
+    Relations: Relations
+    Scoring: Scoring
+    ScoringWeights: ScoringWeights
     Section: Section
+    Surrender: Surrender
+    TechDef: TechDef
+    TechDefArray: TechDefArray
+    Terraform: Terraform
+    Traits: Traits
+    TurnRules: TurnRules
+    Tutorial: Tutorial
+    TutorialStep: TutorialStep
     UnitDef: UnitDef
     UnitDefArray: UnitDefArray
     UnitInstance: UnitInstance
     UnitInstanceArray: UnitInstanceArray
     ValueArray: ValueArray
     VictoryConditions: VictoryConditions
+    War: War
 }
 
 export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
     override readonly types = {
+        BiomeThresholds: {
+            name: BiomeThresholds.$type,
+            properties: {
+                forest: {
+                    name: BiomeThresholds.forest
+                },
+                snow: {
+                    name: BiomeThresholds.snow
+                },
+                swamp: {
+                    name: BiomeThresholds.swamp
+                }
+            },
+            superTypes: []
+        },
         BuildingDef: {
             name: BuildingDef.$type,
             properties: {
@@ -459,6 +1164,12 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
                 cost: {
                     name: BuildingDef.cost
                 },
+                defense: {
+                    name: BuildingDef.defense
+                },
+                maintenance: {
+                    name: BuildingDef.maintenance
+                },
                 name: {
                     name: BuildingDef.name
                 },
@@ -470,6 +1181,12 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
                 },
                 slots: {
                     name: BuildingDef.slots
+                },
+                storage: {
+                    name: BuildingDef.storage
+                },
+                terraform: {
+                    name: BuildingDef.terraform
                 }
             },
             superTypes: []
@@ -519,6 +1236,12 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
         City: {
             name: City.$type,
             properties: {
+                agenda: {
+                    name: City.agenda
+                },
+                ai_seed: {
+                    name: City.ai_seed
+                },
                 blacklist_buildings: {
                     name: City.blacklist_buildings
                 },
@@ -540,9 +1263,15 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
                 nb_slots_units: {
                     name: City.nb_slots_units
                 },
+                persona: {
+                    name: City.persona
+                },
                 player_type: {
                     name: City.player_type
                 },
+                policy: {
+                    name: City.policy
+                },
                 starting_resources: {
                     name: City.starting_resources
                 },
@@ -564,23 +1293,113 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
             },
             superTypes: []
         },
+        CivTrait: {
+            name: CivTrait.$type,
+            properties: {
+                building_discount: {
+                    name: CivTrait.building_discount
+                },
+                city: {
+                    name: CivTrait.city
+                },
+                discounted_buildings: {
+                    name: CivTrait.discounted_buildings
+                },
+                extra_units: {
+                    name: CivTrait.extra_units
+                },
+                income_bonus: {
+                    name: CivTrait.income_bonus
+                },
+                label: {
+                    name: CivTrait.label
+                },
+                travel_speed: {
+                    name: CivTrait.travel_speed
+                }
+            },
+            superTypes: []
+        },
+        Crossing: {
+            name: Crossing.$type,
+            properties: {
+                from_x: {
+                    name: Crossing.from_x
+                },
+                from_y: {
+                    name: Crossing.from_y
+                },
+                to_x: {
+                    name: Crossing.to_x
+                },
+                to_y: {
+                    name: Crossing.to_y
+                },
+                turns: {
+                    name: Crossing.turns
+                }
+            },
+            superTypes: []
+        },
         Game: {
             name: Game.$type,
             properties: {
+                army_stack: {
+                    name: Game.army_stack
+                },
+                attrition: {
+                    name: Game.attrition
+                },
+                biomes: {
+                    name: Game.biomes
+                },
+                build_queue: {
+                    name: Game.build_queue
+                },
+                cancel_refund: {
+                    name: Game.cancel_refund
+                },
+                capture: {
+                    name: Game.capture
+                },
+                city_stack: {
+                    name: Game.city_stack
+                },
                 current_turn: {
                     name: Game.current_turn
                 },
+                fog_of_war: {
+                    name: Game.fog_of_war
+                },
+                map_type: {
+                    name: Game.map_type
+                },
                 map_x: {
                     name: Game.map_x
                 },
                 map_y: {
                     name: Game.map_y
                 },
+                season_length: {
+                    name: Game.season_length
+                },
                 seed: {
                     name: Game.seed
                 },
+                stack_overflow: {
+                    name: Game.stack_overflow
+                },
+                storage: {
+                    name: Game.storage
+                },
+                supply_range: {
+                    name: Game.supply_range
+                },
                 ui_color: {
                     name: Game.ui_color
+                },
+                wrap: {
+                    name: Game.wrap
                 }
             },
             superTypes: [Section.$type]
@@ -595,6 +1414,37 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
             },
             superTypes: []
         },
+        MapEvent: {
+            name: MapEvent.$type,
+            properties: {
+                name: {
+                    name: MapEvent.name
+                },
+                terraform: {
+                    name: MapEvent.terraform
+                },
+                turn: {
+                    name: MapEvent.turn
+                },
+                x: {
+                    name: MapEvent.x
+                },
+                y: {
+                    name: MapEvent.y
+                }
+            },
+            superTypes: []
+        },
+        MapEvents: {
+            name: MapEvents.$type,
+            properties: {
+                events: {
+                    name: MapEvents.events,
+                    defaultValue: []
+                }
+            },
+            superTypes: [Section.$type]
+        },
         Model: {
             name: Model.$type,
             properties: {
@@ -605,11 +1455,29 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
             },
             superTypes: []
         },
+        PolicyDefaults: {
+            name: PolicyDefaults.$type,
+            properties: {
+                military: {
+                    name: PolicyDefaults.military
+                },
+                sortie: {
+                    name: PolicyDefaults.sortie
+                },
+                tax: {
+                    name: PolicyDefaults.tax
+                }
+            },
+            superTypes: []
+        },
         Prereq: {
             name: Prereq.$type,
             properties: {
                 id_building: {
                     name: Prereq.id_building
+                },
+                id_tech: {
+                    name: Prereq.id_tech
                 }
             },
             superTypes: []
@@ -645,20 +1513,276 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
             },
             superTypes: []
         },
+        Puzzle: {
+            name: Puzzle.$type,
+            properties: {
+                max_actions: {
+                    name: Puzzle.max_actions
+                },
+                max_builds: {
+                    name: Puzzle.max_builds
+                },
+                max_turns: {
+                    name: Puzzle.max_turns
+                },
+                objective: {
+                    name: Puzzle.objective
+                },
+                target: {
+                    name: Puzzle.target
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        Raiders: {
+            name: Raiders.$type,
+            properties: {
+                every: {
+                    name: Raiders.every
+                },
+                first_turn: {
+                    name: Raiders.first_turn
+                },
+                growth: {
+                    name: Raiders.growth
+                },
+                pillage: {
+                    name: Raiders.pillage
+                },
+                strength: {
+                    name: Raiders.strength
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        Region: {
+            name: Region.$type,
+            properties: {
+                height: {
+                    name: Region.height
+                },
+                name: {
+                    name: Region.name
+                },
+                width: {
+                    name: Region.width
+                },
+                x: {
+                    name: Region.x
+                },
+                y: {
+                    name: Region.y
+                }
+            },
+            superTypes: []
+        },
+        Regions: {
+            name: Regions.$type,
+            properties: {
+                crossings: {
+                    name: Regions.crossings,
+                    defaultValue: []
+                },
+                regions: {
+                    name: Regions.regions,
+                    defaultValue: []
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        Relation: {
+            name: Relation.$type,
+            properties: {
+                city: {
+                    name: Relation.city
+                },
+                partner: {
+                    name: Relation.partner
+                },
+                stance: {
+                    name: Relation.stance
+                },
+                turn: {
+                    name: Relation.turn
+                }
+            },
+            superTypes: []
+        },
+        Relations: {
+            name: Relations.$type,
+            properties: {
+                relations: {
+                    name: Relations.relations,
+                    defaultValue: []
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        Scoring: {
+            name: Scoring.$type,
+            properties: {
+                weights: {
+                    name: Scoring.weights
+                },
+                wonders: {
+                    name: Scoring.wonders
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        ScoringWeights: {
+            name: ScoringWeights.$type,
+            properties: {
+                per_building: {
+                    name: ScoringWeights.per_building
+                },
+                per_resource: {
+                    name: ScoringWeights.per_resource
+                },
+                per_turn: {
+                    name: ScoringWeights.per_turn
+                },
+                per_unit: {
+                    name: ScoringWeights.per_unit
+                },
+                per_wonder: {
+                    name: ScoringWeights.per_wonder
+                }
+            },
+            superTypes: []
+        },
         Section: {
             name: Section.$type,
             properties: {
             },
             superTypes: []
         },
+        Surrender: {
+            name: Surrender.$type,
+            properties: {
+                income: {
+                    name: Surrender.income
+                },
+                power: {
+                    name: Surrender.power
+                },
+                turns: {
+                    name: Surrender.turns
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        TechDef: {
+            name: TechDef.$type,
+            properties: {
+                cost: {
+                    name: TechDef.cost
+                },
+                name: {
+                    name: TechDef.name
+                },
+                requires: {
+                    name: TechDef.requires
+                },
+                research_time: {
+                    name: TechDef.research_time
+                },
+                unlocks: {
+                    name: TechDef.unlocks
+                }
+            },
+            superTypes: []
+        },
+        TechDefArray: {
+            name: TechDefArray.$type,
+            properties: {
+                techs: {
+                    name: TechDefArray.techs,
+                    defaultValue: []
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        Terraform: {
+            name: Terraform.$type,
+            properties: {
+                radius: {
+                    name: Terraform.radius
+                },
+                terrain: {
+                    name: Terraform.terrain
+                }
+            },
+            superTypes: []
+        },
+        Traits: {
+            name: Traits.$type,
+            properties: {
+                traits: {
+                    name: Traits.traits,
+                    defaultValue: []
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        TurnRules: {
+            name: TurnRules.$type,
+            properties: {
+                action_points: {
+                    name: TurnRules.action_points
+                },
+                block_end_if: {
+                    name: TurnRules.block_end_if,
+                    defaultValue: []
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        Tutorial: {
+            name: Tutorial.$type,
+            properties: {
+                steps: {
+                    name: Tutorial.steps,
+                    defaultValue: []
+                }
+            },
+            superTypes: [Section.$type]
+        },
+        TutorialStep: {
+            name: TutorialStep.$type,
+            properties: {
+                expect: {
+                    name: TutorialStep.expect
+                },
+                gate: {
+                    name: TutorialStep.gate
+                },
+                target: {
+                    name: TutorialStep.target
+                },
+                text: {
+                    name: TutorialStep.text
+                }
+            },
+            superTypes: []
+        },
         UnitDef: {
             name: UnitDef.$type,
             properties: {
                 attack: {
                     name: UnitDef.attack
                 },
+                defense: {
+                    name: UnitDef.defense
+                },
                 name: {
                     name: UnitDef.name
+                },
+                prerequisites: {
+                    name: UnitDef.prerequisites
+                },
+                upkeep: {
+                    name: UnitDef.upkeep
                 }
             },
             superTypes: []
@@ -708,6 +1832,9 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
         VictoryConditions: {
             name: VictoryConditions.$type,
             properties: {
+                favor_threshold: {
+                    name: VictoryConditions.favor_threshold
+                },
                 nb_turns: {
                     name: VictoryConditions.nb_turns
                 },
@@ -716,6 +1843,18 @@ export class ClIvilizationAstReflection extends langium.AbstractAstReflection {
                 }
             },
             superTypes: [Section.$type]
+        },
+        War: {
+            name: War.$type,
+            properties: {
+                elimination_share: {
+                    name: War.elimination_share
+                },
+                reparations: {
+                    name: War.reparations
+                }
+            },
+            superTypes: [Section.$type]
         }
     } as const satisfies langium.AstMetaData
 }
@@ -96,4 +96,18 @@ infantry { attack = 5 }
         expect(result.parseResult.parserErrors).toHaveLength(0);
         expect(result.parseResult.lexerErrors).toHaveLength(0);
     });
+
+    it('parses turn rules blocking the end of turn', async () => {
+        const input = `
+[turn_rules]
+block_end_if = [NO_INCOME, IDLE_CONSTRUCTION]
+`;
+
+        const result = await parse(input);
+
+        expect(result.parseResult.parserErrors).toHaveLength(0);
+        const rules = (result.parseResult?.value as any).sections.find((s: any) => s.$type === "TurnRules");
+        expect(rules).toBeDefined();
+        expect(rules.block_end_if).toEqual(["NO_INCOME", "IDLE_CONSTRUCTION"]);
+    });
 });
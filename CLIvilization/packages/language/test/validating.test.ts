@@ -284,4 +284,176 @@ city_blacklist {
         expect(hasWhitelistBlacklistMsg).toBe(false);
     });
 
+    it('reports an error when a puzzle targets an unknown city', async () => {
+        const input = `
+[cities]
+city1 {
+    x = 1
+    y = 1
+    color = #ffffff
+    starting_resources = 100
+    player_type = PLAYER
+    nb_slots_buildings = 2
+    starting_buildings = []
+    nb_slots_units = 0
+    starting_units = []
+}
+
+[puzzle]
+objective = ELIMINATE
+target = city2
+max_turns = 10
+max_builds = 2
+        `;
+        const res = await parse(input);
+        expect(res).toBeDefined();
+        const hasMsg = res!.diagnostics!.some(d => /Puzzle target 'city2' is not defined/.test(d.message));
+        expect(hasMsg).toBe(true);
+    });
+
+    it('reports an error when a policy slider exceeds 100', async () => {
+        const input = `
+[cities]
+city1 {
+    x = 1
+    y = 1
+    color = #ffffff
+    starting_resources = 100
+    player_type = PLAYER
+    nb_slots_buildings = 2
+    starting_buildings = []
+    nb_slots_units = 0
+    starting_units = []
+    policy = { tax = 120 military = 40 }
+}
+        `;
+        const res = await parse(input);
+        expect(res).toBeDefined();
+        const hasTaxMsg = res!.diagnostics!.some(d => /Policy 'tax' must be between 0 and 100/.test(d.message));
+        const hasMilitaryMsg = res!.diagnostics!.some(d => /Policy 'military'/.test(d.message));
+        expect(hasTaxMsg).toBe(true);
+        expect(hasMilitaryMsg).toBe(false);
+    });
+
+    it('reports an error when a scoring wonder is not a defined building', async () => {
+        const input = `
+[scoring]
+weights = { per_building = 10 per_wonder = 200 }
+wonders = [colosseum]
+
+[buildings]
+farm {
+    cost = 10
+    build_time = 2
+    slots = 1
+    production = { type = RESSOURCE amount = 5 time = 1 cost = 0 }
+    prerequisites = []
+}
+        `;
+        const res = await parse(input);
+        expect(res).toBeDefined();
+        const hasMsg = res!.diagnostics!.some(d => /Wonder 'colosseum' is not defined/.test(d.message));
+        expect(hasMsg).toBe(true);
+    });
+
+    it('reports an error when the turn rules give no action point', async () => {
+        const input = `
+[turn_rules]
+block_end_if = []
+action_points = 0
+        `;
+        const res = await parse(input);
+        expect(res).toBeDefined();
+        const hasMsg = res!.diagnostics!.some(d => /Action points must be at least 1/.test(d.message));
+        expect(hasMsg).toBe(true);
+    });
+
+    it('reports an error when traits name an unknown city or exceed 100%', async () => {
+        const input = `
+[traits]
+Atlantis {
+    travel_speed = 150
+}
+        `;
+        const res = await parse(input);
+        expect(res).toBeDefined();
+        const hasCityMsg = res!.diagnostics!.some(d => /Traits of 'Atlantis': the city is not defined/.test(d.message));
+        const hasSpeedMsg = res!.diagnostics!.some(d => /Trait 'travel_speed' must be between 0 and 100/.test(d.message));
+        expect(hasCityMsg).toBe(true);
+        expect(hasSpeedMsg).toBe(true);
+    });
+
+    it('reports an error when a tech requires or unlocks something undefined', async () => {
+        const input = `
+[techs]
+masonry { cost = 6 research_time = 3 requires = [bronze_working] unlocks = [wall] }
+
+[buildings]
+farm {
+    cost = 10
+    build_time = 2
+    slots = 1
+    production = { type = RESSOURCE amount = 5 time = 1 cost = 0 }
+    prerequisites = [{ id_tech = writing }]
+}
+        `;
+        const res = await parse(input);
+        expect(res).toBeDefined();
+        const hasRequiresMsg = res!.diagnostics!.some(d => /Tech 'masonry' requires 'bronze_working'/.test(d.message));
+        const hasUnlocksMsg = res!.diagnostics!.some(d => /Tech 'masonry' unlocks 'wall'/.test(d.message));
+        const hasPrereqMsg = res!.diagnostics!.some(d => /Prerequisite tech 'writing' is not defined/.test(d.message));
+        expect(hasRequiresMsg).toBe(true);
+        expect(hasUnlocksMsg).toBe(true);
+        expect(hasPrereqMsg).toBe(true);
+    });
+
+    it('reports an error when a crossing does not link two regions', async () => {
+        const input = `
+[regions]
+mainland { x = 0 y = 0 width = 100 height = 40 }
+islands { x = 110 y = 0 width = 50 height = 40 }
+crossings = [
+    { from_x = 99 from_y = 20 to_x = 110 to_y = 20 turns = 3 },
+    { from_x = 10 from_y = 20 to_x = 20 to_y = 20 turns = 1 }
+]
+        `;
+        const res = await parse(input);
+        expect(res).toBeDefined();
+        const messages = res!.diagnostics!.filter(d => /does not link two regions/.test(d.message));
+        expect(messages.length).toBe(1);
+        expect(messages[0].message).toContain('from 10,20 to 20,20');
+    });
+
+    it('reports an error when a map event is outside the map', async () => {
+        const input = `
+[game]
+map_x = 160
+map_y = 40
+current_turn = 1
+ui_color = #00ff00
+
+[map_events]
+volcano { turn = 10 x = 40 y = 12 terraform = { terrain = MOUNTAIN radius = 2 } }
+flood { turn = 20 x = 200 y = 12 terraform = { terrain = WATER } }
+        `;
+        const res = await parse(input);
+        expect(res).toBeDefined();
+        const messages = res!.diagnostics!.filter(d => /is outside the/.test(d.message));
+        expect(messages.length).toBe(1);
+        expect(messages[0].message).toContain("'flood'");
+    });
+
+    it('reports an error when a spoils of war share exceeds 100', async () => {
+        const input = `
+[war]
+elimination_share = 150
+reparations = 20
+        `;
+        const res = await parse(input);
+        expect(res).toBeDefined();
+        const messages = res!.diagnostics!.filter(d => /^War '/.test(d.message));
+        expect(messages.length).toBe(1);
+        expect(messages[0].message).toContain('elimination_share');
+    });
+
 });
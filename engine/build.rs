@@ -76,7 +76,7 @@ fn generate_enums(source_file: &mut BufWriter<File>, content: &str) {
 
         writeln!(
             source_file,
-            "#[derive(Serialize, Deserialize, Debug)]\n#[serde(untagged)]\npub enum {} {{",
+            "#[derive(Serialize, Deserialize, Debug, Clone)]\n#[serde(untagged)]\npub enum {} {{",
             capture.name("NAME").expect("no capture group").as_str()
         )
         .expect("failed to write to source source file");
@@ -108,7 +108,7 @@ fn generate_tagged_enums(source_file: &mut BufWriter<File>, content: &str) {
 
         writeln!(
             source_file,
-            "#[derive(Serialize, Deserialize, Debug)]\npub enum {} {{",
+            "#[derive(Serialize, Deserialize, Debug, Clone)]\n#[allow(clippy::upper_case_acronyms, non_camel_case_types)]\npub enum {} {{",
             capture.name("NAME").expect("no capture group").as_str()
         )
         .expect("failed to write to source source file");
@@ -133,7 +133,7 @@ fn generate_nodes(source_file: &mut BufWriter<File>, content: &str) {
     for capture in node_regex.captures_iter(content) {
         writeln!(
             source_file,
-            "#[derive(Serialize, Deserialize, Debug)]\npub struct {} {{",
+            "#[derive(Serialize, Deserialize, Debug, Clone)]\npub struct {} {{",
             capture.name("NAME").expect("no capture group").as_str(),
         )
         .expect("failed to write to source source file");
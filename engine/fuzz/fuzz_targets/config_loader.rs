@@ -0,0 +1,67 @@
+#![no_main]
+
+use clivilization_engine::game::Game;
+use libfuzzer_sys::arbitrary::{Result, Unstructured};
+use libfuzzer_sys::fuzz_target;
+use serde_json::Value;
+
+/// Valid configuration the structure-aware mutations start from
+const SEED_CONFIG: &str = include_str!("../../game.json");
+
+/// Mutate numbers, strings and arrays of a valid configuration, keeping its overall shape so the
+/// mutations reach the engine instead of stopping at the JSON parser.
+fn mutate(value: &mut Value, u: &mut Unstructured) -> Result<()> {
+    match value {
+        Value::Number(_) if u.ratio(1, 4)? => {
+            *value = match u.choose_index(4)? {
+                0 => Value::from(u.arbitrary::<u32>()?),
+                1 => Value::from(u.arbitrary::<i64>()?),
+                2 => Value::from(u.arbitrary::<f64>()?),
+                _ => Value::from(0),
+            };
+        }
+        Value::String(s) if u.ratio(1, 8)? => *s = u.arbitrary()?,
+        Value::Array(items) => {
+            if !items.is_empty() && u.ratio(1, 8)? {
+                let index = u.choose_index(items.len())?;
+                if u.arbitrary()? {
+                    items.remove(index);
+                } else {
+                    items.push(items[index].clone());
+                }
+            }
+            for item in items {
+                mutate(item, u)?;
+            }
+        }
+        Value::Object(fields) => {
+            for field in fields.values_mut() {
+                mutate(field, u)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Load a configuration and, when it loads, play a full round so the loaded state gets used.
+fn load_and_play(config: &str) {
+    if let Ok(mut game) = Game::from_string(config) {
+        for _ in 0..game.state().civilizations.len() {
+            game.apply_action("end");
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    // raw bytes, for the parser
+    if let Ok(text) = std::str::from_utf8(data) {
+        load_and_play(text);
+    }
+
+    // structure-aware mutations of a valid configuration, for the engine
+    let mut config: Value = serde_json::from_str(SEED_CONFIG).expect("seed config is valid JSON");
+    if mutate(&mut config, &mut Unstructured::new(data)).is_ok() {
+        load_and_play(&config.to_string());
+    }
+});
@@ -0,0 +1,344 @@
+use super::build_queue::QueueEdit;
+use super::composition;
+use std::fmt;
+
+/// Policy slider set by `policy <slider> <value>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicySlider {
+    Tax,
+    Military,
+}
+
+/// Action of the player whose turn it is. The action bar parses what the player typed into
+/// one, programs and AIs can build them directly and submit them with `Game::apply`.
+///
+/// Names of buildings, units and cities are matched regardless of case. A `None` argument
+/// asks the player through a popup listing the possible choices, as typing the bare verb does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Action {
+    EndTurn,
+    Build(Option<String>),
+    /// Show the build queue, or move or cancel one of its entries
+    Queue(Option<QueueEdit>),
+    /// Hire `amount` units (one when None) of `unit` in a single recruitment
+    Hire {
+        unit: Option<String>,
+        amount: Option<u32>,
+    },
+    Research(Option<String>),
+    /// Cancel a construction, the one being built when None, refunding part of its cost
+    CancelBuild(Option<String>),
+    /// Cancel the recruitment in progress, refunding part of its cost
+    CancelHire,
+    /// Call the armies marching on a city back home
+    Recall(Option<String>),
+    /// Send `amount` units (all of them when None) against `target`, or the amount of each unit
+    /// type of `units` when it is not empty
+    Attack {
+        target: Option<String>,
+        amount: Option<u32>,
+        units: Vec<(String, u32)>,
+    },
+    Loan(u32),
+    /// Demolish a building, always confirmed through a popup
+    Demolish(Option<String>),
+    Rename {
+        city: String,
+        name: String,
+    },
+    /// Show the policy sliders and their effects
+    ShowPolicy,
+    SetPolicy {
+        slider: PolicySlider,
+        value: u32,
+    },
+    /// Turn the garrison sortie policy of the city on or off
+    SetSortie(bool),
+    /// Show the favor and treaties with every rival
+    Diplomacy,
+    Gift {
+        target: String,
+        amount: u32,
+    },
+    Trade(String),
+    Pact(String),
+    /// Offer peace terms to a civilization at war with the player
+    Peace(Option<String>),
+    Scout(String),
+    Weather,
+    /// Resource change expected at the next turn start, source by source
+    Income,
+    /// Forecast of an attack, nothing is committed
+    Simulate {
+        target: String,
+        amount: Option<u32>,
+    },
+    /// Ask the advisor for a recommended action, shown in a popup offering to apply it
+    Suggest,
+    /// Write the running match to `saves/<name>.json` of the output directory
+    Save(String),
+}
+
+/// Text that could not be parsed into an action, with the title of the popup explaining why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub title: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn usage(title: &'static str, message: impl Into<String>) -> ParseError {
+    ParseError {
+        title,
+        message: message.into(),
+    }
+}
+
+impl Action {
+    /// Parse an action typed in the action bar. Verbs are case-insensitive, arguments keep their
+    /// case so that renaming a city keeps the name as typed.
+    ///
+    /// # Errors
+    /// Fails with a `ParseError` on an unknown verb or malformed arguments.
+    #[allow(clippy::too_many_lines)]
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let parts: Vec<&str> = input.split_whitespace().collect();
+        let verb = parts.first().map(|v| v.to_lowercase()).unwrap_or_default();
+        let arg = |i: usize| parts.get(i).map(|s| (*s).to_string());
+        let number = |i: usize| parts.get(i).and_then(|a| a.parse::<u32>().ok());
+        Ok(match verb.as_str() {
+            "end"
+                if parts.len() == 1
+                    || parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("turn")) =>
+            {
+                Self::EndTurn
+            }
+            "build" => Self::Build(arg(1)),
+            "queue" if parts.len() == 1 => Self::Queue(None),
+            "queue" => {
+                let edit = match (parts.get(1).map(|s| s.to_lowercase()).as_deref(), number(2)) {
+                    (Some("up"), Some(entry)) => QueueEdit::Up(entry as usize),
+                    (Some("down"), Some(entry)) => QueueEdit::Down(entry as usize),
+                    (Some("cancel"), Some(entry)) => QueueEdit::Cancel(entry as usize),
+                    _ => {
+                        return Err(usage(
+                            "Build queue",
+                            "Usage: queue [up|down|cancel <entry>]",
+                        ));
+                    }
+                };
+                Self::Queue(Some(edit))
+            }
+            "hire" | "recruit" => Self::Hire {
+                unit: arg(1),
+                amount: number(2),
+            },
+            "research" => Self::Research(arg(1)),
+            "cancel" => match parts.get(1).map(|s| s.to_lowercase()).as_deref() {
+                Some("build") => Self::CancelBuild(arg(2)),
+                Some("hire" | "recruit") if parts.len() == 2 => Self::CancelHire,
+                _ => {
+                    return Err(usage(
+                        "Cancel",
+                        "Usage: cancel build [building] | cancel hire",
+                    ));
+                }
+            },
+            "recall" => Self::Recall(arg(1)),
+            "attack" if parts.iter().skip(2).any(|p| p.contains('=')) => {
+                Self::composed_attack(&parts)?
+            }
+            "attack" => Self::Attack {
+                target: arg(1),
+                amount: number(2),
+                units: Vec::new(),
+            },
+            "loan" => Self::Loan(number(1).ok_or_else(|| usage("Loan", "Usage: loan <amount>"))?),
+            "demolish" => Self::Demolish(arg(1)),
+            "rename" => {
+                let (Some(city), Some(name)) = (arg(1), arg(2)) else {
+                    return Err(usage("Rename", "Usage: rename <city> <name>"));
+                };
+                if parts.len() > 3 {
+                    return Err(usage("Rename", "City names are a single word"));
+                }
+                Self::Rename { city, name }
+            }
+            "policy"
+                if parts
+                    .get(1)
+                    .is_some_and(|p| p.eq_ignore_ascii_case("sortie")) =>
+            {
+                match parts.get(2).map(|s| s.to_lowercase()).as_deref() {
+                    Some("on") => Self::SetSortie(true),
+                    Some("off") => Self::SetSortie(false),
+                    _ => return Err(usage("Policy", "Usage: policy sortie <on|off>")),
+                }
+            }
+            "policy" => {
+                let slider = match parts.get(1).map(|s| s.to_lowercase()).as_deref() {
+                    Some("tax") => Some(PolicySlider::Tax),
+                    Some("military") => Some(PolicySlider::Military),
+                    _ => None,
+                };
+                match (slider, number(2).filter(|v| *v <= 100)) {
+                    (Some(slider), Some(value)) => Self::SetPolicy { slider, value },
+                    _ => Self::ShowPolicy,
+                }
+            }
+            "diplomacy" => Self::Diplomacy,
+            "gift" | "trade" | "pact" => match arg(1) {
+                // without a target, show who the gift or treaty could be for
+                None => Self::Diplomacy,
+                Some(target) if verb == "gift" => Self::Gift {
+                    target,
+                    amount: number(2)
+                        .ok_or_else(|| usage("Diplomacy", "Usage: gift <city> <amount>"))?,
+                },
+                Some(target) if verb == "trade" => Self::Trade(target),
+                Some(target) => Self::Pact(target),
+            },
+            "peace" => Self::Peace(arg(1)),
+            "scout" => Self::Scout(arg(1).ok_or_else(|| {
+                usage(
+                    "Scout",
+                    format!(
+                        "Usage: scout <city> (costs {} resources)",
+                        super::espionage::SCOUT_COST
+                    ),
+                )
+            })?),
+            "weather" => Self::Weather,
+            "income" => Self::Income,
+            "simulate" => {
+                let args: Vec<&str> = parts
+                    .iter()
+                    .skip(1)
+                    .copied()
+                    .skip_while(|p| p.eq_ignore_ascii_case("attack"))
+                    .collect();
+                let Some(target) = args.first() else {
+                    return Err(usage(
+                        "Simulation",
+                        "Usage: simulate attack <city> [amount]",
+                    ));
+                };
+                Self::Simulate {
+                    target: (*target).to_string(),
+                    amount: args.get(1).and_then(|a| a.parse::<u32>().ok()),
+                }
+            }
+            "suggest" => Self::Suggest,
+            "save" => {
+                let Some(name) = arg(1) else {
+                    return Err(usage("Save", "Usage: save <name>"));
+                };
+                if parts.len() > 2 {
+                    return Err(usage("Save", "Save names are a single word"));
+                }
+                Self::Save(name)
+            }
+            _ => return Err(usage("Action", format!("Unknown action: {}", input.trim()))),
+        })
+    }
+
+    /// `attack <city> <unit>=<count> ...`, sending the amount of each unit type.
+    fn composed_attack(parts: &[&str]) -> Result<Self, ParseError> {
+        let units = parts
+            .iter()
+            .skip(2)
+            .map(|p| composition::parse_pair(p))
+            .collect::<Option<Vec<_>>>();
+        let (Some(target), Some(units)) = (parts.get(1), units) else {
+            return Err(usage(
+                "Attack",
+                "Usage: attack <city> [amount | <unit>=<count> ...]",
+            ));
+        };
+        Ok(Self::Attack {
+            target: Some((*target).to_string()),
+            amount: None,
+            units,
+        })
+    }
+}
+
+/// Canonical text of the action, parsed back into the same action. Used for replays, which
+/// store actions as text.
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optional = |verb: &str, arg: &Option<String>| match arg {
+            Some(arg) => format!("{verb} {arg}"),
+            None => verb.to_string(),
+        };
+        match self {
+            Self::EndTurn => write!(f, "end"),
+            Self::Build(building) => write!(f, "{}", optional("build", building)),
+            Self::Queue(None) => write!(f, "queue"),
+            Self::Queue(Some(QueueEdit::Up(entry))) => write!(f, "queue up {entry}"),
+            Self::Queue(Some(QueueEdit::Down(entry))) => write!(f, "queue down {entry}"),
+            Self::Queue(Some(QueueEdit::Cancel(entry))) => write!(f, "queue cancel {entry}"),
+            Self::Hire { unit, amount } => {
+                write!(f, "{}", optional("hire", unit))?;
+                match (unit, amount) {
+                    (Some(_), Some(amount)) => write!(f, " {amount}"),
+                    _ => Ok(()),
+                }
+            }
+            Self::Research(tech) => write!(f, "{}", optional("research", tech)),
+            Self::CancelBuild(building) => write!(f, "{}", optional("cancel build", building)),
+            Self::CancelHire => write!(f, "cancel hire"),
+            Self::Recall(target) => write!(f, "{}", optional("recall", target)),
+            Self::Attack {
+                target,
+                amount,
+                units,
+            } => {
+                write!(f, "{}", optional("attack", target))?;
+                for (unit, count) in units {
+                    write!(f, " {unit}={count}")?;
+                }
+                match (target, amount) {
+                    (Some(_), Some(amount)) if units.is_empty() => write!(f, " {amount}"),
+                    _ => Ok(()),
+                }
+            }
+            Self::Loan(amount) => write!(f, "loan {amount}"),
+            Self::Demolish(building) => write!(f, "{}", optional("demolish", building)),
+            Self::Rename { city, name } => write!(f, "rename {city} {name}"),
+            Self::ShowPolicy => write!(f, "policy"),
+            Self::SetPolicy { slider, value } => {
+                let slider = match slider {
+                    PolicySlider::Tax => "tax",
+                    PolicySlider::Military => "military",
+                };
+                write!(f, "policy {slider} {value}")
+            }
+            Self::SetSortie(enabled) => {
+                write!(f, "policy sortie {}", if *enabled { "on" } else { "off" })
+            }
+            Self::Diplomacy => write!(f, "diplomacy"),
+            Self::Gift { target, amount } => write!(f, "gift {target} {amount}"),
+            Self::Trade(target) => write!(f, "trade {target}"),
+            Self::Pact(target) => write!(f, "pact {target}"),
+            Self::Peace(target) => write!(f, "{}", optional("peace", target)),
+            Self::Scout(target) => write!(f, "scout {target}"),
+            Self::Weather => write!(f, "weather"),
+            Self::Income => write!(f, "income"),
+            Self::Simulate { target, amount } => match amount {
+                Some(amount) => write!(f, "simulate attack {target} {amount}"),
+                None => write!(f, "simulate attack {target}"),
+            },
+            Self::Suggest => write!(f, "suggest"),
+            Self::Save(name) => write!(f, "save {name}"),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::game::action::Action;
+use crate::game::state::GameState;
+use crate::game::tech;
+
+/// Categories of the action menu, each one opening a submenu except the end of turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Build,
+    Hire,
+    Attack,
+    Research,
+    End,
+}
+
+impl Category {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Build => "Construire",
+            Self::Hire => "Recruter",
+            Self::Attack => "Attaquer",
+            Self::Research => "Rechercher",
+            Self::End => "Finir le tour",
+        }
+    }
+}
+
+/// Entry of a submenu, with the action it plays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuEntry {
+    pub label: String,
+    pub action: Action,
+}
+
+/// Keyboard-navigated alternative to the action bar: arrows move through the categories and
+/// their entries, built from the definitions every time they are shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActionMenu {
+    /// Selected category
+    pub category: usize,
+    /// Selected entry of the open submenu, None while browsing the categories
+    pub entry: Option<usize>,
+}
+
+/// Categories offered by the menu, research only when the configuration has techs.
+pub fn categories(state: &GameState) -> Vec<Category> {
+    let mut categories = vec![Category::Build, Category::Hire, Category::Attack];
+    if !state.techs.is_empty() {
+        categories.push(Category::Research);
+    }
+    categories.push(Category::End);
+    categories
+}
+
+/// Entries of `category` for the player whose turn it is, without what the city lists forbid.
+/// Resources and slots are not checked here: the action chosen goes through the same checks as
+/// a typed one, and a refusal is explained the same way.
+pub fn entries(state: &GameState, category: Category) -> Vec<MenuEntry> {
+    let Ok(civ) = state.current_civ() else {
+        return Vec::new();
+    };
+    match category {
+        Category::Build => state
+            .buildings
+            .iter()
+            .filter(|b| civ.allows_building(&b.name))
+            .map(|b| MenuEntry {
+                label: format!(
+                    "{} ({} ressources)",
+                    b.name,
+                    civ.modifiers.building_cost(&b.name, b.cost)
+                ),
+                action: Action::Build(Some(b.name.clone())),
+            })
+            .collect(),
+        Category::Hire => state
+            .units
+            .iter()
+            .filter(|u| civ.allows_unit(&u.name))
+            .map(|u| MenuEntry {
+                label: format!("{} (attaque {})", u.name, u.attack),
+                action: Action::Hire {
+                    unit: Some(u.name.clone()),
+                    amount: None,
+                },
+            })
+            .collect(),
+        Category::Attack => state
+            .reachable_targets(state.player_turn)
+            .into_iter()
+            .map(|(i, path)| {
+                let name = state.city_name(i).to_string();
+                MenuEntry {
+                    label: format!("{name} ({} tours)", path.turns),
+                    action: Action::Attack {
+                        target: Some(name),
+                        amount: None,
+                        units: Vec::new(),
+                    },
+                }
+            })
+            .collect(),
+        Category::Research => tech::available(state, state.player_turn)
+            .into_iter()
+            .map(|t| MenuEntry {
+                label: format!(
+                    "{} ({} ressources, {} tours)",
+                    t.name, t.cost, t.research_time
+                ),
+                action: Action::Research(Some(t.name.clone())),
+            })
+            .collect(),
+        Category::End => Vec::new(),
+    }
+}
+
+impl ActionMenu {
+    /// Category under the cursor.
+    pub fn selected_category(&self, state: &GameState) -> Category {
+        let categories = categories(state);
+        categories[self.category.min(categories.len() - 1)]
+    }
+
+    /// Move the cursor by `delta` in the list shown, wrapping around.
+    pub fn move_cursor(&mut self, state: &GameState, delta: isize) {
+        let wrap = |index: usize, len: usize| {
+            (index.cast_signed() + delta)
+                .rem_euclid(len.cast_signed())
+                .cast_unsigned()
+        };
+        match self.entry {
+            Some(entry) => {
+                let len = entries(state, self.selected_category(state)).len();
+                if len > 0 {
+                    self.entry = Some(wrap(entry, len));
+                }
+            }
+            None => self.category = wrap(self.category, categories(state).len()),
+        }
+    }
+
+    /// Open the selected category, or choose the selected entry. Returns the action to play,
+    /// None when a submenu was opened or the category is empty.
+    pub fn select(&mut self, state: &GameState) -> Option<Action> {
+        let category = self.selected_category(state);
+        match self.entry {
+            None if category == Category::End => Some(Action::EndTurn),
+            None => {
+                if !entries(state, category).is_empty() {
+                    self.entry = Some(0);
+                }
+                None
+            }
+            Some(entry) => entries(state, category)
+                .into_iter()
+                .nth(entry)
+                .map(|e| e.action),
+        }
+    }
+
+    /// Leave the open submenu. Returns false when the categories were shown, so the menu closes.
+    pub fn back(&mut self) -> bool {
+        self.entry.take().is_some()
+    }
+}
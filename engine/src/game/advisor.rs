@@ -0,0 +1,194 @@
+use crate::ast::PlayerType;
+use crate::game::raiders;
+use crate::game::state::GameState;
+use crate::game::tech;
+use crate::game::valid_actions;
+use crate::game::{Ai, AiView, DecisionTrace, ScoredAction};
+
+/// Hint shown under the action bar for `civ_index`, from the most urgent situation down: an
+/// enemy army about to arrive, idle building slots, no recruitment, no research. None when
+/// there is nothing to point out, or for AI players.
+pub fn hint(state: &GameState, civ_index: usize) -> Option<String> {
+    let civ = state
+        .civ(civ_index)
+        .ok()
+        .filter(|c| c.alive && !state.game_over)?;
+    if !matches!(civ.city.player_type, PlayerType::PLAYER) {
+        return None;
+    }
+    // the strongest defender the city can train right now
+    let defender = civ
+        .producers
+        .keys()
+        .filter_map(|u| state.unit_def(u))
+        .max_by_key(|d| d.defense.unwrap_or(d.attack))
+        .map(|d| d.name.clone());
+
+    if let Some(army) = state
+        .travels
+        .iter()
+        .filter(|t| t.defender == civ_index && !t.returning())
+        .min_by_key(|t| t.remaining)
+    {
+        let attacker = raiders::army_owner(state, army.attacker);
+        return Some(match (army.remaining, defender) {
+            (0..=1, Some(unit)) if civ.recruitments.is_empty() => {
+                format!(
+                    "Une armée de {attacker} arrive au prochain tour — pensez à recruter des {unit}"
+                )
+            }
+            (0..=1, _) => format!("Une armée de {attacker} arrive au prochain tour"),
+            (turns, _) => format!("Une armée de {attacker} arrive dans {turns} tours"),
+        });
+    }
+    let free_slots = (civ.city.nb_slots_buildings as usize)
+        .saturating_sub(civ.city.buildings.elements.len() + civ.constructions.len());
+    if free_slots > 0 && civ.constructions.is_empty() {
+        return Some(if free_slots == 1 {
+            "Vous avez 1 emplacement de bâtiment libre".to_string()
+        } else {
+            format!("Vous avez {free_slots} emplacements de bâtiment libres")
+        });
+    }
+    if let Some(unit) = defender.filter(|_| civ.recruitments.is_empty()) {
+        return Some(format!(
+            "Aucun recrutement en cours, vos bâtiments peuvent former des {unit}"
+        ));
+    }
+    if !tech::available(state, civ_index).is_empty() {
+        return Some("Aucune recherche en cours, tapez 'research' pour en choisir une".to_string());
+    }
+    None
+}
+
+/// Rules-based AI behind the `suggest` action when no LLM advisor is available: it defends
+/// against stronger reachable rivals, fills free building slots, researches, attacks weaker
+/// rivals and hires, in that order. Every candidate gets the reason shown with the suggestion.
+pub struct HeuristicAi;
+
+impl HeuristicAi {
+    fn candidates(view: &AiView, civ_index: usize) -> Vec<ScoredAction> {
+        let mut candidates = vec![scored(
+            "end",
+            0.0,
+            "nothing more worth doing this turn".to_string(),
+        )];
+        let Some(me) = view.players.get(civ_index) else {
+            return candidates;
+        };
+        let rivals = || {
+            view.players
+                .iter()
+                .enumerate()
+                .filter(move |(i, p)| *i != civ_index && p.scouted && p.travel_turns.is_some())
+                .map(|(_, p)| p)
+        };
+        let threat = rivals()
+            .filter(|p| p.units > me.units)
+            .max_by_key(|p| p.units);
+        for unit in &view.units {
+            let (value, reason) = match threat {
+                Some(p) => (
+                    3.0,
+                    format!(
+                        "{} has more units than you ({} vs {})",
+                        p.name, p.units, me.units
+                    ),
+                ),
+                None => (
+                    1.0,
+                    "more units to defend the city and attack with".to_string(),
+                ),
+            };
+            candidates.push(scored(&format!("hire {unit}"), value, reason));
+        }
+        if me.free_slots > 0 {
+            for building in &view.buildings {
+                let reason = format!("{} free building slot(s)", me.free_slots);
+                candidates.push(scored(&format!("build {building}"), 2.0, reason));
+            }
+        }
+        for tech in &me.techs {
+            candidates.push(scored(
+                &format!("research {tech}"),
+                1.5,
+                "no research in progress".to_string(),
+            ));
+        }
+        for p in rivals().filter(|p| me.units > 0 && p.units < me.units) {
+            let turns = p.travel_turns.unwrap_or_default();
+            let reason = format!(
+                "{} is weaker ({} vs {} units), {turns} turns away",
+                p.name, p.units, me.units
+            );
+            candidates.push(scored(
+                &format!("attack {}", p.name),
+                2.5 - f64::from(turns) / 100.0,
+                reason,
+            ));
+        }
+        candidates.sort_by(|a, b| b.value.total_cmp(&a.value));
+        candidates
+    }
+}
+
+fn scored(action: &str, value: f64, reason: String) -> ScoredAction {
+    ScoredAction {
+        action: action.to_lowercase(),
+        value,
+        reason,
+    }
+}
+
+impl Ai for HeuristicAi {
+    fn select_action(&mut self, view: &AiView, civ_index: usize) -> Option<String> {
+        self.select_action_traced(view, civ_index).0
+    }
+
+    fn select_action_traced(
+        &mut self,
+        view: &AiView,
+        civ_index: usize,
+    ) -> (Option<String>, Option<DecisionTrace>) {
+        let candidates = Self::candidates(view, civ_index);
+        let chosen = candidates.first().map(|c| c.action.clone());
+        let trace = DecisionTrace {
+            civ_index,
+            turn: view.turn,
+            chosen: chosen.clone(),
+            candidates,
+        };
+        (chosen, Some(trace))
+    }
+}
+
+/// Action suggested to the player whose turn it is, with a one-line justification. `advisor`
+/// plays the human civilization from its AI view; only actions the game would accept right now
+/// are suggested, the first accepted candidate of its trace standing in for a refused choice.
+pub fn suggest(state: &GameState, view: &AiView, advisor: &mut dyn Ai) -> Option<(String, String)> {
+    let civ_index = state.player_turn;
+    let valid: Vec<String> = valid_actions::list(state, civ_index)
+        .into_iter()
+        .map(|a| a.syntax.to_lowercase())
+        .collect();
+    let accepted = |action: &str| valid.iter().any(|v| *v == action.trim().to_lowercase());
+    let (chosen, trace) = advisor.select_action_traced(view, civ_index);
+    let candidates = trace.map(|t| t.candidates).unwrap_or_default();
+    let reason = |action: &str| {
+        candidates
+            .iter()
+            .find(|c| c.action.eq_ignore_ascii_case(action))
+            .map_or_else(
+                || "chosen by the AI advisor".to_string(),
+                |c| c.reason.clone(),
+            )
+    };
+    if let Some(action) = chosen.filter(|a| accepted(a)) {
+        let reason = reason(&action);
+        return Some((action, reason));
+    }
+    candidates
+        .iter()
+        .find(|c| accepted(&c.action))
+        .map(|c| (c.action.clone(), c.reason.clone()))
+}
@@ -1,12 +1,15 @@
-use openai::chat::{
-    ChatCompletionDelta, ChatCompletionMessage, ChatCompletionMessageRole,
-};
+use crate::game::diplomacy;
+use crate::game::loans;
+use crate::game::state::Popup;
+use crate::game::{AiView, MAX_MOOD_LEN};
 use openai::Credentials;
+use openai::chat::{ChatCompletionDelta, ChatCompletionMessage, ChatCompletionMessageRole};
 use std::default::Default;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Condvar, Mutex};
 use std::thread;
-use crate::game::AiView;
-use crate::game::state::Popup;
 
 /// OpenAI-based AI client for the game.
 ///
@@ -16,7 +19,7 @@ use crate::game::state::Popup;
 /// # Environment Variables
 /// Requires the following environment variables:
 /// - `OPENAI_KEY` or `OPENAI_API_KEY` - API authentication key
-/// - `OPENAI_BASE_URL` or `OPENAI_API_BASE` - API endpoint URL (optional, defaults to OpenAI)
+/// - `OPENAI_BASE_URL` or `OPENAI_API_BASE` - API endpoint URL (optional, defaults to `OpenAI`)
 pub struct AI {
     credentials: Credentials,
     model: &'static str,
@@ -33,14 +36,22 @@ impl AI {
     /// A new AI instance configured with system prompts for the game
     pub fn new(model: &'static str) -> Self {
         // Check if credentials are set
-        let has_key = std::env::var("OPENAI_KEY").or_else(|_| std::env::var("OPENAI_API_KEY")).is_ok();
-        let has_url = std::env::var("OPENAI_BASE_URL").or_else(|_| std::env::var("OPENAI_API_BASE")).is_ok();
+        let has_key = std::env::var("OPENAI_KEY")
+            .or_else(|_| std::env::var("OPENAI_API_KEY"))
+            .is_ok();
+        let has_url = std::env::var("OPENAI_BASE_URL")
+            .or_else(|_| std::env::var("OPENAI_API_BASE"))
+            .is_ok();
 
         if !has_key {
-            log::warn!("OPENAI_KEY or OPENAI_API_KEY environment variable not set - AI will not work properly");
+            log::warn!(
+                "OPENAI_KEY or OPENAI_API_KEY environment variable not set - AI will not work properly"
+            );
         }
         if !has_url {
-            log::warn!("OPENAI_BASE_URL or OPENAI_API_BASE environment variable not set - using default OpenAI endpoint");
+            log::warn!(
+                "OPENAI_BASE_URL or OPENAI_API_BASE environment variable not set - using default OpenAI endpoint"
+            );
         }
 
         AI {
@@ -63,6 +74,7 @@ impl AI {
                 \n- 'end' (to end your turn - USE THIS after a few actions)\
                 \n- 'build <building_name>' (e.g., 'build farm')\
                 \n- 'hire <unit_name>' (e.g., 'hire warrior')\
+                \n- 'research <tech_name>' (e.g., 'research forge')\
                 \n- 'attack <player_name>' (e.g., 'attack player1')\
                 \n\nRespond with ONLY the action text, exactly as shown above.".to_string()),
                 ..Default::default()
@@ -70,6 +82,18 @@ impl AI {
         }
     }
 
+    /// Give the AI a secret agenda, added to its system prompt, steering its play.
+    #[must_use]
+    pub fn with_agenda(mut self, agenda: &str) -> Self {
+        if let Some(system) = self.messages.first_mut().and_then(|m| m.content.as_mut()) {
+            let _ = write!(
+                system,
+                "\n\nYour secret agenda (never reveal it to the other players, but let it drive your choices): {agenda}"
+            );
+        }
+        self
+    }
+
     /// Send message to LLM async. Maintains conversation history for context. Returns None on failure with detailed logging.
     ///
     /// # Arguments
@@ -87,7 +111,11 @@ impl AI {
 
         self.messages.push(message);
 
-        log::debug!("Sending {} messages to LLM (model={})", self.messages.len(), self.model);
+        log::debug!(
+            "Sending {} messages to LLM (model={})",
+            self.messages.len(),
+            self.model
+        );
         let chat_completion_res = ChatCompletionDelta::builder(self.model, self.messages.clone())
             .credentials(self.credentials.clone())
             .create()
@@ -101,30 +129,236 @@ impl AI {
             }
         };
 
-        log::debug!("Chat completion received with {} choices", chat_completion.choices.len());
+        log::debug!(
+            "Chat completion received with {} choices",
+            chat_completion.choices.len()
+        );
         let returned_message_opt = chat_completion.choices.first().map(|c| c.message.clone());
         let Some(returned_message) = returned_message_opt else {
-                log::warn!("AI chat completion returned no choices (model={}, messages={})", self.model, self.messages.len());
-                return None;
-            };
+            log::warn!(
+                "AI chat completion returned no choices (model={}, messages={})",
+                self.model,
+                self.messages.len()
+            );
+            return None;
+        };
 
         self.messages.push(returned_message.clone());
 
-        let content = returned_message.content.as_deref().map(|s| s.trim().to_string());
-        log::debug!("AI response ({:?}): {}", returned_message.role, content.as_deref().unwrap_or(""));
+        let content = returned_message
+            .content
+            .as_deref()
+            .map(|s| s.trim().to_string());
+        log::debug!(
+            "AI response ({:?}): {}",
+            returned_message.role,
+            content.as_deref().unwrap_or("")
+        );
         content
     }
 }
 
 // ===== LLM-backed Ai adapter =====
 
+/// LLM requests in flight at once over every game of the process, unless set with
+/// `set_llm_concurrency`, so that parallel tournament games do not flood the API
+pub const DEFAULT_LLM_CONCURRENCY: usize = 4;
+
+static LLM_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_LLM_CONCURRENCY);
+static LLM_IN_FLIGHT: Mutex<usize> = Mutex::new(0);
+static LLM_FREED: Condvar = Condvar::new();
+
+/// Change how many LLM requests may be in flight at once, at least one.
+pub fn set_llm_concurrency(limit: usize) {
+    LLM_LIMIT.store(limit.max(1), Ordering::Relaxed);
+    LLM_FREED.notify_all();
+}
+
+/// Slot taken for one LLM request, given back when dropped.
+struct LlmSlot;
+
+impl LlmSlot {
+    /// Wait until fewer requests than the limit are in flight.
+    fn acquire() -> Self {
+        let mut in_flight = LLM_IN_FLIGHT
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        while *in_flight >= LLM_LIMIT.load(Ordering::Relaxed) {
+            in_flight = LLM_FREED
+                .wait(in_flight)
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+        }
+        *in_flight += 1;
+        Self
+    }
+}
+
+impl Drop for LlmSlot {
+    fn drop(&mut self) {
+        let mut in_flight = LLM_IN_FLIGHT
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        *in_flight -= 1;
+        LLM_FREED.notify_one();
+    }
+}
+
+/// Send `prompt` once a request slot is free.
+fn send_limited(rt: &tokio::runtime::Runtime, client: &mut AI, prompt: String) -> Option<String> {
+    let _slot = LlmSlot::acquire();
+    rt.block_on(client.send_message(prompt))
+}
+
+/// Opening of the action prompt: the turn, and the players as the AI sees them.
+fn players_prompt(view: &AiView, civ_idx: usize) -> String {
+    let mut prompt = format!(
+        "=== TURN {} ===\nYou are player: {}\n\nPlayers:\n",
+        view.turn,
+        view.players
+            .get(civ_idx)
+            .map_or("Unknown", |p| p.name.as_str())
+    );
+    for (i, p) in view.players.iter().enumerate() {
+        let marker = if i == civ_idx { " <- YOU" } else { "" };
+        let name = p.persona.as_ref().map_or_else(
+            || p.name.clone(),
+            |persona| format!("{} ({persona})", p.name),
+        );
+        if !p.scouted {
+            let _ = writeln!(
+                prompt,
+                "  {name} - unexplored, Incoming units: {}, Favor toward you: {}",
+                p.incoming,
+                diplomacy::favor_label(p.favor, view.favor_threshold)
+            );
+            continue;
+        }
+        let _ = writeln!(
+            prompt,
+            "  {} - Resources: {}, Income: {:+}/turn, Buildings: {}, Units: {}, Power: {}, Incoming units: {}, Debt: {}, Favor toward you: {}{}",
+            name,
+            p.resources,
+            p.income,
+            p.buildings,
+            p.units,
+            p.power,
+            p.incoming,
+            p.debt,
+            diplomacy::favor_label(p.favor, view.favor_threshold),
+            marker
+        );
+    }
+    let _ = writeln!(
+        prompt,
+        "Your desperation: {}/100 (how far behind the strongest player you are)",
+        view.desperation
+    );
+    prompt
+}
+
+/// Closing of the action prompt: the available buildings and units, then every action the AI may
+/// answer with.
+fn actions_prompt(view: &AiView, civ_idx: usize) -> String {
+    let mut prompt = String::new();
+    prompt.push_str("\nAvailable buildings to build:\n");
+    for b in &view.buildings {
+        let _ = writeln!(prompt, "  - {}", b.to_lowercase());
+    }
+    prompt.push_str("\nAvailable units to hire:\n");
+    for u in &view.units {
+        let _ = writeln!(prompt, "  - {}", u.to_lowercase());
+    }
+    prompt.push_str("\nYour action (respond with ONLY ONE of these, nothing else):\n");
+    prompt.push_str("  end\n");
+    for b in &view.buildings {
+        let _ = writeln!(prompt, "  build {}", b.to_lowercase());
+    }
+    for u in &view.units {
+        let _ = writeln!(prompt, "  hire {}", u.to_lowercase());
+    }
+    for t in view.players.get(civ_idx).map_or(&[][..], |p| &p.techs) {
+        prompt.push_str("  research ");
+        prompt.push_str(&t.to_lowercase());
+        prompt.push('\n');
+    }
+    // only the cities the terrain lets an army reach
+    for p in &view.players {
+        if let Some(turns) = p.travel_turns {
+            let _ = writeln!(
+                prompt,
+                "  attack {}  ({turns} turns away)",
+                p.name.to_lowercase()
+            );
+        }
+    }
+    for (i, p) in view.players.iter().enumerate() {
+        if i != civ_idx {
+            // diplomatic victory: every other player must reach the favor threshold
+            let name = p.name.to_lowercase();
+            let diplomacy_hint = format!(
+                "  gift {name} <amount>  ({} resources per favor, at most {} favor per turn)\n  trade {name}  (+{} favor per turn)\n  pact {name}  (needs {} favor, +{} favor per turn)\n",
+                diplomacy::GIFT_RESOURCES_PER_FAVOR,
+                diplomacy::MAX_GIFT_FAVOR_PER_TURN,
+                diplomacy::TRADE_FAVOR,
+                diplomacy::PACT_REQUIRED_FAVOR,
+                diplomacy::PACT_FAVOR
+            );
+            prompt.push_str(&diplomacy_hint);
+        }
+    }
+    let loan_hint = format!(
+        "  loan <amount>  (repaid with {}% interest over {} turns, missed payments cost morale and units)\n",
+        loans::INTEREST_PERCENT,
+        loans::LOAN_TERM
+    );
+    prompt.push_str(&loan_hint);
+    if let Some(me) = view.players.get(civ_idx)
+        && me.free_slots == 0
+    {
+        // all building slots are taken: demolishing frees one (half the cost refunded)
+        for b in &me.owned_buildings {
+            let demolish = format!(
+                "  demolish {}  (frees a building slot, refunds half its cost)\n",
+                b.to_lowercase()
+            );
+            prompt.push_str(&demolish);
+        }
+    }
+
+    prompt
+}
+
+/// Prompt lines listing the enemy armies of `view`, with when and where they were last seen.
+fn sightings_prompt(view: &AiView) -> String {
+    let name = |i: usize| view.players.get(i).map_or("raiders", |p| p.name.as_str());
+    let mut text = if view.sightings.is_empty() {
+        String::new()
+    } else {
+        "\nEnemy armies seen:\n".to_string()
+    };
+    for s in &view.sightings {
+        let _ = writeln!(
+            text,
+            "  {} units of {} toward {}, last seen {} turn(s) ago at ({}, {})",
+            s.amount,
+            name(s.attacker),
+            name(s.defender),
+            s.age(view.turn),
+            s.tile.0,
+            s.tile.1
+        );
+    }
+    text
+}
+
 /// Internal request types for thread communication. Enum ensures type-safe message passing between sync and async worlds.
 enum LlmRequest {
     SelectAction(AiView, usize, Sender<Option<String>>),
     SelectPopupInput(AiView, usize, Popup, Sender<String>),
+    Mood(usize, Sender<Option<String>>),
 }
 
-/// Thread-based async adapter bridging sync Ai trait to async OpenAI client. Background Tokio runtime handles async calls
+/// Thread-based async adapter bridging sync `Ai` trait to async `OpenAI` client. Background Tokio runtime handles async calls
 /// while exposing synchronous interface. Spawns dedicated thread avoiding executor conflicts.
 pub struct LlmAi {
     tx: Sender<LlmRequest>,
@@ -174,67 +408,73 @@ impl LlmAi {
     /// Create a new LLM-backed AI instance.
     ///
     /// This spawns a background thread with a Tokio runtime that processes
-    /// AI requests asynchronously. The thread will run until the LlmAi
+    /// AI requests asynchronously. The thread will run until the `LlmAi`
     /// instance is dropped and the channel is closed.
     ///
     /// # Arguments
     /// * `model` - The LLM model identifier (must be a static string)
     ///
     /// # Returns
-    /// A new LlmAi instance ready to handle AI requests
+    /// A new `LlmAi` instance ready to handle AI requests
     pub fn new(model: &'static str) -> Self {
+        Self::with_agenda(model, None)
+    }
+
+    /// Create a new LLM-backed AI instance pursuing the secret `agenda` of its city, if any.
+    ///
+    /// # Panics
+    /// The background thread panics when it cannot start its tokio runtime.
+    pub fn with_agenda(model: &'static str, agenda: Option<String>) -> Self {
         let (tx, rx): (Sender<LlmRequest>, Receiver<LlmRequest>) = mpsc::channel();
 
         // Spawn a background thread that owns a tokio runtime and the async LLM client
         thread::spawn(move || {
             let rt = tokio::runtime::Runtime::new().expect("failed to create runtime");
-            let mut ai_client = AI::new(model);
+            let mut ai_client = match &agenda {
+                Some(agenda) => AI::new(model).with_agenda(agenda),
+                None => AI::new(model),
+            };
             // Process requests
             while let Ok(req) = rx.recv() {
                 match req {
                     LlmRequest::SelectAction(view, civ_idx, resp_tx) => {
-                        // Build a simple prompt describing the view and possible actions
-                        let mut prompt = format!("=== TURN {} ===\nYou are player: {}\n\nPlayers:\n", view.turn, view.players.get(civ_idx).map(|p| &p.name).unwrap_or(&"Unknown".to_string()));
-                        for (i, p) in view.players.iter().enumerate() {
-                            let marker = if i == civ_idx { " <- YOU" } else { "" };
-                            prompt.push_str(&format!("  {} - Resources: {}, Buildings: {}, Units: {}{}\n",
-                                p.name, p.resources, p.buildings, p.units, marker));
-                        }
-                        prompt.push_str("\nAvailable buildings to build:\n");
-                        for b in &view.buildings {
-                            prompt.push_str(&format!("  - {}\n", b.to_lowercase()));
-                        }
-                        prompt.push_str("\nAvailable units to hire:\n");
-                        for u in &view.units {
-                            prompt.push_str(&format!("  - {}\n", u.to_lowercase()));
-                        }
-                        prompt.push_str("\nYour action (respond with ONLY ONE of these, nothing else):\n");
-                        prompt.push_str("  end\n");
-                        for b in &view.buildings {
-                            prompt.push_str(&format!("  build {}\n", b.to_lowercase()));
-                        }
-                        for u in &view.units {
-                            prompt.push_str(&format!("  hire {}\n", u.to_lowercase()));
-                        }
-                        for (i, p) in view.players.iter().enumerate() {
-                            if i != civ_idx {
-                                prompt.push_str(&format!("  attack {}\n", p.name.to_lowercase()));
-                            }
-                        }
+                        // Build a prompt describing the view and the possible actions
+                        let prompt = players_prompt(&view, civ_idx)
+                            + &sightings_prompt(&view)
+                            + &actions_prompt(&view, civ_idx);
 
-                        let res = rt.block_on(ai_client.send_message(prompt));
+                        let res = send_limited(&rt, &mut ai_client, prompt);
                         // Parse and clean the response
-                        let out = res.map(|s| Self::clean_llm_response(&s)).or_else(|| Some("end".to_string()));
+                        let out = res
+                            .map(|s| Self::clean_llm_response(&s))
+                            .or_else(|| Some("end".to_string()));
                         let _ = resp_tx.send(out);
                     }
+                    LlmRequest::Mood(civ_idx, resp_tx) => {
+                        let prompt = format!(
+                            "Before your first action of this turn, player {civ_idx}: in at most {MAX_MOOD_LEN} characters, what is your mood or plan (e.g. 'preparing defenses', 'plotting revenge')? Respond with ONLY the status, no quotes."
+                        );
+                        let res = send_limited(&rt, &mut ai_client, prompt);
+                        let mood = res.map(|s| {
+                            s.lines()
+                                .next()
+                                .unwrap_or_default()
+                                .trim_matches(['"', '\'', ' '])
+                                .to_string()
+                        });
+                        let _ = resp_tx.send(mood);
+                    }
                     LlmRequest::SelectPopupInput(_view, civ_idx, popup, resp_tx) => {
                         // Build prompt describing popup
-                        let mut prompt = format!("Popup for player {}: {}\nPrompt: {}\nChoices:\n", civ_idx, popup.title, popup.prompt);
+                        let mut prompt = format!(
+                            "Popup for player {}: {}\nPrompt: {}\nChoices:\n",
+                            civ_idx, popup.title, popup.prompt
+                        );
                         for (i, c) in popup.choices.iter().enumerate() {
-                            prompt.push_str(&format!("{}: {}\n", i+1, c));
+                            let _ = writeln!(prompt, "{}: {c}", i + 1);
                         }
                         prompt.push_str("Provide the input to select (either the 1-based index or the choice text):\n");
-                        let res = rt.block_on(ai_client.send_message(prompt));
+                        let res = send_limited(&rt, &mut ai_client, prompt);
                         let chosen = res.unwrap_or_default();
                         let _ = resp_tx.send(chosen);
                     }
@@ -250,28 +490,74 @@ impl crate::game::Ai for LlmAi {
     fn select_action(&mut self, view: &AiView, civ_index: usize) -> Option<String> {
         let (resp_tx, resp_rx) = mpsc::channel();
         // Clone view to send across thread
-        let view_cloned = AiView { turn: view.turn, player_turn: view.player_turn, players: view.players.clone(), buildings: view.buildings.clone(), units: view.units.clone(), seed: view.seed.clone() };
-        if let Err(e) = self.tx.send(LlmRequest::SelectAction(view_cloned, civ_index, resp_tx)) {
+        let view_cloned = AiView {
+            turn: view.turn,
+            player_turn: view.player_turn,
+            players: view.players.clone(),
+            buildings: view.buildings.clone(),
+            units: view.units.clone(),
+            seed: view.seed.clone(),
+            favor_threshold: view.favor_threshold,
+            sightings: view.sightings.clone(),
+            desperation: view.desperation,
+        };
+        if let Err(e) = self
+            .tx
+            .send(LlmRequest::SelectAction(view_cloned, civ_index, resp_tx))
+        {
             log::error!("Failed to send LLM select_action request: {e}");
             return Some("end".to_string());
         }
         // Wait for response with a timeout
-        if let Ok(opt) = resp_rx.recv_timeout(std::time::Duration::from_secs(10)) { opt } else {
+        if let Ok(opt) = resp_rx.recv_timeout(std::time::Duration::from_secs(10)) {
+            opt
+        } else {
             log::warn!("LLM select_action timed out for civ {civ_index}");
             Some("end".to_string())
         }
     }
 
+    fn mood(&mut self, _view: &AiView, civ_index: usize) -> Option<String> {
+        let (resp_tx, resp_rx) = mpsc::channel();
+        if let Err(e) = self.tx.send(LlmRequest::Mood(civ_index, resp_tx)) {
+            log::error!("Failed to send LLM mood request: {e}");
+            return None;
+        }
+        if let Ok(mood) = resp_rx.recv_timeout(std::time::Duration::from_secs(10)) {
+            mood
+        } else {
+            log::warn!("LLM mood timed out for civ {civ_index}");
+            None
+        }
+    }
+
     fn select_popup_input(&mut self, view: &AiView, civ_index: usize, popup: &Popup) -> String {
         let (resp_tx, resp_rx) = mpsc::channel();
-        let view_cloned = AiView { turn: view.turn, player_turn: view.player_turn, players: view.players.clone(), buildings: view.buildings.clone(), units: view.units.clone(), seed: view.seed.clone() };
-        if let Err(e) = self.tx.send(LlmRequest::SelectPopupInput(view_cloned, civ_index, popup.clone(), resp_tx)) {
+        let view_cloned = AiView {
+            turn: view.turn,
+            player_turn: view.player_turn,
+            players: view.players.clone(),
+            buildings: view.buildings.clone(),
+            units: view.units.clone(),
+            seed: view.seed.clone(),
+            favor_threshold: view.favor_threshold,
+            sightings: view.sightings.clone(),
+            desperation: view.desperation,
+        };
+        if let Err(e) = self.tx.send(LlmRequest::SelectPopupInput(
+            view_cloned,
+            civ_index,
+            popup.clone(),
+            resp_tx,
+        )) {
             log::error!("Failed to send LLM select_popup_input request: {e}");
             return String::new();
         }
-        if let Ok(s) = resp_rx.recv_timeout(std::time::Duration::from_secs(10)) { s } else {
+        if let Ok(s) = resp_rx.recv_timeout(std::time::Duration::from_secs(10)) {
+            s
+        } else {
             log::warn!("LLM select_popup_input timed out for civ {civ_index}");
             String::new()
         }
     }
-}
\ No newline at end of file
+}
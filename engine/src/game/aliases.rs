@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+/// Built-in shorthands for the first word of an action.
+pub const BUILTIN_SHORTHANDS: [(&str, &str); 5] = [
+    ("b", "build"),
+    ("h", "hire"),
+    ("atk", "attack"),
+    ("e", "end"),
+    ("sim", "simulate attack"),
+];
+
+/// Replace the longest leading run of words matching a user alias, then expand a built-in
+/// shorthand on the first word. Expansion happens once, aliases are not recursive.
+pub fn expand(aliases: &BTreeMap<String, String>, action: &str) -> String {
+    let words: Vec<&str> = action.split_whitespace().collect();
+    let mut expanded = words.join(" ");
+    for n in (1..=words.len()).rev() {
+        if let Some(target) = aliases.get(&words[..n].join(" ")) {
+            expanded = std::iter::once(target.as_str())
+                .chain(words[n..].iter().copied())
+                .collect::<Vec<_>>()
+                .join(" ");
+            break;
+        }
+    }
+    expand_shorthand(&expanded)
+}
+
+/// Expand a built-in shorthand on the first word of `action`.
+pub fn expand_shorthand(action: &str) -> String {
+    let mut parts = action.splitn(2, ' ');
+    let first = parts.next().unwrap_or_default();
+    match BUILTIN_SHORTHANDS.iter().find(|(short, _)| *short == first) {
+        Some((_, full)) => match parts.next() {
+            Some(rest) => format!("{full} {rest}"),
+            None => (*full).to_string(),
+        },
+        None => action.to_string(),
+    }
+}
+
+/// Parse `alias <name...> = <action...>` arguments into a (name, action) pair.
+pub fn parse_definition(args: &str) -> Option<(String, String)> {
+    let (name, action) = args.split_once('=')?;
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let action = action.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() || action.is_empty() {
+        None
+    } else {
+        Some((name, action))
+    }
+}
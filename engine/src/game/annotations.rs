@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// Note placed by a spectator on a paused AI game, with an optional map marker. Notes are saved
+/// in the replay being recorded and shown again when it is played back, for casted matches.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub turn: i32,
+    pub text: String,
+    /// Map tile the note points at
+    pub marker: Option<(i32, i32)>,
+}
+
+impl Annotation {
+    /// Read a note typed by the spectator: `@x,y text` also places a marker on the tile (x, y),
+    /// kept inside the `width` x `height` map. Anything else is a plain note.
+    pub fn parse(turn: i32, input: &str, width: usize, height: usize) -> Self {
+        let input = input.trim();
+        let marker = input
+            .strip_prefix('@')
+            .and_then(|rest| rest.split_once(char::is_whitespace).or(Some((rest, ""))))
+            .and_then(|(position, text)| {
+                let (x, y) = position.split_once(',')?;
+                let clamp =
+                    |v: i32, side: usize| v.clamp(0, i32::try_from(side).unwrap_or(i32::MAX) - 1);
+                Some((
+                    (
+                        clamp(x.parse().ok()?, width),
+                        clamp(y.parse().ok()?, height),
+                    ),
+                    text.trim(),
+                ))
+            });
+        match marker {
+            Some((position, text)) => Self {
+                turn,
+                text: text.to_string(),
+                marker: Some(position),
+            },
+            None => Self {
+                turn,
+                text: input.to_string(),
+                marker: None,
+            },
+        }
+    }
+}
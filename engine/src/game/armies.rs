@@ -0,0 +1,124 @@
+use crate::game::combat;
+use crate::game::composition;
+use crate::game::paths::{self, TURN};
+use crate::game::raiders;
+use crate::game::regions;
+use crate::game::state::{GameState, Travel};
+use crate::game::weather;
+use log::info;
+
+/// Movement points an army spends to step from `from` onto the next tile `to` of its path, in
+/// the season of the current turn.
+fn step_cost(state: &GameState, from: (i32, i32), to: (i32, i32)) -> i64 {
+    if let Some(turns) = regions::crossing_turns(state, from, to) {
+        return i64::from(turns) * TURN;
+    }
+    let tile = state
+        .map
+        .tiles
+        .get(to.1.cast_unsigned() as usize)
+        .and_then(|line| line.get(to.0.cast_unsigned() as usize));
+    let cost = tile.and_then(|t| paths::terrain_cost(*t)).unwrap_or(TURN);
+    match (weather::season(state, state.turn), tile) {
+        (Some(season), Some(terrain)) => cost * season.travel_percent(*terrain) / 100,
+        _ => cost,
+    }
+}
+
+/// Movement points needed to walk `path` from its first tile.
+pub fn path_cost(state: &GameState, path: &[(i32, i32)]) -> i64 {
+    path.windows(2).map(|w| step_cost(state, w[0], w[1])).sum()
+}
+
+/// Movement points of an army of `civ_index` for one turn, more with a travel speed trait.
+fn budget(state: &GameState, civ_index: usize) -> i64 {
+    let turns = state
+        .civ(civ_index)
+        .map_or(100, |c| c.modifiers.travel_turns(100));
+    TURN * 100 / i64::from(turns.max(1))
+}
+
+/// Step the army stands on after one more turn of marching, and the movement points it carries
+/// toward the tile after it. Points left at the end of a turn are kept, so crossings longer than
+/// a turn are walked over several turns.
+fn march(state: &GameState, travel: &Travel) -> (usize, i64) {
+    let mut points = budget(state, travel.attacker) + travel.progress;
+    let mut step = travel.step;
+    while step + 1 < travel.path.len() {
+        let cost = step_cost(state, travel.path[step], travel.path[step + 1]);
+        if cost > points {
+            break;
+        }
+        points -= cost;
+        step += 1;
+    }
+    if step + 1 >= travel.path.len() {
+        points = 0;
+    }
+    (step, points)
+}
+
+/// Tile the army will stand on after the next turn of marching.
+pub fn next_tile(state: &GameState, travel: &Travel) -> (i32, i32) {
+    travel
+        .path
+        .get(march(state, travel).0)
+        .copied()
+        .unwrap_or_else(|| travel.tile())
+}
+
+/// Turns before the army reaches the last tile of its path, 0 once it stands on it.
+pub fn turns_left(state: &GameState, travel: &Travel) -> u32 {
+    if travel.step + 1 >= travel.path.len() {
+        return 0;
+    }
+    let cost = path_cost(state, &travel.path[travel.step..]) - travel.progress;
+    let budget = budget(state, travel.attacker);
+    u32::try_from((cost + budget - 1) / budget)
+        .unwrap_or(u32::MAX)
+        .max(1)
+}
+
+/// Move every army one turn along its path, except those held back by the weather. Returns
+/// the indices of the armies standing on the city they march on.
+pub fn advance(state: &mut GameState) -> Vec<usize> {
+    let mut arrived = Vec::new();
+    for i in 0..state.travels.len() {
+        let travel = &state.travels[i];
+        if weather::delaying(state, travel).is_none() {
+            let (step, progress) = march(state, travel);
+            let travel = &mut state.travels[i];
+            travel.step = step;
+            travel.progress = progress;
+        }
+        let remaining = turns_left(state, &state.travels[i]);
+        state.travels[i].remaining = remaining;
+        if remaining == 0 {
+            arrived.push(i);
+        }
+    }
+    arrived
+}
+
+/// Settle `travel`, which reached the end of its path: a recalled army rejoins its city, raiders
+/// raid it and any other army fights the battle of the city it marched on. Ignored when either side is already dead.
+pub fn arrive(state: &mut GameState, travel: &Travel) {
+    if travel.raiding() {
+        raiders::raid(state, travel);
+        return;
+    }
+    if !state.civ(travel.attacker).is_ok_and(|c| c.alive)
+        || !state.civ(travel.defender).is_ok_and(|c| c.alive)
+    {
+        return;
+    }
+    if travel.returning() {
+        composition::disband(state, travel.attacker, travel);
+        info!(
+            "Army of civ {} back home with {} units",
+            travel.attacker, travel.amount
+        );
+    } else {
+        combat::battle(state, travel);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::game::Game;
+use std::time::Instant;
+
+/// Time `rounds` rounds of the per-turn hot path for every civilization: the turn start
+/// (income, constructions, recruitments, travels) and the validation of every recruitment.
+pub fn run(game: &mut Game, rounds: u32) -> String {
+    let state = game.state_mut();
+    let civs = state.civilizations.len();
+    let units: Vec<String> = state.units.iter().map(|u| u.name.clone()).collect();
+    let start = Instant::now();
+    for _ in 0..rounds {
+        for civ in 0..civs {
+            state.on_turn_start(civ);
+            for unit in &units {
+                let _ = state.start_recruitment(civ, unit);
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    let turns = u128::from(rounds) * civs as u128;
+    format!(
+        "{rounds} rounds of {civs} civilizations in {elapsed:.1?} ({} µs per turn)",
+        elapsed.as_micros() / turns.max(1)
+    )
+}
@@ -0,0 +1,163 @@
+use crate::game::cancel;
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+use log::info;
+use std::fmt::Write;
+
+/// Constructions a city may have queued at once, unless set in `[game]`
+pub const DEFAULT_BUILD_QUEUE: u32 = 1;
+
+/// Change to an entry of the build queue, numbered from 1 as listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueEdit {
+    Up(usize),
+    Down(usize),
+    Cancel(usize),
+}
+
+impl QueueEdit {
+    /// Number of the entry changed, from 1.
+    pub fn entry(self) -> usize {
+        match self {
+            Self::Up(entry) | Self::Down(entry) | Self::Cancel(entry) => entry,
+        }
+    }
+
+    /// Popup choices editing `entry`, as parsed back by `QueueEdit::from_choice`.
+    fn choices(entry: usize, refund: u32) -> Vec<String> {
+        vec![
+            format!("Move #{entry} up"),
+            format!("Move #{entry} down"),
+            format!("Cancel #{entry} (refund {refund})"),
+        ]
+    }
+
+    /// Edit described by a choice of the queue popup.
+    fn from_choice(choice: &str) -> Option<Self> {
+        let (verb, rest) = choice.split_once(" #")?;
+        let entry = rest.split_whitespace().next()?.parse().ok()?;
+        match verb {
+            "Move" if rest.ends_with("up") => Some(Self::Up(entry)),
+            "Move" => Some(Self::Down(entry)),
+            "Cancel" => Some(Self::Cancel(entry)),
+            _ => None,
+        }
+    }
+}
+
+/// Resources given back when cancelling construction `index` of `civ_index`: the refunded share
+/// of its cost not yet built, counting all of it for an entry waiting in the queue.
+pub fn refund(state: &GameState, civ_index: usize, index: usize) -> u32 {
+    state
+        .civ(civ_index)
+        .ok()
+        .and_then(|c| c.constructions.get(index))
+        .map_or(0, |c| cancel::refund(state, c.cost, c.remaining, c.total))
+}
+
+/// Prompt and choices of the build queue popup of `civ_index`: its entries with their progress,
+/// or the edits of `entry` once one is chosen.
+pub fn queue_popup(
+    state: &GameState,
+    civ_index: usize,
+    entry: Option<usize>,
+) -> (String, Vec<String>) {
+    let Ok(civ) = state.civ(civ_index) else {
+        return (String::new(), Vec::new());
+    };
+    let label = |i: usize| format!("#{} {}", i + 1, civ.constructions[i].id_building);
+    if let Some(entry) = entry.filter(|e| (1..=civ.constructions.len()).contains(e)) {
+        let prompt = format!("{}: move it in the queue or cancel it", label(entry - 1));
+        return (
+            prompt,
+            QueueEdit::choices(entry, refund(state, civ_index, entry - 1)),
+        );
+    }
+    if civ.constructions.is_empty() {
+        return (
+            format!(
+                "The build queue is empty (up to {} constructions)",
+                state.build_queue
+            ),
+            Vec::new(),
+        );
+    }
+    let mut prompt = format!(
+        "Build queue ({}/{}), only the first entry is being built:",
+        civ.constructions.len(),
+        state.build_queue
+    );
+    for (i, c) in civ.constructions.iter().enumerate() {
+        let _ = write!(
+            prompt,
+            "\n{} {}/{}",
+            label(i),
+            c.total - c.remaining,
+            c.total
+        );
+    }
+    (prompt, (0..civ.constructions.len()).map(label).collect())
+}
+
+/// Apply `edit` to the build queue of `civ_index`. Moving an entry past either end leaves the
+/// queue unchanged.
+///
+/// # Errors
+/// Fails when the civilization does not exist or the edit names no entry of the queue.
+pub fn apply(state: &mut GameState, civ_index: usize, edit: QueueEdit) -> Result<()> {
+    let len = state.civ(civ_index)?.constructions.len();
+    let index = edit
+        .entry()
+        .checked_sub(1)
+        .filter(|i| *i < len)
+        .ok_or_else(|| {
+            anyhow!(
+                "No entry #{} in the build queue ({len} queued)",
+                edit.entry()
+            )
+        })?;
+    match edit {
+        QueueEdit::Up(_) if index > 0 => state
+            .civ_mut(civ_index)?
+            .constructions
+            .swap(index - 1, index),
+        QueueEdit::Down(_) if index + 1 < len => state
+            .civ_mut(civ_index)?
+            .constructions
+            .swap(index, index + 1),
+        QueueEdit::Cancel(_) => {
+            let refund = refund(state, civ_index, index);
+            let civ = state.civ_mut(civ_index)?;
+            let construction = civ.constructions.remove(index);
+            civ.earn(i32::try_from(refund).unwrap_or(i32::MAX));
+            info!(
+                "Civ {civ_index} cancelled {} (+{refund} resources)",
+                construction.id_building
+            );
+        }
+        QueueEdit::Up(_) | QueueEdit::Down(_) => {}
+    }
+    Ok(())
+}
+
+/// Handle a choice of the build queue popup of `civ_index`: an entry first, then its edit.
+/// Returns the popup to open next, if any.
+pub fn choose(
+    state: &mut GameState,
+    civ_index: usize,
+    choice: &str,
+) -> Option<(String, Vec<String>)> {
+    if let Some(edit) = QueueEdit::from_choice(choice) {
+        return match apply(state, civ_index, edit) {
+            Ok(()) => None,
+            Err(e) => Some((format!("{e:#}"), Vec::new())),
+        };
+    }
+    let entry = choice
+        .strip_prefix('#')?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    Some(queue_popup(state, civ_index, Some(entry)))
+}
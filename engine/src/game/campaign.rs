@@ -0,0 +1,230 @@
+use crate::ast::{PlayerType, UnitInstance};
+use crate::game::Game;
+use crate::game::state::GameState;
+use crate::game::utils::{output_path, write_to_file};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Campaign manifest: an ordered list of scenarios played one after the other.
+///
+/// Scenario config paths are resolved relative to the manifest file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CampaignManifest {
+    pub name: String,
+    pub scenarios: Vec<CampaignScenario>,
+}
+
+/// A single scenario of a campaign, pointing to a JSON game config.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CampaignScenario {
+    pub title: String,
+    pub config: String,
+}
+
+/// What the human player keeps from one scenario to the next.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CarryOver {
+    pub resources: i32,
+    pub units: Vec<(String, u32)>,
+    /// Technologies researched, kept when the next scenario defines them
+    #[serde(default)]
+    pub researched: BTreeSet<String>,
+}
+
+/// Persisted campaign progress: which scenarios were won and what carries over.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CampaignProgress {
+    pub completed: Vec<bool>,
+    pub carry_over: Option<CarryOver>,
+}
+
+/// Loaded campaign with its manifest location and progress.
+pub struct Campaign {
+    pub manifest: CampaignManifest,
+    pub progress: CampaignProgress,
+    base_dir: PathBuf,
+}
+
+impl CarryOver {
+    /// Capture the surviving resources, units and technologies of a civilization.
+    pub fn from_state(state: &GameState, civ_index: usize) -> Self {
+        let Ok(civ) = state.civ(civ_index) else {
+            return Self::default();
+        };
+        Self {
+            resources: civ.resources.ressources,
+            units: civ
+                .city
+                .units
+                .units
+                .iter()
+                .map(|u| (u.id_units.clone(), u.nb_units))
+                .collect(),
+            researched: civ.researched.clone(),
+        }
+    }
+
+    /// Grant the carried resources, units and technologies to the first human civilization.
+    /// Units and technologies unknown to the new scenario are dropped since they would have no
+    /// stats.
+    pub fn apply(&self, state: &mut GameState) {
+        let known: Vec<String> = state.units.iter().map(|u| u.name.clone()).collect();
+        let techs: Vec<String> = state.techs.iter().map(|t| t.name.clone()).collect();
+        let Some((civ_index, civ)) = state
+            .civilizations
+            .iter_mut()
+            .enumerate()
+            .find(|(_, c)| matches!(c.city.player_type, PlayerType::PLAYER))
+        else {
+            log::warn!("Campaign carry-over skipped: no human civilization in scenario");
+            return;
+        };
+        civ.earn(self.resources);
+        for (id, amount) in &self.units {
+            if !known.contains(id) {
+                log::warn!(
+                    "Campaign carry-over: unit '{id}' not defined in this scenario, dropped"
+                );
+                continue;
+            }
+            if let Some(inst) = civ.city.units.units.iter_mut().find(|u| &u.id_units == id) {
+                inst.nb_units = inst.nb_units.saturating_add(*amount);
+            } else {
+                civ.city.units.units.push(UnitInstance {
+                    id_units: id.clone(),
+                    nb_units: *amount,
+                });
+            }
+        }
+        for tech in &self.researched {
+            if techs.contains(tech) {
+                civ.researched.insert(tech.clone());
+            } else {
+                log::warn!(
+                    "Campaign carry-over: tech '{tech}' not defined in this scenario, dropped"
+                );
+            }
+        }
+        log::info!(
+            "Applied campaign carry-over to civ {civ_index}: +{} resources, {} unit stacks, {} techs",
+            self.resources,
+            self.units.len(),
+            self.researched.len()
+        );
+    }
+}
+
+impl Campaign {
+    /// Load a manifest and any progress saved for it.
+    ///
+    /// # Errors
+    /// Fails when the manifest cannot be read or parsed, or lists no scenario.
+    pub fn load(manifest_path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(manifest_path).context(format!(
+            "failed to read campaign manifest `{manifest_path}`"
+        ))?;
+        let manifest: CampaignManifest =
+            serde_json::from_str(&contents).context("failed to parse campaign manifest")?;
+        if manifest.scenarios.is_empty() {
+            anyhow::bail!("campaign `{}` has no scenarios", manifest.name);
+        }
+        let base_dir = Path::new(manifest_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        let mut progress: CampaignProgress =
+            std::fs::read_to_string(Self::progress_path(&manifest))
+                .ok()
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default();
+        progress.completed.resize(manifest.scenarios.len(), false);
+        log::info!(
+            "Loaded campaign '{}' ({} scenarios)",
+            manifest.name,
+            manifest.scenarios.len()
+        );
+
+        Ok(Self {
+            manifest,
+            progress,
+            base_dir,
+        })
+    }
+
+    fn progress_file(manifest: &CampaignManifest) -> String {
+        let slug: String = manifest
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("campaigns/{slug}.json")
+    }
+
+    fn progress_path(manifest: &CampaignManifest) -> PathBuf {
+        output_path(&Self::progress_file(manifest))
+    }
+
+    /// A scenario is playable once every scenario before it has been won.
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        self.progress.completed.iter().take(index).all(|c| *c)
+    }
+
+    /// First scenario not yet won (or the last one once the campaign is finished).
+    pub fn next_scenario(&self) -> usize {
+        self.progress
+            .completed
+            .iter()
+            .position(|c| !c)
+            .unwrap_or(self.manifest.scenarios.len() - 1)
+    }
+
+    /// Load a scenario config and apply the carry-over from previous victories.
+    ///
+    /// # Errors
+    /// Fails when the config of the scenario does not load.
+    pub fn start_scenario(&self, index: usize) -> Result<Game> {
+        let scenario = &self.manifest.scenarios[index];
+        let path = self.base_dir.join(&scenario.config);
+        log::info!(
+            "Starting campaign scenario {index} '{}' from {}",
+            scenario.title,
+            path.display()
+        );
+        let mut game = Game::from_file(&path.to_string_lossy())?;
+        if let Some(carry) = &self.progress.carry_over {
+            carry.apply(game.state_mut());
+        }
+        Ok(game)
+    }
+
+    /// Record the outcome of a finished scenario. Only a human victory unlocks the next scenario
+    /// and updates the carry-over.
+    ///
+    /// # Errors
+    /// Fails when the progress cannot be written.
+    pub fn record_result(&mut self, index: usize, state: &GameState) -> Result<()> {
+        let Some(winner) = state.winner() else {
+            return Ok(());
+        };
+        if !state
+            .civ(winner)
+            .is_ok_and(|c| matches!(c.city.player_type, PlayerType::PLAYER))
+        {
+            log::info!("Campaign scenario {index} lost; progress unchanged");
+            return Ok(());
+        }
+        self.progress.completed[index] = true;
+        self.progress.carry_over = Some(CarryOver::from_state(state, winner));
+        let json = serde_json::to_string_pretty(&self.progress)?;
+        write_to_file(&Self::progress_file(&self.manifest), &json)
+    }
+}
@@ -0,0 +1,126 @@
+use crate::game::armies;
+use crate::game::build_queue::{self, QueueEdit};
+use crate::game::composition;
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+use log::info;
+
+/// Share of the unspent cost refunded by cancellations, in percent, unless set in `[game]`
+pub const DEFAULT_CANCEL_REFUND_PERCENT: u32 = 100;
+
+/// Resources given back when cancelling something that cost `cost` with `remaining` of its
+/// `total` turns left: the refunded share of the part not yet built or trained.
+pub fn refund(state: &GameState, cost: u32, remaining: u32, total: u32) -> u32 {
+    let unspent = (u64::from(cost) * u64::from(remaining))
+        .checked_div(u64::from(total))
+        .unwrap_or(u64::from(cost));
+    u32::try_from(unspent * u64::from(state.cancel_refund) / 100).unwrap_or(u32::MAX)
+}
+
+/// Cancel the construction of `building` by `civ_index`, the one being built when None.
+/// Returns the resources refunded.
+///
+/// # Errors
+/// Fails when nothing matching is being built.
+pub fn cancel_construction(
+    state: &mut GameState,
+    civ_index: usize,
+    building: Option<&str>,
+) -> Result<u32> {
+    let constructions = &state.civ(civ_index)?.constructions;
+    let index = match building {
+        Some(name) => constructions
+            .iter()
+            .position(|c| c.id_building.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow!("No construction of {name} in progress"))?,
+        None if constructions.is_empty() => return Err(anyhow!("No construction in progress")),
+        None => 0,
+    };
+    let refund = build_queue::refund(state, civ_index, index);
+    build_queue::apply(state, civ_index, QueueEdit::Cancel(index + 1))?;
+    Ok(refund)
+}
+
+/// Cancel the recruitment in progress of `civ_index`. Returns the resources refunded.
+///
+/// # Errors
+/// Fails when no recruitment is in progress.
+pub fn cancel_recruitment(state: &mut GameState, civ_index: usize) -> Result<u32> {
+    let Some(recruitment) = state.civ(civ_index)?.recruitments.first().cloned() else {
+        return Err(anyhow!("No recruitment in progress"));
+    };
+    let refund = refund(
+        state,
+        recruitment.cost,
+        recruitment.remaining,
+        recruitment.total,
+    );
+    let civ = state.civ_mut(civ_index)?;
+    civ.recruitments.remove(0);
+    civ.earn(i32::try_from(refund).unwrap_or(i32::MAX));
+    info!(
+        "Civ {civ_index} cancelled the recruitment of {} (+{refund} resources)",
+        recruitment.id_unit
+    );
+    Ok(refund)
+}
+
+/// Cities the armies of `civ_index` march on, which `recall` may call the armies back from.
+pub fn recall_targets(state: &GameState, civ_index: usize) -> Vec<String> {
+    let mut targets: Vec<String> = state
+        .travels
+        .iter()
+        .filter(|t| t.attacker == civ_index && !t.returning())
+        .map(|t| state.city_name(t.defender).to_string())
+        .collect();
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+/// Turn the armies of `civ_index` marching on `target` around. They walk their path back and
+/// their units join the city once home; armies still on their first tile are home at once.
+/// Returns the number of units recalled.
+///
+/// # Errors
+/// Fails when `target` names no city or no army of `civ_index` marches on it.
+pub fn recall(state: &mut GameState, civ_index: usize, target: &str) -> Result<u32> {
+    let defender = state
+        .civilizations
+        .iter()
+        .position(|c| c.city.name.eq_ignore_ascii_case(target))
+        .ok_or_else(|| anyhow!("Unknown city: {target}"))?;
+    let armies: Vec<usize> = (0..state.travels.len())
+        .filter(|&i| !state.travels[i].returning())
+        .filter(|&i| {
+            (state.travels[i].attacker, state.travels[i].defender) == (civ_index, defender)
+        })
+        .collect();
+    if armies.is_empty() {
+        return Err(anyhow!(
+            "No army of yours marches on {}",
+            state.city_name(defender)
+        ));
+    }
+    let mut recalled: u32 = 0;
+    for i in armies.into_iter().rev() {
+        let travel = &mut state.travels[i];
+        recalled = recalled.saturating_add(travel.amount);
+        if travel.step == 0 {
+            let travel = state.travels.remove(i);
+            composition::disband(state, civ_index, &travel);
+            continue;
+        }
+        travel.path.truncate(travel.step + 1);
+        travel.path.reverse();
+        travel.step = 0;
+        travel.progress = 0;
+        travel.defender = civ_index;
+        let turns = armies::turns_left(state, &state.travels[i]);
+        let travel = &mut state.travels[i];
+        travel.remaining = turns;
+        travel.total = turns;
+    }
+    info!("Civ {civ_index} recalled {recalled} units marching on civ {defender}");
+    Ok(recalled)
+}
@@ -0,0 +1,164 @@
+use crate::ast::{City, ProductionType, UnitInstance};
+use crate::game::state::GameState;
+use crate::game::war;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+/// City taken from another civilization, held as long as a garrison stays in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Captured {
+    /// Civilization the city was taken from
+    pub founder: usize,
+    /// The city, its units being the garrison
+    pub city: City,
+}
+
+/// Cities `civ_index` holds: its own city and those it captured, none once eliminated.
+pub fn held(state: &GameState, civ_index: usize) -> usize {
+    state
+        .civ(civ_index)
+        .ok()
+        .filter(|c| c.alive)
+        .map_or(0, |c| 1 + c.captured.len())
+}
+
+/// Civilization holding every city left in the game, when cities can be captured.
+pub fn dominator(state: &GameState) -> Option<usize> {
+    let total: usize = (0..state.civilizations.len()).map(|i| held(state, i)).sum();
+    (0..state.civilizations.len()).find(|i| total > 0 && held(state, *i) == total)
+}
+
+/// Every city on the map with the color of its holder: the city of each civilization, razed
+/// ones included, then the captured cities over them.
+pub fn cities(state: &GameState) -> Vec<(&City, &str)> {
+    let own = state
+        .civilizations
+        .iter()
+        .map(|c| (&c.city, c.city.color.as_str()));
+    let captured = state.civilizations.iter().flat_map(|c| {
+        c.captured
+            .iter()
+            .map(move |k| (&k.city, c.city.color.as_str()))
+    });
+    own.chain(captured).collect()
+}
+
+/// Resources the captured cities of `civ_index` produce for it each turn, from their finished
+/// buildings.
+pub fn tribute(state: &GameState, civ_index: usize) -> i32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    civ.captured
+        .iter()
+        .flat_map(|k| &k.city.buildings.elements)
+        .filter_map(|b| state.building_def(&b.id_building))
+        .filter(|d| matches!(d.production.prod_type, ProductionType::RESSOURCE))
+        .fold(0i32, |tribute, d| {
+            tribute.saturating_add(d.production.amount.cast_signed())
+        })
+}
+
+/// Units garrisoning the captured cities of `civ_index`, paying their upkeep like any other.
+pub fn garrisons(state: &GameState, civ_index: usize) -> impl Iterator<Item = &UnitInstance> {
+    state
+        .civ(civ_index)
+        .into_iter()
+        .flat_map(|c| &c.captured)
+        .flat_map(|k| &k.city.units.units)
+}
+
+/// Give the city of `defender` to `attacker`, garrisoned by `garrison`. The defender moves to the
+/// first city it captured, or is eliminated when it holds no other. Returns the report of the
+/// capture.
+pub fn capture(
+    state: &mut GameState,
+    attacker: usize,
+    defender: usize,
+    garrison: Vec<UnitInstance>,
+) -> String {
+    let held_units = garrison
+        .iter()
+        .fold(0u32, |n, u| n.saturating_add(u.nb_units));
+    let Ok(civ) = state.civ_mut(defender) else {
+        return String::new();
+    };
+    let mut city = civ.city.clone();
+    city.units.units = garrison;
+    let name = city.name.clone();
+    civ.constructions.clear();
+    civ.recruitments.clear();
+    let taken = Captured {
+        founder: defender,
+        city,
+    };
+    let moved = if civ.captured.is_empty() {
+        civ.alive = false;
+        civ.city.units.units.clear();
+        None
+    } else {
+        // the capital moves to the first city it took, which keeps its name
+        let next = civ.captured.remove(0).city;
+        let own = &mut civ.city;
+        *own = City {
+            player_type: own.player_type.clone(),
+            color: own.color.clone(),
+            ..next
+        };
+        Some(own.name.clone())
+    };
+    if moved.is_none() {
+        war::eliminate(state, defender);
+    }
+    state.refresh_producers(defender);
+    let victor = state.city_name(attacker);
+    let message = match moved {
+        Some(capital) => format!(
+            "{victor} captured {name} with {held_units} units in garrison: its civilization moves to {capital}"
+        ),
+        None => format!(
+            "{victor} captured {name} with {held_units} units in garrison: its civilization lost its last city"
+        ),
+    };
+    if let Ok(civ) = state.civ_mut(attacker) {
+        civ.captured.push(taken);
+    }
+    message
+}
+
+/// Captured cities of `civ_index` left without a garrison revolt: each goes back for good to the
+/// civilization it was taken from when that one is still alive, and is abandoned otherwise.
+/// Cities it took back from their captors need no garrison. Called at the turn start of the
+/// holder, which is told of each revolt.
+pub fn revolts(state: &mut GameState, civ_index: usize) {
+    let Ok(civ) = state.civ_mut(civ_index) else {
+        return;
+    };
+    let (empty, kept): (Vec<_>, Vec<_>) = civ
+        .captured
+        .drain(..)
+        .partition(|k| k.founder != civ_index && k.city.units.units.is_empty());
+    civ.captured = kept;
+    let holder = civ.city.name.clone();
+    let mut reports = Vec::new();
+    for lost in empty {
+        let name = lost.city.name.clone();
+        if let Ok(founder) = state.civ_mut(lost.founder)
+            && founder.alive
+        {
+            reports.push(format!(
+                "{name} revolted against {holder} and went back to {}",
+                founder.city.name
+            ));
+            founder.captured.push(lost);
+        } else {
+            reports.push(format!(
+                "{name} revolted against {holder} and was abandoned"
+            ));
+        }
+        info!("Captured city {name} of civ {civ_index} revolted");
+    }
+    for report in reports {
+        state.announce(vec![civ_index], "Revolt", &report);
+    }
+}
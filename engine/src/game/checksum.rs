@@ -0,0 +1,59 @@
+use crate::game::saves::SavedGame;
+use crate::game::state::GameState;
+use crate::game::utils::hash_tmb;
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Canonical text of the gameplay state: exactly the fields a save persists, plus the hash of
+/// the configuration they are played with. The display state (camera, popups, panels, caches),
+/// the metrics and the event log are left out, as a save leaves them out.
+pub fn canonical(state: &GameState) -> String {
+    canonical_save(&SavedGame::of_state(state, None), state.config_hash)
+}
+
+/// Canonical text of a save played with the configuration hashed to `config_hash`, one line per
+/// field and per item of the lists, so that a diff points at the civilization or army that
+/// changed.
+pub fn canonical_save(saved: &SavedGame, config_hash: u32) -> String {
+    let mut text = format!("config {config_hash:08x}\n");
+    // serialized rather than debug-printed, since the favors are sorted on the way out
+    let Ok(Value::Object(fields)) = serde_json::to_value(saved) else {
+        return text;
+    };
+    for (field, value) in fields {
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    let _ = writeln!(text, "{field} {item}");
+                }
+            }
+            value => {
+                let _ = writeln!(text, "{field} {value}");
+            }
+        }
+    }
+    text
+}
+
+/// Checksum of the gameplay state, equal on every machine playing the same game. Comparing it
+/// at the start of each turn detects games that drifted apart.
+pub fn state_checksum(state: &GameState) -> u32 {
+    hash_tmb(&canonical(state))
+}
+
+/// Checksum of a save played with the configuration hashed to `config_hash`, equal to the
+/// `state_checksum` of the game it was taken from.
+pub fn save_checksum(saved: &SavedGame, config_hash: u32) -> u32 {
+    hash_tmb(&canonical_save(saved, config_hash))
+}
+
+/// Log a desync with what is needed to investigate it.
+pub fn report_desync(state: &GameState, expected: u32) {
+    log::error!(
+        "Desync at turn {} (player {}): expected state checksum {expected:08x}, got {:08x}\n{}",
+        state.turn,
+        state.player_turn,
+        state_checksum(state),
+        canonical(state)
+    );
+}
@@ -0,0 +1,140 @@
+use crate::game::composition;
+use crate::game::conquest::Conquest;
+use crate::game::diplomacy;
+use crate::game::events::GameEvent;
+use crate::game::state::{GameState, Travel};
+use log::info;
+
+/// Power the city of `civ_index` holds its walls with: the defense of its units, or their attack
+/// when they have none, scaled by morale, plus the defense of its finished buildings.
+pub fn city_defense(state: &GameState, civ_index: usize) -> i32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    // in u64, saturated to i32, since the DSL allows large counts and defenses
+    let garrison = civ.city.units.units.iter().fold(0u64, |garrison, u| {
+        let defense = state
+            .unit_def(&u.id_units)
+            .map_or(0, |d| d.defense.unwrap_or(d.attack));
+        garrison.saturating_add(u64::from(u.nb_units) * u64::from(defense))
+    });
+    let walls = civ
+        .city
+        .buildings
+        .elements
+        .iter()
+        .filter_map(|b| state.building_def(&b.id_building)?.defense)
+        .fold(0u64, |walls, defense| {
+            walls.saturating_add(u64::from(defense))
+        });
+    let power = (garrison.saturating_mul(u64::from(civ.morale)) / 100).saturating_add(walls);
+    i32::try_from(power).unwrap_or(i32::MAX)
+}
+
+/// Units both sides lose in a battle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Casualties {
+    /// Whether the attacker took the city
+    pub won: bool,
+    /// Attacking units lost, out of the army
+    pub attacker: u32,
+    /// Units lost by the garrison
+    pub defender: u32,
+}
+
+/// Outcome of an army of `army` units and `attacker_power` against a garrison of `garrison`
+/// units defending with `defender_power`. The stronger side wins, and each side loses its units
+/// in proportion of the share of the enemy in the power of the battle, rounded down.
+pub fn casualties(
+    attacker_power: i32,
+    defender_power: i32,
+    army: u32,
+    garrison: u32,
+) -> Casualties {
+    let (attack, defense) = (
+        u64::from(attacker_power.max(0).cast_unsigned()),
+        u64::from(defender_power.max(0).cast_unsigned()),
+    );
+    let total = (attack + defense).max(1);
+    // each share is at most the side it is taken from, so it fits back in u32
+    let share =
+        |units: u32, enemy: u64| u32::try_from(u64::from(units) * enemy / total).unwrap_or(units);
+    Casualties {
+        won: attack > defense,
+        attacker: share(army, defense),
+        defender: share(garrison, attack),
+    }
+}
+
+/// Resolve the battle of `travel`, which reached the city it marches on. The army fights with
+/// the attack of its units against the defense of the city. Both sides count their dead and the
+/// survivors of the army go home; a victorious attacker decides the fate of the
+/// city, the survivors waiting at its gates when they may capture it.
+pub fn battle(state: &mut GameState, travel: &Travel) {
+    let (attacker, defender) = (travel.attacker, travel.defender);
+    let attacker_power = composition::power(state, travel);
+    let defender_power = diplomacy::defense_power(state, defender);
+    let garrison = state
+        .civ(defender)
+        .map_or(0, |c| composition::total(&c.city.units.units));
+    let losses = casualties(attacker_power, defender_power, travel.amount, garrison);
+    let lost = state.remove_units_from_city(defender, losses.defender);
+    state.metrics.lose_units(defender, lost);
+    state.metrics.lose_units(attacker, losses.attacker);
+    let mut survivors = travel.clone();
+    composition::lose(&mut survivors, losses.attacker);
+    let army = if state.capture && losses.won {
+        std::mem::take(&mut survivors.composition)
+    } else {
+        Vec::new()
+    };
+    let survivors = if survivors.amount > 0 && army.is_empty() {
+        composition::disband(state, attacker, &survivors);
+        survivors.amount
+    } else {
+        0
+    };
+    let (name, target) = (
+        state.city_name(attacker).to_string(),
+        state.city_name(defender).to_string(),
+    );
+
+    if losses.won {
+        if let Ok(beaten) = state.civ_mut(defender) {
+            beaten.beaten_by.insert(attacker);
+        }
+        // the attacker decides the fate of the city
+        state.pending_conquests.push(Conquest {
+            attacker,
+            defender,
+            attacker_power,
+            defender_power,
+            army,
+        });
+        info!(
+            "Battle resolved: attacker {attacker} defeated defender {defender}, losses {} / {lost}",
+            losses.attacker
+        );
+    } else {
+        state.open_popup(
+            "Battle",
+            &format!(
+                "{name} attacked {target} ({attacker_power} vs {defender_power}) — attack failed, attacker lost {} units and {survivors} went home, defender lost {lost} units",
+                losses.attacker
+            ),
+            vec![],
+        );
+        info!(
+            "Battle resolved: attacker {attacker} failed against {defender}, losses {} / {lost}",
+            losses.attacker
+        );
+    }
+    state.events.publish(
+        state.turn,
+        GameEvent::Battle {
+            attacker,
+            defender,
+            won: losses.won,
+        },
+    );
+}
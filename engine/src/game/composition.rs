@@ -0,0 +1,199 @@
+use crate::ast::UnitInstance;
+use crate::game::state::{GameState, Travel};
+use crate::game::{stacks, war};
+use anyhow::{Result, anyhow};
+use std::fmt::Write;
+
+/// Batch sizes offered for each unit type by the army popup, along with half and all of them
+const AMOUNTS: [u32; 3] = [0, 1, 5];
+
+/// Number of units in `units`, saturated since the DSL allows large counts.
+pub fn total<'a>(units: impl IntoIterator<Item = &'a UnitInstance>) -> u32 {
+    units
+        .into_iter()
+        .fold(0, |total, u| total.saturating_add(u.nb_units))
+}
+
+/// Attack power of `travel`: the attack of each of its units. Armies without a recorded
+/// composition (older saves) fight with one point per unit.
+pub fn power(state: &GameState, travel: &Travel) -> i32 {
+    if travel.composition.is_empty() {
+        return travel.amount.cast_signed();
+    }
+    units_power(state, &travel.composition)
+}
+
+/// Attack power of `units`.
+pub fn units_power(state: &GameState, units: &[UnitInstance]) -> i32 {
+    let power = units.iter().fold(0u64, |power, u| {
+        power.saturating_add(
+            u64::from(u.nb_units) * u64::from(state.unit_def(&u.id_units).map_or(0, |d| d.attack)),
+        )
+    });
+    i32::try_from(power).unwrap_or(i32::MAX)
+}
+
+/// Attack power `travel` keeps after losing `lost` more units.
+pub fn power_after(state: &GameState, travel: &Travel, lost: u32) -> i32 {
+    let mut after = travel.clone();
+    lose(&mut after, lost);
+    power(state, &after)
+}
+
+/// Take `amount` units out of the city of `civ_index`, in garrison order.
+pub fn levy(state: &mut GameState, civ_index: usize, mut amount: u32) -> Vec<UnitInstance> {
+    let mut army = Vec::new();
+    let Ok(civ) = state.civ(civ_index) else {
+        return army;
+    };
+    for unit in &civ.city.units.units {
+        let taken = unit.nb_units.min(amount);
+        if taken > 0 {
+            army.push(UnitInstance {
+                id_units: unit.id_units.clone(),
+                nb_units: taken,
+            });
+            amount -= taken;
+        }
+    }
+    for unit in &army {
+        take(state, civ_index, &unit.id_units, unit.nb_units);
+    }
+    army
+}
+
+/// Take the units of each type of `units` out of the city of `civ_index`. Nothing is taken when
+/// the city holds fewer of any of them.
+///
+/// # Errors
+/// Fails when a unit type is unknown, the city holds too few of it, or no unit is asked.
+pub fn levy_units(
+    state: &mut GameState,
+    civ_index: usize,
+    units: &[(String, u32)],
+) -> Result<Vec<UnitInstance>> {
+    let garrison = &state.civ(civ_index)?.city.units.units;
+    let mut army: Vec<UnitInstance> = Vec::new();
+    for (name, amount) in units.iter().filter(|(_, a)| *a > 0) {
+        let def = state
+            .unit_def(name)
+            .ok_or_else(|| anyhow!("Unknown unit: {name}"))?;
+        let available = total(garrison.iter().filter(|u| u.id_units == def.name));
+        let sent = total(army.iter().filter(|u| u.id_units == def.name));
+        if sent.saturating_add(*amount) > available {
+            return Err(anyhow!(
+                "Not enough {}: {available} in the city, {} asked",
+                def.name,
+                u64::from(sent) + u64::from(*amount)
+            ));
+        }
+        army.push(UnitInstance {
+            id_units: def.name.clone(),
+            nb_units: *amount,
+        });
+    }
+    if army.is_empty() {
+        return Err(anyhow!("Send at least one unit"));
+    }
+    for unit in &army {
+        take(state, civ_index, &unit.id_units, unit.nb_units);
+    }
+    Ok(army)
+}
+
+/// Remove `amount` units of type `unit` from the city of `civ_index`.
+fn take(state: &mut GameState, civ_index: usize, unit: &str, mut amount: u32) {
+    let Ok(civ) = state.civ_mut(civ_index) else {
+        return;
+    };
+    let units = &mut civ.city.units.units;
+    for instance in units.iter_mut().filter(|u| u.id_units == unit) {
+        let taken = instance.nb_units.min(amount);
+        instance.nb_units -= taken;
+        amount -= taken;
+    }
+    units.retain(|u| u.nb_units > 0);
+}
+
+/// Remove `lost` units from `travel`, its first units falling first.
+pub fn lose(travel: &mut Travel, lost: u32) {
+    let lost = lost.min(travel.amount);
+    travel.amount -= lost;
+    let mut left = lost;
+    for unit in &mut travel.composition {
+        let fallen = unit.nb_units.min(left);
+        unit.nb_units -= fallen;
+        left -= fallen;
+    }
+    travel.composition.retain(|u| u.nb_units > 0);
+}
+
+/// Put the units of `travel` back in the city of `civ_index`, each with its own type.
+pub fn disband(state: &mut GameState, civ_index: usize, travel: &Travel) {
+    if travel.composition.is_empty() {
+        war::return_home(state, civ_index, travel.amount);
+        return;
+    }
+    stacks::station(state, civ_index, travel.composition.clone());
+}
+
+/// `unit=count` argument of the attack action.
+pub fn parse_pair(arg: &str) -> Option<(String, u32)> {
+    let (unit, amount) = arg.split_once('=')?;
+    Some((unit.to_string(), amount.parse().ok()?))
+}
+
+/// Prompt and choices of the army popup of `civ_index` attacking `target`, once the amounts of
+/// the first unit types of its city are `picked`: the amounts of the next type, each choice
+/// holding the whole army as parsed back by `from_choice`. None once every type is picked.
+pub fn army_popup(
+    state: &GameState,
+    civ_index: usize,
+    target: &str,
+    picked: &[(String, u32)],
+) -> Option<(String, Vec<String>)> {
+    let garrison = &state.civ(civ_index).ok()?.city.units.units;
+    let types = garrison.iter().filter(|u| u.nb_units > 0).count();
+    let next = garrison
+        .iter()
+        .filter(|u| u.nb_units > 0)
+        .nth(picked.len())?;
+    let available = next.nb_units;
+    let mut amounts: Vec<u32> = AMOUNTS
+        .into_iter()
+        .chain([available / 2, available])
+        .filter(|a| *a <= available)
+        .collect();
+    amounts.sort_unstable();
+    amounts.dedup();
+    let so_far = picked.iter().fold(String::new(), |mut s, (unit, amount)| {
+        let _ = write!(s, " {unit}={amount}");
+        s
+    });
+    let mut choices: Vec<String> = amounts
+        .into_iter()
+        .map(|a| format!("{target}:{so_far} {}={a}", next.id_units))
+        .collect();
+    if picked.is_empty() {
+        choices.push(format!("{target}: all units"));
+    }
+    let prompt = format!(
+        "Army against {target}, step {}/{types}: how many {} to send? ({available} in the city)",
+        picked.len() + 1,
+        next.id_units
+    );
+    Some((prompt, choices))
+}
+
+/// Target and unit amounts of a choice of the army popup, no amounts standing for all units.
+pub fn from_choice(choice: &str) -> Option<(String, Vec<(String, u32)>)> {
+    let (target, units) = choice.split_once(": ")?;
+    if units == "all units" {
+        return Some((target.to_string(), Vec::new()));
+    }
+    let units = units
+        .split_whitespace()
+        .map(parse_pair)
+        .collect::<Option<Vec<_>>>()?;
+    Some((target.to_string(), units))
+}
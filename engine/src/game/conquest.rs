@@ -0,0 +1,189 @@
+use crate::ast::UnitInstance;
+use crate::game::capture;
+use crate::game::state::{GameState, Popup};
+use crate::game::utils::hash_tmb;
+use crate::game::{diplomacy, stacks, war};
+use log::{info, warn};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Share of the defender resources gained by razing its city, in percent
+pub const RAZE_LOOT_PERCENT: i32 = 50;
+/// Morale the attacker loses when razing a city
+pub const RAZE_MORALE_COST: u32 = 10;
+/// Share of the defender resources stolen by pillaging, in percent
+pub const PILLAGE_LOOT_PERCENT: i32 = 30;
+/// Morale the pillaged defender loses
+pub const PILLAGE_MORALE_COST: u32 = 30;
+/// Morale the attacker gains from pillaging
+pub const PILLAGE_MORALE_GAIN: u32 = 10;
+/// Favor every surviving civilization loses toward an attacker razing a city
+pub const RAZE_FAVOR_LOSS: u32 = 10;
+/// Favor the treaty partners of a pillaged civilization lose toward the attacker
+pub const PILLAGE_FAVOR_LOSS: u32 = 15;
+
+/// Choices offered to the attacker, in popup order, `CAPTURE` coming last when it may capture the
+/// city.
+pub const CHOICES: [&str; 2] = ["Raze", "Pillage"];
+/// Choice keeping the city, garrisoned by the survivors of the army
+pub const CAPTURE: &str = "Capture";
+
+/// A won battle waiting for the attacker to decide the fate of the defeated city.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conquest {
+    pub attacker: usize,
+    pub defender: usize,
+    pub attacker_power: i32,
+    pub defender_power: i32,
+    /// Survivors of the army, waiting to garrison the city when `capture` is on in `[game]`;
+    /// empty when they already went home
+    #[serde(default)]
+    pub army: Vec<UnitInstance>,
+}
+
+/// Popup asking the attacker what to do with the defeated city.
+pub fn popup(state: &GameState, conquest: &Conquest) -> Popup {
+    let mut text = format!(
+        "{} defeated {} ({} vs {}).\nRaze: destroy the city and take {RAZE_LOOT_PERCENT}% of its resources (your morale -{RAZE_MORALE_COST}%, favor -{RAZE_FAVOR_LOSS} with every survivor)\nPillage: steal {PILLAGE_LOOT_PERCENT}% of its resources and destroy a building, the city survives (its morale -{PILLAGE_MORALE_COST}%, yours +{PILLAGE_MORALE_GAIN}%, favor -{PILLAGE_FAVOR_LOSS} with its treaty partners)",
+        state.city_name(conquest.attacker),
+        state.city_name(conquest.defender),
+        conquest.attacker_power,
+        conquest.defender_power
+    );
+    let mut choices: Vec<String> = CHOICES.iter().map(ToString::to_string).collect();
+    if !conquest.army.is_empty() {
+        let units = conquest
+            .army
+            .iter()
+            .fold(0u32, |n, u| n.saturating_add(u.nb_units));
+        let _ = write!(
+            text,
+            "\nCapture: take the city and its buildings, garrisoned by the {units} surviving units; it revolts if left without a garrison"
+        );
+        choices.push(CAPTURE.to_string());
+    }
+    Popup::new("Conquest", &text, choices)
+}
+
+/// Apply the attacker decision to the first pending conquest. Unknown choices raze the city.
+pub fn resolve(state: &mut GameState, choice: &str) {
+    if state.pending_conquests.is_empty() {
+        return;
+    }
+    let mut conquest = state.pending_conquests.remove(0);
+    let (attacker, defender) = (conquest.attacker, conquest.defender);
+    let (Ok(defender_civ), Ok(_)) = (state.civ(defender), state.civ(attacker)) else {
+        warn!("Dropping the conquest of civ {defender} by civ {attacker}: unknown civilization");
+        return;
+    };
+    let defender_resources = defender_civ.resources.ressources.max(0);
+    let mut army = std::mem::take(&mut conquest.army);
+    let capturing = choice.eq_ignore_ascii_case(CAPTURE) && !army.is_empty();
+    if !capturing && !army.is_empty() {
+        // the survivors waiting at the gates go home
+        stacks::station(state, attacker, std::mem::take(&mut army));
+    }
+
+    let message = if capturing {
+        capture::capture(state, attacker, defender, army)
+    } else if choice.eq_ignore_ascii_case("pillage") {
+        let loot = percent_of(defender_resources, PILLAGE_LOOT_PERCENT);
+        // drawn from the map seed and the turn, so replays destroy the same building
+        let seed = hash_tmb(&format!(
+            "{}-pillage-{}-{attacker}-{defender}",
+            state.map.seed, state.turn
+        ));
+        let mut rng = SmallRng::seed_from_u64(u64::from(seed));
+        let Ok(defender_civ) = state.civ_mut(defender) else {
+            return;
+        };
+        defender_civ.earn(-loot);
+        defender_civ.morale = defender_civ.morale.saturating_sub(PILLAGE_MORALE_COST);
+        let buildings = &mut defender_civ.city.buildings.elements;
+        let destroyed = (!buildings.is_empty()).then(|| {
+            buildings
+                .remove(rng.random_range(0..buildings.len()))
+                .id_building
+        });
+        state.refresh_producers(defender);
+        let Ok(attacker_civ) = state.civ_mut(attacker) else {
+            return;
+        };
+        attacker_civ.earn(loot);
+        attacker_civ.morale = (attacker_civ.morale + PILLAGE_MORALE_GAIN).min(100);
+        let partners = state
+            .diplomacy
+            .treaties_of(defender)
+            .map(|(_, p)| p)
+            .collect();
+        let outraged = outrage(state, attacker, partners, PILLAGE_FAVOR_LOSS);
+        format!(
+            "{} pillaged {}: {loot} resources stolen, {}{outraged}",
+            state.city_name(attacker),
+            state.city_name(defender),
+            destroyed.map_or_else(
+                || "no building to destroy".to_string(),
+                |b| format!("{b} destroyed")
+            ),
+        )
+    } else {
+        let loot = percent_of(defender_resources, RAZE_LOOT_PERCENT);
+        if let Ok(defender_civ) = state.civ_mut(defender) {
+            defender_civ.alive = false;
+        }
+        war::eliminate(state, defender);
+        let Ok(attacker_civ) = state.civ_mut(attacker) else {
+            return;
+        };
+        attacker_civ.earn(loot);
+        attacker_civ.morale = attacker_civ.morale.saturating_sub(RAZE_MORALE_COST);
+        let outraged = outrage(
+            state,
+            attacker,
+            (0..state.civilizations.len()).collect(),
+            RAZE_FAVOR_LOSS,
+        );
+        format!(
+            "{} razed {}: defender eliminated, {loot} resources taken{outraged}",
+            state.city_name(attacker),
+            state.city_name(defender)
+        )
+    };
+    info!("Conquest resolved: {message}");
+    war::settle(state);
+    state.announce(vec![attacker, defender], "Battle", &message);
+    state.check_game_over();
+}
+
+/// Diplomatic incidents against `attacker`: each of the `witnesses` still alive loses
+/// `favor_loss` favor toward it. Returns the report appended to the conquest message.
+fn outrage(
+    state: &mut GameState,
+    attacker: usize,
+    witnesses: BTreeSet<usize>,
+    favor_loss: u32,
+) -> String {
+    let mut offended = Vec::new();
+    for witness in witnesses {
+        if witness != attacker && state.civ(witness).is_ok_and(|c| c.alive) {
+            diplomacy::incident(state, witness, attacker, favor_loss);
+            offended.push(state.city_name(witness).to_string());
+        }
+    }
+    if offended.is_empty() {
+        return String::new();
+    }
+    format!(
+        "; {} lost {favor_loss} favor toward {}",
+        offended.join(", "),
+        state.city_name(attacker)
+    )
+}
+
+/// `percent` percent of `resources`, in i64 so that large treasuries cannot overflow.
+fn percent_of(resources: i32, percent: i32) -> i32 {
+    i32::try_from(i64::from(resources) * i64::from(percent) / 100).unwrap_or(i32::MAX)
+}
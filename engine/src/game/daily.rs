@@ -0,0 +1,144 @@
+use crate::ast::PlayerType;
+use crate::game::history;
+use crate::game::state::GameState;
+use crate::game::utils::{hash_tmb, output_path, write_to_file};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LEADERBOARD_FILE: &str = "daily_leaderboard.json";
+
+/// Daily challenge: every player gets the same map and victory turn limit on a given day.
+#[derive(Debug, Clone)]
+pub struct DailyChallenge {
+    /// Date of the challenge (YYYY-MM-DD, UTC)
+    pub date: String,
+    /// Map seed derived from the date
+    pub seed: String,
+    /// Turn limit derived from the date
+    pub nb_turns: u32,
+}
+
+/// A finished daily challenge, as stored in the leaderboard and submitted to the endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DailyEntry {
+    pub date: String,
+    pub seed: String,
+    pub player: String,
+    pub score: i32,
+    pub turns: i32,
+    pub won: bool,
+}
+
+impl DailyChallenge {
+    /// Challenge of the current UTC day.
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self::for_date(&date_from_days(secs / 86_400))
+    }
+
+    /// Derive the challenge parameters from a date string.
+    pub fn for_date(date: &str) -> Self {
+        let hash = hash_tmb(&format!("daily-{date}"));
+        Self {
+            date: date.to_string(),
+            seed: format!("daily-{date}"),
+            nb_turns: 20 + hash % 21,
+        }
+    }
+
+    /// Regenerate the map from the daily seed, apply the turn limit and lock reseeding.
+    pub fn apply(&self, state: &mut GameState) {
+        state.map = state.map.reseeded(self.seed.clone());
+        state.map_buffer_cache = None;
+        state.nb_turns = self.nb_turns;
+        state.seed_locked = true;
+        log::info!(
+            "Daily challenge {}: seed '{}', {} turns",
+            self.date,
+            self.seed,
+            self.nb_turns
+        );
+    }
+
+    /// Score the first human civilization (or the first one when spectating), as scored in the
+    /// game history.
+    pub fn score(&self, state: &GameState) -> DailyEntry {
+        let civ_index = state
+            .civilizations
+            .iter()
+            .position(|c| matches!(c.city.player_type, PlayerType::PLAYER))
+            .unwrap_or(0);
+        DailyEntry {
+            date: self.date.clone(),
+            seed: self.seed.clone(),
+            player: state.city_name(civ_index).to_string(),
+            score: history::score(state, civ_index),
+            turns: state.turn,
+            won: state.winner() == Some(civ_index),
+        }
+    }
+
+    /// Append the result to the local leaderboard (best scores first) and, when
+    /// `DAILY_SUBMIT_URL` is set, POST it to that endpoint. Submission failures are only logged.
+    ///
+    /// # Errors
+    /// Fails when the leaderboard cannot be written.
+    pub fn record(&self, state: &GameState) -> anyhow::Result<DailyEntry> {
+        let entry = self.score(state);
+
+        let mut board: Vec<DailyEntry> = std::fs::read_to_string(output_path(LEADERBOARD_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        board.push(entry.clone());
+        board.sort_by(|a, b| b.date.cmp(&a.date).then(b.score.cmp(&a.score)));
+        write_to_file(LEADERBOARD_FILE, &serde_json::to_string_pretty(&board)?)?;
+        log::info!("Daily score {} recorded for {}", entry.score, entry.date);
+
+        #[cfg(feature = "online")]
+        if let Ok(url) = std::env::var("DAILY_SUBMIT_URL")
+            && !url.is_empty()
+        {
+            match submit(&url, &entry) {
+                Ok(()) => log::info!("Daily score submitted to {url}"),
+                Err(e) => log::warn!("Failed to submit daily score to {url}: {e}"),
+            }
+        }
+        Ok(entry)
+    }
+}
+
+/// POST `entry` to the leaderboard server. The blocking client runs its own runtime, which
+/// panics when started from within another one (the LLM AIs run on tokio), so the request is
+/// sent from a thread of its own.
+#[cfg(feature = "online")]
+fn submit(url: &str, entry: &DailyEntry) -> anyhow::Result<()> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| -> anyhow::Result<()> {
+                let client = reqwest::blocking::Client::builder()
+                    .timeout(std::time::Duration::from_secs(10))
+                    .build()?;
+                client.post(url).json(entry).send()?.error_for_status()?;
+                Ok(())
+            })
+            .join()
+            .map_err(|_| anyhow::anyhow!("the leaderboard submission thread panicked"))?
+    })
+}
+
+/// Convert a number of days since 1970-01-01 to a YYYY-MM-DD date (proleptic Gregorian).
+pub(crate) fn date_from_days(days: u64) -> String {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,176 @@
+use crate::game::checksum;
+use crate::game::replay::{Input, ReplayEvent, ReplayHeader, ReplayRecorder};
+use crate::game::state::GameState;
+use crate::game::utils::output_path;
+use crate::game::{Game, UiState};
+use anyhow::{Context, Result, anyhow};
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+/// Number of state snapshots kept, older steps can be exported but no longer jumped to
+pub const MAX_SNAPSHOTS: usize = 200;
+
+/// Time-travel debugger enabled by `--debug`: every input is kept with the state right before
+/// it, so a developer can go back to any recent step, compare two steps, and export the inputs
+/// leading to a bug as a replay.
+pub struct Debugger {
+    header: ReplayHeader,
+    /// Every input since the debugger started; step `n` is the state before `inputs[n]`
+    inputs: Vec<ReplayEvent>,
+    /// Snapshots of the most recent steps, with their step number
+    snapshots: VecDeque<(usize, GameState)>,
+}
+
+impl Debugger {
+    pub fn new(header: ReplayHeader) -> Self {
+        Self {
+            header,
+            inputs: Vec::new(),
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    /// Keep `input`, about to be played by `player`, with the state before it.
+    pub fn record(&mut self, player: usize, input: &Input, state: &GameState) {
+        if self.snapshots.len() == MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back((self.inputs.len(), state.clone()));
+        self.inputs.push(ReplayEvent {
+            turn: state.turn,
+            player,
+            input: input.clone(),
+        });
+    }
+
+    fn snapshot(&self, step: usize) -> Result<&GameState> {
+        self.snapshots
+            .iter()
+            .find(|(s, _)| *s == step)
+            .map(|(_, state)| state)
+            .ok_or_else(|| match self.snapshots.front() {
+                Some((first, _)) => anyhow!(
+                    "no snapshot of step {step}: steps {first} to {} are kept",
+                    self.inputs.len() - 1
+                ),
+                None => anyhow!("no step recorded yet"),
+            })
+    }
+
+    /// List of the kept steps: turn, player and triggering input.
+    pub fn steps(&self) -> String {
+        let first = self
+            .snapshots
+            .front()
+            .map_or(self.inputs.len(), |(s, _)| *s);
+        self.inputs.iter().enumerate().skip(first).fold(
+            format!("{} steps recorded", self.inputs.len()),
+            |mut text, (i, e)| {
+                let _ = write!(
+                    text,
+                    "\n{i}: turn {} player {} {:?}",
+                    e.turn, e.player, e.input
+                );
+                text
+            },
+        )
+    }
+
+    /// Lines of the gameplay state that differ between two steps.
+    ///
+    /// # Errors
+    /// Fails when the snapshot of either step is not kept.
+    pub fn diff(&self, from: usize, to: usize) -> Result<String> {
+        let before = checksum::canonical(self.snapshot(from)?);
+        let after = checksum::canonical(self.snapshot(to)?);
+        let changes = before
+            .lines()
+            .zip(after.lines())
+            .filter(|(a, b)| a != b)
+            .fold(String::new(), |mut text, (a, b)| {
+                let _ = write!(text, "\n- {a}\n+ {b}");
+                text
+            });
+        Ok(if changes.is_empty() {
+            format!("No difference between steps {from} and {to}")
+        } else {
+            format!("Steps {from} -> {to}:{changes}")
+        })
+    }
+
+    /// Go back to the state before `step`, forgetting the later steps.
+    ///
+    /// # Errors
+    /// Fails when the snapshot of `step` is not kept.
+    pub fn jump(&mut self, step: usize) -> Result<GameState> {
+        let state = self.snapshot(step)?.clone();
+        self.inputs.truncate(step);
+        self.snapshots.retain(|(s, _)| *s < step);
+        Ok(state)
+    }
+
+    /// Write the configuration and the inputs before `step` as a replay, the smallest
+    /// reproduction of the state at that step. Play it with the `replay` subcommand.
+    ///
+    /// # Errors
+    /// Fails when `step` was not played yet or the replay cannot be written.
+    pub fn export(&self, step: usize) -> Result<std::path::PathBuf> {
+        if step > self.inputs.len() {
+            return Err(anyhow!(
+                "step {step} does not exist yet ({} recorded)",
+                self.inputs.len()
+            ));
+        }
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let path = output_path(&format!("repros/{secs}-step{step}.replay.zst"));
+        let mut recorder = ReplayRecorder::create_at(path, &self.header)?;
+        for event in &self.inputs[..step] {
+            recorder.record(event);
+        }
+        recorder.finish()
+    }
+}
+
+/// Run a `debug` command of the headless mode and return its output.
+///
+/// # Errors
+/// Fails on an unknown command or invalid arguments.
+pub fn command(game: &mut Game, args: &[&str]) -> Result<String> {
+    let debugger = game
+        .debugger
+        .as_mut()
+        .ok_or_else(|| anyhow!("the debugger is off, start the game with --debug"))?;
+    let step = |i: usize| -> Result<usize> {
+        args.get(i)
+            .ok_or_else(|| anyhow!("missing step number"))?
+            .parse()
+            .context("invalid step number")
+    };
+    match args.first().copied() {
+        Some("steps") | None => Ok(debugger.steps()),
+        Some("diff") => debugger.diff(step(1)?, step(2)?),
+        Some("jump") => {
+            let target = step(1)?;
+            game.state = debugger.jump(target)?;
+            game.ui_state = if game.state.popup.is_some() {
+                UiState::PopupOpen
+            } else {
+                UiState::Normal
+            };
+            Ok(format!("Back to step {target} (turn {})", game.state.turn))
+        }
+        Some("export") => {
+            let target = args.get(1).map_or(Ok(debugger.inputs.len()), |_| step(1))?;
+            let path = debugger.export(target)?;
+            Ok(format!(
+                "Reproduction of step {target} written to {}",
+                path.display()
+            ))
+        }
+        Some(other) => Err(anyhow!(
+            "unknown debug command '{other}' (steps, diff <a> <b>, jump <step>, export [step])"
+        )),
+    }
+}
@@ -0,0 +1,212 @@
+use crate::game::diplomacy;
+use crate::game::influence;
+use crate::game::loans;
+use crate::game::map::{GameMap, Terrain};
+use crate::game::state::GameState;
+
+/// Compass direction and distance (in tiles) from one position to another, the short way around
+/// a map that wraps.
+fn relative_position(map: &GameMap, from: (u32, u32), to: (u32, u32)) -> String {
+    let dx = map.offset_x(i64::from(from.0), i64::from(to.0));
+    let dy = i64::from(to.1) - i64::from(from.1);
+    if dx == 0 && dy == 0 {
+        return "on the same tile".to_string();
+    }
+    // y grows towards the south on the map
+    let vertical = match dy.signum() {
+        -1 if dy.abs() * 2 >= dx.abs() => "north",
+        1 if dy.abs() * 2 >= dx.abs() => "south",
+        _ => "",
+    };
+    let horizontal = match dx.signum() {
+        -1 if dx.abs() * 2 >= dy.abs() => "west",
+        1 if dx.abs() * 2 >= dy.abs() => "east",
+        _ => "",
+    };
+    let direction = match (vertical, horizontal) {
+        ("", h) => h.to_string(),
+        (v, "") => v.to_string(),
+        (v, h) => format!("{v}-{h}"),
+    };
+    let distance = dx.abs().max(dy.abs());
+    let unit = if distance == 1 { "tile" } else { "tiles" };
+    format!("{distance} {unit} {direction}")
+}
+
+fn terrain_name(terrain: Terrain) -> &'static str {
+    match terrain {
+        Terrain::Water => "water",
+        Terrain::Plains => "plains",
+        Terrain::Desert => "desert",
+        Terrain::Mountain => "mountains",
+        Terrain::River => "river",
+        Terrain::Forest => "forest",
+        Terrain::Snow => "snow",
+        Terrain::Swamp => "swamp",
+    }
+}
+
+fn plural(n: u32, word: &str) -> String {
+    if n == 1 {
+        format!("{n} {word}")
+    } else {
+        format!("{n} {word}s")
+    }
+}
+
+/// Textual description of the game from the point of view of a civilization, one sentence
+/// per line, so the game can be followed without the map (screen readers, no color perception).
+pub fn describe_state(state: &GameState, civ_index: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let Some(civ) = state.civilizations.get(civ_index) else {
+        return lines;
+    };
+    let own = (civ.city.x, civ.city.y);
+
+    if let Ok(current) = state.current_civ() {
+        lines.push(format!(
+            "Turn {}. {} is playing.",
+            state.turn, current.city.name
+        ));
+    }
+    let terrain = state
+        .map
+        .tiles
+        .get(own.1 as usize)
+        .and_then(|row| row.get(own.0 as usize))
+        .copied()
+        .map_or("unknown terrain", terrain_name);
+    lines.push(format!(
+        "Your city {} at {},{} on {terrain}, {} resources.",
+        civ.city.name, own.0, own.1, civ.resources.ressources
+    ));
+    if !civ.alive {
+        lines.push("Your city has been destroyed.".to_string());
+    }
+    let (own_influence, foreign_influence) = influence::pressure(state, civ_index);
+    if foreign_influence > own_influence {
+        lines.push(format!(
+            "Foreign influence on your city ({foreign_influence}) exceeds your own ({own_influence}).",
+        ));
+    }
+    if let Some(favor) =
+        diplomacy::lowest_favor(state, civ_index).filter(|f| *f > 0 && state.favor_threshold > 0)
+    {
+        lines.push(format!(
+            "Your lowest favor with a rival is {favor} out of {} needed for a diplomatic victory.",
+            state.favor_threshold
+        ));
+    }
+    if !civ.loans.is_empty() {
+        lines.push(format!(
+            "You owe {} resources on {} loan(s), army morale at {}%.",
+            loans::debt(civ),
+            civ.loans.len(),
+            civ.morale
+        ));
+    }
+
+    lines.extend(city_lines(state, civ_index));
+
+    for (i, other) in state.civilizations.iter().enumerate() {
+        if i == civ_index {
+            continue;
+        }
+        let status = if other.alive { "" } else { " (destroyed)" };
+        lines.push(format!(
+            "Enemy city {}{status} {} at {},{}.",
+            other.city.name,
+            relative_position(&state.map, own, (other.city.x, other.city.y)),
+            other.city.x,
+            other.city.y
+        ));
+    }
+
+    lines.extend(army_lines(state, civ_index));
+
+    lines
+}
+
+/// Buildings, units, constructions and recruitments of the city of `civ_index`.
+fn city_lines(state: &GameState, civ_index: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let Ok(civ) = state.civ(civ_index) else {
+        return lines;
+    };
+    let buildings: Vec<&str> = civ
+        .city
+        .buildings
+        .elements
+        .iter()
+        .map(|b| b.id_building.as_str())
+        .collect();
+    if buildings.is_empty() {
+        lines.push("No buildings.".to_string());
+    } else {
+        lines.push(format!("Buildings: {}.", buildings.join(", ")));
+    }
+    let units: Vec<String> = civ
+        .city
+        .units
+        .units
+        .iter()
+        .map(|u| format!("{} {}", u.nb_units, u.id_units))
+        .collect();
+    if units.is_empty() {
+        lines.push("No units.".to_string());
+    } else {
+        lines.push(format!(
+            "Units: {}, power {}.",
+            units.join(", "),
+            state.calculate_city_power(civ_index)
+        ));
+    }
+    for c in &civ.constructions {
+        lines.push(format!(
+            "Building {}, ready in {}.",
+            c.id_building,
+            plural(c.remaining, "turn")
+        ));
+    }
+    for r in &civ.recruitments {
+        lines.push(format!(
+            "Recruiting {} {} in the {}, ready in {}.",
+            r.amount,
+            r.id_unit,
+            r.producer,
+            plural(r.remaining, "turn")
+        ));
+    }
+    lines
+}
+
+/// Armies marching from or on the city of `civ_index`.
+fn army_lines(state: &GameState, civ_index: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for t in &state.travels {
+        let eta = plural(t.remaining, "turn");
+        if t.returning() && t.attacker == civ_index {
+            lines.push(format!(
+                "Your army of {} is back home in {eta}.",
+                plural(t.amount, "unit")
+            ));
+        } else if t.defender == civ_index {
+            lines.push(format!(
+                "Incoming army of {} from {} arrives in {eta}.",
+                plural(t.amount, "unit"),
+                if t.raiding() {
+                    "raiders"
+                } else {
+                    state.city_name(t.attacker)
+                }
+            ));
+        } else if t.attacker == civ_index {
+            lines.push(format!(
+                "Your army of {} reaches {} in {eta}.",
+                plural(t.amount, "unit"),
+                state.city_name(t.defender)
+            ));
+        }
+    }
+    lines
+}
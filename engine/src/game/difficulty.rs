@@ -0,0 +1,119 @@
+use crate::ast::PlayerType;
+use crate::game::state::GameState;
+use serde::{Deserialize, Serialize};
+
+/// Largest production bonus or penalty, in percent
+pub const MAX_ADJUSTMENT: i32 = 30;
+
+/// Production adjustment of an AI civilization, from its power compared to the human's.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Adjustment {
+    pub civ: usize,
+    pub ai_power: i32,
+    pub human_power: i32,
+    /// Bonus (positive) or penalty (negative) on the income of the AI, in percent
+    pub percent: i32,
+}
+
+/// Dynamic difficulty enabled by `--dynamic-difficulty`: AIs weaker than the human get a
+/// production bonus and stronger ones a penalty, recalculated every `interval` turns.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DynamicDifficulty {
+    pub interval: u32,
+    pub adjustments: Vec<Adjustment>,
+    /// Turn of the last recalculation
+    pub updated_at: Option<i32>,
+}
+
+impl DynamicDifficulty {
+    pub fn new(interval: u32) -> Self {
+        Self {
+            interval: interval.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Adjustment of the income of `civ`, in percent.
+    pub fn percent(&self, civ: usize) -> i32 {
+        self.adjustments
+            .iter()
+            .find(|a| a.civ == civ)
+            .map_or(0, |a| a.percent)
+    }
+}
+
+/// Adjustment for an AI of power `ai_power` against a human of power `human_power`: the
+/// relative gap, scaled to at most `MAX_ADJUSTMENT` when one side has no army at all.
+pub fn adjustment_percent(ai_power: i32, human_power: i32) -> i32 {
+    let strongest = ai_power.max(human_power).max(1);
+    let gap = (i64::from(human_power) - i64::from(ai_power)) * i64::from(MAX_ADJUSTMENT)
+        / i64::from(strongest);
+    i32::try_from(gap.clamp(-i64::from(MAX_ADJUSTMENT), i64::from(MAX_ADJUSTMENT)))
+        .unwrap_or_default()
+}
+
+/// Recalculate the adjustments when they are due. Without a living human player (AI against
+/// AI), every adjustment is dropped.
+pub fn update(state: &mut GameState) {
+    let Some(difficulty) = &state.difficulty else {
+        return;
+    };
+    if difficulty
+        .updated_at
+        .is_some_and(|t| state.turn - t < difficulty.interval.cast_signed())
+    {
+        return;
+    }
+    let human = state
+        .civilizations
+        .iter()
+        .position(|c| c.alive && matches!(c.city.player_type, PlayerType::PLAYER));
+    let adjustments = human.map_or_else(Vec::new, |human| {
+        let human_power = state.calculate_city_power(human);
+        state
+            .civilizations
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.alive && matches!(c.city.player_type, PlayerType::AI))
+            .map(|(civ, _)| {
+                let ai_power = state.calculate_city_power(civ);
+                Adjustment {
+                    civ,
+                    ai_power,
+                    human_power,
+                    percent: adjustment_percent(ai_power, human_power),
+                }
+            })
+            .collect()
+    });
+    let turn = state.turn;
+    if let Some(difficulty) = &mut state.difficulty {
+        difficulty.adjustments = adjustments;
+        difficulty.updated_at = Some(turn);
+    }
+}
+
+/// Lines of the transparency panel, so the player knows how much each AI is helped.
+pub fn describe(state: &GameState) -> Vec<String> {
+    let Some(difficulty) = &state.difficulty else {
+        return Vec::new();
+    };
+    let mut lines = vec![match difficulty.updated_at {
+        Some(turn) => format!(
+            "Recalculé tous les {} tours (dernier: tour {turn})",
+            difficulty.interval
+        ),
+        None => format!("Recalculé tous les {} tours", difficulty.interval),
+    }];
+    if difficulty.adjustments.is_empty() {
+        lines.push("Aucun ajustement".to_string());
+    }
+    for a in &difficulty.adjustments {
+        let name = state.civ(a.civ).map_or("?", |c| c.city.name.as_str());
+        lines.push(format!(
+            "{name}: puissance {} contre {} -> production {:+}%",
+            a.ai_power, a.human_power, a.percent
+        ));
+    }
+    lines
+}
@@ -0,0 +1,663 @@
+use crate::ast::{Relation, Stance};
+use crate::game::combat;
+use crate::game::events::GameEvent;
+use crate::game::state::{GameState, Popup};
+use crate::game::war;
+use anyhow::{Result, anyhow};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Resources to give for one point of favor
+pub const GIFT_RESOURCES_PER_FAVOR: u32 = 2;
+/// Favor gifts can buy from the same civilization in one turn
+pub const MAX_GIFT_FAVOR_PER_TURN: u32 = 10;
+/// Favor both partners of a trade agreement gain toward each other at each of their turn starts
+pub const TRADE_FAVOR: u32 = 3;
+/// Resources each partner of a trade agreement earns at its turn start
+pub const TRADE_INCOME: i32 = 5;
+/// Favor both partners of a defense pact gain toward each other at each of their turn starts
+pub const PACT_FAVOR: u32 = 5;
+/// Favor the partner must already hold toward the proposer to sign a defense pact
+pub const PACT_REQUIRED_FAVOR: u32 = 20;
+/// Share of the power of its pact partners joining the defense of a city, in percent
+pub const PACT_DEFENSE_PERCENT: i32 = 25;
+
+/// Agreement between two civilizations, broken as soon as one attacks the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Treaty {
+    /// Both partners earn resources and favor every turn
+    Trade,
+    /// Partners join the defense of each other's city
+    DefensePact,
+}
+
+impl Treaty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Treaty::Trade => "trade agreement",
+            Treaty::DefensePact => "defense pact",
+        }
+    }
+
+    fn favor(self) -> u32 {
+        match self {
+            Treaty::Trade => TRADE_FAVOR,
+            Treaty::DefensePact => PACT_FAVOR,
+        }
+    }
+}
+
+/// What a proposal offers its partner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Deal {
+    /// Resources given for favor
+    Gift(u32),
+    Treaty(Treaty),
+    /// End of a war between the two
+    Peace(war::Terms),
+}
+
+/// Gift, treaty or peace offered by `from` to `to`, waiting for its answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Proposal {
+    pub from: usize,
+    pub to: usize,
+    pub deal: Deal,
+}
+
+/// Choices offered to the partner of a proposal, in popup order.
+pub const CHOICES: [&str; 2] = ["Accept", "Refuse"];
+
+/// Favor between civilizations and the treaties they signed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Diplomacy {
+    /// Favor held by the first civilization toward the second
+    #[serde(with = "favor_entries")]
+    favor: HashMap<(usize, usize), u32>,
+    /// Signed treaties with their two partners
+    treaties: Vec<(Treaty, usize, usize)>,
+    /// Wars declared by the first civilization on the second, lasting until they make peace
+    #[serde(default)]
+    wars: Vec<(usize, usize)>,
+    /// Gifts, treaties and peace offers waiting for the answer of their partner, oldest first
+    #[serde(default)]
+    proposals: Vec<Proposal>,
+    /// Favor bought with gifts, as `(turn, receiver, giver, favor)` entries of the current turn
+    #[serde(default)]
+    bought: Vec<(i32, usize, usize, u32)>,
+}
+
+/// Favor saved as `(from, to, favor)` entries, JSON maps only having string keys.
+mod favor_entries {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(
+        favor: &HashMap<(usize, usize), u32>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<(usize, usize, u32)> =
+            favor.iter().map(|(&(a, b), &f)| (a, b, f)).collect();
+        entries.sort_unstable();
+        entries.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<HashMap<(usize, usize), u32>, D::Error> {
+        let entries = Vec::<(usize, usize, u32)>::deserialize(d)?;
+        Ok(entries.into_iter().map(|(a, b, f)| ((a, b), f)).collect())
+    }
+}
+
+impl Diplomacy {
+    /// Favor `from` holds toward `to`.
+    pub fn favor(&self, from: usize, to: usize) -> u32 {
+        self.favor.get(&(from, to)).copied().unwrap_or(0)
+    }
+
+    fn add_favor(&mut self, from: usize, to: usize, amount: u32) {
+        let favor = self.favor.entry((from, to)).or_insert(0);
+        *favor = favor.saturating_add(amount);
+    }
+
+    /// Favor `giver` bought from `receiver` with the gifts accepted on `turn`, and those still
+    /// waiting for an answer.
+    fn bought(&self, turn: i32, receiver: usize, giver: usize) -> u32 {
+        let accepted = self
+            .bought
+            .iter()
+            .filter(|b| (b.0, b.1, b.2) == (turn, receiver, giver))
+            .map(|b| b.3);
+        let offered = self.proposals.iter().filter_map(|p| match p.deal {
+            Deal::Gift(amount) if (p.from, p.to) == (giver, receiver) => {
+                Some(amount / GIFT_RESOURCES_PER_FAVOR)
+            }
+            _ => None,
+        });
+        accepted.chain(offered).fold(0, u32::saturating_add)
+    }
+
+    /// Whether `a` and `b` signed a treaty of kind `treaty`.
+    pub fn has_treaty(&self, treaty: Treaty, a: usize, b: usize) -> bool {
+        self.treaties
+            .iter()
+            .any(|(t, x, y)| *t == treaty && ((*x, *y) == (a, b) || (*x, *y) == (b, a)))
+    }
+
+    /// Whether a war was declared between `a` and `b`, by either of them.
+    pub fn declared_war(&self, a: usize, b: usize) -> bool {
+        self.wars.iter().any(|w| *w == (a, b) || *w == (b, a))
+    }
+
+    /// Forget the war declared between `a` and `b`.
+    pub(crate) fn end_war(&mut self, a: usize, b: usize) {
+        self.wars.retain(|w| *w != (a, b) && *w != (b, a));
+    }
+
+    /// Treaties `civ_index` signed, with the partner of each.
+    pub fn treaties_of(&self, civ_index: usize) -> impl Iterator<Item = (Treaty, usize)> + '_ {
+        self.treaties.iter().filter_map(move |(t, a, b)| {
+            if *a == civ_index {
+                Some((*t, *b))
+            } else if *b == civ_index {
+                Some((*t, *a))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Check that a diplomatic action between `from` and `to` is possible.
+pub(crate) fn check_partners(state: &GameState, from: usize, to: usize) -> Result<()> {
+    if from == to {
+        return Err(anyhow!("Cannot negotiate with yourself"));
+    }
+    if state.game_over {
+        return Err(anyhow!("Game is over"));
+    }
+    if !state.civ(to).is_ok_and(|c| c.alive) {
+        return Err(anyhow!("{} is defeated", state.city_name(to)));
+    }
+    if let Some(puzzle) = &state.puzzle {
+        puzzle.check_budget(from, false)?;
+    }
+    Ok(())
+}
+
+/// Offer `amount` resources from `giver` to `receiver`, who accepts or refuses them. Gifts buy at
+/// most `MAX_GIFT_FAVOR_PER_TURN` favor from the same civilization per turn. Returns the favor the
+/// gift earns once accepted.
+///
+/// # Errors
+/// Fails when `receiver` cannot be negotiated with, or the gift is too small, above the treasury of
+/// `giver`, or over the favor left to buy this turn.
+pub fn gift(state: &mut GameState, giver: usize, receiver: usize, amount: u32) -> Result<u32> {
+    check_partners(state, giver, receiver)?;
+    let favor = amount / GIFT_RESOURCES_PER_FAVOR;
+    if favor == 0 {
+        return Err(anyhow!(
+            "Gift too small: give at least {GIFT_RESOURCES_PER_FAVOR} resources"
+        ));
+    }
+    i32::try_from(amount)
+        .ok()
+        .filter(|v| *v <= state.civ(giver).map_or(0, |c| c.resources.ressources))
+        .ok_or_else(|| anyhow!("Not enough resources to give {amount}"))?;
+    let left =
+        MAX_GIFT_FAVOR_PER_TURN.saturating_sub(state.diplomacy.bought(state.turn, receiver, giver));
+    if favor > left {
+        return Err(anyhow!(
+            "{} takes at most {left} more favor in gifts from you this turn ({} resources)",
+            state.city_name(receiver),
+            left * GIFT_RESOURCES_PER_FAVOR
+        ));
+    }
+    state.diplomacy.proposals.push(Proposal {
+        from: giver,
+        to: receiver,
+        deal: Deal::Gift(amount),
+    });
+    if let Some(puzzle) = &mut state.puzzle {
+        puzzle.consume(giver, false);
+    }
+    Ok(favor)
+}
+
+/// Propose `treaty` to `partner`, who accepts or refuses it. Refused while one of them has an army
+/// marching on the other, and pacts need `PACT_REQUIRED_FAVOR` from the partner.
+///
+/// # Errors
+/// Fails when `partner` cannot be negotiated with, the treaty is already signed or offered, the two
+/// are at war, or a pact lacks favor.
+pub fn sign(state: &mut GameState, treaty: Treaty, proposer: usize, partner: usize) -> Result<()> {
+    check_partners(state, proposer, partner)?;
+    let name = state.city_name(partner);
+    if state.diplomacy.has_treaty(treaty, proposer, partner) {
+        return Err(anyhow!("A {} with {name} is already signed", treaty.name()));
+    }
+    if war::at_war(state, proposer, partner) {
+        return Err(anyhow!("{name} refuses: you are at war"));
+    }
+    let favor = state.diplomacy.favor(partner, proposer);
+    if treaty == Treaty::DefensePact && favor < PACT_REQUIRED_FAVOR {
+        return Err(anyhow!(
+            "{name} refuses a defense pact: favor {favor}/{PACT_REQUIRED_FAVOR}"
+        ));
+    }
+    propose(state, proposer, partner, Deal::Treaty(treaty))?;
+    if let Some(puzzle) = &mut state.puzzle {
+        puzzle.consume(proposer, false);
+    }
+    Ok(())
+}
+
+/// Put `deal` to `to`, unless the same offer from `from` is still waiting for an answer.
+pub(crate) fn propose(state: &mut GameState, from: usize, to: usize, deal: Deal) -> Result<()> {
+    let proposal = Proposal { from, to, deal };
+    if state.diplomacy.proposals.contains(&proposal) {
+        return Err(anyhow!(
+            "{} has not answered the same offer yet",
+            state.city_name(to)
+        ));
+    }
+    state.diplomacy.proposals.push(proposal);
+    Ok(())
+}
+
+/// First proposal waiting for an answer between two surviving civilizations.
+pub fn pending(state: &GameState) -> Option<Proposal> {
+    let alive = |i: usize| state.civilizations.get(i).is_some_and(|c| c.alive);
+    state
+        .diplomacy
+        .proposals
+        .iter()
+        .find(|p| alive(p.from) && alive(p.to))
+        .copied()
+}
+
+/// Popup asking the partner of `proposal` whether it accepts it.
+pub fn popup(state: &GameState, proposal: Proposal) -> Popup {
+    let from = state.city_name(proposal.from);
+    let prompt = match proposal.deal {
+        Deal::Gift(amount) => format!(
+            "{from} offers you {amount} resources.\nAccept: the resources are yours and your favor toward {from} grows by {}\nRefuse: {from} keeps them",
+            amount / GIFT_RESOURCES_PER_FAVOR
+        ),
+        Deal::Treaty(Treaty::Trade) => format!(
+            "{from} proposes a trade agreement.\nAccept: both of you earn {TRADE_INCOME} resources and {TRADE_FAVOR} favor toward each other every turn\nRefuse: no agreement"
+        ),
+        Deal::Treaty(Treaty::DefensePact) => format!(
+            "{from} proposes a defense pact.\nAccept: each of you joins the defense of the other's city with {PACT_DEFENSE_PERCENT}% of its power, and both earn {PACT_FAVOR} favor toward each other every turn\nRefuse: no pact"
+        ),
+        Deal::Peace(terms) => {
+            let reparations = state.war.reparations;
+            let payment = match terms {
+                war::Terms::WhitePeace => "nobody pays".to_string(),
+                war::Terms::PayReparations => {
+                    format!("{from} pays you {reparations}% of its resources")
+                }
+                war::Terms::DemandReparations => {
+                    format!("you pay {from} {reparations}% of your resources")
+                }
+            };
+            format!(
+                "{from} offers peace.\nAccept: the armies on both sides go home, and {payment}\nRefuse: the war goes on"
+            )
+        }
+    };
+    Popup::new(
+        "Proposal",
+        &prompt,
+        CHOICES.iter().map(ToString::to_string).collect(),
+    )
+}
+
+/// Apply the answer of the partner to the first pending proposal: an accepted gift is paid and
+/// earns favor, an accepted treaty or peace is signed. Unknown choices refuse it.
+pub fn resolve(state: &mut GameState, choice: &str) {
+    let Some(proposal) = pending(state) else {
+        return;
+    };
+    state.diplomacy.proposals.retain(|p| *p != proposal);
+    let Proposal { from, to, deal } = proposal;
+    let message = if choice.eq_ignore_ascii_case(CHOICES[0]) {
+        conclude(state, proposal).unwrap_or_else(|e| format!("{e:#}"))
+    } else {
+        let what = match deal {
+            Deal::Gift(amount) => format!("gift of {amount} resources"),
+            Deal::Treaty(treaty) => treaty.name().to_string(),
+            Deal::Peace(_) => "peace offer".to_string(),
+        };
+        format!(
+            "{} refused the {what} of {}",
+            state.city_name(to),
+            state.city_name(from)
+        )
+    };
+    info!("Proposal resolved: {message}");
+    state.announce(vec![from, to], "Diplomacy", &message);
+    state.check_game_over();
+}
+
+/// Carry out an accepted proposal, checked again as the proposer may have spent its resources,
+/// gone to war with the partner or made peace since.
+fn conclude(state: &mut GameState, proposal: Proposal) -> Result<String> {
+    let Proposal { from, to, deal } = proposal;
+    let (proposer, partner) = (
+        state.city_name(from).to_string(),
+        state.city_name(to).to_string(),
+    );
+    match deal {
+        Deal::Gift(amount) => {
+            let value = i32::try_from(amount)
+                .ok()
+                .filter(|v| *v <= state.civ(from).map_or(0, |c| c.resources.ressources))
+                .ok_or_else(|| {
+                    anyhow!("{proposer} can no longer give {amount} resources to {partner}")
+                })?;
+            let bought = state.diplomacy.bought(state.turn, to, from);
+            let favor = (amount / GIFT_RESOURCES_PER_FAVOR)
+                .min(MAX_GIFT_FAVOR_PER_TURN.saturating_sub(bought));
+            state.civ_mut(from)?.spend(amount);
+            state.civ_mut(to)?.earn(value);
+            state.diplomacy.add_favor(to, from, favor);
+            let turn = state.turn;
+            state.diplomacy.bought.retain(|b| b.0 == turn);
+            state.diplomacy.bought.push((turn, to, from, favor));
+            Ok(format!(
+                "{partner} accepted {amount} resources from {proposer} and gains {favor} favor toward it"
+            ))
+        }
+        Deal::Treaty(treaty) => {
+            if state.diplomacy.has_treaty(treaty, from, to) {
+                return Err(anyhow!(
+                    "A {} between {proposer} and {partner} is already signed",
+                    treaty.name()
+                ));
+            }
+            if war::at_war(state, from, to) {
+                return Err(anyhow!(
+                    "{proposer} and {partner} are at war: no {}",
+                    treaty.name()
+                ));
+            }
+            state.diplomacy.treaties.push((treaty, from, to));
+            state.events.publish(
+                state.turn,
+                GameEvent::TreatySigned {
+                    treaty,
+                    first: from,
+                    second: to,
+                },
+            );
+            Ok(format!(
+                "{partner} signed a {} with {proposer}",
+                treaty.name()
+            ))
+        }
+        Deal::Peace(terms) => {
+            war::sign_peace(state, from, to, terms)?;
+            Ok(format!(
+                "{partner} accepted the peace offered by {proposer}"
+            ))
+        }
+    }
+}
+
+/// An attack breaks every treaty between the two civilizations, and the defender forgets all the
+/// favor it held toward the attacker. Returns the broken treaties.
+pub fn break_relations(state: &mut GameState, attacker: usize, defender: usize) -> Vec<Treaty> {
+    let diplomacy = &mut state.diplomacy;
+    let mut broken = Vec::new();
+    diplomacy.treaties.retain(|(t, a, b)| {
+        let between = (*a, *b) == (attacker, defender) || (*a, *b) == (defender, attacker);
+        if between {
+            broken.push(*t);
+        }
+        !between
+    });
+    diplomacy.proposals.retain(|p| {
+        (p.from, p.to) != (attacker, defender) && (p.from, p.to) != (defender, attacker)
+    });
+    diplomacy.favor.remove(&(defender, attacker));
+    broken
+}
+
+/// Diplomatic incident caused by `culprit`: `victim` loses `favor_loss` favor toward it.
+/// Returns the favor the victim still holds.
+pub fn incident(state: &mut GameState, victim: usize, culprit: usize, favor_loss: u32) -> u32 {
+    let favor = state
+        .diplomacy
+        .favor(victim, culprit)
+        .saturating_sub(favor_loss);
+    state.diplomacy.favor.insert((victim, culprit), favor);
+    favor
+}
+
+/// Treaty benefits at the turn start of `civ_index`: trade income and favor from every partner.
+pub fn apply_treaties(state: &mut GameState, civ_index: usize) {
+    let treaties: Vec<(Treaty, usize)> = state.diplomacy.treaties_of(civ_index).collect();
+    for (treaty, partner) in treaties {
+        if !state.civ(partner).is_ok_and(|c| c.alive) {
+            continue;
+        }
+        if treaty == Treaty::Trade
+            && let Ok(civ) = state.civ_mut(civ_index)
+        {
+            civ.earn(TRADE_INCOME);
+            state.metrics.gather(civ_index, TRADE_INCOME);
+        }
+        state
+            .diplomacy
+            .add_favor(partner, civ_index, treaty.favor());
+    }
+}
+
+/// Power defending the city of `civ_index`: its own defense plus `PACT_DEFENSE_PERCENT` of the
+/// power of every surviving pact partner.
+pub fn defense_power(state: &GameState, civ_index: usize) -> i32 {
+    let support = state
+        .diplomacy
+        .treaties_of(civ_index)
+        .filter(|(t, p)| *t == Treaty::DefensePact && state.civ(*p).is_ok_and(|c| c.alive))
+        .map(|(_, p)| {
+            i64::from(state.calculate_city_power(p)) * i64::from(PACT_DEFENSE_PERCENT) / 100
+        })
+        .fold(
+            i64::from(combat::city_defense(state, civ_index)),
+            i64::saturating_add,
+        );
+    i32::try_from(support).unwrap_or(i32::MAX)
+}
+
+/// Lowest favor the surviving rivals of `civ_index` hold toward it, if it has any rival left.
+pub fn lowest_favor(state: &GameState, civ_index: usize) -> Option<u32> {
+    (0..state.civilizations.len())
+        .filter(|i| *i != civ_index && state.civ(*i).is_ok_and(|c| c.alive))
+        .map(|i| state.diplomacy.favor(i, civ_index))
+        .min()
+}
+
+/// Civilization holding the favor threshold with every surviving rival, if any. Never without a
+/// threshold.
+pub fn winner(state: &GameState) -> Option<usize> {
+    if state.favor_threshold == 0 {
+        return None;
+    }
+    let winner = (0..state.civilizations.len()).find(|i| {
+        state.civ(*i).is_ok_and(|c| c.alive)
+            && lowest_favor(state, *i).is_some_and(|f| f >= state.favor_threshold)
+    });
+    if let Some(i) = winner {
+        info!("Diplomatic victory reached by civ {i}");
+    }
+    winner
+}
+
+/// Civilizations named by `relation`, which must be two different cities.
+fn partners(state: &GameState, relation: &Relation) -> Result<(usize, usize)> {
+    let find = |name: &str| {
+        state
+            .civilizations
+            .iter()
+            .position(|c| c.city.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow!("diplomacy of unknown city {name}"))
+    };
+    let (city, partner) = (find(&relation.city)?, find(&relation.partner)?);
+    if city == partner {
+        return Err(anyhow!(
+            "diplomacy: {} cannot be its own partner",
+            relation.city
+        ));
+    }
+    Ok((city, partner))
+}
+
+/// Check the `[diplomacy]` relations against the loaded cities, then set those without a turn or
+/// whose turn has passed: the scenario starts with them.
+///
+/// # Errors
+/// Fails when a relation names an unknown city or a city with itself.
+pub fn open_relations(state: &mut GameState) -> Result<()> {
+    for relation in &state.relations {
+        partners(state, relation)?;
+    }
+    let current = u32::try_from(state.turn).unwrap_or(0);
+    let starting: Vec<Relation> = state
+        .relations
+        .iter()
+        .filter(|r| r.turn.is_none_or(|t| t <= current))
+        .cloned()
+        .collect();
+    for relation in &starting {
+        set_stance(state, relation);
+    }
+    Ok(())
+}
+
+/// Set the relations scheduled for the current turn. Called once per round, at the start of the
+/// turn of the first civilization.
+pub fn fire_relations(state: &mut GameState) {
+    let due: Vec<Relation> = state
+        .relations
+        .iter()
+        .filter(|r| {
+            r.turn
+                .is_some_and(|t| i64::from(t) == i64::from(state.turn))
+        })
+        .cloned()
+        .collect();
+    for relation in &due {
+        set_stance(state, relation);
+    }
+}
+
+/// Put the two cities of `relation` in its stance: war breaks their treaties, peace sends their
+/// armies home, and an alliance ends any war between them with a defense pact. Nothing happens
+/// when they already stand so or one of them is defeated.
+fn set_stance(state: &mut GameState, relation: &Relation) {
+    let Ok((city, partner)) = partners(state, relation) else {
+        return;
+    };
+    if !state.civ(city).is_ok_and(|c| c.alive) || !state.civ(partner).is_ok_and(|c| c.alive) {
+        return;
+    }
+    let at_war = war::at_war(state, city, partner);
+    match relation.stance {
+        Stance::WAR if !state.diplomacy.declared_war(city, partner) => {
+            break_relations(state, city, partner);
+            state.diplomacy.wars.push((city, partner));
+            info!("Civ {city} declares war on civ {partner}");
+            state.events.publish(
+                state.turn,
+                GameEvent::WarDeclared {
+                    attacker: city,
+                    defender: partner,
+                },
+            );
+        }
+        Stance::PEACE if at_war => {
+            war::end_war(state, city, partner);
+            info!("Peace between civ {city} and civ {partner}");
+            state.events.publish(
+                state.turn,
+                GameEvent::Peace {
+                    proposer: city,
+                    partner,
+                    reparations: None,
+                },
+            );
+        }
+        Stance::ALLIANCE => {
+            if at_war {
+                war::end_war(state, city, partner);
+            }
+            if !state
+                .diplomacy
+                .has_treaty(Treaty::DefensePact, city, partner)
+            {
+                state
+                    .diplomacy
+                    .treaties
+                    .push((Treaty::DefensePact, city, partner));
+                info!("Alliance between civ {city} and civ {partner}");
+                let event = GameEvent::TreatySigned {
+                    treaty: Treaty::DefensePact,
+                    first: city,
+                    second: partner,
+                };
+                state.events.publish(state.turn, event);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `favor` out of the favor threshold, or alone when the game has no diplomatic victory.
+pub fn favor_label(favor: u32, threshold: u32) -> String {
+    if threshold == 0 {
+        favor.to_string()
+    } else {
+        format!("{favor}/{threshold}")
+    }
+}
+
+/// Favor and treaties of `civ_index` for the `diplomacy` popup.
+pub fn summary(state: &GameState, civ_index: usize) -> String {
+    let mut lines = vec![if state.favor_threshold == 0 {
+        "No diplomatic victory in this game".to_string()
+    } else {
+        format!(
+            "Diplomatic victory at {} favor with every surviving civilization",
+            state.favor_threshold
+        )
+    }];
+    for (i, civ) in state.civilizations.iter().enumerate() {
+        if i == civ_index || !civ.alive {
+            continue;
+        }
+        let treaties: Vec<&str> = state
+            .diplomacy
+            .treaties_of(civ_index)
+            .filter(|(_, p)| *p == i)
+            .map(|(t, _)| t.name())
+            .collect();
+        let treaties = if treaties.is_empty() {
+            "no treaty".to_string()
+        } else {
+            treaties.join(", ")
+        };
+        lines.push(format!(
+            "{}: favor toward you {}, {treaties}",
+            civ.city.name,
+            favor_label(state.diplomacy.favor(i, civ_index), state.favor_threshold)
+        ));
+    }
+    lines.push(format!(
+        "Usage: gift <city> <amount> ({GIFT_RESOURCES_PER_FAVOR} resources per favor, {MAX_GIFT_FAVOR_PER_TURN} favor per turn at most) | trade <city> (+{TRADE_INCOME} resources, +{TRADE_FAVOR} favor per turn) | pact <city> (+{PACT_FAVOR} favor per turn, needs {PACT_REQUIRED_FAVOR} favor)"
+    ));
+    lines.join("\n")
+}
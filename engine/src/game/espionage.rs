@@ -0,0 +1,104 @@
+use crate::ast::ProductionType;
+use crate::game::composition;
+use crate::game::diplomacy;
+use crate::game::fog;
+use crate::game::state::GameState;
+use crate::game::utils::hash_tmb;
+use anyhow::{Result, anyhow};
+use log::info;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+/// Resources spent to send a spy
+pub const SCOUT_COST: i32 = 10;
+/// Highest chance to catch a spy, in percent, however strong the counterintelligence
+pub const MAX_CATCH_PERCENT: u32 = 75;
+/// Favor the spied civilization loses toward the spy's when the spy is caught
+pub const INCIDENT_FAVOR_LOSS: u32 = 20;
+
+/// Counterintelligence strength of a civilization: the sum of its `COUNTERINTEL` buildings.
+/// It is both the noise applied to spy reports and the chance to catch a spy, in percent.
+pub fn counterintelligence(state: &GameState, civ_index: usize) -> u32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    civ.city
+        .buildings
+        .elements
+        .iter()
+        .filter_map(|b| state.building_def(&b.id_building))
+        .filter(|d| matches!(d.production.prod_type, ProductionType::COUNTERINTEL))
+        .fold(0, |strength, d| {
+            strength.saturating_add(d.production.amount)
+        })
+}
+
+/// Send a spy from `spy` onto the city of `target`. Returns the spy report, or the incident when
+/// the target counterintelligence catches the spy.
+///
+/// # Errors
+/// Fails when the target is the spy itself or is defeated, the game is over, or the spy cannot
+/// afford the mission.
+pub fn scout(state: &mut GameState, spy: usize, target: usize) -> Result<String> {
+    if spy == target {
+        return Err(anyhow!("Cannot spy on yourself"));
+    }
+    if state.game_over {
+        return Err(anyhow!("Game is over"));
+    }
+    if !state.civ(target).is_ok_and(|c| c.alive) {
+        return Err(anyhow!("{} is defeated", state.city_name(target)));
+    }
+    if let Some(puzzle) = &state.puzzle {
+        puzzle.check_budget(spy, false)?;
+    }
+    if state.civ(spy)?.resources.ressources < SCOUT_COST {
+        return Err(anyhow!(
+            "Not enough resources to send a spy ({SCOUT_COST} needed)"
+        ));
+    }
+    state.civ_mut(spy)?.spend(SCOUT_COST.cast_unsigned());
+    if let Some(puzzle) = &mut state.puzzle {
+        puzzle.consume(spy, false);
+    }
+
+    let strength = counterintelligence(state, target);
+    // drawn from the game, the spending of the spy telling its missions of a turn apart, so
+    // replays catch the same spies and blur the same reports
+    let seed = format!(
+        "{}-spy-{}-{spy}-{target}-{}",
+        state.map.seed,
+        state.turn,
+        state.civ(spy)?.resources.spent
+    );
+    let mut rng = SmallRng::seed_from_u64(u64::from(hash_tmb(&seed)));
+    let name = state.city_name(target).to_string();
+    if rng.random_range(0..100) < strength.min(MAX_CATCH_PERCENT) {
+        let favor = diplomacy::incident(state, target, spy, INCIDENT_FAVOR_LOSS);
+        info!("Spy of civ {spy} caught by civ {target}");
+        return Ok(format!(
+            "Your spy was caught in {name}: diplomatic incident, its favor toward you drops to {favor}"
+        ));
+    }
+
+    let city = &state.civ(target)?.city;
+    let units = composition::total(&city.units.units);
+    let noise = u32::try_from(u64::from(units) * u64::from(strength) / 100).unwrap_or(u32::MAX);
+    let reported = units
+        .saturating_add(rng.random_range(0..=noise.saturating_mul(2)))
+        .saturating_sub(noise);
+    let report = if noise == 0 {
+        format!(
+            "Spy report on {name}: {units} units, {} buildings",
+            city.buildings.elements.len()
+        )
+    } else {
+        format!(
+            "Spy report on {name}: about {reported} units (±{noise}, blurred by counterintelligence), {} buildings",
+            city.buildings.elements.len()
+        )
+    };
+    // a spy back home also maps the surroundings of the city
+    fog::scouted(state, spy, target);
+    Ok(report)
+}
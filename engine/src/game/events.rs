@@ -0,0 +1,359 @@
+use crate::game::diplomacy::Treaty;
+use crate::game::map::Terrain;
+use crate::game::raiders;
+use crate::game::state::GameState;
+use crate::game::terraform;
+use crate::game::utils::{output_path, write_to_file};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Something that happened during a turn and that players may want to look back at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GameEvent {
+    /// An army reached its target; `won` tells whether the attacker took the city
+    Battle {
+        attacker: usize,
+        defender: usize,
+        won: bool,
+    },
+    /// A building finished its construction
+    ConstructionFinished { civ: usize, building: String },
+    /// A technology finished its research
+    ResearchFinished { civ: usize, tech: String },
+    /// Units finished their recruitment
+    RecruitmentFinished {
+        civ: usize,
+        unit: String,
+        amount: u32,
+    },
+    /// A city was razed, eliminating its civilization
+    Eliminated { civ: usize },
+    /// The garrison of `defender` intercepted `intercepted` units of `attacker` one turn from its
+    /// walls, losing `lost` units
+    Sortie {
+        attacker: usize,
+        defender: usize,
+        intercepted: u32,
+        lost: u32,
+    },
+    /// A map event or a wonder turned `tiles` tiles into `terrain`
+    Terraformed {
+        source: String,
+        terrain: Terrain,
+        tiles: usize,
+    },
+    /// An army of `amount` units lost every way to its target after a terrain change
+    CutOff {
+        attacker: usize,
+        defender: usize,
+        amount: u32,
+    },
+    /// A party of `amount` raiders set out toward `target`
+    RaidersSighted { target: usize, amount: u32 },
+    /// `raiders` raiders reached `defender`; when `won` they carried away `pillaged` resources
+    Raid {
+        defender: usize,
+        raiders: u32,
+        won: bool,
+        pillaged: i32,
+    },
+    /// `attacker` declared war on `defender`
+    WarDeclared { attacker: usize, defender: usize },
+    /// `first` and `second` signed `treaty`
+    TreatySigned {
+        treaty: Treaty,
+        first: usize,
+        second: usize,
+    },
+    /// `proposer` and `partner` made peace; `reparations` names who pays whom
+    Peace {
+        proposer: usize,
+        partner: usize,
+        reparations: Option<(usize, usize)>,
+    },
+    /// Spoils of an elimination or reparations of a peace, paid by `from` to `to`
+    Reparations {
+        from: usize,
+        to: usize,
+        resources: i32,
+        slots: u32,
+    },
+    /// Report shown in a popup (loan installments, influence, supply, conquests, end of the
+    /// game), kept in the log of the civilizations in `civs` once the popup is gone
+    Report {
+        civs: Vec<usize>,
+        topic: String,
+        text: String,
+    },
+}
+
+impl GameEvent {
+    /// Whether the event matters to civilization `civ`.
+    pub fn concerns(&self, civ: usize) -> bool {
+        match self {
+            Self::Battle {
+                attacker, defender, ..
+            }
+            | Self::Sortie {
+                attacker, defender, ..
+            }
+            | Self::CutOff {
+                attacker, defender, ..
+            }
+            | Self::WarDeclared { attacker, defender } => *attacker == civ || *defender == civ,
+            Self::TreatySigned {
+                first: a,
+                second: b,
+                ..
+            }
+            | Self::Peace {
+                proposer: a,
+                partner: b,
+                ..
+            }
+            | Self::Reparations { from: a, to: b, .. } => *a == civ || *b == civ,
+            // the map changes for everyone
+            Self::Terraformed { .. } => true,
+            Self::Report { civs, .. } => civs.contains(&civ),
+            Self::ConstructionFinished { civ: owner, .. }
+            | Self::ResearchFinished { civ: owner, .. }
+            | Self::RecruitmentFinished { civ: owner, .. }
+            | Self::Eliminated { civ: owner }
+            | Self::RaidersSighted { target: owner, .. }
+            | Self::Raid {
+                defender: owner, ..
+            } => *owner == civ,
+        }
+    }
+
+    /// Whether the event is a battle or one of its outcomes, counted as such in the badge.
+    pub fn is_battle(&self) -> bool {
+        matches!(
+            self,
+            Self::Battle { .. } | Self::Sortie { .. } | Self::Raid { .. } | Self::Eliminated { .. }
+        )
+    }
+
+    /// One-line description for the event log.
+    pub fn describe(&self, state: &GameState) -> String {
+        let name = |i: usize| raiders::army_owner(state, i);
+        match self {
+            Self::Battle {
+                attacker,
+                defender,
+                won: true,
+            } => {
+                format!("{} a vaincu {}", name(*attacker), name(*defender))
+            }
+            Self::Battle {
+                attacker,
+                defender,
+                won: false,
+            } => {
+                format!("{} a repoussé {}", name(*defender), name(*attacker))
+            }
+            Self::ConstructionFinished { civ, building } => {
+                format!("{}: {building} terminé", name(*civ))
+            }
+            Self::ResearchFinished { civ, tech } => format!("{}: {tech} découvert", name(*civ)),
+            Self::RecruitmentFinished { civ, unit, amount } => {
+                format!("{}: {amount} {unit} recruté(s)", name(*civ))
+            }
+            Self::Eliminated { civ } => format!("{} a été rasée", name(*civ)),
+            Self::Sortie {
+                attacker,
+                defender,
+                intercepted,
+                lost,
+            } => format!(
+                "La garnison de {} a intercepté {intercepted} unités de {} ({lost} perdues)",
+                name(*defender),
+                name(*attacker)
+            ),
+            Self::Terraformed {
+                source,
+                terrain,
+                tiles,
+            } => {
+                format!(
+                    "{source}: {tiles} case(s) devenue(s) {}",
+                    terraform::terrain_name(*terrain)
+                )
+            }
+            Self::CutOff {
+                attacker,
+                defender,
+                amount,
+            } => format!(
+                "{amount} unités de {} coupées de {} par le terrain, dispersées",
+                name(*attacker),
+                name(*defender)
+            ),
+            Self::RaidersSighted { target, amount } => {
+                format!("{amount} pillards marchent sur {}", name(*target))
+            }
+            Self::Raid {
+                defender,
+                pillaged,
+                won: true,
+                ..
+            } => {
+                format!(
+                    "Des pillards ont pillé {} ({pillaged} ressources)",
+                    name(*defender)
+                )
+            }
+            Self::Raid {
+                defender,
+                raiders,
+                won: false,
+                ..
+            } => {
+                format!("{} a repoussé {raiders} pillards", name(*defender))
+            }
+            Self::WarDeclared { .. }
+            | Self::TreatySigned { .. }
+            | Self::Peace { .. }
+            | Self::Reparations { .. } => self.describe_diplomacy(state),
+            Self::Report { topic, text, .. } => format!("{topic}: {}", text.replace('\n', " ")),
+        }
+    }
+
+    /// One-line description of a war, treaty, peace or reparations event for the event log.
+    fn describe_diplomacy(&self, state: &GameState) -> String {
+        let name = |i: usize| raiders::army_owner(state, i);
+        match self {
+            Self::WarDeclared { attacker, defender } => {
+                format!(
+                    "{} déclare la guerre à {}",
+                    name(*attacker),
+                    name(*defender)
+                )
+            }
+            Self::TreatySigned {
+                treaty,
+                first,
+                second,
+            } => {
+                let treaty = match treaty {
+                    Treaty::Trade => "Accord commercial",
+                    Treaty::DefensePact => "Pacte de défense",
+                };
+                format!("{treaty} entre {} et {}", name(*first), name(*second))
+            }
+            Self::Peace {
+                proposer, partner, ..
+            } => format!("Paix entre {} et {}", name(*proposer), name(*partner)),
+            Self::Reparations {
+                from,
+                to,
+                resources,
+                slots,
+            } => format!(
+                "{} reçoit {resources} ressources et {slots} emplacement(s) de {}",
+                name(*to),
+                name(*from)
+            ),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Event bus of the game: the turn logic publishes events, and each civilization reads them
+/// through its own cursor, so a player only gets the events since their last look at the log.
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    /// Every published event with its turn, oldest first
+    log: Vec<(i32, GameEvent)>,
+    /// Per civilization, number of events of the log already read
+    read: Vec<usize>,
+}
+
+/// Unread events of a civilization, by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Unread {
+    pub battles: usize,
+    pub completions: usize,
+}
+
+impl Unread {
+    pub fn total(self) -> usize {
+        self.battles + self.completions
+    }
+}
+
+impl EventBus {
+    pub fn publish(&mut self, turn: i32, event: GameEvent) {
+        self.log.push((turn, event));
+    }
+
+    /// Number of events published so far.
+    pub fn len(&self) -> usize {
+        self.log.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.log.is_empty()
+    }
+
+    /// Events published after the first `from` ones, whoever they concern.
+    pub fn since(&self, from: usize) -> impl Iterator<Item = &(i32, GameEvent)> {
+        self.log.iter().skip(from)
+    }
+
+    /// Events concerning `civ`, oldest first.
+    pub fn events_of(&self, civ: usize) -> impl DoubleEndedIterator<Item = &(i32, GameEvent)> {
+        self.log.iter().filter(move |(_, e)| e.concerns(civ))
+    }
+
+    /// Events concerning `civ` published since it last read the log.
+    pub fn unread(&self, civ: usize) -> Unread {
+        let from = self.read.get(civ).copied().unwrap_or(0);
+        self.log[from..]
+            .iter()
+            .filter(|(_, e)| e.concerns(civ))
+            .fold(Unread::default(), |mut unread, (_, e)| {
+                if e.is_battle() {
+                    unread.battles += 1;
+                } else {
+                    unread.completions += 1;
+                }
+                unread
+            })
+    }
+
+    /// Mark every event published so far as read by `civ`.
+    pub fn mark_read(&mut self, civ: usize) {
+        if self.read.len() <= civ {
+            self.read.resize(civ + 1, 0);
+        }
+        self.read[civ] = self.log.len();
+    }
+}
+
+/// Chronicle of the whole game, every event of every civilization with its turn, oldest first.
+pub fn chronicle(state: &GameState) -> String {
+    let mut text = format!(
+        "Chronique de la partie '{}', {} tours\n\n",
+        state.map.seed, state.turn
+    );
+    for (turn, event) in &state.events.log {
+        let _ = writeln!(text, "Tour {turn}: {}", event.describe(state));
+    }
+    text
+}
+
+/// Write the chronicle of a finished game to `chronicles/` of the output directory.
+///
+/// # Errors
+/// Fails when the chronicle cannot be written.
+pub fn write_chronicle(state: &GameState) -> anyhow::Result<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let filename = format!("chronicles/{secs}.txt");
+    write_to_file(&filename, &chronicle(state))?;
+    Ok(output_path(&filename))
+}
@@ -0,0 +1,218 @@
+use crate::ast::PlayerType;
+use crate::game::map::GameMap;
+use crate::game::state::GameState;
+use serde::{Deserialize, Serialize};
+
+/// Distance in tiles a city sees around it
+pub const CITY_SIGHT: i32 = 10;
+/// Distance in tiles an army in transit sees around it
+pub const ARMY_SIGHT: i32 = 4;
+/// Distance in tiles revealed around a rival city by a spy that was not caught
+pub const SCOUT_SIGHT: i32 = 6;
+/// Turns an enemy army is remembered where it was last seen once out of sight
+pub const SIGHTING_MEMORY: i32 = 5;
+
+/// Enemy army as a civilization last saw it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sighting {
+    pub attacker: usize,
+    pub defender: usize,
+    pub amount: u32,
+    /// Tile the army stood on
+    pub tile: (i32, i32),
+    /// Turn it was last seen
+    pub turn: i32,
+}
+
+impl Sighting {
+    /// Turns since the army was last seen, at `turn`.
+    pub fn age(&self, turn: i32) -> i32 {
+        turn - self.turn
+    }
+}
+
+/// Tiles explored by each civilization when the fog of war is on. A tile stays explored once
+/// seen; what is in sight right now is computed from the cities and armies.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Fog {
+    /// Explored tiles of each civilization, indexed by civilization, row, then column
+    explored: Vec<Vec<Vec<bool>>>,
+    /// Enemy armies each civilization saw lately, indexed by civilization
+    #[serde(default)]
+    sightings: Vec<Vec<Sighting>>,
+}
+
+impl Fog {
+    /// Remember the armies `seen` by `civ_index` at `turn`. Sightings of the same armies are
+    /// replaced, and those older than `SIGHTING_MEMORY` turns are forgotten.
+    fn spot(&mut self, civ_index: usize, seen: Vec<Sighting>, turn: i32) {
+        if self.sightings.len() <= civ_index {
+            self.sightings.resize(civ_index + 1, Vec::new());
+        }
+        let memory = &mut self.sightings[civ_index];
+        memory.retain(|old| {
+            old.age(turn) <= SIGHTING_MEMORY
+                && !seen
+                    .iter()
+                    .any(|s| (s.attacker, s.defender) == (old.attacker, old.defender))
+        });
+        memory.extend(seen);
+    }
+
+    fn reveal(&mut self, civ_index: usize, (cx, cy): (i32, i32), radius: i32, map: &GameMap) {
+        let (width, height) = (map.width, map.height);
+        if self.explored.len() <= civ_index {
+            self.explored.resize(civ_index + 1, Vec::new());
+        }
+        let grid = &mut self.explored[civ_index];
+        if grid.len() != height {
+            *grid = vec![vec![false; width]; height];
+        }
+        for y in (cy - radius).max(0)..=cy + radius {
+            for x in cx - radius..=cx + radius {
+                if (x - cx).pow(2) + (y - cy).pow(2) > radius * radius {
+                    continue;
+                }
+                let x = map.wrap_x(x);
+                if let Some(tile) = grid
+                    .get_mut(y.cast_unsigned() as usize)
+                    .and_then(|l| l.get_mut(x.cast_unsigned() as usize))
+                {
+                    *tile = true;
+                }
+            }
+        }
+    }
+}
+
+/// Centers and radiuses of what `civ_index` sees right now: its city and its armies in transit.
+pub fn sights(state: &GameState, civ_index: usize) -> Vec<((i32, i32), i32)> {
+    let Ok(civ) = state.civ(civ_index) else {
+        return Vec::new();
+    };
+    let mut sights = vec![(
+        (civ.city.x.cast_signed(), civ.city.y.cast_signed()),
+        CITY_SIGHT,
+    )];
+    sights.extend(
+        state
+            .travels
+            .iter()
+            .filter(|t| t.attacker == civ_index && !t.path.is_empty())
+            .map(|t| (t.tile(), ARMY_SIGHT)),
+    );
+    sights
+}
+
+/// Armies of others than `civ_index` standing on tiles where `visible` holds, as seen this turn.
+fn armies(
+    state: &GameState,
+    civ_index: usize,
+    visible: impl Fn((i32, i32)) -> bool,
+) -> Vec<Sighting> {
+    state
+        .travels
+        .iter()
+        .filter(|t| t.attacker != civ_index && !t.path.is_empty() && visible(t.tile()))
+        .map(|t| Sighting {
+            attacker: t.attacker,
+            defender: t.defender,
+            amount: t.amount,
+            tile: t.tile(),
+            turn: state.turn,
+        })
+        .collect()
+}
+
+/// Mark what every civilization sees as explored and remember the enemy armies in sight. Called
+/// when the game is loaded and at each turn start, once the armies moved.
+pub fn update(state: &mut GameState) {
+    let sights: Vec<_> = (0..state.civilizations.len())
+        .map(|i| sights(state, i))
+        .collect();
+    let seen: Vec<_> = sights
+        .iter()
+        .enumerate()
+        .map(|(i, s)| armies(state, i, |tile| sees(&state.map, s, tile)))
+        .collect();
+    let turn = state.turn;
+    let Some(fog) = &mut state.fog else {
+        return;
+    };
+    for (civ_index, (sights, seen)) in sights.into_iter().zip(seen).enumerate() {
+        fog.spot(civ_index, seen, turn);
+        for (center, radius) in sights {
+            fog.reveal(civ_index, center, radius, &state.map);
+        }
+    }
+}
+
+/// Explore the tiles around the city of `target` for `spy`, after a successful spy mission, and
+/// report the armies standing there.
+pub fn scouted(state: &mut GameState, spy: usize, target: usize) {
+    let Ok(city) = state.civ(target).map(|c| &c.city) else {
+        return;
+    };
+    let sights = [((city.x.cast_signed(), city.y.cast_signed()), SCOUT_SIGHT)];
+    let seen = armies(state, spy, |tile| sees(&state.map, &sights, tile));
+    let turn = state.turn;
+    if let Some(fog) = &mut state.fog {
+        fog.spot(spy, seen, turn);
+        fog.reveal(spy, sights[0].0, SCOUT_SIGHT, &state.map);
+    }
+}
+
+/// Enemy armies `civ_index` knows of. Under the fog of war, those it saw in the last
+/// `SIGHTING_MEMORY` turns, where it last saw them; without it, all of them as they stand.
+pub fn sightings(state: &GameState, civ_index: usize) -> Vec<Sighting> {
+    match &state.fog {
+        Some(fog) => fog
+            .sightings
+            .get(civ_index)
+            .into_iter()
+            .flatten()
+            .filter(|s| s.age(state.turn) <= SIGHTING_MEMORY)
+            .cloned()
+            .collect(),
+        None => armies(state, civ_index, |_| true),
+    }
+}
+
+/// Whether `civ_index` has explored `(x, y)`. Always true without fog of war.
+pub fn explored(state: &GameState, civ_index: usize, (x, y): (i32, i32)) -> bool {
+    let Some(fog) = &state.fog else {
+        return true;
+    };
+    let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+        return false;
+    };
+    fog.explored
+        .get(civ_index)
+        .and_then(|grid| grid.get(y))
+        .and_then(|line| line.get(x))
+        .copied()
+        .unwrap_or(false)
+}
+
+/// Whether `(x, y)` is within one of `sights`, as returned by `sights`, across the edges of a map
+/// that wraps around.
+pub fn sees(map: &GameMap, sights: &[((i32, i32), i32)], (x, y): (i32, i32)) -> bool {
+    sights.iter().any(|((cx, cy), radius)| {
+        let dx = map.offset_x(i64::from(*cx), i64::from(x));
+        dx.pow(2) + i64::from(y - cy).pow(2) <= i64::from(radius * radius)
+    })
+}
+
+/// Civilization whose view the map shows: the player whose turn it is when human, otherwise the
+/// first human player. None without fog of war or human player, and the whole map is shown.
+pub fn viewer(state: &GameState) -> Option<usize> {
+    state.fog.as_ref()?;
+    let human = |i: &usize| {
+        state
+            .civ(*i)
+            .is_ok_and(|c| matches!(c.city.player_type, PlayerType::PLAYER))
+    };
+    Some(state.player_turn)
+        .filter(human)
+        .or_else(|| (0..state.civilizations.len()).find(human))
+}
@@ -0,0 +1,189 @@
+use crate::game::AI_PLAYBACK_DELAYS_MS;
+use crate::game::map::GameMap;
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+
+/// Version of the code layout, bumped on incompatible changes
+const CODE_VERSION: u8 = 1;
+/// RFC 4648 base32 alphabet
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// Characters between two dashes of a printed code
+const GROUP: usize = 4;
+
+/// A setup shared as a short code: the map seed and size, the AI playback speed and the number of
+/// civilizations. The rest of the game comes from the configuration both players load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameCode {
+    pub seed: String,
+    pub width: u16,
+    pub height: u16,
+    /// Index of the AI playback speed in `AI_PLAYBACK_DELAYS_MS`
+    pub speed: u8,
+    pub civilizations: u8,
+}
+
+impl GameCode {
+    /// Code of the running game.
+    pub fn of(state: &GameState) -> Self {
+        Self {
+            seed: state.map.seed.clone(),
+            width: u16::try_from(state.map.width).unwrap_or(u16::MAX),
+            height: u16::try_from(state.map.height).unwrap_or(u16::MAX),
+            speed: u8::try_from(state.ai_speed).unwrap_or(u8::MAX),
+            civilizations: u8::try_from(state.civilizations.len()).unwrap_or(u8::MAX),
+        }
+    }
+
+    /// Printable code, base32 in dash-separated groups, ending with a checksum byte so that
+    /// mistyped codes are refused.
+    pub fn encode(&self) -> String {
+        let seed = &self.seed.as_bytes()[..self.seed.len().min(usize::from(u8::MAX))];
+        let mut bytes = vec![CODE_VERSION];
+        bytes.extend(self.width.to_be_bytes());
+        bytes.extend(self.height.to_be_bytes());
+        bytes.extend([
+            self.speed,
+            self.civilizations,
+            u8::try_from(seed.len()).unwrap_or(u8::MAX),
+        ]);
+        bytes.extend(seed);
+        bytes.push(checksum(&bytes));
+        let text = base32(&bytes);
+        text.as_bytes()
+            .chunks(GROUP)
+            .map(|g| String::from_utf8_lossy(g).into_owned())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Read a code printed by `encode`, regardless of case and dashes.
+    ///
+    /// # Errors
+    /// Fails when the code is malformed, mistyped, or of an unsupported version.
+    pub fn decode(code: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid game code: {code}");
+        let bytes = unbase32(code).ok_or_else(invalid)?;
+        let (checked, sum) = bytes
+            .split_last()
+            .map(|(s, b)| (b, *s))
+            .ok_or_else(invalid)?;
+        if checksum(checked) != sum {
+            return Err(anyhow!("Invalid game code: {code} (mistyped?)"));
+        }
+        let [
+            version,
+            width_hi,
+            width_lo,
+            height_hi,
+            height_lo,
+            speed,
+            civilizations,
+            len,
+            name @ ..,
+        ] = checked
+        else {
+            return Err(invalid());
+        };
+        if *version != CODE_VERSION {
+            return Err(anyhow!("Game code version {version} is not supported"));
+        }
+        if name.len() != usize::from(*len) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            seed: String::from_utf8(name.to_vec()).map_err(|_| invalid())?,
+            width: u16::from_be_bytes([*width_hi, *width_lo]),
+            height: u16::from_be_bytes([*height_hi, *height_lo]),
+            speed: *speed,
+            civilizations: *civilizations,
+        })
+    }
+
+    /// Set up the map and AI speed of the code on the game loaded from the configuration, which
+    /// must have as many civilizations, all fitting on the map.
+    ///
+    /// # Errors
+    /// Fails when the code is for another number of civilizations, or its map is empty or leaves a
+    /// city outside.
+    pub fn apply(&self, state: &mut GameState) -> Result<()> {
+        if usize::from(self.civilizations) != state.civilizations.len() {
+            return Err(anyhow!(
+                "The game code is for {} civilizations, the configuration has {}",
+                self.civilizations,
+                state.civilizations.len()
+            ));
+        }
+        let (width, height) = (usize::from(self.width), usize::from(self.height));
+        if width == 0 || height == 0 {
+            return Err(anyhow!("The game code has an empty {width}x{height} map"));
+        }
+        if let Some(civ) = state
+            .civilizations
+            .iter()
+            .find(|c| c.city.x as usize >= width || c.city.y as usize >= height)
+        {
+            return Err(anyhow!(
+                "{} is outside the {width}x{height} map of the game code",
+                civ.city.name
+            ));
+        }
+        state.map = GameMap::generate(
+            self.seed.clone(),
+            width,
+            height,
+            state.map.biomes,
+            state.map.shape,
+            state.map.wrap,
+        );
+        state.map_buffer_cache = None;
+        state.ai_speed = usize::from(self.speed).min(AI_PLAYBACK_DELAYS_MS.len() - 1);
+        log::info!(
+            "Game code applied: seed '{}', {width}x{height} map",
+            self.seed
+        );
+        Ok(())
+    }
+}
+
+/// Rolling checksum of the bytes, catching swapped characters as well as wrong ones.
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0u8, |sum, b| sum.wrapping_mul(31).wrapping_add(*b))
+}
+
+/// Base32 text of `bytes`, without padding.
+fn base32(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in bytes {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            text.push(char::from(ALPHABET[(buffer >> bits) as usize & 31]));
+        }
+    }
+    if bits > 0 {
+        text.push(char::from(ALPHABET[(buffer << (5 - bits)) as usize & 31]));
+    }
+    text
+}
+
+/// Bytes of a base32 text, dashes and spaces skipped. None on a character outside the alphabet.
+fn unbase32(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.chars().filter(|c| *c != '-' && !c.is_whitespace()) {
+        let value = ALPHABET
+            .iter()
+            .position(|a| char::from(*a) == c.to_ascii_uppercase())?;
+        buffer = (buffer << 5) | u32::try_from(value).ok()?;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push(u8::try_from((buffer >> bits) & 0xFF).ok()?);
+        }
+    }
+    Some(bytes)
+}
@@ -0,0 +1,150 @@
+use crate::game::scoring;
+use crate::game::state::GameState;
+use crate::game::utils::{output_path, write_to_file};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HISTORY_FILE: &str = "history.json";
+
+/// A civilization of a finished game, as stored in the history.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Participant {
+    pub name: String,
+    /// `PLAYER` or `AI`
+    pub player_type: String,
+    pub score: i32,
+}
+
+/// Summary of a finished game, as stored in `history.json` of the output directory.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameRecord {
+    /// End of the game, in seconds since the Unix epoch
+    pub finished_at: u64,
+    pub seed: String,
+    /// Hash of the configuration the game was loaded from, grouping games of the same scenario
+    pub config_hash: String,
+    pub participants: Vec<Participant>,
+    /// Name of the winning civilization, if any
+    pub winner: Option<String>,
+    pub turns: i32,
+}
+
+/// Score of a civilization: its base score (see `scoring::base_score`), plus a bonus for
+/// surviving and winning. Shared by the history and the daily challenge leaderboard.
+pub fn score(state: &GameState, civ_index: usize) -> i32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    let mut score = scoring::base_score(state, civ_index);
+    if civ.alive {
+        score = score.saturating_add(100);
+    }
+    if state.winner() == Some(civ_index) {
+        score = score.saturating_add(500);
+    }
+    score
+}
+
+/// Games recorded so far, oldest first. Missing or invalid files give an empty history.
+pub fn load() -> Vec<GameRecord> {
+    std::fs::read_to_string(output_path(HISTORY_FILE))
+        .ok()
+        .and_then(|s| match serde_json::from_str(&s) {
+            Ok(records) => Some(records),
+            Err(e) => {
+                log::warn!("Ignoring invalid history file: {e}");
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// Append the summary of a finished game to the history.
+///
+/// # Errors
+/// Fails when the history cannot be written.
+pub fn record(state: &GameState) -> anyhow::Result<GameRecord> {
+    let entry = GameRecord {
+        finished_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        seed: state.map.seed.clone(),
+        config_hash: format!("{:08x}", state.config_hash),
+        participants: state
+            .civilizations
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Participant {
+                name: c.city.name.clone(),
+                player_type: format!("{:?}", c.city.player_type),
+                score: score(state, i),
+            })
+            .collect(),
+        winner: state.winner().map(|i| state.city_name(i).to_string()),
+        turns: state.turn,
+    };
+    let mut records = load();
+    records.push(entry.clone());
+    write_to_file(HISTORY_FILE, &serde_json::to_string_pretty(&records)?)?;
+    log::info!("Game recorded in history (config {})", entry.config_hash);
+    Ok(entry)
+}
+
+/// Text report for the `history` subcommand: the last `limit` games and the win rate of every
+/// civilization per configuration, optionally restricted to one configuration hash.
+pub fn report(records: &[GameRecord], config_hash: Option<&str>, limit: usize) -> String {
+    let records: Vec<&GameRecord> = records
+        .iter()
+        .filter(|r| config_hash.is_none_or(|h| r.config_hash == h))
+        .collect();
+    if records.is_empty() {
+        return "No recorded games.".to_string();
+    }
+
+    let mut lines = vec![format!(
+        "Last {} of {} games:",
+        limit.min(records.len()),
+        records.len()
+    )];
+    for r in records.iter().rev().take(limit) {
+        let participants: Vec<String> = r
+            .participants
+            .iter()
+            .map(|p| format!("{} ({}, score {})", p.name, p.player_type, p.score))
+            .collect();
+        lines.push(format!(
+            "  config {} seed '{}': {} turns, winner {} - {}",
+            r.config_hash,
+            r.seed,
+            r.turns,
+            r.winner.as_deref().unwrap_or("none"),
+            participants.join(", ")
+        ));
+    }
+
+    // games and wins per civilization, grouped by configuration
+    let mut stats: BTreeMap<&str, BTreeMap<&str, (u32, u32)>> = BTreeMap::new();
+    for r in &records {
+        let config = stats.entry(r.config_hash.as_str()).or_default();
+        for p in &r.participants {
+            let entry = config.entry(p.name.as_str()).or_default();
+            entry.0 += 1;
+            if r.winner.as_deref() == Some(p.name.as_str()) {
+                entry.1 += 1;
+            }
+        }
+    }
+    lines.push(String::new());
+    lines.push("Win rates per configuration:".to_string());
+    for (config, civs) in stats {
+        lines.push(format!("  config {config}:"));
+        for (name, (games, wins)) in civs {
+            lines.push(format!(
+                "    {name}: {wins}/{games} ({:.0}%)",
+                f64::from(wins) * 100.0 / f64::from(games)
+            ));
+        }
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,111 @@
+use crate::ast::ProductionType;
+use crate::game::capture;
+use crate::game::diplomacy::{TRADE_INCOME, Treaty};
+use crate::game::limits;
+use crate::game::loans::Loan;
+use crate::game::state::GameState;
+
+/// One source of the resource change of a turn start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncomeLine {
+    pub source: String,
+    pub amount: i32,
+}
+
+/// Resource change at the next turn start of `civ_index`, source by source: each producing
+/// building, trade routes, loan installments, upkeep and whatever else the turn start does. The turn
+/// start is played on a copy of the state, so the lines add up to what really happens.
+pub fn breakdown(state: &GameState, civ_index: usize) -> Vec<IncomeLine> {
+    let Ok(civ) = state.civ(civ_index) else {
+        return Vec::new();
+    };
+    let mut probe = state.clone();
+    probe.on_turn_start(civ_index);
+    let Ok(after) = probe.civ(civ_index) else {
+        return Vec::new();
+    };
+    // in i64, the lines being saturated to i32, since the DSL allows large productions
+    let change = i64::from(after.resources.ressources) - i64::from(civ.resources.ressources);
+    let (before, totals) = (
+        state.metrics.totals(civ_index),
+        probe.metrics.totals(civ_index),
+    );
+    let gathered = i64::from(totals.gathered) - i64::from(before.gathered);
+    let upkeep = i64::from(totals.upkeep) - i64::from(before.upkeep);
+    let wasted = i64::from(totals.wasted) - i64::from(before.wasted);
+
+    let mut lines = Vec::new();
+    let mut line = |source: String, amount: i64| {
+        if amount != 0 {
+            let amount = limits::saturate(amount);
+            lines.push(IncomeLine { source, amount });
+        }
+    };
+    let mut produced = 0;
+    for building in civ
+        .city
+        .buildings
+        .elements
+        .iter()
+        .filter(|b| !civ.disabled.contains(&b.id_building))
+    {
+        let Some(def) = state.building_def(&building.id_building) else {
+            continue;
+        };
+        if matches!(def.production.prod_type, ProductionType::RESSOURCE) {
+            produced += i64::from(def.production.amount);
+            line(
+                building.id_building.clone(),
+                i64::from(def.production.amount),
+            );
+        }
+    }
+    let trade: i64 = state
+        .diplomacy
+        .treaties_of(civ_index)
+        .filter(|(t, p)| *t == Treaty::Trade && state.civ(*p).is_ok_and(|c| c.alive))
+        .map(|_| i64::from(TRADE_INCOME))
+        .sum();
+    let captured = i64::from(capture::tribute(state, civ_index));
+    line(
+        format!("captured cities ({})", civ.captured.len()),
+        captured,
+    );
+    line(
+        "terrain, policy, traits, season and difficulty".to_string(),
+        gathered - trade - produced - captured,
+    );
+    line(
+        format!("trade routes ({})", trade / i64::from(TRADE_INCOME)),
+        trade,
+    );
+    let owed = |loans: &[Loan]| -> i64 { loans.iter().map(|l| i64::from(l.owed)).sum() };
+    let paid = owed(&civ.loans) - owed(&after.loans);
+    line("loan installments".to_string(), -paid);
+    line("upkeep and maintenance".to_string(), -upkeep);
+    line("storage overflow".to_string(), -wasted);
+    line(
+        "other (events, spoils...)".to_string(),
+        change - gathered + paid + upkeep + wasted,
+    );
+    lines
+}
+
+/// Text of the `income` popup: the breakdown, then the total.
+pub fn report(state: &GameState, civ_index: usize) -> String {
+    let lines = breakdown(state, civ_index);
+    let total: i64 = lines.iter().map(|l| i64::from(l.amount)).sum();
+    let resources = state.civ(civ_index).map_or(0, |c| c.resources.ressources);
+    let mut text: Vec<String> = lines
+        .iter()
+        .map(|l| format!("{:+5}  {}", l.amount, l.source))
+        .collect();
+    if text.is_empty() {
+        text.push("No income".to_string());
+    }
+    text.push(format!(
+        "Next turn: {total:+} resources ({resources} -> {})",
+        limits::saturate(i64::from(resources) + total)
+    ));
+    text.join("\n")
+}
@@ -0,0 +1,152 @@
+use crate::ast::{ProductionType, UnitInstance};
+use crate::game::composition;
+use crate::game::map::GameMap;
+use crate::game::state::GameState;
+use crate::game::turn_rules::projected_income;
+
+/// Influence every city projects before counting its buildings
+pub const BASE_INFLUENCE: i32 = 10;
+/// Distance in tiles beyond which a city projects no influence
+pub const INFLUENCE_RADIUS: f64 = 40.0;
+/// Highest share of the income lost to foreign pressure, in percent
+pub const MAX_PRODUCTION_PENALTY: i32 = 50;
+/// Share of the units defecting when foreign influence is twice the city's own, in percent
+pub const DEFECTION_PERCENT: u32 = 10;
+
+/// Influence a civilization projects from its city: the base value plus its `INFLUENCE` buildings.
+pub fn strength(state: &GameState, civ_index: usize) -> i32 {
+    let Some(civ) = state.civ(civ_index).ok().filter(|c| c.alive) else {
+        return 0;
+    };
+    civ.city
+        .buildings
+        .elements
+        .iter()
+        .filter_map(|b| state.building_def(&b.id_building))
+        .filter(|d| matches!(d.production.prod_type, ProductionType::INFLUENCE))
+        .fold(BASE_INFLUENCE, |strength, d| {
+            strength.saturating_add(d.production.amount.cast_signed())
+        })
+}
+
+/// Influence of `civ_index` on the tile at `(x, y)`, fading linearly with the distance to its city.
+pub fn projected(state: &GameState, civ_index: usize, x: u32, y: u32) -> i32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    let city = &civ.city;
+    fade(
+        &state.map,
+        strength(state, civ_index),
+        (city.x, city.y),
+        x,
+        y,
+    )
+}
+
+/// Influence `strength` projected from a city standing at `city` onto the tile at `(x, y)`, across
+/// the edges of a map that wraps around.
+pub fn fade(map: &GameMap, strength: i32, city: (u32, u32), x: u32, y: u32) -> i32 {
+    let dx = map.offset_x(i64::from(city.0), i64::from(x));
+    let dx = f64::from(i32::try_from(dx).unwrap_or(i32::MAX));
+    let distance = dx.hypot(f64::from(city.1) - f64::from(y));
+    if distance >= INFLUENCE_RADIUS {
+        return 0;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let projected = (f64::from(strength) * (1.0 - distance / INFLUENCE_RADIUS)) as i32;
+    projected
+}
+
+/// Own influence of a city and the foreign influence rivals project onto it.
+pub fn pressure(state: &GameState, civ_index: usize) -> (i32, i32) {
+    let Ok(civ) = state.civ(civ_index) else {
+        return (0, 0);
+    };
+    let city = &civ.city;
+    let foreign = (0..state.civilizations.len())
+        .filter(|i| *i != civ_index)
+        .map(|i| projected(state, i, city.x, city.y))
+        .fold(0, i32::saturating_add);
+    (strength(state, civ_index), foreign)
+}
+
+/// Apply foreign pressure at the turn start of `civ_index`: part of the income is lost when rivals
+/// out-influence the city, and units defect to the most influent rival when they double it.
+/// Returns a report of what happened, if anything.
+pub fn apply_pressure(state: &mut GameState, civ_index: usize) -> Option<String> {
+    let (own, foreign) = pressure(state, civ_index);
+    if foreign <= own || !state.civ(civ_index).is_ok_and(|c| c.alive) {
+        return None;
+    }
+    let penalty = ((i64::from(foreign) - i64::from(own)) * 100 / i64::from(own.max(1)))
+        .min(i64::from(MAX_PRODUCTION_PENALTY));
+    let lost = (i64::from(projected_income(state, civ_index)) * penalty / 100)
+        .min(i64::from(
+            state.civ(civ_index).map_or(0, |c| c.resources.ressources),
+        ))
+        .max(0);
+    let lost = i32::try_from(lost).unwrap_or(i32::MAX);
+    state.civ_mut(civ_index).ok()?.earn(-lost);
+    let name = state.city_name(civ_index).to_string();
+    let mut report =
+        format!("Foreign influence on {name} ({foreign} vs {own}): {lost} resources lost");
+
+    let city = &state.civ(civ_index).ok()?.city;
+    let rival = (0..state.civilizations.len())
+        .filter(|i| *i != civ_index)
+        .max_by_key(|i| projected(state, *i, city.x, city.y));
+    if i64::from(foreign) >= 2 * i64::from(own)
+        && let Some(rival) = rival
+    {
+        let defected = defect_units(state, civ_index, rival);
+        if defected > 0 {
+            report = format!(
+                "{report}, {defected} units defected to {}",
+                state.city_name(rival)
+            );
+        }
+    }
+    Some(report)
+}
+
+/// Move `DEFECTION_PERCENT` of the units of `from` (at least one) to the city of `to`.
+fn defect_units(state: &mut GameState, from: usize, to: usize) -> u32 {
+    if state.civ(to).is_err() {
+        return 0;
+    }
+    let Ok(giver) = state.civ_mut(from) else {
+        return 0;
+    };
+    let units = &mut giver.city.units.units;
+    let total = u64::from(composition::total(units.iter()));
+    let mut to_move =
+        u32::try_from((total * u64::from(DEFECTION_PERCENT)).div_ceil(100)).unwrap_or(u32::MAX);
+    let mut moved: Vec<UnitInstance> = Vec::new();
+    for unit in units.iter_mut() {
+        let n = unit.nb_units.min(to_move);
+        if n > 0 {
+            unit.nb_units -= n;
+            to_move -= n;
+            moved.push(UnitInstance {
+                id_units: unit.id_units.clone(),
+                nb_units: n,
+            });
+        }
+    }
+    units.retain(|u| u.nb_units > 0);
+
+    let Ok(receiver) = state.civ_mut(to).map(|c| &mut c.city.units.units) else {
+        return 0;
+    };
+    let mut count: u32 = 0;
+    for unit in moved {
+        count = count.saturating_add(unit.nb_units);
+        if let Some(existing) = receiver.iter_mut().find(|u| u.id_units == unit.id_units) {
+            existing.nb_units = existing.nb_units.saturating_add(unit.nb_units);
+        } else {
+            receiver.push(unit);
+        }
+    }
+    count
+}
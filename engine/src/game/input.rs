@@ -0,0 +1,307 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How long a chord waits for its next key before the keys typed so far run their own binding
+pub const CHORD_TIMEOUT: Duration = Duration::from_millis(800);
+/// Keys held at most while another player has the turn, those typed after them are dropped
+pub const HELD_KEYS: usize = 32;
+
+/// What a keyboard shortcut does, run by `Game::run_command`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    EditSeed,
+    RandomSeed,
+    ToggleCamera,
+    MoveCamera(i32, i32),
+    NextRegion,
+    GotoCity,
+    HighContrast,
+    ReducedMotion,
+    LargePopups,
+    Graphs,
+    DismissChanges,
+    Difficulty,
+    Events,
+    ActionsPanel,
+    TextMode,
+    Scroll(i16),
+    ActionMenu,
+    TypeAction,
+    Zoom,
+    Photo,
+    WriteMap,
+    ClosePopup,
+    EndScreen,
+}
+
+/// A key along with the modifiers held. Shift is left out of characters, their case tells it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub const fn plain(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    pub fn of(event: KeyEvent) -> Self {
+        let mut modifiers =
+            event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(event.code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(c) => write!(f, "{c}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Keys typed one after the other to run `command`.
+pub struct Binding {
+    pub keys: &'static [Key],
+    pub command: Command,
+    /// What the binding does, listed by the pending-keys indicator
+    pub help: &'static str,
+}
+
+const fn key(c: char) -> Key {
+    Key::plain(KeyCode::Char(c))
+}
+
+const fn bind(keys: &'static [Key], command: Command, help: &'static str) -> Binding {
+    Binding {
+        keys,
+        command,
+        help,
+    }
+}
+
+/// Shortcuts of the map screen. `g` starts the chords moving the view around, and on its own
+/// still toggles the graphs panel once its chord times out.
+pub const NORMAL: &[Binding] = &[
+    bind(&[key('s')], Command::EditSeed, "modifier la seed"),
+    bind(&[key('r')], Command::RandomSeed, "seed aléatoire"),
+    bind(&[key('v')], Command::ToggleCamera, "mode caméra"),
+    bind(&[key('V')], Command::ToggleCamera, "mode caméra"),
+    bind(&[key('h')], Command::HighContrast, "contraste élevé"),
+    bind(&[key('f')], Command::ReducedMotion, "animations réduites"),
+    bind(&[key('p')], Command::LargePopups, "grandes fenêtres"),
+    bind(&[key('g')], Command::Graphs, "graphiques"),
+    bind(&[Key::ctrl('g')], Command::Graphs, "graphiques"),
+    bind(&[key('g'), key('c')], Command::GotoCity, "aller à la ville"),
+    bind(
+        &[key('g'), key('r')],
+        Command::NextRegion,
+        "région suivante",
+    ),
+    bind(
+        &[key('c')],
+        Command::DismissChanges,
+        "fermer le rechargement",
+    ),
+    bind(&[key('i')], Command::Difficulty, "difficulté"),
+    bind(&[key('e')], Command::Events, "journal"),
+    bind(&[key('l')], Command::ActionsPanel, "actions possibles"),
+    bind(&[key('t')], Command::TextMode, "description textuelle"),
+    bind(&[Key::plain(KeyCode::Up)], Command::Scroll(-1), "défiler"),
+    bind(&[Key::plain(KeyCode::Down)], Command::Scroll(1), "défiler"),
+    bind(&[key('m')], Command::ActionMenu, "menu des actions"),
+    bind(&[key('a')], Command::TypeAction, "saisir une action"),
+    bind(&[key('z')], Command::Zoom, "zoom"),
+    bind(&[key('Z')], Command::Zoom, "zoom"),
+    bind(&[key('o')], Command::Photo, "photo"),
+    bind(&[key('w')], Command::WriteMap, "exporter la carte"),
+];
+
+/// Shortcuts of the camera mode, moving with z, q, s and d.
+pub const CAMERA: &[Binding] = &[
+    bind(&[key('v')], Command::ToggleCamera, "quitter la caméra"),
+    bind(&[key('V')], Command::ToggleCamera, "quitter la caméra"),
+    bind(
+        &[Key::plain(KeyCode::Esc)],
+        Command::ToggleCamera,
+        "quitter la caméra",
+    ),
+    bind(&[key('z')], Command::MoveCamera(0, -1), "haut"),
+    bind(&[key('Z')], Command::MoveCamera(0, -1), "haut"),
+    bind(&[key('s')], Command::MoveCamera(0, 1), "bas"),
+    bind(&[key('S')], Command::MoveCamera(0, 1), "bas"),
+    bind(&[key('q')], Command::MoveCamera(-1, 0), "gauche"),
+    bind(&[key('Q')], Command::MoveCamera(-1, 0), "gauche"),
+    bind(&[key('d')], Command::MoveCamera(1, 0), "droite"),
+    bind(&[key('D')], Command::MoveCamera(1, 0), "droite"),
+    bind(
+        &[Key::plain(KeyCode::Tab)],
+        Command::NextRegion,
+        "région suivante",
+    ),
+];
+
+/// Shortcuts left once the game is over: looking around and the end-of-game statistics.
+pub const GAME_OVER: &[Binding] = &[
+    bind(&[key('z')], Command::Zoom, "zoom"),
+    bind(&[key('Z')], Command::Zoom, "zoom"),
+    bind(&[key('v')], Command::ToggleCamera, "mode caméra"),
+    bind(&[key('V')], Command::ToggleCamera, "mode caméra"),
+    bind(&[key('o')], Command::Photo, "photo"),
+    bind(&[Key::plain(KeyCode::Enter)], Command::ClosePopup, "fermer"),
+    bind(&[Key::plain(KeyCode::Esc)], Command::ClosePopup, "fermer"),
+    bind(&[key('t')], Command::EndScreen, "statistiques"),
+];
+
+/// Turns key presses into commands, holding the first keys of a chord until it is complete,
+/// broken off by a key continuing no binding, or timed out.
+pub struct InputPipeline {
+    bindings: &'static [Binding],
+    pending: Vec<Key>,
+    since: Option<Instant>,
+}
+
+impl Default for InputPipeline {
+    fn default() -> Self {
+        Self {
+            bindings: NORMAL,
+            pending: Vec::new(),
+            since: None,
+        }
+    }
+}
+
+impl InputPipeline {
+    /// Commands to run for `key` pressed at `now` under `bindings`, in order: none while it
+    /// starts or continues a chord, the binding of the pending keys then that of `key` when
+    /// it breaks a chord off.
+    pub fn feed(&mut self, bindings: &'static [Binding], key: Key, now: Instant) -> Vec<Command> {
+        if !std::ptr::eq(self.bindings, bindings) {
+            self.pending.clear();
+            self.bindings = bindings;
+        }
+        self.pending.push(key);
+        if self
+            .bindings
+            .iter()
+            .any(|b| b.keys.len() > self.pending.len() && b.keys.starts_with(&self.pending))
+        {
+            self.since = Some(now);
+            return Vec::new();
+        }
+        if self.exact().is_some() {
+            return self.flush().into_iter().collect();
+        }
+        // the chord broke off: its keys so far run their own binding, then `key` starts over
+        self.pending.pop();
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+        let mut commands: Vec<Command> = self.flush().into_iter().collect();
+        commands.extend(self.feed(bindings, key, now));
+        commands
+    }
+
+    /// Command of a chord left waiting longer than `CHORD_TIMEOUT` at `now`: that of its keys.
+    pub fn expire(&mut self, now: Instant) -> Option<Command> {
+        if self
+            .since
+            .is_some_and(|since| now.duration_since(since) >= CHORD_TIMEOUT)
+        {
+            self.flush()
+        } else {
+            None
+        }
+    }
+
+    /// Pending keys followed by the keys continuing them, for the status bar. None when no
+    /// chord is pending.
+    pub fn hint(&self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let typed: Vec<String> = self.pending.iter().map(ToString::to_string).collect();
+        let next: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|b| b.keys.len() > self.pending.len() && b.keys.starts_with(&self.pending))
+            .map(|b| format!("{}: {}", b.keys[self.pending.len()], b.help))
+            .collect();
+        Some(format!("{} … {}", typed.join(" "), next.join(", ")))
+    }
+
+    fn exact(&self) -> Option<Command> {
+        self.bindings
+            .iter()
+            .find(|b| b.keys == self.pending.as_slice())
+            .map(|b| b.command)
+    }
+
+    fn flush(&mut self) -> Option<Command> {
+        let command = self.exact();
+        self.pending.clear();
+        self.since = None;
+        command
+    }
+}
+
+/// Keys pressed while an AI has the turn, held until a human gets it back rather than lost.
+#[derive(Default)]
+pub struct KeyQueue {
+    keys: Vec<KeyEvent>,
+    dropped: usize,
+}
+
+impl KeyQueue {
+    /// Hold `key`, or drop it once `HELD_KEYS` keys are held.
+    pub fn hold(&mut self, key: KeyEvent) {
+        if self.keys.len() < HELD_KEYS {
+            self.keys.push(key);
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    /// Number of keys held.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.dropped == 0
+    }
+
+    /// Held keys in the order they were pressed, along with the number of keys dropped, leaving
+    /// the queue empty.
+    pub fn release(&mut self) -> (Vec<KeyEvent>, usize) {
+        (
+            std::mem::take(&mut self.keys),
+            std::mem::take(&mut self.dropped),
+        )
+    }
+}
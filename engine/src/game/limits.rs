@@ -0,0 +1,76 @@
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+
+/// Largest width or height of the map, in tiles
+pub const MAX_MAP_SIDE: usize = 1024;
+/// Largest number accepted anywhere in a configuration (costs, amounts, coordinates, turns...)
+pub const MAX_NUMBER: u64 = 1_000_000;
+/// Fields exempt from `MAX_NUMBER`: seeds only feed random generators, never sums, and take any
+/// value of their type
+const SEED_FIELDS: [&str; 1] = ["ai_seed"];
+
+/// Reject numbers the engine cannot play with: negative, fractional, or above `MAX_NUMBER`, where
+/// sums and conversions to signed amounts would overflow. Seeds are left to the parsing of the
+/// configuration, which checks them against their type.
+///
+/// # Errors
+/// Fails on the first number out of range, naming its path.
+pub fn check_numbers(value: &Value, path: &str) -> Result<()> {
+    match value {
+        Value::Number(n) if n.as_u64().is_none_or(|n| n > MAX_NUMBER) => Err(anyhow!(
+            "invalid number {n} at {path}: expected an integer between 0 and {MAX_NUMBER}"
+        )),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, item)| check_numbers(item, &format!("{path}[{i}]"))),
+        Value::Object(fields) => fields
+            .iter()
+            .filter(|(key, _)| !SEED_FIELDS.contains(&key.as_str()))
+            .try_for_each(|(key, field)| check_numbers(field, &format!("{path}.{key}"))),
+        _ => Ok(()),
+    }
+}
+
+/// `value` clamped to the range of i32, for amounts computed in i64 from large accepted numbers.
+pub fn saturate(value: i64) -> i32 {
+    i32::try_from(value).unwrap_or(if value < 0 { i32::MIN } else { i32::MAX })
+}
+
+/// Check the map size before generating the map, so absurd sizes do not exhaust the memory.
+///
+/// # Errors
+/// Fails when a side is empty or over `MAX_MAP_SIDE`.
+pub fn check_map_size(width: usize, height: usize) -> Result<()> {
+    if !(1..=MAX_MAP_SIDE).contains(&width) || !(1..=MAX_MAP_SIDE).contains(&height) {
+        return Err(anyhow!(
+            "invalid map size {width}x{height}: both sides must be between 1 and {MAX_MAP_SIDE}"
+        ));
+    }
+    Ok(())
+}
+
+/// Check a loaded game has cities, all of them on the map.
+///
+/// # Errors
+/// Fails when there is no city or one lies outside the map.
+pub fn check_cities(state: &GameState) -> Result<()> {
+    if state.civilizations.is_empty() {
+        return Err(anyhow!("the configuration has no city"));
+    }
+    for civ in &state.civilizations {
+        let city = &civ.city;
+        if city.x as usize >= state.map.width || city.y as usize >= state.map.height {
+            return Err(anyhow!(
+                "city {} at ({}, {}) is outside the {}x{} map",
+                city.name,
+                city.x,
+                city.y,
+                state.map.width,
+                state.map.height
+            ));
+        }
+    }
+    Ok(())
+}
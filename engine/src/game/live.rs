@@ -0,0 +1,296 @@
+use crate::ast::{Model, Section};
+use crate::game::Game;
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::sync::mpsc::{self, Receiver};
+
+/// A line pushed by the DSL editor in `--watch-stdin` mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveCommand {
+    /// Whole configuration, replacing the running game
+    Config(Value),
+    /// `{"patch": {...}}`: JSON merge patch (RFC 7386) applied to the current configuration
+    Patch(Value),
+}
+
+impl LiveCommand {
+    /// Read a line pushed by the editor: a patch, or a whole document.
+    ///
+    /// # Errors
+    /// Fails when the line is not a JSON object.
+    pub fn parse(line: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(line).context("invalid JSON on stdin")?;
+        match value {
+            Value::Object(mut map) if map.len() == 1 && map.contains_key("patch") => {
+                Ok(Self::Patch(map.remove("patch").unwrap_or_default()))
+            }
+            Value::Object(_) => Ok(Self::Config(value)),
+            _ => Err(anyhow!(
+                "expected a configuration or a {{\"patch\": ...}} object"
+            )),
+        }
+    }
+}
+
+/// Apply a JSON merge patch: objects are merged key by key, `null` removes a key, anything else
+/// replaces the target.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Read stdin line by line on a background thread, so the UI loop can poll for new documents
+/// without blocking. Keys keep coming from the terminal, which crossterm opens directly when
+/// stdin is a pipe.
+pub fn watch_stdin() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if !line.trim().is_empty() && sender.send(line).is_err() {
+                break;
+            }
+        }
+        log::info!("Stdin closed, live link stopped");
+    });
+    receiver
+}
+
+/// Rebuild the game from a pushed line. The running game is left untouched when the document
+/// does not load, so the editor can push documents while they are being typed. AIs are not
+/// carried over since the civilizations may have changed; the caller registers them again.
+/// Custom victory conditions are kept.
+///
+/// # Errors
+/// Fails when the line is invalid, there is no configuration to patch, or the document does not
+/// load.
+pub fn reload(game: &mut Game, line: &str) -> Result<()> {
+    let config = match LiveCommand::parse(line)? {
+        LiveCommand::Config(config) => config,
+        LiveCommand::Patch(patch) => {
+            let source = game
+                .config_source
+                .as_deref()
+                .ok_or_else(|| anyhow!("no configuration loaded to patch"))?;
+            let mut config: Value = serde_json::from_str(source)?;
+            merge_patch(&mut config, &patch);
+            config
+        }
+    };
+    let mut reloaded = Game::from_string(&config.to_string())?;
+    if game.state.text_mode {
+        reloaded.state.toggle_text_mode();
+    }
+    reloaded.state.custom_victories = game.state.custom_victories.clone();
+    reloaded.set_preferences(game.ui_config.preferences.clone());
+    if let Some(difficulty) = &game.state.difficulty {
+        reloaded.enable_dynamic_difficulty(difficulty.interval);
+    }
+    let previous = game
+        .config_source
+        .as_deref()
+        .and_then(|source| serde_json::from_str::<Model>(source).ok());
+    if let Some(previous) = previous {
+        let model: Model = serde_json::from_value(config)?;
+        reloaded.state.config_changes = config_diff(&previous, &model);
+        if reloaded.state.config_changes.is_empty() {
+            reloaded
+                .state
+                .config_changes
+                .push("Aucune définition modifiée".to_string());
+        }
+    }
+    // a replay only holds one configuration: close it and start a new one
+    let recording = game.replay.is_some();
+    game.finish_recording()?;
+    *game = reloaded;
+    if recording {
+        game.start_recording()?;
+    }
+    log::info!("Configuration reloaded from stdin");
+    Ok(())
+}
+
+/// Name of a section in the summary of the changes.
+fn section_name(section: &Section) -> &'static str {
+    match section {
+        Section::BuildingDefArray(_) => "buildings",
+        Section::Cities(_) => "cities",
+        Section::Game(_) => "game",
+        Section::MapEvents(_) => "map_events",
+        Section::Puzzle(_) => "puzzle",
+        Section::Raiders(_) => "raiders",
+        Section::Relations(_) => "diplomacy",
+        Section::Regions(_) => "regions",
+        Section::Scoring(_) => "scoring",
+        Section::Surrender(_) => "surrender",
+        Section::TechDefArray(_) => "techs",
+        Section::TurnRules(_) => "turn_rules",
+        Section::Traits(_) => "traits",
+        Section::Tutorial(_) => "tutorial",
+        Section::UnitDefArray(_) => "units",
+        Section::VictoryConditions(_) => "victory_conditions",
+        Section::War(_) => "war",
+    }
+}
+
+/// Leaves of a JSON value as `path = value` pairs, `path` joining the keys and indices with dots.
+fn flatten(path: &str, value: &Value, leaves: &mut BTreeMap<String, String>) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match value {
+        Value::Object(map) => map.iter().for_each(|(k, v)| flatten(&join(k), v, leaves)),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .for_each(|(i, v)| flatten(&join(&i.to_string()), v, leaves)),
+        Value::Null => {}
+        Value::String(s) => {
+            leaves.insert(path.to_string(), s.clone());
+        }
+        other => {
+            leaves.insert(path.to_string(), other.to_string());
+        }
+    }
+}
+
+/// Flattened fields of every entry of a section, by entry name.
+fn entries<T: serde::Serialize>(
+    defs: &[T],
+    name: impl Fn(&T) -> &str,
+) -> BTreeMap<String, BTreeMap<String, String>> {
+    defs.iter()
+        .map(|def| {
+            let mut leaves = BTreeMap::new();
+            flatten(
+                "",
+                &serde_json::to_value(def).unwrap_or_default(),
+                &mut leaves,
+            );
+            leaves.remove("name");
+            (name(def).to_string(), leaves)
+        })
+        .collect()
+}
+
+/// Lines describing how the fields of `before` became those of `after`.
+fn field_changes(
+    label: &str,
+    before: &BTreeMap<String, String>,
+    after: &BTreeMap<String, String>,
+    changes: &mut Vec<String>,
+) {
+    for (field, new) in after {
+        match before.get(field) {
+            Some(old) if old != new => changes.push(format!("{label}: {field} {old} -> {new}")),
+            None => changes.push(format!("{label}: {field} = {new}")),
+            Some(_) => {}
+        }
+    }
+    for field in before.keys().filter(|f| !after.contains_key(*f)) {
+        changes.push(format!("{label}: {field} supprimé"));
+    }
+}
+
+/// Definitions changed by a reload, one line each: buildings and units by name, the other
+/// sections field by field. Shown to the DSL author in the live link side panel.
+pub fn config_diff(before: &Model, after: &Model) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    let buildings = |m: &Model| {
+        m.sections
+            .iter()
+            .filter_map(|s| match s {
+                Section::BuildingDefArray(defs) => Some(entries(&defs.buildings, |b| &b.name)),
+                _ => None,
+            })
+            .flatten()
+            .collect::<BTreeMap<_, _>>()
+    };
+    let units = |m: &Model| {
+        m.sections
+            .iter()
+            .filter_map(|s| match s {
+                Section::UnitDefArray(defs) => Some(entries(&defs.units, |u| &u.name)),
+                _ => None,
+            })
+            .flatten()
+            .collect::<BTreeMap<_, _>>()
+    };
+    for (kind, old, new) in [
+        ("Bâtiment", buildings(before), buildings(after)),
+        ("Unité", units(before), units(after)),
+    ] {
+        for (name, fields) in &new {
+            match old.get(name) {
+                Some(previous) => {
+                    field_changes(&format!("{kind} {name}"), previous, fields, &mut changes);
+                }
+                None => changes.push(format!("{kind} {name} ajouté")),
+            }
+        }
+        for name in old.keys().filter(|n| !new.contains_key(*n)) {
+            changes.push(format!("{kind} {name} supprimé"));
+        }
+    }
+
+    // every other section is compared as a whole, field by field
+    let others = |m: &Model| {
+        let mut sections: BTreeMap<&str, BTreeMap<String, String>> = BTreeMap::new();
+        for section in &m.sections {
+            if matches!(
+                section,
+                Section::BuildingDefArray(_) | Section::UnitDefArray(_)
+            ) {
+                continue;
+            }
+            let value = serde_json::to_value(section).unwrap_or_default();
+            flatten(
+                "",
+                &value,
+                sections.entry(section_name(section)).or_default(),
+            );
+        }
+        sections
+    };
+    let (old, new) = (others(before), others(after));
+    let empty = BTreeMap::new();
+    for name in old
+        .keys()
+        .chain(new.keys().filter(|n| !old.contains_key(*n)))
+    {
+        let label = format!("[{name}]");
+        match (old.get(name), new.get(name)) {
+            (Some(_), None) => changes.push(format!("{label} supprimé")),
+            (None, Some(_)) => changes.push(format!("{label} ajouté")),
+            (previous, fields) => field_changes(
+                &label,
+                previous.unwrap_or(&empty),
+                fields.unwrap_or(&empty),
+                &mut changes,
+            ),
+        }
+    }
+    changes
+}
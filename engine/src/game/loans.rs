@@ -0,0 +1,107 @@
+use crate::game::composition;
+use crate::game::state::{Civilization, GameState};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// Interest added to a loan, in percent of the borrowed amount
+pub const INTEREST_PERCENT: u32 = 20;
+/// Number of turns over which a loan is repaid
+pub const LOAN_TERM: u32 = 5;
+/// Maximum amount a civilization may owe at once, interest included
+pub const MAX_DEBT: u32 = 300;
+/// Morale lost on every missed installment
+pub const MORALE_PENALTY: u32 = 20;
+/// Morale regained on every turn where all installments were paid
+pub const MORALE_RECOVERY: u32 = 5;
+/// Share of the units deserting on every missed installment, in percent
+pub const DESERTION_PERCENT: u32 = 25;
+
+/// Outstanding loan of a civilization, repaid at each of its turn starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Loan {
+    /// Amount still owed, interest included
+    pub owed: u32,
+    /// Amount collected at each turn start
+    pub installment: u32,
+}
+
+impl Loan {
+    /// Loan of `amount`, at most `MAX_DEBT` so that the interest cannot overflow.
+    fn new(amount: u32) -> Self {
+        let owed = amount + amount * INTEREST_PERCENT / 100;
+        Self {
+            owed,
+            installment: owed.div_ceil(LOAN_TERM),
+        }
+    }
+}
+
+/// Total amount owed by a civilization, interest included.
+pub fn debt(civ: &Civilization) -> u32 {
+    civ.loans.iter().map(|l| l.owed).sum()
+}
+
+/// Borrow `amount` resources, credited immediately and repaid with interest over `LOAN_TERM` turns.
+///
+/// # Errors
+/// Fails when the amount is zero, the debt would go over `MAX_DEBT`, or the puzzle budget is spent.
+pub fn take_loan(state: &mut GameState, civ_index: usize, amount: u32) -> Result<()> {
+    if let Some(puzzle) = &state.puzzle {
+        puzzle.check_budget(civ_index, false)?;
+    }
+    if amount == 0 {
+        return Err(anyhow!("Loan amount must be positive"));
+    }
+    let civ = state.civ_mut(civ_index)?;
+    let available = MAX_DEBT.saturating_sub(debt(civ));
+    // the amount alone is checked first, the interest being computed on it
+    let loan = (amount <= available)
+        .then(|| Loan::new(amount))
+        .filter(|l| l.owed <= available);
+    let Some(loan) = loan else {
+        return Err(anyhow!(
+            "Debt limit reached: {} owed, at most {MAX_DEBT} allowed",
+            u64::from(debt(civ)) + u64::from(amount) * u64::from(100 + INTEREST_PERCENT) / 100
+        ));
+    };
+    civ.earn(amount.cast_signed());
+    civ.loans.push(loan);
+    if let Some(puzzle) = &mut state.puzzle {
+        puzzle.consume(civ_index, false);
+    }
+    Ok(())
+}
+
+/// Collect the installments due at the turn start of `civ_index`. Every installment that cannot be
+/// paid costs morale and makes part of the army desert. Returns a report when the civ defaulted.
+pub fn collect_installments(state: &mut GameState, civ_index: usize) -> Option<String> {
+    let civ = state.civ_mut(civ_index).ok()?;
+    let mut missed = 0;
+    for loan in &mut civ.loans {
+        let due = loan.installment.min(loan.owed);
+        if civ.resources.ressources >= due.cast_signed() {
+            civ.resources.ressources -= due.cast_signed();
+            loan.owed -= due;
+        } else {
+            missed += 1;
+        }
+    }
+    civ.loans.retain(|l| l.owed > 0);
+
+    if missed == 0 {
+        civ.morale = (civ.morale + MORALE_RECOVERY).min(100);
+        return None;
+    }
+    civ.morale = civ.morale.saturating_sub(missed * MORALE_PENALTY);
+    let units = composition::total(&civ.city.units.units);
+    let deserters = units
+        .saturating_mul(DESERTION_PERCENT * missed)
+        .div_ceil(100)
+        .min(units);
+    let lost = state.remove_units_from_city(civ_index, deserters);
+    let civ = state.civ(civ_index).ok()?;
+    Some(format!(
+        "{} missed {missed} loan installment(s): morale down to {}%, {lost} units deserted",
+        civ.city.name, civ.morale
+    ))
+}
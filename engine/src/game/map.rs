@@ -1,62 +1,287 @@
-use crate::game::state::GameState;
-use crate::game::ui::UiConfig;
-use crate::game::utils::{hash_tmb, str_to_color};
+use crate::ast::{Game as GameTerms, MapType};
+use crate::game::utils::hash_tmb;
 use noise::{NoiseFn, Perlin};
-use ratatui::Frame;
-use ratatui::layout::Rect;
-use ratatui::prelude::{Color, Line, Span, Style};
-use ratatui::widgets::{Block, Borders, Paragraph};
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-#[derive(Clone, Debug)]
+/// Source of the terrain revisions, shared by every map so that a regenerated map never reuses
+/// the revision of the one it replaces
+static REVISIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Tiles of map for one river source in the mountains
+const TILES_PER_RIVER: usize = 600;
+/// Share of the production a city next to a river gains, in percent
+pub const RIVER_PRODUCTION_PERCENT: i32 = 25;
+/// Tiles of map for one island of an archipelago
+const TILES_PER_ISLAND: usize = 150;
+
+fn next_revision() -> u64 {
+    REVISIONS.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Terrain {
     Water,
     Plains,
     Desert,
     Mountain,
+    /// Flows from the mountains down to the sea, slow to cross
+    River,
+    /// Wet plains, a little slower to cross and rich in wood
+    Forest,
+    /// Cold lands, slow to cross and poor
+    Snow,
+    /// Wet lowlands along the shore, slow to cross
+    Swamp,
 }
 
 impl Terrain {
-    pub fn to_style(&self) -> Color {
-        match self {
-            Terrain::Water => Color::Indexed(26),
-            Terrain::Plains => Color::Indexed(70),
-            Terrain::Desert => Color::Indexed(220),
-            Terrain::Mountain => Color::Indexed(250),
-        }
-    }
-
     pub fn to_char(&self) -> char {
         match self {
             Terrain::Water => '~',
             Terrain::Plains => '.',
             Terrain::Desert => ':',
             Terrain::Mountain => '^',
+            Terrain::River => '=',
+            Terrain::Forest => '&',
+            Terrain::Snow => '+',
+            Terrain::Swamp => '%',
+        }
+    }
+
+    /// Share of their usual output the buildings of a city standing on this terrain produce, in
+    /// percent.
+    pub fn production_percent(self) -> i32 {
+        match self {
+            Terrain::Forest => 110,
+            Terrain::Snow => 60,
+            Terrain::Swamp => 80,
+            _ => 100,
+        }
+    }
+}
+
+/// Where the biomes spread, on a scale from 0 to 100 of the temperature and moisture of a tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Biomes {
+    /// Plains and desert colder than this are covered in snow
+    pub snow: u32,
+    /// Plains wetter than this grow forests
+    pub forest: u32,
+    /// Lowlands wetter than this turn to swamp
+    pub swamp: u32,
+}
+
+impl Default for Biomes {
+    fn default() -> Self {
+        Self {
+            snow: 25,
+            forest: 60,
+            swamp: 55,
+        }
+    }
+}
+
+impl Biomes {
+    /// Thresholds of the `biomes` of `[game]`, the default ones for those left out.
+    pub fn from_game(game: &GameTerms) -> Self {
+        let default = Self::default();
+        let terms = game.biomes.as_ref();
+        Self {
+            snow: terms.and_then(|t| t.snow).unwrap_or(default.snow).min(100),
+            forest: terms
+                .and_then(|t| t.forest)
+                .unwrap_or(default.forest)
+                .min(100),
+            swamp: terms
+                .and_then(|t| t.swamp)
+                .unwrap_or(default.swamp)
+                .min(100),
+        }
+    }
+}
+
+/// Overall shape of the land, from the `map_type` of `[game]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MapShape {
+    /// Land scattered by the elevation noise alone, without `map_type`
+    #[default]
+    Scattered,
+    /// Large landmasses with winding coasts
+    Continents,
+    /// Small islands spread over the sea
+    Islands,
+    /// A single landmass in the middle of the map, surrounded by the sea
+    Pangaea,
+}
+
+impl MapShape {
+    pub fn from_game(game: &GameTerms) -> Self {
+        match game.map_type {
+            None => Self::Scattered,
+            Some(MapType::CONTINENTS) => Self::Continents,
+            Some(MapType::ISLANDS) => Self::Islands,
+            Some(MapType::PANGAEA) => Self::Pangaea,
+        }
+    }
+}
+
+/// Tile coordinate or map size as a float, maps being at most 1024 tiles wide
+fn coordinate(value: usize) -> f64 {
+    f64::from(u32::try_from(value).unwrap_or(u32::MAX))
+}
+
+/// Noise of `noise` at the tile `(x, y)`, `frequency` being its scale in noise units per tile.
+/// On a map `width` tiles wide that wraps around, the noise is sampled on a cylinder so that both
+/// edges meet without a seam.
+fn sample(noise: &Perlin, (x, y): (f64, f64), frequency: f64, wrap: Option<f64>) -> f64 {
+    match wrap {
+        Some(width) => {
+            let radius = width * frequency / std::f64::consts::TAU;
+            let angle = x / width * std::f64::consts::TAU;
+            noise.get([radius * angle.cos(), radius * angle.sin(), y * frequency])
+        }
+        None => noise.get([x * frequency, y * frequency]),
+    }
+}
+
+/// Elevation of every tile, around -1..1: under -0.2 is water, from 0.5 mountains.
+struct Relief {
+    shape: MapShape,
+    /// Width of the map when it wraps around
+    wrap: Option<f64>,
+    width: f64,
+    height: f64,
+    noise: Perlin,
+    /// Shifts where continents sample the noise, bending their coasts
+    warp: Perlin,
+    /// Centre and radius of each island of an archipelago
+    islands: Vec<(f64, f64, f64)>,
+}
+
+impl Relief {
+    fn new(seed: &str, width: usize, height: usize, shape: MapShape, wrap: bool) -> Self {
+        let count = if shape == MapShape::Islands {
+            (width * height / TILES_PER_ISLAND).max(1)
+        } else {
+            0
+        };
+        let islands = (0..count)
+            .map(|i| {
+                let hash = hash_tmb(&format!("{seed}-island-{i}")) as usize;
+                let (x, y) = (hash % width.max(1), hash / width.max(1) % height.max(1));
+                (
+                    coordinate(x),
+                    coordinate(y),
+                    f64::from(3 + hash_tmb(&format!("{seed}-island-{i}-radius")) % 3),
+                )
+            })
+            .collect();
+        Self {
+            shape,
+            wrap: wrap.then(|| coordinate(width)),
+            width: coordinate(width),
+            height: coordinate(height),
+            noise: Perlin::new(hash_tmb(seed)),
+            warp: Perlin::new(hash_tmb(&format!("{seed}-warp"))),
+            islands,
+        }
+    }
+
+    fn at(&self, x: usize, y: usize) -> f64 {
+        let (x, y) = (coordinate(x), coordinate(y));
+        let noise = sample(&self.noise, (x, y), 0.1, self.wrap);
+        match self.shape {
+            MapShape::Scattered => noise,
+            MapShape::Continents => {
+                let wx = sample(&self.warp, (x, y), 0.05, self.wrap);
+                let wy = sample(&self.warp, (x + 146.0, y + 62.0), 0.05, self.wrap);
+                let land = sample(&self.noise, (x + wx * 37.5, y + wy * 37.5), 0.04, self.wrap);
+                land * 1.2 + noise * 0.3 + 0.1
+            }
+            MapShape::Islands => {
+                let mask = self
+                    .islands
+                    .iter()
+                    .map(|(cx, cy, radius)| {
+                        let dx = (x - cx).abs();
+                        let dx = if self.wrap.is_some() {
+                            dx.min(self.width - dx)
+                        } else {
+                            dx
+                        };
+                        1.0 - (dx.powi(2) + (y - cy).powi(2)) / radius.powi(2)
+                    })
+                    .fold(0.0, f64::max);
+                mask * 1.1 - 0.6 + noise * 0.35
+            }
+            MapShape::Pangaea => {
+                let dx = (x - self.width / 2.0) / (self.width / 2.0).max(1.0);
+                let dy = (y - self.height / 2.0) / (self.height / 2.0).max(1.0);
+                (1.0 - dx * dx - dy * dy) * 1.2 - 0.45 + noise * 0.4
+            }
         }
     }
 }
 
+/// Noise value around -1..1 brought to the 0-100 scale of the biome thresholds
+fn level(noise: f64) -> f64 {
+    ((noise + 1.0) * 50.0).clamp(0.0, 100.0)
+}
+
 #[derive(Clone, Debug)]
 pub struct GameMap {
     pub tiles: Vec<Vec<Terrain>>,
     pub width: usize,
     pub height: usize,
     pub seed: String,
+    /// Thresholds the map was generated with, kept when it is generated again from another seed
+    pub biomes: Biomes,
+    /// Shape the land was generated with, kept like the biomes
+    pub shape: MapShape,
+    /// Whether the east edge leads to the west one, for a map around a cylinder
+    pub wrap: bool,
+    /// Changes whenever the terrain does, so that what is computed from the tiles can be cached
+    /// until then. Tiles must be changed through `set` for it to be kept up to date.
+    pub revision: u64,
 }
 
 impl GameMap {
     pub fn new(seed: String, width: usize, height: usize) -> Self {
+        Self::generate(
+            seed,
+            width,
+            height,
+            Biomes::default(),
+            MapShape::default(),
+            false,
+        )
+    }
+
+    /// Map of `seed` with the land laid out by `shape` and the biomes spread by `biomes`, seamless
+    /// across its east and west edges when it `wrap`s around.
+    pub fn generate(
+        seed: String,
+        width: usize,
+        height: usize,
+        biomes: Biomes,
+        shape: MapShape,
+        wrap: bool,
+    ) -> Self {
         let mut tiles = vec![vec![Terrain::Water; width]; height];
 
-        let perlin_elevation = Perlin::new(hash_tmb(seed.clone()));
-        let perlin_moisture = Perlin::new(hash_tmb(hash_tmb(seed.clone()).to_string()));
-        let scale = 0.1;
+        let relief = Relief::new(&seed, width, height, shape, wrap);
+        let perlin_moisture = Perlin::new(hash_tmb(&hash_tmb(&seed).to_string()));
+        let perlin_temperature = Perlin::new(hash_tmb(&format!("{seed}-temperature")));
+        let elevation_at = |x: usize, y: usize| relief.at(x, y);
 
         for (y, line) in tiles.iter_mut().enumerate() {
             for (x, cell) in line.iter_mut().enumerate() {
-                let elevation = perlin_elevation.get([x as f64 * scale, y as f64 * scale]);
-                let moisture =
-                    perlin_moisture.get([x as f64 * scale * 1.5, y as f64 * scale * 1.5]);
+                let elevation = elevation_at(x, y);
+                let tile = (coordinate(x), coordinate(y));
+                let moisture = sample(&perlin_moisture, tile, 0.15, relief.wrap);
+                let temperature = sample(&perlin_temperature, tile, 0.05, relief.wrap);
 
                 *cell = match (elevation, moisture) {
                     (e, _) if e < -0.2 => Terrain::Water,
@@ -66,184 +291,171 @@ impl GameMap {
                     (e, _) if e >= 0.5 => Terrain::Mountain,
                     _ => Terrain::Plains,
                 };
+                *cell = match *cell {
+                    Terrain::Plains | Terrain::Desert
+                        if level(temperature) < f64::from(biomes.snow) =>
+                    {
+                        Terrain::Snow
+                    }
+                    Terrain::Plains
+                        if elevation < -0.1 && level(moisture) > f64::from(biomes.swamp) =>
+                    {
+                        Terrain::Swamp
+                    }
+                    Terrain::Plains if level(moisture) > f64::from(biomes.forest) => {
+                        Terrain::Forest
+                    }
+                    terrain => terrain,
+                };
             }
         }
+        carve_rivers(&mut tiles, &seed, wrap, elevation_at);
 
         Self {
             tiles,
             width,
             height,
             seed,
+            biomes,
+            shape,
+            wrap,
+            revision: next_revision(),
         }
     }
 
-    pub fn new_random(width: usize, height: usize) -> Self {
-        let seed = rand::random::<u64>().to_string();
-        Self::new(seed, width, height)
+    /// Map of the same size, shape, wrapping and biomes generated from `seed`.
+    #[must_use]
+    pub fn reseeded(&self, seed: String) -> Self {
+        Self::generate(
+            seed,
+            self.width,
+            self.height,
+            self.biomes,
+            self.shape,
+            self.wrap,
+        )
     }
-}
-
-impl Display for GameMap {
-    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        for row in &self.tiles {
-            for terrain in row {
-                formatter.write_char(terrain.to_char())?;
-            }
-            formatter.write_char('\n')?;
-        }
 
-        Ok(())
+    /// Terrain of the tile at `(x, y)`, None outside the map. Columns past an edge of a map that
+    /// wraps around come back from the other edge.
+    pub fn at(&self, x: i64, y: i64) -> Option<Terrain> {
+        let row = self.tiles.get(usize::try_from(y).ok()?)?;
+        let x = if self.wrap {
+            x.rem_euclid(i64::try_from(self.width).ok()?.max(1))
+        } else {
+            x
+        };
+        row.get(usize::try_from(x).ok()?).copied()
     }
-}
-
-pub fn generate_map_buffer(state: &GameState) -> Vec<Vec<Color>> {
-    // Use cached terrain buffer if present, but always overlay dynamic entities (cities, travels)
-    let mut base: Vec<Vec<Color>> = if let Some(buffer) = &state.map_buffer_cache {
-        buffer.clone()
-    } else {
-        state
-            .map
-            .tiles
-            .iter()
-            .map(|line| line.iter().map(Terrain::to_style).collect())
-            .collect()
-    };
-
-    apply_cities_on_map_buffer(state, &mut base);
-    base
-}
 
-pub fn apply_cities_on_map_buffer(state: &GameState, buffer: &mut [Vec<Color>]) {
-    for civ in &state.civilizations {
-        let city = &civ.city;
-        // draw city
-        if (city.y as usize) < buffer.len() && (city.x as usize) < buffer[0].len() {
-            buffer[city.y as usize][city.x as usize] = str_to_color(&city.color);
+    /// Column `x` brought back onto a map that wraps around, unchanged on one that does not.
+    pub fn wrap_x(&self, x: i32) -> i32 {
+        match i32::try_from(self.width) {
+            Ok(width) if self.wrap && width > 0 => x.rem_euclid(width),
+            _ => x,
         }
     }
 
-    // draw traveling units along their paths
-    for t in &state.travels {
-        if t.path.is_empty() { continue; }
-
-        // optionally draw full path when zoomed in (visible when zoom > 1)
-        // draw a continuous path: stop one tile before destination and don't override cities
-        for (i, (sx, sy)) in t.path.iter().enumerate() {
-            // stop before destination (last element)
-            if i + 1 >= t.path.len() { break; }
-            if *sy < 0 || *sx < 0 { continue; }
-            let syu = *sy as usize;
-            let sxu = *sx as usize;
-            if syu >= buffer.len() || sxu >= buffer[0].len() { continue; }
-
-            // don't overwrite city tiles
-            let mut is_city = false;
-            for civ in &state.civilizations {
-                if civ.city.x as usize == sxu && civ.city.y as usize == syu {
-                    is_city = true;
-                    break;
+    /// Columns from `from` to `to`, negative westward, taking the short way around a map that
+    /// wraps around.
+    pub fn offset_x(&self, from: i64, to: i64) -> i64 {
+        let offset = to - from;
+        match i64::try_from(self.width) {
+            Ok(width) if self.wrap && width > 0 => {
+                let offset = offset.rem_euclid(width);
+                if offset * 2 > width {
+                    offset - width
+                } else {
+                    offset
                 }
             }
-            if is_city { continue; }
-
-            // draw path tile
-            buffer[syu][sxu] = Color::Indexed(8);
+            _ => offset,
         }
+    }
 
-        // compute progress index along path from travel.remaining/total
-        let total_turns = t.total.max(1) as f64;
-        let passed = (t.total - t.remaining) as f64;
-        let fraction = (passed / total_turns).clamp(0.0, 1.0);
-        let total_steps = if t.path.len() >= 1 { t.path.len() - 1 } else { 0 } as f64;
-        // use floor to avoid jumping to the next tile too early
-        let idx = (fraction * total_steps).floor() as usize;
-        let pos = t.path.get(idx).unwrap_or(&t.path[t.path.len()-1]);
-        let (px, py) = *pos;
-        if py >= 0 && px >= 0 && (py as usize) < buffer.len() && (px as usize) < buffer[0].len() {
-            // use attacker's color to mark traveling unit (draw on top of path)
-            let col = ratatui::style::Color::Cyan;
-            buffer[py as usize][px as usize] = col;
+    /// Whether a river flows on the tile at `(x, y)` or one of the 8 tiles around it.
+    pub fn near_river(&self, x: u32, y: u32) -> bool {
+        let (x, y) = (i64::from(x), i64::from(y));
+        (-1..=1).any(|dy| (-1..=1).any(|dx| self.at(x + dx, y + dy) == Some(Terrain::River)))
+    }
+
+    /// Change the terrain of the tile at `(x, y)`. Returns false when it is outside the map or
+    /// already has that terrain.
+    pub fn set(&mut self, x: usize, y: usize, terrain: Terrain) -> bool {
+        match self.tiles.get_mut(y).and_then(|line| line.get_mut(x)) {
+            Some(tile) if *tile != terrain => {
+                *tile = terrain;
+                self.revision = next_revision();
+                true
+            }
+            _ => false,
         }
     }
-}
 
-pub fn render_buffer<'a>(state: &GameState, _area: Rect, buffer: &[Vec<Color>], visible_width: usize, visible_height: usize) -> Vec<Line<'a>> {
-    let zoom = state.zoom_level as usize;
+    pub fn new_random(width: usize, height: usize) -> Self {
+        let seed = rand::random::<u64>().to_string();
+        Self::new(seed, width, height)
+    }
+}
 
-    let start_x = (state.camera_x as usize).min(state.map.width.saturating_sub(visible_width));
-    let start_y = (state.camera_y as usize).min(state.map.height.saturating_sub(visible_height));
+/// Run rivers from mountain tiles down to the water, each step onto the lowest neighbouring
+/// tile. Sources are picked from the seed, one for every `TILES_PER_RIVER` tiles; a river
+/// stuck in a hollow before the water is not carved. Rivers cross the edges of a map that
+/// `wrap`s around.
+fn carve_rivers(
+    tiles: &mut [Vec<Terrain>],
+    seed: &str,
+    wrap: bool,
+    elevation: impl Fn(usize, usize) -> f64,
+) {
+    let height = tiles.len();
+    let width = tiles.first().map_or(0, Vec::len);
+    let mut sources: Vec<(u32, usize, usize)> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|(x, y)| tiles[*y][*x] == Terrain::Mountain)
+        .map(|(x, y)| (hash_tmb(&format!("{seed}-river-{x}-{y}")), x, y))
+        .collect();
+    sources.sort_unstable();
+    for (_, x, y) in sources.into_iter().take(width * height / TILES_PER_RIVER) {
+        let mut course = Vec::new();
+        let (mut x, mut y) = (x, y);
+        loop {
+            let (west, east) = if wrap {
+                ((x + width - 1) % width, (x + 1) % width)
+            } else {
+                (x.wrapping_sub(1), x + 1)
+            };
+            let neighbours = [(west, y), (east, y), (x, y.wrapping_sub(1)), (x, y + 1)];
+            let lowest = neighbours
+                .into_iter()
+                .filter(|(nx, ny)| *nx < width && *ny < height && !course.contains(&(*nx, *ny)))
+                .min_by(|a, b| elevation(a.0, a.1).total_cmp(&elevation(b.0, b.1)));
+            let Some(next) = lowest.filter(|n| elevation(n.0, n.1) < elevation(x, y)) else {
+                break;
+            };
+            if tiles[next.1][next.0] == Terrain::Water {
+                for (cx, cy) in course {
+                    if tiles[cy][cx] != Terrain::Mountain {
+                        tiles[cy][cx] = Terrain::River;
+                    }
+                }
+                break;
+            }
+            course.push(next);
+            (x, y) = next;
+        }
+    }
+}
 
-    let _stop_x = start_x + visible_width;
-    let stop_y = start_y + visible_height;
+impl Display for GameMap {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        for row in &self.tiles {
+            for terrain in row {
+                formatter.write_char(terrain.to_char())?;
+            }
+            formatter.write_char('\n')?;
+        }
 
-    buffer[start_y..stop_y]
-        .iter()
-        .flat_map(|t| (0..zoom).map(|_| t.clone()))
-        .collect::<Vec<Vec<Color>>>()
-        .chunks_exact(2)
-        .map(|pair| {
-            Line::from(
-                pair[0]
-                    .iter()
-                    .zip(&pair[1])
-                    .skip(start_x)
-                    .take(visible_width)
-                    .flat_map(|(c1, c2)| {
-                        (0..zoom).map(|_| Span::styled("▄", Style::new().bg(*c1).fg(*c2)))
-                    })
-                    .collect::<Vec<Span>>(),
-            )
-        })
-        .collect::<Vec<Line>>()
-}
-
-/// Draw the game map to a frame area.
-///
-/// Main entry point for rendering the map in the TUI.
-/// Handles buffer generation, caching, camera positioning, and zoom.
-///
-/// # Arguments
-/// * `frame` - The ratatui Frame to draw on
-/// * `area` - The screen area to render into
-/// * `state` - Current game state (mutable for caching)
-/// * `ui_config` - UI configuration
-pub fn draw_map(frame: &mut Frame, area: Rect, state: &mut GameState, ui_config: &UiConfig) {
-    let visible_width = (usize::from(area.width).saturating_sub(2) / usize::from(state.zoom_level)).min(state.map.width);
-    let visible_height = (usize::from(area.height * 2).saturating_sub(2) / usize::from(state.zoom_level)).min(state.map.height);
-
-    let hidden_width = state.map.width - visible_width;
-    let hidden_height = state.map.height - visible_height;
-
-    state.camera_x = state.camera_x.clamp(
-        0,
-        hidden_width as i32,
-    );
-    state.camera_y = state.camera_y.clamp(
-        0,
-        hidden_height as i32,
-    );
-
-    let title = if state.camera_mode {
-        format!(
-            "Map (Camera Mode - Position: {}/{},{}/{} - Zoom: {}x) - Press 'v' or Esc to exit",
-            state.camera_x, hidden_width, state.camera_y, hidden_height, state.zoom_level
-        )
-    } else {
-        format!(
-            "Map (Press 'v' for camera, 'z' to zoom - Zoom: {}x)",
-            state.zoom_level
-        )
-    };
-
-    let buffer = generate_map_buffer(state);
-    let map_lines = render_buffer(state, area, &buffer, visible_width, visible_height);
-
-    // apply ui_config.color to the map widget border
-    let map_widget = Paragraph::new(map_lines).block(
-        Block::default()
-            .title(title)
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(ui_config.color)),
-    );
-    frame.render_widget(map_widget, area);
+        Ok(())
+    }
 }
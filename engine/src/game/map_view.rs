@@ -0,0 +1,575 @@
+use crate::game::UiConfig;
+use crate::game::map::Terrain;
+use crate::game::state::GameState;
+use crate::game::utils::{output_path, str_to_color, write_to_file};
+use crate::game::weather::{Weather, WeatherMap};
+use crate::game::{capture, fog, regions};
+use anyhow::Result;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Color, Line, Span, Style};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+impl Terrain {
+    pub fn to_style(&self) -> Color {
+        match self {
+            Terrain::Water => Color::Indexed(26),
+            Terrain::Plains => Color::Indexed(70),
+            Terrain::Desert => Color::Indexed(220),
+            Terrain::Mountain => Color::Indexed(250),
+            Terrain::River => Color::Indexed(39),
+            Terrain::Forest => Color::Indexed(28),
+            Terrain::Snow => Color::Indexed(195),
+            Terrain::Swamp => Color::Indexed(101),
+        }
+    }
+
+    /// Saturated colors that stay distinguishable on any terminal palette.
+    pub fn to_high_contrast_style(&self) -> Color {
+        match self {
+            Terrain::Water => Color::Blue,
+            Terrain::Plains => Color::Green,
+            Terrain::Desert => Color::Yellow,
+            Terrain::Mountain => Color::White,
+            Terrain::River => Color::Cyan,
+            Terrain::Forest => Color::LightGreen,
+            Terrain::Snow => Color::LightCyan,
+            Terrain::Swamp => Color::DarkGray,
+        }
+    }
+
+    /// Darker colors for the explored tiles out of sight under the fog of war.
+    pub fn to_dim_style(self) -> Color {
+        match self {
+            Terrain::Water => Color::Indexed(17),
+            Terrain::Plains => Color::Indexed(22),
+            Terrain::Desert => Color::Indexed(94),
+            Terrain::Mountain => Color::Indexed(240),
+            Terrain::River => Color::Indexed(24),
+            Terrain::Forest => Color::Indexed(235),
+            Terrain::Snow => Color::Indexed(245),
+            Terrain::Swamp => Color::Indexed(58),
+        }
+    }
+}
+
+impl Weather {
+    pub fn to_style(self) -> Color {
+        match self {
+            Weather::Storm => Color::Indexed(60),
+            Weather::Sandstorm => Color::Indexed(137),
+        }
+    }
+
+    /// Saturated colors that stay distinguishable on any terminal palette.
+    pub fn to_high_contrast_style(self) -> Color {
+        match self {
+            Weather::Storm => Color::Magenta,
+            Weather::Sandstorm => Color::Red,
+        }
+    }
+}
+
+pub fn generate_map_buffer(state: &GameState, ui_config: &UiConfig) -> Vec<Vec<Color>> {
+    // Use cached terrain buffer if present, but always overlay dynamic entities (cities, travels)
+    let mut base: Vec<Vec<Color>> = if let Some(buffer) = &state.map_buffer_cache {
+        buffer.clone()
+    } else {
+        state
+            .map
+            .tiles
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|t| {
+                        if ui_config.preferences.high_contrast {
+                            t.to_high_contrast_style()
+                        } else {
+                            t.to_style()
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+
+    apply_regions_on_map_buffer(state, &mut base);
+    apply_weather_on_map_buffer(state, ui_config, &mut base);
+    apply_cities_on_map_buffer(state, &mut base);
+    apply_fog_on_map_buffer(state, &mut base);
+    base
+}
+
+/// Hide the tiles the viewer has not explored, and dim those it explored but does not see
+/// right now, along with the armies on them. Explored cities stay shown, and the armies seen
+/// on the last turns leave a gray ghost where they were last seen.
+pub fn apply_fog_on_map_buffer(state: &GameState, buffer: &mut [Vec<Color>]) {
+    let Some(viewer) = fog::viewer(state) else {
+        return;
+    };
+    let sights = fog::sights(state, viewer);
+    let cities: Vec<(i32, i32)> = state
+        .civilizations
+        .iter()
+        .map(|c| (c.city.x.cast_signed(), c.city.y.cast_signed()))
+        .collect();
+    for ((y, line), terrains) in (0i32..).zip(buffer.iter_mut()).zip(&state.map.tiles) {
+        for ((x, cell), terrain) in (0i32..).zip(line.iter_mut()).zip(terrains) {
+            if !fog::explored(state, viewer, (x, y)) {
+                *cell = Color::Indexed(235);
+            } else if !cities.contains(&(x, y)) && !fog::sees(&state.map, &sights, (x, y)) {
+                *cell = terrain.to_dim_style();
+            }
+        }
+    }
+    // ghosts of the enemy armies seen on earlier turns, fading as they get older
+    for sighting in fog::sightings(state, viewer)
+        .iter()
+        .filter(|s| s.age(state.turn) > 0)
+    {
+        let (Ok(x), Ok(y)) = (
+            usize::try_from(sighting.tile.0),
+            usize::try_from(sighting.tile.1),
+        ) else {
+            continue;
+        };
+        let shade = 250 - 2 * sighting.age(state.turn).clamp(0, fog::SIGHTING_MEMORY);
+        if let Some(cell) = buffer.get_mut(y).and_then(|l| l.get_mut(x)) {
+            *cell = Color::Indexed(u8::try_from(shade).unwrap_or(240));
+        }
+    }
+}
+
+/// Black out the tiles outside every region and mark both ends of each crossing.
+pub fn apply_regions_on_map_buffer(state: &GameState, buffer: &mut [Vec<Color>]) {
+    let Some(map_regions) = &state.regions else {
+        return;
+    };
+    for (y, line) in (0i32..).zip(buffer.iter_mut()) {
+        for (x, cell) in (0i32..).zip(line.iter_mut()) {
+            if regions::region_at(state, (x, y)).is_none() {
+                *cell = Color::Black;
+            }
+        }
+    }
+    for c in &map_regions.crossings {
+        for (x, y) in [(c.from_x, c.from_y), (c.to_x, c.to_y)] {
+            if let Some(cell) = buffer
+                .get_mut(y as usize)
+                .and_then(|l| l.get_mut(x as usize))
+            {
+                *cell = Color::White;
+            }
+        }
+    }
+}
+
+/// Overlay the storms and sandstorms of the current turn on the terrain.
+pub fn apply_weather_on_map_buffer(
+    state: &GameState,
+    ui_config: &UiConfig,
+    buffer: &mut [Vec<Color>],
+) {
+    let weather = WeatherMap::new(&state.map, state.turn);
+    for (y, line) in (0i32..).zip(buffer.iter_mut()) {
+        for (x, cell) in (0i32..).zip(line.iter_mut()) {
+            if let Some(w) = weather.at(x, y) {
+                *cell = if ui_config.preferences.high_contrast {
+                    w.to_high_contrast_style()
+                } else {
+                    w.to_style()
+                };
+            }
+        }
+    }
+}
+
+pub fn apply_cities_on_map_buffer(state: &GameState, buffer: &mut [Vec<Color>]) {
+    // draw cities in the color of their holder
+    for (city, color) in capture::cities(state) {
+        if (city.y as usize) < buffer.len() && (city.x as usize) < buffer[0].len() {
+            buffer[city.y as usize][city.x as usize] = str_to_color(color);
+        }
+    }
+
+    // draw army stacks and the way left to their target
+    for t in &state.travels {
+        if t.path.is_empty() {
+            continue;
+        }
+
+        // draw the path ahead of the stack: stop one tile before destination and don't override cities
+        for (i, (sx, sy)) in t.path.iter().enumerate().skip(t.step + 1) {
+            // stop before destination (last element)
+            if i + 1 >= t.path.len() {
+                break;
+            }
+            let (Ok(syu), Ok(sxu)) = (usize::try_from(*sy), usize::try_from(*sx)) else {
+                continue;
+            };
+            if syu >= buffer.len() || sxu >= buffer[0].len() {
+                continue;
+            }
+
+            // don't overwrite city tiles
+            let mut is_city = false;
+            for civ in &state.civilizations {
+                if civ.city.x as usize == sxu && civ.city.y as usize == syu {
+                    is_city = true;
+                    break;
+                }
+            }
+            if is_city {
+                continue;
+            }
+
+            // draw path tile
+            buffer[syu][sxu] = Color::Indexed(8);
+        }
+
+        let (px, py) = t.tile();
+        if let (Ok(px), Ok(py)) = (usize::try_from(px), usize::try_from(py))
+            && py < buffer.len()
+            && px < buffer[0].len()
+        {
+            // the stack takes the color of its civilization, like its city, raiders stand out in white
+            let col = if t.raiding() {
+                Color::White
+            } else {
+                state
+                    .civilizations
+                    .get(t.attacker)
+                    .map_or(Color::Cyan, |c| str_to_color(&c.city.color))
+            };
+            buffer[py][px] = col;
+        }
+    }
+
+    // spectator markers of the turn, on top of everything
+    for (x, y) in state.notes_of_turn().filter_map(|note| note.marker) {
+        if let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y))
+            && y < buffer.len()
+            && x < buffer[0].len()
+        {
+            buffer[y][x] = Color::Magenta;
+        }
+    }
+}
+
+/// First tile shown by a viewport of the given size, following the camera.
+fn viewport_origin(
+    state: &GameState,
+    visible_width: usize,
+    visible_height: usize,
+) -> (usize, usize) {
+    let x = usize::try_from(state.map.wrap_x(state.camera_x)).unwrap_or(0);
+    (
+        if state.map.wrap {
+            x
+        } else {
+            x.min(state.map.width.saturating_sub(visible_width))
+        },
+        usize::try_from(state.camera_y)
+            .unwrap_or(0)
+            .min(state.map.height.saturating_sub(visible_height)),
+    )
+}
+
+/// Map columns shown by a viewport `visible_width` tiles wide from `start_x`, on past the east
+/// edge back from the west one on a map that wraps around.
+fn viewport_columns(
+    state: &GameState,
+    start_x: usize,
+    visible_width: usize,
+) -> impl Iterator<Item = usize> {
+    let width = state.map.width.max(1);
+    (start_x..start_x + visible_width).map(move |x| x % width)
+}
+
+/// Screen column of the map column `x` in a viewport starting at `start_x`, None left of it.
+fn viewport_column(state: &GameState, x: usize, start_x: usize) -> Option<usize> {
+    if state.map.wrap {
+        let width = state.map.width.max(1);
+        Some((x % width + width - start_x % width) % width)
+    } else {
+        x.checked_sub(start_x)
+    }
+}
+
+pub fn render_buffer<'a>(
+    state: &GameState,
+    _area: Rect,
+    buffer: &[Vec<Color>],
+    visible_width: usize,
+    visible_height: usize,
+) -> Vec<Line<'a>> {
+    let zoom = state.zoom_level as usize;
+
+    let (start_x, start_y) = viewport_origin(state, visible_width, visible_height);
+
+    let stop_y = start_y + visible_height;
+
+    let mut rows = buffer[start_y..stop_y]
+        .iter()
+        .flat_map(|t| (0..zoom).map(|_| t.clone()))
+        .collect::<Vec<Vec<Color>>>()
+        .chunks_exact(2)
+        .map(|pair| {
+            viewport_columns(state, start_x, visible_width)
+                .flat_map(|x| {
+                    let (c1, c2) = (pair[0][x], pair[1][x]);
+                    (0..zoom).map(move |_| Span::styled("▄", Style::new().bg(c1).fg(c2)))
+                })
+                .collect::<Vec<Span>>()
+        })
+        .collect::<Vec<Vec<Span>>>();
+    if zoom >= 2 {
+        apply_city_labels(state, &mut rows, start_x, start_y);
+    }
+    rows.into_iter().map(Line::from).collect()
+}
+
+/// Longest city label drawn on the map, in characters
+const MAX_LABEL_LEN: usize = 12;
+
+/// `name` cut to `width` characters, ending with an ellipsis when it does not fit.
+fn truncate_label(name: &str, width: usize) -> String {
+    if name.chars().count() <= width {
+        name.to_string()
+    } else {
+        let mut label: String = name.chars().take(width.saturating_sub(1)).collect();
+        label.push('…');
+        label
+    }
+}
+
+/// Write the name of every visible city next to its marker, on the right when there is room
+/// and on the left otherwise. Each span of `rows` is one screen cell.
+fn apply_city_labels(state: &GameState, rows: &mut [Vec<Span>], start_x: usize, start_y: usize) {
+    let zoom = state.zoom_level as usize;
+    for (city, color) in capture::cities(state) {
+        let (Ok(x), Ok(y)) = (usize::try_from(city.x), usize::try_from(city.y)) else {
+            continue;
+        };
+        let (Some(column), Some(line)) =
+            (viewport_column(state, x, start_x), y.checked_sub(start_y))
+        else {
+            continue;
+        };
+        let Some(row) = rows.get_mut(line * zoom / 2) else {
+            continue;
+        };
+        let left = column * zoom;
+        let right = left + zoom;
+        if left >= row.len() {
+            continue;
+        }
+        let len = city.name.chars().count().min(MAX_LABEL_LEN);
+        let room_right = row.len() - right;
+        // on the right when the label fits or has more room there than on the left
+        let (column, width) = if room_right >= len || room_right >= left {
+            (right, len.min(room_right))
+        } else {
+            let width = len.min(left);
+            (left - width, width)
+        };
+        if width < 2 {
+            continue;
+        }
+        let style = Style::new().fg(str_to_color(color)).bg(Color::Black);
+        let label = truncate_label(&city.name, width);
+        for (cell, c) in row[column..].iter_mut().zip(label.chars()) {
+            *cell = Span::styled(c.to_string(), style);
+        }
+    }
+}
+
+/// Draw the game map to a frame area.
+///
+/// Main entry point for rendering the map in the TUI.
+/// Handles buffer generation, caching, camera positioning, and zoom.
+///
+/// # Arguments
+/// * `frame` - The ratatui Frame to draw on
+/// * `area` - The screen area to render into
+/// * `state` - Current game state (mutable for caching)
+/// * `ui_config` - UI configuration
+pub fn draw_map(frame: &mut Frame, area: Rect, state: &mut GameState, ui_config: &UiConfig) {
+    let visible_width = (usize::from(area.width).saturating_sub(2) / usize::from(state.zoom_level))
+        .min(state.map.width);
+    let visible_height = (usize::from(area.height * 2).saturating_sub(2)
+        / usize::from(state.zoom_level))
+    .min(state.map.height);
+
+    let hidden_width = state.map.width - visible_width;
+    let hidden_height = state.map.height - visible_height;
+    state.map_viewport = (visible_width, visible_height);
+
+    state.camera_x = if state.map.wrap {
+        state.map.wrap_x(state.camera_x)
+    } else {
+        state
+            .camera_x
+            .clamp(0, i32::try_from(hidden_width).unwrap_or(i32::MAX))
+    };
+    state.camera_y = state
+        .camera_y
+        .clamp(0, i32::try_from(hidden_height).unwrap_or(i32::MAX));
+
+    let region = regions::camera_region(state)
+        .map(|r| format!(" - Region: {r}"))
+        .unwrap_or_default();
+    let title = if state.camera_mode {
+        let tab = if state.regions.is_some() {
+            ", Tab for next region"
+        } else {
+            ""
+        };
+        format!(
+            "Map (Camera Mode - Position: {}/{},{}/{} - Zoom: {}x{region}) - Press 'v' or Esc to exit{tab}",
+            state.camera_x, hidden_width, state.camera_y, hidden_height, state.zoom_level
+        )
+    } else {
+        format!(
+            "Map (Press 'v' for camera, 'z' to zoom - Zoom: {}x{region})",
+            state.zoom_level
+        )
+    };
+
+    let buffer = generate_map_buffer(state, ui_config);
+    let map_lines = render_buffer(state, area, &buffer, visible_width, visible_height);
+
+    // apply the ui_config accent color to the map widget border
+    let map_widget = Paragraph::new(map_lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(ui_config.accent())),
+    );
+    frame.render_widget(map_widget, area);
+}
+
+/// ANSI SGR parameters selecting `color` as foreground, or background when `background`.
+fn ansi_color(color: Color, background: bool) -> String {
+    let base = if background { 40 } else { 30 };
+    match color {
+        Color::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", base + 8),
+        Color::Indexed(i) => format!("{};5;{i}", base + 8),
+        Color::Black => base.to_string(),
+        Color::Red => (base + 1).to_string(),
+        Color::Green => (base + 2).to_string(),
+        Color::Yellow => (base + 3).to_string(),
+        Color::Blue => (base + 4).to_string(),
+        Color::Magenta => (base + 5).to_string(),
+        Color::Cyan => (base + 6).to_string(),
+        Color::Gray => (base + 7).to_string(),
+        Color::DarkGray => (base + 60).to_string(),
+        Color::LightRed => (base + 61).to_string(),
+        Color::LightGreen => (base + 62).to_string(),
+        Color::LightYellow => (base + 63).to_string(),
+        Color::LightBlue => (base + 64).to_string(),
+        Color::LightMagenta => (base + 65).to_string(),
+        Color::LightCyan => (base + 66).to_string(),
+        Color::White => (base + 67).to_string(),
+        Color::Reset => (base + 9).to_string(),
+    }
+}
+
+/// Rendered map lines as text with ANSI escape codes, a style being written only when it
+/// changes so the file stays small.
+fn lines_to_ansi(lines: &[Line]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        let mut current = None;
+        for span in &line.spans {
+            if current != Some(span.style) {
+                let fg = ansi_color(span.style.fg.unwrap_or(Color::Reset), false);
+                let bg = ansi_color(span.style.bg.unwrap_or(Color::Reset), true);
+                let _ = write!(text, "\x1b[{fg};{bg}m");
+                current = Some(span.style);
+            }
+            text.push_str(&span.content);
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+/// Viewport as plain ASCII, one character per tile: the terrain, `*` for armies on the move,
+/// `@` for cities followed by their name when there is room.
+fn viewport_to_ascii(state: &GameState, visible_width: usize, visible_height: usize) -> String {
+    let (start_x, start_y) = viewport_origin(state, visible_width, visible_height);
+    let mut rows: Vec<Vec<char>> = state.map.tiles[start_y..start_y + visible_height]
+        .iter()
+        .map(|line| {
+            viewport_columns(state, start_x, visible_width)
+                .map(|x| line[x].to_char())
+                .collect()
+        })
+        .collect();
+    let mut put = |x: usize, y: usize, c: char| {
+        if let Some(cell) = y
+            .checked_sub(start_y)
+            .zip(viewport_column(state, x, start_x))
+            .and_then(|(row, column)| rows.get_mut(row)?.get_mut(column))
+        {
+            *cell = c;
+        }
+    };
+    for travel in state.travels.iter().filter(|t| !t.path.is_empty()) {
+        let (x, y) = travel.tile();
+        if let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) {
+            put(x, y, if travel.raiding() { '!' } else { '*' });
+        }
+    }
+    for (city, _) in capture::cities(state) {
+        let (x, y) = (city.x as usize, city.y as usize);
+        put(x, y, '@');
+        let end = viewport_column(state, x, start_x).map_or(x + 1, |column| start_x + column + 1);
+        let room = (start_x + visible_width)
+            .saturating_sub(end)
+            .min(MAX_LABEL_LEN);
+        if room >= 2 {
+            for (c, dx) in truncate_label(&city.name, room).chars().zip(1..) {
+                put(x + dx, y, c);
+            }
+        }
+    }
+    rows.into_iter().fold(String::new(), |mut text, row| {
+        text.extend(row);
+        text.push('\n');
+        text
+    })
+}
+
+/// Photo mode: write the map viewport of the last frame to `photos/` of the output directory,
+/// as ANSI art (colors, zoom and labels as on screen) and as plain ASCII for chat apps.
+/// Returns the paths of both files.
+///
+/// # Errors
+/// Fails when a file cannot be written.
+pub fn photo(state: &GameState, ui_config: &UiConfig) -> Result<(PathBuf, PathBuf)> {
+    let (width, height) = state.map_viewport;
+    let (width, height) = if width == 0 || height == 0 {
+        (state.map.width, state.map.height)
+    } else {
+        (width, height)
+    };
+    let buffer = generate_map_buffer(state, ui_config);
+    let lines = render_buffer(state, Rect::default(), &buffer, width, height);
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let name = format!("photos/{secs}-turn{}", state.turn);
+    write_to_file(&format!("{name}.ans"), &lines_to_ansi(&lines))?;
+    write_to_file(
+        &format!("{name}.txt"),
+        &viewport_to_ascii(state, width, height),
+    )?;
+    Ok((
+        output_path(&format!("{name}.ans")),
+        output_path(&format!("{name}.txt")),
+    ))
+}
@@ -0,0 +1,180 @@
+use crate::game::events::GameEvent;
+use crate::game::history;
+use crate::game::influence;
+use crate::game::state::GameState;
+use serde::Serialize;
+
+/// Per-civilization values sampled at the start of a turn.
+#[derive(Serialize, Debug, Clone)]
+pub struct CivSample {
+    pub power: i32,
+    pub resources: i32,
+    pub alive: bool,
+    /// Influence projected from the city, see `influence::strength`
+    pub influence: i32,
+    /// Resources gathered since the start of the game
+    pub gathered: u32,
+    /// Resources spent since the start of the game
+    pub spent: u32,
+}
+
+/// Running counts of a civilization the state does not keep by itself.
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct CivTotals {
+    /// Resources earned by buildings and trade agreements
+    pub gathered: u32,
+    /// Units killed in battles, sorties, attrition and armies cut off
+    pub units_lost: u32,
+    /// Resources paid for the upkeep of units and the maintenance of buildings
+    pub upkeep: u32,
+    /// Resources lost over the storage of the city
+    pub wasted: u32,
+}
+
+/// What a civilization did over the whole game, for the end-of-game screen.
+#[derive(Debug, Clone)]
+pub struct CivSummary {
+    pub gathered: u32,
+    pub spent: u32,
+    /// Buildings finished during the game
+    pub buildings: usize,
+    pub units_lost: u32,
+    /// Battles and sorties it fought, on either side
+    pub battles: usize,
+    pub score: i32,
+}
+
+/// Snapshot of every civilization at a given turn.
+#[derive(Serialize, Debug, Clone)]
+pub struct TurnSample {
+    pub turn: i32,
+    /// One entry per civilization, in the same order as `GameState::civilizations`
+    pub civs: Vec<CivSample>,
+}
+
+/// Turn-by-turn history of the game, used by the graphs panel and exported in snapshots.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Metrics {
+    pub history: Vec<TurnSample>,
+    /// One entry per civilization, grown as civilizations show up in the counts
+    pub totals: Vec<CivTotals>,
+}
+
+impl Metrics {
+    /// Record the current values of every civilization. A turn already sampled is overwritten.
+    pub fn record(&mut self, state: &GameState) {
+        let sample = TurnSample {
+            turn: state.turn,
+            civs: (state.civilizations.iter().enumerate())
+                .map(|(i, civ)| CivSample {
+                    power: state.calculate_city_power(i),
+                    resources: civ.resources.ressources,
+                    alive: civ.alive,
+                    influence: influence::strength(state, i),
+                    gathered: self.totals(i).gathered,
+                    spent: civ.resources.spent,
+                })
+                .collect(),
+        };
+        if let Some(last) = self.history.last_mut()
+            && last.turn == sample.turn
+        {
+            *last = sample;
+        } else {
+            self.history.push(sample);
+        }
+    }
+
+    /// Running counts of one civilization.
+    pub fn totals(&self, civ_index: usize) -> CivTotals {
+        self.totals.get(civ_index).copied().unwrap_or_default()
+    }
+
+    fn totals_mut(&mut self, civ_index: usize) -> &mut CivTotals {
+        if self.totals.len() <= civ_index {
+            self.totals.resize(civ_index + 1, CivTotals::default());
+        }
+        &mut self.totals[civ_index]
+    }
+
+    /// Count `amount` resources earned by `civ_index`.
+    pub fn gather(&mut self, civ_index: usize, amount: i32) {
+        let totals = self.totals_mut(civ_index);
+        totals.gathered = totals
+            .gathered
+            .saturating_add(amount.max(0).cast_unsigned());
+    }
+
+    /// Count `amount` resources paid by `civ_index` for its units and buildings.
+    pub fn pay_upkeep(&mut self, civ_index: usize, amount: u32) {
+        let totals = self.totals_mut(civ_index);
+        totals.upkeep = totals.upkeep.saturating_add(amount);
+    }
+
+    /// Count `amount` resources of `civ_index` lost over its storage.
+    pub fn waste(&mut self, civ_index: usize, amount: u32) {
+        let totals = self.totals_mut(civ_index);
+        totals.wasted = totals.wasted.saturating_add(amount);
+    }
+
+    /// Count `amount` units of `civ_index` killed.
+    pub fn lose_units(&mut self, civ_index: usize, amount: u32) {
+        let totals = self.totals_mut(civ_index);
+        totals.units_lost = totals.units_lost.saturating_add(amount);
+    }
+
+    /// Power history of one civilization, oldest first.
+    pub fn power_series(&self, civ_index: usize) -> Vec<u64> {
+        self.series(civ_index, |s| s.power)
+    }
+
+    /// Resource history of one civilization, oldest first.
+    pub fn resource_series(&self, civ_index: usize) -> Vec<u64> {
+        self.series(civ_index, |s| s.resources)
+    }
+
+    /// Resources gathered since the start of the game, turn by turn.
+    pub fn gathered_series(&self, civ_index: usize) -> Vec<u64> {
+        self.history
+            .iter()
+            .filter_map(|t| t.civs.get(civ_index))
+            .map(|s| u64::from(s.gathered))
+            .collect()
+    }
+
+    /// Resources spent since the start of the game, turn by turn.
+    pub fn spent_series(&self, civ_index: usize) -> Vec<u64> {
+        self.history
+            .iter()
+            .filter_map(|t| t.civs.get(civ_index))
+            .map(|s| u64::from(s.spent))
+            .collect()
+    }
+
+    fn series(&self, civ_index: usize, value: impl Fn(&CivSample) -> i32) -> Vec<u64> {
+        self.history
+            .iter()
+            .filter_map(|t| t.civs.get(civ_index))
+            .map(|s| u64::try_from(value(s)).unwrap_or(0))
+            .collect()
+    }
+}
+
+/// Totals of `civ_index` over the game: resources, buildings finished and battles fought come
+/// from the state and its event log, the rest from the running counts of the metrics.
+pub fn summary(state: &GameState, civ_index: usize) -> CivSummary {
+    let events = || state.events.events_of(civ_index).map(|(_, e)| e);
+    let totals = state.metrics.totals(civ_index);
+    CivSummary {
+        gathered: totals.gathered,
+        spent: state.civ(civ_index).map_or(0, |c| c.resources.spent),
+        buildings: events()
+            .filter(|e| matches!(e, GameEvent::ConstructionFinished { .. }))
+            .count(),
+        units_lost: totals.units_lost,
+        battles: events()
+            .filter(|e| matches!(e, GameEvent::Battle { .. } | GameEvent::Sortie { .. }))
+            .count(),
+        score: history::score(state, civ_index),
+    }
+}
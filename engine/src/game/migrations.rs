@@ -0,0 +1,82 @@
+use anyhow::{Result, anyhow};
+use log::info;
+use serde_json::Value;
+
+/// Step upgrading a saved document from version `from` to the next one, on its JSON so that it
+/// may add, rename or restructure fields before the document is deserialized.
+pub struct Migration {
+    pub from: u32,
+    /// What changed in the format, for the logs
+    pub change: &'static str,
+    pub apply: fn(&mut Value),
+}
+
+/// Migrations of the save format, one per version bump
+pub const SAVES: &[Migration] = &[Migration {
+    from: 1,
+    change: "armies record their unit types and cities the units waiting outside",
+    apply: unit_stacks,
+}];
+
+/// Migrations of the replay header, one per version bump
+pub const REPLAYS: &[Migration] = &[];
+
+/// Upgrade `document` to version `current`, one migration at a time from the version it records.
+/// Documents written by a newer engine, or by a version without migration, are refused.
+///
+/// # Errors
+/// Fails when the document is from a newer engine or a version without migration.
+pub fn migrate(mut document: Value, migrations: &[Migration], current: u32) -> Result<Value> {
+    let mut version = document
+        .get("version")
+        .and_then(Value::as_u64)
+        .and_then(|v| u32::try_from(v).ok())
+        .ok_or_else(|| anyhow!("missing format version"))?;
+    if version > current {
+        return Err(anyhow!(
+            "format {version} comes from a newer engine (this one reads up to {current})"
+        ));
+    }
+    while version < current {
+        let migration = migrations
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or_else(|| anyhow!("format {version} is not supported (expected {current})"))?;
+        info!(
+            "Migrating format {version} to {}: {}",
+            version + 1,
+            migration.change
+        );
+        (migration.apply)(&mut document);
+        version += 1;
+        document["version"] = version.into();
+    }
+    Ok(document)
+}
+
+/// Save format 2: armies are sent unit type by unit type and full cities keep units outside.
+/// Armies of older saves keep fighting with one point per unit.
+fn unit_stacks(save: &mut Value) {
+    for travel in items(save, "travels") {
+        set_default(travel, "composition", Value::Array(Vec::new()));
+    }
+    for civ in items(save, "civilizations") {
+        set_default(civ, "waiting", Value::Array(Vec::new()));
+    }
+}
+
+/// Items of the array field `key` of `document`, none when it is missing.
+fn items<'a>(document: &'a mut Value, key: &str) -> impl Iterator<Item = &'a mut Value> {
+    document
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+/// Give `object` the field `key` when it does not have it yet.
+fn set_default(object: &mut Value, key: &str, default: Value) {
+    if let Some(fields) = object.as_object_mut() {
+        fields.entry(key).or_insert(default);
+    }
+}
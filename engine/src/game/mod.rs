@@ -1,17 +1,87 @@
+pub mod action;
+pub mod action_menu;
+pub mod advisor;
+#[cfg(feature = "llm")]
 pub mod ai;
+pub mod aliases;
+pub mod annotations;
+pub mod armies;
+pub mod bench;
+pub mod build_queue;
+pub mod campaign;
+pub mod cancel;
+pub mod capture;
+pub mod checksum;
+pub mod combat;
+pub mod composition;
+pub mod conquest;
+pub mod daily;
+pub mod debugger;
+pub mod describe;
+pub mod difficulty;
+pub mod diplomacy;
+pub mod espionage;
+pub mod events;
+pub mod fog;
+pub mod game_code;
+pub mod history;
+pub mod income;
+pub mod influence;
+#[cfg(feature = "tui")]
+pub mod input;
+pub mod limits;
+#[cfg(feature = "tui")]
+pub mod live;
+pub mod loans;
 pub mod map;
+#[cfg(feature = "tui")]
+pub mod map_view;
+pub mod metrics;
+pub mod migrations;
+pub mod paths;
+pub mod pbem;
+pub mod policy;
+pub mod preferences;
+pub mod raiders;
+pub mod recruitment;
+pub mod regions;
+pub mod replay;
+pub mod report;
+pub mod saves;
+pub mod scoring;
+pub mod signing;
+pub mod simulation;
+pub mod sortie;
+pub mod stacks;
 pub mod state;
+pub mod storage;
+pub mod supply;
+pub mod surrender;
+pub mod tech;
+pub mod terraform;
+pub mod tournament;
+pub mod traits;
+pub mod turn_rules;
+pub mod tutorial;
+#[cfg(feature = "tui")]
 pub mod ui;
+pub mod upkeep;
 pub mod utils;
+pub mod valid_actions;
+pub mod victory;
+pub mod war;
+pub mod weather;
 
 use self::state::GameState;
+#[cfg(feature = "tui")]
 use self::ui::draw_ui;
-use crate::game::ui::UiConfig;
-use crate::game::utils::{str_to_color, write_to_file};
+use crate::game::preferences::Preferences;
+use crate::game::utils::str_to_color;
 use anyhow::Context;
 use rand::Rng;
 use rand::SeedableRng;
 use rand::rngs::SmallRng;
+use ratatui_core::style::Color;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum UiState {
@@ -19,16 +89,82 @@ pub enum UiState {
     EditingSeed,
     CameraMode,
     ActionEditing,
+    ActionMenu,
     PopupOpen,
 }
 
+/// Display settings shared by the terminal interface and the map rendering.
+pub struct UiConfig {
+    pub color: Color,
+    pub preferences: Preferences,
+    /// Color of the civilization whose turn it is, lightened for the black background
+    pub turn_color: Option<Color>,
+    /// Keys of a shortcut chord typed so far and the keys completing it, shown in the status bar
+    pub pending_keys: Option<String>,
+    /// What became of the keys pressed during the last AI turn, shown in the status bar until the
+    /// next key
+    pub held_keys_notice: Option<String>,
+}
+
+impl UiConfig {
+    /// Border and accent color: the color of the civilization whose turn it is, so that nobody
+    /// plays for the wrong city in hotseat games, else the `ui_color` of the game. Forced to
+    /// white in high-contrast mode.
+    pub fn accent(&self) -> Color {
+        if self.preferences.high_contrast {
+            Color::White
+        } else {
+            self.turn_color.unwrap_or(self.color)
+        }
+    }
+}
+
+/// Longest AI mood shown next to a civilization name, longer ones are cut
+pub const MAX_MOOD_LEN: usize = 40;
+
+/// Delays between two AI actions in the TUI, from slowest to fastest playback speed.
+pub const AI_PLAYBACK_DELAYS_MS: [u64; 5] = [1000, 500, 250, 100, 0];
+
 // ===== AI trait + simple RandomAI implementation =====
 
+/// One candidate action weighed by an AI, with the score it got and why.
+#[derive(Clone, Debug)]
+pub struct ScoredAction {
+    pub action: String,
+    pub value: f64,
+    pub reason: String,
+}
+
+/// Explanation of a single AI decision: every candidate considered, best first.
+#[derive(Clone, Debug)]
+pub struct DecisionTrace {
+    pub civ_index: usize,
+    pub turn: i32,
+    pub chosen: Option<String>,
+    pub candidates: Vec<ScoredAction>,
+}
+
 /// Clean AI abstraction with immutable view preventing state corruption. Send bound enables concurrent execution.
 pub trait Ai: Send {
     /// Request action from AI. None signals turn end, enabling iterative loops without special parsing.
     fn select_action(&mut self, view: &AiView, civ_index: usize) -> Option<String>;
 
+    /// Request action along with the scored candidates behind it. AIs that cannot explain themselves
+    /// (e.g. LLM-backed ones) keep the default, which returns no trace.
+    fn select_action_traced(
+        &mut self,
+        view: &AiView,
+        civ_index: usize,
+    ) -> (Option<String>, Option<DecisionTrace>) {
+        (self.select_action(view, civ_index), None)
+    }
+
+    /// Short mood or status shown next to the civilization name ("preparing defenses"), asked
+    /// once per turn before the first action. None keeps the previous one.
+    fn mood(&mut self, _view: &AiView, _civ_index: usize) -> Option<String> {
+        None
+    }
+
     /// Handle popup with intelligent default (first choice). Optional override reduces boilerplate for simple AIs.
     fn select_popup_input(
         &mut self,
@@ -45,24 +181,37 @@ pub trait Ai: Send {
     }
 }
 
-/// Lightweight random AI using SmallRng (2-3x faster than crypto RNGs).
+/// Lightweight random AI using `SmallRng` (2-3x faster than crypto RNGs).
 pub struct RandomAi {
     rng: SmallRng,
 }
 
+impl Default for RandomAi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RandomAi {
     pub fn new() -> Self {
         // Seed SmallRng from a random u64
-        let mut tr = rand::thread_rng();
+        let mut tr = rand::rng();
         let seed: u64 = tr.random();
         let rng = SmallRng::seed_from_u64(seed);
         Self { rng }
     }
+
+    /// Random AI playing the same choices on every run, for reproducible tournaments.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
 }
 
-impl Ai for RandomAi {
-    fn select_action(&mut self, view: &AiView, civ_index: usize) -> Option<String> {
-        // Build a list of candidate actions
+impl RandomAi {
+    /// Candidate actions a random player may pick from. "end" is always available.
+    fn candidate_actions(view: &AiView, civ_index: usize) -> Vec<String> {
         let mut actions: Vec<String> = Vec::new();
         // end is always allowed
         actions.push("end".to_string());
@@ -75,19 +224,102 @@ impl Ai for RandomAi {
         for u in &view.units {
             actions.push(format!("hire {}", u.to_lowercase()));
         }
-        // attack options (other players)
+        // research options
+        if let Some(me) = view.players.get(civ_index) {
+            for t in &me.techs {
+                actions.push(format!("research {}", t.to_lowercase()));
+            }
+        }
+        // attack options (other players the terrain lets an army reach)
+        for p in view.players.iter().filter(|p| p.travel_turns.is_some()) {
+            actions.push(format!("attack {}", p.name.to_lowercase()));
+        }
+        // demolish only when every slot is taken, to make room for another building
+        if let Some(me) = view.players.get(civ_index)
+            && me.free_slots == 0
+        {
+            for b in &me.owned_buildings {
+                actions.push(format!("demolish {}", b.to_lowercase()));
+            }
+        }
+        // court the other players: small gifts when rich, treaties otherwise
+        let rich = view
+            .players
+            .get(civ_index)
+            .is_some_and(|p| p.resources >= 50);
         for (i, p) in view.players.iter().enumerate() {
             if i != civ_index {
-                actions.push(format!("attack {}", p.name.to_lowercase()));
+                let name = p.name.to_lowercase();
+                if rich {
+                    actions.push(format!("gift {name} 20"));
+                }
+                actions.push(format!("trade {name}"));
+                if p.favor >= diplomacy::PACT_REQUIRED_FAVOR {
+                    actions.push(format!("pact {name}"));
+                }
             }
         }
-
-        if actions.is_empty() {
-            return Some("end".into());
+        // borrow only when debt free, so a random player does not pile up loans
+        if view.players.get(civ_index).is_some_and(|p| p.debt == 0) {
+            actions.push("loan 50".to_string());
         }
+        actions
+    }
+}
+
+impl Ai for RandomAi {
+    fn select_action(&mut self, view: &AiView, civ_index: usize) -> Option<String> {
+        self.select_action_traced(view, civ_index).0
+    }
+
+    /// Mood from how the civilization compares to its rivals, no dice involved.
+    fn mood(&mut self, view: &AiView, civ_index: usize) -> Option<String> {
+        let me = view.players.get(civ_index)?;
+        let rivals = || {
+            view.players
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != civ_index)
+                .map(|(_, p)| p)
+        };
+        let mood = if rivals().all(|p| p.units <= me.units) && me.units > 0 {
+            "prépare une offensive"
+        } else if rivals().any(|p| p.units > me.units) {
+            "prépare ses défenses"
+        } else if rivals().all(|p| p.resources <= me.resources) {
+            "amasse des richesses"
+        } else {
+            "observe ses voisins"
+        };
+        Some(mood.to_string())
+    }
+
+    /// Give every candidate a uniform random roll and play the highest one, so the trace shows the actual rolls.
+    fn select_action_traced(
+        &mut self,
+        view: &AiView,
+        civ_index: usize,
+    ) -> (Option<String>, Option<DecisionTrace>) {
+        let mut candidates: Vec<ScoredAction> = Self::candidate_actions(view, civ_index)
+            .into_iter()
+            .map(|action| ScoredAction {
+                action,
+                value: self.rng.random::<f64>(),
+                reason: "uniform random roll".to_string(),
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.value.total_cmp(&a.value));
 
-        let idx = self.rng.gen_range(0..actions.len());
-        Some(actions.swap_remove(idx))
+        let chosen = candidates
+            .first()
+            .map_or_else(|| "end".to_string(), |c| c.action.clone());
+        let trace = DecisionTrace {
+            civ_index,
+            turn: view.turn,
+            chosen: Some(chosen.clone()),
+            candidates,
+        };
+        (Some(chosen), Some(trace))
     }
 
     fn select_popup_input(
@@ -100,7 +332,7 @@ impl Ai for RandomAi {
             // no choices, return empty input
             String::new()
         } else {
-            let idx = self.rng.gen_range(0..popup.choices.len());
+            let idx = self.rng.random_range(0..popup.choices.len());
             // return 1-based index as string
             (idx + 1).to_string()
         }
@@ -115,18 +347,66 @@ pub struct Game {
     ui_config: UiConfig,
     // One AI slot per civilization; None means human / not driven by AI.
     ais: Vec<Option<Box<dyn Ai>>>,
+    // Actions played by the current AI this turn, for the safety cap
+    ai_actions_done: usize,
+    // JSON configuration the game was loaded from, kept for replays
+    config_source: Option<String>,
+    // Replay being recorded, if any
+    replay: Option<replay::ReplayRecorder>,
+    // Time-travel debugger, enabled by --debug
+    debugger: Option<debugger::Debugger>,
+    // Play-by-mail session, enabled by --pbem
+    pbem: Option<pbem::Session>,
+    // Base seed of the random AIs, set by --ai-seed
+    ai_seed: Option<u64>,
+    // AI answering the `suggest` action, the heuristic one when None
+    advisor: Option<Box<dyn Ai>>,
+    // Keyboard shortcuts, chords included
+    #[cfg(feature = "tui")]
+    input: input::InputPipeline,
+    // Keys pressed during an AI turn, replayed once a human has the turn
+    #[cfg(feature = "tui")]
+    held_keys: input::KeyQueue,
 }
 
 /// Compact player statistics hiding implementation details. Encapsulation prevents AI coupling to internals.
 #[derive(Clone)]
+#[non_exhaustive]
 pub struct AiPlayerView {
     pub name: String,
+    /// Public persona of the city, as shown to the players
+    pub persona: Option<String>,
     pub resources: i32,
     pub buildings: usize,
     pub units: usize,
+    /// Outstanding debt, interest included
+    pub debt: u32,
+    /// Names of the finished buildings
+    pub owned_buildings: Vec<String>,
+    /// Building slots neither built nor under construction
+    pub free_slots: usize,
+    /// Favor this player holds toward the player whose turn it is
+    pub favor: u32,
+    /// Technologies this player may start researching
+    pub techs: Vec<String>,
+    /// Turns an army of the player whose turn it is takes to reach this city, None when the
+    /// terrain blocks the way or for that player itself
+    pub travel_turns: Option<u32>,
+    /// Whether the player whose turn it is explored this city. Under the fog of war, the
+    /// resources, debt, buildings, units, power, income, persona, favor, technologies and travel
+    /// turns of unexplored cities are left empty.
+    pub scouted: bool,
+    /// Military power of the city, as `GameState::calculate_city_power` counts it
+    pub power: i32,
+    /// Resources earned per turn by the buildings, upkeep and maintenance paid
+    pub income: i32,
+    /// Units of the armies marching on the city that the player whose turn it is knows of: all
+    /// those marching on its own city, its own armies, and those it sighted under the fog of war
+    pub incoming: u32,
 }
 
 /// Immutable state snapshot for AIs. Cloneable for thread-safe async backend communication.
+#[non_exhaustive]
 pub struct AiView {
     pub turn: i32,
     pub player_turn: usize,
@@ -134,24 +414,60 @@ pub struct AiView {
     pub buildings: Vec<String>,
     pub units: Vec<String>,
     pub seed: String,
+    /// Favor needed with every surviving player for a diplomatic victory
+    pub favor_threshold: u32,
+    /// Enemy armies the player whose turn it is knows of: under the fog of war, where it last
+    /// saw them in the last turns
+    pub sightings: Vec<fog::Sighting>,
+    /// How hopeless the position of the player whose turn it is has become, from 0 to 100, as
+    /// worked out by `surrender::desperation`
+    pub desperation: u32,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
+    /// Default game, with default preferences: the front-end passes the user's through
+    /// `set_preferences`, so games built by the library never depend on files of the host.
     pub fn new() -> Self {
-        let state = GameState::new();
+        let preferences = Preferences::default();
+        let mut state = GameState::new();
+        state.record_metrics();
         let mut ais: Vec<Option<Box<dyn Ai>>> = Vec::new();
         ais.resize_with(state.civilizations.len(), || None);
         Self {
             state,
             ui_state: UiState::Normal,
             ui_config: UiConfig {
-                color: ratatui::style::Color::Rgb(255, 255, 255),
+                color: Color::Rgb(255, 255, 255),
+                preferences,
+                turn_color: None,
+                pending_keys: None,
+                held_keys_notice: None,
             },
             ais,
+            ai_actions_done: 0,
+            config_source: None,
+            replay: None,
+            debugger: None,
+            pbem: None,
+            ai_seed: None,
+            advisor: None,
+            #[cfg(feature = "tui")]
+            input: input::InputPipeline::default(),
+            #[cfg(feature = "tui")]
+            held_keys: input::KeyQueue::default(),
         }
     }
 
-    /// Load from JSON with rich error context propagation. Delegates to from_string for DRY parsing logic.
+    /// Load from JSON with rich error context propagation. Delegates to `from_string` for DRY parsing logic.
+    ///
+    /// # Errors
+    /// Fails when the file cannot be read or its configuration does not load.
     pub fn from_file(config_path: &str) -> anyhow::Result<Self> {
         // Read file
         let contents = std::fs::read_to_string(config_path)
@@ -161,13 +477,26 @@ impl Game {
     }
 
     /// Parse JSON using serde for zero-boilerplate deserialization. Section-based architecture enables modular configs.
+    ///
+    /// # Errors
+    /// Fails when the JSON is malformed, holds numbers out of range, or describes an unplayable
+    /// game.
     pub fn from_string(config_string: &str) -> anyhow::Result<Self> {
-        // Parse JSON into AST model
-        let model: crate::ast::Model =
+        // Parse JSON once, check its numbers, then build the AST model from it
+        let value: serde_json::Value =
             serde_json::from_str(config_string).context("failed to parse config JSON")?;
+        limits::check_numbers(&value, "config")?;
+        let model: crate::ast::Model =
+            serde_json::from_value(value).context("failed to parse config JSON")?;
 
         // Start from default game state
         let mut game = Game::new();
+        game.state.config_hash = utils::hash_tmb(config_string);
+        game.config_source = Some(config_string.to_string());
+
+        let mut puzzle = None;
+        let mut tutorial = None;
+        let mut civ_traits = None;
 
         // Walk sections and apply relevant settings (only Game section is needed for now)
         for section in model.sections {
@@ -177,124 +506,289 @@ impl Game {
                     game.ui_config.color = str_to_color(&g.ui_color);
 
                     // map settings
-                    let map = map::GameMap::new(
-                        g.seed.clone().unwrap_or("pokemon".into()),
+                    limits::check_map_size(g.map_x as usize, g.map_y as usize)?;
+                    let seed = g.seed.clone().unwrap_or("pokemon".into());
+                    game.state.map = map::GameMap::generate(
+                        seed,
                         g.map_x as usize,
                         g.map_y as usize,
+                        map::Biomes::from_game(&g),
+                        map::MapShape::from_game(&g),
+                        matches!(g.wrap, Some(crate::ast::Toggle::on)),
                     );
-                    game.state.map = map;
 
                     // current turn
                     game.state.turn = g.current_turn.cast_signed();
+
+                    load_rules(&mut game.state, &g);
+                }
+                crate::ast::Section::BuildingDefArray(bda) => game.state.buildings = bda.buildings,
+                crate::ast::Section::UnitDefArray(uda) => game.state.units = uda.units,
+                crate::ast::Section::TechDefArray(tda) => game.state.techs = tda.techs,
+                crate::ast::Section::Regions(regions) => game.state.regions = Some(regions),
+                crate::ast::Section::MapEvents(events) => game.state.map_events = events.events,
+                crate::ast::Section::Relations(relations) => {
+                    game.state.relations = relations.relations;
                 }
-                crate::ast::Section::BuildingDefArray(bda) => {
-                    game.state.buildings = bda.buildings;
+                crate::ast::Section::War(terms) => game.state.war = war::War::from_terms(&terms),
+                crate::ast::Section::Raiders(terms) => {
+                    game.state.raiders = Some(raiders::Raiders::from_terms(&terms));
                 }
-                crate::ast::Section::UnitDefArray(uda) => {
-                    game.state.units = uda.units;
+                crate::ast::Section::Surrender(terms) => {
+                    game.state.surrender = Some(surrender::Surrender::from_terms(&terms));
                 }
                 crate::ast::Section::Cities(cities) => {
                     // Load cities into civilizations
-                    game.state.civilizations = cities
-                        .cities
-                        .into_iter()
-                        .map(|city| state::Civilization {
-                            resources: state::Resources { ressources: 100 },
-                            city,
-                            alive: true,
-                            constructions: Vec::new(),
-                            recruitments: Vec::new(),
-                        })
-                        .collect();
+                    game.state.civilizations =
+                        cities.cities.into_iter().map(found_civilization).collect();
                     // Ensure AI slots match civilizations
-                    game.ais = Vec::new();
-                    game.ais
-                        .resize_with(game.state.civilizations.len(), || None);
+                    game.ais = std::iter::repeat_with(|| None)
+                        .take(game.state.civilizations.len())
+                        .collect();
                 }
                 crate::ast::Section::VictoryConditions(vc) => {
                     game.state.nb_turns = vc.nb_turns;
                     game.state.resources_spent = vc.resources_spent;
+                    game.state.favor_threshold =
+                        vc.favor_threshold.unwrap_or(game.state.favor_threshold);
+                }
+                crate::ast::Section::Tutorial(t) => tutorial = Some(t.steps),
+                crate::ast::Section::Scoring(weights) => game.state.scoring = Some(weights),
+                crate::ast::Section::TurnRules(rules) => {
+                    game.state.end_turn_rules = rules.block_end_if;
+                    game.state.action_points = rules.action_points;
                 }
+                crate::ast::Section::Puzzle(p) => puzzle = Some(p),
+                crate::ast::Section::Traits(t) => civ_traits = Some(t.traits),
             }
         }
 
+        limits::check_cities(&game.state)?;
+        regions::check(&game.state)?;
+        terraform::check(&game.state)?;
+        diplomacy::open_relations(&mut game.state)?;
+        fog::update(&mut game.state);
+        for civ in 0..game.state.civilizations.len() {
+            turn_rules::refill_action_points(&mut game.state, civ);
+        }
+        // Techs add themselves to the prerequisites of what they unlock
+        tech::resolve(&mut game.state)?;
+        game.state.index_defs();
+        // Traits reference cities, buildings and units, so resolve them once every section is loaded
+        if let Some(civ_traits) = civ_traits {
+            traits::resolve(&mut game.state, civ_traits)?;
+        }
+        if let Some(weights) = &game.state.scoring {
+            scoring::check(weights, &game.state)?;
+        }
+
+        // Puzzle rules reference cities, so resolve them once every section is loaded
+        if let Some(rules) = puzzle {
+            game.state.puzzle = Some(victory::PuzzleState::new(rules, &game.state)?);
+        }
+        if let Some(steps) = tutorial {
+            game.state.tutorial = Some(tutorial::TutorialState::new(steps, &game.state));
+        }
+        game.state.record_metrics();
+
         Ok(game)
     }
 
+    /// Play the game in `terminal` until the player quits.
+    ///
+    /// # Errors
+    /// Fails when the terminal cannot be drawn or read.
+    #[cfg(feature = "tui")]
     pub fn run(
         &mut self,
         terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>,
     ) -> std::io::Result<()> {
+        self.ui_config.turn_color = self
+            .state
+            .current_civ()
+            .ok()
+            .map(|c| utils::readable_color(str_to_color(&c.city.color)));
+        // a chord left waiting runs the binding of its keys so far
+        if let Some(command) = self.input.expire(std::time::Instant::now()) {
+            self.run_command(command);
+        }
+        self.ui_config.pending_keys = self.input.hint();
+        if !self.held_keys.is_empty() {
+            self.ui_config.pending_keys =
+                Some(format!("{} touche(s) en attente", self.held_keys.len()));
+        }
+        // informational popups close by themselves when the preferences ask for it
+        let timeout = self.ui_config.preferences.popup_timeout;
+        if timeout > 0
+            && self
+                .state
+                .expire_popup(std::time::Duration::from_secs(timeout))
+        {
+            self.ui_state = self.popup_state(false);
+        }
+        // the turn changed hands: start the banner telling whose turn it is
+        if self
+            .state
+            .turn_banner
+            .is_none_or(|(civ, _)| civ != self.state.player_turn)
+        {
+            self.state.turn_banner = Some((self.state.player_turn, std::time::Instant::now()));
+        }
         terminal.draw(|frame| draw_ui(frame, &mut self.state, &self.ui_config))?;
         Ok(())
     }
 
+    #[cfg(feature = "tui")]
     pub fn handle_key(&mut self, key: crossterm::event::KeyEvent) {
-        use crossterm::event::KeyCode;
+        self.ui_config.held_keys_notice = None;
+        // shortcuts go through the input pipeline, text entry and menus take the keys as typed
+        let bindings = if self.state.game_over {
+            input::GAME_OVER
+        } else {
+            match self.ui_state {
+                UiState::Normal => input::NORMAL,
+                UiState::CameraMode => input::CAMERA,
+                _ => return self.handle_entry_key(key),
+            }
+        };
+        for command in self
+            .input
+            .feed(bindings, input::Key::of(key), std::time::Instant::now())
+        {
+            self.run_command(command);
+        }
+    }
 
-        // If the game is over, prevent game actions but still allow zoom and entering camera mode.
-        if self.state.game_over {
-            match key.code {
-                KeyCode::Char('z') | KeyCode::Char('Z') => {
-                    self.state.cycle_zoom();
-                    return;
-                }
-                KeyCode::Char('v') | KeyCode::Char('V') => {
-                    // allow entering camera mode to move around
-                    self.state.toggle_camera_mode();
-                    self.ui_state = UiState::CameraMode;
-                    return;
-                }
-                _ => {
-                    // ignore all other keys when game is over
-                    return;
-                }
+    /// Hold a key pressed while an AI has the turn, to replay it once a human has the turn.
+    #[cfg(feature = "tui")]
+    pub fn hold_key(&mut self, key: crossterm::event::KeyEvent) {
+        self.held_keys.hold(key);
+    }
+
+    /// Replay the keys held during the last AI turn, now that a human has the turn. They are
+    /// discarded when a popup opened in the meantime, as they were not meant to answer it.
+    /// Returns the notice left in the status bar when keys were discarded or dropped.
+    #[cfg(feature = "tui")]
+    pub fn release_held_keys(&mut self) -> Option<String> {
+        if self.held_keys.is_empty() {
+            return None;
+        }
+        let (keys, dropped) = self.held_keys.release();
+        let notice = if self.state.popup.is_some() {
+            log::info!(
+                "Discarding {} keys held during the AI turn: a popup opened",
+                keys.len()
+            );
+            Some(format!(
+                "{} touche(s) ignorée(s): une fenêtre s'est ouverte",
+                keys.len() + dropped
+            ))
+        } else {
+            for key in keys {
+                self.handle_key(key);
+            }
+            (dropped > 0)
+                .then(|| format!("{dropped} touche(s) ignorée(s): trop de touches en attente"))
+        };
+        self.ui_config.held_keys_notice.clone_from(&notice);
+        notice
+    }
+
+    /// Run the command of a keyboard shortcut.
+    #[cfg(feature = "tui")]
+    fn run_command(&mut self, command: input::Command) {
+        use input::Command;
+
+        match command {
+            // enter seed editing mode
+            Command::EditSeed if !self.state.seed_locked => {
+                self.state.toggle_seed_edit();
+                self.ui_state = UiState::EditingSeed;
+            }
+            // Pick random seed
+            Command::RandomSeed if !self.state.seed_locked => {
+                self.state.map = self.state.map.reseeded(rand::random::<u64>().to_string());
+                self.record(replay::Input::Seed(self.state.map.seed.clone()));
+            }
+            Command::ToggleCamera => {
+                self.state.toggle_camera_mode();
+                self.ui_state = if self.state.camera_mode {
+                    UiState::CameraMode
+                } else {
+                    UiState::Normal
+                };
+            }
+            Command::MoveCamera(dx, dy) => self.state.move_camera(dx, dy),
+            // jump to the next region of the map
+            Command::NextRegion => regions::focus_next_region(&mut self.state),
+            Command::GotoCity => self.state.focus_city(self.state.player_turn),
+            // accessibility settings, persisted in the user preferences
+            Command::HighContrast => {
+                self.ui_config.preferences.high_contrast ^= true;
+                self.save_preferences();
+            }
+            Command::ReducedMotion => {
+                self.ui_config.preferences.reduced_motion ^= true;
+                self.save_preferences();
+            }
+            Command::LargePopups => {
+                self.ui_config.preferences.large_popups ^= true;
+                self.save_preferences();
+            }
+            // toggle the history graphs panel
+            Command::Graphs => self.state.show_graphs ^= true,
+            // dismiss the summary of the last live reload
+            Command::DismissChanges => self.state.config_changes.clear(),
+            // toggle the dynamic difficulty panel
+            Command::Difficulty if self.state.difficulty.is_some() => {
+                self.state.show_difficulty ^= true;
             }
+            // toggle the event log panel, opening it reads the pending events
+            Command::Events => {
+                self.state.show_events ^= true;
+                self.state.events_scroll = 0;
+            }
+            // toggle the possible actions panel
+            Command::ActionsPanel => self.state.show_actions ^= true,
+            // toggle the textual description panel
+            Command::TextMode => self.state.toggle_text_mode(),
+            Command::Scroll(delta) if self.state.text_mode => self.state.scroll_descriptions(delta),
+            Command::Scroll(delta) if self.state.show_events => self.state.scroll_events(delta),
+            // open the action menu, browsed with the arrows
+            Command::ActionMenu => {
+                self.state.action_menu = Some(action_menu::ActionMenu::default());
+                self.ui_state = UiState::ActionMenu;
+            }
+            // start typing an action
+            Command::TypeAction => {
+                self.state.start_action_input();
+                self.ui_state = UiState::ActionEditing;
+            }
+            Command::Zoom => self.state.cycle_zoom(),
+            // photo mode: export the map viewport as ANSI art and ASCII
+            Command::Photo if self.state.popup.is_none() => self.take_photo(),
+            Command::WriteMap => self.write_map(),
+            // the game over popup gives way to the end-of-game statistics
+            Command::ClosePopup if self.state.popup.is_some() => self.state.close_popup(),
+            Command::EndScreen => self.state.show_end_screen ^= true,
+            _ => {}
         }
+    }
+
+    /// Keys of the text inputs, the action menu and popups.
+    #[cfg(feature = "tui")]
+    fn handle_entry_key(&mut self, key: crossterm::event::KeyEvent) {
+        use crossterm::event::KeyCode;
 
         match self.ui_state {
-            UiState::Normal => {
-                match key.code {
-                    // enter seed editing mode
-                    KeyCode::Char('s') => {
-                        self.state.toggle_seed_edit();
-                        self.ui_state = UiState::EditingSeed;
-                    }
-                    // Pick random seed
-                    KeyCode::Char('r') => {
-                        self.state.map =
-                            map::GameMap::new_random(self.state.map.width, self.state.map.height);
-                    }
-                    KeyCode::Char('v' | 'V') => {
-                        self.state.toggle_camera_mode();
-                        self.ui_state = UiState::CameraMode;
-                    }
-                    KeyCode::Char('a') => {
-                        // start typing an action
-                        self.state.start_action_input();
-                        self.ui_state = UiState::ActionEditing;
-                    }
-                    KeyCode::Char('z' | 'Z') => {
-                        self.state.cycle_zoom();
-                    }
-                    KeyCode::Char('w') => {
-                        // Write map to file
-                        let filename = format!("map_{}.txt", self.state.map.seed);
-                        // Open file and get map string
-                        let map_string = self.state.map.to_string();
-                        write_to_file(&filename, &map_string).expect("TODO: panic message");
-                    }
-                    _ => {
-                        // other global key handling could go here
-                    }
-                }
-            }
+            UiState::Normal | UiState::CameraMode => {}
             UiState::EditingSeed => {
                 match key.code {
                     // submit seed and exit editing
                     KeyCode::Enter => {
                         // GameState::submit_seed already clears seed_editing
                         self.state.submit_seed();
+                        self.record(replay::Input::Seed(self.state.map.seed.clone()));
                         self.ui_state = UiState::Normal;
                     }
                     // cancel editing with Esc (stop editing, don't change seed)
@@ -316,42 +810,26 @@ impl Game {
                     _ => {}
                 }
             }
-            UiState::CameraMode => {
+            UiState::ActionEditing => {
                 match key.code {
-                    // exit camera mode
-                    KeyCode::Char('v' | 'V') | KeyCode::Esc => {
-                        self.state.toggle_camera_mode();
+                    KeyCode::Enter if self.state.annotating => {
+                        let input = std::mem::take(&mut self.state.action_input);
+                        self.state.annotating = false;
+                        self.state.action_editing = false;
+                        self.annotate(&input);
                         self.ui_state = UiState::Normal;
                     }
-                    // camera movement
-                    KeyCode::Char('z' | 'Z') => {
-                        self.state.move_camera(0, -1);
-                    }
-                    KeyCode::Char('s' | 'S') => {
-                        self.state.move_camera(0, 1);
-                    }
-                    KeyCode::Char('q' | 'Q') => {
-                        self.state.move_camera(-1, 0);
-                    }
-                    KeyCode::Char('d' | 'D') => {
-                        self.state.move_camera(1, 0);
-                    }
-                    _ => {}
-                }
-            }
-            UiState::ActionEditing => {
-                match key.code {
                     KeyCode::Enter => {
                         // submit action, may open a popup
-                        let opened = self.state.submit_action();
-                        self.ui_state = if opened {
-                            UiState::PopupOpen
-                        } else {
-                            UiState::Normal
-                        };
+                        let text = self.state.action_input.clone();
+                        let opened = self.submit_text(&text, true);
+                        let finished = self.state.update_tutorial();
+                        let conquest = self.resolve_conquests();
+                        self.ui_state = self.popup_state(opened || finished || conquest);
                     }
                     KeyCode::Esc => {
                         self.state.action_editing = false;
+                        self.state.annotating = false;
                         self.ui_state = UiState::Normal;
                     }
                     KeyCode::Backspace => {
@@ -363,14 +841,19 @@ impl Game {
                     _ => {}
                 }
             }
+            UiState::ActionMenu => self.handle_menu_key(key.code),
             UiState::PopupOpen => match key.code {
                 KeyCode::Enter => {
+                    if let Some(p) = &self.state.popup {
+                        self.record(replay::Input::Popup(p.input.clone()));
+                    }
                     self.state.submit_popup();
-                    self.ui_state = UiState::Normal;
+                    let opened = self.state.update_tutorial() | self.resolve_conquests();
+                    self.ui_state = self.popup_state(opened);
                 }
                 KeyCode::Esc => {
                     self.state.close_popup();
-                    self.ui_state = UiState::Normal;
+                    self.ui_state = self.popup_state(false);
                 }
                 KeyCode::Backspace => {
                     if let Some(p) = &mut self.state.popup {
@@ -387,21 +870,150 @@ impl Game {
         }
     }
 
+    /// Keys of the action menu: arrows to browse it, Enter to pick an action.
+    #[cfg(feature = "tui")]
+    fn handle_menu_key(&mut self, code: crossterm::event::KeyCode) {
+        use crossterm::event::KeyCode;
+
+        let Some(mut menu) = self.state.action_menu.take() else {
+            self.ui_state = UiState::Normal;
+            return;
+        };
+        let chosen = match code {
+            KeyCode::Up => {
+                menu.move_cursor(&self.state, -1);
+                None
+            }
+            KeyCode::Down => {
+                menu.move_cursor(&self.state, 1);
+                None
+            }
+            KeyCode::Right | KeyCode::Enter => menu.select(&self.state),
+            // back to the categories, or close the menu from there
+            KeyCode::Left | KeyCode::Esc => {
+                if !menu.back() {
+                    self.ui_state = UiState::Normal;
+                    return;
+                }
+                None
+            }
+            _ => None,
+        };
+        // the chosen action goes through the same pipeline as a typed one
+        match chosen {
+            Some(action) => {
+                self.apply(action);
+            }
+            None => self.state.action_menu = Some(menu),
+        }
+    }
+
+    #[cfg(feature = "tui")]
+    fn save_preferences(&mut self) {
+        if let Err(e) = self.ui_config.preferences.save() {
+            log::warn!("Failed to save preferences: {e}");
+        }
+    }
+
     // ===== Headless / programmatic API =====
 
     /// Apply action programmatically reusing interactive pipeline. Boolean signals popup needing additional input.
     pub fn apply_action(&mut self, action: &str) -> bool {
         // prepare action input like interactive mode would
         log::info!("apply_action called: {action}");
-        self.state.action_input = action.to_string();
-        self.state.action_editing = true;
-        let opened = self.state.submit_action();
+        let opened = self.submit_text(action, false)
+            | self.state.update_tutorial()
+            | self.resolve_conquests();
         // update UI state to reflect popup if needed
-        self.ui_state = if opened {
-            UiState::PopupOpen
+        self.ui_state = self.popup_state(opened);
+        opened
+    }
+
+    /// Submit the text of an action of the player whose turn it is, its user aliases expanded.
+    /// Alias commands edit the aliases, saved in the preferences of the user when `persist`, and
+    /// are no input of the game. Returns whether a popup was opened.
+    fn submit_text(&mut self, text: &str, persist: bool) -> bool {
+        let command = text.trim().to_lowercase();
+        if command == "alias" || command.starts_with("alias ") || command.starts_with("unalias ") {
+            self.alias_command(&command, persist);
+            return true;
+        }
+        let input = self.canonical_input(text);
+        self.record(replay::Input::Action(input.clone()));
+        self.state.action_input = input;
+        self.state.action_editing = true;
+        self.state.submit_action() | self.resolve_save() | self.resolve_suggestion()
+    }
+
+    /// `text` with the user aliases and built-in shorthands expanded, as the canonical text of
+    /// its action when it parses: what replays, the debugger and play-by-email chains record, so
+    /// they play the same on another machine or after the aliases change.
+    fn canonical_input(&self, text: &str) -> String {
+        let typed = text.trim();
+        let lowered = typed.to_lowercase();
+        let expanded = aliases::expand(&self.ui_config.preferences.aliases, &lowered);
+        // the text typed by the player keeps its case (city names), unless an alias rewrote it
+        let source = if expanded == lowered {
+            typed.to_string()
         } else {
-            UiState::Normal
+            expanded
         };
+        action::Action::parse(&source).map_or(source, |a| a.to_string())
+    }
+
+    /// Handle `alias` (list), `alias <name> = <action>` (define) and `unalias <name>` (remove),
+    /// reporting the result in a popup. Changes are saved in the preferences when `persist`, from
+    /// the terminal interface only.
+    fn alias_command(&mut self, txt: &str, persist: bool) {
+        self.state.action_input.clear();
+        self.state.action_editing = false;
+        let user = &mut self.ui_config.preferences.aliases;
+        let message = if let Some(name) = txt.strip_prefix("unalias ") {
+            let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+            match user.remove(&name) {
+                Some(_) => format!("Alias '{name}' removed"),
+                None => format!("Unknown alias: {name}"),
+            }
+        } else if let Some(args) = txt.strip_prefix("alias ") {
+            match aliases::parse_definition(args) {
+                Some((name, action)) => {
+                    let message = format!("Alias '{name}' -> '{action}'");
+                    user.insert(name, action);
+                    message
+                }
+                None => "Usage: alias <name> = <action>, unalias <name>".to_string(),
+            }
+        } else {
+            let builtin = aliases::BUILTIN_SHORTHANDS
+                .iter()
+                .map(|(k, v)| format!("{k} -> {v} (built-in)"));
+            user.iter()
+                .map(|(k, v)| format!("{k} -> {v}"))
+                .chain(builtin)
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        if persist
+            && txt != "alias"
+            && let Err(e) = self.ui_config.preferences.save()
+        {
+            log::warn!("Failed to save aliases: {e}");
+        }
+        self.state.open_popup("Alias", &message, vec![]);
+    }
+
+    /// Submit a typed action for the player whose turn it is, like `apply_action` without going
+    /// through text. Returns whether a popup was opened.
+    pub fn apply(&mut self, action: action::Action) -> bool {
+        log::info!("apply called: {action:?}");
+        self.record(replay::Input::Action(action.to_string()));
+        let opened = self.state.submit(action)
+            | self.resolve_save()
+            | self.resolve_suggestion()
+            | self.state.update_tutorial()
+            | self.resolve_conquests();
+        self.ui_state = self.popup_state(opened);
         opened
     }
 
@@ -411,16 +1023,404 @@ impl Game {
             return false;
         }
         log::info!("submit_popup_input: {input}");
+        self.record(replay::Input::Popup(input.to_string()));
         if let Some(p) = &mut self.state.popup {
             p.input = input.to_string();
         }
         self.state.submit_popup();
-        self.ui_state = UiState::Normal;
+        let opened = self.state.update_tutorial() | self.resolve_conquests();
+        self.ui_state = self.popup_state(opened);
+        true
+    }
+
+    /// Interface state once the input was handled: popups stay open while one shows, be it
+    /// newly `opened` or one that was waiting.
+    fn popup_state(&self, opened: bool) -> UiState {
+        if opened || self.state.popup.is_some() {
+            UiState::PopupOpen
+        } else {
+            UiState::Normal
+        }
+    }
+
+    /// Write the save requested by a `save` action and tell the player where it went. Returns
+    /// true when a popup was opened.
+    fn resolve_save(&mut self) -> bool {
+        let Some(name) = self.state.pending_save.take() else {
+            return false;
+        };
+        let message = match saves::write(self, &name) {
+            Ok(path) => format!(
+                "Game saved to\n{}\nResume it with --load-save {name}",
+                path.display()
+            ),
+            Err(e) => format!("Could not save the game: {e:#}"),
+        };
+        self.state.open_popup("Save", &message, vec![]);
+        true
+    }
+
+    /// Ask the advisor for the action the player whose turn it is should play next, shown in a
+    /// popup offering to apply it. Returns true when a popup was opened.
+    fn resolve_suggestion(&mut self) -> bool {
+        if !std::mem::take(&mut self.state.pending_suggestion) {
+            return false;
+        }
+        let view = self.make_ai_view();
+        let suggestion = match &mut self.advisor {
+            Some(ai) => advisor::suggest(&self.state, &view, ai.as_mut()),
+            None => advisor::suggest(&self.state, &view, &mut advisor::HeuristicAi),
+        };
+        match suggestion {
+            Some((action, reason)) => {
+                log::info!(
+                    "Suggested to civ {}: {action} ({reason})",
+                    self.state.player_turn
+                );
+                let prompt = format!("Suggested action: {action}\nWhy: {reason}");
+                self.state.open_popup(
+                    "Suggestion",
+                    &prompt,
+                    vec![format!("Apply: {action}"), "Dismiss".to_string()],
+                );
+            }
+            None => {
+                self.state
+                    .open_popup("Suggestion", "The advisor has no action to suggest", vec![]);
+            }
+        }
         true
     }
 
+    /// Let the attacker of each won battle decide the fate of the defeated city, then the leader
+    /// answer each surrender offered to it and every civilization the gifts, treaties and peace
+    /// proposed to it, in order: AIs answer through `select_popup_input`, humans get a popup on their own
+    /// turn. Returns true when a popup was opened.
+    fn resolve_conquests(&mut self) -> bool {
+        loop {
+            // won battles first, then the surrenders offered to the strongest civilization
+            let (decider, popup) = if let Some(pending) = self.state.pending_conquests.first() {
+                (pending.attacker, conquest::popup(&self.state, pending))
+            } else if let Some(offer) = surrender::pending(&self.state) {
+                (offer.to, surrender::popup(&self.state, offer))
+            } else if let Some(proposal) = diplomacy::pending(&self.state) {
+                (proposal.to, diplomacy::popup(&self.state, proposal))
+            } else {
+                return false;
+            };
+            if self.ais.get(decider).is_some_and(Option::is_some) {
+                let view = self.make_ai_view();
+                let input = {
+                    let ai_mut = self.ais[decider].as_mut().unwrap();
+                    ai_mut.select_popup_input(&view, decider, &popup)
+                };
+                log::info!(
+                    "AI {} choice for civ {decider}: {input}",
+                    popup.title.to_lowercase()
+                );
+                self.record_as(decider, replay::Input::Popup(input.clone()));
+                let choice = input
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| popup.choices.get(i.wrapping_sub(1)))
+                    .or_else(|| {
+                        popup
+                            .choices
+                            .iter()
+                            .find(|c| c.to_lowercase().starts_with(&input.trim().to_lowercase()))
+                    })
+                    .unwrap_or(&popup.choices[0]);
+                match popup.title.as_str() {
+                    "Surrender" => surrender::resolve(&mut self.state, choice),
+                    "Proposal" => diplomacy::resolve(&mut self.state, choice),
+                    _ => conquest::resolve(&mut self.state, choice),
+                }
+            } else {
+                // reports give way to the question, which waits under another question
+                let asking = self
+                    .state
+                    .popup
+                    .as_ref()
+                    .is_some_and(|p| p.priority > state::PopupPriority::Info);
+                if self.state.player_turn != decider || asking {
+                    return false;
+                }
+                self.state.show_popup(popup);
+                return true;
+            }
+        }
+    }
+
+    /// Use the preferences of the user, loaded by the front-end from its data directory.
+    pub fn set_preferences(&mut self, preferences: Preferences) {
+        self.ui_config.preferences = preferences;
+    }
+
+    /// Seed AI civilizations without an `ai_seed` of their own from `base`, so that games between
+    /// random AIs play the same way on every run.
+    pub fn set_ai_seed(&mut self, base: u64) {
+        self.ai_seed = Some(base);
+    }
+
+    /// Seed of the AI of `civ_index`: the `ai_seed` of its city, else the base seed plus the index
+    /// of the civilization. None when neither is set, and the AI seeds itself at random.
+    pub fn ai_seed(&self, civ_index: usize) -> Option<u64> {
+        let civ = self.state.civilizations.get(civ_index)?;
+        civ.city
+            .ai_seed
+            .map(u64::from)
+            .or_else(|| self.ai_seed.map(|base| base.wrapping_add(civ_index as u64)))
+    }
+
+    /// Seeds of the AIs of every civilization, None for humans and unseeded AIs.
+    fn ai_seeds(&self) -> Vec<Option<u64>> {
+        self.state
+            .civilizations
+            .iter()
+            .enumerate()
+            .map(|(i, civ)| {
+                matches!(civ.city.player_type, crate::ast::PlayerType::AI)
+                    .then(|| self.ai_seed(i))
+                    .flatten()
+            })
+            .collect()
+    }
+
+    /// Start recording a replay of the game from its current position.
+    ///
+    /// # Errors
+    /// Fails when the replay file cannot be created.
+    pub fn start_recording(&mut self) -> anyhow::Result<()> {
+        let header = replay::ReplayHeader {
+            version: replay::REPLAY_VERSION,
+            config: self.config_source.clone(),
+            seed: self.state.map.seed.clone(),
+            ai_seeds: self.ai_seeds(),
+        };
+        self.replay = Some(replay::ReplayRecorder::create(&header)?);
+        Ok(())
+    }
+
+    /// Scale the income of the AIs to their power gap with the human, recalculated every
+    /// `interval` turns.
+    pub fn enable_dynamic_difficulty(&mut self, interval: u32) {
+        self.state.difficulty = Some(difficulty::DynamicDifficulty::new(interval));
+        difficulty::update(&mut self.state);
+    }
+
+    /// Start keeping state snapshots for the time-travel debugger.
+    pub fn enable_debugger(&mut self) {
+        self.debugger = Some(debugger::Debugger::new(replay::ReplayHeader {
+            version: replay::REPLAY_VERSION,
+            config: self.config_source.clone(),
+            seed: self.state.map.seed.clone(),
+            ai_seeds: self.ai_seeds(),
+        }));
+    }
+
+    /// Stop recording and complete the replay file. Returns its path, if a replay was recorded.
+    ///
+    /// # Errors
+    /// Fails when the replay cannot be completed or signed.
+    pub fn finish_recording(&mut self) -> anyhow::Result<Option<std::path::PathBuf>> {
+        self.replay
+            .take()
+            .map(replay::ReplayRecorder::finish)
+            .transpose()
+    }
+
+    /// Record an input of the player whose turn it is in the replay, if one is being recorded.
+    fn record(&mut self, input: replay::Input) {
+        self.record_as(self.state.player_turn, input);
+    }
+
+    fn record_as(&mut self, player: usize, input: replay::Input) {
+        if let Some(debugger) = &mut self.debugger {
+            debugger.record(player, &input, &self.state);
+        }
+        if let Some(session) = &mut self.pbem {
+            session.record(self.state.turn, player, &input);
+        }
+        if let Some(recorder) = &mut self.replay {
+            if recorder.is_new_turn(self.state.turn) {
+                let snapshot = saves::SavedGame::of_state(&self.state, None);
+                recorder.record(&replay::ReplayEvent {
+                    turn: self.state.turn,
+                    player,
+                    input: replay::Input::Snapshot(Box::new(snapshot)),
+                });
+            }
+            recorder.record(&replay::ReplayEvent {
+                turn: self.state.turn,
+                player,
+                input,
+            });
+        }
+    }
+
+    /// Rebuild a game from a replay, streaming its events. Inputs of AI civilizations are served
+    /// by `ReplayAi`s so the same code paths run as in the recorded game; `each` is called after
+    /// every input of a human player. The states recorded at each turn are compared with the
+    /// replayed game whenever the replay reaches them outside of an AI turn: mismatches are
+    /// reported in the log, and the recorded state is put back.
+    ///
+    /// # Errors
+    /// Fails when the replay cannot be read or one of its events cannot be played.
+    pub fn play_replay(path: &std::path::Path, each: impl FnMut(&Game)) -> anyhow::Result<Game> {
+        let (header, events) = replay::open(path)?;
+        let mut game = match &header.config {
+            Some(config) => Game::from_string(config)?,
+            None => Game::new(),
+        };
+        game.state.map = game.state.map.reseeded(header.seed);
+        let queues = game.replay_ais();
+        game.play_events(events, &queues, each)?;
+        Ok(game)
+    }
+
+    /// Register a `ReplayAi` for every AI civilization, returning the queues of recorded inputs
+    /// they play.
+    pub(crate) fn replay_ais(&mut self) -> Vec<Option<replay::InputQueue>> {
+        let queues: Vec<Option<replay::InputQueue>> = self
+            .state
+            .civilizations
+            .iter()
+            .map(|c| {
+                matches!(c.city.player_type, crate::ast::PlayerType::AI)
+                    .then(replay::InputQueue::default)
+            })
+            .collect();
+        for (i, queue) in queues.iter().enumerate() {
+            if let Some(queue) = queue {
+                self.register_ai(i, Box::new(replay::ReplayAi::new(queue.clone())));
+            }
+        }
+        queues
+    }
+
+    /// Play recorded `events` on the game, the inputs of AI civilizations going to their `queues`
+    /// (see `replay_ais`). `each` is called after every input of a human player.
+    pub(crate) fn play_events(
+        &mut self,
+        events: impl Iterator<Item = anyhow::Result<replay::ReplayEvent>>,
+        queues: &[Option<replay::InputQueue>],
+        mut each: impl FnMut(&Game),
+    ) -> anyhow::Result<()> {
+        let queue_of = |player: usize| queues.get(player).and_then(Option::as_ref);
+        let mut events = events.peekable();
+        loop {
+            // hand the AIs their inputs up to the next human one, then let them play
+            let mut batch_started = false;
+            while let Some(Ok(event)) = events.peek()
+                && let Some(queue) = queue_of(event.player)
+            {
+                if let replay::Input::Checksum(expected) = event.input
+                    && !batch_started
+                {
+                    self.check_replay_checksum(expected);
+                } else if let replay::Input::Snapshot(saved) = &event.input
+                    && !batch_started
+                {
+                    self.resync_replay(saved);
+                } else if let replay::Input::Annotate(note) = &event.input {
+                    // notes are shown before the AI turn they were placed in
+                    self.state.annotations.push(note.clone());
+                    each(self);
+                } else if let Ok(mut queue) = queue.lock() {
+                    batch_started = true;
+                    queue.push_back(event.input.clone());
+                }
+                events.next();
+            }
+            // every AI in a row plays its turn, as long as inputs of its own are left
+            while !self.state.game_over
+                && let Some(queue) = queue_of(self.state.player_turn)
+                && queue.lock().is_ok_and(|q| !q.is_empty())
+            {
+                let (player, turn) = (self.state.player_turn, self.state.turn);
+                self.run_ai_turn();
+                if (self.state.player_turn, self.state.turn) == (player, turn) {
+                    break;
+                }
+            }
+            let Some(event) = events.next() else {
+                break;
+            };
+            let event = event?;
+            // AIs answer the proposals of a human input right away: their answers come after it
+            while let Some(Ok(next)) = events.peek()
+                && matches!(next.input, replay::Input::Popup(_))
+                && let Some(queue) = queue_of(next.player)
+            {
+                if let Ok(mut queue) = queue.lock() {
+                    queue.push_back(next.input.clone());
+                }
+                events.next();
+            }
+            match event.input {
+                replay::Input::Action(action) => {
+                    self.apply_action(&action);
+                }
+                replay::Input::Popup(input) => {
+                    self.submit_popup_input(&input);
+                }
+                replay::Input::Step => self.step(),
+                replay::Input::Seed(seed) => {
+                    self.state.map = self.state.map.reseeded(seed);
+                    self.state.map_buffer_cache = None;
+                }
+                replay::Input::Checksum(expected) => {
+                    self.check_replay_checksum(expected);
+                    continue;
+                }
+                replay::Input::Snapshot(saved) => {
+                    self.resync_replay(&saved);
+                    continue;
+                }
+                replay::Input::Annotate(note) => self.state.annotations.push(note),
+                replay::Input::Mood(mood) => self.set_mood(event.player, &mood),
+            }
+            each(self);
+        }
+        Ok(())
+    }
+
+    fn check_replay_checksum(&self, expected: u32) {
+        if checksum::state_checksum(&self.state) != expected {
+            checksum::report_desync(&self.state, expected);
+        }
+    }
+
+    /// Compare the replayed game with the state `saved` at the start of a turn, and put that
+    /// state back when they drifted apart so the rest of the replay plays from it.
+    fn resync_replay(&mut self, saved: &saves::SavedGame) {
+        let expected = checksum::save_checksum(saved, self.state.config_hash);
+        if checksum::state_checksum(&self.state) == expected {
+            return;
+        }
+        checksum::report_desync(&self.state, expected);
+        match saved.clone().apply(&mut self.state) {
+            Ok(()) => log::warn!(
+                "Replay resynchronized on the state recorded at turn {} (player {})",
+                saved.turn,
+                saved.player_turn
+            ),
+            Err(e) => log::error!("Replay could not be resynchronized: {e:#}"),
+        }
+    }
+
+    /// Pass the turn without playing an action (headless `step` command), recorded in the replay.
+    pub fn pass_turn(&mut self) {
+        self.record(replay::Input::Step);
+        self.step();
+    }
+
     /// Advance to next player with automatic turn rollover. Modulo arithmetic ensures seamless civilization cycling.
     pub fn step(&mut self) {
+        if self.state.civilizations.is_empty() {
+            return;
+        }
         self.state.player_turn = (self.state.player_turn + 1) % self.state.civilizations.len();
         if self.state.player_turn == 0 {
             self.state.turn += 1;
@@ -447,16 +1447,94 @@ impl Game {
                     "resources": c.resources.ressources,
                     "buildings": c.city.buildings.elements.len(),
                     "units": c.city.units.units.len(),
+                    "debt": loans::debt(c),
+                    "morale": c.morale,
+                    "mood": c.mood,
                 })
             })
             .collect();
 
-        serde_json::json!({
+        let mut snapshot = serde_json::json!({
             "turn": self.state.turn,
             "player_turn": self.state.player_turn,
             "players": players,
             "seed": self.state.map.seed,
-        })
+        });
+        if self.state.game_over {
+            snapshot["game_over"] = serde_json::json!(true);
+            snapshot["winner"] = serde_json::json!(
+                self.state
+                    .winner
+                    .and_then(|w| self.state.civilizations.get(w))
+                    .map(|c| c.city.name.clone())
+            );
+        }
+        let notes: Vec<_> = self.state.notes_of_turn().collect();
+        if !notes.is_empty() {
+            snapshot["annotations"] = serde_json::json!(notes);
+        }
+        if let Some(difficulty) = &self.state.difficulty {
+            snapshot["difficulty"] = serde_json::json!(
+                difficulty
+                    .adjustments
+                    .iter()
+                    .map(|a| serde_json::json!({ "civ": a.civ, "percent": a.percent }))
+                    .collect::<Vec<_>>()
+            );
+        }
+        snapshot
+    }
+
+    /// Export the map viewport of the last frame and tell the player where it was written.
+    #[cfg(feature = "tui")]
+    fn take_photo(&mut self) {
+        let message = match map_view::photo(&self.state, &self.ui_config) {
+            Ok((ansi, ascii)) => format!(
+                "Map saved to\n{}\n{}\nShow the colored one with `cat` in a terminal.",
+                ansi.display(),
+                ascii.display()
+            ),
+            Err(e) => format!("Could not save the map: {e:#}"),
+        };
+        self.state.open_popup("Photo", &message, vec![]);
+        self.ui_state = UiState::PopupOpen;
+    }
+
+    /// Write the whole map as text to the output directory, telling where or why it failed.
+    #[cfg(feature = "tui")]
+    fn write_map(&mut self) {
+        let filename = format!("map_{}.txt", self.state.map.seed);
+        let message = match utils::write_to_file(&filename, &self.state.map.to_string()) {
+            Ok(()) => format!("Map saved to\n{}", utils::output_path(&filename).display()),
+            Err(e) => format!("Could not save the map: {e:#}"),
+        };
+        self.state.open_popup("Map", &message, vec![]);
+        self.ui_state = UiState::PopupOpen;
+    }
+
+    /// Start typing a spectator note (TUI, while the AI playback is paused).
+    pub fn start_annotation(&mut self) {
+        self.state.annotating = true;
+        self.state.start_action_input();
+        self.ui_state = UiState::ActionEditing;
+    }
+
+    /// Place a spectator note on the current turn, saved in the replay being recorded.
+    pub fn annotate(&mut self, input: &str) {
+        let note = annotations::Annotation::parse(
+            self.state.turn,
+            input,
+            self.state.map.width,
+            self.state.map.height,
+        );
+        log::info!("Annotation placed: {note:?}");
+        self.record(replay::Input::Annotate(note.clone()));
+        self.state.annotations.push(note);
+    }
+
+    /// AI answering the `suggest` action of human players, instead of the heuristic advisor.
+    pub fn set_advisor(&mut self, ai: Box<dyn Ai>) {
+        self.advisor = Some(ai);
     }
 
     /// Register AI with automatic Vec growth preventing index panics. Box<dyn Ai> enables heterogeneous mixing.
@@ -469,46 +1547,151 @@ impl Game {
         log::info!("Registered AI for civ {civ_index}");
     }
 
+    /// Add an end condition checked after the DSL ones each time the game looks for its end, for
+    /// programs embedding the engine. A closure `Fn(&GameState) -> Option<Outcome>` works too.
+    pub fn add_victory_condition(&mut self, condition: impl victory::VictoryCondition + 'static) {
+        self.state
+            .custom_victories
+            .push(std::sync::Arc::new(condition));
+        log::info!(
+            "Registered custom victory condition ({} in total)",
+            self.state.custom_victories.len()
+        );
+    }
+
     /// Enumerate valid actions from game rules. Lowercase normalization ensures parser compatibility.
     pub fn ai_possible_actions(&self, civ_index: usize) -> Vec<String> {
         let mut actions: Vec<String> = Vec::new();
         actions.push("end".to_string());
-        for b in &self.state.buildings {
-            actions.push(format!("build {}", b.name.to_lowercase()));
-        }
-        for u in &self.state.units {
-            actions.push(format!("hire {}", u.name.to_lowercase()));
+        // builds, hires and attacks cost an action point in the paced mode
+        if let Ok(civ) = self.state.civ(civ_index)
+            && turn_rules::check_action_point(&self.state, civ_index).is_ok()
+        {
+            for b in self
+                .state
+                .buildings
+                .iter()
+                .filter(|b| civ.allows_building(&b.name))
+            {
+                actions.push(format!("build {}", b.name.to_lowercase()));
+            }
+            for u in self.state.units.iter().filter(|u| civ.allows_unit(&u.name)) {
+                actions.push(format!("hire {}", u.name.to_lowercase()));
+            }
+            for (i, _) in self.state.reachable_targets(civ_index) {
+                actions.push(format!("attack {}", self.state.city_name(i).to_lowercase()));
+            }
+            for t in tech::available(&self.state, civ_index) {
+                actions.push(format!("research {}", t.name.to_lowercase()));
+            }
         }
+        actions.push("loan <amount>".to_string());
         for (i, civ) in self.state.civilizations.iter().enumerate() {
-            if i != civ_index {
-                actions.push(format!("attack {}", civ.city.name.to_lowercase()));
+            if i != civ_index && civ.alive {
+                let name = civ.city.name.to_lowercase();
+                actions.push(format!("gift {name} <amount>"));
+                actions.push(format!("trade {name}"));
+                actions.push(format!("pact {name}"));
+                actions.push(format!("scout {name}"));
+                if war::at_war(&self.state, civ_index, i) {
+                    actions.push(format!("peace {name}"));
+                }
             }
         }
-        log::debug!("ai_possible_actions for civ {} => {} actions", civ_index, actions.len());
+        for b in self
+            .state
+            .civ(civ_index)
+            .map_or(&[][..], |c| &c.city.buildings.elements)
+        {
+            actions.push(format!("demolish {}", b.id_building.to_lowercase()));
+        }
+        log::debug!(
+            "ai_possible_actions for civ {} => {} actions",
+            civ_index,
+            actions.len()
+        );
         actions
     }
 
     /// Build minimal AI view snapshot. Aggregates distributed state, exposing only decision-relevant data.
     pub fn make_ai_view(&self) -> AiView {
+        let targets = self.state.reachable_targets(self.state.player_turn);
+        let viewer = self.state.player_turn;
+        let sightings = fog::sightings(&self.state, viewer);
         let players = self
             .state
             .civilizations
             .iter()
-            .map(|c| AiPlayerView {
-                name: c.city.name.clone(),
-                resources: c.resources.ressources,
-                buildings: c.city.buildings.elements.len(),
-                units: c.city.units.units.len(),
+            .enumerate()
+            .map(|(i, c)| {
+                let scouted = i == viewer
+                    || fog::explored(
+                        &self.state,
+                        viewer,
+                        (c.city.x.cast_signed(), c.city.y.cast_signed()),
+                    );
+                let mut player = AiPlayerView {
+                    name: c.city.name.clone(),
+                    persona: c.city.persona.clone(),
+                    resources: c.resources.ressources,
+                    buildings: c.city.buildings.elements.len(),
+                    units: c.city.units.units.len(),
+                    debt: loans::debt(c),
+                    owned_buildings: c
+                        .city
+                        .buildings
+                        .elements
+                        .iter()
+                        .map(|b| b.id_building.clone())
+                        .collect(),
+                    free_slots: (c.city.nb_slots_buildings as usize)
+                        .saturating_sub(c.city.buildings.elements.len() + c.constructions.len()),
+                    favor: self.state.diplomacy.favor(i, viewer),
+                    techs: tech::available(&self.state, i)
+                        .iter()
+                        .map(|t| t.name.clone())
+                        .collect(),
+                    travel_turns: targets.iter().find(|(t, _)| *t == i).map(|(_, p)| p.turns),
+                    scouted,
+                    power: self.state.calculate_city_power(i),
+                    income: turn_rules::projected_income(&self.state, i)
+                        - upkeep::due(&self.state, i),
+                    incoming: self.known_incoming(i, &sightings),
+                };
+                if !scouted {
+                    player.resources = 0;
+                    player.buildings = 0;
+                    player.units = 0;
+                    player.debt = 0;
+                    player.owned_buildings.clear();
+                    player.free_slots = 0;
+                    player.power = 0;
+                    player.income = 0;
+                    player.persona = None;
+                    player.favor = 0;
+                    player.techs.clear();
+                    player.travel_turns = None;
+                }
+                player
             })
             .collect();
 
+        // only what the city of the current player may build and hire
+        let current = self.state.current_civ().ok();
         let buildings = self
             .state
             .buildings
             .iter()
+            .filter(|b| current.is_some_and(|c| c.allows_building(&b.name)))
             .map(|b| b.name.clone())
             .collect();
-        let units = self.state.units.iter().map(|u| u.name.clone()).collect();
+        let units = self
+            .state
+            .units
+            .iter()
+            .filter(|u| current.is_some_and(|c| c.allows_unit(&u.name)))
+            .map(|u| u.name.clone())
+            .collect();
 
         AiView {
             turn: self.state.turn,
@@ -517,105 +1700,240 @@ impl Game {
             buildings,
             units,
             seed: self.state.map.seed.clone(),
+            favor_threshold: self.state.favor_threshold,
+            sightings,
+            desperation: surrender::desperation(&self.state, viewer),
         }
     }
 
-    /// Execute AI actions for current player with safety cap preventing infinite loops. Handles popups automatically.
+    /// Units marching on the city of `civ_index` that the player whose turn it is knows of, given
+    /// the enemy armies it `sighted`.
+    fn known_incoming(&self, civ_index: usize, sighted: &[fog::Sighting]) -> u32 {
+        let viewer = self.state.player_turn;
+        let marching = |t: &&state::Travel| t.defender == civ_index && !t.returning();
+        if civ_index == viewer {
+            return self
+                .state
+                .travels
+                .iter()
+                .filter(marching)
+                .fold(0, |n, t| n.saturating_add(t.amount));
+        }
+        let own = self
+            .state
+            .travels
+            .iter()
+            .filter(marching)
+            .filter(|t| t.attacker == viewer)
+            .fold(0u32, |n, t| n.saturating_add(t.amount));
+        sighted
+            .iter()
+            .filter(|s| s.defender == civ_index && s.attacker != civ_index)
+            .fold(own, |n, s| n.saturating_add(s.amount))
+    }
+
+    /// Execute a single AI action for the current player, handling popups automatically. Returns
+    /// true while the same AI still has the turn, so the caller can pace, pause or step the turn.
     /// AI thinking flag managed by caller for UI updates before blocking operation.
-    pub fn run_ai_for_current_player(&mut self) {
+    ///
+    /// # Panics
+    /// Never: the AI of the current player is checked to be registered before it is used.
+    pub fn run_ai_for_current_player(&mut self) -> bool {
         // safety cap to avoid infinite loops from buggy AIs
         const MAX_ACTIONS: usize = 256;
-        let mut actions_done = 0usize;
-        let initial_player = self.state.player_turn;
 
-        // Check if current player is AI before starting
         let civ_idx = self.state.player_turn;
+
+        // if there is no AI registered for this civ, stop
         if civ_idx >= self.ais.len() || self.ais[civ_idx].is_none() {
-            self.state.ai_thinking = false;
-            return;
+            log::debug!("No AI registered for civ {civ_idx}");
+            return self.finish_ai_turn();
         }
-        
+
+        // Only run AI if the civilization is actually flagged AI in the city definition
         if let Some(civ) = self.state.civilizations.get(civ_idx) {
             use crate::ast::PlayerType;
             if !matches!(civ.city.player_type, PlayerType::AI) {
-                self.state.ai_thinking = false;
-                return;
+                log::debug!("Civ {civ_idx} is not marked as AI; skipping");
+                return self.finish_ai_turn();
             }
         } else {
-            self.state.ai_thinking = false;
-            return;
+            log::warn!("Civ {civ_idx} not found in state");
+            return self.finish_ai_turn();
         }
 
+        if self.ai_actions_done >= MAX_ACTIONS {
+            log::warn!("AI action loop reached MAX_ACTIONS ({MAX_ACTIONS}), forcing end turn");
+            self.step();
+            return self.finish_ai_turn();
+        }
 
-        loop {
-            if actions_done >= MAX_ACTIONS {
-                log::warn!("AI action loop reached MAX_ACTIONS ({MAX_ACTIONS}), forcing end turn");
-                self.step();
-                break;
-            }
-            let civ_idx = self.state.player_turn;
+        // build view snapshot
+        let view = self.make_ai_view();
 
-            // if there is no AI registered for this civ, stop
-            if civ_idx >= self.ais.len() {
-                log::debug!("No AI registered for civ {civ_idx} (out of range)");
-                break;
-            }
-            if self.ais[civ_idx].is_none() {
-                log::debug!("No AI registered for civ {civ_idx}");
-                break;
+        // the mood of the AI is refreshed at the start of its turn
+        if self.ai_actions_done == 0 {
+            let mood = self.ais[civ_idx].as_mut().unwrap().mood(&view, civ_idx);
+            if let Some(mood) = mood {
+                self.record(replay::Input::Mood(mood.clone()));
+                self.set_mood(civ_idx, &mood);
             }
+        }
 
-            // Only run AI if the civilization is actually flagged AI in the city definition
-            if let Some(civ) = self.state.civilizations.get(civ_idx) {
-                use crate::ast::PlayerType;
-                if !matches!(civ.city.player_type, PlayerType::AI) {
-                    log::debug!("Civ {civ_idx} is not marked as AI; skipping");
-                    break;
-                }
-            } else {
-                log::warn!("Civ {civ_idx} not found in state");
-                break;
-            }
+        // ask AI for action, keeping its explanation for the spectator debug panel
+        let (action_opt, trace) = {
+            let ai_mut = self.ais[civ_idx].as_mut().unwrap();
+            ai_mut.select_action_traced(&view, civ_idx)
+        };
+        if trace.is_some() {
+            self.state.last_decision = trace;
+        }
 
-            // Check if turn has changed from initial player (AI said "end" successfully)
-            if civ_idx != initial_player {
-                log::debug!("AI turn completed, player changed from {initial_player} to {civ_idx}");
-                break;
+        if let Some(action) = action_opt {
+            log::info!("AI selected action for civ {civ_idx}: {action}");
+            let opened = self.apply_action(&action);
+            // popups opened once the turn has passed belong to the next player
+            if opened
+                && self.state.player_turn == civ_idx
+                && let Some(popup) = &self.state.popup
+            {
+                log::info!("AI opened popup: {}", popup.title);
+                let popup_clone = popup.clone();
+                let view2 = self.make_ai_view();
+                let input = {
+                    let ai_mut = self.ais[civ_idx].as_mut().unwrap();
+                    ai_mut.select_popup_input(&view2, civ_idx, &popup_clone)
+                };
+                log::info!("AI popup input for civ {civ_idx}: {input}");
+                self.submit_popup_input(&input);
             }
+        } else {
+            log::info!("AI returned no action for civ {civ_idx}; ending turn");
+            self.record(replay::Input::Step);
+            self.step();
+            return self.finish_ai_turn();
+        }
+        self.ai_actions_done += 1;
 
-            // build view snapshot
-            let view = self.make_ai_view();
+        // Check if turn has changed (AI said "end" successfully)
+        if self.state.player_turn != civ_idx {
+            log::debug!(
+                "AI turn completed, player changed from {civ_idx} to {}",
+                self.state.player_turn
+            );
+            return self.finish_ai_turn();
+        }
+        true
+    }
 
-            // ask AI for action
-            let action_opt = {
-                let ai_mut = self.ais[civ_idx].as_mut().unwrap();
-                ai_mut.select_action(&view, civ_idx)
-            };
+    /// Play the whole turn of the current AI player at once (headless mode).
+    pub fn run_ai_turn(&mut self) {
+        while self.run_ai_for_current_player() {}
+    }
 
-            if let Some(action) = action_opt {
-                log::info!("AI selected action for civ {civ_idx}: {action}");
-                let opened = self.apply_action(&action);
-                if opened && let Some(popup) = &self.state.popup {
-                    log::info!("AI opened popup: {}", popup.title);
-                    let popup_clone = popup.clone();
-                    let view2 = self.make_ai_view();
-                    let input = {
-                        let ai_mut = self.ais[civ_idx].as_mut().unwrap();
-                        ai_mut.select_popup_input(&view2, civ_idx, &popup_clone)
-                    };
-                    log::info!("AI popup input for civ {civ_idx}: {input}");
-                    self.submit_popup_input(&input);
+    /// Play the game to its end through the programmatic API: AIs play their turns, and players
+    /// without an AI end theirs, picking the first choice of any popup left for them. Gives up
+    /// after `max_turns` turns when the victory conditions never end the game.
+    pub fn play_to_end(&mut self, max_turns: u32) {
+        let last_turn = self.state.turn.saturating_add(max_turns.cast_signed());
+        while !self.state.game_over && self.state.turn < last_turn {
+            let (player, turn) = (self.state.player_turn, self.state.turn);
+            self.run_ai_turn();
+            if self.state.game_over || (self.state.player_turn, self.state.turn) != (player, turn) {
+                continue;
+            }
+            match self.state.popup.as_ref().map(|p| p.choices.is_empty()) {
+                Some(false) => {
+                    self.submit_popup_input("1");
                 }
-            } else {
-                log::info!("AI returned no action for civ {civ_idx}; ending turn");
-                self.step();
-                break;
+                Some(true) => self.state.close_popup(),
+                // the turn rules may refuse to end the turn, pass it then
+                None if self.apply(action::Action::EndTurn) => {
+                    self.state.close_popup();
+                    self.pass_turn();
+                }
+                None => {}
             }
-
-            actions_done += 1;
         }
-        
+    }
+
+    /// Reset the per-turn AI bookkeeping. Always returns false for `run_ai_for_current_player`.
+    fn finish_ai_turn(&mut self) -> bool {
+        self.ai_actions_done = 0;
         // Clear AI thinking flag when done
         self.state.ai_thinking = false;
+        false
+    }
+
+    /// Show `mood` next to the name of civilization `civ`, trimmed and cut to `MAX_MOOD_LEN`
+    /// characters. An empty mood clears it.
+    pub fn set_mood(&mut self, civ: usize, mood: &str) {
+        let Some(civ) = self.state.civilizations.get_mut(civ) else {
+            return;
+        };
+        let mood: String = mood.trim().chars().take(MAX_MOOD_LEN).collect();
+        civ.mood = (!mood.is_empty()).then_some(mood);
+    }
+
+    /// Pause or resume AI playback in the TUI.
+    pub fn toggle_ai_pause(&mut self) {
+        self.state.ai_paused = !self.state.ai_paused;
     }
+
+    /// Move AI playback one speed level faster or slower.
+    pub fn change_ai_speed(&mut self, faster: bool) {
+        let max = AI_PLAYBACK_DELAYS_MS.len() - 1;
+        self.state.ai_speed = if faster {
+            (self.state.ai_speed + 1).min(max)
+        } else {
+            self.state.ai_speed.saturating_sub(1)
+        };
+    }
+
+    /// Delay between two AI actions at the current playback speed.
+    pub fn ai_delay(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(AI_PLAYBACK_DELAYS_MS[self.state.ai_speed])
+    }
+}
+
+/// Civilization of a city of the `[cities]` section, as the game starts.
+fn found_civilization(city: crate::ast::City) -> state::Civilization {
+    state::Civilization {
+        resources: state::Resources {
+            ressources: 100,
+            spent: 0,
+        },
+        policy: policy::Policy::from_defaults(city.policy.as_ref()),
+        city,
+        alive: true,
+        constructions: Vec::new(),
+        recruitments: Vec::new(),
+        loans: Vec::new(),
+        morale: 100,
+        producers: std::collections::BTreeMap::new(),
+        action_points: 0,
+        modifiers: traits::Modifiers::default(),
+        mood: None,
+        researched: std::collections::BTreeSet::new(),
+        research: None,
+        waiting: Vec::new(),
+        disabled: std::collections::BTreeSet::new(),
+        captured: Vec::new(),
+        beaten_by: std::collections::BTreeSet::new(),
+        eliminated_at: None,
+    }
+}
+
+/// Apply the rules of the `[game]` section that only set a value of the state.
+fn load_rules(state: &mut GameState, g: &crate::ast::Game) {
+    // supply lines
+    state.supply_range = g.supply_range.unwrap_or(state.supply_range);
+    state.attrition = g.attrition.unwrap_or(state.attrition).min(100);
+    state.build_queue = g.build_queue.unwrap_or(state.build_queue).max(1);
+    state.cancel_refund = g.cancel_refund.unwrap_or(state.cancel_refund).min(100);
+    state.stacks = stacks::StackLimits::from_game(g);
+    state.fog = matches!(g.fog_of_war, Some(crate::ast::Toggle::on)).then(fog::Fog::default);
+    state.capture = matches!(g.capture, Some(crate::ast::Toggle::on));
+    state.season_length = g.season_length.filter(|l| *l > 0);
+    state.storage = g.storage;
 }
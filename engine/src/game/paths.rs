@@ -0,0 +1,136 @@
+use crate::game::map::Terrain;
+use crate::game::regions;
+use crate::game::state::GameState;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Movement points of one turn of travel
+pub const TURN: i64 = 1000;
+
+/// Movement points an army spends to enter a tile of `terrain`: a turn for water and rivers,
+/// two thirds of a turn through swamps, half a turn through forests and snow, a third of a turn
+/// for open land. None for mountains, which are impassable.
+pub fn terrain_cost(terrain: Terrain) -> Option<i64> {
+    match terrain {
+        Terrain::Water | Terrain::River => Some(TURN),
+        Terrain::Mountain => None,
+        Terrain::Swamp => Some(TURN * 2 / 3),
+        Terrain::Forest | Terrain::Snow => Some(TURN / 2),
+        Terrain::Plains | Terrain::Desert => Some(TURN / 3),
+    }
+}
+
+/// A way between two tiles and the turns an army takes to walk it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathInfo {
+    /// Tiles walked, both ends included
+    pub tiles: Vec<(i32, i32)>,
+    /// Travel turns, before the travel modifiers of the civilization sending the army
+    pub turns: u32,
+}
+
+/// Start and end tiles of a way
+type Ends = ((i32, i32), (i32, i32));
+
+/// Ways already found between two tiles, None when there is none. Only valid for the terrain
+/// revision they were found on: the whole cache is dropped when the map changes.
+#[derive(Debug, Clone, Default)]
+pub struct PathCache {
+    revision: u64,
+    paths: HashMap<Ends, Option<PathInfo>>,
+}
+
+impl PathCache {
+    /// Cached way from `from` to `to` on the map at `revision`, computed by `find` when missing.
+    pub fn get_or_find(
+        &mut self,
+        revision: u64,
+        from: (i32, i32),
+        to: (i32, i32),
+        find: impl FnOnce() -> Option<PathInfo>,
+    ) -> Option<PathInfo> {
+        if self.revision != revision {
+            self.paths.clear();
+            self.revision = revision;
+        }
+        self.paths.entry((from, to)).or_insert_with(find).clone()
+    }
+}
+
+/// Lowest movement points an army can spend to get from `from` to `to`, used to guide the search
+/// toward the target. Crossings jump over many tiles at once, so nothing is assumed with regions.
+fn estimate(state: &GameState, from: (i32, i32), to: (i32, i32)) -> i64 {
+    if state.regions.is_some() {
+        return 0;
+    }
+    let dx = state.map.offset_x(i64::from(from.0), i64::from(to.0));
+    (dx.abs() + i64::from((from.1 - to.1).abs())) * (TURN / 3)
+}
+
+/// Cheapest way from `from` to `to` on the map, both ends included, found with an A* search.
+///
+/// Mountains are impassable, water costs more than land, and armies only leave a region through
+/// its crossings. Armies walk off an edge of a map that wraps around onto the other one. None
+/// when the terrain blocks every way or an end is outside the map.
+pub fn find(state: &GameState, from: (i32, i32), to: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+    let (width, height) = (state.map.width, state.map.height);
+    let index = |(x, y): (i32, i32)| {
+        let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+        (x < width && y < height).then_some(y * width + x)
+    };
+    let (start, goal) = (index(from)?, index(to)?);
+
+    let mut cost = vec![i64::MAX; width * height];
+    let mut parent: Vec<Option<(i32, i32)>> = vec![None; width * height];
+    // min-heap of (estimated total cost, cost so far, tile)
+    let mut heap = BinaryHeap::new();
+    cost[start] = 0;
+    heap.push(Reverse((estimate(state, from, to), 0, from)));
+
+    while let Some(Reverse((_, spent, tile))) = heap.pop() {
+        let here = index(tile)?;
+        if spent > cost[here] {
+            continue;
+        }
+        if here == goal {
+            let mut path = vec![tile];
+            while let Some(previous) = parent[index(*path.last()?)?] {
+                path.push(previous);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let (x, y) = tile;
+        let steps = [
+            (state.map.wrap_x(x - 1), y),
+            (state.map.wrap_x(x + 1), y),
+            (x, y - 1),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .filter(|next| regions::connected(state, tile, *next))
+        .filter_map(|next| {
+            let i = index(next)?;
+            Some((next, terrain_cost(state.map.tiles[i / width][i % width])?))
+        })
+        .chain(
+            regions::crossings_from(state, tile)
+                .into_iter()
+                .map(|(next, turns)| (next, i64::from(turns) * TURN)),
+        );
+        for (next, step) in steps {
+            let Some(i) = index(next) else { continue };
+            let spent = spent.saturating_add(step);
+            if spent < cost[i] {
+                cost[i] = spent;
+                parent[i] = Some(tile);
+                heap.push(Reverse((
+                    spent.saturating_add(estimate(state, next, to)),
+                    spent,
+                    next,
+                )));
+            }
+        }
+    }
+    None
+}
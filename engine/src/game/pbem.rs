@@ -0,0 +1,383 @@
+use crate::ast::PlayerType;
+use crate::game::Game;
+use crate::game::checksum::state_checksum;
+use crate::game::replay::{Input, ReplayEvent};
+use crate::game::saves::SavedGame;
+use crate::game::signing;
+use anyhow::{Context, Result, anyhow, bail};
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the passphrase the players of a play-by-mail game share
+pub const KEY_VAR: &str = "PBEM_KEY";
+/// Version of the turn file format
+pub const PBEM_VERSION: u32 = 2;
+/// First bytes of every turn file
+const MAGIC: &[u8] = b"CLIVPBEM";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Passphrase of the play-by-mail game from the environment.
+///
+/// # Errors
+/// Fails when the variable is not set or empty.
+pub fn key() -> Result<String> {
+    std::env::var(KEY_VAR)
+        .ok()
+        .filter(|k| !k.is_empty())
+        .with_context(|| {
+            format!("no play-by-mail key: set {KEY_VAR} to the passphrase shared by the players")
+        })
+}
+
+/// Keys of a play-by-mail game, derived once from the passphrase with Argon2id and the salt the
+/// game was started with.
+#[derive(Clone)]
+pub struct Keys {
+    salt: [u8; SALT_LEN],
+    master: [u8; 32],
+}
+
+impl std::fmt::Debug for Keys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Keys").finish_non_exhaustive()
+    }
+}
+
+impl Keys {
+    /// Keys of the game started with `salt`.
+    ///
+    /// # Errors
+    /// Fails when Argon2 rejects the passphrase.
+    pub fn derive(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self> {
+        let mut master = [0; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut master)
+            .map_err(|e| anyhow!("failed to derive the play-by-mail key: {e}"))?;
+        Ok(Self { salt, master })
+    }
+
+    /// Keys of a new game, under a random salt.
+    fn generate(passphrase: &str) -> Result<Self> {
+        Self::derive(passphrase, rand::random())
+    }
+
+    fn subkey(&self, parts: &[&[u8]]) -> [u8; 32] {
+        signing::hmac(&self.master, parts)
+    }
+
+    /// Key sealing the turn files.
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.subkey(&[b"encrypt"])))
+    }
+
+    /// Key of the links `player` adds to the action chain. Like every key of the game it derives
+    /// from the shared passphrase, so any player can compute it: the chain detects files tampered
+    /// with by outsiders, not a player forging the links of another.
+    fn chain(&self, player: usize) -> [u8; 32] {
+        self.subkey(&[b"chain", &(player as u64).to_le_bytes()])
+    }
+}
+
+/// What one session played before handing the game over: the inputs of a human turn and of the
+/// AI turns after it, chained to the previous sessions by their MACs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Link {
+    /// Turn the session started on
+    pub turn: i32,
+    /// Human player of the session
+    pub player: usize,
+    pub inputs: Vec<ReplayEvent>,
+    /// State checksum when the game was handed over
+    pub checksum: u32,
+    /// Hex HMAC-SHA256, under the chain key derived for `player` from the shared passphrase, of the
+    /// previous MAC and of everything above
+    pub mac: String,
+}
+
+impl Link {
+    fn sign(&self, keys: &Keys, previous: &str) -> Result<String> {
+        let content =
+            serde_json::to_string(&(self.turn, self.player, &self.inputs, self.checksum))?;
+        Ok(signing::hex(&signing::hmac(
+            &keys.chain(self.player),
+            &[previous.as_bytes(), content.as_bytes()],
+        )))
+    }
+}
+
+/// Content of a turn file once decrypted: the game as it started and as handed over, and the
+/// sessions that led from one to the other.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnFile {
+    pub version: u32,
+    pub origin: SavedGame,
+    pub save: SavedGame,
+    pub chain: Vec<Link>,
+}
+
+impl TurnFile {
+    /// Check the action chain: the MAC of every link, then its inputs replayed on the game the
+    /// previous link left, which must reach the checksum the link recorded. The saved game must be
+    /// the one the last link left, field for field.
+    ///
+    /// # Errors
+    /// Fails on the first link whose MAC or checksum does not match, or when the save differs from
+    /// the game the chain leads to.
+    pub fn verify(&self, keys: &Keys) -> Result<()> {
+        let mut replayed = self.origin.clone().restore()?;
+        let queues = replayed.replay_ais();
+        let mut previous = String::new();
+        for link in &self.chain {
+            if link.sign(keys, &previous)? != link.mac {
+                bail!(
+                    "action chain broken at turn {} (player {})",
+                    link.turn,
+                    link.player
+                );
+            }
+            replayed.play_events(link.inputs.iter().cloned().map(Ok), &queues, |_| {})?;
+            let checksum = state_checksum(&replayed.state);
+            if checksum != link.checksum {
+                bail!(
+                    "the inputs of turn {} (player {}) do not replay to the game handed over (checksum {checksum:08x}, expected {:08x})",
+                    link.turn,
+                    link.player,
+                    link.checksum
+                );
+            }
+            previous.clone_from(&link.mac);
+        }
+        // the whole save is compared, not only the checksum, so that no field it persists can
+        // be edited without breaking the chain
+        let save = serde_json::to_value(&self.save)?;
+        let expected = serde_json::to_value(SavedGame::of(&replayed))?;
+        if let Some((field, _)) = expected
+            .as_object()
+            .into_iter()
+            .flatten()
+            .find(|(field, value)| save.get(field.as_str()) != Some(*value))
+        {
+            bail!("the game does not match its action chain ({field} differs)");
+        }
+        Ok(())
+    }
+}
+
+/// Play-by-mail game in progress on this machine, from the launch until the game is handed over
+/// to the next human player.
+#[derive(Debug, Clone)]
+pub struct Session {
+    path: PathBuf,
+    keys: Keys,
+    origin: SavedGame,
+    /// Human player of this session, known once one has the turn
+    player: Option<usize>,
+    /// Turn `player` started playing on
+    turn: i32,
+    chain: Vec<Link>,
+    inputs: Vec<ReplayEvent>,
+}
+
+impl Session {
+    pub fn new(
+        game: &Game,
+        path: PathBuf,
+        keys: Keys,
+        origin: SavedGame,
+        chain: Vec<Link>,
+    ) -> Self {
+        let player = human_turn(game);
+        Self {
+            path,
+            keys,
+            origin,
+            player,
+            turn: game.state.turn,
+            chain,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, turn: i32, player: usize, input: &Input) {
+        self.inputs.push(ReplayEvent {
+            turn,
+            player,
+            input: input.clone(),
+        });
+    }
+}
+
+/// Player whose turn it is, when a human one.
+fn human_turn(game: &Game) -> Option<usize> {
+    let current = game.state.player_turn;
+    let civ = game.state.civilizations.get(current)?;
+    matches!(civ.city.player_type, PlayerType::PLAYER).then_some(current)
+}
+
+/// Compress and encrypt `text` with ChaCha20-Poly1305: the magic, the salt of the keys, a random
+/// nonce and the ciphertext, authenticated along with the magic and the salt.
+///
+/// # Errors
+/// Fails when the text cannot be compressed or encrypted.
+pub fn seal(keys: &Keys, text: &str) -> Result<Vec<u8>> {
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let body = zstd::encode_all(text.as_bytes(), 0)?;
+    let aad = [MAGIC, &keys.salt].concat();
+    let sealed = keys
+        .cipher()
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &body,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| anyhow!("failed to encrypt the turn file"))?;
+    Ok([MAGIC, &keys.salt, &nonce, &sealed].concat())
+}
+
+/// Decrypt a turn file sealed by `seal`, failing when it was tampered with or sealed under
+/// another passphrase. Returns the keys of the game along with the text.
+///
+/// # Errors
+/// Fails when the data is not a turn file, or was tampered with or sealed under another passphrase.
+pub fn open(passphrase: &str, data: &[u8]) -> Result<(String, Keys)> {
+    let rest = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| anyhow!("not a turn file"))?;
+    if rest.len() < SALT_LEN + NONCE_LEN {
+        bail!("truncated turn file");
+    }
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let keys = Keys::derive(passphrase, salt.try_into()?)?;
+    let aad = [MAGIC, salt].concat();
+    let body = keys
+        .cipher()
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: sealed,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| anyhow!("the turn file was tampered with or sealed with another key"))?;
+    Ok((String::from_utf8(zstd::decode_all(body.as_slice())?)?, keys))
+}
+
+/// Decrypt the turn file at `path` and check its action chain.
+///
+/// # Errors
+/// Fails when the file cannot be read or opened, is of another format, or its action chain does not
+/// verify.
+pub fn read(path: &Path, passphrase: &str) -> Result<(TurnFile, Keys, Game)> {
+    let data = std::fs::read(path)
+        .with_context(|| format!("failed to read turn file {}", path.display()))?;
+    let (text, keys) =
+        open(passphrase, &data).with_context(|| format!("invalid turn file {}", path.display()))?;
+    let file: TurnFile = serde_json::from_str(&text).context("invalid turn file")?;
+    if file.version != PBEM_VERSION {
+        bail!(
+            "turn file format {} is not supported (expected {PBEM_VERSION})",
+            file.version
+        );
+    }
+    file.verify(&keys)
+        .with_context(|| format!("invalid turn file {}", path.display()))?;
+    let game = file.save.clone().restore()?;
+    Ok((file, keys, game))
+}
+
+/// Play `game` by mail: the turn file is written to `path` once it is handed over. The game
+/// goes through a save first, so that the sessions replay from the state they were played on.
+///
+/// # Errors
+/// Fails when the keys cannot be derived or the game does not restore from its save.
+pub fn start(game: &mut Game, path: PathBuf, passphrase: &str) -> Result<()> {
+    log::info!("Starting play-by-mail game, turn file {}", path.display());
+    let keys = Keys::generate(passphrase)?;
+    let origin = SavedGame::of(game);
+    *game = origin.clone().restore()?;
+    game.pbem = Some(Session::new(game, path, keys, origin, Vec::new()));
+    Ok(())
+}
+
+/// Continue the game of the turn file at `path`, to be handed over again to the same file.
+///
+/// # Errors
+/// Fails when the turn file cannot be read or verified.
+pub fn resume(path: &Path, passphrase: &str) -> Result<Game> {
+    let (file, keys, mut game) = read(path, passphrase)?;
+    log::info!(
+        "Resuming play-by-mail game from {} ({} sessions)",
+        path.display(),
+        file.chain.len()
+    );
+    game.pbem = Some(Session::new(
+        &game,
+        path.to_path_buf(),
+        keys,
+        file.origin,
+        file.chain,
+    ));
+    Ok(game)
+}
+
+/// Write the turn file once the turn of the human player of the session is over and another
+/// human player, or the same one in a game against the AIs, has the turn, or the game is over.
+/// Returns the path written and the city of the player it goes to.
+///
+/// # Errors
+/// Fails when the turn file cannot be sealed or written.
+pub fn hand_over(game: &mut Game) -> Result<Option<(PathBuf, String)>> {
+    let current = game.state.player_turn;
+    let human = human_turn(game);
+    let Some(session) = &mut game.pbem else {
+        return Ok(None);
+    };
+    // a game starting on the turn of an AI belongs to the first human player after it
+    if session.player.is_none() {
+        session.player = human;
+        session.turn = game.state.turn;
+    }
+    let passed = session
+        .player
+        .is_some_and(|p| p != current || game.state.turn != session.turn);
+    if !(game.state.game_over || passed && human.is_some()) {
+        return Ok(None);
+    }
+    let Some(session) = game.pbem.take() else {
+        return Ok(None);
+    };
+    let mut chain = session.chain;
+    let mut link = Link {
+        turn: session.turn,
+        player: session.player.unwrap_or(current),
+        inputs: session.inputs,
+        checksum: state_checksum(&game.state),
+        mac: String::new(),
+    };
+    link.mac = link.sign(&session.keys, chain.last().map_or("", |l| l.mac.as_str()))?;
+    chain.push(link);
+    let file = TurnFile {
+        version: PBEM_VERSION,
+        origin: session.origin,
+        save: SavedGame::of(game),
+        chain,
+    };
+    let data = seal(&session.keys, &serde_json::to_string(&file)?)?;
+    std::fs::write(&session.path, data)
+        .with_context(|| format!("failed to write turn file {}", session.path.display()))?;
+    let next = game
+        .state
+        .civilizations
+        .get(current)
+        .map(|c| c.city.name.clone())
+        .unwrap_or_default();
+    log::info!("Turn file written to {} for {next}", session.path.display());
+    Ok(Some((session.path, next)))
+}
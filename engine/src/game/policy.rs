@@ -0,0 +1,94 @@
+use crate::ast::{PolicyDefaults, Toggle};
+use serde::{Deserialize, Serialize};
+
+/// Slider value leaving income and production speed unchanged
+pub const NEUTRAL: u32 = 50;
+
+/// Economic policy of a civilization. Both sliders go from 0 to 100 and are neutral at 50.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Policy {
+    /// Tax rate: raises income but slows constructions down (growth)
+    pub tax: u32,
+    /// Military focus: speeds recruitments up at the expense of income (economy)
+    pub military: u32,
+    /// Whether the garrison sorties against armies one turn from the city on favorable odds
+    #[serde(default)]
+    pub sortie: bool,
+    /// Construction progress accumulated from growth, one step per `NEUTRAL` points
+    growth_progress: u32,
+    /// Recruitment progress accumulated from military focus, one step per `NEUTRAL` points
+    military_progress: u32,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            tax: NEUTRAL,
+            military: NEUTRAL,
+            sortie: false,
+            growth_progress: 0,
+            military_progress: 0,
+        }
+    }
+}
+
+impl Policy {
+    /// Policy of a city, from its DSL defaults when given.
+    pub fn from_defaults(defaults: Option<&PolicyDefaults>) -> Self {
+        let mut policy = Self::default();
+        if let Some(defaults) = defaults {
+            policy.tax = defaults.tax.unwrap_or(NEUTRAL).min(100);
+            policy.military = defaults.military.unwrap_or(NEUTRAL).min(100);
+            policy.sortie = matches!(defaults.sortie, Some(Toggle::on));
+        }
+        policy
+    }
+
+    /// Income after taxes and economy focus, from the base production of the buildings.
+    /// Each slider scales income from 50% to 150%.
+    pub fn income(&self, base: i64) -> i64 {
+        let tax = i64::from(NEUTRAL + self.tax);
+        let economy = i64::from(NEUTRAL + 100 - self.military);
+        base.saturating_mul(tax).saturating_mul(economy) / 10_000
+    }
+
+    /// Construction steps for this turn: 2 with no taxes, 1 at neutral, none at full taxes.
+    pub fn construction_steps(&mut self) -> u32 {
+        self.growth_progress += 100 - self.tax;
+        let steps = self.growth_progress / NEUTRAL;
+        self.growth_progress %= NEUTRAL;
+        steps
+    }
+
+    /// Turns until a construction with `remaining` steps finishes at the current tax rate,
+    /// `None` when it never does (full taxes).
+    pub fn construction_eta(&self, remaining: u32) -> Option<u32> {
+        eta(self.growth_progress, 100 - self.tax, remaining)
+    }
+
+    /// Turns until a recruitment with `remaining` steps finishes at the current military focus,
+    /// `None` when it never does (no military focus).
+    pub fn recruitment_eta(&self, remaining: u32) -> Option<u32> {
+        eta(self.military_progress, self.military, remaining)
+    }
+
+    /// Recruitment steps for this turn: 2 at full military focus, 1 at neutral, none at 0.
+    pub fn recruitment_steps(&mut self) -> u32 {
+        self.military_progress += self.military;
+        let steps = self.military_progress / NEUTRAL;
+        self.military_progress %= NEUTRAL;
+        steps
+    }
+}
+
+/// Turns for `progress`, growing by `rate` each turn, to reach `remaining` steps of `NEUTRAL` points.
+fn eta(progress: u32, rate: u32, remaining: u32) -> Option<u32> {
+    let needed = (remaining * NEUTRAL).saturating_sub(progress);
+    if needed == 0 {
+        Some(0)
+    } else if rate == 0 {
+        None
+    } else {
+        Some(needed.div_ceil(rate))
+    }
+}
@@ -0,0 +1,49 @@
+use crate::game::utils::{output_path, write_to_file};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const PREFERENCES_FILE: &str = "preferences.json";
+
+/// User preferences persisted between sessions in `preferences.json` of the output directory.
+///
+/// Missing fields fall back to their default so older files keep loading.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Preferences {
+    /// Force a high-contrast palette for the map and borders
+    pub high_contrast: bool,
+    /// Disable flashing overlays such as the "AI is thinking" popup
+    pub reduced_motion: bool,
+    /// Render popup text larger: bold, spaced lines in a bigger popup
+    pub large_popups: bool,
+    /// Seconds after which reports and other popups without choices close by themselves, 0 to
+    /// keep them until dismissed
+    pub popup_timeout: u64,
+    /// User-defined action aliases (e.g. `b f` -> `build farm`)
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl Preferences {
+    /// Load preferences, or defaults when the file is missing or invalid.
+    pub fn load() -> Self {
+        std::fs::read_to_string(output_path(PREFERENCES_FILE))
+            .ok()
+            .and_then(|s| match serde_json::from_str(&s) {
+                Ok(p) => Some(p),
+                Err(e) => {
+                    log::warn!("Ignoring invalid preferences file: {e}");
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Write the preferences to the output directory.
+    ///
+    /// # Errors
+    /// Fails when the file cannot be written.
+    pub fn save(&self) -> Result<()> {
+        write_to_file(PREFERENCES_FILE, &serde_json::to_string_pretty(self)?)
+    }
+}
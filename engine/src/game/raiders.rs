@@ -0,0 +1,188 @@
+use crate::ast::Raiders as RaidersTerms;
+use crate::game::armies;
+use crate::game::combat;
+use crate::game::composition;
+use crate::game::diplomacy;
+use crate::game::events::GameEvent;
+use crate::game::state::{GameState, Travel};
+use log::info;
+
+/// Attacker of the armies of the raiders, which belong to no civilization
+pub const RAIDERS: usize = usize::MAX;
+/// Share of its resources a city loses to raiders beating its garrison, unless set in `[raiders]`
+pub const DEFAULT_PILLAGE_PERCENT: u32 = 25;
+
+/// Raiding parties of the `[raiders]` section, sent against the weakest city at a steady pace.
+#[derive(Debug, Clone)]
+pub struct Raiders {
+    /// Turns between two parties
+    pub every: u32,
+    /// Units of the first party
+    pub strength: u32,
+    /// Units each party has over the previous one
+    pub growth: u32,
+    /// Turn the first party sets out
+    pub first_turn: u32,
+    /// Share of the resources of the city the raiders carry away when they win, in percent
+    pub pillage: u32,
+}
+
+impl Raiders {
+    pub fn from_terms(terms: &RaidersTerms) -> Self {
+        let every = terms.every.max(1);
+        Self {
+            every,
+            strength: terms.strength,
+            growth: terms.growth.unwrap_or(0),
+            first_turn: terms.first_turn.unwrap_or(every),
+            pillage: terms.pillage.unwrap_or(DEFAULT_PILLAGE_PERCENT).min(100),
+        }
+    }
+
+    /// Units of the party setting out on `turn`, None when no party sets out then.
+    pub fn party(&self, turn: i32) -> Option<u32> {
+        let since = u32::try_from(turn).ok()?.checked_sub(self.first_turn)?;
+        (since % self.every == 0).then(|| {
+            self.strength
+                .saturating_add(self.growth.saturating_mul(since / self.every))
+        })
+    }
+}
+
+/// Name given to the owner of the army of `attacker` in the texts of the game.
+pub fn army_owner(state: &GameState, attacker: usize) -> &str {
+    if attacker == RAIDERS {
+        "pillards"
+    } else {
+        state.civ(attacker).map_or("?", |c| c.city.name.as_str())
+    }
+}
+
+/// Surviving city with the weakest defense, the first one on a tie.
+fn weakest_city(state: &GameState) -> Option<usize> {
+    (0..state.civilizations.len())
+        .filter(|i| state.civ(*i).is_ok_and(|c| c.alive))
+        .min_by_key(|i| diplomacy::defense_power(state, *i))
+}
+
+/// Corners and middles of the edges of the map, where the raiders come from.
+fn edges(state: &GameState) -> Vec<(i32, i32)> {
+    let (w, h) = (state.map.width.max(1) - 1, state.map.height.max(1) - 1);
+    let points = [
+        (0, 0),
+        (w / 2, 0),
+        (w, 0),
+        (w, h / 2),
+        (w, h),
+        (w / 2, h),
+        (0, h),
+        (0, h / 2),
+    ];
+    points
+        .into_iter()
+        .map(|(x, y)| {
+            (
+                i32::try_from(x).unwrap_or(i32::MAX),
+                i32::try_from(y).unwrap_or(i32::MAX),
+            )
+        })
+        .collect()
+}
+
+/// Send the raiding party of the current turn, if any, toward the weakest city. It comes from the
+/// edge of the map closest to the city with a way to it. Called once per round, at the start of
+/// the turn of the first civilization.
+pub fn spawn(state: &mut GameState) {
+    let Some(amount) = state.raiders.as_ref().and_then(|r| r.party(state.turn)) else {
+        return;
+    };
+    let Some(target) = weakest_city(state).filter(|_| amount > 0) else {
+        return;
+    };
+    let Ok(city) = state.civ(target).map(|c| &c.city) else {
+        return;
+    };
+    let dst = (city.x.cast_signed(), city.y.cast_signed());
+    let mut edges = edges(state);
+    edges.sort_by_key(|(x, y)| (x - dst.0).pow(2) + (y - dst.1).pow(2));
+    let Some(path) = edges.into_iter().find_map(|src| state.path(src, dst)) else {
+        info!("Raiders found no way to civ {target}");
+        return;
+    };
+    let mut travel = Travel {
+        attacker: RAIDERS,
+        defender: target,
+        amount,
+        composition: Vec::new(),
+        remaining: 0,
+        total: 0,
+        path: path.tiles,
+        step: 0,
+        progress: 0,
+    };
+    travel.remaining = armies::turns_left(state, &travel);
+    travel.total = travel.remaining;
+    info!(
+        "{amount} raiders set out toward civ {target}, {} turns away",
+        travel.total
+    );
+    state.travels.push(travel);
+    state
+        .events
+        .publish(state.turn, GameEvent::RaidersSighted { target, amount });
+}
+
+/// Resolve the raid of `travel`, which reached the city it marched on. The raiders fight like an
+/// army but never take the city: when they beat the garrison they carry away a share of its
+/// resources, and their survivors scatter either way.
+pub fn raid(state: &mut GameState, travel: &Travel) {
+    let defender = travel.defender;
+    if !state.civ(defender).is_ok_and(|c| c.alive) {
+        return;
+    }
+    let raiders_power = composition::power(state, travel);
+    let defender_power = diplomacy::defense_power(state, defender);
+    let garrison = state
+        .civ(defender)
+        .map_or(0, |c| composition::total(&c.city.units.units));
+    let losses = combat::casualties(raiders_power, defender_power, travel.amount, garrison);
+    let lost = state.remove_units_from_city(defender, losses.defender);
+    state.metrics.lose_units(defender, lost);
+    let share = state
+        .raiders
+        .as_ref()
+        .map_or(DEFAULT_PILLAGE_PERCENT, |r| r.pillage);
+    let Ok(civ) = state.civ_mut(defender) else {
+        return;
+    };
+    let pillaged = if losses.won {
+        i32::try_from(i64::from(civ.resources.ressources.max(0)) * i64::from(share) / 100)
+            .unwrap_or(i32::MAX)
+    } else {
+        0
+    };
+    civ.earn(-pillaged);
+    let name = civ.city.name.clone();
+    let text = if losses.won {
+        format!(
+            "{} raiders fell on {name} ({raiders_power} vs {defender_power}) and carried away {pillaged} resources, the garrison lost {lost} units",
+            travel.amount
+        )
+    } else {
+        format!(
+            "{name} drove off {} raiders ({raiders_power} vs {defender_power}), losing {lost} units",
+            travel.amount
+        )
+    };
+    info!("{text}");
+    state.open_popup("Raid", &text, vec![]);
+    state.events.publish(
+        state.turn,
+        GameEvent::Raid {
+            defender,
+            raiders: travel.amount,
+            won: losses.won,
+            pillaged,
+        },
+    );
+}
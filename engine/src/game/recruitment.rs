@@ -0,0 +1,69 @@
+use crate::ast::BuildingDef;
+use crate::game::state::{Civilization, GameState};
+
+/// Batch sizes offered by the hire popup, along with the most the player can afford
+const AMOUNTS: [u32; 4] = [1, 2, 5, 10];
+
+/// Turns taken to train `amount` units produced by `producer`: its finished buildings each train
+/// one unit per production time, side by side.
+pub fn batch_time(civ: &Civilization, producer: &BuildingDef, amount: u32) -> u32 {
+    let buildings = u32::try_from(producers(civ, producer))
+        .unwrap_or(u32::MAX)
+        .max(1);
+    producer
+        .production
+        .time
+        .saturating_mul(amount.div_ceil(buildings))
+}
+
+/// Finished buildings of `producer` in the city of `civ`.
+fn producers(civ: &Civilization, producer: &BuildingDef) -> usize {
+    civ.city
+        .buildings
+        .elements
+        .iter()
+        .filter(|b| b.id_building == producer.name)
+        .count()
+}
+
+/// Prompt and choices of the hire popup once `unit` is chosen: the batch sizes `civ_index` can
+/// afford, each as parsed back by `from_choice`.
+pub fn amount_popup(state: &GameState, civ_index: usize, unit: &str) -> (String, Vec<String>) {
+    let Some((civ, producer)) = state
+        .civ(civ_index)
+        .ok()
+        .and_then(|c| Some((c, state.buildings.get(*c.producers.get(unit)?)?)))
+    else {
+        return (
+            format!("How many {unit} to hire?"),
+            vec![format!("{unit} x1")],
+        );
+    };
+    let cost = producer.production.cost;
+    let most = u32::try_from(civ.resources.ressources.max(0))
+        .unwrap_or(0)
+        .checked_div(cost)
+        .unwrap_or(u32::MAX);
+    let mut amounts: Vec<u32> = AMOUNTS.into_iter().filter(|a| *a <= most.max(1)).collect();
+    if most > 1 && most < u32::MAX && !amounts.contains(&most) {
+        amounts.push(most);
+    }
+    let buildings = producers(civ, producer);
+    let prompt = format!(
+        "How many {unit} to hire? {cost} resources and {} turns each, {buildings} {} training them side by side",
+        producer.production.time, producer.name
+    );
+    (
+        prompt,
+        amounts
+            .into_iter()
+            .map(|a| format!("{unit} x{a}"))
+            .collect(),
+    )
+}
+
+/// Unit and amount of a choice of the hire popup.
+pub fn from_choice(choice: &str) -> Option<(String, u32)> {
+    let (unit, amount) = choice.rsplit_once(" x")?;
+    Some((unit.to_string(), amount.parse().ok()?))
+}
@@ -0,0 +1,143 @@
+use crate::ast::{Crossing, Region};
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+
+/// Whether `(x, y)` lies inside `region`.
+fn contains(region: &Region, (x, y): (i32, i32)) -> bool {
+    let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+        return false;
+    };
+    (region.x..region.x + region.width).contains(&x)
+        && (region.y..region.y + region.height).contains(&y)
+}
+
+/// Index of the region containing `tile`, None outside every region or without a `[regions]`
+/// section.
+pub fn region_at(state: &GameState, tile: (i32, i32)) -> Option<usize> {
+    state
+        .regions
+        .as_ref()?
+        .regions
+        .iter()
+        .position(|r| contains(r, tile))
+}
+
+/// Whether an army may step from `from` to the neighboring tile `to`: always on a map without
+/// regions, only inside the same region otherwise. Regions are left through their crossings.
+pub fn connected(state: &GameState, from: (i32, i32), to: (i32, i32)) -> bool {
+    state.regions.is_none()
+        || region_at(state, to).is_some_and(|r| region_at(state, from) == Some(r))
+}
+
+fn ends(crossing: &Crossing) -> ((i32, i32), (i32, i32)) {
+    (
+        (crossing.from_x.cast_signed(), crossing.from_y.cast_signed()),
+        (crossing.to_x.cast_signed(), crossing.to_y.cast_signed()),
+    )
+}
+
+/// Tiles reachable from `tile` through a crossing, both ways, with the turns the crossing takes.
+pub fn crossings_from(state: &GameState, tile: (i32, i32)) -> Vec<((i32, i32), u32)> {
+    let Some(regions) = &state.regions else {
+        return Vec::new();
+    };
+    regions
+        .crossings
+        .iter()
+        .filter_map(|c| match ends(c) {
+            (from, to) if from == tile => Some((to, c.turns)),
+            (from, to) if to == tile => Some((from, c.turns)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Turns taken by the crossing between `from` and `to`, None when they are not linked by one.
+pub fn crossing_turns(state: &GameState, from: (i32, i32), to: (i32, i32)) -> Option<u32> {
+    crossings_from(state, from)
+        .into_iter()
+        .find(|(end, _)| *end == to)
+        .map(|(_, turns)| turns)
+}
+
+/// Region the camera is over, for the map title.
+pub fn camera_region(state: &GameState) -> Option<&str> {
+    let index = region_at(state, (state.camera_x, state.camera_y))?;
+    state
+        .regions
+        .as_ref()
+        .map(|r| r.regions[index].name.as_str())
+}
+
+/// Move the camera to the top-left corner of the region after the one it is over.
+pub fn focus_next_region(state: &mut GameState) {
+    let Some(regions) = &state.regions else {
+        return;
+    };
+    let next = region_at(state, (state.camera_x, state.camera_y))
+        .map_or(0, |i| (i + 1) % regions.regions.len());
+    let region = &regions.regions[next];
+    state.camera_x = region.x.cast_signed();
+    state.camera_y = region.y.cast_signed();
+}
+
+/// Reject regions outside the map or overlapping each other, crossings not linking two
+/// regions, and cities outside every region.
+///
+/// # Errors
+/// Fails on the first region, crossing or city breaking these rules.
+pub fn check(state: &GameState) -> Result<()> {
+    let Some(regions) = &state.regions else {
+        return Ok(());
+    };
+    for (i, region) in regions.regions.iter().enumerate() {
+        if region.width == 0
+            || region.height == 0
+            || (region.x + region.width) as usize > state.map.width
+            || (region.y + region.height) as usize > state.map.height
+        {
+            return Err(anyhow!(
+                "region {} does not fit in the {}x{} map",
+                region.name,
+                state.map.width,
+                state.map.height
+            ));
+        }
+        let overlaps = |other: &Region| {
+            region.x < other.x + other.width
+                && other.x < region.x + region.width
+                && region.y < other.y + other.height
+                && other.y < region.y + region.height
+        };
+        if let Some(other) = regions.regions[i + 1..].iter().find(|o| overlaps(o)) {
+            return Err(anyhow!(
+                "regions {} and {} overlap",
+                region.name,
+                other.name
+            ));
+        }
+    }
+    for crossing in &regions.crossings {
+        let (from, to) = ends(crossing);
+        match (region_at(state, from), region_at(state, to)) {
+            (Some(a), Some(b)) if a != b => {}
+            _ => {
+                return Err(anyhow!(
+                    "crossing from {},{} to {},{} does not link two regions",
+                    from.0,
+                    from.1,
+                    to.0,
+                    to.1
+                ));
+            }
+        }
+    }
+    if let Some(civ) = state
+        .civilizations
+        .iter()
+        .find(|c| region_at(state, (c.city.x.cast_signed(), c.city.y.cast_signed())).is_none())
+    {
+        return Err(anyhow!("city {} is outside every region", civ.city.name));
+    }
+    Ok(())
+}
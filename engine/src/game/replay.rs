@@ -0,0 +1,244 @@
+use crate::game::annotations::Annotation;
+use crate::game::migrations;
+use crate::game::saves::SavedGame;
+use crate::game::state::Popup;
+use crate::game::utils::output_path;
+use crate::game::{Ai, AiView, signing};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Version of the replay format, bumped along with a migration in `migrations::REPLAYS`
+pub const REPLAY_VERSION: u32 = 1;
+/// zstd compression level, high enough for long games while staying cheap per action
+const COMPRESSION_LEVEL: i32 = 9;
+
+/// First line of a replay: everything needed to rebuild the starting position.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReplayHeader {
+    pub version: u32,
+    /// JSON configuration the game was loaded from, `None` for the default game
+    pub config: Option<String>,
+    /// Map seed when the recording started (it may differ from the config, e.g. daily challenges)
+    pub seed: String,
+    /// Seed of the random AI of every civilization, None for humans and AIs seeded at random
+    #[serde(default)]
+    pub ai_seeds: Vec<Option<u64>>,
+}
+
+/// Input played during the game, in the order it was played.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Input {
+    /// Action typed or selected by the player (`build farm`, `end`...)
+    Action(String),
+    /// Answer to the open popup
+    Popup(String),
+    /// Turn passed without an action (an AI returning no action, or the headless `step` command)
+    Step,
+    /// Map regenerated from a new seed
+    Seed(String),
+    /// State checksum at the first input of a turn, written by older engines: mismatches are
+    /// only reported, having no state to go back to
+    Checksum(u32),
+    /// Gameplay state at the first input of a turn, put back during playback when the replayed
+    /// game drifted from it
+    Snapshot(Box<SavedGame>),
+    /// Note placed by a spectator
+    Annotate(Annotation),
+    /// Mood an AI showed at the start of its turn
+    Mood(String),
+}
+
+/// One line of a replay after the header: an input and who played it when.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReplayEvent {
+    pub turn: i32,
+    pub player: usize,
+    pub input: Input,
+}
+
+/// Writes a replay as zstd-compressed JSON lines: the header, then one event per input. The
+/// stream is flushed at every new turn so unfinished replays stay readable.
+pub struct ReplayRecorder {
+    path: PathBuf,
+    encoder: zstd::Encoder<'static, BufWriter<File>>,
+    last_turn: i32,
+}
+
+impl ReplayRecorder {
+    /// Start a replay in `replays/` of the output directory.
+    ///
+    /// # Errors
+    /// Fails when the file cannot be created or the header written.
+    pub fn create(header: &ReplayHeader) -> Result<Self> {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let slug: String = header
+            .seed
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        Self::create_at(
+            output_path(&format!("replays/{secs}-{slug}.replay.zst")),
+            header,
+        )
+    }
+
+    /// Start a replay at `path`.
+    ///
+    /// # Errors
+    /// Fails when the file cannot be created or the header written.
+    pub fn create_at(path: PathBuf, header: &ReplayHeader) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let file =
+            File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+        let mut recorder = Self {
+            encoder: zstd::Encoder::new(BufWriter::new(file), COMPRESSION_LEVEL)?,
+            path,
+            last_turn: i32::MIN,
+        };
+        recorder.write_line(&serde_json::to_string(header)?)?;
+        log::info!("Recording replay to {}", recorder.path.display());
+        Ok(recorder)
+    }
+
+    /// Whether the next event of `turn` is the first of its turn.
+    pub fn is_new_turn(&self, turn: i32) -> bool {
+        turn != self.last_turn
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        self.encoder.write_all(line.as_bytes())?;
+        self.encoder.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Append an event. Failures are logged rather than interrupting the game.
+    pub fn record(&mut self, event: &ReplayEvent) {
+        let result = serde_json::to_string(event)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| self.write_line(&line))
+            .and_then(|()| {
+                if event.turn != self.last_turn {
+                    self.last_turn = event.turn;
+                    self.encoder.flush()?;
+                }
+                Ok(())
+            });
+        if let Err(e) = result {
+            log::warn!("Failed to record replay event: {e}");
+        }
+    }
+
+    /// Complete the compressed stream, and sign the replay when a tournament key is set.
+    ///
+    /// # Errors
+    /// Fails when the stream cannot be completed or the replay signed.
+    pub fn finish(self) -> Result<PathBuf> {
+        let mut writer = self.encoder.finish()?;
+        writer.flush()?;
+        if let Some(key) = signing::tournament_key() {
+            signing::sign_file(&self.path, &key)?;
+        }
+        log::info!("Replay saved to {}", self.path.display());
+        Ok(self.path)
+    }
+}
+
+/// Open a replay for streaming: the header, then the events decoded one at a time.
+///
+/// # Errors
+/// Fails when the file cannot be opened or its header read. Events that cannot be read come out as
+/// errors.
+pub fn open(path: &Path) -> Result<(ReplayHeader, impl Iterator<Item = Result<ReplayEvent>>)> {
+    let file =
+        File::open(path).with_context(|| format!("failed to open replay {}", path.display()))?;
+    let mut lines = BufReader::new(zstd::Decoder::new(file)?).lines();
+    let first = lines
+        .next()
+        .ok_or_else(|| anyhow!("empty replay {}", path.display()))??;
+    let header = serde_json::from_str(&first).context("invalid replay header")?;
+    let header = migrations::migrate(header, migrations::REPLAYS, REPLAY_VERSION)?;
+    let header: ReplayHeader = serde_json::from_value(header).context("invalid replay header")?;
+    let events = lines.map(|line| serde_json::from_str(&line?).context("invalid replay event"));
+    Ok((header, events))
+}
+
+/// Queue of recorded inputs shared between the replay driver and a `ReplayAi`.
+pub type InputQueue = Arc<Mutex<VecDeque<Input>>>;
+
+/// Plays back the recorded inputs of an AI civilization, so the replay follows the same code
+/// paths as the original game.
+pub struct ReplayAi {
+    inputs: InputQueue,
+}
+
+impl ReplayAi {
+    pub fn new(inputs: InputQueue) -> Self {
+        Self { inputs }
+    }
+
+    /// Next recorded input, skipping the checksums and notes that are not the AI's.
+    fn next(&self) -> Option<Input> {
+        let mut inputs = self.inputs.lock().ok()?;
+        loop {
+            match inputs.pop_front()? {
+                Input::Checksum(_) | Input::Snapshot(_) | Input::Annotate(_) | Input::Mood(_) => {}
+                input => return Some(input),
+            }
+        }
+    }
+}
+
+impl Ai for ReplayAi {
+    fn mood(&mut self, _view: &AiView, _civ_index: usize) -> Option<String> {
+        let mut inputs = self.inputs.lock().ok()?;
+        while matches!(
+            inputs.front(),
+            Some(Input::Checksum(_) | Input::Snapshot(_) | Input::Annotate(_))
+        ) {
+            inputs.pop_front();
+        }
+        if !matches!(inputs.front(), Some(Input::Mood(_))) {
+            return None;
+        }
+        match inputs.pop_front() {
+            Some(Input::Mood(mood)) => Some(mood),
+            _ => None,
+        }
+    }
+
+    fn select_action(&mut self, _view: &AiView, civ_index: usize) -> Option<String> {
+        match self.next() {
+            Some(Input::Action(action)) => Some(action),
+            Some(Input::Step) | None => None,
+            Some(other) => {
+                log::warn!(
+                    "Replay out of sync for civ {civ_index}: expected an action, got {other:?}"
+                );
+                None
+            }
+        }
+    }
+
+    fn select_popup_input(&mut self, _view: &AiView, civ_index: usize, popup: &Popup) -> String {
+        match self.next() {
+            Some(Input::Popup(input)) => input,
+            other => {
+                log::warn!(
+                    "Replay out of sync for civ {civ_index}: expected an answer to '{}', got {other:?}",
+                    popup.title
+                );
+                "1".to_string()
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use crate::game::events::GameEvent;
+use crate::game::influence;
+use crate::game::map::Terrain;
+use crate::game::state::GameState;
+use crate::game::utils::{output_path, write_to_file};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Turns a battle marker stays on the map of the report, fading away
+pub const MARKER_TURNS: i32 = 3;
+
+/// City of the report, drawn on the map and naming the curves of the graphs.
+#[derive(Serialize)]
+struct CityMark {
+    name: String,
+    color: String,
+    x: u32,
+    y: u32,
+}
+
+/// One position of the turn slider, from a sample of the metrics.
+#[derive(Serialize)]
+struct Frame {
+    turn: i32,
+    /// Index of the territory map of the turn in `Timeline::territories`
+    territory: usize,
+    power: Vec<i32>,
+    resources: Vec<i32>,
+    alive: Vec<bool>,
+}
+
+/// Battle drawn on the map at the city it was fought at.
+#[derive(Serialize)]
+struct Marker {
+    turn: i32,
+    x: u32,
+    y: u32,
+    text: String,
+}
+
+/// Everything the page draws, embedded in it as JSON.
+#[derive(Serialize)]
+struct Timeline<'a> {
+    seed: &'a str,
+    width: usize,
+    height: usize,
+    /// Terrain of the map, row after row, as drawn in text mode
+    terrain: String,
+    civs: Vec<CityMark>,
+    frames: Vec<Frame>,
+    /// Distinct territory maps of the game, row after row: the base 36 index of the civilization
+    /// owning each tile, `.` for none. Frames share them as long as the borders do not move.
+    territories: Vec<String>,
+    battles: Vec<Marker>,
+    /// Every event of the game with its turn, as written in the event log
+    events: Vec<(i32, String)>,
+}
+
+/// Owner of every tile given the influence of each civilization, dead ones projecting none: the
+/// civilization projecting the most influence on it, the first one on a tie. Water belongs to no
+/// one.
+fn territory(state: &GameState, influence: &[i32]) -> String {
+    let mut owners = String::with_capacity(state.map.width * state.map.height);
+    for (y, row) in (0..).zip(&state.map.tiles) {
+        for (x, terrain) in (0..).zip(row) {
+            let owner = (state.civilizations.iter().enumerate())
+                .filter(|_| *terrain != Terrain::Water)
+                .map(|(i, civ)| {
+                    let city = &civ.city;
+                    (
+                        i,
+                        influence.get(i).map_or(0, |s| {
+                            influence::fade(&state.map, *s, (city.x, city.y), x, y)
+                        }),
+                    )
+                })
+                .filter(|(_, projected)| *projected > 0)
+                .fold(None, |best: Option<(usize, i32)>, (i, p)| match best {
+                    Some((_, b)) if b >= p => best,
+                    _ => Some((i, p)),
+                });
+            owners.push(
+                owner
+                    .and_then(|(i, _)| char::from_digit(u32::try_from(i).ok()?, 36))
+                    .unwrap_or('.'),
+            );
+        }
+    }
+    owners
+}
+
+/// City color as written in the configuration when it is a `#RRGGBB` color, white otherwise.
+fn css_color(color: &str) -> String {
+    let hex = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if hex {
+        color.to_string()
+    } else {
+        "#FFFFFF".to_string()
+    }
+}
+
+fn timeline(state: &GameState) -> Timeline<'_> {
+    let mut territories = Vec::new();
+    let mut known: HashMap<Vec<i32>, usize> = HashMap::new();
+    let frames = state
+        .metrics
+        .history
+        .iter()
+        .map(|sample| {
+            let influence: Vec<i32> = sample
+                .civs
+                .iter()
+                .map(|c| if c.alive { c.influence } else { 0 })
+                .collect();
+            let territory = *known.entry(influence).or_insert_with_key(|influence| {
+                territories.push(territory(state, influence));
+                territories.len() - 1
+            });
+            Frame {
+                turn: sample.turn,
+                territory,
+                power: sample.civs.iter().map(|c| c.power).collect(),
+                resources: sample.civs.iter().map(|c| c.resources).collect(),
+                alive: sample.civs.iter().map(|c| c.alive).collect(),
+            }
+        })
+        .collect();
+    let log: Vec<&(i32, GameEvent)> = state.events.since(0).collect();
+    let battles = log
+        .iter()
+        .filter(|(_, e)| e.is_battle())
+        .filter_map(|(turn, event)| {
+            let place = match event {
+                GameEvent::Battle { defender, .. }
+                | GameEvent::Sortie { defender, .. }
+                | GameEvent::Raid { defender, .. }
+                | GameEvent::Eliminated { civ: defender } => *defender,
+                _ => return None,
+            };
+            let city = &state.civ(place).ok()?.city;
+            Some(Marker {
+                turn: *turn,
+                x: city.x,
+                y: city.y,
+                text: event.describe(state),
+            })
+        })
+        .collect();
+    Timeline {
+        seed: &state.map.seed,
+        width: state.map.width,
+        height: state.map.height,
+        terrain: state
+            .map
+            .tiles
+            .iter()
+            .flatten()
+            .map(Terrain::to_char)
+            .collect(),
+        civs: state
+            .civilizations
+            .iter()
+            .map(|c| CityMark {
+                name: c.city.name.clone(),
+                color: css_color(&c.city.color),
+                x: c.city.x,
+                y: c.city.y,
+            })
+            .collect(),
+        frames,
+        territories,
+        battles,
+        events: log
+            .iter()
+            .map(|(turn, e)| (*turn, e.describe(state)))
+            .collect(),
+    }
+}
+
+/// Report of the whole game as a standalone HTML page: the territory of every civilization on
+/// the map, turn by turn under a slider, with the battles fought and the power and resource
+/// graphs. Built from the metrics and the event log, so it needs no other file.
+pub fn html(state: &GameState) -> String {
+    // `<` would let a city name close the script element
+    let data = serde_json::to_string(&timeline(state))
+        .unwrap_or_else(|_| "null".to_string())
+        .replace('<', "\\u003c");
+    TEMPLATE
+        .replace("/*DATA*/null", &data)
+        .replace("/*MARKER_TURNS*/3", &MARKER_TURNS.to_string())
+}
+
+/// Write the report of a finished game to `reports/` of the output directory.
+///
+/// # Errors
+/// Fails when the report cannot be written.
+pub fn write_report(state: &GameState) -> anyhow::Result<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let filename = format!("reports/{secs}.html");
+    write_to_file(&filename, &html(state))?;
+    Ok(output_path(&filename))
+}
+
+const TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="fr">
+<head>
+<meta charset="utf-8">
+<title>CLIvilization - chronologie</title>
+<style>
+  body { background: #111; color: #ddd; font-family: monospace; margin: 1.5em; }
+  h1 { font-size: 1.3em; }
+  #controls { margin: 1em 0; display: flex; gap: 1em; align-items: center; }
+  #slider { width: 480px; }
+  #layout { display: flex; gap: 2em; flex-wrap: wrap; }
+  canvas { background: #000; border: 1px solid #333; }
+  #legend span { margin-right: 1.2em; }
+  #events { max-height: 240px; overflow-y: auto; padding-left: 1.2em; }
+  #events li.battle { color: #f66; }
+</style>
+</head>
+<body>
+<h1 id="title"></h1>
+<div id="controls">
+  <button id="play">Lecture</button>
+  <input type="range" id="slider" min="0" value="0">
+  <span id="turn"></span>
+</div>
+<div id="legend"></div>
+<div id="layout">
+  <canvas id="map"></canvas>
+  <div>
+    <h2>Puissance</h2>
+    <canvas id="power" width="480" height="180"></canvas>
+    <h2>Ressources</h2>
+    <canvas id="resources" width="480" height="180"></canvas>
+    <h2>Événements du tour</h2>
+    <ul id="events"></ul>
+  </div>
+</div>
+<script>
+const DATA = /*DATA*/null;
+const MARKER_TURNS = /*MARKER_TURNS*/3;
+const TERRAIN = { '~': '#1d3b5a', '.': '#4b6b33', ':': '#b59a5a', '^': '#6d6259', '=': '#3a7bbf', '&': '#2f5424', '+': '#dfe7ea', '%': '#5b5a3a' };
+const cell = Math.max(2, Math.floor(720 / Math.max(DATA.width, 1)));
+const map = document.getElementById('map');
+map.width = DATA.width * cell;
+map.height = DATA.height * cell;
+const slider = document.getElementById('slider');
+slider.max = Math.max(DATA.frames.length - 1, 0);
+slider.value = slider.max;
+document.getElementById('title').textContent =
+  `Partie '${DATA.seed}' : ${DATA.frames.length ? DATA.frames[DATA.frames.length - 1].turn : 0} tours`;
+const legend = document.getElementById('legend');
+DATA.civs.forEach(c => {
+  const span = document.createElement('span');
+  span.style.color = c.color;
+  span.textContent = '■ ' + c.name;
+  legend.appendChild(span);
+});
+
+function drawMap(frame) {
+  const ctx = map.getContext('2d');
+  const owners = DATA.territories[frame.territory] || '';
+  for (let y = 0; y < DATA.height; y++) {
+    for (let x = 0; x < DATA.width; x++) {
+      const i = y * DATA.width + x;
+      ctx.globalAlpha = 1;
+      ctx.fillStyle = TERRAIN[DATA.terrain[i]] || '#000';
+      ctx.fillRect(x * cell, y * cell, cell, cell);
+      const owner = parseInt(owners[i], 36);
+      if (!isNaN(owner) && DATA.civs[owner]) {
+        ctx.globalAlpha = 0.45;
+        ctx.fillStyle = DATA.civs[owner].color;
+        ctx.fillRect(x * cell, y * cell, cell, cell);
+      }
+    }
+  }
+  ctx.globalAlpha = 1;
+  ctx.font = `${Math.max(10, cell * 3)}px monospace`;
+  DATA.civs.forEach((c, i) => {
+    ctx.fillStyle = frame.alive[i] === false ? '#555' : c.color;
+    ctx.fillRect((c.x - 1) * cell, (c.y - 1) * cell, cell * 3, cell * 3);
+    ctx.fillStyle = '#fff';
+    ctx.fillText(c.name, (c.x + 2) * cell, c.y * cell);
+  });
+  DATA.battles.filter(b => b.turn <= frame.turn && frame.turn - b.turn < MARKER_TURNS).forEach(b => {
+    ctx.globalAlpha = 1 - (frame.turn - b.turn) / MARKER_TURNS;
+    ctx.strokeStyle = '#ff3030';
+    ctx.lineWidth = 2;
+    ctx.beginPath();
+    ctx.arc((b.x + 0.5) * cell, (b.y + 0.5) * cell, cell * 4, 0, 2 * Math.PI);
+    ctx.stroke();
+  });
+  ctx.globalAlpha = 1;
+}
+
+function drawChart(canvas, key, index) {
+  const ctx = canvas.getContext('2d');
+  ctx.clearRect(0, 0, canvas.width, canvas.height);
+  const values = DATA.frames.flatMap(f => f[key]);
+  const top = Math.max(1, ...values);
+  const step = canvas.width / Math.max(DATA.frames.length - 1, 1);
+  const y = v => canvas.height - 4 - (Math.max(v, 0) / top) * (canvas.height - 8);
+  DATA.civs.forEach((c, i) => {
+    ctx.strokeStyle = c.color;
+    ctx.lineWidth = 1.5;
+    ctx.beginPath();
+    DATA.frames.forEach((f, t) => {
+      const v = f[key][i] === undefined ? 0 : f[key][i];
+      if (t === 0) ctx.moveTo(0, y(v)); else ctx.lineTo(t * step, y(v));
+    });
+    ctx.stroke();
+  });
+  ctx.strokeStyle = '#888';
+  ctx.beginPath();
+  ctx.moveTo(index * step, 0);
+  ctx.lineTo(index * step, canvas.height);
+  ctx.stroke();
+}
+
+function listEvents(frame) {
+  const list = document.getElementById('events');
+  list.replaceChildren();
+  const battles = new Set(DATA.battles.map(b => b.turn + ' ' + b.text));
+  DATA.events.filter(([turn]) => turn === frame.turn).forEach(([turn, text]) => {
+    const item = document.createElement('li');
+    item.textContent = text;
+    if (battles.has(turn + ' ' + text)) item.className = 'battle';
+    list.appendChild(item);
+  });
+  if (!list.children.length) {
+    const item = document.createElement('li');
+    item.textContent = 'Aucun événement';
+    list.appendChild(item);
+  }
+}
+
+function show() {
+  const index = Number(slider.value);
+  const frame = DATA.frames[index];
+  if (!frame) return;
+  document.getElementById('turn').textContent = `Tour ${frame.turn}`;
+  drawMap(frame);
+  drawChart(document.getElementById('power'), 'power', index);
+  drawChart(document.getElementById('resources'), 'resources', index);
+  listEvents(frame);
+}
+
+let timer = null;
+document.getElementById('play').addEventListener('click', e => {
+  if (timer) {
+    clearInterval(timer);
+    timer = null;
+    e.target.textContent = 'Lecture';
+    return;
+  }
+  if (Number(slider.value) >= Number(slider.max)) slider.value = 0;
+  e.target.textContent = 'Pause';
+  timer = setInterval(() => {
+    if (Number(slider.value) >= Number(slider.max)) {
+      clearInterval(timer);
+      timer = null;
+      e.target.textContent = 'Lecture';
+      return;
+    }
+    slider.value = Number(slider.value) + 1;
+    show();
+  }, 300);
+});
+slider.addEventListener('input', show);
+show();
+</script>
+</body>
+</html>
+"#;
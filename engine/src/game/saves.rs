@@ -0,0 +1,316 @@
+use crate::game::campaign::CampaignProgress;
+use crate::game::conquest::Conquest;
+use crate::game::daily::date_from_days;
+use crate::game::difficulty::DynamicDifficulty;
+use crate::game::diplomacy::Diplomacy;
+use crate::game::fog::{self, Fog};
+use crate::game::migrations;
+use crate::game::state::{Civilization, GameState, Travel};
+use crate::game::surrender::Surrender;
+use crate::game::terraform::{self, TerrainChange};
+use crate::game::tutorial::TutorialState;
+use crate::game::utils::{output_dir, output_path, write_to_file};
+use crate::game::victory::PuzzleState;
+use crate::game::{Game, UiState};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Version of the save format, bumped along with a migration in `migrations::SAVES` so that
+/// saves of older versions still load
+pub const SAVE_VERSION: u32 = 2;
+/// Longest save name accepted by `save <name>`
+pub const MAX_SAVE_NAME_LEN: usize = 40;
+
+/// A save found in the output directory.
+#[derive(Debug, Clone)]
+pub struct SaveInfo {
+    pub path: PathBuf,
+    /// What kind of save this is (`game` or `campaign`)
+    pub kind: &'static str,
+    /// Short description of the saved progress
+    pub summary: String,
+    /// Last modification date (YYYY-MM-DD, UTC), if the filesystem reports it
+    pub modified: Option<String>,
+}
+
+/// Running match written by the `save <name>` action. The configuration is kept as loaded and
+/// the map as its seed, both rebuilt on load; everything the turns changed is stored as is.
+/// Display state, the metrics history and the event log start afresh.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    /// Configuration the game was loaded from, None for the default game
+    pub config: Option<String>,
+    pub seed: String,
+    pub turn: i32,
+    pub player_turn: usize,
+    pub civilizations: Vec<Civilization>,
+    pub diplomacy: Diplomacy,
+    pub travels: Vec<Travel>,
+    pub pending_conquests: Vec<Conquest>,
+    pub puzzle: Option<PuzzleState>,
+    pub tutorial: Option<TutorialState>,
+    pub game_over: bool,
+    pub winner: Option<usize>,
+    /// Tiles changed by map events and wonders, applied again on the regenerated map
+    #[serde(default)]
+    pub terrain_changes: Vec<TerrainChange>,
+    /// Tiles explored by each civilization, when the fog of war is on
+    #[serde(default)]
+    pub fog: Option<Fog>,
+    /// Surrender offers waiting for an answer and the turns each civilization has been behind,
+    /// when AI civilizations can give up
+    #[serde(default)]
+    pub surrender: Option<Surrender>,
+    /// Income adjustments of the dynamic difficulty and the turn they were worked out, when it
+    /// is on
+    #[serde(default)]
+    pub difficulty: Option<DynamicDifficulty>,
+}
+
+impl SavedGame {
+    pub fn of(game: &Game) -> Self {
+        Self::of_state(&game.state, game.config_source.clone())
+    }
+
+    /// Save of `state`, loaded from `config`.
+    pub fn of_state(state: &GameState, config: Option<String>) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            config,
+            seed: state.map.seed.clone(),
+            turn: state.turn,
+            player_turn: state.player_turn,
+            civilizations: state.civilizations.clone(),
+            diplomacy: state.diplomacy.clone(),
+            travels: state.travels.clone(),
+            pending_conquests: state.pending_conquests.clone(),
+            puzzle: state.puzzle.clone(),
+            tutorial: state.tutorial.clone(),
+            game_over: state.game_over,
+            winner: state.winner,
+            terrain_changes: state.terrain_changes.clone(),
+            fog: state.fog.clone(),
+            surrender: state.surrender.clone(),
+            difficulty: state.difficulty.clone(),
+        }
+    }
+
+    /// Rebuild the game: load the configuration, regenerate the map from its seed, then put
+    /// the saved progress back.
+    ///
+    /// # Errors
+    /// Fails when the save format is not supported, or its configuration does not load or defines
+    /// other civilizations.
+    pub fn restore(self) -> Result<Game> {
+        if self.version != SAVE_VERSION {
+            return Err(anyhow!(
+                "save format {} is not supported (expected {SAVE_VERSION})",
+                self.version
+            ));
+        }
+        let mut game = match &self.config {
+            Some(config) => Game::from_string(config)?,
+            None => Game::new(),
+        };
+        self.apply(&mut game.state)?;
+        game.ui_state = UiState::Normal;
+        Ok(game)
+    }
+
+    /// Put the saved progress back on `state`, loaded from the configuration of the save: the map
+    /// is regenerated from its seed and everything the turns changed is replaced.
+    ///
+    /// # Errors
+    /// Fails when the configuration of `state` defines other civilizations.
+    pub fn apply(self, state: &mut GameState) -> Result<()> {
+        if self.civilizations.len() != state.civilizations.len() {
+            return Err(anyhow!(
+                "save has {} civilizations but its configuration defines {}",
+                self.civilizations.len(),
+                state.civilizations.len()
+            ));
+        }
+        state.map = state.map.reseeded(self.seed);
+        state.map_buffer_cache = None;
+        state.path_cache = RefCell::default();
+        state.terrain_changes.clear();
+        for change in self.terrain_changes {
+            terraform::set_terrain(state, change);
+        }
+        state.turn = self.turn;
+        state.player_turn = self.player_turn;
+        state.civilizations = self.civilizations;
+        state.diplomacy = self.diplomacy;
+        state.travels = self.travels;
+        state.pending_conquests = self.pending_conquests;
+        state.puzzle = self.puzzle;
+        state.tutorial = self.tutorial;
+        state.game_over = self.game_over;
+        state.winner = self.winner;
+        if state.fog.is_some() && self.fog.is_some() {
+            state.fog = self.fog;
+        }
+        if state.surrender.is_some() && self.surrender.is_some() {
+            state.surrender = self.surrender;
+        }
+        if self.difficulty.is_some() {
+            state.difficulty = self.difficulty;
+        }
+        fog::update(state);
+        Ok(())
+    }
+}
+
+/// Read a save written by this engine or an older one, migrated to the current format.
+///
+/// # Errors
+/// Fails when the text is not a save, or its format is newer or unsupported.
+pub fn parse(text: &str) -> Result<SavedGame> {
+    let document =
+        migrations::migrate(serde_json::from_str(text)?, migrations::SAVES, SAVE_VERSION)?;
+    Ok(serde_json::from_value(document)?)
+}
+
+/// Path of the save named `name`, rejecting names that could escape the saves directory.
+///
+/// # Errors
+/// Fails when the name is empty, too long, or holds anything but letters, digits, '-' or '_'.
+pub fn save_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty()
+        || name.len() > MAX_SAVE_NAME_LEN
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!(
+            "Save names are up to {MAX_SAVE_NAME_LEN} letters, digits, '-' or '_'"
+        ));
+    }
+    Ok(output_path(&format!("saves/{name}.json")))
+}
+
+/// Write the running match to `saves/<name>.json` of the output directory, replacing any save
+/// of the same name.
+///
+/// # Errors
+/// Fails when the name is invalid or the save cannot be written.
+pub fn write(game: &Game, name: &str) -> Result<PathBuf> {
+    let path = save_path(name)?;
+    let json = serde_json::to_string(&SavedGame::of(game))?;
+    write_to_file(&format!("saves/{name}.json"), &json)?;
+    Ok(path)
+}
+
+/// Load a save given by path, or by name from the saves directory.
+///
+/// # Errors
+/// Fails when the save cannot be read, parsed or restored.
+pub fn load(save: &str) -> Result<Game> {
+    let path = if Path::new(save).is_file() {
+        PathBuf::from(save)
+    } else {
+        save_path(save)?
+    };
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read save {}", path.display()))?;
+    let saved = parse(&text).with_context(|| format!("invalid save {}", path.display()))?;
+    log::info!("Loading save {} (turn {})", path.display(), saved.turn);
+    saved.restore()
+}
+
+/// JSON files of `dir` in the output directory, with their modification date.
+fn json_files(dir: &str) -> Vec<(PathBuf, Option<String>)> {
+    let Ok(entries) = std::fs::read_dir(output_dir().join(dir)) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+        .map(|entry| {
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| date_from_days(d.as_secs() / 86_400));
+            (entry.path(), modified)
+        })
+        .collect()
+}
+
+/// Saves found in the output directory, sorted by path: running matches written by `save` and
+/// campaign progress. Unreadable files are listed as such rather than skipped.
+pub fn discover() -> Vec<SaveInfo> {
+    let mut saves = Vec::new();
+    for (path, modified) in json_files("saves") {
+        let summary = match std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| parse(&s).ok())
+        {
+            Some(game) => {
+                let player = game
+                    .civilizations
+                    .get(game.player_turn)
+                    .map_or("?", |c| c.city.name.as_str());
+                format!("turn {}, {player} to play", game.turn)
+            }
+            None => "unreadable".to_string(),
+        };
+        saves.push(SaveInfo {
+            path,
+            kind: "game",
+            summary,
+            modified,
+        });
+    }
+    for (path, modified) in json_files("campaigns") {
+        let summary = match std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<CampaignProgress>(&s).ok())
+        {
+            Some(progress) => {
+                let won = progress.completed.iter().filter(|c| **c).count();
+                let carried = progress.carry_over.map_or(0, |c| c.resources);
+                format!(
+                    "{won}/{} scenarios won, {carried} resources carried over",
+                    progress.completed.len()
+                )
+            }
+            None => "unreadable".to_string(),
+        };
+        saves.push(SaveInfo {
+            path,
+            kind: "campaign",
+            summary,
+            modified,
+        });
+    }
+    saves.sort_by(|a, b| a.path.cmp(&b.path));
+    saves
+}
+
+/// Text listing for the `saves` subcommand.
+pub fn report(saves: &[SaveInfo]) -> String {
+    let mut lines = vec![format!("Output directory: {}", output_dir().display())];
+    if saves.is_empty() {
+        lines.push("No saves found.".to_string());
+    }
+    for save in saves {
+        let name = save
+            .path
+            .file_stem()
+            .map_or_else(String::new, |s| s.to_string_lossy().into_owned());
+        lines.push(format!(
+            "  [{}] {name}: {} (modified {}) - {}",
+            save.kind,
+            save.summary,
+            save.modified.as_deref().unwrap_or("unknown"),
+            save.path.display()
+        ));
+    }
+    lines.join("\n")
+}
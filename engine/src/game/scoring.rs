@@ -0,0 +1,93 @@
+use crate::ast::Scoring;
+use crate::game::composition;
+use crate::game::history;
+use crate::game::limits;
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+use std::fmt::Write;
+
+/// Points of a civilization before the survival and victory bonuses. Without a `[scoring]`
+/// section, its remaining resources plus its army power; otherwise the weighted sum of its
+/// buildings, wonders, units, resources and turns survived, missing weights counting for nothing.
+pub fn base_score(state: &GameState, civ_index: usize) -> i32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    let Some(scoring) = &state.scoring else {
+        return civ
+            .resources
+            .ressources
+            .saturating_add(state.calculate_city_power(civ_index));
+    };
+    let is_wonder = |id: &String| {
+        scoring
+            .wonders
+            .as_ref()
+            .is_some_and(|w| w.values.contains(id))
+    };
+    let (wonders, buildings): (Vec<_>, Vec<_>) = civ
+        .city
+        .buildings
+        .elements
+        .iter()
+        .partition(|b| is_wonder(&b.id_building));
+    let units = composition::total(&civ.city.units.units);
+    let weights = &scoring.weights;
+    // in i64, saturated to i32, since the DSL allows large weights and counts
+    let weighted = |w: Option<u32>, n: i64| i64::from(w.unwrap_or(0)).saturating_mul(n);
+    let score = [
+        weighted(weights.per_building, count(buildings.len())),
+        weighted(weights.per_wonder, count(wonders.len())),
+        weighted(weights.per_unit, i64::from(units)),
+        weighted(weights.per_resource, i64::from(civ.resources.ressources)),
+        weighted(
+            weights.per_turn,
+            i64::from(turns_survived(state, civ_index)),
+        ),
+    ]
+    .into_iter()
+    .fold(0, i64::saturating_add);
+    limits::saturate(score)
+}
+
+fn count(n: usize) -> i64 {
+    i64::try_from(n).unwrap_or(i64::MAX)
+}
+
+/// Turns a civilization lived through: until now, or until its city was razed.
+pub fn turns_survived(state: &GameState, civ_index: usize) -> i32 {
+    state
+        .civ(civ_index)
+        .ok()
+        .and_then(|c| c.eliminated_at)
+        .unwrap_or(state.turn)
+}
+
+/// Check the wonders of a `[scoring]` section are buildings of the configuration.
+///
+/// # Errors
+/// Fails when a wonder is not a building of the configuration.
+pub fn check(scoring: &Scoring, state: &GameState) -> Result<()> {
+    for wonder in scoring.wonders.iter().flat_map(|w| &w.values) {
+        if state.building_def(wonder).is_none() {
+            return Err(anyhow!(
+                "unknown building '{wonder}' in the wonders of [scoring]"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Final scores of every civilization, best first, for the game over screen.
+pub fn summary(state: &GameState) -> String {
+    let mut scores: Vec<(i32, &str)> = (0..state.civilizations.len())
+        .map(|i| (history::score(state, i), state.city_name(i)))
+        .collect();
+    scores.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scores
+        .iter()
+        .fold("Scores:".to_string(), |mut text, (score, name)| {
+            let _ = write!(text, "\n- {name}: {score}");
+            text
+        })
+}
@@ -0,0 +1,92 @@
+use anyhow::{Context, Result, anyhow};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the tournament key. When set, every file the game writes in the
+/// output directory gets a `.sig` file next to it.
+pub const KEY_VAR: &str = "TOURNAMENT_KEY";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Tournament key from the environment, if set and not empty.
+pub fn tournament_key() -> Option<String> {
+    std::env::var(KEY_VAR).ok().filter(|k| !k.is_empty())
+}
+
+/// Path of the signature of `path`: the same name with `.sig` appended.
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+fn mac(key: &[u8], parts: &[&[u8]]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac
+}
+
+/// HMAC-SHA256 of `parts`, one after the other, under `key`.
+pub(crate) fn hmac(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    mac(key, parts).finalize().into_bytes().into()
+}
+
+/// Lowercase hex encoding of `bytes`.
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{b:02x}");
+        hex
+    })
+}
+
+/// Hex-encoded HMAC-SHA256 of `content` under `key`.
+pub fn signature(key: &str, content: &[u8]) -> String {
+    hex(&hmac(key.as_bytes(), &[content]))
+}
+
+/// Write the signature of the file at `path` next to it.
+///
+/// # Errors
+/// Fails when the file cannot be read or the signature written.
+pub fn sign_file(path: &Path, key: &str) -> Result<PathBuf> {
+    let content =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let sig_path = signature_path(path);
+    std::fs::write(&sig_path, signature(key, &content) + "\n")
+        .with_context(|| format!("failed to write {}", sig_path.display()))?;
+    Ok(sig_path)
+}
+
+/// Check the file at `path` against its `.sig` file. Fails when the signature is missing,
+/// malformed, or does not match the content under `key`.
+///
+/// # Errors
+/// Fails when the signature is missing, malformed or does not match.
+pub fn verify_file(path: &Path, key: &str) -> Result<()> {
+    let content =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let sig_path = signature_path(path);
+    let hex = std::fs::read_to_string(&sig_path)
+        .with_context(|| format!("missing signature {}", sig_path.display()))?;
+    let hex = hex.trim();
+    let expected = (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| anyhow!("malformed signature {}", sig_path.display()))?;
+    mac(key.as_bytes(), &[&content])
+        .verify_slice(&expected)
+        .map_err(|_| {
+            anyhow!(
+                "{} was tampered with or signed with another key",
+                path.display()
+            )
+        })
+}
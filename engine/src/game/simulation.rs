@@ -0,0 +1,187 @@
+use crate::game::composition;
+use crate::game::diplomacy;
+use crate::game::fog;
+use crate::game::sortie;
+use crate::game::state::{GameState, Travel};
+use crate::game::supply;
+use anyhow::{Result, anyhow};
+
+/// Safety cap on simulated turn starts, in case the weather keeps an army in place
+const MAX_SIMULATED_TURNS: u32 = 1000;
+
+/// Expected result of an attack, computed on a cloned state without committing anything.
+#[derive(Debug, Clone)]
+pub struct AttackForecast {
+    /// Units that would be sent
+    pub sent: u32,
+    /// Turn starts before the army arrives, weather delays included
+    pub turns: u32,
+    /// Units lost on the way out of supply
+    pub attrition: u32,
+    /// Defender power when the army arrives, counting recruitments already in progress
+    pub defender_power: i32,
+    /// Extra defender power it could still recruit before the army arrives
+    pub reinforcement: i32,
+    /// Chance to eliminate the defender, from 0.0 to 1.0
+    pub win_probability: f64,
+    /// Defender units lost in the simulated battle
+    pub defender_losses: u32,
+}
+
+fn total_units(state: &GameState, civ_index: usize) -> u32 {
+    state
+        .civ(civ_index)
+        .map_or(0, |c| composition::total(&c.city.units.units))
+}
+
+/// Worst case of what the defender could add on top of its known recruitments: one unit of its
+/// strongest producible type per production cycle of its own turns, as far as resources allow.
+fn possible_reinforcement(state: &GameState, defender: usize, defender_turns: u32) -> i32 {
+    let Ok(civ) = state.civ(defender) else {
+        return 0;
+    };
+    let producers = civ.city.buildings.elements.iter().filter_map(|b| {
+        let def = state.building_def(&b.id_building)?;
+        let unit = def.production.prod_unit_id.as_ref()?;
+        let attack = state.unit_def(unit)?.attack;
+        Some((def.production.time.max(1), def.production.cost, attack))
+    });
+    let Some((time, cost, attack)) = producers.max_by_key(|(time, _, attack)| attack / time) else {
+        return 0;
+    };
+    let by_time = defender_turns / time;
+    let by_resources = u32::try_from(civ.resources.ressources)
+        .unwrap_or(0)
+        .checked_div(cost)
+        .unwrap_or(by_time);
+    i32::try_from(u64::from(by_time.min(by_resources)) * u64::from(attack)).unwrap_or(i32::MAX)
+}
+
+/// Run an attack on a clone of the state through the regular turn pipeline until the army
+/// arrives. Known defender units and recruitments are taken into account; recruitments the
+/// defender has not started yet are the uncertainty behind the win probability. Under the fog of
+/// war, only cities the attacker explored can be simulated, since the forecast reveals their army.
+///
+/// # Errors
+/// Fails when the defender city is unexplored or the attack cannot start.
+///
+/// # Panics
+/// Never: a successful `start_attack` always queues a travel.
+pub fn simulate_attack(
+    state: &GameState,
+    attacker: usize,
+    defender: usize,
+    amount: Option<u32>,
+) -> Result<AttackForecast> {
+    let city = &state.civ(defender)?.city;
+    if !fog::explored(
+        state,
+        attacker,
+        (city.x.cast_signed(), city.y.cast_signed()),
+    ) {
+        return Err(anyhow!(
+            "{} lies unexplored under the fog of war, scout it before simulating an attack",
+            city.name
+        ));
+    }
+    let mut sim = state.clone();
+    // a what-if must neither consume the puzzle budget nor end the real game
+    sim.puzzle = None;
+    sim.text_mode = false;
+    sim.start_attack(attacker, defender, amount)?;
+    let travel = sim
+        .travels
+        .last()
+        .expect("start_attack queued a travel")
+        .clone();
+    let in_transit = |sim: &GameState| {
+        sim.travels.iter().rposition(|t| {
+            t.attacker == attacker && t.defender == defender && t.total == travel.total
+        })
+    };
+
+    // each turn start moves every army by one step unless the weather holds it back, so the
+    // attack resolves on the turn start where it leaves the travels
+    let mut turns = 0;
+    let mut arriving = travel.amount;
+    let mut arriving_power = composition::power(&sim, &travel);
+    let mut defender_turns = 0;
+    let mut player = sim.player_turn;
+    let mut defender_power = diplomacy::defense_power(&sim, defender);
+    let mut units_before_battle = total_units(&sim, defender);
+    while let Some(i) = in_transit(&sim)
+        && turns < MAX_SIMULATED_TURNS
+    {
+        let t = &sim.travels[i];
+        let lost = supply::next_losses(&sim, t);
+        arriving = t.amount - lost;
+        arriving_power = composition::power_after(&sim, t, lost);
+        if t.remaining <= 1 {
+            defender_power = diplomacy::defense_power(&sim, defender);
+            units_before_battle = total_units(&sim, defender);
+        }
+        player = (player + 1) % sim.civilizations.len();
+        if player == 0 {
+            sim.turn += 1;
+        }
+        sim.player_turn = player;
+        if player == defender {
+            defender_turns += 1;
+        }
+        sim.on_turn_start(player);
+        turns += 1;
+    }
+
+    let won = !sim.civ(defender).is_ok_and(|c| c.alive)
+        || sim.pending_conquests.iter().any(|c| c.defender == defender);
+    let defender_losses = units_before_battle.saturating_sub(total_units(&sim, defender));
+    let reinforcement = possible_reinforcement(state, defender, defender_turns);
+    let margin = arriving_power - defender_power;
+    let win_probability = if !won || margin <= 0 {
+        0.0
+    } else if margin > reinforcement {
+        1.0
+    } else {
+        f64::from(margin) / (f64::from(reinforcement) + 1.0)
+    };
+
+    Ok(AttackForecast {
+        sent: travel.amount,
+        turns,
+        attrition: travel.amount - arriving,
+        defender_power,
+        reinforcement,
+        win_probability,
+        defender_losses,
+    })
+}
+
+/// Expected result of a garrison intercepting an army one turn before it reaches its walls.
+#[derive(Debug, Clone)]
+pub struct SortieForecast {
+    /// Strength of the army once it has taken its last step, attrition included
+    pub army: i32,
+    /// Power of the garrison in the field, without the help of defense pacts
+    pub garrison: i32,
+    /// Garrison power against the army, in percent
+    pub odds: i32,
+    /// Garrison units lost if it sorties and wins
+    pub losses: u32,
+}
+
+/// Forecast of the garrison of the target of `travel` meeting it in the field. Nothing is
+/// committed; `sortie::apply_sorties` decides from this forecast.
+pub fn simulate_sortie(state: &GameState, travel: &Travel) -> SortieForecast {
+    let army = composition::power_after(state, travel, supply::next_losses(state, travel));
+    let garrison = state.calculate_city_power(travel.defender);
+    SortieForecast {
+        army,
+        garrison,
+        odds: i32::try_from(i64::from(garrison) * 100 / i64::from(army.max(1))).unwrap_or(i32::MAX),
+        losses: u32::try_from(
+            u64::from(army.max(0).cast_unsigned()) * u64::from(sortie::SORTIE_LOSSES_PERCENT) / 100,
+        )
+        .unwrap_or(u32::MAX)
+        .min(total_units(state, travel.defender)),
+    }
+}
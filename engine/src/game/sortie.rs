@@ -0,0 +1,53 @@
+use crate::game::events::GameEvent;
+use crate::game::simulation::simulate_sortie;
+use crate::game::state::GameState;
+use log::info;
+
+/// Garrison power needed against an incoming army to sortie, in percent of the army strength
+pub const SORTIE_ODDS_PERCENT: i32 = 150;
+/// Garrison units lost when intercepting an army, in percent of its strength. Lower than the
+/// half lost when an army breaks against the walls, as the garrison picks the ground.
+pub const SORTIE_LOSSES_PERCENT: u32 = 25;
+
+/// Let the garrisons of the cities with the sortie policy intercept the armies one turn from
+/// their walls, when the forecast gives them favorable odds. Intercepted armies are destroyed
+/// and the sorties are published in the event log.
+pub fn apply_sorties(state: &mut GameState) {
+    let mut i = 0;
+    while i < state.travels.len() {
+        let travel = &state.travels[i];
+        let (attacker, defender) = (travel.attacker, travel.defender);
+        let defending = state
+            .civ(defender)
+            .is_ok_and(|c| c.alive && c.policy.sortie)
+            && (travel.raiding() || state.civ(attacker).is_ok_and(|c| c.alive));
+        if travel.remaining != 1 || travel.returning() || !defending {
+            i += 1;
+            continue;
+        }
+        let forecast = simulate_sortie(state, travel);
+        if forecast.odds < SORTIE_ODDS_PERCENT {
+            i += 1;
+            continue;
+        }
+        let travel = state.travels.remove(i);
+        let lost = state.remove_units_from_city(defender, forecast.losses);
+        state.metrics.lose_units(defender, lost);
+        if !travel.raiding() {
+            state.metrics.lose_units(attacker, travel.amount);
+        }
+        info!(
+            "Sortie: civ {defender} intercepted {} units of civ {attacker} ({} vs {}), lost {lost} units",
+            travel.amount, forecast.garrison, forecast.army
+        );
+        state.events.publish(
+            state.turn,
+            GameEvent::Sortie {
+                attacker,
+                defender,
+                intercepted: travel.amount,
+                lost,
+            },
+        );
+    }
+}
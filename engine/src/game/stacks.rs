@@ -0,0 +1,166 @@
+use crate::ast::{self, StackOverflow, UnitInstance};
+use crate::game::composition;
+use crate::game::state::{Civilization, GameState};
+use anyhow::{Result, anyhow};
+use log::info;
+
+/// Stack limits of the `[game]` section: units a city holds and units an army carries. No limit
+/// when unset.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StackLimits {
+    pub city: Option<u32>,
+    pub army: Option<u32>,
+    /// Whether units over a limit wait outside rather than being refused
+    pub wait: bool,
+}
+
+impl StackLimits {
+    pub fn from_game(game: &ast::Game) -> Self {
+        Self {
+            city: game.city_stack.map(|l| l.max(1)),
+            army: game.army_stack.map(|l| l.max(1)),
+            wait: !matches!(game.stack_overflow, Some(StackOverflow::REFUSE)),
+        }
+    }
+}
+
+/// Units garrisoned in the city of `civ`, those waiting outside left out.
+pub fn garrison(civ: &Civilization) -> u32 {
+    composition::total(&civ.city.units.units)
+}
+
+/// Units waiting outside the city of `civ`.
+pub fn waiting(civ: &Civilization) -> u32 {
+    composition::total(&civ.waiting)
+}
+
+/// Units the city of `civ_index` can still take in.
+fn room(state: &GameState, civ_index: usize) -> u32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    state
+        .stacks
+        .city
+        .map_or(u32::MAX, |limit| limit.saturating_sub(garrison(civ)))
+}
+
+/// Check that `amount` more units may be trained in the city of `civ_index`. Refused when they
+/// would not fit and overflowing units are refused rather than kept waiting outside.
+///
+/// # Errors
+/// Fails when the units would not fit in the city.
+pub fn check_recruitment(state: &GameState, civ_index: usize, amount: u32) -> Result<()> {
+    let (Some(limit), false) = (state.stacks.city, state.stacks.wait) else {
+        return Ok(());
+    };
+    let civ = state.civ(civ_index)?;
+    let training = civ
+        .recruitments
+        .iter()
+        .fold(0, |sum: u32, r| sum.saturating_add(r.amount));
+    if garrison(civ)
+        .saturating_add(training)
+        .saturating_add(amount)
+        > limit
+    {
+        return Err(anyhow!("{} holds at most {limit} units", civ.city.name));
+    }
+    Ok(())
+}
+
+/// Units an army may take out of the `asked` ones: refused over the army limit, or capped to it
+/// when overflowing units wait, here in their city.
+///
+/// # Errors
+/// Fails when `asked` is over the army limit and overflowing units are refused.
+pub fn army_size(state: &GameState, asked: u32) -> Result<u32> {
+    match state.stacks.army {
+        Some(limit) if asked > limit && !state.stacks.wait => {
+            Err(anyhow!("Armies hold at most {limit} units"))
+        }
+        Some(limit) => Ok(asked.min(limit)),
+        None => Ok(asked),
+    }
+}
+
+/// Unit amounts an army may take out of the asked `units`, the last types capped first when
+/// overflowing units wait in their city.
+///
+/// # Errors
+/// Fails when the units are over the army limit and overflowing units are refused.
+pub fn cap_units(state: &GameState, units: &[(String, u32)]) -> Result<Vec<(String, u32)>> {
+    let asked = units
+        .iter()
+        .fold(0, |asked: u32, (_, a)| asked.saturating_add(*a));
+    let mut room = army_size(state, asked)?;
+    Ok(units
+        .iter()
+        .map(|(unit, amount)| {
+            let sent = (*amount).min(room);
+            room -= sent;
+            (unit.clone(), sent)
+        })
+        .collect())
+}
+
+/// Bring `units` into the city of `civ_index`, trained or back from war. Units over the city
+/// limit wait outside, or are dismissed when overflowing units are refused. Returns the units
+/// that did not enter the city.
+pub fn station(state: &mut GameState, civ_index: usize, units: Vec<UnitInstance>) -> u32 {
+    let mut room = room(state, civ_index);
+    let wait = state.stacks.wait;
+    let Ok(civ) = state.civ_mut(civ_index) else {
+        return composition::total(&units);
+    };
+    let mut left_out: u32 = 0;
+    for mut unit in units {
+        let entering = unit.nb_units.min(room);
+        room -= entering;
+        if entering > 0 {
+            merge(
+                &mut civ.city.units.units,
+                UnitInstance {
+                    id_units: unit.id_units.clone(),
+                    nb_units: entering,
+                },
+            );
+        }
+        unit.nb_units -= entering;
+        if unit.nb_units == 0 {
+            continue;
+        }
+        left_out = left_out.saturating_add(unit.nb_units);
+        if wait {
+            merge(&mut civ.waiting, unit);
+        }
+    }
+    if left_out > 0 {
+        let fate = if wait {
+            "wait outside"
+        } else {
+            "were dismissed"
+        };
+        info!("City of civ {civ_index} full: {left_out} units {fate}");
+    }
+    left_out
+}
+
+/// Let the units waiting outside the city of `civ_index` in, as far as there is room.
+pub fn admit_waiting(state: &mut GameState, civ_index: usize) {
+    let Ok(civ) = state.civ_mut(civ_index) else {
+        return;
+    };
+    let waiting = std::mem::take(&mut civ.waiting);
+    if !waiting.is_empty() {
+        station(state, civ_index, waiting);
+    }
+}
+
+/// Add `unit` to `units`, with the units of the same type when there are some.
+fn merge(units: &mut Vec<UnitInstance>, unit: UnitInstance) {
+    match units.iter_mut().find(|u| u.id_units == unit.id_units) {
+        Some(instance) => instance.nb_units = instance.nb_units.saturating_add(unit.nb_units),
+        None => units.push(unit),
+    }
+}
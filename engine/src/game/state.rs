@@ -1,19 +1,77 @@
-use super::map::{GameMap, Terrain};
-use std::collections::{BinaryHeap};
-use std::cmp::Reverse;
+use super::DecisionTrace;
+use super::action::{Action, PolicySlider};
+use super::action_menu::ActionMenu;
+use super::aliases;
+use super::annotations::Annotation;
+use super::armies;
+use super::build_queue;
+use super::cancel;
+use super::capture::{self, Captured};
+use super::checksum;
+use super::composition;
+use super::conquest::{self, Conquest};
+use super::describe::describe_state;
+use super::difficulty::{self, DynamicDifficulty};
+use super::diplomacy::{self, Diplomacy, Treaty};
+use super::espionage;
+use super::events::{EventBus, GameEvent};
+use super::fog::{self, Fog};
+use super::income;
+use super::influence;
+use super::loans::{self, Loan};
+use super::map::GameMap;
+use super::metrics::Metrics;
+use super::paths::{self, PathCache, PathInfo};
+use super::policy::{self, Policy};
+use super::raiders::{self, RAIDERS, Raiders};
+use super::recruitment;
+use super::scoring;
+use super::simulation::simulate_attack;
+use super::sortie;
+use super::stacks::{self, StackLimits};
+use super::storage;
+use super::supply;
+use super::surrender::{self, Surrender};
+use super::tech::{self, Research};
+use super::terraform::{self, TerrainChange};
+use super::traits::Modifiers;
+use super::turn_rules::{
+    check_action_point, end_turn_blockers, projected_income, refill_action_points,
+    spend_action_point,
+};
+use super::tutorial::TutorialState;
+use super::upkeep;
+use super::utils::{did_you_mean, unknown_with_suggestions};
+use super::valid_actions::{self, ValidAction};
+use super::victory::{self, CustomConditions, PuzzleState};
+use super::war::{self, War};
+use super::weather;
 use crate::ast::{
-    BuildingDef, BuildingInstance, BuildingInstanceArray, City, PlayerType, PrereqArray,
-    Production, ProductionType, UnitDef, UnitInstance, UnitInstanceArray,
+    BlockingCondition, BuildingDef, BuildingInstance, BuildingInstanceArray, City, MapEvent,
+    PlayerType, PrereqArray, Production, ProductionType, Regions, Relation, Scoring, TechDef,
+    UnitDef, UnitInstance, UnitInstanceArray, ValueArray,
 };
-use ratatui::style::Color;
-use log::{debug, info, warn};
 use anyhow::{Result, anyhow};
+use log::{debug, info, warn};
+use ratatui_core::style::Color;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Share of a building cost refunded when demolishing it, in percent
+pub const DEMOLISH_REFUND_PERCENT: u32 = 50;
+
+/// Longest name a city can be renamed to, so map labels and panels stay readable
+pub const MAX_CITY_NAME_LEN: usize = 20;
+
+/// Popups kept waiting under the open one, the oldest of the least urgent being dropped beyond
+pub const MAX_WAITING_POPUPS: usize = 16;
 
 /// Represents a civilization (player) in the game.
 ///
 /// Each civilization has resources, a city with buildings and units,
 /// and can be alive or defeated.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Civilization {
     /// Resource pool
     pub resources: Resources,
@@ -25,17 +83,127 @@ pub struct Civilization {
     pub constructions: Vec<Construction>,
     /// In-progress recruitments (units being trained)
     pub recruitments: Vec<Recruitment>,
+    /// Outstanding loans, repaid at each turn start
+    pub loans: Vec<Loan>,
+    /// Army morale in percent, lowered by missed loan installments and scaling military power
+    pub morale: u32,
+    /// Tax and military focus sliders
+    pub policy: Policy,
+    /// Index in the building definitions of the building producing each unit, among the
+    /// finished buildings of the city. Refreshed with `refresh_producers` when they change.
+    pub producers: BTreeMap<String, usize>,
+    /// Action points left this turn, when the `action_points` turn rule paces the game
+    pub action_points: u32,
+    /// Passive bonuses from the `[traits]` section
+    #[serde(default)]
+    pub modifiers: Modifiers,
+    /// Short mood or status the AI playing the civilization shows next to its name
+    #[serde(default)]
+    pub mood: Option<String>,
+    /// Technologies researched so far
+    #[serde(default)]
+    pub researched: BTreeSet<String>,
+    /// Technology being researched
+    #[serde(default)]
+    pub research: Option<Research>,
+    /// Units waiting outside the city, full when they were trained or came back
+    #[serde(default)]
+    pub waiting: Vec<UnitInstance>,
+    /// Buildings shut down for want of maintenance, by name: they produce nothing until the city
+    /// pays for them again
+    #[serde(default)]
+    pub disabled: BTreeSet<String>,
+    /// Cities taken from other civilizations, when `capture` is on in `[game]`
+    #[serde(default)]
+    pub captured: Vec<Captured>,
+    /// Civilizations that won a battle against the city, sharing its remains when it is
+    /// eliminated
+    #[serde(default)]
+    pub beaten_by: BTreeSet<usize>,
+    /// Turn the civilization lost its last city
+    #[serde(default)]
+    pub eliminated_at: Option<i32>,
+}
+
+/// Indexes of the building and unit definitions by name, so the turn logic does not scan the
+/// definitions for every building of every city. Rebuilt by `index_defs` when they change.
+#[derive(Debug, Clone, Default)]
+pub struct DefIndex {
+    buildings: HashMap<String, usize>,
+    units: HashMap<String, usize>,
+}
+
+impl DefIndex {
+    pub fn building<'a>(&self, defs: &'a [BuildingDef], name: &str) -> Option<&'a BuildingDef> {
+        self.buildings.get(name).and_then(|&i| defs.get(i))
+    }
+
+    pub fn unit<'a>(&self, defs: &'a [UnitDef], name: &str) -> Option<&'a UnitDef> {
+        self.units.get(name).and_then(|&i| defs.get(i))
+    }
 }
 
 /// Resource pool for a civilization.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Resources {
     /// Amount of resources available. The game uses a single resource type currently.
     pub ressources: i32,
+    /// Resources paid for constructions, recruitments, gifts and spies since the start
+    #[serde(default)]
+    pub spent: u32,
+}
+
+impl Civilization {
+    /// Pay `amount` from the resource pool, counted in the resources spent.
+    pub fn spend(&mut self, amount: u32) {
+        self.resources.ressources = self
+            .resources
+            .ressources
+            .saturating_sub(amount.cast_signed());
+        self.resources.spent = self.resources.spent.saturating_add(amount);
+    }
+
+    /// Add `amount` to the resource pool, saturated so large incomes cannot overflow it.
+    pub fn earn(&mut self, amount: i32) {
+        self.resources.ressources = self.resources.ressources.saturating_add(amount);
+    }
+
+    /// Whether the whitelist and blacklist of the city let it build `building`.
+    pub fn allows_building(&self, building: &str) -> bool {
+        allowed(
+            building,
+            self.city.whitelist_buildings.as_ref(),
+            self.city.blacklist_buildings.as_ref(),
+        )
+    }
+
+    /// Whether the whitelist and blacklist of the city let it hire `unit`.
+    pub fn allows_unit(&self, unit: &str) -> bool {
+        allowed(
+            unit,
+            self.city.whitelist_units.as_ref(),
+            self.city.blacklist_units.as_ref(),
+        )
+    }
+}
+
+/// A name is allowed when it is in the whitelist, if any, and not in the blacklist, if any.
+fn allowed(name: &str, whitelist: Option<&ValueArray>, blacklist: Option<&ValueArray>) -> bool {
+    whitelist.is_none_or(|w| w.values.iter().any(|v| v == name))
+        && !blacklist.is_some_and(|b| b.values.iter().any(|v| v == name))
+}
+
+/// Civilization at `index` out of `civilizations`, borrowing only them so that the definitions
+/// of the state stay readable alongside.
+fn civ_in(civilizations: &mut [Civilization], index: usize) -> Result<&mut Civilization> {
+    civilizations
+        .get_mut(index)
+        .ok_or_else(|| anyhow!("Invalid civilization index {index}"))
 }
 
 /// Core game state aggregating map, players, turns, and UI state. Mutable caching fields optimize rendering hot paths.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct GameState {
     pub map: GameMap,
     pub turn: i32,
@@ -47,6 +215,8 @@ pub struct GameState {
 
     /// Whether the seed input field is being edited
     pub seed_editing: bool,
+    /// Whether changing the map seed is forbidden (daily challenge)
+    pub seed_locked: bool,
 
     /// Camera position for viewing the map
     pub camera_x: i32,
@@ -55,14 +225,64 @@ pub struct GameState {
     pub camera_mode: bool,
     /// Cached map rendering buffer
     pub map_buffer_cache: Option<Vec<Vec<Color>>>,
+    /// Ways found between tiles, kept until the terrain revision changes
+    pub path_cache: RefCell<PathCache>,
+    /// Tiles shown by the map at the last frame (width, height), photographed by the photo mode
+    pub map_viewport: (usize, usize),
 
     /// Building and unit definitions (templates)
     pub buildings: Vec<BuildingDef>,
     pub units: Vec<UnitDef>,
-
-    /// Victory conditions
+    /// Technologies of the `[techs]` section
+    pub techs: Vec<TechDef>,
+    /// Regions of the map and their crossings, None when the whole map is one region
+    pub regions: Option<Regions>,
+    /// Terrain changes scheduled by the `[map_events]` section
+    pub map_events: Vec<MapEvent>,
+    /// Diplomatic stances of the `[diplomacy]` section, set at load or at their turn
+    pub relations: Vec<Relation>,
+    /// Tiles whose terrain changed since the map was generated
+    pub terrain_changes: Vec<TerrainChange>,
+    /// Tiles explored by each civilization, None when the fog of war is off
+    pub fog: Option<Fog>,
+    /// Whether a won attack may capture the city, from `capture` in `[game]`
+    pub capture: bool,
+    /// Turns each season lasts, None when the game has no seasons
+    pub season_length: Option<u32>,
+    /// Resources a city stores before counting its storage buildings, None when unlimited
+    pub storage: Option<u32>,
+    /// Spoils of war of the `[war]` section
+    pub war: War,
+    /// Raiding parties of the `[raiders]` section, None without raiders
+    pub raiders: Option<Raiders>,
+    /// When AI civilizations give up, from the `[surrender]` section, None when they never do
+    pub surrender: Option<Surrender>,
+    /// Lookup of the definitions by name
+    pub defs: DefIndex,
+
+    /// Last turn of the game, 0 for no limit
     pub nb_turns: u32,
+    /// Resources a civilization spends in all to end the game, 0 for no limit
     pub resources_spent: u32,
+    /// Favor needed with every surviving civilization for a diplomatic victory, 0 for none
+    pub favor_threshold: u32,
+
+    /// Favor between civilizations and their treaties
+    pub diplomacy: Diplomacy,
+
+    /// Hash of the configuration the game was loaded from, 0 for the default game
+    pub config_hash: u32,
+
+    /// Distance from their home city beyond which armies suffer attrition
+    pub supply_range: u32,
+    /// Share of an army lost on each travel turn out of supply, in percent
+    pub attrition: u32,
+    /// Constructions a city may have queued at once
+    pub build_queue: u32,
+    /// Share of the unspent cost refunded by cancellations, in percent
+    pub cancel_refund: u32,
+    /// Units a city holds and an army carries at most
+    pub stacks: StackLimits,
 
     /// Zoom level for map rendering (1, 2, or 3)
     pub zoom_level: u8,
@@ -73,15 +293,90 @@ pub struct GameState {
 
     /// Currently open popup (if any)
     pub popup: Option<Popup>,
+    /// Popups waiting under the open one, the next one to show last
+    pub popups: Vec<Popup>,
 
     /// Active travels (attacks in transit)
     pub travels: Vec<Travel>,
 
     /// Whether the game is over
     pub game_over: bool,
+    /// Winner decided by the victory engine once the game is over
+    pub winner: Option<usize>,
+
+    /// Puzzle rules and consumed budget, when the scenario is a puzzle
+    pub puzzle: Option<PuzzleState>,
+    /// Scenario conditions preventing a human player from ending their turn
+    pub end_turn_rules: Vec<BlockingCondition>,
+    /// Action points given to each civilization every turn, unlimited actions when None
+    pub action_points: Option<u32>,
+    /// Production adjustments of the AIs, when dynamic difficulty is enabled
+    pub difficulty: Option<DynamicDifficulty>,
+    /// Whether the dynamic difficulty panel is shown
+    pub show_difficulty: bool,
+    /// Weights of the final score, from the `[scoring]` section
+    pub scoring: Option<Scoring>,
+    /// Tutorial steps and progress, when the scenario is a tutorial
+    pub tutorial: Option<TutorialState>,
+
+    /// Won battles waiting for the attacker to raze or pillage the defeated city
+    pub pending_conquests: Vec<Conquest>,
+    /// Name given to `save`, written by the game once the action is accepted
+    pub pending_save: Option<String>,
+    /// Whether a `suggest` action waits for the advisor, asked by the game
+    pub pending_suggestion: bool,
 
     /// Whether an AI is currently thinking/acting
     pub ai_thinking: bool,
+    /// Civilization whose turn was last drawn and when it started, for the turn banner
+    pub turn_banner: Option<(usize, std::time::Instant)>,
+    /// Whether AI playback is paused in the TUI
+    pub ai_paused: bool,
+    /// Index of the AI playback speed in `AI_PLAYBACK_DELAYS_MS`
+    pub ai_speed: usize,
+
+    /// Most recent explained AI decision, shown in the debug panel when spectating
+    pub last_decision: Option<DecisionTrace>,
+
+    /// Whether the textual description panel is shown (accessibility)
+    pub text_mode: bool,
+    /// Textual description of the situation, regenerated each turn in text mode
+    pub descriptions: Vec<String>,
+    /// Scroll offset of the description panel
+    pub description_scroll: u16,
+
+    /// Per-turn power and resource history of every civilization
+    pub metrics: Metrics,
+    /// Whether the history graphs panel is shown
+    pub show_graphs: bool,
+    /// Whether the end-of-game statistics cover the map once the game is over
+    pub show_end_screen: bool,
+
+    /// Battles and completions of the game, read by each player in the event log
+    pub events: EventBus,
+    /// Whether the event log panel is shown
+    pub show_events: bool,
+    /// Scroll offset of the event log panel
+    pub events_scroll: u16,
+    /// Whether the possible actions panel is shown
+    pub show_actions: bool,
+    /// Actions the current player can play, as listed by the possible actions panel
+    pub valid_actions: Vec<ValidAction>,
+    /// Checksum of the state `valid_actions` was computed for
+    pub valid_actions_at: Option<u32>,
+    /// Action menu opened with `m`, as an alternative to typing actions
+    pub action_menu: Option<ActionMenu>,
+
+    /// Spectator notes placed so far, or read from the replay being played
+    pub annotations: Vec<Annotation>,
+    /// Whether the action bar is used to type a spectator note
+    pub annotating: bool,
+
+    /// Definitions changed by the last live reload, shown in a side panel until dismissed
+    pub config_changes: Vec<String>,
+
+    /// End conditions registered by the program embedding the engine
+    pub custom_victories: CustomConditions,
 }
 
 /// A popup dialog shown to the user for choices or information.
@@ -93,36 +388,130 @@ pub struct Popup {
     pub choices: Vec<String>,
     /// User's input/selection
     pub input: String,
+    pub priority: PopupPriority,
+    /// When the popup came on screen, for the auto-dismissal of informational popups
+    pub shown: std::time::Instant,
+}
+
+/// How urgent a popup is: a popup opening over a more urgent one waits under it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PopupPriority {
+    /// Reports and errors, which only need reading
+    Info,
+    /// Popups waiting for a choice
+    Question,
+    /// The end of the game
+    Critical,
+}
+
+impl Popup {
+    /// Popup with no input yet, a question when it offers choices and informational otherwise.
+    pub fn new(title: &str, prompt: &str, choices: Vec<String>) -> Self {
+        let priority = if choices.is_empty() {
+            PopupPriority::Info
+        } else {
+            PopupPriority::Question
+        };
+        Self {
+            title: title.to_string(),
+            prompt: prompt.to_string(),
+            choices,
+            input: String::new(),
+            priority,
+            shown: std::time::Instant::now(),
+        }
+    }
+
+    /// Choice the input selects: its 1-based index, or the first choice it starts, regardless of
+    /// case.
+    fn chosen(&self) -> Option<String> {
+        let sel = self.input.trim();
+        if let Ok(idx) = sel.parse::<usize>()
+            && (1..=self.choices.len()).contains(&idx)
+        {
+            return Some(self.choices[idx - 1].clone());
+        }
+        self.choices
+            .iter()
+            .find(|c| c.to_lowercase().starts_with(&sel.to_lowercase()))
+            .cloned()
+    }
 }
 
 /// An in-progress building construction.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Construction {
     pub id_building: String,
     pub remaining: u32,
     pub total: u32,
+    /// Resources paid for it, partly refunded when it is cancelled
+    #[serde(default)]
+    pub cost: u32,
 }
 
 /// An in-progress unit recruitment.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Recruitment {
     pub id_unit: String,
     pub remaining: u32,
+    pub total: u32,
     pub amount: u32,
+    /// Building producing the unit
+    pub producer: String,
+    /// Resources paid for the recruitment
+    #[serde(default)]
+    pub cost: u32,
 }
 
-/// A traveling attack force.
+/// An army stack marching on a rival city.
 ///
-/// Represents units moving from one city to attack another.
-/// The attack resolves when remaining reaches 0.
-#[derive(Debug, Clone)]
+/// The stack stands on `path[step]` and moves along its path at each turn start. The attack
+/// resolves when it reaches the last tile, the city of the defender.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Travel {
     pub attacker: usize,
     pub defender: usize,
     pub amount: u32,
+    /// Units of each type in the army, `amount` in all
+    #[serde(default)]
+    pub composition: Vec<UnitInstance>,
+    /// Turns left before the stack reaches the city
     pub remaining: u32,
     pub total: u32,
     pub path: Vec<(i32, i32)>,
+    /// Index in the path of the tile the stack stands on
+    #[serde(default)]
+    pub step: usize,
+    /// Movement points carried toward the next tile of the path
+    #[serde(default)]
+    pub progress: i64,
+}
+
+impl Travel {
+    /// Tile the stack stands on.
+    pub fn tile(&self) -> (i32, i32) {
+        self.path
+            .get(self.step)
+            .or(self.path.last())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether the army was recalled and marches back to its own city.
+    pub fn returning(&self) -> bool {
+        self.attacker == self.defender
+    }
+
+    /// Whether the army belongs to the raiders rather than to a civilization.
+    pub fn raiding(&self) -> bool {
+        self.attacker == RAIDERS
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameState {
@@ -133,15 +522,19 @@ impl GameState {
     /// - Two civilizations (one player, one AI)
     /// - Default buildings and units
     /// - Starting resources
+    #[allow(clippy::too_many_lines)]
     pub fn new() -> Self {
-        Self {
+        let mut state = Self {
             map: GameMap::new_random(160usize, 40usize),
             turn: 1,
             player_turn: 0,
 
             civilizations: Vec::from([
                 Civilization {
-                    resources: Resources { ressources: 100 },
+                    resources: Resources {
+                        ressources: 100,
+                        spent: 0,
+                    },
                     city: City {
                         name: "Player".to_string(),
                         x: 10,
@@ -159,13 +552,34 @@ impl GameState {
                         units: UnitInstanceArray { units: Vec::new() },
                         whitelist_buildings: None,
                         whitelist_units: None,
+                        policy: None,
+                        ai_seed: None,
+                        persona: None,
+                        agenda: None,
                     },
                     alive: true,
                     constructions: Vec::new(),
                     recruitments: Vec::new(),
+                    loans: Vec::new(),
+                    morale: 100,
+                    policy: Policy::default(),
+                    producers: BTreeMap::new(),
+                    action_points: 0,
+                    modifiers: Modifiers::default(),
+                    mood: None,
+                    researched: BTreeSet::new(),
+                    research: None,
+                    waiting: Vec::new(),
+                    disabled: BTreeSet::new(),
+                    captured: Vec::new(),
+                    beaten_by: BTreeSet::new(),
+                    eliminated_at: None,
                 },
                 Civilization {
-                    resources: Resources { ressources: 100 },
+                    resources: Resources {
+                        ressources: 100,
+                        spent: 0,
+                    },
                     city: City {
                         name: "IA".to_string(),
                         x: 20,
@@ -183,25 +597,79 @@ impl GameState {
                         units: UnitInstanceArray { units: Vec::new() },
                         whitelist_buildings: None,
                         whitelist_units: None,
+                        policy: None,
+                        ai_seed: None,
+                        persona: None,
+                        agenda: None,
                     },
                     alive: true,
                     constructions: Vec::new(),
                     recruitments: Vec::new(),
+                    loans: Vec::new(),
+                    morale: 100,
+                    policy: Policy::default(),
+                    producers: BTreeMap::new(),
+                    action_points: 0,
+                    modifiers: Modifiers::default(),
+                    mood: None,
+                    researched: BTreeSet::new(),
+                    research: None,
+                    waiting: Vec::new(),
+                    disabled: BTreeSet::new(),
+                    captured: Vec::new(),
+                    beaten_by: BTreeSet::new(),
+                    eliminated_at: None,
                 },
             ]),
 
             seed_editing: false,
+            seed_locked: false,
             camera_x: 0,
             camera_y: 0,
             camera_mode: false,
+            map_viewport: (0, 0),
             map_buffer_cache: None,
+            path_cache: RefCell::default(),
             zoom_level: 1,
             action_editing: false,
             action_input: String::new(),
             popup: None,
+            popups: Vec::new(),
             travels: Vec::new(),
             game_over: false,
+            winner: None,
+            puzzle: None,
+            end_turn_rules: Vec::new(),
+            action_points: None,
+            difficulty: None,
+            show_difficulty: false,
+            scoring: None,
+            tutorial: None,
+            pending_conquests: Vec::new(),
+            pending_save: None,
+            pending_suggestion: false,
             ai_thinking: false,
+            turn_banner: None,
+            ai_paused: false,
+            ai_speed: 3,
+            last_decision: None,
+            text_mode: false,
+            descriptions: Vec::new(),
+            description_scroll: 0,
+            metrics: Metrics::default(),
+            show_graphs: false,
+            show_end_screen: true,
+            events: EventBus::default(),
+            show_events: false,
+            events_scroll: 0,
+            show_actions: false,
+            valid_actions: Vec::new(),
+            valid_actions_at: None,
+            action_menu: None,
+            annotations: Vec::new(),
+            annotating: false,
+            config_changes: Vec::new(),
+            custom_victories: CustomConditions::default(),
             buildings: Vec::from([
                 BuildingDef {
                     name: "Farm".to_string(),
@@ -218,6 +686,10 @@ impl GameState {
                         time: 1,
                     },
                     slots: 1,
+                    terraform: None,
+                    defense: None,
+                    maintenance: None,
+                    storage: None,
                 },
                 BuildingDef {
                     name: "Barracks".to_string(),
@@ -234,14 +706,92 @@ impl GameState {
                         time: 3,
                     },
                     slots: 1,
+                    terraform: None,
+                    defense: None,
+                    maintenance: None,
+                    storage: None,
                 },
             ]),
             units: Vec::from([UnitDef {
                 name: "Warrior".to_string(),
                 attack: 1,
+                defense: None,
+                prerequisites: None,
+                upkeep: None,
             }]),
+            techs: Vec::new(),
+            regions: None,
+            map_events: Vec::new(),
+            relations: Vec::new(),
+            terrain_changes: Vec::new(),
+            fog: None,
+            capture: false,
+            season_length: None,
+            storage: None,
+            war: War::default(),
+            raiders: None,
+            surrender: None,
             nb_turns: 500,
             resources_spent: 300,
+            favor_threshold: 0,
+            diplomacy: Diplomacy::default(),
+            config_hash: 0,
+            supply_range: supply::DEFAULT_SUPPLY_RANGE,
+            attrition: supply::DEFAULT_ATTRITION,
+            build_queue: build_queue::DEFAULT_BUILD_QUEUE,
+            cancel_refund: cancel::DEFAULT_CANCEL_REFUND_PERCENT,
+            stacks: StackLimits::default(),
+            defs: DefIndex::default(),
+        };
+        state.index_defs();
+        state
+    }
+
+    /// Index the building and unit definitions by name and refresh the producers of every city.
+    /// The first definition of a name wins, as with the former linear scans.
+    pub fn index_defs(&mut self) {
+        let mut defs = DefIndex::default();
+        for (i, b) in self.buildings.iter().enumerate() {
+            defs.buildings.entry(b.name.clone()).or_insert(i);
+        }
+        for (i, u) in self.units.iter().enumerate() {
+            defs.units.entry(u.name.clone()).or_insert(i);
+        }
+        self.defs = defs;
+        for civ_index in 0..self.civilizations.len() {
+            self.refresh_producers(civ_index);
+        }
+    }
+
+    /// Definition of the technology called `name`.
+    pub fn tech_def(&self, name: &str) -> Option<&TechDef> {
+        self.techs.iter().find(|t| t.name == name)
+    }
+
+    /// Definition of the building called `name`.
+    pub fn building_def(&self, name: &str) -> Option<&BuildingDef> {
+        self.defs.building(&self.buildings, name)
+    }
+
+    /// Definition of the unit called `name`.
+    pub fn unit_def(&self, name: &str) -> Option<&UnitDef> {
+        self.defs.unit(&self.units, name)
+    }
+
+    /// Rebuild the producers map of a city, after its finished buildings changed.
+    pub fn refresh_producers(&mut self, civ_index: usize) {
+        let Some(civ) = self.civilizations.get_mut(civ_index) else {
+            return;
+        };
+        civ.producers.clear();
+        for b_inst in &civ.city.buildings.elements {
+            if let Some(&i) = self.defs.buildings.get(&b_inst.id_building)
+                && let Some(bdef) = self.buildings.get(i)
+                && matches!(bdef.production.prod_type, ProductionType::UNIT)
+                && let Some(unit) = &bdef.production.prod_unit_id
+            {
+                civ.producers.entry(unit.clone()).or_insert(i);
+            }
         }
     }
 
@@ -269,7 +819,7 @@ impl GameState {
 
     /// Regenerate map from current seed and exit edit mode. Atomic operation ensures consistent state.
     pub fn submit_seed(&mut self) {
-        self.map = GameMap::new(self.map.seed.clone(), self.map.width, self.map.height);
+        self.map = self.map.reseeded(self.map.seed.clone());
         self.seed_editing = false;
     }
 
@@ -278,6 +828,71 @@ impl GameState {
         self.camera_mode = !self.camera_mode;
     }
 
+    /// Advance the tutorial past every completed step. Returns true when it just finished,
+    /// in which case a popup congratulates the player.
+    pub fn update_tutorial(&mut self) -> bool {
+        let Some(mut tutorial) = self.tutorial.take() else {
+            return false;
+        };
+        let finished = tutorial.advance(self);
+        self.tutorial = Some(tutorial);
+        if finished {
+            self.open_popup(
+                "Tutorial",
+                "Tutorial complete! You know the basics, have fun.",
+                vec![],
+            );
+        }
+        finished
+    }
+
+    /// Sample the power and resources of every civilization for the current turn.
+    pub fn record_metrics(&mut self) {
+        let mut metrics = std::mem::take(&mut self.metrics);
+        metrics.record(self);
+        self.metrics = metrics;
+    }
+
+    /// Toggle the textual description panel, regenerating its content when shown.
+    pub fn toggle_text_mode(&mut self) {
+        self.text_mode = !self.text_mode;
+        self.refresh_descriptions();
+    }
+
+    /// Regenerate the textual description for the current player (text mode only).
+    pub fn refresh_descriptions(&mut self) {
+        if self.text_mode {
+            self.descriptions = describe_state(self, self.player_turn);
+            self.description_scroll = 0;
+        }
+    }
+
+    /// Recompute the possible actions of the current player when the gameplay state changed since
+    /// they were last listed.
+    pub fn refresh_valid_actions(&mut self) {
+        let checksum = checksum::state_checksum(self);
+        if self.valid_actions_at != Some(checksum) {
+            self.valid_actions = valid_actions::list(self, self.player_turn);
+            self.valid_actions_at = Some(checksum);
+        }
+    }
+
+    /// Scroll the description panel by `delta` lines, staying within its content.
+    pub fn scroll_descriptions(&mut self, delta: i16) {
+        let max = u16::try_from(self.descriptions.len().saturating_sub(1)).unwrap_or(u16::MAX);
+        self.description_scroll = self
+            .description_scroll
+            .saturating_add_signed(delta)
+            .min(max);
+    }
+
+    /// Scroll the event log panel by `delta` lines, staying within the events of the player.
+    pub fn scroll_events(&mut self, delta: i16) {
+        let count = self.events.events_of(self.player_turn).count();
+        let max = u16::try_from(count.saturating_sub(1)).unwrap_or(u16::MAX);
+        self.events_scroll = self.events_scroll.saturating_add_signed(delta).min(max);
+    }
+
     /// Start editing an action input.
     pub fn start_action_input(&mut self) {
         self.action_input.clear();
@@ -308,114 +923,268 @@ impl GameState {
     /// * `prompt` - Prompt text
     /// * `choices` - List of available choices (empty if free text input)
     pub fn open_popup(&mut self, title: &str, prompt: &str, choices: Vec<String>) {
-        self.popup = Some(Popup {
-            title: title.to_string(),
-            prompt: prompt.to_string(),
-            choices,
-            input: String::new(),
-        });
+        self.show_popup(Popup::new(title, prompt, choices));
+    }
+
+    /// Show `popup` over the open one unless the open one is more urgent, in which case it
+    /// waits under it, above the less urgent waiting popups. Among popups equally urgent, the
+    /// last opened shows first.
+    pub fn show_popup(&mut self, mut popup: Popup) {
+        match self.popup.take() {
+            Some(open) if open.priority > popup.priority => {
+                let at = self
+                    .popups
+                    .iter()
+                    .position(|p| p.priority > popup.priority)
+                    .unwrap_or(self.popups.len());
+                self.popups.insert(at, popup);
+                self.popup = Some(open);
+            }
+            open => {
+                self.popups.extend(open);
+                popup.shown = std::time::Instant::now();
+                self.popup = Some(popup);
+            }
+        }
+        if self.popups.len() > MAX_WAITING_POPUPS {
+            let dropped = self.popups.remove(0);
+            debug!(
+                "Dropping the waiting popup '{}', too many popups are waiting",
+                dropped.title
+            );
+        }
         // stop editing action while popup is open
         self.action_editing = false;
     }
 
-    /// Close the currently open popup.
+    /// Close the currently open popup, showing the next waiting one if any.
     pub fn close_popup(&mut self) {
-        self.popup = None;
+        self.popup = self.popups.pop().map(|mut p| {
+            p.shown = std::time::Instant::now();
+            p
+        });
     }
 
-    /// Parse and execute action with automatic popup generation for missing parameters. Lowercase parsing provides case-insensitive UX.
+    /// Close the open popup when it is informational and was shown for `after` or longer.
+    /// Returns true when it was closed.
+    pub fn expire_popup(&mut self, after: std::time::Duration) -> bool {
+        let expired = self
+            .popup
+            .as_ref()
+            .is_some_and(|p| p.priority == PopupPriority::Info && p.shown.elapsed() >= after);
+        if expired {
+            self.close_popup();
+        }
+        expired
+    }
+
+    /// Parse the action bar into an `Action` and execute it, with automatic popup generation for
+    /// missing parameters. Lowercase parsing provides case-insensitive UX.
     ///
     /// # Returns
     /// true if a popup was opened for further input, false otherwise
     pub fn submit_action(&mut self) -> bool {
         let txt = self.action_input.trim().to_lowercase();
-        debug!("submit_action called (player={}): '{}'", self.player_turn, txt);
+        debug!(
+            "submit_action called (player={}): '{}'",
+            self.player_turn, txt
+        );
         if txt.is_empty() {
             self.action_editing = false;
             self.action_input.clear();
             return false;
         }
 
-        // end turn
-        if txt == "end" || txt == "end turn" {
-            self.player_turn = (self.player_turn + 1) % self.civilizations.len();
-            if self.player_turn == 0 {
-                self.turn += 1;
+        // user aliases are expanded by the front-end, which records the expanded action
+        let expanded = aliases::expand_shorthand(&txt);
+        if expanded != txt {
+            debug!("Expanded action '{txt}' to '{expanded}'");
+        }
+        // tutorials only accept the action expected by the current step
+        if let Some(reason) = self
+            .tutorial
+            .as_ref()
+            .and_then(|t| t.rejection(self, &expanded))
+        {
+            debug!("Tutorial rejected action '{expanded}'");
+            self.open_popup("Tutorial", &reason, vec![]);
+            return true;
+        }
+        // the text typed by the player keeps its case (city names), unless an alias rewrote it
+        let source = if expanded == txt {
+            self.action_input.trim().to_string()
+        } else {
+            expanded
+        };
+        match Action::parse(&source) {
+            Ok(action) => self.perform(action),
+            Err(e) => {
+                warn!("Invalid action by player {}: {}", self.player_turn, e);
+                self.open_popup(e.title, &e.message, vec![]);
+                true
             }
-            // process turn start effects
-            info!("Player ended turn; new player_turn={} turn={}", self.player_turn, self.turn);
-            self.on_turn_start(self.player_turn);
+        }
+    }
 
-            self.action_input.clear();
-            self.action_editing = false;
-            return false;
+    /// Execute an action of the player whose turn it is, opening popups for missing parameters
+    /// and errors.
+    ///
+    /// # Returns
+    /// true if a popup was opened for further input, false otherwise
+    pub fn submit(&mut self, action: Action) -> bool {
+        let txt = action.to_string().to_lowercase();
+        // tutorials only accept the action expected by the current step
+        if let Some(reason) = self.tutorial.as_ref().and_then(|t| t.rejection(self, &txt)) {
+            debug!("Tutorial rejected action '{txt}'");
+            self.open_popup("Tutorial", &reason, vec![]);
+            return true;
         }
+        self.perform(action)
+    }
+
+    /// Execute an action already accepted by the tutorial.
+    #[allow(clippy::too_many_lines)]
+    fn perform(&mut self, action: Action) -> bool {
+        debug!("perform called (player={}): {:?}", self.player_turn, action);
+        match action {
+            Action::EndTurn => {
+                let blockers = end_turn_blockers(self, self.player_turn);
+                if !blockers.is_empty() {
+                    info!(
+                        "End of turn blocked for player {}: {:?}",
+                        self.player_turn, blockers
+                    );
+                    self.open_popup("End Turn", &blockers.join("\n"), vec![]);
+                    return true;
+                }
+                self.player_turn = (self.player_turn + 1) % self.civilizations.len();
+                if self.player_turn == 0 {
+                    self.turn += 1;
+                }
+                // process turn start effects
+                info!(
+                    "Player ended turn; new player_turn={} turn={}",
+                    self.player_turn, self.turn
+                );
+                self.on_turn_start(self.player_turn);
 
-        let parts: Vec<&str> = txt.split_whitespace().collect();
-        match parts.first().copied() {
-            Some("build") => {
+                self.action_input.clear();
+                self.action_editing = false;
+                return false;
+            }
+            Action::Build(building) => {
                 // build [type]
-                if parts.len() < 2 {
+                let Some(bname) = building.map(|b| b.to_lowercase()) else {
                     // open popup to choose building type
-                    let choices = self.buildings.iter().map(|b| b.name.clone()).collect();
-                    debug!("Opening Build popup for player {} (no building specified)", self.player_turn);
-                    self.open_popup("Build", "Choose building type:", choices);
-                    return true;
-                } else {
-                    let bname = parts[1];
-                    if let Some(bdef) = self
+                    let Ok(civ) = self.current_civ() else {
+                        return true;
+                    };
+                    let choices = self
                         .buildings
                         .iter()
-                        .find(|b| b.name.to_lowercase() == bname)
-                    {
-                        // attempt to start construction
-                        let name = bdef.name.clone();
-                        match self.start_construction(self.player_turn, &name) {
-                            Ok(()) => {
-                                info!("Started construction '{}' for civ {}", name, self.player_turn);
-                            }
-                            Err(err) => {
-                                warn!("Failed to start construction for civ {}: {}", self.player_turn, err);
-                                self.open_popup("Build", &format!("{:#}", err), vec![]);
-                                return true;
-                            }
+                        .filter(|b| civ.allows_building(&b.name))
+                        .map(|b| b.name.clone())
+                        .collect();
+                    debug!(
+                        "Opening Build popup for player {} (no building specified)",
+                        self.player_turn
+                    );
+                    self.open_popup("Build", "Choose building type:", choices);
+                    return true;
+                };
+                if let Some(bdef) = self
+                    .buildings
+                    .iter()
+                    .find(|b| b.name.to_lowercase() == bname)
+                {
+                    // attempt to start construction
+                    let name = bdef.name.clone();
+                    match self.start_construction(self.player_turn, &name) {
+                        Ok(()) => {
+                            info!(
+                                "Started construction '{}' for civ {}",
+                                name, self.player_turn
+                            );
+                        }
+                        Err(err) => {
+                            warn!(
+                                "Failed to start construction for civ {}: {}",
+                                self.player_turn, err
+                            );
+                            self.open_popup("Build", &format!("{err:#}"), vec![]);
+                            return true;
                         }
-                    } else {
-                        warn!("Unknown building requested by player {}: {}", self.player_turn, bname);
-                        self.open_popup("Build", &format!("Unknown building: {bname}"), vec![]);
-                        return true;
                     }
+                } else {
+                    warn!(
+                        "Unknown building requested by player {}: {}",
+                        self.player_turn, bname
+                    );
+                    let suggestions =
+                        did_you_mean(&bname, self.buildings.iter().map(|b| b.name.as_str()));
+                    let message = unknown_with_suggestions("building", &bname, &suggestions);
+                    self.open_popup("Build", &message, suggestions);
+                    return true;
                 }
             }
-            Some("hire" | "recruit") => {
-                if parts.len() < 2 {
-                    let choices = self.units.iter().map(|u| u.name.clone()).collect();
-                    debug!("Opening Hire popup for player {} (no unit specified)", self.player_turn);
+            Action::Hire { unit, amount } => {
+                let Some(uname) = unit.map(|u| u.to_lowercase()) else {
+                    let Ok(civ) = self.current_civ() else {
+                        return true;
+                    };
+                    let choices = self
+                        .units
+                        .iter()
+                        .filter(|u| civ.allows_unit(&u.name))
+                        .map(|u| u.name.clone())
+                        .collect();
+                    debug!(
+                        "Opening Hire popup for player {} (no unit specified)",
+                        self.player_turn
+                    );
                     self.open_popup("Hire", "Choose unit to hire:", choices);
                     return true;
-                } else {
-                    let uname = parts[1];
-                    if let Some(udef) = self.units.iter().find(|u| u.name.to_lowercase() == uname) {
-                        let uname_owned = udef.name.clone();
-                        match self.start_recruitment(self.player_turn, &uname_owned) {
-                            Ok(()) => {
-                                info!("Started recruitment '{}' for civ {}", uname_owned, self.player_turn);
-                            }
-                            Err(err) => {
-                                warn!("Failed to start recruitment for civ {}: {}", self.player_turn, err);
-                                self.open_popup("Hire", &format!("{:#}", err), vec![]);
-                                return true;
-                            }
+                };
+                if let Some(udef) = self.units.iter().find(|u| u.name.to_lowercase() == uname) {
+                    let uname_owned = udef.name.clone();
+                    match self.start_batch_recruitment(
+                        self.player_turn,
+                        &uname_owned,
+                        amount.unwrap_or(1),
+                    ) {
+                        Ok(()) => {
+                            info!(
+                                "Started recruitment '{}' for civ {}",
+                                uname_owned, self.player_turn
+                            );
+                        }
+                        Err(err) => {
+                            warn!(
+                                "Failed to start recruitment for civ {}: {}",
+                                self.player_turn, err
+                            );
+                            self.open_popup("Hire", &format!("{err:#}"), vec![]);
+                            return true;
                         }
-                    } else {
-                        warn!("Unknown unit requested by player {}: {}", self.player_turn, uname);
-                        self.open_popup("Hire", &format!("Unknown unit: {uname}"), vec![]);
-                        return true;
                     }
+                } else {
+                    warn!(
+                        "Unknown unit requested by player {}: {}",
+                        self.player_turn, uname
+                    );
+                    let suggestions =
+                        did_you_mean(&uname, self.units.iter().map(|u| u.name.as_str()));
+                    let message = unknown_with_suggestions("unit", &uname, &suggestions);
+                    self.open_popup("Hire", &message, suggestions);
+                    return true;
                 }
             }
-            Some("attack") => {
-                if parts.len() < 2 {
+            Action::Attack {
+                target,
+                amount,
+                units,
+            } => {
+                let Some(target) = target.map(|t| t.to_lowercase()) else {
                     // choose target player
                     let choices = self
                         .civilizations
@@ -424,184 +1193,782 @@ impl GameState {
                         .filter(|(i, _)| *i != self.player_turn)
                         .map(|(_, c)| c.city.name.clone())
                         .collect();
-                    debug!("Opening Attack popup for player {} (no target specified)", self.player_turn);
+                    debug!(
+                        "Opening Attack popup for player {} (no target specified)",
+                        self.player_turn
+                    );
                     self.open_popup("Attack", "Choose player to attack:", choices);
                     return true;
-                } else {
-                    let target = parts[1];
-                    if let Some((idx, _)) = self
-                        .civilizations
-                        .iter()
-                        .enumerate()
-                        .find(|(_, c)| c.city.name.to_lowercase() == target)
-                    {
-                        // optional amount as third argument
-                        let amount = if parts.len() >= 3 {
-                            parts[2].parse::<u32>().ok()
-                        } else {
-                            None
-                        };
-                        match self.start_attack(self.player_turn, idx, amount) {
-                            Ok(()) => {
-                                info!("Started attack from {} to {} (amount {:?})", self.player_turn, idx, amount);
-                            }
-                            Err(e) => {
-                                warn!("Failed to start attack for civ {}: {}", self.player_turn, e);
-                                self.open_popup("Attack", &format!("{:#}", e), vec![]);
-                                return true;
-                            }
+                };
+                if let Some((idx, _)) = self
+                    .civilizations
+                    .iter()
+                    .enumerate()
+                    .find(|(_, c)| c.city.name.to_lowercase() == target)
+                {
+                    match self.start_composed_attack(self.player_turn, idx, amount, &units) {
+                        Ok(()) => {
+                            info!(
+                                "Started attack from {} to {} (amount {:?})",
+                                self.player_turn, idx, amount
+                            );
+                        }
+                        Err(e) => {
+                            warn!("Failed to start attack for civ {}: {}", self.player_turn, e);
+                            self.open_popup("Attack", &format!("{e:#}"), vec![]);
+                            return true;
                         }
-                    } else {
-                        warn!("Unknown attack target requested by player {}: {}", self.player_turn, target);
-                        self.open_popup("Attack", &format!("Unknown target: {target}"), vec![]);
-                        return true;
                     }
+                } else {
+                    warn!(
+                        "Unknown attack target requested by player {}: {}",
+                        self.player_turn, target
+                    );
+                    let suggestions = self.suggest_targets(&target);
+                    let message = unknown_with_suggestions("target", &target, &suggestions);
+                    self.open_popup("Attack", &message, suggestions);
+                    return true;
                 }
             }
-            _ => {
-                warn!("Unknown action by player {}: {}", self.player_turn, txt);
-                self.open_popup("Action", &format!("Unknown action: {txt}"), vec![]);
+            Action::Queue(None) => {
+                // queue: entries of the build queue, to move or cancel
+                let (prompt, choices) = build_queue::queue_popup(self, self.player_turn, None);
+                self.open_popup("Build queue", &prompt, choices);
                 return true;
             }
-        }
-
-        // default: clear action
-        self.action_input.clear();
-        self.action_editing = false;
-        false
-    }
-
-    /// Handle popup submission with fuzzy choice matching. Accepts both numeric indices and name prefixes for flexibility.
-    ///
-    /// Interprets the user's selection (by index or name) and executes
-    /// the corresponding action (build, hire, attack, etc.).
-    pub fn submit_popup(&mut self) {
-        if self.popup.is_none() {
-            debug!("submit_popup called but no popup present (player {})", self.player_turn);
-            return;
-        }
-        let popup = self.popup.as_ref().unwrap().clone();
-        debug!("submit_popup called for '{}' (player {}), input='{}'", popup.title, self.player_turn, popup.input);
-        // if choices exist, try to parse input as index or name
-        if !popup.choices.is_empty() {
-            let sel = popup.input.trim();
-            let mut chosen: Option<String> = None;
-            if let Ok(idx) = sel.parse::<usize>()
-                && idx >= 1 && idx <= popup.choices.len() {
-                    chosen = Some(popup.choices[idx - 1].clone());
+            Action::Queue(Some(edit)) => {
+                // queue up|down|cancel <entry>
+                if let Err(e) = build_queue::apply(self, self.player_turn, edit) {
+                    warn!(
+                        "Build queue edit failed for civ {}: {}",
+                        self.player_turn, e
+                    );
+                    self.open_popup("Build queue", &format!("{e:#}"), vec![]);
+                    return true;
                 }
-            if chosen.is_none() {
-                // try match by name
-                for c in &popup.choices {
-                    if c.to_lowercase().starts_with(&sel.to_lowercase()) {
-                        chosen = Some(c.clone());
-                        break;
+            }
+            Action::Loan(amount) => {
+                // loan <amount>: instant resources, repaid with interest over the next turns
+                match loans::take_loan(self, self.player_turn, amount) {
+                    Ok(()) => {
+                        info!("Civ {} took a loan of {}", self.player_turn, amount);
+                    }
+                    Err(e) => {
+                        warn!("Failed to take loan for civ {}: {}", self.player_turn, e);
+                        self.open_popup("Loan", &format!("{e:#}"), vec![]);
+                        return true;
                     }
                 }
             }
-
-            if let Some(ch) = chosen {
-                // interpret by popup title
-                match popup.title.as_str() {
-                    "Build" => {
-                        if let Some(bdef) = self.buildings.iter().find(|b| b.name == ch) {
-                            let name = bdef.name.clone();
-                            if let Err(err) = self.start_construction(self.player_turn, &name) {
-                                warn!("start_construction failed in popup for civ {}: {}", self.player_turn, err);
-                                self.open_popup("Build", &format!("{:#}", err), vec![]);
-                                return;
+            Action::Demolish(building) => {
+                // demolish [building], always confirmed through a popup
+                let owned: Vec<String> = self
+                    .current_civ()
+                    .map(|c| {
+                        c.city
+                            .buildings
+                            .elements
+                            .iter()
+                            .map(|b| b.id_building.clone())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let Some(bname) = building.map(|b| b.to_lowercase()) else {
+                    let mut choices = owned;
+                    choices.sort();
+                    choices.dedup();
+                    self.open_popup("Demolish", "Choose building to demolish:", choices);
+                    return true;
+                };
+                if let Some(name) = owned.iter().find(|b| b.to_lowercase() == bname) {
+                    let name = name.clone();
+                    self.confirm_demolition(&name);
+                } else {
+                    warn!(
+                        "Unknown building to demolish for player {}: {}",
+                        self.player_turn, bname
+                    );
+                    let suggestions = did_you_mean(&bname, owned.iter().map(String::as_str));
+                    let message = unknown_with_suggestions("building", &bname, &suggestions);
+                    self.open_popup("Demolish", &message, suggestions);
+                }
+                return true;
+            }
+            Action::Rename { city, name } => {
+                // rename <city> <name>, the new name keeps its case
+                if let Err(err) = self.rename_city(self.player_turn, &city, &name) {
+                    warn!(
+                        "Failed to rename city for civ {}: {}",
+                        self.player_turn, err
+                    );
+                    self.open_popup("Rename", &format!("{err:#}"), vec![]);
+                    return true;
+                }
+            }
+            Action::ShowPolicy => {
+                let Ok(civ) = self.current_civ() else {
+                    return false;
+                };
+                let message = format!(
+                    "Tax: {}%, military focus: {}% (neutral at {}), sortie: {}\nUsage: policy tax <0-100> | policy military <0-100> | policy sortie <on|off>\nTaxes raise income but slow constructions down, military focus speeds recruitments up at the expense of income.\nWith the sortie on, the garrison intercepts armies one turn from the city when it is at least {}% as strong.",
+                    civ.policy.tax,
+                    civ.policy.military,
+                    policy::NEUTRAL,
+                    if civ.policy.sortie { "on" } else { "off" },
+                    sortie::SORTIE_ODDS_PERCENT
+                );
+                self.open_popup("Policy", &message, vec![]);
+                return true;
+            }
+            Action::SetPolicy { slider, value } => {
+                // policy [tax|military] [0-100]
+                let Ok(civ) = self.civ_mut(self.player_turn) else {
+                    return false;
+                };
+                match slider {
+                    PolicySlider::Tax => civ.policy.tax = value,
+                    PolicySlider::Military => civ.policy.military = value,
+                }
+                info!(
+                    "Civ {} set policy: {:?} {}",
+                    self.player_turn, slider, value
+                );
+            }
+            Action::SetSortie(enabled) => {
+                // policy sortie [on|off]
+                let Ok(civ) = self.civ_mut(self.player_turn) else {
+                    return false;
+                };
+                civ.policy.sortie = enabled;
+                info!("Civ {} set sortie policy: {}", self.player_turn, enabled);
+            }
+            Action::Diplomacy => {
+                // diplomacy: favor and treaties with every rival
+                let summary = diplomacy::summary(self, self.player_turn);
+                self.open_popup("Diplomacy", &summary, vec![]);
+                return true;
+            }
+            Action::Gift { ref target, .. }
+            | Action::Trade(ref target)
+            | Action::Pact(ref target) => {
+                // gift <city> <amount> | trade <city> | pact <city>
+                let target = target.to_lowercase();
+                let Some(idx) = self
+                    .civilizations
+                    .iter()
+                    .position(|c| c.city.name.to_lowercase() == target)
+                else {
+                    let suggestions = self.suggest_targets(&target);
+                    // the same action on each suggested city, applied when chosen
+                    let choices = suggestions
+                        .iter()
+                        .map(|city| {
+                            let target = city.clone();
+                            match action {
+                                Action::Gift { amount, .. } => Action::Gift { target, amount },
+                                Action::Trade(_) => Action::Trade(target),
+                                _ => Action::Pact(target),
                             }
-                            info!("Construction started from popup for civ {}: {}", self.player_turn, name);
-                        }
+                            .to_string()
+                        })
+                        .collect();
+                    self.open_popup(
+                        "Diplomacy",
+                        &unknown_with_suggestions("target", &target, &suggestions),
+                        choices,
+                    );
+                    return true;
+                };
+                let result = if let Action::Gift { amount, .. } = action {
+                    diplomacy::gift(self, self.player_turn, idx, amount).map(|favor| {
+                        format!(
+                            "{amount} resources offered to {} for {favor} favor",
+                            self.city_name(idx)
+                        )
+                    })
+                } else {
+                    let treaty = if matches!(action, Action::Trade(_)) {
+                        Treaty::Trade
+                    } else {
+                        Treaty::DefensePact
+                    };
+                    diplomacy::sign(self, treaty, self.player_turn, idx)
+                        .map(|()| format!("{} proposed to {}", treaty.name(), self.city_name(idx)))
+                };
+                match result {
+                    Ok(report) => {
+                        info!("Civ {}: {}", self.player_turn, report);
+                        self.check_game_over();
                     }
-                    "Hire" => {
-                        if let Some(udef) = self.units.iter().find(|u| u.name == ch) {
-                            let name = udef.name.clone();
-                            if let Err(err) = self.start_recruitment(self.player_turn, &name) {
-                                warn!("start_recruitment failed in popup for civ {}: {}", self.player_turn, err);
-                                self.open_popup("Hire", &format!("{:#}", err), vec![]);
-                                return;
-                            }
-                            info!("Recruitment started from popup for civ {}: {}", self.player_turn, name);
-                        }
+                    Err(e) => {
+                        warn!(
+                            "Diplomatic action failed for civ {}: {}",
+                            self.player_turn, e
+                        );
+                        self.open_popup("Diplomacy", &format!("{e:#}"), vec![]);
+                        return true;
                     }
-                    "Attack" => {
-                        if let Some((idx, _)) = self
+                }
+            }
+            Action::Peace(target) => {
+                // peace [city]: terms offered to a civilization at war with the player
+                let partner = match target.map(|t| t.to_lowercase()) {
+                    None => None,
+                    Some(target) => {
+                        let Some(idx) = self
                             .civilizations
                             .iter()
-                            .enumerate()
-                            .find(|(_, c)| c.city.name == ch)
-                        {
-                            if let Err(e) = self.start_attack(self.player_turn, idx, None) {
-                                warn!("start_attack failed in popup for civ {}: {}", self.player_turn, e);
-                                self.open_popup("Attack", &format!("{:#}", e), vec![]);
-                                return;
-                            }
-                            info!("Attack started from popup for civ {} -> {}", self.player_turn, idx);
-                        }
+                            .position(|c| c.city.name.to_lowercase() == target)
+                        else {
+                            let suggestions = self.suggest_targets(&target);
+                            let message = unknown_with_suggestions("target", &target, &suggestions);
+                            self.open_popup("Peace", &message, suggestions);
+                            return true;
+                        };
+                        Some(idx)
                     }
-                    _ => {}
-                }
+                };
+                let (prompt, choices) = war::peace_popup(self, self.player_turn, partner);
+                self.open_popup("Peace", &prompt, choices);
+                return true;
             }
-        }
-
-        // close popup after handling
-        self.close_popup();
-        // reset action input
-        self.action_input.clear();
-        self.action_editing = false;
-    }
-
-    /// Start a building construction for a civilization.
-    ///
-    /// This method:
-    /// - Validates the building exists and can be built
-    /// - Checks for available building slots
-    /// - Deducts resources
-    /// - Adds the construction to the in-progress queue
-    ///
-    /// # Arguments
+            Action::Scout(target) => {
+                // scout <city>: spy report on a rival city, blurred or caught by its counterintelligence
+                let target = target.to_lowercase();
+                let Some(idx) = self
+                    .civilizations
+                    .iter()
+                    .position(|c| c.city.name.to_lowercase() == target)
+                else {
+                    let suggestions = self.suggest_targets(&target);
+                    self.open_popup(
+                        "Scout",
+                        &unknown_with_suggestions("target", &target, &suggestions),
+                        suggestions,
+                    );
+                    return true;
+                };
+                let message = match espionage::scout(self, self.player_turn, idx) {
+                    Ok(report) => report,
+                    Err(e) => {
+                        warn!("Scouting failed for civ {}: {}", self.player_turn, e);
+                        format!("{e:#}")
+                    }
+                };
+                self.open_popup("Scout", &message, vec![]);
+                return true;
+            }
+            Action::Weather => {
+                // weather: forecast for the next turns, nothing is committed
+                let forecast = weather::forecast(self, self.player_turn);
+                self.open_popup("Weather", &forecast, vec![]);
+                return true;
+            }
+            Action::Income => {
+                let report = income::report(self, self.player_turn);
+                self.open_popup("Income", &report, vec![]);
+                return true;
+            }
+            Action::Simulate { target, amount } => {
+                // simulate attack <target> [amount]: what-if forecast, nothing is committed
+                let target = target.to_lowercase();
+                let Some(idx) = self
+                    .civilizations
+                    .iter()
+                    .position(|c| c.city.name.to_lowercase() == target)
+                else {
+                    let suggestions = self.suggest_targets(&target);
+                    let message = unknown_with_suggestions("target", &target, &suggestions);
+                    self.open_popup("Simulation", &message, suggestions);
+                    return true;
+                };
+                let message = match simulate_attack(self, self.player_turn, idx, amount) {
+                    Ok(f) => format!(
+                        "Sending {} units to {}: arrival in {} turns, {} lost to attrition out of supply\nDefender power at arrival: {} (+{} if it keeps recruiting)\nWin probability: {:.0}%, expected defender losses: {}",
+                        f.sent,
+                        self.city_name(idx),
+                        f.turns,
+                        f.attrition,
+                        f.defender_power,
+                        f.reinforcement,
+                        f.win_probability * 100.0,
+                        f.defender_losses
+                    ),
+                    Err(e) => format!("{e:#}"),
+                };
+                debug!(
+                    "Simulation for player {} against {}: {}",
+                    self.player_turn, idx, message
+                );
+                self.open_popup("Simulation", &message, vec![]);
+                return true;
+            }
+            Action::CancelBuild(building) => {
+                // cancel build [building]: the construction in progress by default
+                match cancel::cancel_construction(self, self.player_turn, building.as_deref()) {
+                    Ok(refund) => info!(
+                        "Civ {} cancelled a construction (+{refund} resources)",
+                        self.player_turn
+                    ),
+                    Err(e) => {
+                        self.open_popup("Cancel", &format!("{e:#}"), vec![]);
+                        return true;
+                    }
+                }
+            }
+            Action::CancelHire => {
+                if let Err(e) = cancel::cancel_recruitment(self, self.player_turn) {
+                    self.open_popup("Cancel", &format!("{e:#}"), vec![]);
+                    return true;
+                }
+            }
+            Action::Recall(target) => {
+                let Some(target) = target else {
+                    let choices = cancel::recall_targets(self, self.player_turn);
+                    let prompt = if choices.is_empty() {
+                        "No army of yours is marching"
+                    } else {
+                        "Choose the city to recall your armies from:"
+                    };
+                    self.open_popup("Recall", prompt, choices);
+                    return true;
+                };
+                if let Err(e) = cancel::recall(self, self.player_turn, &target) {
+                    self.open_popup("Recall", &format!("{e:#}"), vec![]);
+                    return true;
+                }
+            }
+            Action::Research(tech_name) => {
+                // research [tech]
+                let Some(tname) = tech_name.map(|t| t.to_lowercase()) else {
+                    let choices = tech::available(self, self.player_turn)
+                        .iter()
+                        .map(|t| t.name.clone())
+                        .collect();
+                    self.open_popup("Research", "Choose technology to research:", choices);
+                    return true;
+                };
+                let Some(name) = self
+                    .techs
+                    .iter()
+                    .find(|t| t.name.to_lowercase() == tname)
+                    .map(|t| t.name.clone())
+                else {
+                    let suggestions =
+                        did_you_mean(&tname, self.techs.iter().map(|t| t.name.as_str()));
+                    let message = unknown_with_suggestions("technology", &tname, &suggestions);
+                    self.open_popup("Research", &message, suggestions);
+                    return true;
+                };
+                if let Err(err) = tech::start_research(self, self.player_turn, &name) {
+                    warn!(
+                        "Failed to start research for civ {}: {}",
+                        self.player_turn, err
+                    );
+                    self.open_popup("Research", &format!("{err:#}"), vec![]);
+                    return true;
+                }
+                info!("Started research '{}' for civ {}", name, self.player_turn);
+            }
+            Action::Suggest => {
+                // the advisor is an AI of the game, asked by `Game::resolve_suggestion`
+                self.pending_suggestion = true;
+            }
+            Action::Save(name) => {
+                // the save holds the configuration of the game, written by `Game::resolve_save`
+                self.pending_save = Some(name);
+            }
+        }
+
+        // default: clear action
+        self.action_input.clear();
+        self.action_editing = false;
+        false
+    }
+
+    /// Handle popup submission with fuzzy choice matching. Accepts both numeric indices and name prefixes for flexibility.
+    ///
+    /// Interprets the user's selection (by index or name) and executes
+    /// the corresponding action (build, hire, attack, etc.).
+    pub fn submit_popup(&mut self) {
+        let Some(popup) = self.popup.take() else {
+            debug!(
+                "submit_popup called but no popup present (player {})",
+                self.player_turn
+            );
+            return;
+        };
+        self.answer_popup(&popup);
+        // the next waiting popup shows unless answering opened another one
+        if self.popup.is_none() {
+            self.close_popup();
+        }
+    }
+
+    /// Act on the input of `popup`, taken off the screen. Popups opened meanwhile show at once.
+    fn answer_popup(&mut self, popup: &Popup) {
+        debug!(
+            "submit_popup called for '{}' (player {}), input='{}'",
+            popup.title, self.player_turn, popup.input
+        );
+        if let Some(ch) = popup.chosen() {
+            // interpret by popup title
+            match popup.title.as_str() {
+                "Build" => {
+                    if let Some(bdef) = self.building_def(&ch) {
+                        let name = bdef.name.clone();
+                        if let Err(err) = self.start_construction(self.player_turn, &name) {
+                            warn!(
+                                "start_construction failed in popup for civ {}: {}",
+                                self.player_turn, err
+                            );
+                            self.open_popup("Build", &format!("{err:#}"), vec![]);
+                            return;
+                        }
+                        info!(
+                            "Construction started from popup for civ {}: {}",
+                            self.player_turn, name
+                        );
+                    }
+                }
+                // a unit chosen first, then how many of them
+                "Hire" if self.choose_hire(&ch) => return,
+                "Research" => {
+                    if let Err(err) = tech::start_research(self, self.player_turn, &ch) {
+                        warn!(
+                            "start_research failed in popup for civ {}: {}",
+                            self.player_turn, err
+                        );
+                        self.open_popup("Research", &format!("{err:#}"), vec![]);
+                        return;
+                    }
+                    info!(
+                        "Research started from popup for civ {}: {}",
+                        self.player_turn, ch
+                    );
+                }
+                // cities holding several unit types choose how many of each to send
+                "Attack" if self.choose_army(&ch, &[]) => return,
+                "Attack" if self.attack_chosen(&ch) => return,
+                "Demolish" => {
+                    self.confirm_demolition(&ch);
+                    return;
+                }
+                "Confirm Demolish" if self.demolish_confirmed(&ch) => return,
+                // an entry chosen first, then the way it is moved or cancelled
+                "Build queue" if self.choose_queue_entry(&ch) => return,
+                // a city chosen first, then the terms offered to it
+                "Peace" if self.choose_peace(&ch) => return,
+                // a target suggested for a mistyped name, forecast with every unit
+                "Simulation" => {
+                    self.perform(Action::Simulate {
+                        target: ch,
+                        amount: None,
+                    });
+                }
+                // a gift or treaty offered to a city suggested for a mistyped name
+                "Diplomacy" => {
+                    if let Ok(action) = Action::parse(&ch) {
+                        self.submit(action);
+                    }
+                }
+                // a target suggested for a mistyped name
+                "Scout" => {
+                    self.submit(Action::Scout(ch));
+                }
+                "Suggestion" if self.apply_suggestion(&ch) => return,
+                "Army" if self.choose_army_step(&ch) => return,
+                "Recall" => {
+                    if let Err(e) = cancel::recall(self, self.player_turn, &ch) {
+                        self.open_popup("Recall", &format!("{e:#}"), vec![]);
+                        return;
+                    }
+                }
+                "Conquest" => {
+                    conquest::resolve(self, &ch);
+                    self.action_input.clear();
+                    return;
+                }
+                "Surrender" => {
+                    surrender::resolve(self, &ch);
+                    self.action_input.clear();
+                    return;
+                }
+                "Proposal" => diplomacy::resolve(self, &ch),
+                _ => {}
+            }
+        }
+
+        // reset action input
+        self.action_input.clear();
+        self.action_editing = false;
+    }
+
+    /// Attack the city chosen in the attack popup with every unit. Returns true when the attack
+    /// could not start and its error was shown.
+    fn attack_chosen(&mut self, city: &str) -> bool {
+        let Some(idx) = self.civilizations.iter().position(|c| c.city.name == city) else {
+            return false;
+        };
+        if let Err(e) = self.start_attack(self.player_turn, idx, None) {
+            warn!(
+                "start_attack failed in popup for civ {}: {e}",
+                self.player_turn
+            );
+            self.open_popup("Attack", &format!("{e:#}"), vec![]);
+            return true;
+        }
+        info!(
+            "Attack started from popup for civ {} -> {idx}",
+            self.player_turn
+        );
+        false
+    }
+
+    /// Demolish the building confirmed in the popup. Returns true when it could not be
+    /// demolished and the error was shown.
+    fn demolish_confirmed(&mut self, choice: &str) -> bool {
+        let Some(name) = choice.strip_prefix("Demolish ") else {
+            return false;
+        };
+        match self.demolish_building(self.player_turn, name) {
+            Ok(refund) => {
+                info!(
+                    "Civ {} demolished {name} (+{refund} resources)",
+                    self.player_turn
+                );
+                false
+            }
+            Err(e) => {
+                warn!(
+                    "demolish_building failed in popup for civ {}: {e}",
+                    self.player_turn
+                );
+                self.open_popup("Demolish", &format!("{e:#}"), vec![]);
+                true
+            }
+        }
+    }
+
+    /// Handle a choice of the build queue popup: an entry first, then the way it is moved or
+    /// cancelled. Returns true when another popup was opened.
+    fn choose_queue_entry(&mut self, choice: &str) -> bool {
+        let Some((prompt, choices)) = build_queue::choose(self, self.player_turn, choice) else {
+            return false;
+        };
+        self.open_popup("Build queue", &prompt, choices);
+        true
+    }
+
+    /// Handle a choice of the hire popup: a unit first, then how many of them are hired.
+    /// Returns true when another popup was opened.
+    fn choose_hire(&mut self, choice: &str) -> bool {
+        if let Some(udef) = self.unit_def(choice) {
+            let (prompt, choices) =
+                recruitment::amount_popup(self, self.player_turn, &udef.name.clone());
+            self.open_popup("Hire", &prompt, choices);
+            return true;
+        }
+        let Some((unit, amount)) = recruitment::from_choice(choice) else {
+            return false;
+        };
+        if let Err(err) = self.start_batch_recruitment(self.player_turn, &unit, amount) {
+            warn!(
+                "start_recruitment failed in popup for civ {}: {}",
+                self.player_turn, err
+            );
+            self.open_popup("Hire", &format!("{err:#}"), vec![]);
+            return true;
+        }
+        info!(
+            "Recruitment started from popup for civ {}: {} x{}",
+            self.player_turn, unit, amount
+        );
+        false
+    }
+
+    /// Open the army popup of the attack on `target` once the amounts of the first unit types are
+    /// `picked`, when the city holds several types. Returns true when the popup was opened.
+    fn choose_army(&mut self, target: &str, picked: &[(String, u32)]) -> bool {
+        let types = self.civ(self.player_turn).map_or(0, |c| {
+            c.city.units.units.iter().filter(|u| u.nb_units > 0).count()
+        });
+        if types < 2 {
+            return false;
+        }
+        let Some((prompt, choices)) =
+            composition::army_popup(self, self.player_turn, target, picked)
+        else {
+            return false;
+        };
+        self.open_popup("Army", &prompt, choices);
+        true
+    }
+
+    /// Handle a choice of the army popup: the amount of the next unit type, and the attack once
+    /// every type is chosen. Returns true when another popup was opened.
+    fn choose_army_step(&mut self, choice: &str) -> bool {
+        let Some((target, units)) = composition::from_choice(choice) else {
+            return false;
+        };
+        if !units.is_empty() && self.choose_army(&target, &units) {
+            return true;
+        }
+        let Some(idx) = self
+            .civilizations
+            .iter()
+            .position(|c| c.city.name == target)
+        else {
+            return false;
+        };
+        if let Err(e) = self.start_composed_attack(self.player_turn, idx, None, &units) {
+            warn!(
+                "start_attack failed in army popup for civ {}: {}",
+                self.player_turn, e
+            );
+            self.open_popup("Attack", &format!("{e:#}"), vec![]);
+            return true;
+        }
+        info!(
+            "Attack started from popup for civ {} -> {}: {:?}",
+            self.player_turn, idx, units
+        );
+        false
+    }
+
+    /// Handle a choice of the suggestion popup, playing the suggested action when applied.
+    /// Returns true when the action opened another popup.
+    fn apply_suggestion(&mut self, choice: &str) -> bool {
+        let Some(action) = choice
+            .strip_prefix("Apply: ")
+            .and_then(|a| Action::parse(a).ok())
+        else {
+            return false;
+        };
+        info!(
+            "Civ {} applies the suggestion: {}",
+            self.player_turn, action
+        );
+        self.submit(action)
+    }
+
+    /// Handle a choice of the peace popup: a city first, then the terms offered to it. Returns
+    /// true when another popup was opened.
+    fn choose_peace(&mut self, choice: &str) -> bool {
+        if let Some(partner) = self
+            .civilizations
+            .iter()
+            .position(|c| c.city.name == choice)
+        {
+            let (prompt, choices) = war::peace_popup(self, self.player_turn, Some(partner));
+            self.open_popup("Peace", &prompt, choices);
+            return true;
+        }
+        if let Err(e) = war::propose(self, self.player_turn, choice) {
+            warn!("Peace failed for civ {}: {}", self.player_turn, e);
+            self.open_popup("Peace", &format!("{e:#}"), vec![]);
+            return true;
+        }
+        info!("Civ {} offered: {}", self.player_turn, choice);
+        false
+    }
+
+    /// Other civilizations whose name is close to a mistyped attack target.
+    fn suggest_targets(&self, target: &str) -> Vec<String> {
+        did_you_mean(
+            target,
+            self.civilizations
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != self.player_turn)
+                .map(|(_, c)| c.city.name.as_str()),
+        )
+    }
+
+    /// Start a building construction for a civilization.
+    ///
+    /// This method:
+    /// - Validates the building exists and can be built
+    /// - Checks for available building slots
+    /// - Deducts resources
+    /// - Adds the construction to the end of the build queue
+    ///
+    /// # Arguments
     /// * `civ_index` - Index of the civilization building
     /// * `building_name` - Name of the building to construct
     ///
     /// # Returns
     /// Ok(()) on success, or an error describing why construction cannot start
-    pub fn start_construction(
-        &mut self,
+    ///
+    /// # Errors
+    /// Fails when the building is unknown, locked or unaffordable, no slot or queue room is left,
+    /// or the action point or puzzle budget is spent.
+    pub fn start_construction(&mut self, civ_index: usize, building_name: &str) -> Result<()> {
+        debug!("start_construction called: civ={civ_index} building='{building_name}'");
+        if let Some(puzzle) = &self.puzzle {
+            puzzle.check_budget(civ_index, true)?;
+        }
+        let construction = self
+            .check_construction(civ_index, building_name)
+            .inspect_err(|e| warn!("start_construction failed for civ {civ_index}: {e}"))?;
+        let civ = civ_in(&mut self.civilizations, civ_index)?;
+        civ.spend(construction.cost);
+        civ.constructions.push(construction);
+        if let Some(puzzle) = &mut self.puzzle {
+            puzzle.consume(civ_index, true);
+        }
+        spend_action_point(self, civ_index);
+        Ok(())
+    }
+
+    /// Construction `civ_index` would start for `building_name`, checked against every rule of
+    /// `start_construction` but the puzzle budget, without changing anything.
+    ///
+    /// # Errors
+    /// Fails when the building is unknown, locked or unaffordable, no slot or queue room is left,
+    /// or the action points are spent.
+    pub fn check_construction(
+        &self,
         civ_index: usize,
         building_name: &str,
-    ) -> Result<()> {
-        debug!("start_construction called: civ={civ_index} building='{building_name}'");
-        let bdef = if let Some(b) = self.buildings.iter().find(|b| b.name == building_name) { b } else {
-            warn!("start_construction: unknown building '{building_name}' for civ {civ_index}");
+    ) -> Result<Construction> {
+        check_action_point(self, civ_index)?;
+        let Some(bdef) = self.defs.building(&self.buildings, building_name) else {
             return Err(anyhow!("Unknown building: {building_name}"));
         };
-        let civ = &mut self.civilizations[civ_index];
+        tech::check_prerequisites(self, civ_index, &bdef.prerequisites)?;
+        let civ = self.civ(civ_index)?;
+        if !civ.allows_building(&bdef.name) {
+            return Err(anyhow!("{} cannot build {}", civ.city.name, bdef.name));
+        }
         let occupied = civ.city.buildings.elements.len() + civ.constructions.len();
-        // Only one construction at a time
-        if !civ.constructions.is_empty() {
-            warn!("start_construction: another construction already in progress for civ {civ_index}");
-            return Err(anyhow!("Another construction is already in progress"));
+        // queued constructions wait for the ones before them
+        if civ.constructions.len() >= self.build_queue as usize {
+            if self.build_queue <= 1 {
+                return Err(anyhow!("Another construction is already in progress"));
+            }
+            return Err(anyhow!(
+                "The build queue is full ({} constructions)",
+                self.build_queue
+            ));
         }
 
         // check for available slots
         if occupied >= civ.city.nb_slots_buildings as usize {
-            warn!("start_construction: no available building slots for civ {civ_index}");
             return Err(anyhow!("No available building slots"));
         }
 
-        // check resources
-        if civ.resources.ressources < bdef.cost as i32 {
-            warn!("start_construction: not enough resources for civ {} (cost={})", civ_index, bdef.cost);
+        // check resources, the cost may be lowered by the traits of the civilization
+        let cost = civ.modifiers.building_cost(&bdef.name, bdef.cost);
+        if civ.resources.ressources < cost.cast_signed() {
             return Err(anyhow!("Not enough resources for building"));
         }
-        civ.resources.ressources -= bdef.cost as i32;
-        civ.constructions.push(Construction {
+        Ok(Construction {
             id_building: bdef.name.clone(),
             remaining: bdef.build_time,
             total: bdef.build_time,
-        });
-        Ok(())
+            cost,
+        })
     }
 
     /// Start unit recruitment for a civilization.
@@ -619,59 +1986,248 @@ impl GameState {
     ///
     /// # Returns
     /// Ok(()) on success, or an error describing why recruitment cannot start
+    ///
+    /// # Errors
+    /// Fails like `start_batch_recruitment`.
     pub fn start_recruitment(&mut self, civ_index: usize, unit_name: &str) -> Result<()> {
-        debug!("start_recruitment called: civ={civ_index} unit='{unit_name}'");
-        let udef = if let Some(u) = self.units.iter().find(|u| u.name == unit_name) { u } else {
-            warn!("start_recruitment: unknown unit '{unit_name}' for civ {civ_index}");
+        self.start_batch_recruitment(civ_index, unit_name, 1)
+    }
+
+    /// Start recruiting `amount` units at once, as a single recruitment paying the cost of each
+    /// unit. The producing buildings of the city train them side by side, so the time grows with
+    /// the units each of them trains.
+    ///
+    /// # Errors
+    /// Fails when the unit is unknown, locked or unaffordable, no building of the city trains it,
+    /// the city has no room for it, another recruitment is in progress, or the action point or
+    /// puzzle budget is spent.
+    pub fn start_batch_recruitment(
+        &mut self,
+        civ_index: usize,
+        unit_name: &str,
+        amount: u32,
+    ) -> Result<()> {
+        debug!("start_recruitment called: civ={civ_index} unit='{unit_name}' amount={amount}");
+        if let Some(puzzle) = &self.puzzle {
+            puzzle.check_budget(civ_index, false)?;
+        }
+        let recruitment = self
+            .check_recruitment(civ_index, unit_name, amount)
+            .inspect_err(|e| warn!("start_recruitment failed for civ {civ_index}: {e}"))?;
+        let civ = civ_in(&mut self.civilizations, civ_index)?;
+        civ.spend(recruitment.cost);
+        civ.recruitments.push(recruitment);
+        if let Some(puzzle) = &mut self.puzzle {
+            puzzle.consume(civ_index, false);
+        }
+        spend_action_point(self, civ_index);
+        Ok(())
+    }
+
+    /// Recruitment `civ_index` would start for `amount` units of `unit_name`, checked against
+    /// every rule of `start_batch_recruitment` but the puzzle budget, without changing anything.
+    ///
+    /// # Errors
+    /// Fails like `start_batch_recruitment`, the puzzle budget aside.
+    pub fn check_recruitment(
+        &self,
+        civ_index: usize,
+        unit_name: &str,
+        amount: u32,
+    ) -> Result<Recruitment> {
+        if amount == 0 {
+            return Err(anyhow!("Hire at least one unit"));
+        }
+        check_action_point(self, civ_index)?;
+        let Some(udef) = self.defs.unit(&self.units, unit_name) else {
             return Err(anyhow!("Unknown unit: {unit_name}"));
         };
-        let civ = &mut self.civilizations[civ_index];
-        // check for building that can produce this unit (built only)
-        let mut producer: Option<&BuildingDef> = None;
-        for b_inst in &civ.city.buildings.elements {
-            if let Some(bdef) = self.buildings.iter().find(|b| b.name == b_inst.id_building)
-                && format!("{:?}", bdef.production.prod_type).to_lowercase() == "unit"
-                    && let Some(prod_id) = &bdef.production.prod_unit_id
-                        && prod_id == &udef.name {
-                            producer = Some(bdef);
-                            break;
-                        }
+        if let Some(prereqs) = &udef.prerequisites {
+            tech::check_prerequisites(self, civ_index, prereqs)?;
         }
-        // no producer found
-        if producer.is_none() {
-            warn!("start_recruitment: no producer building for unit '{unit_name}' civ {civ_index}");
-            return Err(anyhow!("No building able to produce this unit is present"));
+        let civ = self.civ(civ_index)?;
+        if !civ.allows_unit(&udef.name) {
+            return Err(anyhow!("{} cannot hire {}", civ.city.name, udef.name));
         }
+        // building that can produce this unit (built only), from the producers cache
+        let Some(bdef) = civ
+            .producers
+            .get(&udef.name)
+            .and_then(|&i| self.buildings.get(i))
+        else {
+            return Err(anyhow!("No building able to produce this unit is present"));
+        };
 
         // only one recruitment at a time
         if !civ.recruitments.is_empty() {
-            warn!("start_recruitment: recruitment already in progress for civ {civ_index}");
             return Err(anyhow!("Another recruitment is already in progress"));
         }
 
+        stacks::check_recruitment(self, civ_index, amount)?;
+
         // check for available unit slots
         let occupied_units = civ.city.units.units.len() + civ.recruitments.len();
         if occupied_units >= civ.city.nb_slots_units as usize {
-            warn!("start_recruitment: no available unit slots for civ {civ_index}");
             return Err(anyhow!("No available unit slots"));
         }
 
-        // use producer's production time and cost
-        let bdef = producer.unwrap();
-        let cost = bdef.production.cost as i32;
-        if civ.resources.ressources < cost {
-            warn!("start_recruitment: not enough resources for civ {civ_index} (cost={cost})");
-            return Err(anyhow!("Not enough resources to recruit unit"));
+        // use producer's production time and cost, for each unit of the batch
+        let cost = bdef.production.cost.saturating_mul(amount);
+        if i64::from(civ.resources.ressources) < i64::from(cost) {
+            return Err(anyhow!(if amount == 1 {
+                "Not enough resources to recruit unit".to_string()
+            } else {
+                format!("Not enough resources to recruit {amount} units ({cost} needed)")
+            }));
         }
-        civ.resources.ressources -= cost;
-        civ.recruitments.push(Recruitment {
+        let time = recruitment::batch_time(civ, bdef, amount);
+        Ok(Recruitment {
             id_unit: udef.name.clone(),
-            remaining: bdef.production.time,
-            amount: 1,
-        });
+            remaining: time,
+            total: time,
+            amount,
+            producer: bdef.name.clone(),
+            cost,
+        })
+    }
+
+    /// Give a new name to the city of a civilization, used from then on by every action, the
+    /// map labels, the snapshots and the history. Names are single words, unique regardless of case.
+    ///
+    /// # Errors
+    /// Fails when `city` is unknown or not the city of `civ_index`, or the name is invalid or
+    /// taken.
+    pub fn rename_city(&mut self, civ_index: usize, city: &str, name: &str) -> Result<()> {
+        debug!("rename_city called: civ={civ_index} city='{city}' name='{name}'");
+        let target = self
+            .civilizations
+            .iter()
+            .position(|c| c.city.name.to_lowercase() == city.to_lowercase())
+            .ok_or_else(|| anyhow!("Unknown city: {city}"))?;
+        if target != civ_index {
+            return Err(anyhow!("You can only rename your own city"));
+        }
+        if name.is_empty()
+            || name.chars().count() > MAX_CITY_NAME_LEN
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(anyhow!(
+                "Invalid name '{name}': up to {MAX_CITY_NAME_LEN} letters, digits, '-' or '_'"
+            ));
+        }
+        if self
+            .civilizations
+            .iter()
+            .enumerate()
+            .any(|(i, c)| i != target && c.city.name.to_lowercase() == name.to_lowercase())
+        {
+            return Err(anyhow!("Another city is already named {name}"));
+        }
+        let civ = self.civ_mut(target)?;
+        info!("City '{}' renamed to '{name}'", civ.city.name);
+        civ.city.name = name.to_string();
         Ok(())
     }
 
+    /// Resources refunded to `civ_index` when demolishing a building of the given definition,
+    /// a share of what the civilization paid for it.
+    pub fn demolish_refund(&self, civ_index: usize, building_name: &str) -> u32 {
+        let Ok(civ) = self.civ(civ_index) else {
+            return 0;
+        };
+        self.buildings
+            .iter()
+            .find(|b| b.name == building_name)
+            .map_or(0, |b| {
+                civ.modifiers.building_cost(&b.name, b.cost) * DEMOLISH_REFUND_PERCENT / 100
+            })
+    }
+
+    /// Demolish one finished building of a civilization.
+    ///
+    /// This method:
+    /// - Checks the civilization owns such a building
+    /// - Removes it, freeing its slot
+    /// - Refunds `DEMOLISH_REFUND_PERCENT` of its cost
+    ///
+    /// # Arguments
+    /// * `civ_index` - Index of the civilization demolishing
+    /// * `building_name` - Name of the building to demolish
+    ///
+    /// # Returns
+    /// The refunded resources, or an error describing why the building cannot be demolished
+    ///
+    /// # Errors
+    /// Fails when the civilization owns no such building or the puzzle budget is spent.
+    pub fn demolish_building(&mut self, civ_index: usize, building_name: &str) -> Result<u32> {
+        debug!("demolish_building called: civ={civ_index} building='{building_name}'");
+        if let Some(puzzle) = &self.puzzle {
+            puzzle.check_budget(civ_index, false)?;
+        }
+        let refund = self.demolish_refund(civ_index, building_name);
+        let civ = self.civ_mut(civ_index)?;
+        let Some(pos) = civ
+            .city
+            .buildings
+            .elements
+            .iter()
+            .position(|b| b.id_building == building_name)
+        else {
+            warn!("demolish_building: civ {civ_index} has no '{building_name}'");
+            return Err(anyhow!("No {building_name} to demolish"));
+        };
+        civ.city.buildings.elements.remove(pos);
+        civ.earn(i32::try_from(refund).unwrap_or(i32::MAX));
+        self.refresh_producers(civ_index);
+        if let Some(puzzle) = &mut self.puzzle {
+            puzzle.consume(civ_index, false);
+        }
+        Ok(refund)
+    }
+
+    /// Open a popup about something that happened, kept in the event log of `civs` so that it
+    /// can be read again once another popup replaced it.
+    pub fn announce(&mut self, civs: Vec<usize>, title: &str, message: &str) {
+        let event = GameEvent::Report {
+            civs,
+            topic: title.to_string(),
+            text: message.to_string(),
+        };
+        self.events.publish(self.turn, event);
+        self.open_popup(title, message, vec![]);
+    }
+
+    /// Announce a report to `civ_index` when a human plays it, only log it otherwise.
+    fn tell_player(&mut self, civ_index: usize, title: &str, message: &str) {
+        if self
+            .civ(civ_index)
+            .is_ok_and(|c| matches!(c.city.player_type, PlayerType::PLAYER))
+        {
+            self.announce(vec![civ_index], title, message);
+        } else {
+            let event = GameEvent::Report {
+                civs: vec![civ_index],
+                topic: title.to_string(),
+                text: message.to_string(),
+            };
+            self.events.publish(self.turn, event);
+        }
+    }
+
+    /// Open the confirmation popup before demolishing a building.
+    fn confirm_demolition(&mut self, building_name: &str) {
+        let refund = self.demolish_refund(self.player_turn, building_name);
+        self.open_popup(
+            "Confirm Demolish",
+            &format!(
+                "Demolish {building_name}? Its slot is freed and {refund} resources are refunded."
+            ),
+            vec![format!("Demolish {building_name}"), "Cancel".to_string()],
+        );
+    }
+
     /// Called at the start of each turn for a player.
     ///
     /// This method:
@@ -686,134 +2242,231 @@ impl GameState {
     /// * `player_index` - Index of the player whose turn is starting
     pub fn on_turn_start(&mut self, player_index: usize) {
         info!("on_turn_start: player {} turn={}", player_index, self.turn);
-        let civ = &mut self.civilizations[player_index];
-        // resource from finished buildings
-        for b_inst in &civ.city.buildings.elements {
-            if let Some(bdef) = self.buildings.iter().find(|b| b.name == b_inst.id_building)
-                && format!("{:?}", bdef.production.prod_type).to_lowercase() == "ressource" {
-                    civ.resources.ressources += bdef.production.amount as i32;
-                }
+        self.drop_orphan_travels();
+        if self.civ(player_index).is_err() {
+            warn!("on_turn_start: no civilization {player_index}");
+            return;
+        }
+        // scheduled map and diplomacy changes happen once per round
+        if player_index == 0 {
+            terraform::fire_map_events(self);
+            diplomacy::fire_relations(self);
+            raiders::spawn(self);
+        }
+        refill_action_points(self, player_index);
+        difficulty::update(self);
+        // units waiting outside come in as the city frees up
+        stacks::admit_waiting(self, player_index);
+        // resource from finished buildings, scaled by the tax and economy policies
+        let income = projected_income(self, player_index);
+        self.metrics.gather(player_index, income);
+        if let Ok(civ) = self.civ_mut(player_index) {
+            civ.earn(income);
         }
+        // units and buildings cost their upkeep, cut down when the treasury runs dry
+        if let Some(report) = upkeep::pay(self, player_index) {
+            info!("Upkeep of civ {player_index}: {report}");
+            self.tell_player(player_index, "Upkeep", &report);
+        }
+        // resources beyond the storage of the city are lost
+        storage::spill(self, player_index);
+        surrender::evaluate(self, player_index);
+        capture::revolts(self, player_index);
 
-        // process constructions
+        // process the head of the build queue, faster with low taxes
+        let Ok(civ) = civ_in(&mut self.civilizations, player_index) else {
+            return;
+        };
+        let build_steps = civ.policy.construction_steps();
         let mut finished_builds: Vec<usize> = Vec::new();
-        for (i, cons) in civ.constructions.iter_mut().enumerate() {
-            if cons.remaining > 0 {
-                cons.remaining -= 1;
-            }
+        if let Some(cons) = civ.constructions.first_mut() {
+            cons.remaining = cons.remaining.saturating_sub(build_steps);
             if cons.remaining == 0 {
-                finished_builds.push(i);
+                finished_builds.push(0);
             }
         }
-        // finalize in reverse order to remove by index safely
-        for idx in finished_builds.into_iter().rev() {
+        let mut finished_buildings: Vec<String> = Vec::new();
+        for idx in finished_builds {
             let cons = civ.constructions.remove(idx);
             let id = cons.id_building.clone();
-            civ.city.buildings.elements.push(BuildingInstance { id_building: id.clone(), level: 1 });
+            civ.city.buildings.elements.push(BuildingInstance {
+                id_building: id.clone(),
+                level: 1,
+            });
+            finished_buildings.push(id.clone());
             info!("Construction finished for civ {player_index}: {id}");
+            self.events.publish(
+                self.turn,
+                GameEvent::ConstructionFinished {
+                    civ: player_index,
+                    building: id,
+                },
+            );
         }
 
-        // process recruitments
-        let mut finished_recruits: Vec<usize> = Vec::new();
-        for (i, rec) in civ.recruitments.iter_mut().enumerate() {
-            if rec.remaining > 0 {
-                rec.remaining -= 1;
-            }
-            if rec.remaining == 0 {
-                finished_recruits.push(i);
-            }
+        self.advance_recruitments(player_index);
+        if !finished_buildings.is_empty() {
+            self.refresh_producers(player_index);
         }
-        for idx in finished_recruits.into_iter().rev() {
-            let rec = civ.recruitments.remove(idx);
-            // add unit instance (merge if existing)
-            let id_unit = rec.id_unit.clone();
-            if let Some(ui) = civ
-                .city
-                .units
-                .units
-                .iter_mut()
-                .find(|u| u.id_units == id_unit)
-            {
-                ui.nb_units += rec.amount;
-            } else {
-                civ.city.units.units.push(UnitInstance { id_units: id_unit.clone(), nb_units: rec.amount });
-            }
-            info!("Recruitment finished for civ {}: {} (+{} units)", player_index, id_unit, rec.amount);
+        for building in &finished_buildings {
+            terraform::building_finished(self, player_index, building);
         }
+        tech::advance_research(self, player_index);
 
-        // process travels (attacks in transit)
-        let mut arrived: Vec<usize> = Vec::new();
-        for (i, t) in self.travels.iter_mut().enumerate() {
-            if t.remaining > 0 {
-                t.remaining -= 1;
-            }
-            if t.remaining == 0 {
-                arrived.push(i);
-            }
+        // collect loan installments, defaulting costs morale and units
+        if let Some(report) = loans::collect_installments(self, player_index) {
+            info!("{report}");
+            self.announce(vec![player_index], "Loan", &report);
         }
+
+        // treaties bring trade income and favor from the partners
+        diplomacy::apply_treaties(self, player_index);
+
+        // foreign influence may cost income and make units defect
+        if let Some(report) = influence::apply_pressure(self, player_index) {
+            info!("{report}");
+            self.announce(vec![player_index], "Influence", &report);
+        }
+
+        // armies marching out of supply lose units, only human players are told
+        for (attacker, report) in supply::apply_attrition(self) {
+            self.tell_player(attacker, "Supply", &report);
+        }
+
+        // armies move along their paths, bad weather holds them back for the turn
+        let arrived = armies::advance(self);
         for idx in arrived.into_iter().rev() {
             let t = self.travels.remove(idx);
-            // if either side is already dead, ignore
-            if !self.civilizations[t.attacker].alive || !self.civilizations[t.defender].alive {
-                continue;
-            }
+            armies::arrive(self, &t);
+        }
 
-            let attacker_power = t.amount as i32;
-            let defender_power = self.calculate_city_power(t.defender);
-
-            if attacker_power > defender_power {
-                // attacker wins: defender loses the game
-                self.civilizations[t.defender].alive = false;
-                // remove all defender units
-                self.civilizations[t.defender].city.units.units.clear();
-                // feedback popup
-                self.open_popup(
-                    "Battle",
-                    &format!(
-                        "{} attacked {} ({} vs {}) — defender eliminated",
-                        self.civilizations[t.attacker].city.name,
-                        self.civilizations[t.defender].city.name,
-                        attacker_power,
-                        defender_power
-                    ),
-                    vec![],
-                );
-                info!("Battle resolved: attacker {} defeated defender {}", t.attacker, t.defender);
-            } else {
-                // defender holds: attacker units are lost (they were removed when sent); defender loses some units as casualties
-                let casualties = (attacker_power as u32) / 2;
-                let lost = self.remove_units_from_city(t.defender, casualties);
-                self.open_popup(
-                    "Battle",
-                    &format!(
-                        "{} attacked {} ({} vs {}) — attack failed, defender lost {} units",
-                        self.civilizations[t.attacker].city.name,
-                        self.civilizations[t.defender].city.name,
-                        attacker_power,
-                        defender_power,
-                        lost
-                    ),
-                    vec![],
+        // garrisons with the sortie policy intercept the armies about to reach them
+        sortie::apply_sorties(self);
+
+        // check victory conditions (elimination, puzzle objectives)
+        self.check_game_over();
+        // increment turn counter maybe handled elsewhere; keep turn as-is here
+        fog::update(self);
+        self.record_metrics();
+        self.refresh_descriptions();
+        self.update_tutorial();
+    }
+
+    /// Drop the armies in transit from or to a civilization that does not exist, so the rest of
+    /// the turn can index civilizations from travels safely.
+    fn drop_orphan_travels(&mut self) {
+        let civs = self.civilizations.len();
+        self.travels.retain(|t| {
+            let valid = (t.attacker < civs || t.raiding()) && t.defender < civs;
+            if !valid {
+                warn!(
+                    "Dropping travel from civ {} to civ {}: unknown civilization",
+                    t.attacker, t.defender
                 );
-                info!("Battle resolved: attacker {} failed against {} (defender lost {} units)", t.attacker, t.defender, lost);
+            }
+            valid
+        });
+    }
+
+    /// Civilization at `index`, or an error when there is none.
+    ///
+    /// # Errors
+    /// Fails when `index` is out of range.
+    pub fn civ(&self, index: usize) -> Result<&Civilization> {
+        self.civilizations
+            .get(index)
+            .ok_or_else(|| anyhow!("Invalid civilization index {index}"))
+    }
+
+    /// Mutable civilization at `index`, or an error when there is none.
+    ///
+    /// # Errors
+    /// Fails when `index` is out of range.
+    pub fn civ_mut(&mut self, index: usize) -> Result<&mut Civilization> {
+        civ_in(&mut self.civilizations, index)
+    }
+
+    /// Name of the city of the civilization at `index` for messages, `?` when there is none.
+    pub fn city_name(&self, index: usize) -> &str {
+        self.civ(index).map_or("?", |c| c.city.name.as_str())
+    }
+
+    /// Civilization whose turn it is.
+    ///
+    /// # Errors
+    /// Fails when the turn belongs to no civilization.
+    pub fn current_civ(&self) -> Result<&Civilization> {
+        self.civ(self.player_turn)
+    }
+
+    /// Advance the recruitments of `player_index`, faster with a military focus, and station the
+    /// units of those finished.
+    fn advance_recruitments(&mut self, player_index: usize) {
+        let Ok(civ) = self.civ_mut(player_index) else {
+            return;
+        };
+        let recruit_steps = civ.policy.recruitment_steps();
+        let mut finished_recruits: Vec<usize> = Vec::new();
+        for (i, rec) in civ.recruitments.iter_mut().enumerate() {
+            rec.remaining = rec.remaining.saturating_sub(recruit_steps);
+            if rec.remaining == 0 {
+                finished_recruits.push(i);
             }
         }
+        let finished: Vec<Recruitment> = finished_recruits
+            .into_iter()
+            .rev()
+            .map(|idx| civ.recruitments.remove(idx))
+            .collect();
+        for rec in finished {
+            // add unit instance (merge if existing), overflowing units staying outside
+            let id_unit = rec.id_unit.clone();
+            stacks::station(
+                self,
+                player_index,
+                vec![UnitInstance {
+                    id_units: id_unit.clone(),
+                    nb_units: rec.amount,
+                }],
+            );
+            info!(
+                "Recruitment finished for civ {}: {} (+{} units)",
+                player_index, id_unit, rec.amount
+            );
+            self.events.publish(
+                self.turn,
+                GameEvent::RecruitmentFinished {
+                    civ: player_index,
+                    unit: id_unit,
+                    amount: rec.amount,
+                },
+            );
+        }
+    }
 
-        // check victory: if only one alive remains, end game
-        let alive_count = self.civilizations.iter().filter(|c| c.alive).count();
-        if alive_count <= 1 && !self.game_over {
+    /// Check victory conditions (elimination, puzzle objectives). Deferred while a won battle
+    /// still waits for the attacker decision, as razing or pillaging changes who is alive.
+    pub fn check_game_over(&mut self) {
+        if !self.game_over
+            && self.pending_conquests.is_empty()
+            && let Some(outcome) = victory::evaluate(self)
+        {
+            info!("Game over: {}", outcome.message);
             self.game_over = true;
-            if let Some(winner) = self.civilizations.iter().find(|c| c.alive) {
-                self.open_popup(
-                    "Game Over",
-                    &format!("Winner: {}", winner.city.name),
-                    vec![],
-                );
-            } else {
-                self.open_popup("Game Over", "No winners", vec![]);
-            }
+            self.winner = outcome.winner;
+            self.record_metrics();
+            let message = format!("{}\n\n{}", outcome.message, scoring::summary(self));
+            let event = GameEvent::Report {
+                civs: (0..self.civilizations.len()).collect(),
+                topic: "Game Over".to_string(),
+                text: message.clone(),
+            };
+            self.events.publish(self.turn, event);
+            self.show_popup(Popup {
+                priority: PopupPriority::Critical,
+                ..Popup::new("Game Over", &message, vec![])
+            });
         }
-        // increment turn counter maybe handled elsewhere; keep turn as-is here
     }
 
     /// Remove units with smallest-first priority. Returns actual removed count for battle casualty reporting.
@@ -824,9 +2477,11 @@ impl GameState {
     ///
     /// # Returns
     /// The actual number of units removed
-    fn remove_units_from_city(&mut self, civ_index: usize, mut to_remove: u32) -> u32 {
+    pub(crate) fn remove_units_from_city(&mut self, civ_index: usize, mut to_remove: u32) -> u32 {
         debug!("remove_units_from_city called: civ={civ_index} to_remove={to_remove}");
-        let civ = &mut self.civilizations[civ_index];
+        let Ok(civ) = self.civ_mut(civ_index) else {
+            return 0;
+        };
         let mut removed: u32 = 0;
         let mut i = 0;
         while i < civ.city.units.units.len() && to_remove > 0 {
@@ -857,166 +2512,155 @@ impl GameState {
     ///
     /// # Returns
     /// Ok(()) on success, or an error describing why the attack cannot start
+    ///
+    /// # Errors
+    /// Fails when either civilization is invalid or defeated, the target is the attacker, the game
+    /// is over, the action point or puzzle budget is spent, or no units can be sent.
     pub fn start_attack(
         &mut self,
         attacker_idx: usize,
         defender_idx: usize,
         amount_opt: Option<u32>,
     ) -> Result<()> {
-        if attacker_idx >= self.civilizations.len() || defender_idx >= self.civilizations.len() {
-            return Err(anyhow!("Invalid civilization index"));
-        }
+        self.start_composed_attack(attacker_idx, defender_idx, amount_opt, &[])
+    }
+
+    /// Launch an attack like `start_attack`, sending the amount of each unit type of `units`
+    /// instead of the first `amount_opt` units of the garrison when it is not empty.
+    ///
+    /// # Errors
+    /// Fails like `start_attack`, or when the city holds too few of a unit type.
+    pub fn start_composed_attack(
+        &mut self,
+        attacker_idx: usize,
+        defender_idx: usize,
+        amount_opt: Option<u32>,
+        units: &[(String, u32)],
+    ) -> Result<()> {
+        self.civ(attacker_idx)?;
+        self.civ(defender_idx)?;
         if attacker_idx == defender_idx {
             return Err(anyhow!("Cannot attack yourself"));
         }
         if self.game_over {
             return Err(anyhow!("Game is over"));
         }
+        if let Some(puzzle) = &self.puzzle {
+            puzzle.check_budget(attacker_idx, false)?;
+        }
+        check_action_point(self, attacker_idx)?;
 
-        if !self.civilizations[attacker_idx].alive {
+        if !self.civ(attacker_idx).is_ok_and(|c| c.alive) {
             return Err(anyhow!("Attacker is not alive"));
         }
-        if !self.civilizations[defender_idx].alive {
+        if !self.civ(defender_idx).is_ok_and(|c| c.alive) {
             return Err(anyhow!("Target is already defeated"));
         }
 
         // count available units
-        let total_units: u32 = self.civilizations[attacker_idx]
-            .city
-            .units
-            .units
-            .iter()
-            .map(|u| u.nb_units)
-            .sum();
+        let total_units = composition::total(&self.civ(attacker_idx)?.city.units.units);
         if total_units == 0 {
             return Err(anyhow!("No units available to send"));
         }
 
-        let send_amount = amount_opt.unwrap_or(total_units).min(total_units);
-        if send_amount == 0 {
-            return Err(anyhow!("Invalid amount to send"));
-        }
-
         // remove units from attacker immediately (they are now in transit)
-        let removed = self.remove_units_from_city(attacker_idx, send_amount);
+        let army = if units.is_empty() {
+            let send_amount =
+                stacks::army_size(self, amount_opt.unwrap_or(total_units).min(total_units))?;
+            if send_amount == 0 {
+                return Err(anyhow!("Invalid amount to send"));
+            }
+            composition::levy(self, attacker_idx, send_amount)
+        } else {
+            let units = stacks::cap_units(self, units)?;
+            composition::levy_units(self, attacker_idx, &units)?
+        };
+        let removed = composition::total(&army);
         if removed == 0 {
             return Err(anyhow!("Failed to remove units"));
         }
 
+        // attacking breaks every treaty with the target
+        for treaty in diplomacy::break_relations(self, attacker_idx, defender_idx) {
+            info!(
+                "Civ {attacker_idx} broke its {} with civ {defender_idx}",
+                treaty.name()
+            );
+        }
+
         // compute travel path using weighted shortest path allowing water (but not mountain)
-        let a = &self.civilizations[attacker_idx].city;
-        let b = &self.civilizations[defender_idx].city;
+        let a = &self.civ(attacker_idx)?.city;
+        let b = &self.civ(defender_idx)?.city;
         let src = (a.x.cast_signed(), a.y.cast_signed());
         let dst = (b.x.cast_signed(), b.y.cast_signed());
-        let path_opt = self.bfs_path(src, dst);
-        if path_opt.is_none() {
-            return Err(anyhow!("No path to target (blocked by terrain)"));
-        }
-        let path = path_opt.unwrap();
-        // compute time to traverse the path accounting for water slowdown
-        // default: land tiles move at 3 blocks/turn, water at 1 block/turn
-        let land_speed = 3.0_f64; // blocks per turn on land
-        let water_speed = 1.0_f64; // blocks per turn on water
-        let mut total_time: f64 = 0.0;
-        if path.len() >= 2 {
-            for i in 1..path.len() {
-                let (nx, ny) = path[i];
-                let terrain = &self.map.tiles[ny as usize][nx as usize];
-                let step_time = match terrain {
-                    Terrain::Water => 1.0 / water_speed,
-                    Terrain::Mountain => continue, // should not happen, mountain is impassable
-                    _ => 1.0 / land_speed,
-                };
-                total_time += step_time;
-            }
-        }
-        let mut turns = total_time.ceil() as u32;
-        if turns == 0 { turns = 1; }
-
-        self.travels.push(Travel {
+        let path = self
+            .path(src, dst)
+            .ok_or_else(|| anyhow!("No path to target (blocked by terrain)"))?;
+        let mut travel = Travel {
             attacker: attacker_idx,
             defender: defender_idx,
             amount: removed,
-            remaining: turns,
-            total: turns,
-            path,
-        });
+            composition: army,
+            remaining: 0,
+            total: 0,
+            path: path.tiles,
+            step: 0,
+            progress: 0,
+        };
+        travel.remaining = armies::turns_left(self, &travel);
+        travel.total = travel.remaining;
+        self.travels.push(travel);
+        if let Some(puzzle) = &mut self.puzzle {
+            puzzle.consume(attacker_idx, false);
+        }
+        spend_action_point(self, attacker_idx);
         Ok(())
     }
 
-    /// Find a weighted shortest path from source to destination on the map.
-    ///
-    /// Uses Dijkstra's algorithm to find a path that:
-    /// - Avoids mountains (impassable)
-    /// - Allows water but with higher cost (slower movement)
-    /// - Prefers land tiles
-    ///
-    /// # Arguments
-    /// * `src` - Source coordinates (x, y)
-    /// * `dst` - Destination coordinates (x, y)
-    ///
-    /// # Returns
-    /// Some(path) if a path exists, None otherwise
-    fn bfs_path(&self, src: (i32, i32), dst: (i32, i32)) -> Option<Vec<(i32, i32)>> {
-        let width = self.map.width as i32;
-        let height = self.map.height as i32;
-        let (sx, sy) = src;
-        let (dx, dy) = dst;
-        if sx < 0 || sy < 0 || dx < 0 || dy < 0 { return None; }
-        if sx >= width || sy >= height || dx >= width || dy >= height { return None; }
-
-        // Dijkstra structures (integer scaled costs to avoid f64 ordering issues)
-        const SCALE: i64 = 1000; // 1.0 turn == 1000 units
-        let mut dist: Vec<Vec<i64>> = vec![vec![i64::MAX; width as usize]; height as usize];
-        let mut parent: Vec<Vec<Option<(i32,i32)>>> = vec![vec![None; width as usize]; height as usize];
-        // min-heap of (cost, x, y) using Reverse to get smallest cost
-        let mut heap: BinaryHeap<Reverse<(i64, i32, i32)>> = BinaryHeap::new();
-
-        dist[sy as usize][sx as usize] = 0;
-        heap.push(Reverse((0, sx, sy)));
-
-        let neighbors = [(-1,0),(1,0),(0,-1),(0,1)];
-        while let Some(Reverse((cost, cx, cy))) = heap.pop() {
-            if cost > dist[cy as usize][cx as usize] { continue; }
-            if (cx, cy) == (dx, dy) {
-                // reconstruct path
-                let mut path = Vec::new();
-                let mut cur = Some((cx, cy));
-                while let Some(p) = cur {
-                    path.push(p);
-                    cur = parent[p.1 as usize][p.0 as usize];
-                }
-                path.reverse();
-                return Some(path);
-            }
+    /// Shortest way from `from` to `to` with its travel turns, None when the terrain blocks it.
+    /// Ways are cached until the terrain changes, so every caller can ask again freely.
+    pub fn path(&self, from: (i32, i32), to: (i32, i32)) -> Option<PathInfo> {
+        let revision = self.map.revision;
+        self.path_cache
+            .borrow_mut()
+            .get_or_find(revision, from, to, || {
+                let tiles = paths::find(self, from, to)?;
+                let turns = self.travel_time(&tiles);
+                Some(PathInfo { tiles, turns })
+            })
+    }
 
-            for (ox, oy) in neighbors.iter() {
-                let nx = cx + ox;
-                let ny = cy + oy;
-                if nx < 0 || ny < 0 || nx >= width || ny >= height { continue; }
-                // check terrain of destination tile
-                let terrain = &self.map.tiles[ny as usize][nx as usize];
-                if matches!(terrain, Terrain::Mountain) { continue; }
-                // cost per step scaled as integers: water slower, land faster
-                let step_cost_scaled: i64 = match terrain {
-                    Terrain::Water => SCALE,        // 1.0 turn == SCALE units
-                    _ => (SCALE as f64 / 3.0).round() as i64, // land: ~1/3 turn
-                };
-                let new_cost = cost.saturating_add(step_cost_scaled);
-                if new_cost < dist[ny as usize][nx as usize] {
-                    dist[ny as usize][nx as usize] = new_cost;
-                    parent[ny as usize][nx as usize] = Some((cx, cy));
-                    heap.push(Reverse((new_cost, nx, ny)));
-                }
-            }
-        }
+    /// Surviving rivals an army of `civ_index` can reach, with the way to their city. Turns
+    /// include the travel modifiers of `civ_index`.
+    pub fn reachable_targets(&self, civ_index: usize) -> Vec<(usize, PathInfo)> {
+        let Ok(civ) = self.civ(civ_index) else {
+            return Vec::new();
+        };
+        let home = (civ.city.x.cast_signed(), civ.city.y.cast_signed());
+        self.civilizations
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| *i != civ_index && c.alive)
+            .filter_map(|(i, c)| {
+                let mut path = self.path(home, (c.city.x.cast_signed(), c.city.y.cast_signed()))?;
+                path.turns = civ.modifiers.travel_turns(path.turns);
+                Some((i, path))
+            })
+            .collect()
+    }
 
-        None
+    /// Turns an army takes to walk `path`, found by `paths::find`, before travel modifiers.
+    fn travel_time(&self, path: &[(i32, i32)]) -> u32 {
+        let cost = armies::path_cost(self, path);
+        u32::try_from((cost + paths::TURN - 1) / paths::TURN)
+            .unwrap_or(u32::MAX)
+            .max(1)
     }
 
     /// Move the camera by the specified offset.
     ///
-    /// Only works when camera mode is active.
+    /// Only works when camera mode is active. On a map that wraps around, the camera pans on past
+    /// the east and west edges.
     ///
     /// # Arguments
     /// * `dx` - Horizontal offset
@@ -1028,6 +2672,38 @@ impl GameState {
         }
     }
 
+    /// Center the camera on the city of `civ_index`.
+    pub fn focus_city(&mut self, civ_index: usize) {
+        let Ok((x, y)) = self
+            .civ(civ_index)
+            .map(|c| (c.city.x.cast_signed(), c.city.y.cast_signed()))
+        else {
+            return;
+        };
+        // the next draw clamps the camera to the map, or brings it around a map that wraps
+        let (width, height) = self.map_viewport;
+        self.camera_x = x - i32::try_from(width / 2).unwrap_or(0);
+        self.camera_y = y - i32::try_from(height / 2).unwrap_or(0);
+    }
+
+    /// Index of the winning civilization once the game is over, as decided by the victory engine.
+    pub fn winner(&self) -> Option<usize> {
+        if self.game_over { self.winner } else { None }
+    }
+
+    /// Spectator notes placed during the current turn.
+    pub fn notes_of_turn(&self) -> impl Iterator<Item = &Annotation> {
+        self.annotations.iter().filter(|a| a.turn == self.turn)
+    }
+
+    /// Whether nobody at the keyboard controls a civilization (AI vs AI games).
+    pub fn is_spectating(&self) -> bool {
+        !self
+            .civilizations
+            .iter()
+            .any(|c| matches!(c.city.player_type, PlayerType::PLAYER))
+    }
+
     /// Cycle through zoom levels (1 -> 2 -> 3 -> 1).
     pub fn cycle_zoom(&mut self) {
         self.zoom_level = match self.zoom_level {
@@ -1047,20 +2723,17 @@ impl GameState {
     /// # Returns
     /// Total military power
     pub fn calculate_city_power(&self, civ_index: usize) -> i32 {
-        let civ = &self.civilizations[civ_index];
-        let mut power = 0;
-
-        // Power from units
-        for unit in &civ.city.units.units {
-            let id = &unit.id_units;
-            power += unit.nb_units as i32
-                * self
-                    .units
-                    .iter()
-                    .find(|u| &u.name == id)
-                    .map_or(0, |u| u.attack as i32);
-        }
+        let Ok(civ) = self.civ(civ_index) else {
+            return 0;
+        };
+
+        // Power from units, in u64 since the DSL allows large counts and attacks
+        let power = civ.city.units.units.iter().fold(0u64, |power, unit| {
+            let attack = self.unit_def(&unit.id_units).map_or(0, |u| u.attack);
+            power.saturating_add(u64::from(unit.nb_units) * u64::from(attack))
+        });
 
-        power
+        // Low morale weakens the army
+        i32::try_from(power.saturating_mul(u64::from(civ.morale)) / 100).unwrap_or(i32::MAX)
     }
 }
@@ -0,0 +1,62 @@
+use crate::game::events::GameEvent;
+use crate::game::state::GameState;
+use crate::game::turn_rules::projected_income;
+use crate::game::upkeep;
+use log::info;
+
+/// Resources the city of `civ_index` can store: the `storage` of `[game]` plus that of its
+/// buildings in service. None when the game does not cap resources.
+pub fn capacity(state: &GameState, civ_index: usize) -> Option<i32> {
+    let base = state.storage?;
+    let civ = state.civ(civ_index).ok()?;
+    let capacity = civ
+        .city
+        .buildings
+        .elements
+        .iter()
+        .filter(|b| !civ.disabled.contains(&b.id_building))
+        .filter_map(|b| state.building_def(&b.id_building))
+        .fold(u64::from(base), |capacity, d| {
+            capacity.saturating_add(u64::from(d.storage.unwrap_or(0)))
+        });
+    Some(i32::try_from(capacity).unwrap_or(i32::MAX))
+}
+
+/// Resources `civ_index` will lose at its next turn start if it spends nothing until then: what
+/// its net income brings above its capacity.
+pub fn projected_waste(state: &GameState, civ_index: usize) -> i32 {
+    let (Some(capacity), Ok(civ)) = (capacity(state, civ_index), state.civ(civ_index)) else {
+        return 0;
+    };
+    let next = i64::from(civ.resources.ressources) + i64::from(projected_income(state, civ_index))
+        - i64::from(upkeep::due(state, civ_index));
+    i32::try_from((next - i64::from(capacity)).max(0)).unwrap_or(i32::MAX)
+}
+
+/// Waste the resources of `civ_index` above its capacity, at its turn start once income and
+/// upkeep are settled. Resources won between two turn starts, such as loot or reparations, may
+/// exceed the capacity until then. Returns the resources wasted.
+pub fn spill(state: &mut GameState, civ_index: usize) -> i32 {
+    let Some(capacity) = capacity(state, civ_index) else {
+        return 0;
+    };
+    let Ok(civ) = state.civ_mut(civ_index) else {
+        return 0;
+    };
+    let resources = &mut civ.resources.ressources;
+    let wasted = resources.saturating_sub(capacity).max(0);
+    if wasted == 0 {
+        return 0;
+    }
+    *resources = capacity;
+    info!("Civ {civ_index} wasted {wasted} resources over its storage of {capacity}");
+    state.metrics.waste(civ_index, wasted.cast_unsigned());
+    let text = format!("{wasted} resources wasted, the storage of the city is full ({capacity})");
+    let event = GameEvent::Report {
+        civs: vec![civ_index],
+        topic: "Storage".to_string(),
+        text,
+    };
+    state.events.publish(state.turn, event);
+    wasted
+}
@@ -0,0 +1,63 @@
+use crate::game::armies;
+use crate::game::composition;
+use crate::game::state::{GameState, Travel};
+use log::info;
+
+/// Distance in tiles from its home city within which an army is supplied, unless set in `[game]`
+pub const DEFAULT_SUPPLY_RANGE: u32 = 40;
+/// Share of an army lost on each travel turn out of supply, in percent, unless set in `[game]`
+pub const DEFAULT_ATTRITION: u32 = 5;
+
+/// Whether an army of `travel` standing on `pos` is too far from its home city to be supplied.
+fn out_of_supply(state: &GameState, travel: &Travel, pos: (i32, i32)) -> bool {
+    let Ok(home) = state.civ(travel.attacker).map(|c| &c.city) else {
+        return false;
+    };
+    let dx = state.map.offset_x(i64::from(home.x), i64::from(pos.0));
+    let dx = f64::from(i32::try_from(dx).unwrap_or(i32::MAX));
+    let distance = dx.hypot(f64::from(home.y) - f64::from(pos.1));
+    distance > f64::from(state.supply_range)
+}
+
+/// Units an army of `amount` loses in one travel turn out of supply. Rounded down, so small raids
+/// get through while large armies melt on long marches.
+fn losses(state: &GameState, amount: u32) -> u32 {
+    u32::try_from(u64::from(amount) * u64::from(state.attrition) / 100)
+        .map_or(amount, |lost| lost.min(amount))
+}
+
+/// Units `travel` will lose at the next turn start, when it moves out of supply. Raiders live off
+/// the land and never do.
+pub fn next_losses(state: &GameState, travel: &Travel) -> u32 {
+    let next = armies::next_tile(state, travel);
+    if !travel.raiding() && out_of_supply(state, travel, next) {
+        losses(state, travel.amount)
+    } else {
+        0
+    }
+}
+
+/// Apply attrition to every army about to move out of supply. Armies losing all their units are
+/// dissolved. Returns the attacker and a report for each army that suffered losses.
+pub fn apply_attrition(state: &mut GameState) -> Vec<(usize, String)> {
+    let mut reports = Vec::new();
+    for i in 0..state.travels.len() {
+        let lost = next_losses(state, &state.travels[i]);
+        if lost == 0 {
+            continue;
+        }
+        state.metrics.lose_units(state.travels[i].attacker, lost);
+        let travel = &mut state.travels[i];
+        composition::lose(travel, lost);
+        let (attacker, defender, left) = (travel.attacker, travel.defender, travel.amount);
+        let report = format!(
+            "The army of {} marching on {} lost {lost} units out of supply ({left} left)",
+            state.city_name(attacker),
+            state.city_name(defender)
+        );
+        info!("{report}");
+        reports.push((attacker, report));
+    }
+    state.travels.retain(|t| t.amount > 0);
+    reports
+}
@@ -0,0 +1,216 @@
+use crate::ast::{PlayerType, Surrender as SurrenderTerms};
+use crate::game::events::GameEvent;
+use crate::game::state::{GameState, Popup};
+use crate::game::turn_rules::projected_income;
+use crate::game::{upkeep, war};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Share of the power of the leader under which a civilization is behind, unless set in
+/// `[surrender]`
+pub const DEFAULT_POWER_PERCENT: u32 = 25;
+/// Share of the net income of the leader under which a civilization is behind, unless set in
+/// `[surrender]`
+pub const DEFAULT_INCOME_PERCENT: u32 = 50;
+
+/// Choices offered to the leader, in popup order.
+pub const CHOICES: [&str; 3] = ["Accept surrender", "Accept tribute", "Refuse"];
+
+/// When AI civilizations give up, from the `[surrender]` section, and the offers waiting for an
+/// answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Surrender {
+    /// Turns in a row a civilization stays behind before giving up
+    pub turns: u32,
+    /// Share of the power of the leader under which a civilization is behind, in percent
+    pub power: u32,
+    /// Share of the net income of the leader under which a civilization is behind, in percent
+    pub income: u32,
+    /// Turns in a row each civilization has been behind
+    behind: BTreeMap<usize, u32>,
+    /// Offers waiting for the leader, oldest first
+    pub offers: Vec<Offer>,
+}
+
+impl Surrender {
+    pub fn from_terms(terms: &SurrenderTerms) -> Self {
+        Self {
+            turns: terms.turns.max(1),
+            power: terms.power.unwrap_or(DEFAULT_POWER_PERCENT).min(100),
+            income: terms.income.unwrap_or(DEFAULT_INCOME_PERCENT).min(100),
+            behind: BTreeMap::new(),
+            offers: Vec::new(),
+        }
+    }
+}
+
+/// `from` giving up to `to`, the strongest civilization left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Offer {
+    pub from: usize,
+    pub to: usize,
+}
+
+/// Strongest surviving rival of `civ_index`, the first one on a tie.
+fn leader(state: &GameState, civ_index: usize) -> Option<usize> {
+    (0..state.civilizations.len())
+        .filter(|i| *i != civ_index && state.civ(*i).is_ok_and(|c| c.alive))
+        .rev()
+        .max_by_key(|i| state.calculate_city_power(*i))
+}
+
+/// Share of the power and of the net income of the leader `civ_index` holds, in percent and at
+/// most 100. None without a rival left.
+fn shares(state: &GameState, civ_index: usize) -> Option<(u32, u32)> {
+    let leader = leader(state, civ_index)?;
+    let share = |own: i32, theirs: i32| {
+        if theirs <= 0 {
+            100
+        } else {
+            (i64::from(own.max(0)) * 100 / i64::from(theirs))
+                .clamp(0, 100)
+                .try_into()
+                .unwrap_or(100)
+        }
+    };
+    let income = |i: usize| projected_income(state, i).saturating_sub(upkeep::due(state, i));
+    Some((
+        share(
+            state.calculate_city_power(civ_index),
+            state.calculate_city_power(leader),
+        ),
+        share(income(civ_index), income(leader)),
+    ))
+}
+
+/// How hopeless the position of `civ_index` is against the strongest civilization left, from 0
+/// (level with it or ahead) to 100 (no power and no income left): what it lacks of the power and
+/// of the net income of the leader, on average.
+pub fn desperation(state: &GameState, civ_index: usize) -> u32 {
+    shares(state, civ_index).map_or(0, |(power, income)| 100 - u32::midpoint(power, income))
+}
+
+/// Count the turns the AI `civ_index` spends behind the leader in both power and income, and
+/// offer its surrender to the leader once it has been for `turns` turns in a row. Called at the
+/// turn start of every civilization.
+pub fn evaluate(state: &mut GameState, civ_index: usize) {
+    let Some(terms) = &state.surrender else {
+        return;
+    };
+    let behind = state
+        .civ(civ_index)
+        .is_ok_and(|c| c.alive && matches!(c.city.player_type, PlayerType::AI))
+        && shares(state, civ_index)
+            .is_some_and(|(power, income)| power < terms.power && income < terms.income);
+    let turns = if behind {
+        terms.behind.get(&civ_index).copied().unwrap_or(0) + 1
+    } else {
+        0
+    };
+    let Some(to) = leader(state, civ_index).filter(|_| turns >= terms.turns) else {
+        if let Some(terms) = &mut state.surrender {
+            terms.behind.insert(civ_index, turns);
+        }
+        return;
+    };
+    info!(
+        "Civ {civ_index} gives up after {turns} turns behind, offering its surrender to civ {to}"
+    );
+    let desperation = desperation(state, civ_index);
+    if let Some(terms) = &mut state.surrender {
+        terms.behind.insert(civ_index, 0);
+        if !terms.offers.iter().any(|o| o.from == civ_index) {
+            terms.offers.push(Offer {
+                from: civ_index,
+                to,
+            });
+        }
+    }
+    let text = format!("Desperation {desperation}/100: offers its surrender or a tribute");
+    let event = GameEvent::Report {
+        civs: vec![civ_index, to],
+        topic: "Surrender".to_string(),
+        text,
+    };
+    state.events.publish(state.turn, event);
+}
+
+/// First offer waiting for an answer.
+pub fn pending(state: &GameState) -> Option<Offer> {
+    state
+        .surrender
+        .as_ref()
+        .and_then(|s| s.offers.first().copied())
+}
+
+/// Popup asking the leader whether it takes the surrender or the tribute of `offer`.
+pub fn popup(state: &GameState, offer: Offer) -> Popup {
+    let resources = state
+        .civ(offer.from)
+        .map_or(0, |c| c.resources.ressources.max(0));
+    Popup::new(
+        "Surrender",
+        &format!(
+            "{} cannot keep up with {} and gives up.\nAccept surrender: the city is eliminated and every resource it holds ({}) is yours\nAccept tribute: peace, and {}% of its resources are paid to you\nRefuse: the war goes on",
+            state.city_name(offer.from),
+            state.city_name(offer.to),
+            resources,
+            state.war.reparations
+        ),
+        CHOICES.iter().map(ToString::to_string).collect(),
+    )
+}
+
+/// Apply the answer of the leader to the first offer. Unknown choices refuse it.
+pub fn resolve(state: &mut GameState, choice: &str) {
+    let Some(offer) = pending(state) else {
+        return;
+    };
+    if let Some(terms) = &mut state.surrender {
+        terms.offers.remove(0);
+    }
+    let Offer { from, to } = offer;
+    if !state.civ(from).is_ok_and(|c| c.alive) || !state.civ(to).is_ok_and(|c| c.alive) {
+        return;
+    }
+    let (loser, winner) = (
+        state.city_name(from).to_string(),
+        state.city_name(to).to_string(),
+    );
+    let message = if choice.eq_ignore_ascii_case(CHOICES[0]) {
+        let resources = state.civ(from).map_or(0, |c| c.resources.ressources.max(0));
+        if let Ok(civ) = state.civ_mut(from) {
+            civ.earn(-resources);
+        }
+        if let Ok(civ) = state.civ_mut(to) {
+            civ.earn(resources);
+        }
+        war::end_war(state, from, to);
+        if let Ok(civ) = state.civ_mut(from) {
+            civ.alive = false;
+        }
+        war::eliminate(state, from);
+        format!("{loser} surrendered to {winner}, leaving it {resources} resources")
+    } else if choice.eq_ignore_ascii_case(CHOICES[1]) {
+        war::end_war(state, from, to);
+        state.events.publish(
+            state.turn,
+            GameEvent::Peace {
+                proposer: from,
+                partner: to,
+                reparations: Some((from, to)),
+            },
+        );
+        format!(
+            "{loser} pays a tribute of {}% of its resources to {winner} for peace",
+            state.war.reparations
+        )
+    } else {
+        format!("{winner} refused the surrender of {loser}")
+    };
+    info!("Surrender resolved: {message}");
+    war::settle(state);
+    state.announce(vec![from, to], "Surrender", &message);
+    state.check_game_over();
+}
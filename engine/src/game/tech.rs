@@ -0,0 +1,203 @@
+use crate::ast::{PrereqArray, TechDef};
+use crate::game::events::GameEvent;
+use crate::game::state::GameState;
+use crate::game::turn_rules::{check_action_point, spend_action_point};
+use anyhow::{Result, anyhow};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+/// A technology being researched by a civilization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Research {
+    pub id_tech: String,
+    pub remaining: u32,
+    pub total: u32,
+}
+
+/// Check the `[techs]` section against the loaded buildings and units, then add each tech to the
+/// prerequisites of what it unlocks, so the gating only reads prerequisites.
+///
+/// # Errors
+/// Fails when a tech requires or unlocks something unknown.
+pub fn resolve(state: &mut GameState) -> Result<()> {
+    let known = |state: &GameState, name: &str| state.techs.iter().any(|t| t.name == name);
+    for tech in &state.techs {
+        if let Some(unknown) = tech
+            .requires
+            .iter()
+            .flat_map(|r| &r.values)
+            .find(|r| !known(state, r))
+        {
+            return Err(anyhow!(
+                "tech {} requires unknown tech {unknown}",
+                tech.name
+            ));
+        }
+    }
+    let referenced = state
+        .buildings
+        .iter()
+        .map(|b| &b.prerequisites)
+        .chain(state.units.iter().filter_map(|u| u.prerequisites.as_ref()))
+        .flat_map(|p| &p.prereqs);
+    for prereq in referenced {
+        if let Some(tech) = prereq.id_tech.as_ref().filter(|t| !known(state, t)) {
+            return Err(anyhow!("prerequisite on unknown tech {tech}"));
+        }
+        if let Some(building) = prereq
+            .id_building
+            .as_ref()
+            .filter(|b| state.building_def(b).is_none())
+        {
+            return Err(anyhow!("prerequisite on unknown building {building}"));
+        }
+    }
+
+    let unlocks: Vec<(String, String)> = state
+        .techs
+        .iter()
+        .flat_map(|t| {
+            t.unlocks
+                .iter()
+                .flat_map(|u| &u.values)
+                .map(|u| (t.name.clone(), u.clone()))
+        })
+        .collect();
+    for (tech, unlocked) in unlocks {
+        let prereq = crate::ast::Prereq {
+            id_building: None,
+            id_tech: Some(tech.clone()),
+        };
+        if let Some(building) = state.buildings.iter_mut().find(|b| b.name == unlocked) {
+            building.prerequisites.prereqs.push(prereq);
+        } else if let Some(unit) = state.units.iter_mut().find(|u| u.name == unlocked) {
+            unit.prerequisites
+                .get_or_insert_with(|| PrereqArray {
+                    prereqs: Vec::new(),
+                })
+                .prereqs
+                .push(prereq);
+        } else {
+            return Err(anyhow!(
+                "tech {tech} unlocks unknown building or unit {unlocked}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that `civ_index` has researched the techs and finished the buildings of `prereqs`.
+///
+/// # Errors
+/// Fails on the first missing tech or building.
+pub fn check_prerequisites(
+    state: &GameState,
+    civ_index: usize,
+    prereqs: &PrereqArray,
+) -> Result<()> {
+    let civ = state.civ(civ_index)?;
+    for prereq in &prereqs.prereqs {
+        if let Some(tech) = prereq
+            .id_tech
+            .as_ref()
+            .filter(|t| !civ.researched.contains(*t))
+        {
+            return Err(anyhow!("Requires the {tech} technology"));
+        }
+        if let Some(building) = prereq.id_building.as_ref().filter(|b| {
+            !civ.city
+                .buildings
+                .elements
+                .iter()
+                .any(|e| e.id_building == **b)
+        }) {
+            return Err(anyhow!("Requires a finished {building}"));
+        }
+    }
+    Ok(())
+}
+
+/// Techs `civ_index` has not researched yet and whose required techs it has, none while another
+/// research is in progress.
+pub fn available(state: &GameState, civ_index: usize) -> Vec<&TechDef> {
+    let Some(civ) = state.civ(civ_index).ok().filter(|c| c.research.is_none()) else {
+        return Vec::new();
+    };
+    state
+        .techs
+        .iter()
+        .filter(|t| !civ.researched.contains(&t.name))
+        .filter(|t| {
+            t.requires
+                .iter()
+                .flat_map(|r| &r.values)
+                .all(|r| civ.researched.contains(r))
+        })
+        .collect()
+}
+
+/// Start researching `tech_name`, paying its cost. One research at a time.
+///
+/// # Errors
+/// Fails when the tech is unknown or already researched, a research is in progress, a required tech
+/// is missing, or the civilization cannot pay or has no action point left.
+pub fn start_research(state: &mut GameState, civ_index: usize, tech_name: &str) -> Result<()> {
+    check_action_point(state, civ_index)?;
+    let tech = state
+        .tech_def(tech_name)
+        .ok_or_else(|| anyhow!("Unknown technology: {tech_name}"))?
+        .clone();
+    let civ = state.civ(civ_index)?;
+    if civ.researched.contains(&tech.name) {
+        return Err(anyhow!("{} is already researched", tech.name));
+    }
+    if civ.research.is_some() {
+        return Err(anyhow!("Another research is already in progress"));
+    }
+    if let Some(missing) = tech
+        .requires
+        .iter()
+        .flat_map(|r| &r.values)
+        .find(|r| !civ.researched.contains(*r))
+    {
+        return Err(anyhow!("Requires the {missing} technology"));
+    }
+    if civ.resources.ressources < tech.cost.cast_signed() {
+        return Err(anyhow!("Not enough resources to research {}", tech.name));
+    }
+    let civ = state.civ_mut(civ_index)?;
+    civ.spend(tech.cost);
+    civ.research = Some(Research {
+        id_tech: tech.name,
+        remaining: tech.research_time,
+        total: tech.research_time,
+    });
+    spend_action_point(state, civ_index);
+    Ok(())
+}
+
+/// Move the research of `civ_index` one turn forward, adding the tech to its researched set
+/// when it is done.
+pub fn advance_research(state: &mut GameState, civ_index: usize) {
+    let Ok(civ) = state.civ_mut(civ_index) else {
+        return;
+    };
+    let Some(research) = &mut civ.research else {
+        return;
+    };
+    research.remaining = research.remaining.saturating_sub(1);
+    if research.remaining > 0 {
+        return;
+    }
+    let tech = research.id_tech.clone();
+    civ.research = None;
+    civ.researched.insert(tech.clone());
+    info!("Research finished for civ {civ_index}: {tech}");
+    state.events.publish(
+        state.turn,
+        GameEvent::ResearchFinished {
+            civ: civ_index,
+            tech,
+        },
+    );
+}
@@ -0,0 +1,206 @@
+use crate::ast::{Terraform, TerrainType};
+use crate::game::armies;
+use crate::game::events::GameEvent;
+use crate::game::map::Terrain;
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// A tile whose terrain changed during the game, kept so that restoring a save changes it again
+/// on the map regenerated from the seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerrainChange {
+    pub x: i32,
+    pub y: i32,
+    pub terrain: Terrain,
+}
+
+fn terrain_of(terrain: &TerrainType) -> Terrain {
+    match terrain {
+        TerrainType::WATER => Terrain::Water,
+        TerrainType::PLAINS => Terrain::Plains,
+        TerrainType::DESERT => Terrain::Desert,
+        TerrainType::MOUNTAIN => Terrain::Mountain,
+        TerrainType::FOREST => Terrain::Forest,
+        TerrainType::SNOW => Terrain::Snow,
+        TerrainType::SWAMP => Terrain::Swamp,
+    }
+}
+
+/// Name of a terrain in the event log.
+pub fn terrain_name(terrain: Terrain) -> &'static str {
+    match terrain {
+        Terrain::Water => "eau",
+        Terrain::Plains => "plaine",
+        Terrain::Desert => "désert",
+        Terrain::Mountain => "montagne",
+        Terrain::River => "rivière",
+        Terrain::Forest => "forêt",
+        Terrain::Snow => "neige",
+        Terrain::Swamp => "marais",
+    }
+}
+
+/// Change the terrain of a tile, recording the change. Returns false when it already had it.
+pub fn set_terrain(state: &mut GameState, change: TerrainChange) -> bool {
+    let (Ok(x), Ok(y)) = (usize::try_from(change.x), usize::try_from(change.y)) else {
+        return false;
+    };
+    if !state.map.set(x, y, change.terrain) {
+        return false;
+    }
+    state.terrain_changes.push(change);
+    true
+}
+
+/// Change the tiles within `radius` of `center` to the terrain of `terraform`, then redraw the
+/// map and reroute the armies whose way goes through them. City tiles keep their terrain so
+/// that every city stays reachable. Returns the number of tiles changed.
+fn apply(state: &mut GameState, center: (i32, i32), terraform: &Terraform) -> usize {
+    let terrain = terrain_of(&terraform.terrain);
+    let radius = terraform.radius.unwrap_or(0).cast_signed();
+    let cities: BTreeSet<(i32, i32)> = state
+        .civilizations
+        .iter()
+        .map(|c| (c.city.x.cast_signed(), c.city.y.cast_signed()))
+        .collect();
+    let mut changed = BTreeSet::new();
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let (x, y) = (center.0 + dx, center.1 + dy);
+            if dx * dx + dy * dy > radius * radius || cities.contains(&(x, y)) {
+                continue;
+            }
+            if set_terrain(state, TerrainChange { x, y, terrain }) {
+                changed.insert((x, y));
+            }
+        }
+    }
+    if !changed.is_empty() {
+        state.map_buffer_cache = None;
+        reroute_travels(state, &changed);
+    }
+    changed.len()
+}
+
+/// Reject map events centered outside the map.
+///
+/// # Errors
+/// Fails on the first event outside the map.
+pub fn check(state: &GameState) -> Result<()> {
+    if let Some(event) = state
+        .map_events
+        .iter()
+        .find(|e| e.x as usize >= state.map.width || e.y as usize >= state.map.height)
+    {
+        return Err(anyhow!(
+            "map event {} is outside the {}x{} map",
+            event.name,
+            state.map.width,
+            state.map.height
+        ));
+    }
+    Ok(())
+}
+
+/// Fire the map events scheduled for the current turn. Called once per round, at the start of
+/// the turn of the first civilization.
+pub fn fire_map_events(state: &mut GameState) {
+    let due: Vec<_> = state
+        .map_events
+        .iter()
+        .filter(|e| e.turn.cast_signed() == state.turn)
+        .cloned()
+        .collect();
+    for event in due {
+        let center = (event.x.cast_signed(), event.y.cast_signed());
+        let tiles = apply(state, center, &event.terraform);
+        info!("Map event '{}' changed {tiles} tiles", event.name);
+        state.events.publish(
+            state.turn,
+            GameEvent::Terraformed {
+                source: event.name,
+                terrain: terrain_of(&event.terraform.terrain),
+                tiles,
+            },
+        );
+    }
+}
+
+/// Terraform around the city of `civ_index` when `building` is a wonder changing the map.
+pub fn building_finished(state: &mut GameState, civ_index: usize, building: &str) {
+    let Some(terraform) = state
+        .building_def(building)
+        .and_then(|b| b.terraform.clone())
+    else {
+        return;
+    };
+    let Ok(city) = state.civ(civ_index).map(|c| &c.city) else {
+        return;
+    };
+    let center = (city.x.cast_signed(), city.y.cast_signed());
+    let tiles = apply(state, center, &terraform);
+    info!("Building '{building}' of civ {civ_index} changed {tiles} tiles");
+    state.events.publish(
+        state.turn,
+        GameEvent::Terraformed {
+            source: building.to_string(),
+            terrain: terrain_of(&terraform.terrain),
+            tiles,
+        },
+    );
+}
+
+/// Find a new way for the armies whose path ahead goes through a changed tile, from the tile
+/// they reached. Armies left without any way to their target disband.
+fn reroute_travels(state: &mut GameState, changed: &BTreeSet<(i32, i32)>) {
+    let mut i = 0;
+    while i < state.travels.len() {
+        let travel = &state.travels[i];
+        let here = travel.step;
+        if !travel.path[here..]
+            .iter()
+            .any(|tile| changed.contains(tile))
+        {
+            i += 1;
+            continue;
+        }
+        let Ok(target) = state
+            .civ(travel.defender)
+            .map(|c| (c.city.x.cast_signed(), c.city.y.cast_signed()))
+        else {
+            i += 1;
+            continue;
+        };
+        if let Some(route) = state.path(travel.path[here], target) {
+            let travel = &mut state.travels[i];
+            travel.path.truncate(here);
+            travel.path.extend(route.tiles);
+            travel.progress = 0;
+            let turns = armies::turns_left(state, &state.travels[i]);
+            let travel = &mut state.travels[i];
+            travel.total = travel.total - travel.remaining.min(travel.total) + turns;
+            travel.remaining = turns;
+            i += 1;
+        } else {
+            let travel = state.travels.remove(i);
+            if !travel.raiding() {
+                state.metrics.lose_units(travel.attacker, travel.amount);
+            }
+            info!(
+                "Army of civ {} toward civ {} cut off by the terrain, {} units lost",
+                travel.attacker, travel.defender, travel.amount
+            );
+            state.events.publish(
+                state.turn,
+                GameEvent::CutOff {
+                    attacker: travel.attacker,
+                    defender: travel.defender,
+                    amount: travel.amount,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::ast::PlayerType;
+use crate::game::utils::hash_tmb;
+use crate::game::{Ai, Game};
+use anyhow::Result;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Turns after which a tournament game the victory conditions never end counts as a draw
+pub const MAX_GAME_TURNS: u32 = 1000;
+
+/// Result of one game of a tournament.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameOutcome {
+    pub seed: String,
+    /// Turns played before the game ended
+    pub turns: i32,
+    /// Winning civilization, None for a draw
+    pub winner: Option<usize>,
+    /// Resources spent by each civilization
+    pub spent: Vec<u32>,
+}
+
+/// Aggregated results of a civilization over the tournament.
+#[derive(Debug, Clone, Serialize)]
+pub struct CivStats {
+    pub name: String,
+    pub wins: u32,
+    pub win_rate: f64,
+    pub average_spent: f64,
+}
+
+/// Aggregated results of a tournament, printed by `--simulate`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TournamentStats {
+    pub games: u32,
+    pub draws: u32,
+    pub average_turns: f64,
+    pub civs: Vec<CivStats>,
+    pub outcomes: Vec<GameOutcome>,
+}
+
+/// Seed of game `index` of a tournament on a map seeded with `base`.
+pub fn game_seed(base: &str, index: u32) -> String {
+    format!("{base}-{index}")
+}
+
+/// Games played so far by a tournament, reported after each game.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    pub done: u32,
+    pub total: u32,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    pub fn games_per_sec(&self) -> f64 {
+        f64::from(self.done) / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Time left at the current pace.
+    pub fn eta(&self) -> Duration {
+        let rate = self.games_per_sec();
+        if rate <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(f64::from(self.total - self.done) / rate)
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} games, {:.2} games/s, ETA {}s",
+            self.done,
+            self.total,
+            self.games_per_sec(),
+            self.eta().as_secs()
+        )
+    }
+}
+
+/// Play one AI-vs-AI game of `config` (the default game when None) on the map of `seed`. Every
+/// civilization is played by the AI returned by `ai` for its index and the `ai_seed` of its city,
+/// or a seed derived from the game seed, so the same seed replays the same game with
+/// deterministic AIs. The game owns all of its state, so games may run on several threads.
+///
+/// # Errors
+/// Fails when the configuration does not load.
+pub fn play_game(
+    config: Option<&str>,
+    seed: &str,
+    ai: &impl Fn(usize, u64) -> Box<dyn Ai>,
+) -> Result<GameOutcome> {
+    let mut game = match config {
+        Some(config) => Game::from_string(config)?,
+        None => Game::new(),
+    };
+    let state = &mut game.state;
+    state.map = state.map.reseeded(seed.to_string());
+    for civ in &mut state.civilizations {
+        civ.city.player_type = PlayerType::AI;
+    }
+    let start = state.turn;
+    game.set_ai_seed(u64::from(hash_tmb(seed)));
+    for civ in 0..game.state.civilizations.len() {
+        let ai_seed = game.ai_seed(civ).unwrap_or_default();
+        game.register_ai(civ, ai(civ, ai_seed));
+    }
+    game.play_to_end(MAX_GAME_TURNS);
+    let state = &game.state;
+    Ok(GameOutcome {
+        seed: seed.to_string(),
+        turns: state.turn - start,
+        winner: state.winner.filter(|_| state.game_over),
+        spent: state
+            .civilizations
+            .iter()
+            .map(|c| c.resources.spent)
+            .collect(),
+    })
+}
+
+/// Play `games` games on `threads` threads, each on its own seed derived from the map seed of
+/// `template`, and aggregate their results in the order of the seeds. `progress` is told after
+/// each finished game.
+///
+/// # Errors
+/// Fails when one of the games fails.
+pub fn run(
+    template: &Game,
+    games: u32,
+    threads: usize,
+    ai: impl Fn(usize, u64) -> Box<dyn Ai> + Sync,
+    progress: impl Fn(Progress) + Sync,
+) -> Result<TournamentStats> {
+    let base = template.state.map.seed.clone();
+    let config = template.config_source.as_deref();
+    let next = AtomicU32::new(0);
+    let results = Mutex::new(Vec::new());
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, games.max(1) as usize) {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= games {
+                        break;
+                    }
+                    let seed = game_seed(&base, i);
+                    log::info!("Tournament game {}/{games} on seed {seed}", i + 1);
+                    let outcome = play_game(config, &seed, &ai);
+                    let mut results = results.lock().unwrap_or_else(PoisonError::into_inner);
+                    results.push((i, outcome));
+                    let done = u32::try_from(results.len()).unwrap_or(games);
+                    progress(Progress {
+                        done,
+                        total: games,
+                        elapsed: start.elapsed(),
+                    });
+                }
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap_or_else(PoisonError::into_inner);
+    results.sort_by_key(|(i, _)| *i);
+    let outcomes = results
+        .into_iter()
+        .map(|(_, o)| o)
+        .collect::<Result<Vec<_>>>()?;
+
+    let played = f64::from(games.max(1));
+    let count = |matches: &dyn Fn(&GameOutcome) -> bool| -> u32 {
+        outcomes.iter().filter(|o| matches(o)).fold(0, |n, _| n + 1)
+    };
+    let civs = template
+        .state
+        .civilizations
+        .iter()
+        .enumerate()
+        .map(|(i, civ)| {
+            let wins = count(&|o| o.winner == Some(i));
+            let spent: f64 = outcomes
+                .iter()
+                .filter_map(|o| o.spent.get(i))
+                .map(|s| f64::from(*s))
+                .sum();
+            CivStats {
+                name: civ.city.name.clone(),
+                wins,
+                win_rate: f64::from(wins) / played,
+                average_spent: spent / played,
+            }
+        })
+        .collect();
+    Ok(TournamentStats {
+        games,
+        draws: count(&|o| o.winner.is_none()),
+        average_turns: f64::from(outcomes.iter().map(|o| o.turns).sum::<i32>()) / played,
+        civs,
+        outcomes,
+    })
+}
+
+impl TournamentStats {
+    /// One row per civilization, with the tournament-wide figures repeated on each row.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "civ,wins,win_rate,average_spent,games,draws,average_turns\n".to_string();
+        for civ in &self.civs {
+            let _ = writeln!(
+                csv,
+                "{},{},{:.3},{:.1},{},{},{:.1}",
+                csv_field(&civ.name),
+                civ.wins,
+                civ.win_rate,
+                civ.average_spent,
+                self.games,
+                self.draws,
+                self.average_turns
+            );
+        }
+        csv
+    }
+}
+
+/// `field` quoted as RFC 4180 asks when it holds a comma, a quote or a line break, its quotes
+/// doubled.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\r', '\n']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
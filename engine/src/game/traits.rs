@@ -0,0 +1,148 @@
+use crate::ast::CivTrait;
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// Passive modifiers of a civilization, resolved from its entry of the `[traits]` section.
+/// The turn logic never reads the traits directly, only these modifiers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Modifiers {
+    /// Name of the trait shown to the players
+    pub label: Option<String>,
+    /// Percent taken off the cost of the discounted buildings
+    pub building_discount: u32,
+    /// Buildings the discount applies to, every building when empty
+    pub discounted_buildings: Vec<String>,
+    /// Percent taken off the travel time of armies, always at least one turn
+    pub travel_speed: u32,
+    /// Percent added to the income of the city
+    pub income_bonus: u32,
+}
+
+impl Modifiers {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Cost of `building` for the civilization, at least 1 when it was not free.
+    pub fn building_cost(&self, building: &str, cost: u32) -> u32 {
+        let discounted = self.discounted_buildings.is_empty()
+            || self
+                .discounted_buildings
+                .iter()
+                .any(|b| b.eq_ignore_ascii_case(building));
+        if !discounted || self.building_discount == 0 {
+            return cost;
+        }
+        (cost - cost * self.building_discount / 100).max(cost.min(1))
+    }
+
+    /// Travel time of an army that would take `turns` turns without the trait.
+    pub fn travel_turns(&self, turns: u32) -> u32 {
+        (turns - turns * self.travel_speed / 100).max(1)
+    }
+
+    pub fn income(&self, income: i64) -> i64 {
+        if income <= 0 {
+            return income;
+        }
+        income.saturating_add(income.saturating_mul(i64::from(self.income_bonus)) / 100)
+    }
+
+    /// Lines describing the strengths of the civilization, for the info panel.
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(label) = &self.label {
+            lines.push(label.clone());
+        }
+        if self.building_discount > 0 {
+            if self.discounted_buildings.is_empty() {
+                lines.push(format!("Bâtiments -{}%", self.building_discount));
+            } else {
+                lines.push(format!(
+                    "{} -{}%",
+                    self.discounted_buildings.join(", "),
+                    self.building_discount
+                ));
+            }
+        }
+        if self.travel_speed > 0 {
+            lines.push(format!("Voyages -{}%", self.travel_speed));
+        }
+        if self.income_bonus > 0 {
+            lines.push(format!("Revenu +{}%", self.income_bonus));
+        }
+        lines
+    }
+}
+
+/// Check the `[traits]` entries against the loaded cities, buildings and units, then give each
+/// civilization its modifiers and its extra starting units.
+///
+/// # Errors
+/// Fails when an entry names an unknown city, building or unit.
+pub fn resolve(state: &mut GameState, traits: Vec<CivTrait>) -> Result<()> {
+    for entry in traits {
+        let civ_index = state
+            .civilizations
+            .iter()
+            .position(|c| c.city.name.eq_ignore_ascii_case(&entry.city))
+            .ok_or_else(|| anyhow!("traits of unknown city {}", entry.city))?;
+        for (name, value) in [
+            ("building_discount", entry.building_discount),
+            ("travel_speed", entry.travel_speed),
+        ] {
+            if value.is_some_and(|v| v > 100) {
+                return Err(anyhow!(
+                    "{name} of the traits of {} must be between 0 and 100",
+                    entry.city
+                ));
+            }
+        }
+        let discounted_buildings = entry
+            .discounted_buildings
+            .map_or_else(Vec::new, |a| a.values);
+        if let Some(unknown) = discounted_buildings
+            .iter()
+            .find(|b| state.building_def(b).is_none())
+        {
+            return Err(anyhow!(
+                "traits of {}: unknown building {unknown}",
+                entry.city
+            ));
+        }
+        let extra_units = entry.extra_units.map_or_else(Vec::new, |a| a.units);
+        if let Some(unknown) = extra_units
+            .iter()
+            .find(|u| !state.units.iter().any(|d| d.name == u.id_units))
+        {
+            return Err(anyhow!(
+                "traits of {}: unknown unit {}",
+                entry.city,
+                unknown.id_units
+            ));
+        }
+
+        let civ = state.civ_mut(civ_index)?;
+        civ.modifiers = Modifiers {
+            label: entry.label,
+            building_discount: entry.building_discount.unwrap_or(0),
+            discounted_buildings,
+            travel_speed: entry.travel_speed.unwrap_or(0),
+            income_bonus: entry.income_bonus.unwrap_or(0),
+        };
+        for extra in extra_units {
+            match civ
+                .city
+                .units
+                .units
+                .iter_mut()
+                .find(|u| u.id_units == extra.id_units)
+            {
+                Some(units) => units.nb_units = units.nb_units.saturating_add(extra.nb_units),
+                None => civ.city.units.units.push(extra),
+            }
+        }
+    }
+    Ok(())
+}
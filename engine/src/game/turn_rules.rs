@@ -0,0 +1,142 @@
+use crate::ast::{BlockingCondition, PlayerType, ProductionType};
+use crate::game::capture;
+use crate::game::limits;
+use crate::game::map::RIVER_PRODUCTION_PERCENT;
+use crate::game::state::GameState;
+use crate::game::weather;
+use anyhow::{Result, anyhow};
+
+/// Resources a civilization will earn at its next turn start from its finished buildings in
+/// service, raised next to a river, changed by the terrain of its city and the season there,
+/// plus what its captured cities produce, raised by its traits and scaled by the dynamic
+/// difficulty adjustment of AIs.
+pub fn projected_income(state: &GameState, civ_index: usize) -> i32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    // in i64, saturated to i32, since the DSL allows many buildings producing large amounts
+    let base = civ
+        .city
+        .buildings
+        .elements
+        .iter()
+        .filter(|b| !civ.disabled.contains(&b.id_building))
+        .filter_map(|b| state.building_def(&b.id_building))
+        .filter(|d| matches!(d.production.prod_type, ProductionType::RESSOURCE))
+        .fold(0i64, |base, d| {
+            base.saturating_add(i64::from(d.production.amount))
+        });
+    let percent = |base: i64, percent: i32| base.saturating_mul(i64::from(percent)) / 100;
+    let base = if state.map.near_river(civ.city.x, civ.city.y) {
+        percent(base, 100 + RIVER_PRODUCTION_PERCENT)
+    } else {
+        base
+    };
+    let terrain = state
+        .map
+        .tiles
+        .get(civ.city.y as usize)
+        .and_then(|row| row.get(civ.city.x as usize));
+    let base = terrain.map_or(base, |t| percent(base, t.production_percent()));
+    let base = match (weather::season(state, state.turn), terrain) {
+        (Some(season), Some(terrain)) => percent(base, season.production_percent(*terrain)),
+        _ => base,
+    };
+    let base = base.saturating_add(i64::from(capture::tribute(state, civ_index)));
+    let income = civ.modifiers.income(civ.policy.income(base));
+    let income = match &state.difficulty {
+        Some(difficulty) => percent(income, 100 + difficulty.percent(civ_index)),
+        None => income,
+    };
+    limits::saturate(income)
+}
+
+/// Whether any building (or unit when `units`) could be started right now, through the checks
+/// of `start_construction` and `start_recruitment`.
+fn can_start_any(state: &GameState, civ_index: usize, units: bool) -> bool {
+    if units {
+        state
+            .units
+            .iter()
+            .any(|u| state.check_recruitment(civ_index, &u.name, 1).is_ok())
+    } else {
+        state
+            .buildings
+            .iter()
+            .any(|b| state.check_construction(civ_index, &b.name).is_ok())
+    }
+}
+
+/// Explanations of every scenario rule currently preventing `civ_index` from ending its turn.
+/// Only human players are blocked: rules are meant for tutorials, and an AI stuck on them
+/// would never give the turn back.
+pub fn end_turn_blockers(state: &GameState, civ_index: usize) -> Vec<String> {
+    let Ok(civ) = state.civ(civ_index) else {
+        return Vec::new();
+    };
+    if !matches!(civ.city.player_type, PlayerType::PLAYER) || !civ.alive {
+        return Vec::new();
+    }
+    state
+        .end_turn_rules
+        .iter()
+        .filter_map(|rule| match rule {
+            BlockingCondition::NO_INCOME if projected_income(state, civ_index) <= 0 => {
+                Some("Your city has no income: build a resource building first.".to_string())
+            }
+            BlockingCondition::IDLE_CONSTRUCTION
+                if civ.constructions.is_empty() && can_start_any(state, civ_index, false) =>
+            {
+                Some(
+                    "A construction can be started: use 'build' before ending the turn."
+                        .to_string(),
+                )
+            }
+            BlockingCondition::IDLE_RECRUITMENT
+                if civ.recruitments.is_empty() && can_start_any(state, civ_index, true) =>
+            {
+                Some("A unit can be recruited: use 'hire' before ending the turn.".to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Give `civ_index` its action points for the turn, in the paced mode of `action_points`.
+pub fn refill_action_points(state: &mut GameState, civ_index: usize) {
+    let Some(points) = state.action_points else {
+        return;
+    };
+    if let Some(civ) = state.civilizations.get_mut(civ_index) {
+        civ.action_points = points;
+    }
+}
+
+/// Check that `civ_index` has an action point left for a build, hire or attack.
+///
+/// # Errors
+/// Fails when the civilization has no action point left.
+pub fn check_action_point(state: &GameState, civ_index: usize) -> Result<()> {
+    let Some(points) = state.action_points else {
+        return Ok(());
+    };
+    if state
+        .civilizations
+        .get(civ_index)
+        .is_some_and(|c| c.action_points == 0)
+    {
+        return Err(anyhow!(
+            "No action point left this turn ({points} per turn)"
+        ));
+    }
+    Ok(())
+}
+
+/// Spend an action point of `civ_index` after a successful build, hire or attack.
+pub fn spend_action_point(state: &mut GameState, civ_index: usize) {
+    if state.action_points.is_some()
+        && let Ok(civ) = state.civ_mut(civ_index)
+    {
+        civ.action_points = civ.action_points.saturating_sub(1);
+    }
+}
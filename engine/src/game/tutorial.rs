@@ -0,0 +1,115 @@
+use crate::ast::{PlayerType, TutorialGate, TutorialStep};
+use crate::game::state::GameState;
+use serde::{Deserialize, Serialize};
+
+/// Progress through the `[tutorial]` steps of a scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TutorialState {
+    pub steps: Vec<TutorialStep>,
+    /// Index of the current step (`steps.len()` once finished)
+    pub current: usize,
+    /// Turn and player when the current step started, for `END_TURN` gates
+    started_at: (i32, usize),
+}
+
+impl TutorialState {
+    pub fn new(steps: Vec<TutorialStep>, state: &GameState) -> Self {
+        Self {
+            steps,
+            current: 0,
+            started_at: (state.turn, state.player_turn),
+        }
+    }
+
+    pub fn current_step(&self) -> Option<&TutorialStep> {
+        self.steps.get(self.current)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+
+    /// Reason why a human player may not submit `action` at this step, if any.
+    pub fn rejection(&self, state: &GameState, action: &str) -> Option<String> {
+        let step = self.current_step()?;
+        let expect = step.expect.as_ref()?;
+        if !state
+            .current_civ()
+            .is_ok_and(|c| matches!(c.city.player_type, PlayerType::PLAYER))
+        {
+            return None;
+        }
+        if action.starts_with(&expect.to_lowercase()) {
+            None
+        } else {
+            Some(format!("{}\nExpected action: {expect}", step.text))
+        }
+    }
+
+    /// Whether an action written as `syntax`, whose `<...>` and `[...]` arguments are left to the
+    /// player, could be accepted at this step.
+    pub fn accepts_syntax(&self, state: &GameState, syntax: &str) -> bool {
+        let literal = syntax
+            .split(['<', '['])
+            .next()
+            .unwrap_or_default()
+            .trim_end()
+            .to_lowercase();
+        if self.rejection(state, &literal).is_none() {
+            return true;
+        }
+        let expect = self.current_step().and_then(|s| s.expect.as_ref());
+        literal.len() < syntax.len()
+            && expect.is_some_and(|e| e.to_lowercase().starts_with(&literal))
+    }
+
+    /// Whether the gate of the current step is satisfied by the human player's city.
+    fn gate_met(&self, state: &GameState, step: &TutorialStep) -> bool {
+        let Some((player, civ)) = state
+            .civilizations
+            .iter()
+            .enumerate()
+            .find(|(_, c)| matches!(c.city.player_type, PlayerType::PLAYER))
+        else {
+            return true;
+        };
+        let target_matches = |name: &str| step.target.as_ref().is_none_or(|t| t == name);
+        match step.gate {
+            TutorialGate::BUILDING => {
+                civ.city
+                    .buildings
+                    .elements
+                    .iter()
+                    .any(|b| target_matches(&b.id_building))
+                    || civ
+                        .constructions
+                        .iter()
+                        .any(|c| target_matches(&c.id_building))
+            }
+            TutorialGate::UNIT => {
+                civ.city
+                    .units
+                    .units
+                    .iter()
+                    .any(|u| target_matches(&u.id_units))
+                    || civ.recruitments.iter().any(|r| target_matches(&r.id_unit))
+            }
+            TutorialGate::ATTACK => state.travels.iter().any(|t| t.attacker == player),
+            TutorialGate::END_TURN => (state.turn, state.player_turn) != self.started_at,
+        }
+    }
+
+    /// Move past every step whose gate is met. Returns true when the last step was just completed.
+    pub fn advance(&mut self, state: &GameState) -> bool {
+        let was_finished = self.is_finished();
+        while let Some(step) = self.current_step() {
+            if !self.gate_met(state, step) {
+                break;
+            }
+            log::info!("Tutorial step {} completed", self.current + 1);
+            self.current += 1;
+            self.started_at = (state.turn, state.player_turn);
+        }
+        !was_finished && self.is_finished()
+    }
+}
@@ -1,17 +1,35 @@
+use super::action_menu::{self, ActionMenu};
+use super::advisor;
+use super::campaign::Campaign;
+use super::difficulty;
+use super::diplomacy;
+use super::fog;
+use super::game_code::GameCode;
+use super::influence;
+use super::loans;
+use super::metrics;
+use super::raiders;
+use super::stacks;
 use super::state::GameState;
-use crate::game::map::draw_map;
-use crate::game::utils::hsv_to_rgb;
+use super::storage;
+use super::turn_rules;
+use super::upkeep;
+use super::weather;
+use super::{AI_PLAYBACK_DELAYS_MS, UiConfig};
+use crate::game::map_view::draw_map;
+use crate::game::utils::{hsv_to_rgb, readable_color, str_to_color, text_color_on};
 use crossterm::execute;
 use crossterm::terminal::{LeaveAlternateScreen, disable_raw_mode};
 use ratatui::{
     prelude::*,
     style::Style,
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph, Sparkline, Wrap},
 };
+use std::fmt::Write;
+use std::time::Duration;
 
-pub struct UiConfig {
-    pub color: Color,
-}
+/// How long the banner announcing a new player stays on screen
+const TURN_BANNER_DURATION: Duration = Duration::from_millis(1500);
 
 pub fn draw_ui(frame: &mut Frame, state: &mut GameState, ui_config: &UiConfig) {
     let size = frame.area();
@@ -21,23 +39,184 @@ pub fn draw_ui(frame: &mut Frame, state: &mut GameState, ui_config: &UiConfig) {
             Constraint::Length(3),
             Constraint::Min(0),
             Constraint::Length(3),
+            Constraint::Length(1),
         ])
         .split(size);
 
     draw_status_bar(frame, chunks[0], state, ui_config);
     draw_main_area(frame, chunks[1], state, ui_config);
     draw_action(frame, chunks[2], state, ui_config);
+    draw_hint(frame, chunks[3], state);
+    draw_turn_banner(frame, state, ui_config);
+    if let Some(menu) = &state.action_menu {
+        draw_action_menu(frame, state, menu, ui_config);
+    }
+    if state.game_over && state.show_end_screen && state.popup.is_none() {
+        draw_end_screen(frame, state, ui_config);
+    }
 }
 
-fn draw_status_bar(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiConfig) {
-    let status = Block::default()
+/// Statistics of the finished game over the whole screen: totals of each civilization, then
+/// their power and resources gathered and spent turn by turn.
+fn draw_end_screen(frame: &mut Frame, state: &GameState, ui_config: &UiConfig) {
+    let area = frame.area();
+    frame.render_widget(Clear, area);
+    let block = Block::default()
         .title(format!(
-            "Civilization {} AC (Turn {}) (Press Ctrl+Q to quit)",
-            state.turn * 10,
+            " Fin de partie - {} tours (t: carte, Ctrl+Q: quitter) ",
             state.turn
         ))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(ui_config.color));
+        .border_style(Style::default().fg(ui_config.accent()));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let civs = state.civilizations.len();
+    let outcome = match state.winner() {
+        Some(i) => format!("Vainqueur : {}", state.city_name(i)),
+        None => "Aucun vainqueur".to_string(),
+    };
+    let mut lines = vec![
+        Line::styled(outcome, Style::default().add_modifier(Modifier::BOLD)),
+        Line::raw(""),
+        Line::raw(format!(
+            "{:<16} {:>10} {:>10} {:>10} {:>8} {:>9} {:>7}",
+            "Cité", "Récoltées", "Dépensées", "Bâtiments", "Pertes", "Batailles", "Score"
+        )),
+    ];
+    for (i, civ) in state.civilizations.iter().enumerate() {
+        let s = metrics::summary(state, i);
+        lines.push(Line::styled(
+            format!(
+                "{:<16} {:>10} {:>10} {:>10} {:>8} {:>9} {:>7}",
+                civ.city.name, s.gathered, s.spent, s.buildings, s.units_lost, s.battles, s.score
+            ),
+            Style::default().fg(str_to_color(&civ.city.color)),
+        ));
+    }
+    let table_height = u16::try_from(lines.len() + 1).unwrap_or(u16::MAX);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(table_height), Constraint::Min(0)])
+        .split(inner);
+    frame.render_widget(Paragraph::new(lines), parts[0]);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); civs * 3])
+        .split(parts[1]);
+    for (i, civ) in state.civilizations.iter().enumerate() {
+        let series = [
+            ("puissance", state.metrics.power_series(i)),
+            ("récoltées", state.metrics.gathered_series(i)),
+            ("dépensées", state.metrics.spent_series(i)),
+        ];
+        for (j, (label, data)) in series.into_iter().enumerate() {
+            let Some(row) = rows.get(i * 3 + j) else {
+                return;
+            };
+            draw_series(
+                frame,
+                *row,
+                &format!("{} {label}", civ.city.name),
+                &data,
+                str_to_color(&civ.city.color),
+            );
+        }
+    }
+}
+
+/// One labelled sparkline: the label with the last value, then the most recent values that fit.
+fn draw_series(frame: &mut Frame, area: Rect, label: &str, data: &[u64], color: Color) {
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(28), Constraint::Min(0)])
+        .split(area);
+    let last = data.last().copied().unwrap_or(0);
+    frame.render_widget(Paragraph::new(format!("{label}: {last}")), cols[0]);
+    // Keep the most recent turns when the history is wider than the panel
+    let skip = data.len().saturating_sub(usize::from(cols[1].width));
+    frame.render_widget(
+        Sparkline::default()
+            .data(&data[skip..])
+            .style(Style::default().fg(color)),
+        cols[1],
+    );
+}
+
+fn draw_status_bar(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiConfig) {
+    let mut title = Vec::new();
+    // Name of the player whose turn it is, on the color of its city
+    if let Ok(civ) = state.current_civ() {
+        let color = readable_color(str_to_color(&civ.city.color));
+        let style = if ui_config.preferences.high_contrast {
+            Style::default().fg(Color::Black).bg(Color::White)
+        } else {
+            Style::default().fg(text_color_on(color)).bg(color)
+        };
+        title.push(Span::styled(
+            format!(" {} ", civ.city.name),
+            style.add_modifier(Modifier::BOLD),
+        ));
+    }
+    title.push(Span::raw(format!(
+        "Civilization {} AC (Turn {}) (Press Ctrl+Q to quit) ",
+        state.turn * 10,
+        state.turn
+    )));
+    // Season of the turn and the turns it still lasts
+    if let (Some(season), Some(left)) = (
+        weather::season(state, state.turn),
+        weather::season_turns_left(state),
+    ) {
+        title.push(Span::raw(format!(
+            " {} ({left} tour(s)) ",
+            weather::season_name(season)
+        )));
+    }
+    // Action points left, in the paced mode
+    if let (Some(points), Ok(civ)) = (state.action_points, state.current_civ()) {
+        title.push(Span::raw(format!(" PA {}/{points} ", civ.action_points)));
+    }
+    // Resources about to be lost over the storage of the city
+    let waste = storage::projected_waste(state, state.player_turn);
+    if waste > 0 {
+        title.push(Span::styled(
+            format!(" Stockage plein: {waste} perdues "),
+            Style::default().fg(Color::White).bg(Color::Red),
+        ));
+    }
+    // Badge of the events the current player has not seen in the event log yet
+    let unread = state.events.unread(state.player_turn);
+    if unread.total() > 0 {
+        title.push(Span::styled(
+            format!(
+                " {} nouveaux: {} combats, {} terminés (e: journal) ",
+                unread.total(),
+                unread.battles,
+                unread.completions
+            ),
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ));
+    }
+    // Chord being typed, with the keys completing it
+    if let Some(pending) = &ui_config.pending_keys {
+        title.push(Span::styled(
+            format!(" {pending} "),
+            Style::default().fg(Color::Black).bg(Color::Cyan),
+        ));
+    }
+    // Keys of the last AI turn that could not be replayed
+    if let Some(notice) = &ui_config.held_keys_notice {
+        title.push(Span::styled(
+            format!(" {notice} "),
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ));
+    }
+    let status = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(ui_config.accent()));
     frame.render_widget(status, area);
 }
 
@@ -47,129 +226,547 @@ fn draw_main_area(frame: &mut Frame, area: Rect, state: &mut GameState, ui_confi
         .constraints([Constraint::Percentage(80), Constraint::Percentage(20)])
         .split(area);
 
-    draw_map(frame, areas[0], state, ui_config);
-    draw_info_panel(frame, areas[1], state, ui_config);
+    // Optional panels stacked under the map
+    let mut constraints = vec![Constraint::Min(0)];
+    if state.text_mode {
+        constraints.push(Constraint::Percentage(35));
+    }
+    if state.show_graphs {
+        constraints.push(Constraint::Percentage(35));
+    }
+    if state.show_events {
+        constraints.push(Constraint::Percentage(30));
+    }
+    let show_difficulty = state.show_difficulty && state.difficulty.is_some();
+    if show_difficulty {
+        constraints.push(Constraint::Percentage(25));
+    }
+    let map_areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(areas[0]);
+    draw_map(frame, map_areas[0], state, ui_config);
+    let mut next = 1;
+    if state.text_mode {
+        draw_description_panel(frame, map_areas[next], state, ui_config);
+        next += 1;
+    }
+    if state.show_graphs {
+        draw_graphs_panel(frame, map_areas[next], state, ui_config);
+        next += 1;
+    }
+    if state.show_events {
+        draw_events_panel(frame, map_areas[next], state, ui_config);
+        next += 1;
+    }
+    if show_difficulty {
+        draw_difficulty_panel(frame, map_areas[next], state, ui_config);
+    }
+    // Changes of the last live reload stay above the info panel until dismissed
+    let side = if state.config_changes.is_empty() {
+        areas[1]
+    } else {
+        let side = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(40), Constraint::Min(0)])
+            .split(areas[1]);
+        draw_config_changes_panel(frame, side[0], state, ui_config);
+        side[1]
+    };
+    // Possible actions of the current player, recomputed whenever the state changes
+    let side = if state.show_actions {
+        state.refresh_valid_actions();
+        let side = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(45), Constraint::Min(0)])
+            .split(side);
+        draw_actions_panel(frame, side[0], state, ui_config);
+        side[1]
+    } else {
+        side
+    };
+    // Tutorial instructions stay pinned above the info panel
+    if let Some(tutorial) = &state.tutorial
+        && let Some(step) = tutorial.current_step()
+    {
+        let side = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(8), Constraint::Min(0)])
+            .split(side);
+        let mut text = step.text.clone();
+        if let Some(expect) = &step.expect {
+            text.push_str("\n\n> ");
+            text.push_str(expect);
+        }
+        let panel = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .title(format!(
+                    "Tutoriel ({}/{})",
+                    tutorial.current + 1,
+                    tutorial.steps.len()
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(ui_config.accent())),
+        );
+        frame.render_widget(panel, side[0]);
+        draw_info_panel(frame, side[1], state, ui_config);
+    } else {
+        draw_info_panel(frame, side, state, ui_config);
+    }
+}
+
+/// Definitions changed by the last live reload, so DSL authors see the effect of their edits.
+fn draw_config_changes_panel(
+    frame: &mut Frame,
+    area: Rect,
+    state: &GameState,
+    ui_config: &UiConfig,
+) {
+    let panel = Paragraph::new(state.config_changes.join("\n"))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title("Configuration rechargée (c: fermer)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(ui_config.accent())),
+        );
+    frame.render_widget(panel, area);
+}
+
+/// Power and resource history of each civilization as sparklines, one row each.
+fn draw_graphs_panel(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiConfig) {
+    let block = Block::default()
+        .title(format!(
+            "Graphiques - puissance et ressources ({} tours, g: masquer)",
+            state.metrics.history.len()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(ui_config.accent()));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(2); state.civilizations.len() * 2])
+        .split(inner);
+    for (i, civ) in state.civilizations.iter().enumerate() {
+        let color = str_to_color(&civ.city.color);
+        let series = [
+            ("puissance", state.metrics.power_series(i)),
+            ("ressources", state.metrics.resource_series(i)),
+        ];
+        for (j, (label, data)) in series.into_iter().enumerate() {
+            let Some(row) = rows.get(i * 2 + j) else {
+                return;
+            };
+            draw_series(
+                frame,
+                *row,
+                &format!("{} {label}", civ.city.name),
+                &data,
+                color,
+            );
+        }
+    }
+}
+
+/// Events of the current player, most recent first. Showing them marks them as read.
+fn draw_events_panel(frame: &mut Frame, area: Rect, state: &mut GameState, ui_config: &UiConfig) {
+    state.events.mark_read(state.player_turn);
+    let lines: Vec<Line> = state
+        .events
+        .events_of(state.player_turn)
+        .rev()
+        .map(|(turn, event)| Line::from(format!("Tour {turn}: {}", event.describe(state))))
+        .collect();
+    let panel = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((state.events_scroll, 0))
+        .block(
+            Block::default()
+                .title("Journal des événements (↑/↓: défiler, e: masquer)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(ui_config.accent())),
+        );
+    frame.render_widget(panel, area);
+}
+
+/// Actions the current player can play right now, with their syntax and cost, so nothing
+/// typed from this list gets rejected.
+fn draw_actions_panel(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiConfig) {
+    let lines: Vec<Line> = state
+        .valid_actions
+        .iter()
+        .map(|a| {
+            if a.cost.is_empty() {
+                Line::from(a.syntax.clone())
+            } else {
+                Line::from(format!("{} ({})", a.syntax, a.cost))
+            }
+        })
+        .collect();
+    let panel = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title("Actions possibles (l: masquer)")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(ui_config.accent())),
+    );
+    frame.render_widget(panel, area);
+}
+
+/// Production adjustments of the AIs under dynamic difficulty, so the player knows how much
+/// each rival is helped or held back.
+fn draw_difficulty_panel(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiConfig) {
+    let panel = Paragraph::new(difficulty::describe(state).join("\n"))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title("Difficulté dynamique (i: masquer)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(ui_config.accent())),
+        );
+    frame.render_widget(panel, area);
+}
+
+/// Textual description of the situation for players who cannot rely on the map.
+fn draw_description_panel(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiConfig) {
+    let panel = Paragraph::new(state.descriptions.join("\n"))
+        .wrap(Wrap { trim: false })
+        .scroll((state.description_scroll, 0))
+        .block(
+            Block::default()
+                .title("Description (t: masquer, ↑/↓: défiler)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(ui_config.accent())),
+        );
+    frame.render_widget(panel, area);
 }
 
+#[allow(clippy::too_many_lines)]
 fn draw_info_panel(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiConfig) {
+    let Ok(civ) = state.current_civ() else {
+        return;
+    };
+    // When spectating AI vs AI, reserve the bottom of the panel for the AI decision trace
+    let constraints = if state.is_spectating() {
+        vec![
+            Constraint::Percentage(30),
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+        ]
+    } else {
+        vec![Constraint::Percentage(40), Constraint::Percentage(60)]
+    };
     let areas = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .constraints(constraints)
         .split(area);
 
+    if state.is_spectating() {
+        draw_decision_panel(frame, areas[2], state, ui_config);
+    }
+
     // Game Info
-    let info_text = format!(
-        "{}\n\nJoueurs: \n{}\n\nTour actuel: {}",
-        format!("Seed: {}", state.map.seed),
+    let mut info_text = format!(
+        "Seed: {}\nCode: {}\n\nJoueurs: \n{}\n\nTour actuel: {}",
+        state.map.seed,
+        GameCode::of(state).encode(),
         // List players
         state
             .civilizations
             .iter()
-            .map(|c| format!("- {} ({:?})", c.city.name, c.city.player_type))
+            .map(|c| {
+                let mut line = format!("- {} ({:?})", c.city.name, c.city.player_type);
+                if let Some(persona) = &c.city.persona {
+                    let _ = write!(line, " — {persona}");
+                }
+                if let Some(mood) = &c.mood {
+                    let _ = write!(line, " « {mood} »");
+                }
+                let strengths = c.modifiers.describe();
+                if !strengths.is_empty() {
+                    let _ = write!(line, " {}", strengths.join(", "));
+                }
+                line
+            })
             .collect::<Vec<_>>()
             .join("\n"),
-        state.civilizations[state.player_turn].city.name
+        civ.city.name
     );
 
+    if let Some(puzzle) = &state.puzzle {
+        info_text.push_str("\n\nPuzzle: ");
+        info_text.push_str(&puzzle.summary(state));
+    }
+
+    // Spectator notes of the turn, with their marker
+    for (i, note) in state.notes_of_turn().enumerate() {
+        if i == 0 {
+            info_text.push_str("\n\nNotes:");
+        }
+        let _ = match note.marker {
+            Some((x, y)) => write!(info_text, "\n- [{x},{y}] {}", note.text),
+            None => write!(info_text, "\n- {}", note.text),
+        };
+    }
+
     let info = Paragraph::new(info_text).block(
         Block::default()
             .title("Info")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(ui_config.color)),
+            .border_style(Style::default().fg(ui_config.accent())),
     );
     frame.render_widget(info, areas[0]);
 
     // Player info
-    // Build a string describing current constructions (or "Aucun")
-    let constructions_text = if state.civilizations[state.player_turn]
-        .constructions
-        .is_empty()
-    {
-        "Aucun".to_string()
-    } else {
-        state.civilizations[state.player_turn]
-            .constructions
-            .iter()
-            .map(|construction| {
-                let building_name = state
-                    .buildings
-                    .iter()
-                    .find(|u| u.name == construction.id_building)
-                    .map(|u| u.name.clone())
-                    .unwrap_or(construction.id_building.clone());
-                format!(
-                    "- {} ({} tours restants)",
-                    building_name, construction.remaining
-                )
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
-    };
-
-    let recruitement_text = if state.civilizations[state.player_turn]
-        .recruitments
-        .is_empty()
-    {
-        "Aucun".to_string()
-    } else {
-        state.civilizations[state.player_turn]
-            .recruitments
+    // One progress gauge per construction, recruitment and army on the move (or "Aucun")
+    // queued constructions wait for the ones before them
+    let mut queued = 0;
+    let constructions_text = progress_list(civ.constructions.iter().enumerate().map(|(i, c)| {
+        queued += c.remaining;
+        let eta = civ.policy.construction_eta(queued);
+        let label = if i == 0 {
+            format!("1. {}", c.id_building)
+        } else {
+            format!("{}. {} (en attente)", i + 1, c.id_building)
+        };
+        progress_line(&label, c.total.saturating_sub(c.remaining), c.total, eta)
+    }));
+    let recruitement_text = progress_list(civ.recruitments.iter().map(|r| {
+        let label = format!("{} x{} ({})", r.id_unit, r.amount, r.producer);
+        let eta = civ.policy.recruitment_eta(r.remaining);
+        progress_line(&label, r.total.saturating_sub(r.remaining), r.total, eta)
+    }));
+    let name = |i: usize| state.civ(i).map_or("?", |c| c.city.name.as_str());
+    let travels_text = progress_list(
+        state
+            .travels
             .iter()
-            .map(|recruitement| {
-                let building_name = state
-                    .units
-                    .iter()
-                    .find(|u| u.name == recruitement.id_unit)
-                    .map(|u| u.name.clone())
-                    .unwrap_or(recruitement.id_unit.clone());
-                format!(
-                    "- {} ({} tours restants)",
-                    building_name, recruitement.remaining
+            .filter(|t| t.attacker == state.player_turn || t.defender == state.player_turn)
+            .map(|t| {
+                let label = if t.returning() {
+                    format!("{} unités de retour", t.amount)
+                } else if t.attacker == state.player_turn {
+                    format!("{} unités vers {}", t.amount, name(t.defender))
+                } else {
+                    format!(
+                        "{} unités ennemies de {}",
+                        t.amount,
+                        raiders::army_owner(state, t.attacker)
+                    )
+                };
+                progress_line(
+                    &label,
+                    t.total.saturating_sub(t.remaining),
+                    t.total,
+                    Some(t.remaining),
                 )
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
-    };
+            }),
+    );
 
-    let player_text = format!(
-        "Ressources: {}\nForce Millitaire: {}\nBatiments: {}\nUnités: {}\n\nActions disponibles:\n{}\n\nBatiment en construction: \n{}\n\nUnités en recrutement: \n{}",
-        state.civilizations[state.player_turn].resources.ressources,
+    let influence = influence::pressure(state, state.player_turn);
+    let mut player_text = format!(
+        "Ressources: {}\nRevenu net: {:+}/tour (entretien: {})\nDette: {}\nMoral: {}%\nPolitique: impôts {}%, militaire {}%, sortie {}\nInfluence: {} (étrangère: {})\nFaveur minimale: {}\nForce Millitaire: {}\nBatiments: {}\nUnités: {}\n\nActions disponibles:\n{}\n\nFile de construction ({}/{}): \n{}\n\nUnités en recrutement: \n{}\n\nArmées en marche: \n{}",
+        civ.resources.ressources,
+        turn_rules::projected_income(state, state.player_turn)
+            - upkeep::due(state, state.player_turn),
+        upkeep::due(state, state.player_turn),
+        loans::debt(civ),
+        civ.morale,
+        civ.policy.tax,
+        civ.policy.military,
+        if civ.policy.sortie { "oui" } else { "non" },
+        influence.0,
+        influence.1,
+        diplomacy::favor_label(
+            diplomacy::lowest_favor(state, state.player_turn).unwrap_or(0),
+            state.favor_threshold
+        ),
         state.calculate_city_power(state.player_turn),
-        state.civilizations[state.player_turn]
-            .city
-            .buildings
-            .elements
-            .len()
-            .to_string()
+        civ.city.buildings.elements.len().to_string()
             + "/"
-            + &state.civilizations[state.player_turn]
-                .city
-                .nb_slots_buildings
-                .to_string()
+            + &civ.city.nb_slots_buildings.to_string()
             + "\n- "
-            + &state.civilizations[state.player_turn]
-                .constructions
-                .len()
-                .to_string()
+            + &civ.constructions.len().to_string()
             + " en construction",
         0,
-        "- Construire Batiment (build)\n- Recruter Unité(hire)\n- Attaquer (attack)\n- Finir Tour (end)",
+        "- Construire Batiment (build)\n- File de construction (queue)\n- Recruter Unité(hire)\n- Attaquer (attack)\n- Simuler attaque (simulate attack)\n- Démolir Batiment (demolish)\n- Rechercher Technologie (research)\n- Emprunter (loan)\n- Politique (policy)\n- Diplomatie (diplomacy, gift, trade, pact)\n- Espionner (scout)\n- Prévisions météo (weather)\n- Revenus (income)\n- Finir Tour (end)",
+        civ.constructions.len(),
+        state.build_queue,
         constructions_text,
-        recruitement_text
+        recruitement_text,
+        travels_text
     );
+    // Research only shows up when the configuration has a tech tree
+    if !state.techs.is_empty() {
+        let research_text = progress_list(civ.research.iter().map(|r| {
+            progress_line(
+                &r.id_tech,
+                r.total.saturating_sub(r.remaining),
+                r.total,
+                Some(r.remaining),
+            )
+        }));
+        let researched: Vec<&str> = civ.researched.iter().map(String::as_str).collect();
+        let _ = write!(
+            player_text,
+            "\n\nRecherche en cours: \n{research_text}\n\nTechnologies: {}",
+            if researched.is_empty() {
+                "Aucune".to_string()
+            } else {
+                researched.join(", ")
+            }
+        );
+    }
+    // Storage of the city, and the spending that leads to the economic victory
+    if let Some(capacity) = storage::capacity(state, state.player_turn) {
+        let _ = write!(
+            player_text,
+            "\n\nStockage: {}/{capacity}",
+            civ.resources.ressources
+        );
+        let waste = storage::projected_waste(state, state.player_turn);
+        if waste > 0 {
+            let _ = write!(player_text, " (plein: {waste} perdues au prochain tour)");
+        }
+        if state.resources_spent > 0 {
+            let _ = write!(
+                player_text,
+                "\nDépensées: {}/{}",
+                civ.resources.spent, state.resources_spent
+            );
+        }
+    }
+    // Cities taken from other civilizations, with their garrison
+    if !civ.captured.is_empty() {
+        let captured: Vec<String> = civ
+            .captured
+            .iter()
+            .map(|k| {
+                format!(
+                    "{} ({} en garnison)",
+                    k.city.name,
+                    k.city.units.units.iter().map(|u| u.nb_units).sum::<u32>()
+                )
+            })
+            .collect();
+        let _ = write!(player_text, "\n\nVilles capturées: {}", captured.join(", "));
+    }
+    // Units left outside a full city by the stack limit
+    let waiting = stacks::waiting(civ);
+    if waiting > 0 {
+        let _ = write!(
+            player_text,
+            "\n\nHors les murs: {waiting} unités attendent une place"
+        );
+    }
+    // Enemy armies last seen under the fog of war
+    if state.fog.is_some() {
+        let sightings = fog::sightings(state, state.player_turn);
+        let lines = sightings
+            .iter()
+            .filter(|s| s.defender != state.player_turn)
+            .map(|s| {
+                format!(
+                    "{} unités de {} vues il y a {} tour(s) en ({}, {})",
+                    s.amount,
+                    raiders::army_owner(state, s.attacker),
+                    s.age(state.turn),
+                    s.tile.0,
+                    s.tile.1
+                )
+            });
+        let _ = write!(
+            player_text,
+            "\n\nArmées aperçues: \n{}",
+            progress_list(lines)
+        );
+    }
 
     let player = Paragraph::new(player_text).block(
         Block::default()
-            .title(format!(
-                "Jouer - {}",
-                state.civilizations[state.player_turn].city.name
-            ))
+            .title(format!("Jouer - {}", civ.city.name))
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(ui_config.color)),
+            .border_style(Style::default().fg(ui_config.accent())),
     );
     frame.render_widget(player, areas[1]);
 }
 
+/// Lines of a progress list, "Aucun" when empty.
+fn progress_list(lines: impl Iterator<Item = String>) -> String {
+    let lines: Vec<String> = lines.collect();
+    if lines.is_empty() {
+        "Aucun".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+/// Progress gauge of something finishing in `eta` turns (`None`: stalled by the policy).
+fn progress_line(label: &str, done: u32, total: u32, eta: Option<u32>) -> String {
+    const WIDTH: u32 = 10;
+    let filled = (done * WIDTH)
+        .checked_div(total)
+        .unwrap_or(WIDTH)
+        .min(WIDTH) as usize;
+    let eta = match eta {
+        Some(1) => "1 tour restant".to_string(),
+        Some(turns) => format!("{turns} tours restants"),
+        None => "à l'arrêt".to_string(),
+    };
+    format!(
+        "- {label}\n  [{}{}] {eta}",
+        "#".repeat(filled),
+        "-".repeat(WIDTH as usize - filled)
+    )
+}
+
+/// Draw the candidates the last AI weighed, best first, with the chosen one marked.
+fn draw_decision_panel(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiConfig) {
+    let (title, lines) = if let Some(trace) = &state.last_decision {
+        let name = state
+            .civilizations
+            .get(trace.civ_index)
+            .map_or("?", |c| c.city.name.as_str());
+        let lines: Vec<Line> = trace
+            .candidates
+            .iter()
+            .map(|c| {
+                let chosen = trace.chosen.as_deref() == Some(c.action.as_str());
+                let text = format!(
+                    "{} {:.2} {} ({})",
+                    if chosen { ">" } else { " " },
+                    c.value,
+                    c.action,
+                    c.reason
+                );
+                if chosen {
+                    Line::from(Span::styled(text, Style::default().fg(Color::Yellow)))
+                } else {
+                    Line::from(Span::raw(text))
+                }
+            })
+            .collect();
+        (
+            format!("Décision IA - {} (Tour {})", name, trace.turn),
+            lines,
+        )
+    } else {
+        (
+            "Décision IA".to_string(),
+            vec![Line::from(Span::raw("Aucune décision expliquée"))],
+        )
+    };
+
+    let panel = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(ui_config.accent())),
+    );
+    frame.render_widget(panel, area);
+}
+
 /// Draw a centered popup overlay when the AI is thinking.
 fn draw_ai_thinking_popup(frame: &mut Frame, ui_config: &UiConfig) {
     let full = frame.area();
@@ -196,65 +793,136 @@ fn draw_ai_thinking_popup(frame: &mut Frame, ui_config: &UiConfig) {
     frame.render_widget(bg_block, popup_area);
 
     // Draw the popup content
-    let mut styled_lines: Vec<Line> = Vec::new();
-    styled_lines.push(Line::from(Span::styled(
-        String::new(),
-        Style::default().fg(Color::White).bg(Color::Black),
-    )));
-    styled_lines.push(Line::from(Span::styled(
-        "⏳ AI is thinking...",
-        Style::default().fg(Color::Yellow).bg(Color::Black),
-    )));
-    styled_lines.push(Line::from(Span::styled(
-        String::new(),
-        Style::default().fg(Color::White).bg(Color::Black),
-    )));
-    styled_lines.push(Line::from(Span::styled(
-        "Please wait while the AI makes its move.",
-        Style::default().fg(Color::White).bg(Color::Black),
-    )));
+    let styled_lines = vec![
+        Line::from(Span::styled(
+            String::new(),
+            Style::default().fg(Color::White).bg(Color::Black),
+        )),
+        Line::from(Span::styled(
+            "⏳ AI is thinking...",
+            Style::default().fg(Color::Yellow).bg(Color::Black),
+        )),
+        Line::from(Span::styled(
+            String::new(),
+            Style::default().fg(Color::White).bg(Color::Black),
+        )),
+        Line::from(Span::styled(
+            "Please wait while the AI makes its move.",
+            Style::default().fg(Color::White).bg(Color::Black),
+        )),
+    ];
 
     let popup_widget = Paragraph::new(styled_lines)
         .block(
             Block::default()
                 .title("AI Turn")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(ui_config.color)),
+                .border_style(Style::default().fg(ui_config.accent())),
         )
         .alignment(ratatui::layout::Alignment::Center);
     frame.render_widget(popup_widget, popup_area);
 }
 
+/// Advice of the advisor for the player whose turn it is, on the line under the action bar.
+/// Banner across the middle of the screen for a moment when the turn passes to a human player,
+/// on the color of its city. Not shown with reduced motion, the status bar names the player.
+fn draw_turn_banner(frame: &mut Frame, state: &GameState, ui_config: &UiConfig) {
+    let Some((civ_index, since)) = state.turn_banner else {
+        return;
+    };
+    let Ok(civ) = state.civ(civ_index) else {
+        return;
+    };
+    if since.elapsed() >= TURN_BANNER_DURATION
+        || ui_config.preferences.reduced_motion
+        || state.popup.is_some()
+        || state.game_over
+        || !matches!(civ.city.player_type, crate::ast::PlayerType::PLAYER)
+    {
+        return;
+    }
+    let text = format!("Au tour de {}", civ.city.name);
+    let full = frame.area();
+    let width = u16::try_from(text.chars().count() + 8)
+        .unwrap_or(u16::MAX)
+        .min(full.width);
+    let area = Rect {
+        x: full.x + full.width.saturating_sub(width) / 2,
+        y: full.y + full.height.saturating_sub(3) / 2,
+        width,
+        height: 3.min(full.height),
+    };
+    let (fg, bg) = if ui_config.preferences.high_contrast {
+        (Color::Black, Color::White)
+    } else {
+        let color = ui_config.accent();
+        (text_color_on(color), color)
+    };
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(vec![Line::raw(""), Line::raw(text), Line::raw("")])
+            .style(Style::default().fg(fg).bg(bg).add_modifier(Modifier::BOLD))
+            .alignment(Alignment::Center),
+        area,
+    );
+}
+
+fn draw_hint(frame: &mut Frame, area: Rect, state: &GameState) {
+    let Some(hint) = advisor::hint(state, state.player_turn).filter(|_| !state.ai_thinking) else {
+        return;
+    };
+    let line = Paragraph::new(format!(" Conseil : {hint}")).style(Style::default().fg(Color::Gray));
+    frame.render_widget(line, area);
+}
+
+#[allow(clippy::too_many_lines)]
 fn draw_action(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiConfig) {
     // Show AI thinking message if AI is processing
-    let action_text = if state.ai_thinking {
-        "⏳ AI is thinking, please wait...".to_string()
+    let speed = format!("{}/{}", state.ai_speed + 1, AI_PLAYBACK_DELAYS_MS.len());
+    let action_text = if state.annotating {
+        format!(
+            "Note: {}_ (@x,y text: place a marker, Enter: save, Esc: cancel)",
+            state.action_input
+        )
+    } else if state.ai_thinking && state.ai_paused {
+        format!("⏸ AI paused (Space: resume, n: next action, a: annotate, +/-: speed {speed})")
+    } else if state.ai_thinking {
+        format!("⏳ AI is thinking, please wait... (Space: pause, +/-: speed {speed})")
     } else if state.action_editing {
         format!("{}_", state.action_input)
     } else if !state.action_input.is_empty() {
         state.action_input.clone()
     } else {
-        "(press 'a' to type an action)".to_string()
+        "(press 'a' to type an action, 'm' to pick one from the menu)".to_string()
     };
 
     let resources = Paragraph::new(action_text.clone()).block(
         Block::default()
             .title("Action")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(ui_config.color)),
+            .border_style(Style::default().fg(ui_config.accent())),
     );
     frame.render_widget(resources, area);
 
-    // Show AI thinking popup overlay if AI is processing
-    if state.ai_thinking {
+    // Show AI thinking popup overlay if AI is processing (it flashes on every AI turn, so
+    // reduced motion keeps the message in the action bar only). Hidden while paused so the
+    // map can be inspected.
+    if state.ai_thinking && !state.ai_paused && !ui_config.preferences.reduced_motion {
         draw_ai_thinking_popup(frame, ui_config);
     }
 
     // If a popup is open, render a centered overlay on top of everything
     if let Some(popup) = &state.popup {
+        let large = ui_config.preferences.large_popups;
         let full = frame.area();
-        let w = full.width.saturating_sub(10).min(60);
-        let h = full.height.saturating_sub(8).min(12);
+        let w = full
+            .width
+            .saturating_sub(10)
+            .min(if large { 90 } else { 60 });
+        let h = full
+            .height
+            .saturating_sub(8)
+            .min(if large { 24 } else { 12 });
         let x = full.x + (full.width.saturating_sub(w) / 2);
         let y = full.y + (full.height.saturating_sub(h) / 2);
         let popup_area = Rect {
@@ -287,11 +955,16 @@ fn draw_action(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiC
         let bg_block = Paragraph::new(bg_lines);
         frame.render_widget(bg_block, popup_area);
         // Build styled lines for popup content (force white on black so map colors don't bleed)
-        let mut styled_lines: Vec<Line> = Vec::new();
-        styled_lines.push(Line::from(Span::styled(
-            popup.prompt.clone(),
-            Style::default().fg(Color::White).bg(Color::Black),
-        )));
+        let mut styled_lines: Vec<Line> = popup
+            .prompt
+            .lines()
+            .map(|l| {
+                Line::from(Span::styled(
+                    l.to_string(),
+                    Style::default().fg(Color::White).bg(Color::Black),
+                ))
+            })
+            .collect();
         styled_lines.push(Line::from(Span::styled(
             String::new(),
             Style::default().fg(Color::White).bg(Color::Black),
@@ -314,26 +987,155 @@ fn draw_action(frame: &mut Frame, area: Rect, state: &GameState, ui_config: &UiC
             )));
         }
 
-        let popup_widget = Paragraph::new(styled_lines).block(
+        // Large popups: bold text with a blank line between lines
+        if large {
+            styled_lines = styled_lines
+                .into_iter()
+                .flat_map(|line| [line.patch_style(Modifier::BOLD), Line::from("")])
+                .collect();
+        }
+
+        let popup_widget = Paragraph::new(styled_lines)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .title(popup.title.clone())
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(ui_config.accent())),
+            );
+        frame.render_widget(popup_widget, popup_area);
+    }
+}
+
+/// Draw the action menu over the map: the categories, or the entries of the open one, the
+/// selected line marked like in the campaign menu.
+fn draw_action_menu(frame: &mut Frame, state: &GameState, menu: &ActionMenu, ui_config: &UiConfig) {
+    let (title, labels, selected) = if let Some(entry) = menu.entry {
+        let category = menu.selected_category(state);
+        let labels: Vec<String> = action_menu::entries(state, category)
+            .into_iter()
+            .map(|e| e.label)
+            .collect();
+        (category.label(), labels, entry)
+    } else {
+        let labels = action_menu::categories(state)
+            .iter()
+            .map(|c| c.label().to_string())
+            .collect();
+        ("Actions", labels, menu.category)
+    };
+    let lines: Vec<Line> = labels
+        .into_iter()
+        .enumerate()
+        .map(|(i, label)| {
+            if i == selected {
+                Line::from(Span::styled(
+                    format!("> {label}"),
+                    Style::default().fg(Color::Yellow),
+                ))
+            } else {
+                Line::from(format!("  {label}"))
+            }
+        })
+        .collect();
+
+    let full = frame.area();
+    let width = full.width.saturating_sub(10).min(60);
+    let height = u16::try_from(lines.len() + 2)
+        .unwrap_or(u16::MAX)
+        .min(full.height.saturating_sub(4));
+    let area = Rect {
+        x: full.x + full.width.saturating_sub(width) / 2,
+        y: full.y + full.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    let menu_widget = Paragraph::new(lines)
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .block(
             Block::default()
-                .title(popup.title.clone())
+                .title(format!(
+                    "{title} (↑/↓ choisir, →/Entrée valider, ←/Échap retour)"
+                ))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(ui_config.color)),
+                .border_style(Style::default().fg(ui_config.accent())),
         );
-        frame.render_widget(popup_widget, popup_area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(menu_widget, area);
+}
+
+/// Draw the campaign scenario selection screen.
+///
+/// # Errors
+/// Fails when the terminal cannot be drawn.
+pub fn draw_campaign_menu(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    campaign: &Campaign,
+    selected: usize,
+) -> anyhow::Result<()> {
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, scenario) in campaign.manifest.scenarios.iter().enumerate() {
+        let status = if campaign.progress.completed[i] {
+            "[terminé]"
+        } else if campaign.is_unlocked(i) {
+            "[disponible]"
+        } else {
+            "[verrouillé]"
+        };
+        let text = format!(
+            "{} {}. {} {}",
+            if i == selected { ">" } else { " " },
+            i + 1,
+            scenario.title,
+            status
+        );
+        let style = if i == selected {
+            Style::default().fg(Color::Yellow)
+        } else if campaign.is_unlocked(i) {
+            Style::default()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        lines.push(Line::from(Span::styled(text, style)));
+    }
+    if let Some(carry) = &campaign.progress.carry_over {
+        lines.push(Line::from(""));
+        lines.push(Line::from(format!(
+            "Héritage: {} ressources, {} unités",
+            carry.resources,
+            carry.units.iter().map(|(_, n)| n).sum::<u32>()
+        )));
     }
+
+    terminal.draw(|f| {
+        let block = Paragraph::new(lines.clone()).block(
+            Block::default()
+                .title(format!(
+                    "Campagne: {} (↑/↓ choisir, Entrée jouer, Ctrl+Q quitter)",
+                    campaign.manifest.name
+                ))
+                .borders(Borders::ALL),
+        );
+        f.render_widget(block, f.area());
+    })?;
+
+    Ok(())
 }
 
+/// Draw the 256-color test screen.
+///
+/// # Errors
+/// Fails when the terminal cannot be drawn.
 pub fn draw_color_test_256(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
 ) -> anyhow::Result<()> {
     // Build 16x16 grid of indexed colors (0..=255)
-    let cols = 16;
+    let cols: u8 = 16;
     let mut lines: Vec<Line> = Vec::new();
     for row in 0..16 {
         let mut spans: Vec<Span> = Vec::new();
         for col in 0..cols {
-            let idx = (row * cols + col) as u8;
+            let idx = row * cols + col;
             // Each cell shows the index as 3 chars with background set to the indexed color
             let text = format!("{idx:>3}");
             let style = Style::default().bg(Color::Indexed(idx)).fg(Color::Reset);
@@ -357,6 +1159,10 @@ pub fn draw_color_test_256(
     Ok(())
 }
 
+/// Draw the true-color test screen.
+///
+/// # Errors
+/// Fails when the terminal cannot be drawn.
 pub fn draw_color_test_rgb(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     offset: u32,
@@ -368,6 +1174,8 @@ pub fn draw_color_test_rgb(
     for v in 0..height {
         let mut spans: Vec<Span> = Vec::new();
         for h in offset..=360 {
+            // hues stay within 0 to 360, exact in an f32
+            #[allow(clippy::cast_precision_loss)]
             let (r, g, b) = hsv_to_rgb(h as f32, 1.0, f32::from(v) / f32::from(height));
             let color = Color::Rgb(r, g, b);
             let text = "  "; // two spaces for better visibility
@@ -390,6 +1198,10 @@ pub fn draw_color_test_rgb(
     Ok(())
 }
 
+/// Leave the alternate screen and restore the terminal.
+///
+/// # Errors
+/// Fails when the terminal cannot be restored.
 pub fn cleanup_term(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
 ) -> anyhow::Result<()> {
@@ -0,0 +1,157 @@
+use crate::game::capture;
+use crate::game::state::GameState;
+use std::fmt::Write;
+
+/// Upkeep the units of `civ_index` cost each turn: those in its city, waiting outside,
+/// marching in its armies and garrisoning the cities it captured.
+pub fn unit_upkeep(state: &GameState, civ_index: usize) -> i32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    let armies = state
+        .travels
+        .iter()
+        .filter(|t| t.attacker == civ_index)
+        .flat_map(|t| &t.composition);
+    civ.city
+        .units
+        .units
+        .iter()
+        .chain(&civ.waiting)
+        .chain(armies)
+        .chain(capture::garrisons(state, civ_index))
+        .fold(0u64, |upkeep, u| {
+            upkeep.saturating_add(u64::from(u.nb_units) * u64::from(per_unit(state, &u.id_units)))
+        })
+        .try_into()
+        .unwrap_or(i32::MAX)
+}
+
+/// Upkeep of one unit of type `unit`.
+fn per_unit(state: &GameState, unit: &str) -> u32 {
+    state.unit_def(unit).and_then(|d| d.upkeep).unwrap_or(0)
+}
+
+/// Maintenance the buildings of `civ_index` in service cost each turn.
+pub fn maintenance(state: &GameState, civ_index: usize) -> i32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    civ.city
+        .buildings
+        .elements
+        .iter()
+        .filter(|b| !civ.disabled.contains(&b.id_building))
+        .filter_map(|b| state.building_def(&b.id_building))
+        .fold(0u64, |maintenance, d| {
+            maintenance.saturating_add(u64::from(d.maintenance.unwrap_or(0)))
+        })
+        .try_into()
+        .unwrap_or(i32::MAX)
+}
+
+/// Resources `civ_index` owes each turn for its units and buildings in service.
+pub fn due(state: &GameState, civ_index: usize) -> i32 {
+    unit_upkeep(state, civ_index).saturating_add(maintenance(state, civ_index))
+}
+
+/// Pay the upkeep of the units and buildings of `civ_index` at its turn start. Shut down buildings
+/// come back into service when the treasury can afford them again. When it cannot pay, units of
+/// the city are disbanded, the costliest first, then buildings are shut down until what is left
+/// is affordable. Returns a report of the cuts, if any.
+pub fn pay(state: &mut GameState, civ_index: usize) -> Option<String> {
+    let civ = state.civ_mut(civ_index).ok()?;
+    let reopened = std::mem::take(&mut civ.disabled);
+    let available = civ.resources.ressources.max(0);
+    let mut report = String::new();
+    let disbanded = disband(state, civ_index, available);
+    if disbanded > 0 {
+        let _ = write!(report, "{disbanded} units disbanded for want of upkeep. ");
+    }
+    let shut = shut_down(state, civ_index, available);
+    if !shut.is_empty() {
+        let _ = write!(
+            report,
+            "Shut down for want of maintenance: {}. ",
+            shut.join(", ")
+        );
+    }
+    let reopened: Vec<&String> = reopened.iter().filter(|b| !shut.contains(b)).collect();
+    if !reopened.is_empty() {
+        let names: Vec<&str> = reopened.iter().map(|b| b.as_str()).collect();
+        let _ = write!(report, "Back in service: {}. ", names.join(", "));
+    }
+    let paid = due(state, civ_index).min(available);
+    state.civ_mut(civ_index).ok()?.earn(-paid);
+    state.metrics.pay_upkeep(civ_index, paid.cast_unsigned());
+    let report = report.trim_end().to_string();
+    (!report.is_empty()).then_some(report)
+}
+
+/// Disband units of the city of `civ_index`, the costliest first, until its upkeep fits in
+/// `available` along with the maintenance of its buildings. Returns the units disbanded.
+fn disband(state: &mut GameState, civ_index: usize, available: i32) -> u32 {
+    let mut disbanded: u32 = 0;
+    while due(state, civ_index) > available {
+        let Ok(civ) = state.civ(civ_index) else {
+            break;
+        };
+        let costliest = civ
+            .city
+            .units
+            .units
+            .iter()
+            .chain(&civ.waiting)
+            .map(|u| (u.id_units.clone(), per_unit(state, &u.id_units)))
+            .filter(|(_, cost)| *cost > 0)
+            .max_by_key(|(_, cost)| *cost);
+        let Some((unit, cost)) = costliest else {
+            break;
+        };
+        // units waiting outside go first
+        let mut needed = (due(state, civ_index) - available)
+            .cast_unsigned()
+            .div_ceil(cost);
+        let Ok(civ) = state.civ_mut(civ_index) else {
+            break;
+        };
+        for units in [&mut civ.waiting, &mut civ.city.units.units] {
+            for instance in units.iter_mut().filter(|u| u.id_units == unit) {
+                let taken = instance.nb_units.min(needed);
+                instance.nb_units -= taken;
+                needed -= taken;
+                disbanded = disbanded.saturating_add(taken);
+            }
+            units.retain(|u| u.nb_units > 0);
+        }
+    }
+    state.metrics.lose_units(civ_index, disbanded);
+    disbanded
+}
+
+/// Shut down buildings of `civ_index`, the costliest first, until its upkeep fits in `available`.
+/// Returns the buildings shut down.
+fn shut_down(state: &mut GameState, civ_index: usize, available: i32) -> Vec<String> {
+    let mut shut = Vec::new();
+    while due(state, civ_index) > available {
+        let Ok(civ) = state.civ(civ_index) else {
+            break;
+        };
+        let costliest = civ
+            .city
+            .buildings
+            .elements
+            .iter()
+            .filter(|b| !civ.disabled.contains(&b.id_building))
+            .filter_map(|b| state.building_def(&b.id_building))
+            .filter(|d| d.maintenance.unwrap_or(0) > 0)
+            .max_by_key(|d| d.maintenance)
+            .map(|d| d.name.clone());
+        let (Some(building), Ok(civ)) = (costliest, state.civ_mut(civ_index)) else {
+            break;
+        };
+        civ.disabled.insert(building.clone());
+        shut.push(building);
+    }
+    shut
+}
@@ -1,39 +1,71 @@
 use anyhow::{Result, anyhow};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Directory receiving every file the game writes, chosen once at startup
+static OUTPUT_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Choose where the game writes its files: the platform data directory (`~/.local/share/clivilization`
+/// on Linux, `~/Library/Application Support/clivilization` on macOS, `%APPDATA%\clivilization` on
+/// Windows), or `output/` in the working directory in portable mode. Later calls are ignored.
+pub fn init_output_dir(portable: bool) {
+    let dir = if portable {
+        PathBuf::from("output")
+    } else {
+        dirs::data_dir().map_or_else(|| PathBuf::from("output"), |d| d.join("clivilization"))
+    };
+    if OUTPUT_DIR.set(dir).is_err() {
+        log::warn!("Output directory already chosen, ignoring new choice");
+    }
+}
+
+/// Directory receiving every file the game writes (the platform data directory by default).
+pub fn output_dir() -> &'static Path {
+    OUTPUT_DIR.get_or_init(|| {
+        dirs::data_dir().map_or_else(|| PathBuf::from("output"), |d| d.join("clivilization"))
+    })
+}
+
+/// Path of `filename` in the output directory.
+pub fn output_path(filename: &str) -> PathBuf {
+    output_dir().join(filename)
+}
 
 /// Hash a string using FNV-1a algorithm.
-/// 
+///
 /// This is a simple non-cryptographic hash function useful for generating
 /// deterministic seeds from text input.
-/// 
+///
 /// # Arguments
 /// * `text` - The string to hash
-/// 
+///
 /// # Returns
 /// A 32-bit hash value
-/// 
+///
 /// Hash string using FNV-1a for deterministic seed generation. Non-cryptographic but fast and collision-resistant for game purposes.
-pub fn hash_tmb(text: String) -> u32 {
-    let mut hash: u32 = 2166136261; // FNV offset basis
+pub fn hash_tmb(text: &str) -> u32 {
+    let mut hash: u32 = 2_166_136_261; // FNV offset basis
 
     for byte in text.as_bytes() {
         hash ^= u32::from(*byte);
-        hash = hash.wrapping_mul(16777619); // FNV prime
+        hash = hash.wrapping_mul(16_777_619); // FNV prime
     }
 
     hash
 }
 
 /// Convert HSV color values to RGB.
-/// 
+///
 /// # Arguments
 /// * `h` - Hue (0.0 to 360.0)
 /// * `s` - Saturation (0.0 to 1.0)
 /// * `v` - Value/Brightness (0.0 to 1.0)
-/// 
+///
 /// # Returns
 /// A tuple of (red, green, blue) values from 0 to 255
-/// 
+///
 /// Convert HSV to RGB using standard color wheel math. Handles all hue ranges with continuous transitions.
+#[allow(clippy::many_single_char_names)]
 pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
     let c = v * s;
     let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
@@ -49,46 +81,151 @@ pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
         _ => (0.0, 0.0, 0.0),
     };
 
-    let r = ((r1 + m) * 255.0).round() as u8;
-    let g = ((g1 + m) * 255.0).round() as u8;
-    let b = ((b1 + m) * 255.0).round() as u8;
+    // the channels stay within 0.0 to 1.0 for the documented inputs
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let channel = |value: f32| ((value + m) * 255.0).round() as u8;
 
-    (r, g, b)
+    (channel(r1), channel(g1), channel(b1))
 }
 
 /// Parse an HTML hex color string into a ratatui Color.
-/// 
+///
 /// # Arguments
 /// * `s` - Color string in the format "#RRGGBB"
-/// 
+///
 /// # Returns
 /// A ratatui Color, or white if the format is invalid
-/// 
+///
 /// Parse HTML hex color (#RRGGBB) with graceful fallback to white. Tolerates invalid input without panicking.
-pub fn str_to_color(s: &str) -> ratatui::style::Color {
+pub fn str_to_color(s: &str) -> ratatui_core::style::Color {
     // Str is in html hex format: #RRGGBB
     if s.len() != 7 || !s.starts_with('#') {
-        ratatui::style::Color::White
+        ratatui_core::style::Color::White
     } else {
         let r = u8::from_str_radix(&s[1..3], 16).unwrap_or(255);
         let g = u8::from_str_radix(&s[3..5], 16).unwrap_or(255);
         let b = u8::from_str_radix(&s[5..7], 16).unwrap_or(255);
-        ratatui::style::Color::Rgb(r, g, b)
+        ratatui_core::style::Color::Rgb(r, g, b)
+    }
+}
+
+/// Perceived brightness of an RGB color, from 0 to 255.
+fn luminance(red: u8, green: u8, blue: u8) -> u32 {
+    (299 * u32::from(red) + 587 * u32::from(green) + 114 * u32::from(blue)) / 1000
+}
+
+/// `color` lightened towards white until it reads well on the black background of the
+/// interface. Dark city colors (navy, maroon...) would otherwise vanish as borders.
+pub fn readable_color(color: ratatui_core::style::Color) -> ratatui_core::style::Color {
+    const MIN_LUMINANCE: u32 = 110;
+    let ratatui_core::style::Color::Rgb(red, green, blue) = color else {
+        return color;
+    };
+    let current = luminance(red, green, blue);
+    if current >= MIN_LUMINANCE {
+        return color;
+    }
+    // move each channel the same share of the way to 255
+    let lighten = |channel: u8| {
+        let gap = 255 - u32::from(channel);
+        u8::try_from(u32::from(channel) + gap * (MIN_LUMINANCE - current) / (255 - current))
+            .unwrap_or(u8::MAX)
+    };
+    ratatui_core::style::Color::Rgb(lighten(red), lighten(green), lighten(blue))
+}
+
+/// Black or white, whichever stays legible written over `background`.
+pub fn text_color_on(background: ratatui_core::style::Color) -> ratatui_core::style::Color {
+    match background {
+        ratatui_core::style::Color::Rgb(red, green, blue) if luminance(red, green, blue) < 140 => {
+            ratatui_core::style::Color::White
+        }
+        _ => ratatui_core::style::Color::Black,
     }
 }
 
-/// Write to output/ directory with automatic creation. Rich error context aids debugging file I/O failures.
+/// Write to the output directory with automatic creation. Rich error context aids debugging file I/O failures.
+///
+/// # Errors
+/// Fails when the directory or the file cannot be written.
 pub fn write_to_file(filename: &str, content: &str) -> Result<()> {
-    // Create output/ directory if it doesn't exist
-    if let Err(e) = std::fs::create_dir_all("output") {
-        log::error!("Failed to create output/ directory: {e}");
-        return Err(anyhow!("Failed to create output/ directory: {e}"));
+    let filepath = output_path(filename);
+    if let Some(parent) = filepath.parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        log::error!("Failed to create directory {}: {e}", parent.display());
+        return Err(anyhow!(
+            "Failed to create directory {}: {e}",
+            parent.display()
+        ));
     }
-    let filepath = format!("output/{filename}");
     if let Err(e) = std::fs::write(&filepath, content) {
-        log::error!("Failed to write to file {filepath}: {e}");
-        return Err(anyhow!("Failed to write to file {filepath}: {e}"));
+        log::error!("Failed to write to file {}: {e}", filepath.display());
+        return Err(anyhow!(
+            "Failed to write to file {}: {e}",
+            filepath.display()
+        ));
+    }
+    log::info!("Wrote file {}", filepath.display());
+    // tournament games sign everything they write so tampered results can be detected
+    if let Some(key) = super::signing::tournament_key() {
+        super::signing::sign_file(&filepath, &key)?;
     }
-    log::info!("Wrote file {filepath}");
     Ok(())
 }
+
+/// Edit distance between two strings, counted in characters. Levenshtein distance where
+/// swapping two adjacent characters counts as a single typo (optimal string alignment).
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Candidates close to a mistyped name (case-insensitive), closest first, at most three.
+/// A third of the input length may be wrong, with at least one edit allowed.
+pub fn did_you_mean<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let input = input.to_lowercase();
+    let max_distance = (input.chars().count() / 3).max(1);
+    let mut scored: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|c| (levenshtein(&input, &c.to_lowercase()), c))
+        .filter(|(d, _)| *d <= max_distance)
+        .collect();
+    scored.sort_by_key(|(d, _)| *d);
+    scored
+        .into_iter()
+        .take(3)
+        .map(|(_, c)| c.to_string())
+        .collect()
+}
+
+/// Error message for an unknown name, with suggestions when there are some.
+pub fn unknown_with_suggestions(kind: &str, input: &str, suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        format!("Unknown {kind}: {input}")
+    } else {
+        format!(
+            "Unknown {kind}: {input} — did you mean {}?",
+            suggestions.join(", ")
+        )
+    }
+}
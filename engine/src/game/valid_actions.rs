@@ -0,0 +1,249 @@
+use crate::game::build_queue;
+use crate::game::cancel;
+use crate::game::composition;
+use crate::game::diplomacy::{self, Treaty};
+use crate::game::espionage::{self, SCOUT_COST};
+use crate::game::fog;
+use crate::game::loans::{self, INTEREST_PERCENT, MAX_DEBT};
+use crate::game::state::GameState;
+use crate::game::tech;
+use crate::game::turn_rules::end_turn_blockers;
+use crate::game::war::{self, Terms};
+
+/// An action the player whose turn it is can play right now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidAction {
+    /// Text to type in the action bar, `<...>` marking the arguments left to the player
+    pub syntax: String,
+    /// What the action costs, empty when it is free
+    pub cost: String,
+}
+
+impl ValidAction {
+    fn new(syntax: impl Into<String>, cost: impl Into<String>) -> Self {
+        Self {
+            syntax: syntax.into(),
+            cost: cost.into(),
+        }
+    }
+}
+
+/// Whether `action` succeeds on a clone of the state, so the validation is the very one run by
+/// `submit_action` and nothing is committed.
+fn accepted<T>(
+    state: &GameState,
+    action: impl FnOnce(&mut GameState) -> anyhow::Result<T>,
+) -> bool {
+    let mut probe = state.clone();
+    probe.map_buffer_cache = None;
+    action(&mut probe).is_ok()
+}
+
+/// Largest loan `civ_index` may still take before reaching the debt limit.
+fn max_loan(state: &GameState, civ_index: usize) -> u32 {
+    let Ok(civ) = state.civ(civ_index) else {
+        return 0;
+    };
+    let available = MAX_DEBT.saturating_sub(loans::debt(civ));
+    let mut amount = available * 100 / (100 + INTEREST_PERCENT);
+    while amount > 0 && !accepted(state, |s| loans::take_loan(s, civ_index, amount)) {
+        amount -= 1;
+    }
+    amount
+}
+
+/// Edits of the build queue of `civ_index`: reordering once two entries wait, and cancelling
+/// each entry for its refund.
+fn queue_actions(state: &GameState, civ_index: usize) -> Vec<ValidAction> {
+    let mut actions = Vec::new();
+    let Ok(civ) = state.civ(civ_index) else {
+        return actions;
+    };
+    let constructions = &civ.constructions;
+    if constructions.len() > 1 {
+        actions.push(ValidAction::new("queue up|down <entry>", ""));
+    }
+    for (i, c) in constructions.iter().enumerate() {
+        let refund = build_queue::refund(state, civ_index, i);
+        actions.push(ValidAction::new(
+            format!("queue cancel {}", i + 1),
+            format!("rend {refund} ressources ({})", c.id_building),
+        ));
+    }
+    actions
+}
+
+/// Cancellations of the recruitment in progress of `civ_index` and recalls of its armies.
+fn cancel_actions(state: &GameState, civ_index: usize) -> Vec<ValidAction> {
+    let mut actions = Vec::new();
+    if let Some(r) = state
+        .civ(civ_index)
+        .ok()
+        .and_then(|c| c.recruitments.first())
+    {
+        let refund = cancel::refund(state, r.cost, r.remaining, r.total);
+        actions.push(ValidAction::new(
+            "cancel hire",
+            format!("rend {refund} ressources ({})", r.id_unit),
+        ));
+    }
+    for target in cancel::recall_targets(state, civ_index) {
+        actions.push(ValidAction::new(format!("recall {target}"), ""));
+    }
+    actions
+}
+
+/// Attacks, diplomacy, spying and simulations `civ_index` may direct at each surviving rival.
+fn rival_actions(state: &GameState, civ_index: usize) -> Vec<ValidAction> {
+    let mut actions = Vec::new();
+    let Ok(civ) = state.civ(civ_index) else {
+        return actions;
+    };
+    let units = composition::total(&civ.city.units.units);
+    let targets = state.reachable_targets(civ_index);
+    for (i, rival) in state.civilizations.iter().enumerate() {
+        if i == civ_index || !rival.alive {
+            continue;
+        }
+        let name = &rival.city.name;
+        if let Some((_, path)) = targets.iter().find(|(t, _)| *t == i)
+            && accepted(state, |s| s.start_attack(civ_index, i, None))
+        {
+            actions.push(ValidAction::new(
+                format!("attack {name} [nombre]"),
+                format!("jusqu'à {units} unités, {} tours", path.turns),
+            ));
+        }
+        let gift = diplomacy::GIFT_RESOURCES_PER_FAVOR;
+        if accepted(state, |s| diplomacy::gift(s, civ_index, i, gift)) {
+            actions.push(ValidAction::new(
+                format!("gift {name} <montant>"),
+                format!("{gift} ressources par faveur"),
+            ));
+        }
+        for (verb, treaty) in [("trade", Treaty::Trade), ("pact", Treaty::DefensePact)] {
+            if accepted(state, |s| diplomacy::sign(s, treaty, civ_index, i)) {
+                actions.push(ValidAction::new(format!("{verb} {name}"), ""));
+            }
+        }
+        if accepted(state, |s| espionage::scout(s, civ_index, i)) {
+            actions.push(ValidAction::new(
+                format!("scout {name}"),
+                format!("{SCOUT_COST} ressources"),
+            ));
+        }
+        if accepted(state, |s| {
+            war::make_peace(s, civ_index, i, Terms::WhitePeace)
+        }) {
+            actions.push(ValidAction::new(
+                format!("peace {name}"),
+                "conditions au choix",
+            ));
+        }
+        if fog::explored(
+            state,
+            civ_index,
+            (rival.city.x.cast_signed(), rival.city.y.cast_signed()),
+        ) {
+            actions.push(ValidAction::new(
+                format!("simulate attack {name} [nombre]"),
+                "",
+            ));
+        }
+    }
+    actions
+}
+
+/// Actions of `civ_index` that would be accepted right now, with their exact syntax and cost.
+/// Each one is tried on a clone of the state through the same checks as the action bar, and
+/// the tutorial filters them like it filters typed actions.
+pub fn list(state: &GameState, civ_index: usize) -> Vec<ValidAction> {
+    let Some(civ) = state.civ(civ_index).ok().filter(|_| !state.game_over) else {
+        return Vec::new();
+    };
+    let mut actions = Vec::new();
+    if end_turn_blockers(state, civ_index).is_empty() {
+        actions.push(ValidAction::new("end", ""));
+    }
+    for b in &state.buildings {
+        if accepted(state, |s| s.start_construction(civ_index, &b.name)) {
+            let cost = civ.modifiers.building_cost(&b.name, b.cost);
+            actions.push(ValidAction::new(
+                format!("build {}", b.name),
+                format!("{cost} ressources"),
+            ));
+        }
+    }
+    actions.extend(queue_actions(state, civ_index));
+    actions.extend(cancel_actions(state, civ_index));
+    for u in &state.units {
+        if accepted(state, |s| s.start_recruitment(civ_index, &u.name)) {
+            let cost = civ
+                .producers
+                .get(&u.name)
+                .and_then(|&i| state.buildings.get(i))
+                .map_or(0, |b| b.production.cost);
+            actions.push(ValidAction::new(
+                format!("hire {}", u.name),
+                format!("{cost} ressources"),
+            ));
+        }
+    }
+    for t in tech::available(state, civ_index) {
+        if accepted(state, |s| tech::start_research(s, civ_index, &t.name)) {
+            actions.push(ValidAction::new(
+                format!("research {}", t.name),
+                format!("{} ressources", t.cost),
+            ));
+        }
+    }
+    let mut owned: Vec<&str> = civ
+        .city
+        .buildings
+        .elements
+        .iter()
+        .map(|b| b.id_building.as_str())
+        .collect();
+    owned.sort_unstable();
+    owned.dedup();
+    for name in owned {
+        if accepted(state, |s| s.demolish_building(civ_index, name)) {
+            let refund = state.demolish_refund(civ_index, name);
+            actions.push(ValidAction::new(
+                format!("demolish {name}"),
+                format!("+{refund} ressources"),
+            ));
+        }
+    }
+    actions.extend(rival_actions(state, civ_index));
+    let loan = max_loan(state, civ_index);
+    if loan > 0 {
+        actions.push(ValidAction::new(
+            "loan <montant>",
+            format!("jusqu'à {loan}, +{INTEREST_PERCENT}% d'intérêts"),
+        ));
+    }
+    for syntax in [
+        "policy tax <0-100>",
+        "policy military <0-100>",
+        "policy sortie <on|off>",
+        "diplomacy",
+        "weather",
+        "income",
+        "save <nom>",
+    ] {
+        actions.push(ValidAction::new(syntax, ""));
+    }
+    actions.push(ValidAction::new(
+        format!("rename {} <nom>", civ.city.name),
+        "",
+    ));
+
+    match &state.tutorial {
+        Some(tutorial) => actions
+            .into_iter()
+            .filter(|a| tutorial.accepts_syntax(state, &a.syntax))
+            .collect(),
+        None => actions,
+    }
+}
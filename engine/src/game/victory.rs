@@ -0,0 +1,288 @@
+use crate::ast::{PlayerType, Puzzle, PuzzleObjective};
+use crate::game::capture;
+use crate::game::diplomacy;
+use crate::game::scoring;
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// End of game as decided by the victory engine.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// Winning civilization, if any
+    pub winner: Option<usize>,
+    pub message: String,
+}
+
+/// End condition defined by a program embedding the engine, next to those of the DSL. It is
+/// checked each time the game looks for its end, after the built-in conditions.
+pub trait VictoryCondition: Send + Sync {
+    /// Outcome of the game if the condition is met.
+    fn evaluate(&self, state: &GameState) -> Option<Outcome>;
+}
+
+impl<F> VictoryCondition for F
+where
+    F: Fn(&GameState) -> Option<Outcome> + Send + Sync,
+{
+    fn evaluate(&self, state: &GameState) -> Option<Outcome> {
+        self(state)
+    }
+}
+
+/// Custom conditions registered on a game. They are shared, so snapshots of the state (debugger,
+/// simulations) keep checking them.
+#[derive(Clone, Default)]
+pub struct CustomConditions(Vec<Arc<dyn VictoryCondition>>);
+
+impl CustomConditions {
+    pub fn push(&mut self, condition: Arc<dyn VictoryCondition>) {
+        self.0.push(condition);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl std::fmt::Debug for CustomConditions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} custom victory conditions", self.0.len())
+    }
+}
+
+/// Runtime state of a puzzle scenario: the rules from the `[puzzle]` section plus the
+/// budget already consumed by the puzzle player.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PuzzleState {
+    pub rules: Puzzle,
+    /// Civilization solving the puzzle (first human player)
+    pub player: usize,
+    /// Civilization to eliminate for `ELIMINATE` objectives
+    pub target: Option<usize>,
+    /// Turn at which the puzzle started
+    pub start_turn: i32,
+    pub actions_used: u32,
+    pub builds_used: u32,
+}
+
+impl PuzzleState {
+    /// Resolve the puzzle rules against the loaded civilizations.
+    ///
+    /// # Errors
+    /// Fails when there is no PLAYER city, the target is not a city, or an ELIMINATE goal has no
+    /// target.
+    pub fn new(rules: Puzzle, state: &GameState) -> Result<Self> {
+        let player = state
+            .civilizations
+            .iter()
+            .position(|c| matches!(c.city.player_type, PlayerType::PLAYER))
+            .ok_or_else(|| anyhow!("puzzle scenario requires a PLAYER city"))?;
+        let target = match &rules.target {
+            Some(name) => Some(
+                state
+                    .civilizations
+                    .iter()
+                    .position(|c| &c.city.name == name)
+                    .ok_or_else(|| anyhow!("puzzle target `{name}` is not a city"))?,
+            ),
+            None => None,
+        };
+        if matches!(rules.objective, PuzzleObjective::ELIMINATE) && target.is_none() {
+            return Err(anyhow!("ELIMINATE puzzle requires a target"));
+        }
+        Ok(Self {
+            rules,
+            player,
+            target,
+            start_turn: state.turn,
+            actions_used: 0,
+            builds_used: 0,
+        })
+    }
+
+    /// Check that the puzzle player may still perform an action (a build when `is_build`).
+    ///
+    /// # Errors
+    /// Fails when the action or build budget is spent.
+    pub fn check_budget(&self, civ_index: usize, is_build: bool) -> Result<()> {
+        if civ_index != self.player {
+            return Ok(());
+        }
+        if let Some(max) = self.rules.max_actions
+            && self.actions_used >= max
+        {
+            return Err(anyhow!("Action budget exhausted ({max} actions)"));
+        }
+        if is_build
+            && let Some(max) = self.rules.max_builds
+            && self.builds_used >= max
+        {
+            return Err(anyhow!("Build budget exhausted ({max} builds)"));
+        }
+        Ok(())
+    }
+
+    /// Count a successful action of the puzzle player against the budget.
+    pub fn consume(&mut self, civ_index: usize, is_build: bool) {
+        if civ_index != self.player {
+            return;
+        }
+        self.actions_used += 1;
+        if is_build {
+            self.builds_used += 1;
+        }
+    }
+
+    /// Short description of the objective and the remaining budget for the info panel.
+    pub fn summary(&self, state: &GameState) -> String {
+        let objective = match (&self.rules.objective, self.target) {
+            (PuzzleObjective::ELIMINATE, Some(t)) => {
+                format!("Éliminer {}", state.city_name(t))
+            }
+            _ => "Survivre".to_string(),
+        };
+        let mut parts = vec![objective];
+        if let Some(max) = self.rules.max_turns {
+            parts.push(format!("tours {}/{max}", state.turn - self.start_turn));
+        }
+        if let Some(max) = self.rules.max_actions {
+            parts.push(format!("actions {}/{max}", self.actions_used));
+        }
+        if let Some(max) = self.rules.max_builds {
+            parts.push(format!("constructions {}/{max}", self.builds_used));
+        }
+        parts.join(", ")
+    }
+
+    fn evaluate(&self, state: &GameState) -> Option<Outcome> {
+        let player_name = state.city_name(self.player);
+        if !state.civ(self.player).is_ok_and(|c| c.alive) {
+            return Some(Outcome {
+                winner: None,
+                message: format!("Puzzle failed: {player_name} was eliminated"),
+            });
+        }
+        if let Some(target) = self.target
+            && !state.civ(target).is_ok_and(|c| c.alive)
+        {
+            return Some(Outcome {
+                winner: Some(self.player),
+                message: format!(
+                    "Puzzle solved: {player_name} eliminated {}",
+                    state.city_name(target)
+                ),
+            });
+        }
+        if let Some(max) = self.rules.max_turns
+            && state.turn - self.start_turn >= max.cast_signed()
+        {
+            return Some(match self.rules.objective {
+                PuzzleObjective::SURVIVE => Outcome {
+                    winner: Some(self.player),
+                    message: format!("Puzzle solved: {player_name} survived {max} turns"),
+                },
+                PuzzleObjective::ELIMINATE => Outcome {
+                    winner: None,
+                    message: format!("Puzzle failed: objective not reached within {max} turns"),
+                },
+            });
+        }
+        None
+    }
+}
+
+/// End of the game by the limits of `[victory_conditions]`: once turn `nb_turns` is over, or once
+/// a civilization has spent `resources_spent` resources in all. The surviving civilization with
+/// the best score (as without its survival and victory bonuses) wins, a tie for the best score is
+/// a draw. A limit of 0 is no limit.
+fn limits(state: &GameState) -> Option<Outcome> {
+    let spender = state
+        .civilizations
+        .iter()
+        .filter(|c| state.resources_spent > 0 && c.resources.spent >= state.resources_spent)
+        .max_by_key(|c| c.resources.spent);
+    let reason = if state.nb_turns > 0 && state.turn > state.nb_turns.cast_signed() {
+        format!("Turn limit reached ({} turns)", state.nb_turns)
+    } else if let Some(civ) = spender {
+        format!("{} spent {} resources", civ.city.name, civ.resources.spent)
+    } else {
+        return None;
+    };
+    let mut scores: Vec<(i32, usize)> = (0..state.civilizations.len())
+        .filter(|i| state.civ(*i).is_ok_and(|c| c.alive))
+        .map(|i| (scoring::base_score(state, i), i))
+        .collect();
+    scores.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    let best = scores.first().map(|(score, _)| *score);
+    let tied: Vec<&str> = scores
+        .iter()
+        .filter(|(score, _)| Some(*score) == best)
+        .map(|(_, i)| state.city_name(*i))
+        .collect();
+    Some(match (tied.as_slice(), best) {
+        ([name], Some(score)) => Outcome {
+            winner: scores.first().map(|(_, i)| *i),
+            message: format!("{reason}: {name} wins with {score} points"),
+        },
+        _ => Outcome {
+            winner: None,
+            message: format!("{reason}: draw between {}", tied.join(", ")),
+        },
+    })
+}
+
+/// Decide whether the game is over: puzzle objectives first, then domination, by holding every
+/// city when they can be captured or by being the last civilization standing, then diplomatic
+/// victory, then the turn and spending limits, then the custom conditions in registration order.
+pub fn evaluate(state: &GameState) -> Option<Outcome> {
+    if let Some(outcome) = state.puzzle.as_ref().and_then(|p| p.evaluate(state)) {
+        return Some(outcome);
+    }
+    if let Some(i) = capture::dominator(state).filter(|_| state.capture) {
+        return Some(Outcome {
+            winner: Some(i),
+            message: format!(
+                "Domination: {} holds all {} cities",
+                state.city_name(i),
+                capture::held(state, i)
+            ),
+        });
+    }
+
+    let mut alive = state
+        .civilizations
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.alive);
+    let outcome = match (alive.next(), alive.next()) {
+        (Some((i, c)), None) => Some(Outcome {
+            winner: Some(i),
+            message: format!("Winner: {}", c.city.name),
+        }),
+        (None, _) => Some(Outcome {
+            winner: None,
+            message: "No winners".to_string(),
+        }),
+        _ => diplomacy::winner(state).map(|i| Outcome {
+            winner: Some(i),
+            message: format!(
+                "Diplomatic victory: {} holds {} favor with every surviving civilization",
+                state.city_name(i),
+                state.favor_threshold
+            ),
+        }),
+    };
+    outcome.or_else(|| limits(state)).or_else(|| {
+        state
+            .custom_victories
+            .0
+            .iter()
+            .find_map(|c| c.evaluate(state))
+    })
+}
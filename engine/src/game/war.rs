@@ -0,0 +1,337 @@
+use crate::ast::{UnitInstance, War as WarTerms};
+use crate::game::composition;
+use crate::game::diplomacy;
+use crate::game::events::GameEvent;
+use crate::game::stacks;
+use crate::game::state::GameState;
+use anyhow::{Result, anyhow};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+/// Share of its resources a civilization pays as war reparations, unless set in `[war]`
+pub const DEFAULT_REPARATIONS_PERCENT: u32 = 20;
+
+/// Spoils of war, and how far the event log has been settled.
+#[derive(Debug, Clone)]
+pub struct War {
+    /// Share of the resources and building slots an eliminated civilization leaves to its
+    /// victors, in percent
+    pub elimination_share: u32,
+    /// Share of its resources the payer of reparations gives, in percent
+    pub reparations: u32,
+    /// Events of the log already settled
+    settled: usize,
+}
+
+impl Default for War {
+    fn default() -> Self {
+        Self {
+            elimination_share: 0,
+            reparations: DEFAULT_REPARATIONS_PERCENT,
+            settled: 0,
+        }
+    }
+}
+
+impl War {
+    pub fn from_terms(terms: &WarTerms) -> Self {
+        Self {
+            elimination_share: terms.elimination_share.min(100),
+            reparations: terms
+                .reparations
+                .unwrap_or(DEFAULT_REPARATIONS_PERCENT)
+                .min(100),
+            settled: 0,
+        }
+    }
+}
+
+/// Terms of a peace treaty, chosen by the civilization asking for peace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Terms {
+    /// Armies go home, nobody pays
+    WhitePeace,
+    /// The proposer pays reparations to the partner
+    PayReparations,
+    /// The partner pays reparations to the proposer, accepted only by a weaker partner
+    DemandReparations,
+}
+
+impl Terms {
+    const ALL: [Self; 3] = [
+        Self::WhitePeace,
+        Self::PayReparations,
+        Self::DemandReparations,
+    ];
+
+    /// Popup choice offering these terms to `partner`.
+    fn label(self, partner: &str) -> String {
+        match self {
+            Self::WhitePeace => format!("White peace with {partner}"),
+            Self::PayReparations => format!("Pay reparations to {partner}"),
+            Self::DemandReparations => format!("Demand reparations from {partner}"),
+        }
+    }
+}
+
+/// Whether `a` and `b` are at war: one of them declared war on the other, or an army of one of
+/// them is marching on the other.
+pub fn at_war(state: &GameState, a: usize, b: usize) -> bool {
+    state.diplomacy.declared_war(a, b)
+        || state
+            .travels
+            .iter()
+            .any(|t| (t.attacker, t.defender) == (a, b) || (t.attacker, t.defender) == (b, a))
+}
+
+/// Prompt and choices of the peace popup of `civ_index`: the civilizations it is at war with, or
+/// the terms it may offer to `partner`.
+pub fn peace_popup(
+    state: &GameState,
+    civ_index: usize,
+    partner: Option<usize>,
+) -> (String, Vec<String>) {
+    let Some(partner) = partner else {
+        let enemies: Vec<String> = state
+            .civilizations
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| c.alive && at_war(state, civ_index, *i))
+            .map(|(_, c)| c.city.name.clone())
+            .collect();
+        if enemies.is_empty() {
+            return ("You are at war with nobody".to_string(), enemies);
+        }
+        return (
+            "Choose the civilization to make peace with:".to_string(),
+            enemies,
+        );
+    };
+    let name = state.city_name(partner);
+    let prompt = format!(
+        "Peace with {name}: armies on both sides go home. Reparations are {}% of the payer's resources.",
+        state.war.reparations
+    );
+    (prompt, Terms::ALL.iter().map(|t| t.label(name)).collect())
+}
+
+/// Offer the peace described by the popup `choice` of `proposer`.
+///
+/// # Errors
+/// Fails when the choice is not a peace offer or the peace cannot be offered.
+pub fn propose(state: &mut GameState, proposer: usize, choice: &str) -> Result<()> {
+    let (partner, terms) = state
+        .civilizations
+        .iter()
+        .enumerate()
+        .find_map(|(i, c)| {
+            Terms::ALL
+                .into_iter()
+                .find(|t| t.label(&c.city.name) == choice)
+                .map(|t| (i, t))
+        })
+        .ok_or_else(|| anyhow!("Unknown peace terms: {choice}"))?;
+    make_peace(state, proposer, partner, terms)
+}
+
+/// Offer peace on `terms` to `partner`, who accepts or refuses it.
+///
+/// # Errors
+/// Fails when `partner` cannot be negotiated with, the two are not at war, or `partner` cannot be
+/// asked for reparations.
+pub fn make_peace(
+    state: &mut GameState,
+    proposer: usize,
+    partner: usize,
+    terms: Terms,
+) -> Result<()> {
+    diplomacy::check_partners(state, proposer, partner)?;
+    check_peace(state, proposer, partner, terms)?;
+    diplomacy::propose(state, proposer, partner, diplomacy::Deal::Peace(terms))
+}
+
+/// Check that `partner` may accept peace on `terms` from `proposer`.
+fn check_peace(state: &GameState, proposer: usize, partner: usize, terms: Terms) -> Result<()> {
+    let name = state.city_name(partner);
+    if !at_war(state, proposer, partner) {
+        return Err(anyhow!("You are not at war with {name}"));
+    }
+    if terms == Terms::DemandReparations
+        && diplomacy::defense_power(state, proposer) <= diplomacy::defense_power(state, partner)
+    {
+        return Err(anyhow!(
+            "{name} refuses to pay reparations to a weaker civilization"
+        ));
+    }
+    Ok(())
+}
+
+/// End the war between `proposer` and `partner` once the partner accepted the peace: their
+/// armies marching on each other go home, and the reparations agreed are paid when the event log
+/// is settled.
+pub(crate) fn sign_peace(
+    state: &mut GameState,
+    proposer: usize,
+    partner: usize,
+    terms: Terms,
+) -> Result<()> {
+    check_peace(state, proposer, partner, terms)?;
+    end_war(state, proposer, partner);
+    let payer = match terms {
+        Terms::WhitePeace => None,
+        Terms::PayReparations => Some((proposer, partner)),
+        Terms::DemandReparations => Some((partner, proposer)),
+    };
+    info!("Peace between civ {proposer} and civ {partner}, reparations {payer:?}");
+    state.events.publish(
+        state.turn,
+        GameEvent::Peace {
+            proposer,
+            partner,
+            reparations: payer,
+        },
+    );
+    settle(state);
+    Ok(())
+}
+
+/// Send home the armies `a` and `b` march on each other with, and forget the war either declared.
+pub(crate) fn end_war(state: &mut GameState, a: usize, b: usize) {
+    let (recalled, marching): (Vec<_>, Vec<_>) = std::mem::take(&mut state.travels)
+        .into_iter()
+        .partition(|t| (t.attacker, t.defender) == (a, b) || (t.attacker, t.defender) == (b, a));
+    state.travels = marching;
+    for travel in recalled {
+        composition::disband(state, travel.attacker, &travel);
+    }
+    state.diplomacy.end_war(a, b);
+}
+
+/// Put `amount` units of a recalled army back in the city of `civ_index`.
+pub(crate) fn return_home(state: &mut GameState, civ_index: usize, amount: u32) {
+    let Ok(civ) = state.civ(civ_index) else {
+        return;
+    };
+    let garrison = civ.city.units.units.first().map(|u| u.id_units.clone());
+    if let Some(id_units) = garrison.or_else(|| state.units.first().map(|u| u.name.clone())) {
+        stacks::station(
+            state,
+            civ_index,
+            vec![UnitInstance {
+                id_units,
+                nb_units: amount,
+            }],
+        );
+    }
+}
+
+/// Publish the elimination of `civ`, remembering its turn for the scores.
+pub fn eliminate(state: &mut GameState, civ: usize) {
+    let turn = state.turn;
+    if let Ok(eliminated) = state.civ_mut(civ) {
+        eliminated.eliminated_at = Some(turn);
+    }
+    state.events.publish(turn, GameEvent::Eliminated { civ });
+}
+
+/// Apply the spoils of the events published since the last call: eliminations leave their share
+/// to the victors, and peace treaties get their reparations paid.
+pub fn settle(state: &mut GameState) {
+    let events: Vec<GameEvent> = state
+        .events
+        .since(state.war.settled)
+        .map(|(_, e)| e.clone())
+        .collect();
+    state.war.settled += events.len();
+    for event in events {
+        match event {
+            GameEvent::Eliminated { civ } => share_spoils(state, civ),
+            GameEvent::Peace {
+                reparations: Some((from, to)),
+                ..
+            } => {
+                let share = state.war.reparations;
+                transfer(state, from, &[to], share, 0);
+            }
+            _ => {}
+        }
+    }
+    // the transfers published their own events, which need no settling
+    state.war.settled = state.events.len();
+}
+
+/// Share the remains of the eliminated `civ` between the civilizations that beat it in battle
+/// and those whose armies were still marching on it.
+fn share_spoils(state: &mut GameState, civ: usize) {
+    let share = state.war.elimination_share;
+    if share == 0 {
+        return;
+    }
+    let beaten_by = state
+        .civ(civ)
+        .map(|c| c.beaten_by.clone())
+        .unwrap_or_default();
+    let mut victors: Vec<usize> = beaten_by
+        .into_iter()
+        .chain(
+            state
+                .travels
+                .iter()
+                .filter(|t| t.defender == civ)
+                .map(|t| t.attacker),
+        )
+        .filter(|v| *v != civ && state.civ(*v).is_ok_and(|c| c.alive))
+        .collect();
+    victors.sort_unstable();
+    victors.dedup();
+    if !victors.is_empty() {
+        transfer(state, civ, &victors, share, share);
+    }
+}
+
+/// Move `resource_share` percent of the resources and `slot_share` percent of the building slots
+/// of `from` to `to`, split evenly.
+fn transfer(
+    state: &mut GameState,
+    from: usize,
+    to: &[usize],
+    resource_share: u32,
+    slot_share: u32,
+) {
+    let Ok(giver) = state.civ_mut(from) else {
+        return;
+    };
+    // the shares may exceed 100 percent in the DSL, no more than everything is given
+    let share = |amount: u32, share: u32| {
+        u32::try_from((u64::from(amount) * u64::from(share) / 100).min(u64::from(amount)))
+            .unwrap_or(amount)
+    };
+    let resources = share(
+        giver.resources.ressources.max(0).cast_unsigned(),
+        resource_share,
+    )
+    .cast_signed();
+    let slots = share(giver.city.nb_slots_buildings, slot_share);
+    giver.earn(-resources);
+    giver.city.nb_slots_buildings -= slots;
+    let count = u32::try_from(to.len()).unwrap_or(1).max(1);
+    for &receiver in to {
+        let resources = resources / count.cast_signed();
+        let slots = slots / count;
+        let Ok(civ) = state.civ_mut(receiver) else {
+            continue;
+        };
+        civ.earn(resources);
+        civ.city.nb_slots_buildings = civ.city.nb_slots_buildings.saturating_add(slots);
+        info!("Civ {receiver} receives {resources} resources and {slots} slots from civ {from}");
+        state.events.publish(
+            state.turn,
+            GameEvent::Reparations {
+                from,
+                to: receiver,
+                resources,
+                slots,
+            },
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::game::map::{GameMap, Terrain};
+use crate::game::state::{GameState, Travel};
+use crate::game::utils::hash_tmb;
+use noise::{NoiseFn, Perlin};
+
+/// Size of weather fronts, smaller is larger
+const WEATHER_SCALE: f64 = 0.08;
+/// How far weather fronts move from one turn to the next
+const WEATHER_DRIFT: f64 = 0.35;
+/// Noise level above which a tile is hit by bad weather
+const WEATHER_THRESHOLD: f64 = 0.3;
+/// Number of turns covered by the `weather` forecast, the current one included
+pub const FORECAST_TURNS: i32 = 4;
+
+/// Transient weather slowing down armies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weather {
+    /// Storm over water
+    Storm,
+    /// Sandstorm in the desert
+    Sandstorm,
+}
+
+impl Weather {
+    pub fn name(self) -> &'static str {
+        match self {
+            Weather::Storm => "storm",
+            Weather::Sandstorm => "sandstorm",
+        }
+    }
+}
+
+/// Season of the cycle set by `season_length` in `[game]`, changing what cities produce and how
+/// fast armies march depending on the terrain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    const CYCLE: [Season; 4] = [
+        Season::Spring,
+        Season::Summer,
+        Season::Autumn,
+        Season::Winter,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        }
+    }
+
+    /// Share of their usual output the buildings of a city standing on `terrain` produce, in
+    /// percent: summer ripens the plains and parches the desert, winter freezes the plains and
+    /// the mountains.
+    pub fn production_percent(self, terrain: Terrain) -> i32 {
+        match (self, terrain) {
+            (Season::Summer, Terrain::Plains) => 125,
+            (Season::Summer, Terrain::Desert)
+            | (Season::Winter, Terrain::Plains | Terrain::Mountain) => 50,
+            _ => 100,
+        }
+    }
+
+    /// Movement points an army spends stepping onto `terrain`, in percent of the usual cost:
+    /// summer heat slows it in the desert, autumn mud and winter snow on the plains, autumn and
+    /// winter seas on water.
+    pub fn travel_percent(self, terrain: Terrain) -> i64 {
+        match (self, terrain) {
+            (Season::Summer, Terrain::Desert)
+            | (Season::Winter, Terrain::Plains | Terrain::Water) => 150,
+            (Season::Autumn, Terrain::Plains | Terrain::Water) => 125,
+            _ => 100,
+        }
+    }
+}
+
+/// Name of a season in the interface.
+pub fn season_name(season: Season) -> &'static str {
+    match season {
+        Season::Spring => "printemps",
+        Season::Summer => "été",
+        Season::Autumn => "automne",
+        Season::Winter => "hiver",
+    }
+}
+
+/// Season of `turn`, None when the game has no seasons. The first turn opens a spring.
+pub fn season(state: &GameState, turn: i32) -> Option<Season> {
+    let length = i64::from(state.season_length?);
+    let index = (i64::from(turn) - 1).div_euclid(length).rem_euclid(4);
+    Season::CYCLE.get(usize::try_from(index).ok()?).copied()
+}
+
+/// Turns left in the season of the current turn, the current one included.
+pub fn season_turns_left(state: &GameState) -> Option<u32> {
+    let length = i64::from(state.season_length?);
+    let left = length - (i64::from(state.turn) - 1).rem_euclid(length);
+    u32::try_from(left).ok()
+}
+
+/// Weather of a map for a given turn, derived from the map seed so every player sees the same sky.
+pub struct WeatherMap<'a> {
+    map: &'a GameMap,
+    noise: Perlin,
+    turn: i32,
+}
+
+impl<'a> WeatherMap<'a> {
+    pub fn new(map: &'a GameMap, turn: i32) -> Self {
+        Self {
+            map,
+            noise: Perlin::new(hash_tmb(&format!("{}-weather", map.seed))),
+            turn,
+        }
+    }
+
+    /// Weather on the tile at `(x, y)`, if any.
+    pub fn at(&self, x: i32, y: i32) -> Option<Weather> {
+        let terrain = self
+            .map
+            .tiles
+            .get(usize::try_from(y).ok()?)?
+            .get(usize::try_from(x).ok()?)?;
+        let level = self.noise.get([
+            f64::from(x) * WEATHER_SCALE,
+            f64::from(y) * WEATHER_SCALE,
+            f64::from(self.turn) * WEATHER_DRIFT,
+        ]);
+        if level < WEATHER_THRESHOLD {
+            return None;
+        }
+        match terrain {
+            Terrain::Water => Some(Weather::Storm),
+            Terrain::Desert => Some(Weather::Sandstorm),
+            _ => None,
+        }
+    }
+
+    /// Number of tiles hit by storms and by sandstorms.
+    pub fn coverage(&self) -> (usize, usize) {
+        let mut storms = 0;
+        let mut sandstorms = 0;
+        let height = i32::try_from(self.map.height).unwrap_or(i32::MAX);
+        let width = i32::try_from(self.map.width).unwrap_or(i32::MAX);
+        for y in 0..height {
+            for x in 0..width {
+                match self.at(x, y) {
+                    Some(Weather::Storm) => storms += 1,
+                    Some(Weather::Sandstorm) => sandstorms += 1,
+                    None => {}
+                }
+            }
+        }
+        (storms, sandstorms)
+    }
+}
+
+/// Weather holding back an army on its current tile this turn, if any.
+pub fn delaying(state: &GameState, travel: &Travel) -> Option<Weather> {
+    let (x, y) = travel.tile();
+    WeatherMap::new(&state.map, state.turn).at(x, y)
+}
+
+/// Forecast for the next turns: weather coverage and the armies of `civ_index` it would hold back.
+pub fn forecast(state: &GameState, civ_index: usize) -> String {
+    let mut lines = Vec::new();
+    if let (Some(season), Some(left)) = (season(state, state.turn), season_turns_left(state)) {
+        lines.push(format!("Season: {} for {left} more turn(s)", season.name()));
+    }
+    for turn in state.turn..state.turn + FORECAST_TURNS {
+        let weather = WeatherMap::new(&state.map, turn);
+        let (storms, sandstorms) = weather.coverage();
+        let mut line =
+            format!("Turn {turn}: storms over {storms} tiles, sandstorms over {sandstorms} tiles");
+        for travel in state.travels.iter().filter(|t| t.attacker == civ_index) {
+            let (x, y) = travel.tile();
+            if let Some(w) = weather.at(x, y) {
+                line = format!(
+                    "{line}\n  your army marching on {} is held back by a {} on its tile",
+                    state.city_name(travel.defender),
+                    w.name()
+                );
+            }
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
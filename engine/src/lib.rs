@@ -0,0 +1,8 @@
+// Engine library, shared by the game binary and the fuzz targets. The pedantic lint asking for
+// `#[must_use]` on every public item is meant for published crates, not this one.
+#![allow(clippy::must_use_candidate)]
+
+pub mod ast;
+pub mod game;
+pub mod logger;
+pub mod prelude;
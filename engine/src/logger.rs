@@ -19,8 +19,11 @@ fn level_from_env() -> LevelFilter {
     }
 }
 
-/// Initialize thread-safe singleton logger using OnceLock for lock-free concurrent calls.
-/// Truncates log file on startup for clean sessions. Environment-aware via LOG_LEVEL variable.
+/// Initialize thread-safe singleton logger using `OnceLock` for lock-free concurrent calls.
+/// Truncates log file on startup for clean sessions. Environment-aware via `LOG_LEVEL` variable.
+///
+/// # Errors
+/// Fails when the log directory or file cannot be created, or the logger cannot be set up.
 pub fn init<P: AsRef<Path>>(log_file: P) -> Result<()> {
     if LOGGER_INITIALIZED.get().is_some() {
         return Ok(());
@@ -29,7 +32,7 @@ pub fn init<P: AsRef<Path>>(log_file: P) -> Result<()> {
     let path = log_file.as_ref();
     if let Some(parent) = path.parent() {
         create_dir_all(parent)
-            .with_context(|| format!("failed to create log directory {parent:?}"))?;
+            .with_context(|| format!("failed to create log directory {}", parent.display()))?;
     }
 
     let file = OpenOptions::new()
@@ -37,7 +40,7 @@ pub fn init<P: AsRef<Path>>(log_file: P) -> Result<()> {
         .write(true)
         .truncate(true)
         .open(path)
-        .with_context(|| format!("failed to open log file {path:?}"))?;
+        .with_context(|| format!("failed to open log file {}", path.display()))?;
 
     let config = ConfigBuilder::new().build();
 
@@ -46,6 +49,9 @@ pub fn init<P: AsRef<Path>>(log_file: P) -> Result<()> {
     WriteLogger::init(level, config, file).context("failed to initialize file logger")?;
 
     LOGGER_INITIALIZED.set(()).ok();
-    info!("Logger initialized (level={level:?}), logging to {path:?}");
+    info!(
+        "Logger initialized (level={level:?}), logging to {}",
+        path.display()
+    );
     Ok(())
 }
@@ -1,23 +1,31 @@
-mod ast;
-mod game;
-mod logger;
+use clivilization_engine::{ast, game, logger};
 
-use crate::game::ui::{cleanup_term, draw_color_test_256, draw_color_test_rgb};
 use anyhow::Context;
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use clivilization_engine::game::campaign::Campaign;
+use clivilization_engine::game::daily::DailyChallenge;
+#[cfg(feature = "tui")]
+use clivilization_engine::game::ui::{
+    cleanup_term, draw_campaign_menu, draw_color_test_256, draw_color_test_rgb,
+};
+#[cfg(feature = "tui")]
 use crossterm::{
     event::{self, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{EnterAlternateScreen, enable_raw_mode},
 };
+#[cfg(feature = "tui")]
 use ratatui::{backend::CrosstermBackend, prelude::*};
 use std::io;
+
+/// Turns played by `--autoplay` before giving up on a game the victory conditions never end
+const MAX_AUTOPLAY_TURNS: u32 = 1000;
 use log::warn;
-use crate::game::ai::LlmAi;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[allow(clippy::struct_excessive_bools)]
 struct Args {
     /// Run a color test screen instead of the game
     #[arg(long, value_parser = ["256", "rgb"])]
@@ -27,6 +35,10 @@ struct Args {
     #[arg(long)]
     config: Option<String>,
 
+    /// Resume a game written by the `save <name>` action, given by name or path
+    #[arg(long, value_name = "SAVE", conflicts_with_all = ["config", "campaign", "daily"])]
+    load_save: Option<String>,
+
     /// Dump config blob
     #[arg(long)]
     blob: bool,
@@ -34,10 +46,119 @@ struct Args {
     /// Run in headless mode, for automated testing or AI play
     #[arg(long)]
     headless: bool,
+
+    /// Play a campaign described by a JSON manifest chaining several scenarios
+    #[arg(long)]
+    campaign: Option<String>,
+
+    /// Play today's daily challenge: seed and turn limit derived from the date, reseeding disabled
+    #[arg(long, conflicts_with = "campaign")]
+    daily: bool,
+
+    /// Show a textual description of the map and armies next to the map (screen-reader friendly)
+    #[arg(long)]
+    text_mode: bool,
+
+    /// Record a replay of the game in `replays/` of the output directory
+    #[arg(long)]
+    record: bool,
+
+    /// Scale the production of AIs to their power gap with the human player, recalculated
+    /// every TURNS turns (single-player games)
+    #[arg(long, value_name = "TURNS")]
+    dynamic_difficulty: Option<u32>,
+
+    /// Preview mode for the DSL editor: read newline-delimited JSON configurations, or
+    /// `{"patch": ...}` merge patches, on stdin and hot-apply them to the running game
+    #[arg(long, conflicts_with = "headless")]
+    watch_stdin: bool,
+
+    /// Time-travel debugger for the headless mode: keep snapshots of the recent steps and
+    /// accept `debug steps|diff|jump|export` commands
+    #[arg(long, requires = "headless")]
+    debug: bool,
+
+    /// Headless mode without stdin: play the whole game through the programmatic API (human
+    /// players end their turns without acting) and print the final snapshot
+    #[arg(long, requires = "headless")]
+    autoplay: bool,
+
+    /// Play GAMES AI-vs-AI games back to back, each on its own seed derived from the map seed,
+    /// and print win rates, average game length and resources spent
+    #[arg(long, value_name = "GAMES", conflicts_with_all = ["campaign", "load_save"])]
+    simulate: Option<u32>,
+
+    /// Threads playing the games of `--simulate` side by side (all the cores by default)
+    #[arg(long, value_name = "THREADS", requires = "simulate")]
+    jobs: Option<usize>,
+
+    /// LLM requests in flight at once over every game of `--simulate`
+    #[arg(long, value_name = "REQUESTS", requires = "simulate")]
+    llm_concurrency: Option<usize>,
+
+    /// Output format of `--simulate`
+    #[arg(long, value_parser = ["json", "csv"], default_value = "json", requires = "simulate")]
+    stats_format: String,
+
+    /// Play the setup shared as a game code (map seed and size, AI speed), shown under the seed
+    /// in game, on the loaded configuration
+    #[arg(long, conflicts_with_all = ["load_save", "daily", "campaign"])]
+    code: Option<String>,
+
+    /// Play by mail: resume the game of the turn file FILE, or start a new one when there is
+    /// none, and write it back encrypted once the next human player has the turn. The players
+    /// share the passphrase in `PBEM_KEY`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["load_save", "campaign", "daily", "simulate", "code"])]
+    pbem: Option<String>,
+
+    /// Seed the random AIs from SEED (plus the civilization index) so AI games replay the same
+    /// way; cities may set their own `ai_seed` in the DSL
+    #[arg(long, value_name = "SEED")]
+    ai_seed: Option<u64>,
+
+    /// Keep every written file in `output/` of the working directory instead of the platform
+    /// data directory
+    #[arg(long)]
+    portable: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List past games and win rates per configuration from the game history
+    History {
+        /// Only show games of this configuration hash
+        #[arg(long)]
+        config_hash: Option<String>,
+
+        /// Number of recent games to list
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// List the saves found in the output directory
+    Saves,
+    /// Play a recorded replay back, printing a JSON snapshot after every human input
+    Replay { file: String },
+    /// Time the turn logic on the loaded configuration (`--config`, blob or default game)
+    Bench {
+        /// Number of rounds, each one a turn of every civilization
+        #[arg(long, default_value_t = 1000)]
+        rounds: u32,
+    },
+    /// Check a file written during a tournament game against its `.sig` signature
+    Verify {
+        file: String,
+
+        /// Tournament key, defaults to the `TOURNAMENT_KEY` environment variable
+        #[arg(long)]
+        key: Option<String>,
+    },
+}
+
+#[allow(clippy::too_many_lines)]
+fn main() -> Result<()> {
     // Load .env file (optional)
     if dotenvy::dotenv().is_err() {
         warn!("Warning: No .env file found. Environment variables must be set manually.");
@@ -45,8 +166,14 @@ async fn main() -> Result<()> {
         warn!("Optional: OPENAI_BASE_URL or OPENAI_API_BASE, AI_MODEL, LOG_LEVEL");
     }
 
+    // Use clap to parse a --test-color flag for testing color schemes
+    let matches = Args::parse();
+
+    // Every file goes to the platform data directory, or to output/ in portable mode
+    game::utils::init_output_dir(matches.portable);
+
     // Initialize file logger as early as possible so any startup errors are logged
-    logger::init("output/game.log").context("failed to initialize logger")?;
+    logger::init(game::utils::output_path("game.log")).context("failed to initialize logger")?;
 
     // Install a panic hook so unexpected panics are recorded to the log file
     std::panic::set_hook(Box::new(|panic_info| {
@@ -69,8 +196,34 @@ async fn main() -> Result<()> {
 
     // LLM-backed AI will be registered per-civ below
 
-    // Use clap to parse a --test-color flag for testing color schemes
-    let matches = Args::parse();
+    if let Some(Command::Verify { file, key }) = &matches.command {
+        let key = key
+            .clone()
+            .or_else(game::signing::tournament_key)
+            .context("no tournament key: pass --key or set TOURNAMENT_KEY")?;
+        game::signing::verify_file(std::path::Path::new(file), &key)?;
+        println!("{file}: signature valid");
+        return Ok(());
+    }
+    if let Some(Command::Replay { file }) = &matches.command {
+        let game = game::Game::play_replay(std::path::Path::new(file), |g| {
+            println!("{}", g.snapshot_value());
+        })?;
+        println!("{}", serde_json::to_string(&game.snapshot_value())?);
+        return Ok(());
+    }
+    if let Some(Command::Saves) = &matches.command {
+        println!("{}", game::saves::report(&game::saves::discover()));
+        return Ok(());
+    }
+    if let Some(Command::History { config_hash, limit }) = &matches.command {
+        let records = game::history::load();
+        println!(
+            "{}",
+            game::history::report(&records, config_hash.as_deref(), *limit)
+        );
+        return Ok(());
+    }
 
     // Get the config blob
     let blob = option_env!("CONFIG_BLOB");
@@ -85,11 +238,31 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Load campaign manifest and progress if provided
+    let mut campaign = matches
+        .campaign
+        .as_deref()
+        .map(Campaign::load)
+        .transpose()?;
+    let campaign_scenario = campaign.as_ref().map(Campaign::next_scenario);
+
     // Load config if provided
     log::info!("Loading game configuration");
-    let mut game = if let Some(config_path) = matches.config {
+    let pbem = matches
+        .pbem
+        .as_deref()
+        .map(|file| game::pbem::key().map(|key| (std::path::PathBuf::from(file), key)))
+        .transpose()?;
+    let mut game = if let Some((file, key)) = pbem.as_ref().filter(|(file, _)| file.is_file()) {
+        game::pbem::resume(file, key)?
+    } else if let Some(save) = &matches.load_save {
+        log::info!("Loading save {save}");
+        game::saves::load(save)?
+    } else if let (Some(campaign), Some(index)) = (&campaign, campaign_scenario) {
+        campaign.start_scenario(index)?
+    } else if let Some(config_path) = &matches.config {
         log::info!("Loading config from {config_path}");
-        game::Game::from_file(&config_path)?
+        game::Game::from_file(config_path)?
     } else if let Some(blob_str) = blob {
         log::info!("Loading config from embedded blob");
         game::Game::from_string(blob_str)?
@@ -97,128 +270,242 @@ async fn main() -> Result<()> {
         log::info!("Creating default game instance");
         game::Game::new()
     };
+    if let Some((file, key)) = pbem
+        && !file.is_file()
+    {
+        game::pbem::start(&mut game, file, &key)?;
+    }
 
-    // If headless, run a simple stdin-driven loop and avoid initializing terminal or crossterm
+    if let Some(Command::Bench { rounds }) = &matches.command {
+        println!("{}", game::bench::run(&mut game, *rounds));
+        return Ok(());
+    }
+
+    if let Some(games) = matches.simulate {
+        let ai_type = std::env::var("AI_TYPE").unwrap_or_else(|_| "llm".to_string());
+        let ai_model =
+            std::env::var("AI_MODEL").unwrap_or_else(|_| "openai/gpt-4o-mini".to_string());
+        #[cfg(feature = "llm")]
+        if let Some(limit) = matches.llm_concurrency {
+            game::ai::set_llm_concurrency(limit);
+        }
+        let threads = matches.jobs.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, std::num::NonZero::get)
+        });
+        let agendas: Vec<Option<String>> = game
+            .state()
+            .civilizations
+            .iter()
+            .map(|c| c.city.agenda.clone())
+            .collect();
+        let ai = |civ: usize, seed| -> Box<dyn game::Ai> {
+            if ai_type.eq_ignore_ascii_case("random") {
+                Box::new(game::RandomAi::seeded(seed))
+            } else {
+                llm_ai(&ai_model, agendas.get(civ).cloned().flatten())
+            }
+        };
+        let progress = |p: game::tournament::Progress| eprint!("\r{p}  ");
+        let stats = game::tournament::run(&game, games, threads, ai, progress)?;
+        eprintln!();
+        if matches.stats_format == "csv" {
+            print!("{}", stats.to_csv());
+        } else {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        }
+        return Ok(());
+    }
+
+    if matches.text_mode {
+        game.state_mut().toggle_text_mode();
+    }
+    if let Some(interval) = matches.dynamic_difficulty {
+        game.enable_dynamic_difficulty(interval);
+    }
+    if let Some(code) = &matches.code {
+        game::game_code::GameCode::decode(code)?.apply(game.state_mut())?;
+    }
+    if let Some(seed) = matches.ai_seed {
+        game.set_ai_seed(seed);
+    }
+
+    let mut daily = matches.daily.then(DailyChallenge::today);
+    if let Some(challenge) = &daily {
+        challenge.apply(game.state_mut());
+    }
+
+    // If headless, run a simple stdin-driven loop (or play the whole game with --autoplay) and
+    // avoid initializing terminal or crossterm
     if matches.headless {
         log::info!("Starting in headless mode");
-        
-        use std::io::{BufRead, BufReader};
 
-        let stdin = io::stdin();
-        let reader = BufReader::new(stdin);
+        register_ais(&mut game, "headless");
 
-        // Register AIs for headless mode
-        // First collect indices to avoid borrowing `game` immutably while mutating it
-        let ai_type = std::env::var("AI_TYPE").unwrap_or_else(|_| "llm".to_string());
-        let ai_model = std::env::var("AI_MODEL").unwrap_or_else(|_| "openai/gpt-4o-mini".to_string());
-        let mut ai_indices: Vec<usize> = Vec::new();
-        for (i, civ) in game.state().civilizations.iter().enumerate() {
-            if matches!(civ.city.player_type, ast::PlayerType::AI) {
-                ai_indices.push(i);
-            }
+        if matches.record {
+            game.start_recording()?;
         }
-        for i in ai_indices {
-            match ai_type.to_lowercase().as_str() {
-                "random" => {
-                    game.register_ai(i, Box::new(game::RandomAi::new()));
-                    log::info!("Registered RandomAi for civ {} (headless)", i);
-                }
-                "llm" | _ => {
-                    game.register_ai(i, Box::new(LlmAi::new(Box::leak(ai_model.clone().into_boxed_str()))));
-                    log::info!("Registered LlmAi for civ {} (headless) with model {}", i, ai_model);
-                }
-            }
+        if matches.debug {
+            game.enable_debugger();
         }
 
-        // Emit initial snapshot
-        let snap = game.snapshot_value();
-        println!("{}", serde_json::to_string(&snap)?);
+        if matches.autoplay {
+            game.play_to_end(MAX_AUTOPLAY_TURNS);
+            println!("{}", serde_json::to_string(&game.snapshot_value())?);
+        } else {
+            run_headless_commands(&mut game)?;
+        }
 
-        // If current player is AI, run it immediately
-        game.run_ai_for_current_player();
-        println!("{}", serde_json::to_string(&game.snapshot_value())?);
+        if let (Some(campaign), Some(index)) = (campaign.as_mut(), campaign_scenario) {
+            campaign.record_result(index, game.state())?;
+        }
+        if let Some(challenge) = daily.take().filter(|_| game.state().game_over) {
+            challenge.record(game.state())?;
+        }
+        if game.state().game_over {
+            game::history::record(game.state())?;
+            game::events::write_chronicle(game.state())?;
+            game::report::write_report(game.state())?;
+        }
+        game.finish_recording()?;
 
-        for line in reader.lines() {
-            let line = line?;
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
+        return Ok(());
+    }
+
+    run_tui(matches, game, campaign, campaign_scenario, daily)
+}
+
+/// Headless loop: print a JSON snapshot, then run the commands read from stdin (`apply`,
+/// `popup`, `step`, `snapshot`, `debug`, `quit`) and print a snapshot after each of them.
+fn run_headless_commands(game: &mut game::Game) -> Result<()> {
+    use std::io::{BufRead, BufReader};
+
+    let stdin = io::stdin();
+    let reader = BufReader::new(stdin);
+
+    // Emit initial snapshot
+    let snap = game.snapshot_value();
+    println!("{}", serde_json::to_string(&snap)?);
+
+    // If current player is AI, run it immediately
+    game.run_ai_turn();
+    println!("{}", serde_json::to_string(&game.snapshot_value())?);
+
+    for line in reader.lines() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let mut parts = trimmed.split_whitespace();
+        match parts.next().unwrap_or("") {
+            "quit" | "exit" => break,
+            "snapshot" => {
+                let snap = game.snapshot_value();
+                println!("{}", serde_json::to_string(&snap)?);
             }
-            let mut parts = trimmed.split_whitespace();
-            match parts.next().unwrap_or("") {
-                "quit" | "exit" => break,
-                "snapshot" => {
-                    let snap = game.snapshot_value();
-                    println!("{}", serde_json::to_string(&snap)?);
+            "debug" => {
+                let args: Vec<&str> = parts.collect();
+                match game::debugger::command(game, &args) {
+                    Ok(text) => println!("{}", serde_json::json!({ "debug": text })),
+                    Err(e) => println!("{}", serde_json::json!({ "error": format!("{e:#}") })),
                 }
-                "step" => {
-                    game.step();
-                    // after stepping, if new player is AI, run it
-                    game.run_ai_for_current_player();
-                    let snap = game.snapshot_value();
-                    println!("{}", serde_json::to_string(&snap)?);
+            }
+            "step" => {
+                game.pass_turn();
+                // after stepping, if new player is AI, run it
+                game.run_ai_turn();
+                let snap = game.snapshot_value();
+                println!("{}", serde_json::to_string(&snap)?);
+            }
+            "apply" => {
+                // apply rest of line as action
+                let action = parts.collect::<Vec<&str>>().join(" ");
+
+                // If current player is AI, refuse to apply human actions
+                let civ_idx = game.state().player_turn;
+                if let Some(civ) = game.state().civilizations.get(civ_idx)
+                    && matches!(civ.city.player_type, ast::PlayerType::AI)
+                {
+                    log::warn!("Headless apply refused: it's AI's turn for civ {civ_idx}");
+                    println!("{{\"error\":\"cannot apply action: it's AI's turn\"}}");
+                    continue;
                 }
-                "apply" => {
-                    // apply rest of line as action
-                    let action = parts.collect::<Vec<&str>>().join(" ");
-
-                    // If current player is AI, refuse to apply human actions
-                    let civ_idx = game.state().player_turn;
-                    if let Some(civ) = game.state().civilizations.get(civ_idx)
-                        && matches!(civ.city.player_type, ast::PlayerType::AI) {
-                            log::warn!("Headless apply refused: it's AI's turn for civ {civ_idx}");
-                            println!("{{\"error\":\"cannot apply action: it's AI's turn\"}}");
-                            continue;
-                        }
-
-                    log::info!("Applying action from stdin: {action}");
-                    let opened = game.apply_action(&action);
-                    if opened {
-                        // print snapshot with popup
-                        let mut v = game.snapshot_value();
-                        if let Some(p) = &game.state().popup {
-                            v["popup"] = serde_json::json!({"title": p.title, "prompt": p.prompt, "choices": p.choices});
-                        }
-                        println!("{}", serde_json::to_string(&v)?);
-                    } else {
-                        // action applied; if this caused the player to end and next is AI, run it
-                        game.run_ai_for_current_player();
-                        println!("{}", serde_json::to_string(&game.snapshot_value())?);
+
+                log::info!("Applying action from stdin: {action}");
+                let opened = game.apply_action(&action);
+                if opened {
+                    // print snapshot with popup
+                    let mut v = game.snapshot_value();
+                    if let Some(p) = &game.state().popup {
+                        v["popup"] = serde_json::json!({"title": p.title, "prompt": p.prompt, "choices": p.choices});
+                    }
+                    println!("{}", serde_json::to_string(&v)?);
+                } else {
+                    // action applied; if this caused the player to end and next is AI, run it
+                    game.run_ai_turn();
+                    // the AI turn may leave a popup for the human player (e.g. a conquest decision)
+                    let mut v = game.snapshot_value();
+                    if let Some(p) = &game.state().popup {
+                        v["popup"] = serde_json::json!({"title": p.title, "prompt": p.prompt, "choices": p.choices});
                     }
+                    println!("{}", serde_json::to_string(&v)?);
                 }
-                "popup" => {
-                    // submit popup input (rest of line)
-                    let input = parts.collect::<Vec<&str>>().join(" ");
-
-                    // If current player is AI, refuse to submit popup input
-                    let civ_idx = game.state().player_turn;
-                    if let Some(civ) = game.state().civilizations.get(civ_idx)
-                        && matches!(civ.city.player_type, ast::PlayerType::AI) {
-                            log::warn!("Headless popup submit refused: it's AI's turn for civ {civ_idx}");
-                            println!("{{\"error\":\"cannot submit popup: it's AI's turn\"}}");
-                            continue;
-                        }
-
-                    log::info!("Submitting popup input from stdin: {input}");
-                    let _processed = game.submit_popup_input(&input);
-                    // After popup submission, AI may have to act (e.g., popup closed)
-                    game.run_ai_for_current_player();
-                    let snap = game.snapshot_value();
-                    println!("{}", serde_json::to_string(&snap)?);
+            }
+            "popup" => {
+                // submit popup input (rest of line)
+                let input = parts.collect::<Vec<&str>>().join(" ");
+
+                // If current player is AI, refuse to submit popup input
+                let civ_idx = game.state().player_turn;
+                if let Some(civ) = game.state().civilizations.get(civ_idx)
+                    && matches!(civ.city.player_type, ast::PlayerType::AI)
+                {
+                    log::warn!("Headless popup submit refused: it's AI's turn for civ {civ_idx}");
+                    println!("{{\"error\":\"cannot submit popup: it's AI's turn\"}}");
+                    continue;
                 }
-                other => {
-                    // Unknown command -> respond with a helpful message and snapshot
-                    log::warn!("Unknown headless command: {other}");
-                    eprintln!("Unknown command: {trimmed}");
-                    let snap = game.snapshot_value();
-                    println!("{}", serde_json::to_string(&snap)?);
+
+                log::info!("Submitting popup input from stdin: {input}");
+                let _processed = game.submit_popup_input(&input);
+                // After popup submission, AI may have to act (e.g., popup closed)
+                game.run_ai_turn();
+                let mut v = game.snapshot_value();
+                if let Some(p) = &game.state().popup {
+                    v["popup"] = serde_json::json!({"title": p.title, "prompt": p.prompt, "choices": p.choices});
                 }
+                println!("{}", serde_json::to_string(&v)?);
+            }
+            other => {
+                // Unknown command -> respond with a helpful message and snapshot
+                log::warn!("Unknown headless command: {other}");
+                eprintln!("Unknown command: {trimmed}");
+                let snap = game.snapshot_value();
+                println!("{}", serde_json::to_string(&snap)?);
             }
         }
 
-        return Ok(());
+        // In play-by-mail mode, stop once the turn file is written for the next player
+        if let Some((path, next)) = game::pbem::hand_over(game)? {
+            println!(
+                "{}",
+                serde_json::json!({ "pbem": path.display().to_string(), "next": next })
+            );
+            break;
+        }
     }
+    Ok(())
+}
 
+/// Interactive terminal game loop, with the campaign menu and the color test screens.
+#[cfg(feature = "tui")]
+#[allow(clippy::too_many_lines)]
+fn run_tui(
+    matches: Args,
+    mut game: game::Game,
+    mut campaign: Option<Campaign>,
+    mut campaign_scenario: Option<usize>,
+    mut daily: Option<DailyChallenge>,
+) -> Result<()> {
     log::info!("Starting UI mode");
 
     // Setup terminal (only for non-headless mode)
@@ -252,56 +539,153 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Register AIs for UI mode as well so the UI can auto-play AI turns
-    if !matches.headless {
-        use crate::game::ai::LlmAi;
-        let ai_type = std::env::var("AI_TYPE").unwrap_or_else(|_| "llm".to_string());
-        let ai_model = std::env::var("AI_MODEL").unwrap_or_else(|_| "openai/gpt-4o-mini".to_string());
-        let mut ai_indices: Vec<usize> = Vec::new();
-        for (i, civ) in game.state().civilizations.iter().enumerate() {
-            if matches!(civ.city.player_type, ast::PlayerType::AI) {
-                ai_indices.push(i);
-            }
-        }
-        for i in ai_indices {
-            match ai_type.to_lowercase().as_str() {
-                "random" => {
-                    game.register_ai(i, Box::new(game::RandomAi::new()));
-                    log::info!("Registered RandomAi for civ {} (UI)", i);
-                }
-                "llm" | _ => {
-                    game.register_ai(i, Box::new(LlmAi::new(Box::leak(ai_model.clone().into_boxed_str()))));
-                    log::info!("Registered LlmAi for civ {} (UI) with model {}", i, ai_model);
+    // Let the player pick a campaign scenario among the unlocked ones
+    if let Some(campaign) = &campaign {
+        let mut selected = campaign.next_scenario();
+        loop {
+            draw_campaign_menu(&mut terminal, campaign, selected)?;
+            if event::poll(std::time::Duration::from_millis(100))?
+                && let Event::Key(key) = event::read()?
+            {
+                match key.code {
+                    KeyCode::Up => selected = selected.saturating_sub(1),
+                    KeyCode::Down => {
+                        selected = (selected + 1).min(campaign.manifest.scenarios.len() - 1);
+                    }
+                    KeyCode::Enter if campaign.is_unlocked(selected) => break,
+                    KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        cleanup_term(&mut terminal)?;
+                        return Ok(());
+                    }
+                    _ => {}
                 }
             }
         }
+        game = campaign.start_scenario(selected)?;
+        if matches.text_mode {
+            game.state_mut().toggle_text_mode();
+        }
+        if let Some(interval) = matches.dynamic_difficulty {
+            game.enable_dynamic_difficulty(interval);
+        }
+        campaign_scenario = Some(selected);
     }
 
+    // the preferences of the user only apply to the terminal interface
+    game.set_preferences(game::preferences::Preferences::load());
+
+    // Register AIs for UI mode as well so the UI can auto-play AI turns
+    register_ais(&mut game, "UI");
+
+    // Documents pushed by the DSL editor, in live link mode
+    let live_link = matches.watch_stdin.then(game::live::watch_stdin);
+
     // Game loop
+    // AI actions are played one at a time so the spectator can pause, step and change speed
+    let mut next_ai_action = std::time::Instant::now();
+    let mut step_requested = false;
+    let mut history_recorded = false;
+    if matches.record {
+        game.start_recording()?;
+    }
     loop {
+        // Hot-apply the documents pushed by the editor since the last frame
+        for line in live_link
+            .iter()
+            .flat_map(std::sync::mpsc::Receiver::try_iter)
+        {
+            match game::live::reload(&mut game, &line) {
+                Ok(()) => register_ais(&mut game, "UI"),
+                Err(e) => {
+                    log::warn!("Live reload failed: {e:#}");
+                    game.state_mut()
+                        .open_popup("Live reload", &format!("{e:#}"), vec![]);
+                }
+            }
+        }
+
         // Check if current player is AI and set the flag (but don't run yet)
-        let is_ai_turn = if let Some(civ) = game.state().civilizations.get(game.state().player_turn) {
+        let is_ai_turn = if let Some(civ) = game.state().civilizations.get(game.state().player_turn)
+        {
             matches!(civ.city.player_type, ast::PlayerType::AI)
         } else {
             false
         };
-        
+
         if is_ai_turn {
             game.state_mut().ai_thinking = true;
             game.state_mut().action_input.clear();
             game.state_mut().action_editing = false;
+        } else {
+            // keys pressed during the AI turn play now
+            game.release_held_keys();
         }
 
         // Draw frame (this will show the AI thinking popup if ai_thinking is true)
         game.run(&mut terminal)?;
 
-        // Now run the AI if it's their turn (after the popup has been drawn)
-        if is_ai_turn {
+        // Persist campaign progress once the scenario is decided
+        if game.state().game_over
+            && let (Some(campaign), Some(index)) = (campaign.as_mut(), campaign_scenario.take())
+        {
+            campaign.record_result(index, game.state())?;
+        }
+        if game.state().game_over
+            && let Some(challenge) = daily.take()
+        {
+            let entry = challenge.record(game.state())?;
+            // Append the score to the game over popup rather than replacing it
+            if let Some(popup) = &mut game.state_mut().popup {
+                popup.prompt = format!(
+                    "{}\nDéfi du {}: score {}",
+                    popup.prompt, entry.date, entry.score
+                );
+            }
+        }
+
+        if game.state().game_over && !history_recorded {
+            game::history::record(game.state())?;
+            game::events::write_chronicle(game.state())?;
+            game::report::write_report(game.state())?;
+            history_recorded = true;
+        }
+
+        // In play-by-mail mode, the game ends here once handed over to the next player
+        if let Some((path, next)) = game::pbem::hand_over(&mut game)? {
+            cleanup_term(&mut terminal)?;
+            game.finish_recording()?;
+            if game.state().game_over {
+                println!(
+                    "Game over: send the turn file {} to the other players",
+                    path.display()
+                );
+            } else {
+                println!(
+                    "Turn file written to {}: send it to the player of {next}",
+                    path.display()
+                );
+            }
+            return Ok(());
+        }
+
+        // Now play one AI action if it's their turn (after the popup has been drawn)
+        let now = std::time::Instant::now();
+        let ai_running = is_ai_turn && !game.state().ai_paused;
+        if is_ai_turn && (step_requested || (ai_running && now >= next_ai_action)) {
+            step_requested = false;
             game.run_ai_for_current_player();
+            next_ai_action = std::time::Instant::now() + game.ai_delay();
         }
 
-        // Handle input
-        if event::poll(std::time::Duration::from_millis(100))?
+        // Handle input, waking up in time for the next AI action
+        let timeout = if ai_running {
+            next_ai_action
+                .saturating_duration_since(std::time::Instant::now())
+                .min(std::time::Duration::from_millis(100))
+        } else {
+            std::time::Duration::from_millis(100)
+        };
+        if event::poll(timeout)?
             && let Event::Key(key) = event::read()?
         {
             // Quit on Ctrl+Q
@@ -309,12 +693,27 @@ async fn main() -> Result<()> {
                 break;
             }
 
-            // If it's currently an AI player's turn, ignore user input (prevent playing on AI's turn)
+            // Spectator notes are typed in the action bar, even on an AI turn
+            if game.state().annotating {
+                game.handle_key(key);
+                continue;
+            }
+
+            // If it's currently an AI player's turn, playback controls apply at once and the
+            // other keys wait for a human to have the turn
             if let Some(civ) = game.state().civilizations.get(game.state().player_turn)
-                && matches!(civ.city.player_type, ast::PlayerType::AI) {
-                    log::debug!("User input ignored because it's AI's turn: {key:?}");
-                    continue;
+                && matches!(civ.city.player_type, ast::PlayerType::AI)
+            {
+                match key.code {
+                    KeyCode::Char(' ') => game.toggle_ai_pause(),
+                    KeyCode::Char('n') if game.state().ai_paused => step_requested = true,
+                    KeyCode::Char('a') if game.state().ai_paused => game.start_annotation(),
+                    KeyCode::Char('+') => game.change_ai_speed(true),
+                    KeyCode::Char('-') => game.change_ai_speed(false),
+                    _ => game.hold_key(key),
                 }
+                continue;
+            }
 
             // Forward other keys to game handler
             game.handle_key(key);
@@ -323,5 +722,71 @@ async fn main() -> Result<()> {
 
     // Cleanup
     cleanup_term(&mut terminal)?;
+    game.finish_recording()?;
     Ok(())
 }
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(
+    _: Args,
+    _: game::Game,
+    _: Option<Campaign>,
+    _: Option<usize>,
+    _: Option<DailyChallenge>,
+) -> Result<()> {
+    anyhow::bail!(
+        "this build has no terminal interface (`tui` feature disabled): run it with --headless"
+    )
+}
+
+/// Register the AI selected by `AI_TYPE` (LLM by default) for every AI civilization, and as the
+/// advisor of human players.
+fn register_ais(game: &mut game::Game, mode: &str) {
+    let ai_type = std::env::var("AI_TYPE").unwrap_or_else(|_| "llm".to_string());
+    let ai_model = std::env::var("AI_MODEL").unwrap_or_else(|_| "openai/gpt-4o-mini".to_string());
+    // First collect indices to avoid borrowing `game` immutably while mutating it
+    let ai_indices: Vec<usize> = game
+        .state()
+        .civilizations
+        .iter()
+        .enumerate()
+        .filter(|(_, civ)| matches!(civ.city.player_type, ast::PlayerType::AI))
+        .map(|(i, _)| i)
+        .collect();
+    for i in ai_indices {
+        if ai_type.eq_ignore_ascii_case("random") {
+            let ai = game
+                .ai_seed(i)
+                .map_or_else(game::RandomAi::new, game::RandomAi::seeded);
+            game.register_ai(i, Box::new(ai));
+            log::info!(
+                "Registered RandomAi for civ {i} ({mode}), seed {:?}",
+                game.ai_seed(i)
+            );
+        } else {
+            let agenda = game.state().civ(i).ok().and_then(|c| c.city.agenda.clone());
+            game.register_ai(i, llm_ai(&ai_model, agenda));
+            log::info!("Registered LlmAi for civ {i} ({mode}) with model {ai_model}");
+        }
+    }
+    // the `suggest` action asks the LLM too when there is one, the heuristic advisor otherwise
+    if cfg!(feature = "llm") && !ai_type.eq_ignore_ascii_case("random") {
+        game.set_advisor(llm_ai(&ai_model, None));
+        log::info!("Registered LlmAi as advisor ({mode}) with model {ai_model}");
+    }
+}
+
+#[cfg(feature = "llm")]
+fn llm_ai(model: &str, agenda: Option<String>) -> Box<dyn game::Ai> {
+    Box::new(game::ai::LlmAi::with_agenda(
+        Box::leak(model.to_string().into_boxed_str()),
+        agenda,
+    ))
+}
+
+/// Without the `llm` feature, LLM players fall back to the random AI.
+#[cfg(not(feature = "llm"))]
+fn llm_ai(_model: &str, _agenda: Option<String>) -> Box<dyn game::Ai> {
+    log::warn!("This build has no LLM support (`llm` feature disabled), using RandomAi instead");
+    Box::new(game::RandomAi::new())
+}
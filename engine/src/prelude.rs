@@ -0,0 +1,20 @@
+//! What programs using the engine as a library build against: bots, servers and bindings.
+//!
+//! ```ignore
+//! use clivilization_engine::prelude::*;
+//! ```
+//!
+//! Items re-exported here only change with the major version. The other items of `game` serve
+//! the engine itself and may change in any release, the fields of `GameState` among them: they
+//! are public for the engine modules, so read them through `Game::state` and change the game
+//! through `Game::apply` rather than `Game::state_mut`. Actions, events and AI views are
+//! `#[non_exhaustive]`, as new ones come with new features: match them with a wildcard arm.
+
+pub use crate::game::action::{Action, ParseError, PolicySlider};
+pub use crate::game::build_queue::QueueEdit;
+pub use crate::game::events::{GameEvent, Unread};
+pub use crate::game::state::{GameState, Popup};
+pub use crate::game::victory::{Outcome, VictoryCondition};
+pub use crate::game::{Ai, AiPlayerView, AiView, DecisionTrace, Game, RandomAi, ScoredAction};
+/// Errors of the engine, with the context of what failed
+pub use anyhow::{Error, Result};
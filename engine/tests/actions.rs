@@ -0,0 +1,2726 @@
+//! Typed actions: parsing of the action bar and the `Game::apply` entry point.
+
+use clivilization_engine::ast::UnitInstance;
+use clivilization_engine::game::action::{Action, PolicySlider};
+use clivilization_engine::game::action_menu::ActionMenu;
+use clivilization_engine::game::advisor;
+use clivilization_engine::game::build_queue::QueueEdit;
+use clivilization_engine::game::events::{self, GameEvent};
+use clivilization_engine::game::state::{Popup, Travel};
+use clivilization_engine::game::valid_actions;
+use clivilization_engine::game::{Ai, AiView, Game};
+use common::{
+    CONFIG, add_building, add_section, config_with, grant_building, resource_building, section,
+};
+
+mod common;
+
+/// AI ending its turns and refusing every proposal.
+struct Refuser;
+
+impl Ai for Refuser {
+    fn select_action(&mut self, _view: &AiView, _civ_index: usize) -> Option<String> {
+        Some("end".to_string())
+    }
+
+    fn select_popup_input(&mut self, _view: &AiView, _civ_index: usize, _popup: &Popup) -> String {
+        "Refuse".to_string()
+    }
+}
+
+#[test]
+fn actions_survive_a_round_trip_through_text() {
+    let actions = [
+        Action::EndTurn,
+        Action::Build(Some("tour".to_string())),
+        Action::Hire {
+            unit: None,
+            amount: None,
+        },
+        Action::Hire {
+            unit: Some("mele".to_string()),
+            amount: Some(5),
+        },
+        Action::Research(Some("maçonnerie".to_string())),
+        Action::Attack {
+            target: Some("Noxus".to_string()),
+            amount: Some(3),
+            units: Vec::new(),
+        },
+        Action::Loan(50),
+        Action::Rename {
+            city: "Demacia".to_string(),
+            name: "Lumière".to_string(),
+        },
+        Action::SetPolicy {
+            slider: PolicySlider::Military,
+            value: 70,
+        },
+        Action::SetSortie(true),
+        Action::Queue(None),
+        Action::Queue(Some(QueueEdit::Cancel(2))),
+        Action::Gift {
+            target: "Noxus".to_string(),
+            amount: 10,
+        },
+        Action::Simulate {
+            target: "Noxus".to_string(),
+            amount: None,
+        },
+        Action::CancelBuild(Some("tour".to_string())),
+        Action::CancelHire,
+        Action::Income,
+        Action::Recall(None),
+        Action::Suggest,
+        Action::Save("before-attack".to_string()),
+    ];
+    for action in actions {
+        assert_eq!(Action::parse(&action.to_string()), Ok(action));
+    }
+}
+
+#[test]
+fn incomplete_actions_are_explained() {
+    assert_eq!(Action::parse("loan").unwrap_err().title, "Loan");
+    assert_eq!(
+        Action::parse("dance").unwrap_err().message,
+        "Unknown action: dance"
+    );
+    assert_eq!(Action::parse("policy tax 150"), Ok(Action::ShowPolicy));
+    assert_eq!(
+        Action::parse("RECRUIT mele"),
+        Ok(Action::Hire {
+            unit: Some("mele".to_string()),
+            amount: None
+        })
+    );
+}
+
+#[test]
+fn mistyped_names_suggest_the_closest_ones() {
+    use clivilization_engine::game::utils::{did_you_mean, levenshtein};
+
+    assert_eq!(levenshtein("farn", "farm"), 1);
+    // swapping two adjacent letters is a single typo
+    assert_eq!(levenshtein("fram", "farm"), 1);
+    assert_eq!(levenshtein("", "farm"), 4);
+    let buildings = ["farm", "forge", "tour"];
+    assert_eq!(did_you_mean("farn", buildings), vec!["farm"]);
+    assert_eq!(did_you_mean("FRAM", buildings), vec!["farm"]);
+    assert!(did_you_mean("castle", buildings).is_empty());
+}
+
+#[test]
+fn mistyped_simulation_targets_offer_the_suggested_cities() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.state_mut().civilizations[0].city.units.units = vec![UnitInstance {
+        id_units: "mele".to_string(),
+        nb_units: 3,
+    }];
+    assert!(game.apply(Action::Simulate {
+        target: "Noxsu".to_string(),
+        amount: None,
+    }));
+    let popup = game.state().popup.clone().unwrap();
+    assert_eq!(popup.title, "Simulation");
+    assert!(popup.prompt.contains("did you mean Noxus?"));
+    assert_eq!(popup.choices, vec!["Noxus"]);
+
+    game.submit_popup_input("1");
+    let popup = game.state().popup.clone().unwrap();
+    assert_eq!(popup.title, "Simulation");
+    assert!(popup.prompt.contains("Noxus"), "{}", popup.prompt);
+    assert!(popup.prompt.contains("Win probability"), "{}", popup.prompt);
+}
+
+#[test]
+fn mistyped_gift_targets_offer_the_gift_to_the_suggested_cities() {
+    use clivilization_engine::game::diplomacy;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    assert!(game.apply(Action::Gift {
+        target: "Noxsu".to_string(),
+        amount: 5,
+    }));
+    let popup = game.state().popup.clone().unwrap();
+    assert_eq!(popup.title, "Diplomacy");
+    assert_eq!(popup.choices, vec!["gift Noxus 5"]);
+
+    game.submit_popup_input("1");
+    let proposal = diplomacy::pending(game.state()).unwrap();
+    assert_eq!((proposal.from, proposal.to), (0, 1));
+    assert!(matches!(proposal.deal, diplomacy::Deal::Gift(5)));
+}
+
+#[test]
+fn mistyped_scout_targets_offer_the_suggested_cities() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    assert!(game.apply(Action::Scout("Noxsu".to_string())));
+    let popup = game.state().popup.clone().unwrap();
+    assert_eq!(popup.title, "Scout");
+    assert_eq!(popup.choices, vec!["Noxus"]);
+
+    let resources = game.state().civilizations[0].resources.ressources;
+    game.submit_popup_input("1");
+    assert!(game.state().civilizations[0].resources.ressources < resources);
+}
+
+/// Game where Demacia has `army` melee units against a Noxus city without walls, holding
+/// `garrison` melee units.
+fn skirmish(army: u32, garrison: u32) -> Game {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let state = game.state_mut();
+    state.civilizations[0].city.units.units = vec![UnitInstance {
+        id_units: "mele".to_string(),
+        nb_units: army,
+    }];
+    let noxus = &mut state.civilizations[1].city;
+    noxus.buildings.elements.clear();
+    noxus.units.units = vec![UnitInstance {
+        id_units: "mele".to_string(),
+        nb_units: garrison,
+    }];
+    noxus.units.units.retain(|u| u.nb_units > 0);
+    game
+}
+
+#[test]
+fn simulations_leave_the_game_untouched() {
+    let mut game = skirmish(50, 5);
+    game.state_mut().attrition = 20;
+    game.state_mut().supply_range = 5;
+    let before = format!("{:?}", game.state());
+    assert!(game.apply(Action::Simulate {
+        target: "Noxus".to_string(),
+        amount: None,
+    }));
+    assert!(game.state().popup.is_some());
+    game.state_mut().popup = None;
+    assert_eq!(format!("{:?}", game.state()), before);
+}
+
+#[test]
+fn simulations_are_certain_of_lopsided_battles() {
+    use clivilization_engine::game::simulation::simulate_attack;
+
+    let game = skirmish(1000, 0);
+    let forecast = simulate_attack(game.state(), 0, 1, None).unwrap();
+    assert_eq!(forecast.reinforcement, 0);
+    assert!((forecast.win_probability - 1.0).abs() < f64::EPSILON);
+
+    let game = skirmish(1, 1000);
+    let forecast = simulate_attack(game.state(), 0, 1, None).unwrap();
+    assert!(forecast.win_probability.abs() < f64::EPSILON);
+}
+
+#[test]
+fn simulations_forecast_the_march_of_the_real_attack() {
+    use clivilization_engine::game::simulation::simulate_attack;
+
+    // without a garrison the attacker loses no one in the battle, only on the way
+    let mut game = skirmish(100, 0);
+    game.state_mut().attrition = 20;
+    game.state_mut().supply_range = 5;
+    let forecast = simulate_attack(game.state(), 0, 1, None).unwrap();
+    assert!(forecast.attrition > 0);
+
+    game.apply(Action::Attack {
+        target: Some("Noxus".to_string()),
+        amount: None,
+        units: Vec::new(),
+    });
+    assert_eq!(game.state().travels[0].amount, forecast.sent);
+    let mut turns = 0;
+    while !game.state().travels.is_empty() && turns < 1000 {
+        game.apply(Action::EndTurn);
+        turns += 1;
+    }
+    assert_eq!(turns, forecast.turns);
+    assert_eq!(
+        game.state().metrics.totals(0).units_lost,
+        forecast.attrition
+    );
+    assert!(
+        game.state()
+            .pending_conquests
+            .iter()
+            .any(|c| c.defender == 1)
+    );
+}
+
+#[test]
+fn typed_actions_are_applied_like_typed_text() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let opened = game.apply(Action::Build(Some("TOUR".to_string())));
+    assert!(!opened);
+    let civ = game.state().current_civ().unwrap();
+    assert_eq!(civ.constructions[0].id_building, "tour");
+}
+
+#[test]
+fn possible_actions_follow_the_validation_of_the_action_bar() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let listed = |game: &Game| -> Vec<String> {
+        valid_actions::list(game.state(), game.state().player_turn)
+            .into_iter()
+            .map(|a| a.syntax)
+            .collect()
+    };
+    let before = listed(&game);
+    assert!(before.contains(&"build tour".to_string()));
+    // no unit can be hired before a producing building exists
+    assert!(!before.iter().any(|a| a.starts_with("hire")));
+
+    game.apply(Action::Build(Some("tour".to_string())));
+    // only one construction at a time
+    assert!(!listed(&game).iter().any(|a| a.starts_with("build")));
+}
+
+#[test]
+fn researched_techs_unlock_the_buildings_they_gate() {
+    use clivilization_engine::game::campaign::CarryOver;
+
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({
+                "techs": [{ "name": "maçonnerie", "cost": 2, "research_time": 1, "unlocks": { "values": ["tour"] } }]
+            }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    assert!(game.apply(Action::Build(Some("tour".to_string()))));
+    assert!(game.state().current_civ().unwrap().constructions.is_empty());
+
+    game.apply(Action::Research(Some("maçonnerie".to_string())));
+    let player = game.state().player_turn;
+    game.state_mut().on_turn_start(player);
+    assert!(
+        game.state()
+            .current_civ()
+            .unwrap()
+            .researched
+            .contains("maçonnerie")
+    );
+    game.apply(Action::Build(Some("tour".to_string())));
+    assert_eq!(
+        game.state().current_civ().unwrap().constructions[0].id_building,
+        "tour"
+    );
+
+    // the techs carry over to the next scenario of a campaign that defines them
+    let carried = CarryOver::from_state(game.state(), player);
+    let mut next = Game::from_string(&config).unwrap();
+    carried.apply(next.state_mut());
+    assert!(
+        next.state().civilizations[player]
+            .researched
+            .contains("maçonnerie")
+    );
+    let mut next = Game::from_string(CONFIG).unwrap();
+    carried.apply(next.state_mut());
+    assert!(next.state().civilizations[player].researched.is_empty());
+}
+
+#[test]
+fn action_menu_offers_the_actions_of_the_definitions() {
+    let game = Game::from_string(CONFIG).unwrap();
+    let state = game.state();
+    let mut menu = ActionMenu::default();
+    // the build category opens on the first building
+    assert_eq!(menu.select(state), None);
+    menu.move_cursor(state, 1);
+    assert_eq!(
+        menu.select(state),
+        Some(Action::Build(Some("inhibiteur".to_string())))
+    );
+    assert!(menu.back());
+    // moving up from the first category wraps around to the end of turn
+    menu.move_cursor(state, -1);
+    assert_eq!(menu.select(state), Some(Action::EndTurn));
+    assert!(!menu.back());
+}
+
+#[test]
+fn city_lists_restrict_what_can_be_built() {
+    let config = config_with(|c| {
+        section(c, "cities")["cities"][0]["blacklist_buildings"] =
+            serde_json::json!({ "values": ["tour"] });
+    });
+    let mut game = Game::from_string(&config).unwrap();
+
+    let civ = game.state().player_turn;
+    let err = game
+        .state_mut()
+        .start_construction(civ, "tour")
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Demacia cannot build tour");
+    game.apply(Action::Build(None));
+    assert_eq!(
+        game.state().popup.as_ref().unwrap().choices,
+        vec!["inhibiteur".to_string()]
+    );
+    assert!(
+        !game
+            .ai_possible_actions(civ)
+            .contains(&"build tour".to_string())
+    );
+}
+
+#[test]
+fn armies_leave_a_region_through_its_crossings() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({
+                "regions": [
+                    { "name": "west", "x": 0, "y": 0, "width": 50, "height": 40 },
+                    { "name": "east", "x": 60, "y": 0, "width": 100, "height": 40 }
+                ],
+                "crossings": [{ "from_x": 49, "from_y": 10, "to_x": 60, "to_y": 10, "turns": 6 }]
+            }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    game.state_mut().civilizations[0]
+        .city
+        .units
+        .units
+        .push(UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 3,
+        });
+    game.apply(Action::Attack {
+        target: Some("Noxus".to_string()),
+        amount: None,
+        units: Vec::new(),
+    });
+
+    let travel = &game.state().travels[0];
+    assert!(
+        travel
+            .path
+            .windows(2)
+            .any(|w| w[0] == (49, 10) && w[1] == (60, 10))
+    );
+    // the crossing alone takes longer than the land walk of the same length
+    assert!(travel.total > 6);
+}
+
+#[test]
+fn paths_are_found_again_once_the_terrain_changes() {
+    use clivilization_engine::game::map::Terrain;
+    use clivilization_engine::game::terraform::{self, TerrainChange};
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let (demacia, noxus) = ((8, 10), (90, 20));
+    let path = game
+        .state()
+        .path(demacia, noxus)
+        .expect("a way between the two cities");
+    assert_eq!(path.tiles.first(), Some(&demacia));
+    assert_eq!(path.tiles.last(), Some(&noxus));
+    assert_eq!(game.state().reachable_targets(0), vec![(1, path.clone())]);
+
+    let (x, y) = path.tiles[path.tiles.len() / 2];
+    terraform::set_terrain(
+        game.state_mut(),
+        TerrainChange {
+            x,
+            y,
+            terrain: Terrain::Mountain,
+        },
+    );
+    let detour = game
+        .state()
+        .path(demacia, noxus)
+        .expect("a way around the mountain");
+    assert!(!detour.tiles.contains(&(x, y)));
+}
+
+#[test]
+fn paths_go_around_mountains_with_single_steps() {
+    use clivilization_engine::game::map::Terrain;
+
+    let game = Game::from_string(CONFIG).unwrap();
+    let state = game.state();
+    let path = state.path((8, 10), (90, 20)).unwrap();
+    for w in path.tiles.windows(2) {
+        assert_eq!((w[0].0 - w[1].0).abs() + (w[0].1 - w[1].1).abs(), 1);
+    }
+    assert!(
+        path.tiles
+            .iter()
+            .all(
+                |(x, y)| state.map.tiles[y.cast_unsigned() as usize][x.cast_unsigned() as usize]
+                    != Terrain::Mountain
+            )
+    );
+    // no way leads out of the map
+    assert_eq!(state.path((8, 10), (-1, 20)), None);
+}
+
+#[test]
+fn armies_march_tile_by_tile_to_the_city_they_attack() {
+    use clivilization_engine::game::armies;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.state_mut().civilizations[0]
+        .city
+        .units
+        .units
+        .push(UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 3,
+        });
+    game.apply(Action::Attack {
+        target: Some("Noxus".to_string()),
+        amount: None,
+        units: Vec::new(),
+    });
+    let path = game.state().path((8, 10), (90, 20)).unwrap();
+    let travel = &game.state().travels[0];
+    assert_eq!(travel.tile(), (8, 10));
+    assert_eq!(travel.remaining, path.turns);
+
+    let mut step = 0;
+    while game.state().travels[0].remaining > 0 {
+        let arrived = armies::advance(game.state_mut());
+        let travel = &game.state().travels[0];
+        assert!(travel.step >= step);
+        assert_eq!(travel.tile(), path.tiles[travel.step]);
+        assert_eq!(arrived.is_empty(), travel.remaining > 0);
+        step = travel.step;
+    }
+    assert_eq!(game.state().travels[0].tile(), (90, 20));
+}
+
+#[test]
+fn scouting_explores_the_fog_around_a_rival_city() {
+    use clivilization_engine::game::fog;
+
+    let config = config_with(|c| section(c, "map_x")["fog_of_war"] = "on".into());
+    let mut game = Game::from_string(&config).unwrap();
+    let noxus = (90, 20);
+    assert!(fog::explored(game.state(), 0, (8, 10)));
+    assert!(!fog::explored(game.state(), 0, noxus));
+    let view = game.make_ai_view();
+    assert!(view.players[0].scouted);
+    assert!(!view.players[1].scouted);
+    assert_eq!(view.players[1].resources, 0);
+    assert_eq!(view.players[1].travel_turns, None);
+    assert!(view.players[1].persona.is_none() && view.players[1].techs.is_empty());
+
+    game.apply(Action::Scout("Noxus".to_string()));
+    assert!(fog::explored(game.state(), 0, noxus));
+    let view = game.make_ai_view();
+    assert!(view.players[1].scouted);
+    assert!(view.players[1].travel_turns.is_some());
+}
+
+#[test]
+fn simulations_do_not_reveal_unexplored_cities() {
+    let config = config_with(|c| section(c, "map_x")["fog_of_war"] = "on".into());
+    let mut game = Game::from_string(&config).unwrap();
+    game.state_mut().civilizations[0].city.units.units = vec![UnitInstance {
+        id_units: "mele".to_string(),
+        nb_units: 3,
+    }];
+    let simulate = |game: &mut Game| {
+        game.state_mut().popup = None;
+        game.apply(Action::Simulate {
+            target: "Noxus".to_string(),
+            amount: None,
+        });
+        game.state().popup.clone().unwrap().prompt
+    };
+
+    let prompt = simulate(&mut game);
+    assert!(prompt.contains("unexplored"), "{prompt}");
+    assert!(!prompt.contains("Defender power"), "{prompt}");
+
+    game.state_mut().popup = None;
+    game.apply(Action::Scout("Noxus".to_string()));
+    let prompt = simulate(&mut game);
+    assert!(prompt.contains("Defender power"), "{prompt}");
+}
+
+#[test]
+fn spy_missions_are_drawn_from_the_game() {
+    use clivilization_engine::game::espionage;
+
+    let config = config_with(|c| {
+        section(c, "buildings")["buildings"][1]["production"] =
+            serde_json::json!({ "prod_type": "COUNTERINTEL", "amount": 50, "time": 1, "cost": 0 });
+        section(c, "cities")["cities"][1]["buildings"]["elements"] =
+            serde_json::json!([{ "id_building": "inhibiteur", "level": 1 }]);
+    });
+    let missions = || {
+        let mut game = Game::from_string(&config).unwrap();
+        let state = game.state_mut();
+        state.civilizations[0].resources.ressources = 100;
+        state.civilizations[1].city.units.units = vec![UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 40,
+        }];
+        (0..8)
+            .map(|_| espionage::scout(state, 0, 1).unwrap())
+            .collect::<Vec<_>>()
+    };
+    let reports = missions();
+    assert_eq!(missions(), reports);
+    assert!(
+        reports.iter().any(|r| r.contains("caught")) && reports.iter().any(|r| r.contains("±20")),
+        "{reports:?}"
+    );
+}
+
+#[test]
+fn armies_out_of_sight_are_remembered_where_they_were_last_seen() {
+    use clivilization_engine::game::fog::{self, SIGHTING_MEMORY};
+
+    let config = config_with(|c| section(c, "map_x")["fog_of_war"] = "on".into());
+    let mut game = Game::from_string(&config).unwrap();
+    // an army of Noxus walks past the walls of Demacia, then out of its sight
+    game.state_mut().travels.push(Travel {
+        attacker: 1,
+        defender: 0,
+        amount: 3,
+        composition: Vec::new(),
+        remaining: 4,
+        total: 4,
+        path: vec![(40, 30), (12, 12), (40, 30)],
+        step: 1,
+        progress: 0,
+    });
+    fog::update(game.state_mut());
+    let seen = game.make_ai_view().sightings;
+    assert_eq!(seen.len(), 1);
+    assert_eq!(
+        (seen[0].attacker, seen[0].amount, seen[0].tile),
+        (1, 3, (12, 12))
+    );
+
+    let turn = game.state().turn;
+    game.state_mut().travels[0].step = 2;
+    game.state_mut().turn = turn + 2;
+    fog::update(game.state_mut());
+    let ghost = &game.make_ai_view().sightings[0];
+    assert_eq!((ghost.tile, ghost.age(turn + 2)), ((12, 12), 2));
+
+    // old sightings fade away
+    game.state_mut().turn = turn + SIGHTING_MEMORY + 1;
+    fog::update(game.state_mut());
+    assert!(game.make_ai_view().sightings.is_empty());
+}
+
+#[test]
+fn ai_views_rate_the_strength_of_the_cities_they_know() {
+    let config = config_with(|c| section(c, "map_x")["fog_of_war"] = "on".into());
+    let mut game = Game::from_string(&config).unwrap();
+    game.state_mut().travels.push(Travel {
+        attacker: 1,
+        defender: 0,
+        amount: 3,
+        composition: Vec::new(),
+        remaining: 4,
+        total: 4,
+        path: vec![(40, 30), (12, 12)],
+        step: 0,
+        progress: 0,
+    });
+    let view = game.make_ai_view();
+    assert_eq!(view.players[0].power, game.state().calculate_city_power(0));
+    assert_eq!(view.players[0].incoming, 3);
+    // the strength of an unexplored city is hidden
+    assert!(!view.players[1].scouted);
+    assert_eq!((view.players[1].power, view.players[1].income), (0, 0));
+
+    game.apply(Action::Scout("Noxus".to_string()));
+    let view = game.make_ai_view();
+    assert_eq!(view.players[1].power, game.state().calculate_city_power(1));
+    assert_eq!(view.players[1].incoming, 0);
+}
+
+#[test]
+fn the_game_report_replays_territory_power_and_battles_turn_by_turn() {
+    use clivilization_engine::game::report;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.state_mut().record_metrics();
+    game.apply(Action::EndTurn);
+    game.apply(Action::EndTurn);
+    let turn = game.state().turn;
+    game.state_mut().events.publish(
+        turn,
+        GameEvent::Battle {
+            attacker: 0,
+            defender: 1,
+            won: false,
+        },
+    );
+    game.state_mut().record_metrics();
+
+    let page = report::html(game.state());
+    let start = page.find("const DATA = ").unwrap() + "const DATA = ".len();
+    let end = start + page[start..].find(";\n").unwrap();
+    let data: serde_json::Value = serde_json::from_str(&page[start..end]).unwrap();
+    let frames = data["frames"].as_array().unwrap();
+    assert_eq!(frames.len(), game.state().metrics.history.len());
+    assert!(frames.len() >= 2);
+    // each city holds the tiles around it
+    let width = game.state().map.width;
+    for (i, civ) in game.state().civilizations.iter().enumerate() {
+        let territory = data["territories"]
+            [usize::try_from(frames[0]["territory"].as_u64().unwrap()).unwrap()]
+        .as_str()
+        .unwrap();
+        let tile = civ.city.y as usize * width + civ.city.x as usize;
+        assert_eq!(
+            territory.chars().nth(tile),
+            char::from_digit(u32::try_from(i).unwrap(), 36)
+        );
+    }
+    let noxus = &game.state().civilizations[1].city;
+    assert_eq!(data["battles"][0]["turn"], turn);
+    assert_eq!(
+        (
+            data["battles"][0]["x"].as_u64(),
+            data["battles"][0]["y"].as_u64()
+        ),
+        (Some(noxus.x.into()), Some(noxus.y.into()))
+    );
+}
+
+#[test]
+fn popups_wait_under_more_urgent_ones_instead_of_being_overwritten() {
+    use clivilization_engine::game::state::{Popup, PopupPriority};
+    use std::time::Duration;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let state = game.state_mut();
+    state.open_popup("Battle", "Noxus drove off Demacia", vec![]);
+    state.show_popup(Popup {
+        priority: PopupPriority::Critical,
+        ..Popup::new("Game Over", "Noxus wins", vec![])
+    });
+    state.open_popup("Upkeep", "2 units disbanded", vec![]);
+    state.open_popup(
+        "Conquest",
+        "Raze or pillage?",
+        vec!["Raze".to_string(), "Pillage".to_string()],
+    );
+    let title = |state: &clivilization_engine::game::state::GameState| {
+        state.popup.as_ref().map(|p| p.title.clone())
+    };
+    assert_eq!(title(state).as_deref(), Some("Game Over"));
+    assert_eq!(state.popups.len(), 3);
+
+    // informational popups close by themselves, the others wait for an answer
+    assert!(!state.expire_popup(Duration::ZERO));
+    state.close_popup();
+    assert_eq!(title(state).as_deref(), Some("Conquest"));
+    assert!(!state.expire_popup(Duration::ZERO));
+    state.close_popup();
+    assert_eq!(title(state).as_deref(), Some("Upkeep"));
+    assert!(state.expire_popup(Duration::ZERO));
+    assert_eq!(title(state).as_deref(), Some("Battle"));
+    assert!(game.submit_popup_input(""));
+    assert!(game.state().popup.is_none());
+}
+
+#[test]
+fn seasons_change_production_and_the_pace_of_armies() {
+    use clivilization_engine::game::armies;
+    use clivilization_engine::game::map::Terrain;
+    use clivilization_engine::game::turn_rules::projected_income;
+    use clivilization_engine::game::weather::{self, Season};
+
+    let config = config_with(|c| {
+        section(c, "map_x")["season_length"] = 2.into();
+        add_building(c, resource_building("farm", 10));
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    grant_building(&mut game, 0, "farm");
+    let spring = game.state().turn;
+    assert_eq!(weather::season(game.state(), spring), Some(Season::Spring));
+    assert_eq!(
+        weather::season(game.state(), spring + 6),
+        Some(Season::Winter)
+    );
+    assert_eq!(
+        weather::season(game.state(), spring + 8),
+        Some(Season::Spring)
+    );
+
+    let (x, y) = (
+        game.state().civilizations[0].city.x as usize,
+        game.state().civilizations[0].city.y as usize,
+    );
+    game.state_mut().map.set(x, y, Terrain::Plains);
+    let income = projected_income(game.state(), 0);
+    assert!(income > 0);
+    let army = Travel {
+        attacker: 0,
+        defender: 1,
+        amount: 1,
+        composition: Vec::new(),
+        remaining: 0,
+        total: 0,
+        path: game.state().path((12, 12), (40, 30)).unwrap().tiles,
+        step: 0,
+        progress: 0,
+    };
+    let march = armies::turns_left(game.state(), &army);
+
+    // the city stands on the plains, which freeze in winter
+    game.state_mut().turn = spring + 6;
+    assert_eq!(projected_income(game.state(), 0), income / 2);
+    assert!(armies::turns_left(game.state(), &army) > march);
+}
+
+#[test]
+fn rivers_run_to_the_sea_and_water_the_cities_beside_them() {
+    use clivilization_engine::game::map::Terrain;
+    use clivilization_engine::game::paths::{TURN, terrain_cost};
+    use clivilization_engine::game::turn_rules::projected_income;
+
+    let config = config_with(|c| add_building(c, resource_building("farm", 20)));
+    let mut game = Game::from_string(&config).unwrap();
+    let map = &game.state().map;
+    let (width, height) = (
+        i64::try_from(map.width).unwrap(),
+        i64::try_from(map.height).unwrap(),
+    );
+    let rivers: Vec<(i64, i64)> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|(x, y)| map.at(*x, *y) == Some(Terrain::River))
+        .collect();
+    assert!(!rivers.is_empty());
+    // every river ends on the shore
+    let near = |(x, y): (i64, i64), terrain| {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .iter()
+            .any(|(dx, dy)| map.at(x + dx, y + dy) == Some(terrain))
+    };
+    assert!(rivers.iter().any(|tile| near(*tile, Terrain::Water)));
+    assert!(
+        rivers
+            .iter()
+            .all(|tile| near(*tile, Terrain::River) || near(*tile, Terrain::Water))
+    );
+    assert_eq!(terrain_cost(Terrain::River), Some(TURN));
+
+    grant_building(&mut game, 0, "farm");
+    let (x, y) = (
+        game.state().civilizations[0].city.x as usize,
+        game.state().civilizations[0].city.y as usize,
+    );
+    for (tx, ty) in (x - 1..=x + 1).flat_map(|tx| (y - 1..=y + 1).map(move |ty| (tx, ty))) {
+        game.state_mut().map.set(tx, ty, Terrain::Plains);
+    }
+    let dry = projected_income(game.state(), 0);
+    game.state_mut().map.set(x + 1, y, Terrain::River);
+    assert_eq!(projected_income(game.state(), 0), dry * 5 / 4);
+}
+
+#[test]
+fn biomes_spread_by_the_thresholds_of_the_game_and_slow_or_starve_cities() {
+    use clivilization_engine::game::map::{GameMap, Terrain};
+    use clivilization_engine::game::paths::terrain_cost;
+    use clivilization_engine::game::turn_rules::projected_income;
+
+    let has = |map: &GameMap, terrain| map.tiles.iter().flatten().any(|t| *t == terrain);
+    let game = Game::from_string(CONFIG).unwrap();
+    for terrain in [Terrain::Forest, Terrain::Snow, Terrain::Swamp] {
+        assert!(has(&game.state().map, terrain), "{terrain:?}");
+    }
+
+    let config = config_with(|c| {
+        section(c, "map_x")["biomes"] =
+            serde_json::json!({ "snow": 0, "forest": 100, "swamp": 100 });
+        add_building(c, resource_building("farm", 20));
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    // the thresholds hold when the map is drawn again from another seed
+    for map in [
+        game.state().map.clone(),
+        game.state().map.reseeded("other".to_string()),
+    ] {
+        assert!(
+            ![Terrain::Forest, Terrain::Snow, Terrain::Swamp]
+                .iter()
+                .any(|t| has(&map, *t))
+        );
+    }
+
+    grant_building(&mut game, 0, "farm");
+    let (x, y) = (
+        game.state().civilizations[0].city.x as usize,
+        game.state().civilizations[0].city.y as usize,
+    );
+    for (tx, ty) in (x - 1..=x + 1).flat_map(|tx| (y - 1..=y + 1).map(move |ty| (tx, ty))) {
+        game.state_mut().map.set(tx, ty, Terrain::Plains);
+    }
+    let plains = projected_income(game.state(), 0);
+    game.state_mut().map.set(x, y, Terrain::Snow);
+    assert_eq!(projected_income(game.state(), 0), plains * 3 / 5);
+    game.state_mut().map.set(x, y, Terrain::Forest);
+    assert!(projected_income(game.state(), 0) > plains);
+
+    let cost = |terrain| terrain_cost(terrain).unwrap();
+    assert!(
+        cost(Terrain::Plains) < cost(Terrain::Forest)
+            && cost(Terrain::Forest) < cost(Terrain::Swamp)
+    );
+    assert!(
+        cost(Terrain::Snow) > cost(Terrain::Plains) && cost(Terrain::Swamp) < cost(Terrain::Water)
+    );
+}
+
+#[test]
+fn map_type_lays_out_continents_islands_or_a_pangaea() {
+    use clivilization_engine::game::map::{GameMap, MapShape, Terrain};
+
+    let land = |map: &GameMap, x: usize, y: usize| map.tiles[y][x] != Terrain::Water;
+    let share = |map: &GameMap| {
+        let tiles = map.tiles.iter().flatten();
+        tiles.clone().filter(|t| **t != Terrain::Water).count() * 100 / tiles.count()
+    };
+    let map_of = |map_type: &str| {
+        let config = config_with(|c| section(c, "map_x")["map_type"] = map_type.into());
+        Game::from_string(&config).unwrap().state().map.clone()
+    };
+
+    let scattered = Game::from_string(CONFIG).unwrap().state().map.clone();
+    assert_eq!(scattered.shape, MapShape::Scattered);
+
+    let pangaea = map_of("PANGAEA");
+    assert_eq!(pangaea.shape, MapShape::Pangaea);
+    let (w, h) = (pangaea.width, pangaea.height);
+    assert!(land(&pangaea, w / 2, h / 2));
+    assert!(
+        [(0, 0), (w - 1, 0), (0, h - 1), (w - 1, h - 1)]
+            .iter()
+            .all(|(x, y)| !land(&pangaea, *x, *y))
+    );
+
+    let islands = map_of("ISLANDS");
+    assert!((1..60).contains(&share(&islands)), "{}", share(&islands));
+
+    let continents = map_of("CONTINENTS");
+    assert!(
+        (20..90).contains(&share(&continents)),
+        "{}",
+        share(&continents)
+    );
+    assert_ne!(continents.tiles, scattered.tiles);
+    // the shape holds when the map is drawn again from another seed
+    assert_eq!(
+        continents.reseeded("other".to_string()).shape,
+        MapShape::Continents
+    );
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn wrapping_maps_join_their_east_and_west_edges() {
+    use clivilization_engine::game::map::Terrain;
+    use clivilization_engine::game::{fog, influence, map_view, paths};
+    use ratatui::layout::Rect;
+    use ratatui::style::Color;
+
+    let config = config_with(|c| section(c, "map_x")["wrap"] = "on".into());
+    let mut game = Game::from_string(&config).unwrap();
+    assert!(game.state().map.wrap);
+    assert!(!Game::from_string(CONFIG).unwrap().state().map.wrap);
+    // the map still wraps when drawn again from another seed
+    assert!(game.state().map.reseeded("other".to_string()).wrap);
+
+    let (width, height) = (game.state().map.width, game.state().map.height);
+    for (x, y) in (0..width).flat_map(|x| (0..height).map(move |y| (x, y))) {
+        game.state_mut().map.set(x, y, Terrain::Plains);
+    }
+    let map = &game.state().map;
+    let east = i32::try_from(width).unwrap() - 1;
+    assert_eq!(map.offset_x(0, i64::from(east)), -1);
+    assert_eq!(map.at(-1, 0), map.at(i64::from(east), 0));
+    // armies walk across the seam rather than all the way back over the map
+    assert_eq!(
+        paths::find(game.state(), (0, 5), (east, 5)).unwrap(),
+        vec![(0, 5), (east, 5)]
+    );
+    // cities see and project their influence across the seam as well
+    assert!(fog::sees(map, &[((1, 5), 2)], (east, 5)));
+    assert!(!fog::sees(
+        &Game::from_string(CONFIG).unwrap().state().map,
+        &[((1, 5), 2)],
+        (east, 5)
+    ));
+    let seam = u32::try_from(east).unwrap();
+    assert_eq!(
+        influence::fade(map, 40, (0, 5), seam, 5),
+        influence::fade(map, 40, (0, 5), 1, 5)
+    );
+
+    // the viewport tiles across the seam: the west edge shows right after the east one
+    let mut buffer = vec![vec![Color::Blue; width]; height];
+    for row in &mut buffer {
+        row[0] = Color::Red;
+    }
+    game.state_mut().camera_x = east - 1;
+    let lines = map_view::render_buffer(game.state(), Rect::default(), &buffer, 4, 2);
+    let colors: Vec<_> = lines[0]
+        .spans
+        .iter()
+        .map(|span| span.style.bg.unwrap())
+        .collect();
+    assert_eq!(
+        colors,
+        vec![Color::Blue, Color::Blue, Color::Red, Color::Blue]
+    );
+}
+
+#[test]
+fn resources_over_the_storage_of_the_city_are_wasted() {
+    use clivilization_engine::game::storage;
+
+    let config = config_with(|c| {
+        section(c, "map_x")["storage"] = 50.into();
+        let mut granary = resource_building("granary", 0);
+        granary["storage"] = 100.into();
+        add_building(c, granary);
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    game.state_mut().civilizations[0].resources.ressources = 200;
+    assert_eq!(storage::capacity(game.state(), 0), Some(50));
+    assert_eq!(storage::projected_waste(game.state(), 0), 150);
+
+    game.state_mut().on_turn_start(0);
+    assert_eq!(game.state().civilizations[0].resources.ressources, 50);
+    assert_eq!(game.state().metrics.totals(0).wasted, 150);
+
+    // a granary raises the storage
+    grant_building(&mut game, 0, "granary");
+    game.state_mut().civilizations[0].resources.ressources = 200;
+    assert_eq!(storage::capacity(game.state(), 0), Some(150));
+    game.state_mut().on_turn_start(0);
+    assert_eq!(game.state().civilizations[0].resources.ressources, 150);
+}
+
+/// Share the remains of eliminated civilizations and pay reparations at peace in `config`.
+fn war_terms(config: &mut serde_json::Value) {
+    add_section(
+        config,
+        serde_json::json!({ "elimination_share": 50, "reparations": 10 }),
+    );
+}
+
+fn war_config() -> String {
+    config_with(war_terms)
+}
+
+#[test]
+fn peace_recalls_the_armies_and_pays_reparations() {
+    let mut game = Game::from_string(&war_config()).unwrap();
+    game.state_mut().civilizations[0]
+        .city
+        .units
+        .units
+        .push(UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 3,
+        });
+    game.apply(Action::Attack {
+        target: Some("Noxus".to_string()),
+        amount: None,
+        units: Vec::new(),
+    });
+    let (demacia, noxus) = (
+        game.state().civilizations[0].resources.ressources,
+        game.state().civilizations[1].resources.ressources,
+    );
+
+    // peace is an offer the partner may refuse
+    game.register_ai(1, Box::new(Refuser));
+    game.apply(Action::Peace(Some("Noxus".to_string())));
+    game.submit_popup_input("White peace");
+    assert!(
+        game.state()
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .contains("Noxus refused the peace offer of Demacia")
+    );
+    game.state_mut().close_popup();
+    assert_eq!(game.state().travels.len(), 1);
+
+    game.register_ai(1, Box::new(advisor::HeuristicAi));
+    game.apply(Action::Peace(Some("Noxus".to_string())));
+    assert_eq!(game.state().popup.as_ref().unwrap().choices.len(), 3);
+    game.submit_popup_input("Pay reparations");
+    assert!(game.state().travels.is_empty());
+    assert_eq!(
+        game.state().civilizations[0].city.units.units[0].nb_units,
+        3
+    );
+    let paid = demacia / 10;
+    assert_eq!(
+        game.state().civilizations[0].resources.ressources,
+        demacia - paid
+    );
+    assert_eq!(
+        game.state().civilizations[1].resources.ressources,
+        noxus + paid
+    );
+}
+
+#[test]
+fn victors_share_the_remains_of_an_eliminated_civilization() {
+    use clivilization_engine::game::conquest::{self, Conquest};
+    use clivilization_engine::game::saves::SavedGame;
+
+    let mut game = Game::from_string(&war_config()).unwrap();
+    let state = game.state_mut();
+    let slots = (
+        state.civilizations[0].city.nb_slots_buildings,
+        state.civilizations[1].city.nb_slots_buildings,
+    );
+    let resources = (
+        state.civilizations[0].resources.ressources,
+        state.civilizations[1].resources.ressources,
+    );
+    state.civilizations[1].beaten_by.insert(0);
+    state.pending_conquests.push(Conquest {
+        attacker: 0,
+        defender: 1,
+        attacker_power: 3,
+        defender_power: 0,
+        army: Vec::new(),
+    });
+    // the victors are saved along with the game, unlike the event log
+    let mut game = SavedGame::of(&game).restore().unwrap();
+    let state = game.state_mut();
+    conquest::resolve(state, "Raze");
+
+    assert!(!state.civilizations[1].alive);
+    // half of the resources as raze loot, then half of the treasury left behind as spoils
+    let half = resources.1 / 2;
+    assert_eq!(
+        state.civilizations[0].resources.ressources,
+        resources.0 + half + half
+    );
+    assert_eq!(
+        state.civilizations[0].city.nb_slots_buildings,
+        slots.0 + slots.1 / 2
+    );
+}
+
+#[test]
+fn captured_cities_change_hands_and_revolt_without_a_garrison() {
+    use clivilization_engine::game::capture;
+    use clivilization_engine::game::combat;
+    use clivilization_engine::game::conquest;
+
+    let config = config_with(|c| {
+        war_terms(c);
+        section(c, "map_x")["capture"] = "on".into();
+        let cities = section(c, "cities")["cities"].as_array_mut().unwrap();
+        let mut piltover = cities[1].clone();
+        piltover["name"] = "Piltover".into();
+        piltover["x"] = 50.into();
+        piltover["y"] = 30.into();
+        cities.push(piltover);
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    let state = game.state_mut();
+    let take = |state: &mut _, attacker: usize, defender: usize| {
+        let army = vec![UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 10,
+        }];
+        let travel = Travel {
+            attacker,
+            defender,
+            amount: 10,
+            composition: army,
+            remaining: 0,
+            total: 1,
+            path: vec![(0, 0)],
+            step: 0,
+            progress: 0,
+        };
+        combat::battle(state, &travel);
+        let pending = &state.pending_conquests[0];
+        assert_eq!(
+            conquest::popup(state, pending).choices.last().unwrap(),
+            conquest::CAPTURE
+        );
+        conquest::resolve(state, conquest::CAPTURE);
+    };
+
+    // Demacia takes Noxus, its only city: Noxus is out, its city garrisoned by the army
+    take(state, 0, 1);
+    assert!(!state.civilizations[1].alive);
+    assert_eq!(state.civilizations[0].captured[0].city.name, "Noxus");
+    assert_eq!(
+        state.civilizations[0].captured[0].city.units.units[0].nb_units,
+        10
+    );
+    assert_eq!(capture::held(state, 0), 2);
+    assert!(!state.game_over);
+
+    // Piltover takes Demacia, which moves to Noxus
+    take(state, 2, 0);
+    assert!(state.civilizations[0].alive);
+    assert_eq!(state.civilizations[0].city.name, "Noxus");
+    assert_eq!((capture::held(state, 0), capture::held(state, 2)), (1, 2));
+
+    // left without a garrison, Demacia revolts and goes back to its civilization for good
+    state.civilizations[2].captured[0].city.units.units.clear();
+    state.on_turn_start(2);
+    assert_eq!((capture::held(state, 0), capture::held(state, 2)), (2, 1));
+    state.on_turn_start(0);
+    assert_eq!(capture::held(state, 0), 2);
+
+    // holding every city wins the game
+    take(state, 0, 2);
+    assert!(state.game_over);
+    assert_eq!(state.winner, Some(0));
+    assert!(state.events.since(0).any(
+        |(_, e)| matches!(e, GameEvent::Report { text, .. } if text.starts_with("Domination"))
+    ));
+}
+
+#[test]
+fn pillaged_buildings_are_drawn_from_the_map_seed() {
+    use clivilization_engine::game::conquest::{self, Conquest};
+
+    let pillaged = || {
+        let mut game = Game::from_string(&war_config()).unwrap();
+        let state = game.state_mut();
+        for level in 1..=6 {
+            let building = serde_json::from_value(
+                serde_json::json!({ "id_building": "tour", "level": level }),
+            )
+            .unwrap();
+            state.civilizations[1]
+                .city
+                .buildings
+                .elements
+                .push(building);
+        }
+        state.pending_conquests.push(Conquest {
+            attacker: 0,
+            defender: 1,
+            attacker_power: 3,
+            defender_power: 0,
+            army: Vec::new(),
+        });
+        conquest::resolve(state, "Pillage");
+        state.civilizations[1]
+            .city
+            .buildings
+            .elements
+            .iter()
+            .map(|b| b.level)
+            .collect::<Vec<_>>()
+    };
+    let left = pillaged();
+    assert_eq!(left.len(), 5);
+    assert_eq!(pillaged(), left);
+}
+
+#[test]
+fn razing_and_pillaging_cost_favor_with_the_other_civilizations() {
+    use clivilization_engine::game::conquest::{self, Conquest};
+    use clivilization_engine::game::diplomacy;
+
+    let config = config_with(|c| {
+        war_terms(c);
+        let cities = section(c, "cities")["cities"].as_array_mut().unwrap();
+        for (name, y) in [("Piltover", 30), ("Zaun", 35)] {
+            let mut city = cities[1].clone();
+            city["name"] = name.into();
+            city["x"] = 50.into();
+            city["y"] = y.into();
+            cities.push(city);
+        }
+        add_section(
+            c,
+            serde_json::json!({ "relations": [{ "city": "Piltover", "partner": "Noxus", "stance": "ALLIANCE" }] }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    let state = game.state_mut();
+    for friend in [2, 3] {
+        diplomacy::gift(state, 0, friend, 20).unwrap();
+        diplomacy::resolve(state, "Accept");
+    }
+    assert_eq!(
+        (state.diplomacy.favor(2, 0), state.diplomacy.favor(3, 0)),
+        (10, 10)
+    );
+
+    // only the allies of the pillaged city take offense
+    state.pending_conquests.push(Conquest {
+        attacker: 0,
+        defender: 1,
+        attacker_power: 3,
+        defender_power: 0,
+        army: Vec::new(),
+    });
+    conquest::resolve(state, "Pillage");
+    assert_eq!(
+        (state.diplomacy.favor(2, 0), state.diplomacy.favor(3, 0)),
+        (0, 10)
+    );
+    assert!(
+        state
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .contains("Piltover lost 15 favor toward Demacia")
+    );
+
+    // every survivor takes offense at a raze
+    state.popup = None;
+    state.pending_conquests.push(Conquest {
+        attacker: 0,
+        defender: 1,
+        attacker_power: 3,
+        defender_power: 0,
+        army: Vec::new(),
+    });
+    conquest::resolve(state, "Raze");
+    assert_eq!(state.diplomacy.favor(3, 0), 0);
+    assert!(
+        state
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .contains("Piltover, Zaun lost 10 favor toward Demacia")
+    );
+}
+
+#[test]
+fn ai_seeds_come_from_the_city_or_the_base_seed() {
+    let config = config_with(|c| section(c, "cities")["cities"][1]["ai_seed"] = 7.into());
+    let mut game = Game::from_string(&config).unwrap();
+    assert_eq!(game.ai_seed(0), None);
+    assert_eq!(game.ai_seed(1), Some(7));
+
+    game.set_ai_seed(100);
+    assert_eq!(game.ai_seed(0), Some(100));
+    assert_eq!(game.ai_seed(1), Some(7));
+}
+
+#[test]
+fn walls_and_garrisons_both_count_their_dead() {
+    use clivilization_engine::game::combat;
+    use clivilization_engine::game::diplomacy;
+    use clivilization_engine::game::state::Travel;
+
+    let config = config_with(|c| {
+        let noxus = &mut section(c, "cities")["cities"][1];
+        noxus["buildings"]["elements"] = serde_json::json!([{ "id_building": "tour", "level": 1 }]);
+        noxus["units"]["units"] = serde_json::json!([{ "id_units": "mele", "nb_units": 4 }]);
+        section(c, "buildings")["buildings"][0]["defense"] = 6.into();
+        section(c, "units")["units"][0]["defense"] = 2.into();
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    assert_eq!(game.state().buildings[0].name, "tour");
+    // 4 units defending with 2 each, behind a tower of 6
+    assert_eq!(diplomacy::defense_power(game.state(), 1), 14);
+
+    let travel = Travel {
+        attacker: 0,
+        defender: 1,
+        amount: 7,
+        composition: Vec::new(),
+        remaining: 0,
+        total: 1,
+        path: vec![(90, 20)],
+        step: 0,
+        progress: 0,
+    };
+    combat::battle(game.state_mut(), &travel);
+    // 7 against 14: the army loses two thirds of its units, the garrison one third
+    assert_eq!(
+        combat::casualties(7, 14, 7, 4),
+        combat::Casualties {
+            won: false,
+            attacker: 4,
+            defender: 1
+        }
+    );
+    assert_eq!(
+        game.state().civilizations[1].city.units.units[0].nb_units,
+        3
+    );
+    assert_eq!(
+        game.state().civilizations[0].city.units.units[0].nb_units,
+        3
+    );
+    assert!(game.state().pending_conquests.is_empty());
+}
+
+#[test]
+fn the_build_queue_only_builds_its_first_entry() {
+    let config = config_with(|c| section(c, "map_x")["build_queue"] = 3.into());
+    let mut game = Game::from_string(&config).unwrap();
+    game.apply(Action::Build(Some("tour".to_string())));
+    game.apply(Action::Build(Some("inhibiteur".to_string())));
+    let queue = |game: &Game| -> Vec<(String, u32)> {
+        let civ = game.state().current_civ().unwrap();
+        civ.constructions
+            .iter()
+            .map(|c| (c.id_building.clone(), c.remaining))
+            .collect()
+    };
+    let [(_, tour), (_, inhibiteur)] = queue(&game)[..] else {
+        panic!("two queued constructions expected");
+    };
+
+    let player = game.state().player_turn;
+    game.state_mut().on_turn_start(player);
+    assert_eq!(queue(&game)[0].1, tour - 1);
+    assert_eq!(queue(&game)[1].1, inhibiteur);
+
+    game.apply(Action::Queue(Some(QueueEdit::Up(2))));
+    assert_eq!(queue(&game)[0].0, "inhibiteur");
+    // a waiting entry is refunded in full
+    let resources = game.state().current_civ().unwrap().resources.ressources;
+    let cost = game.state().current_civ().unwrap().constructions[0].cost;
+    game.apply(Action::Queue(Some(QueueEdit::Cancel(1))));
+    assert_eq!(queue(&game).len(), 1);
+    assert_eq!(
+        game.state().current_civ().unwrap().resources.ressources,
+        resources + cost.cast_signed()
+    );
+}
+
+#[test]
+fn hired_batches_are_trained_side_by_side() {
+    let config = config_with(|c| {
+        section(c, "cities")["cities"][0]["buildings"]["elements"] = serde_json::json!([{ "id_building": "tour", "level": 1 }, { "id_building": "tour", "level": 1 }]);
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    let resources = game.state().current_civ().unwrap().resources.ressources;
+    assert_eq!(
+        Action::parse("hire mele 5").unwrap(),
+        Action::Hire {
+            unit: Some("mele".to_string()),
+            amount: Some(5)
+        }
+    );
+    game.apply(Action::parse("hire mele 5").unwrap());
+
+    let civ = game.state().current_civ().unwrap();
+    let recruitment = &civ.recruitments[0];
+    assert_eq!((recruitment.amount, recruitment.total), (5, 6));
+    assert_eq!(civ.resources.ressources, resources - 5 * 2);
+
+    // the popup asks for the unit, then for how many of them
+    let mut game = Game::from_string(&config).unwrap();
+    game.apply(Action::Hire {
+        unit: None,
+        amount: None,
+    });
+    game.submit_popup_input("mele");
+    let choices = game.state().popup.as_ref().unwrap().choices.clone();
+    // the usual batch sizes, then as many as the resources pay for
+    assert_eq!(choices[..4], ["mele x1", "mele x2", "mele x5", "mele x10"]);
+    assert_eq!(choices[4], format!("mele x{}", resources / 2));
+    game.submit_popup_input("3");
+    assert_eq!(
+        game.state().current_civ().unwrap().recruitments[0].amount,
+        5
+    );
+}
+
+#[test]
+fn the_advisor_warns_of_armies_before_idle_slots() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    assert_eq!(
+        advisor::hint(game.state(), 0).unwrap(),
+        "Vous avez 2 emplacements de bâtiment libres"
+    );
+    // AI players get no hint
+    assert_eq!(advisor::hint(game.state(), 1), None);
+
+    game.state_mut().travels.push(Travel {
+        attacker: 1,
+        defender: 0,
+        amount: 3,
+        composition: Vec::new(),
+        remaining: 1,
+        total: 4,
+        path: vec![(90, 20), (8, 10)],
+        step: 0,
+        progress: 0,
+    });
+    assert_eq!(
+        advisor::hint(game.state(), 0).unwrap(),
+        "Une armée de Noxus arrive au prochain tour"
+    );
+}
+
+#[test]
+fn suggestions_can_be_applied_from_their_popup() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    assert!(game.apply(Action::Suggest));
+    let popup = game.state().popup.clone().unwrap();
+    assert_eq!(popup.title, "Suggestion");
+    // free building slots come first when no rival threatens the city
+    let action = popup.choices[0]
+        .strip_prefix("Apply: ")
+        .unwrap()
+        .to_string();
+    assert!(action.starts_with("build "), "{action}");
+    assert!(
+        popup.prompt.contains("free building slot"),
+        "{}",
+        popup.prompt
+    );
+
+    game.submit_popup_input("1");
+    assert!(game.state().popup.is_none());
+    let civ = game.state().current_civ().unwrap();
+    assert_eq!(
+        format!("build {}", civ.constructions[0].id_building),
+        action
+    );
+}
+
+#[test]
+fn cancelled_orders_refund_their_unspent_share_and_recalled_armies_walk_home() {
+    let config = config_with(|c| {
+        section(c, "map_x")["cancel_refund"] = 50.into();
+        section(c, "cities")["cities"][0]["buildings"]["elements"] =
+            serde_json::json!([{ "id_building": "tour", "level": 1 }]);
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    let resources = |game: &Game| game.state().current_civ().unwrap().resources.ressources;
+    let start = resources(&game);
+
+    game.apply(Action::parse("hire mele 2").unwrap());
+    assert_eq!(resources(&game), start - 4);
+    game.apply(Action::parse("cancel hire").unwrap());
+    assert!(game.state().current_civ().unwrap().recruitments.is_empty());
+    assert_eq!(resources(&game), start - 2);
+    assert_eq!(Action::parse("cancel").unwrap_err().title, "Cancel");
+
+    game.state_mut().civilizations[0]
+        .city
+        .units
+        .units
+        .push(UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 3,
+        });
+    game.apply(Action::Attack {
+        target: Some("Noxus".to_string()),
+        amount: None,
+        units: Vec::new(),
+    });
+    game.state_mut().on_turn_start(1);
+    let marched = game.state().travels[0].step;
+    assert!(marched > 0);
+    game.apply(Action::Recall(Some("noxus".to_string())));
+    let travel = game.state().travels[0].clone();
+    assert!(travel.returning());
+    assert_eq!(travel.path.len(), marched + 1);
+
+    for _ in 0..=travel.remaining {
+        game.state_mut().on_turn_start(1);
+    }
+    assert!(game.state().travels.is_empty());
+    let units: u32 = game.state().civilizations[0]
+        .city
+        .units
+        .units
+        .iter()
+        .map(|u| u.nb_units)
+        .sum();
+    assert_eq!(units, 3);
+}
+
+#[test]
+fn reports_stay_in_the_event_log_and_the_chronicle() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.apply(Action::Loan(20));
+    game.state_mut().civilizations[0].resources.ressources = 0;
+    game.state_mut().on_turn_start(0);
+    assert_eq!(game.state().popup.as_ref().unwrap().title, "Loan");
+
+    // the next popup hides the report, the event log keeps it
+    game.state_mut()
+        .open_popup("Weather", "Storms ahead", vec![]);
+    let logged: Vec<String> = game
+        .state()
+        .events
+        .events_of(0)
+        .map(|(_, e)| e.describe(game.state()))
+        .collect();
+    assert!(logged.iter().any(|e| e.starts_with("Loan: ")), "{logged:?}");
+    assert!(
+        game.state()
+            .events
+            .events_of(1)
+            .all(|(_, e)| !matches!(e, GameEvent::Report { .. }))
+    );
+    assert!(
+        events::chronicle(game.state())
+            .lines()
+            .any(|l| l.contains("Loan: "))
+    );
+}
+
+#[test]
+fn loans_stop_at_the_debt_limit_and_defaults_cost_morale_and_units() {
+    use clivilization_engine::game::loans::{self, DESERTION_PERCENT, MAX_DEBT, MORALE_PENALTY};
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    for amount in [u32::MAX, MAX_DEBT + 1, MAX_DEBT] {
+        assert!(game.apply(Action::Loan(amount)));
+        assert!(
+            game.state()
+                .popup
+                .as_ref()
+                .unwrap()
+                .prompt
+                .contains("Debt limit reached")
+        );
+        game.state_mut().close_popup();
+    }
+    assert!(game.state().civilizations[0].loans.is_empty());
+    // 250 and its interest owe exactly the limit, one more resource is too much
+    assert!(!game.apply(Action::Loan(250)));
+    assert_eq!(loans::debt(&game.state().civilizations[0]), MAX_DEBT);
+    assert!(game.apply(Action::Loan(1)));
+    assert!(
+        game.state()
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .contains("301 owed")
+    );
+    game.state_mut().close_popup();
+
+    let state = game.state_mut();
+    state.civilizations[0].resources.ressources = 0;
+    state.civilizations[0].city.units.units = vec![UnitInstance {
+        id_units: "mele".to_string(),
+        nb_units: 8,
+    }];
+    let morale = state.civilizations[0].morale;
+    state.on_turn_start(0);
+    let civ = &game.state().civilizations[0];
+    assert_eq!(civ.morale, morale - MORALE_PENALTY);
+    let units: u32 = civ.city.units.units.iter().map(|u| u.nb_units).sum();
+    assert_eq!(units, 8 - 8 * DESERTION_PERCENT / 100);
+    assert_eq!(loans::debt(civ), MAX_DEBT);
+}
+
+#[test]
+fn armies_are_sent_unit_type_by_unit_type() {
+    use clivilization_engine::game::composition;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let garrison = vec![
+        UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 4,
+        },
+        UnitInstance {
+            id_units: "casteur".to_string(),
+            nb_units: 2,
+        },
+    ];
+    game.state_mut().civilizations[0].city.units.units = garrison.clone();
+    let action = Action::parse("attack Noxus mele=3 casteur=2").unwrap();
+    assert_eq!(Action::parse(&action.to_string()), Ok(action.clone()));
+    game.apply(action);
+
+    let travel = &game.state().travels[0];
+    assert_eq!(travel.amount, 5);
+    let sent: Vec<_> = travel
+        .composition
+        .iter()
+        .map(|u| (u.id_units.as_str(), u.nb_units))
+        .collect();
+    assert_eq!(sent, [("mele", 3), ("casteur", 2)]);
+    // the army fights with the attack of its units rather than their number
+    assert_eq!(composition::power(game.state(), travel), 3 + 2 * 2);
+    let left = &game.state().civilizations[0].city.units.units;
+    assert_eq!((left.len(), left[0].nb_units), (1, 1));
+
+    // the popup asks for each unit type in turn
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.state_mut().civilizations[0].city.units.units = garrison;
+    game.apply(Action::Attack {
+        target: None,
+        amount: None,
+        units: Vec::new(),
+    });
+    game.submit_popup_input("Noxus");
+    let popup = game.state().popup.clone().unwrap();
+    assert_eq!(popup.title, "Army");
+    assert_eq!(
+        popup.choices,
+        [
+            "Noxus: mele=0",
+            "Noxus: mele=1",
+            "Noxus: mele=2",
+            "Noxus: mele=4",
+            "Noxus: all units"
+        ]
+    );
+    game.submit_popup_input("Noxus: mele=2");
+    assert_eq!(game.state().popup.as_ref().unwrap().choices.len(), 3);
+    game.submit_popup_input("Noxus: mele=2 casteur=1");
+
+    assert!(game.state().popup.is_none());
+    let sent: Vec<_> = game.state().travels[0]
+        .composition
+        .iter()
+        .map(|u| (u.id_units.as_str(), u.nb_units))
+        .collect();
+    assert_eq!(sent, [("mele", 2), ("casteur", 1)]);
+}
+
+#[test]
+fn the_end_screen_sums_up_income_losses_and_battles() {
+    use clivilization_engine::game::{combat, metrics};
+
+    let config = config_with(|c| add_building(c, resource_building("mine", 5)));
+    let mut game = Game::from_string(&config).unwrap();
+    grant_building(&mut game, 0, "mine");
+    let garrison = |game: &Game| -> u32 {
+        game.state().civilizations[0]
+            .city
+            .units
+            .units
+            .iter()
+            .map(|u| u.nb_units)
+            .sum()
+    };
+    let before = garrison(&game);
+    let travel = Travel {
+        attacker: 0,
+        defender: 1,
+        amount: 4,
+        composition: vec![UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 4,
+        }],
+        remaining: 0,
+        total: 1,
+        path: vec![(90, 20)],
+        step: 0,
+        progress: 0,
+    };
+    combat::battle(game.state_mut(), &travel);
+    let (demacia, noxus) = (
+        metrics::summary(game.state(), 0),
+        metrics::summary(game.state(), 1),
+    );
+    assert_eq!((demacia.battles, noxus.battles), (1, 1));
+    // every unit of the army either died or came home
+    assert_eq!(demacia.units_lost + garrison(&game) - before, 4);
+
+    let resources = game.state().civilizations[0].resources.ressources;
+    game.state_mut().on_turn_start(0);
+    let earned = game.state().civilizations[0].resources.ressources - resources;
+    assert!(earned > 0);
+    assert_eq!(
+        metrics::summary(game.state(), 0).gathered,
+        earned.cast_unsigned()
+    );
+    assert_eq!(
+        game.state().metrics.gathered_series(0).last(),
+        Some(&u64::from(earned.cast_unsigned()))
+    );
+}
+
+#[test]
+fn units_over_the_stack_limits_wait_outside_or_are_refused() {
+    use clivilization_engine::game::stacks;
+
+    let stacked = |overflow: &str| -> Game {
+        let config = config_with(|c| {
+            let game = section(c, "map_x");
+            game["city_stack"] = 5.into();
+            game["army_stack"] = 3.into();
+            game["stack_overflow"] = overflow.into();
+            section(c, "cities")["cities"][0]["buildings"]["elements"] =
+                serde_json::json!([{ "id_building": "tour", "level": 1 }]);
+        });
+        let mut game = Game::from_string(&config).unwrap();
+        game.state_mut().civilizations[0].city.units.units = vec![UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 4,
+        }];
+        game
+    };
+
+    // trained units that do not fit wait outside, and armies leave the rest at home
+    let mut game = stacked("WAIT");
+    game.state_mut()
+        .start_batch_recruitment(0, "mele", 3)
+        .unwrap();
+    game.state_mut().civilizations[0].recruitments[0].remaining = 1;
+    game.state_mut().on_turn_start(0);
+    let civ = &game.state().civilizations[0];
+    assert_eq!((stacks::garrison(civ), stacks::waiting(civ)), (5, 2));
+    game.state_mut().start_attack(0, 1, Some(5)).unwrap();
+    assert_eq!(game.state().travels[0].amount, 3);
+    // the waiting units come in once the army freed room up
+    game.state_mut().on_turn_start(0);
+    let civ = &game.state().civilizations[0];
+    assert_eq!((stacks::garrison(civ), stacks::waiting(civ)), (4, 0));
+
+    let mut game = stacked("REFUSE");
+    assert!(
+        game.state_mut()
+            .start_batch_recruitment(0, "mele", 2)
+            .is_err()
+    );
+    assert!(stacks::check_recruitment(game.state(), 0, u32::MAX).is_err());
+    assert!(game.state_mut().start_attack(0, 1, Some(4)).is_err());
+    assert_eq!(stacks::garrison(&game.state().civilizations[0]), 4);
+}
+
+#[test]
+fn cities_load_a_public_persona_and_a_secret_agenda() {
+    let config = config_with(|c| {
+        let noxus = &mut section(c, "cities")["cities"][1];
+        noxus["persona"] = "Swain, the grand general".into();
+        noxus["agenda"] = "Attack the richest civilization first.".into();
+    });
+    let game = Game::from_string(&config).unwrap();
+
+    assert_eq!(
+        game.state().civilizations[1].city.agenda.as_deref(),
+        Some("Attack the richest civilization first.")
+    );
+    let view = game.make_ai_view();
+    assert_eq!(
+        view.players[1].persona.as_deref(),
+        Some("Swain, the grand general")
+    );
+    assert_eq!(view.players[0].persona, None);
+}
+
+#[test]
+fn the_income_breakdown_adds_up_to_the_next_turn_start() {
+    use clivilization_engine::game::income;
+
+    let config = config_with(|c| add_building(c, resource_building("mine", 5)));
+    let mut game = Game::from_string(&config).unwrap();
+    grant_building(&mut game, 0, "mine");
+    game.apply(Action::Loan(50));
+
+    let lines = income::breakdown(game.state(), 0);
+    assert!(lines.iter().any(|l| l.source == "mine" && l.amount == 5));
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.source == "loan installments" && l.amount < 0)
+    );
+    let resources = game.state().civilizations[0].resources.ressources;
+    game.state_mut().on_turn_start(0);
+    let change = game.state().civilizations[0].resources.ressources - resources;
+    assert_eq!(lines.iter().map(|l| l.amount).sum::<i32>(), change);
+
+    game.apply(Action::Income);
+    assert_eq!(game.state().popup.as_ref().unwrap().title, "Income");
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn key_chords_wait_for_their_next_key() {
+    use clivilization_engine::game::input::{CHORD_TIMEOUT, Command, InputPipeline, Key, NORMAL};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::time::Instant;
+
+    let key = |c| Key::plain(KeyCode::Char(c));
+    let now = Instant::now();
+    let mut input = InputPipeline::default();
+    assert!(input.feed(NORMAL, key('g'), now).is_empty());
+    assert!(input.hint().unwrap().contains("c: aller à la ville"));
+    assert_eq!(input.feed(NORMAL, key('c'), now), [Command::GotoCity]);
+    assert_eq!(input.hint(), None);
+    // a key continuing no chord runs the pending binding first
+    input.feed(NORMAL, key('g'), now);
+    assert_eq!(
+        input.feed(NORMAL, key('e'), now),
+        [Command::Graphs, Command::Events]
+    );
+    // so does waiting, while modifiers pick their own bindings
+    input.feed(NORMAL, key('g'), now);
+    assert_eq!(input.expire(now), None);
+    assert_eq!(input.expire(now + CHORD_TIMEOUT), Some(Command::Graphs));
+    let ctrl_g = Key::of(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL));
+    assert_eq!(input.feed(NORMAL, ctrl_g, now), [Command::Graphs]);
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.handle_key(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE));
+    assert!(game.state().show_events);
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn keys_held_during_an_ai_turn_are_replayed_unless_a_popup_opened() {
+    use clivilization_engine::game::input::HELD_KEYS;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.hold_key(key('e'));
+    game.hold_key(key('l'));
+    assert!(!game.state().show_events);
+    assert_eq!(game.release_held_keys(), None);
+    assert!(game.state().show_events && game.state().show_actions);
+
+    // keys over the limit are dropped, and a popup opened meanwhile discards the rest
+    for _ in 0..=HELD_KEYS {
+        game.hold_key(key('e'));
+    }
+    game.state_mut()
+        .open_popup("Battle", "Noxus attacked", vec![]);
+    let notice = game.release_held_keys().unwrap();
+    assert!(notice.starts_with(&format!("{} touche(s) ignorée(s)", HELD_KEYS + 1)));
+    assert!(game.state().show_events);
+    assert_eq!(game.release_held_keys(), None);
+}
+
+#[test]
+fn raiders_march_on_the_weakest_city_and_pillage_it() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({
+                "every": 3, "strength": 4, "growth": 2, "first_turn": 2, "pillage": 50
+            }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    let raiders = game.state().raiders.clone().unwrap();
+    assert_eq!(
+        (raiders.party(2), raiders.party(4), raiders.party(8)),
+        (Some(4), None, Some(8))
+    );
+
+    // Noxus holds a garrison, so the raiders of turn 2 go for Demacia
+    game.state_mut().civilizations[1]
+        .city
+        .units
+        .units
+        .push(UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 5,
+        });
+    game.state_mut().on_turn_start(0);
+    let travel = &game.state().travels[0];
+    assert!(travel.raiding());
+    assert_eq!((travel.defender, travel.amount), (0, 4));
+    assert!(
+        advisor::hint(game.state(), 0)
+            .unwrap()
+            .starts_with("Une armée de pillards arrive")
+    );
+
+    // the turn starts of Noxus walk them to the city without sending another party
+    for _ in 0..100 {
+        if game.state().travels.is_empty() {
+            break;
+        }
+        game.state_mut().on_turn_start(1);
+    }
+    assert!(game.state().travels.is_empty());
+    let raid = game.state().events.events_of(0).find_map(|(_, e)| match e {
+        GameEvent::Raid {
+            raiders,
+            won,
+            pillaged,
+            ..
+        } => Some((*raiders, *won, *pillaged)),
+        _ => None,
+    });
+    let (raiders, won, pillaged) = raid.unwrap();
+    assert_eq!((raiders, won), (4, true));
+    assert!(pillaged > 0);
+    assert!(game.state().civilizations[0].alive);
+    assert_eq!(game.state().events.unread(0).battles, 1);
+}
+
+#[test]
+fn gifts_are_limited_to_the_resources_of_the_giver() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let before: Vec<i32> = game
+        .state()
+        .civilizations
+        .iter()
+        .map(|c| c.resources.ressources)
+        .collect();
+    for amount in [3_000_000_000, u32::MAX, 101] {
+        assert!(game.apply(Action::Gift {
+            target: "noxus".to_string(),
+            amount
+        }));
+        assert!(
+            game.state()
+                .popup
+                .as_ref()
+                .unwrap()
+                .prompt
+                .contains("Not enough resources")
+        );
+        game.state_mut().close_popup();
+    }
+    let after: Vec<i32> = game
+        .state()
+        .civilizations
+        .iter()
+        .map(|c| c.resources.ressources)
+        .collect();
+    assert_eq!(after, before);
+    assert!(!game.state().game_over);
+}
+
+#[test]
+fn partners_accept_or_refuse_gifts_and_treaties() {
+    use clivilization_engine::game::diplomacy::{MAX_GIFT_FAVOR_PER_TURN, Treaty};
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.state_mut().civilizations[0].resources.ressources = 100;
+    game.register_ai(1, Box::new(Refuser));
+    game.apply(Action::Gift {
+        target: "noxus".to_string(),
+        amount: 10,
+    });
+    assert!(
+        game.state()
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .contains("Noxus refused the gift of 10 resources")
+    );
+    game.state_mut().close_popup();
+    game.apply(Action::Trade("noxus".to_string()));
+    game.state_mut().close_popup();
+    assert_eq!(game.state().civilizations[0].resources.ressources, 100);
+    assert_eq!(game.state().diplomacy.favor(1, 0), 0);
+    assert!(!game.state().diplomacy.has_treaty(Treaty::Trade, 0, 1));
+
+    // the default AI accepts, and gifts buy a limited favor per turn
+    game.register_ai(1, Box::new(advisor::HeuristicAi));
+    game.apply(Action::Gift {
+        target: "noxus".to_string(),
+        amount: 10,
+    });
+    assert!(
+        game.state()
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .contains("Noxus accepted 10 resources from Demacia")
+    );
+    game.state_mut().close_popup();
+    assert_eq!(game.state().civilizations[0].resources.ressources, 90);
+    assert_eq!(game.state().diplomacy.favor(1, 0), 5);
+    game.apply(Action::Gift {
+        target: "noxus".to_string(),
+        amount: 2 * MAX_GIFT_FAVOR_PER_TURN,
+    });
+    assert!(
+        game.state()
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .contains("at most 5 more favor")
+    );
+    game.state_mut().close_popup();
+    game.apply(Action::Trade("noxus".to_string()));
+    game.state_mut().close_popup();
+    assert!(game.state().diplomacy.has_treaty(Treaty::Trade, 0, 1));
+
+    // no diplomatic victory unless the game sets a threshold
+    assert!(!game.state().game_over);
+    game.state_mut().favor_threshold = 10;
+    game.apply(Action::Gift {
+        target: "noxus".to_string(),
+        amount: 10,
+    });
+    assert_eq!(game.state().diplomacy.favor(1, 0), 10);
+    assert!(game.state().game_over);
+}
+
+#[test]
+fn scenarios_start_at_war_and_script_the_diplomacy_of_later_turns() {
+    use clivilization_engine::game::diplomacy::{self, Treaty};
+    use clivilization_engine::game::war;
+
+    let relations = |relations: serde_json::Value| {
+        let config = config_with(|c| add_section(c, serde_json::json!({ "relations": relations })));
+        Game::from_string(&config)
+    };
+    let mut game = relations(serde_json::json!([
+        { "city": "Noxus", "partner": "Demacia", "stance": "WAR" },
+        { "city": "Noxus", "partner": "demacia", "stance": "PEACE", "turn": 3 },
+        { "city": "Demacia", "partner": "Noxus", "stance": "ALLIANCE", "turn": 4 }
+    ]))
+    .unwrap();
+    assert!(war::at_war(game.state(), 0, 1));
+    assert!(game.state().travels.is_empty());
+    assert!(diplomacy::sign(game.state_mut(), Treaty::Trade, 0, 1).is_err());
+    assert!(game.state().events.events_of(0).any(|(_, e)| *e
+        == GameEvent::WarDeclared {
+            attacker: 1,
+            defender: 0
+        }));
+
+    game.state_mut().turn = 3;
+    game.state_mut().on_turn_start(0);
+    assert!(!war::at_war(game.state(), 0, 1));
+    assert!(!game.state().diplomacy.has_treaty(Treaty::DefensePact, 0, 1));
+    game.state_mut().turn = 4;
+    game.state_mut().on_turn_start(0);
+    assert!(game.state().diplomacy.has_treaty(Treaty::DefensePact, 0, 1));
+
+    let Err(error) =
+        relations(serde_json::json!([{ "city": "Noxus", "partner": "Ionia", "stance": "WAR" }]))
+    else {
+        panic!("a relation with an unknown city should be rejected");
+    };
+    assert!(format!("{error:#}").contains("unknown city Ionia"));
+}
+
+#[test]
+fn ais_left_far_behind_offer_their_surrender_to_the_leader() {
+    use clivilization_engine::game::saves::SavedGame;
+    use clivilization_engine::game::surrender::{self, Offer};
+
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({ "turns": 2, "power": 50, "income": 50 }),
+        );
+        add_building(c, resource_building("farm", 10));
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    assert_eq!(surrender::desperation(game.state(), 1), 0);
+
+    // Demacia has an army and a farm, Noxus nothing
+    grant_building(&mut game, 0, "farm");
+    game.state_mut().civilizations[0]
+        .city
+        .units
+        .units
+        .push(UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 10,
+        });
+    assert_eq!(surrender::desperation(game.state(), 1), 100);
+    assert_eq!(surrender::desperation(game.state(), 0), 0);
+    game.state_mut().player_turn = 1;
+    assert_eq!(game.make_ai_view().desperation, 100);
+
+    // the turns spent behind and the offers waiting for an answer survive a save
+    let resave = |game: &Game| SavedGame::of(game).restore().unwrap();
+    game.state_mut().on_turn_start(1);
+    assert_eq!(surrender::pending(game.state()), None);
+    let mut game = resave(&game);
+    game.state_mut().on_turn_start(1);
+    assert_eq!(
+        surrender::pending(game.state()),
+        Some(Offer { from: 1, to: 0 })
+    );
+    let mut game = resave(&game);
+    assert_eq!(
+        surrender::pending(game.state()),
+        Some(Offer { from: 1, to: 0 })
+    );
+    let popup = surrender::popup(game.state(), Offer { from: 1, to: 0 });
+    assert_eq!(popup.choices, surrender::CHOICES);
+
+    let (demacia, noxus) = (
+        game.state().civilizations[0].resources.ressources,
+        game.state().civilizations[1].resources.ressources,
+    );
+    surrender::resolve(game.state_mut(), "Accept surrender");
+    assert_eq!(surrender::pending(game.state()), None);
+    assert!(!game.state().civilizations[1].alive);
+    assert_eq!(
+        game.state().civilizations[0].resources.ressources,
+        demacia + noxus
+    );
+    assert!(game.state().game_over);
+}
+
+#[test]
+fn bots_can_be_written_against_the_prelude_alone() {
+    use clivilization_engine::prelude::{Ai, AiView};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Ends its turn at once, counting the turns it was asked to play.
+    struct Idle(Arc<AtomicU32>);
+
+    impl Ai for Idle {
+        fn select_action(&mut self, view: &AiView, _civ_index: usize) -> Option<String> {
+            assert_eq!(view.players.len(), 2);
+            self.0.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+
+    let asked = Arc::new(AtomicU32::new(0));
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.register_ai(1, Box::new(Idle(asked.clone())));
+    let start = game.state().turn;
+    game.play_to_end(3);
+    assert_eq!(game.state().turn, start + 3);
+    assert!(asked.load(Ordering::Relaxed) >= 3);
+}
+
+#[test]
+fn unpaid_upkeep_disbands_units_then_shuts_buildings_down() {
+    use clivilization_engine::game::upkeep;
+
+    let config = config_with(|c| {
+        let units = &mut section(c, "units")["units"];
+        units[0]["upkeep"] = 3.into();
+        units[1]["upkeep"] = 5.into();
+        let buildings = &mut section(c, "buildings")["buildings"];
+        buildings[0]["maintenance"] = 10.into();
+        buildings[1]["maintenance"] = 20.into();
+        section(c, "cities")["cities"][0]["buildings"]["elements"] = serde_json::json!([{ "id_building": "tour", "level": 1 }, { "id_building": "inhibiteur", "level": 1 }]);
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    let state = game.state_mut();
+    state.civilizations[0].city.units.units = vec![
+        UnitInstance {
+            id_units: "mele".to_string(),
+            nb_units: 10,
+        },
+        UnitInstance {
+            id_units: "casteur".to_string(),
+            nb_units: 4,
+        },
+    ];
+    state.civilizations[0].resources.ressources = 100;
+    assert_eq!(upkeep::due(state, 0), 80);
+
+    state.on_turn_start(0);
+    assert_eq!(state.civilizations[0].resources.ressources, 20);
+    assert!(state.popup.is_none());
+
+    // 20 left for 80 due: the casteurs go first, then the meles, then the costliest building
+    state.on_turn_start(0);
+    assert!(state.civilizations[0].city.units.units.is_empty());
+    assert!(state.civilizations[0].disabled.contains("inhibiteur"));
+    assert_eq!(state.civilizations[0].resources.ressources, 10);
+    assert_eq!(state.metrics.totals(0).upkeep, 90);
+    let popup = state.popup.take().unwrap();
+    assert_eq!(popup.title, "Upkeep");
+    assert!(popup.prompt.starts_with("14 units disbanded"));
+
+    // paying again brings the building back
+    state.civilizations[0].resources.ressources = 100;
+    state.on_turn_start(0);
+    assert!(state.civilizations[0].disabled.is_empty());
+    assert_eq!(state.civilizations[0].resources.ressources, 70);
+    assert_eq!(
+        state.popup.take().unwrap().prompt,
+        "Back in service: inhibiteur."
+    );
+}
+
+#[test]
+fn tournament_csv_quotes_the_names_of_the_civilizations() {
+    use clivilization_engine::game::tournament::{CivStats, TournamentStats};
+
+    let civ = |name: &str| CivStats {
+        name: name.to_string(),
+        wins: 1,
+        win_rate: 0.5,
+        average_spent: 10.0,
+    };
+    let stats = TournamentStats {
+        games: 2,
+        draws: 0,
+        average_turns: 12.0,
+        civs: vec![civ("Noxus"), civ("Bandle, City"), civ("The \"Void\"")],
+        outcomes: Vec::new(),
+    };
+    let csv = stats.to_csv();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows[1], "Noxus,1,0.500,10.0,2,0,12.0");
+    assert_eq!(rows[2], "\"Bandle, City\",1,0.500,10.0,2,0,12.0");
+    assert_eq!(rows[3], "\"The \"\"Void\"\"\",1,0.500,10.0,2,0,12.0");
+}
+
+#[test]
+fn aliases_are_expanded_before_the_inputs_are_recorded() {
+    use clivilization_engine::game::debugger;
+    use clivilization_engine::game::preferences::Preferences;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let mut preferences = Preferences::default();
+    preferences
+        .aliases
+        .insert("bt".to_string(), "build tour".to_string());
+    game.set_preferences(preferences);
+    game.enable_debugger();
+    game.apply_action("bt");
+    assert_eq!(
+        game.state().current_civ().unwrap().constructions[0].id_building,
+        "tour"
+    );
+    // alias commands are no input of the game
+    game.apply_action("alias i = build inhibiteur");
+    game.state_mut().popup = None;
+    game.apply_action("i");
+
+    let steps = debugger::command(&mut game, &["steps"]).unwrap();
+    assert!(steps.starts_with("2 steps recorded"), "{steps}");
+    assert!(steps.contains("Action(\"build tour\")"), "{steps}");
+    assert!(steps.contains("Action(\"build inhibiteur\")"), "{steps}");
+}
+
+#[test]
+fn idle_construction_blocks_the_end_of_turn_while_a_building_is_affordable() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({ "block_end_if": ["IDLE_CONSTRUCTION"] }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    let turn = game.state().turn;
+    assert!(game.apply(Action::EndTurn));
+    let popup = game.state().popup.clone().unwrap();
+    assert_eq!(popup.title, "End Turn");
+    assert!(popup.prompt.contains("A construction can be started"));
+    assert_eq!(game.state().turn, turn);
+    // checking the buildings starts none of them
+    assert!(game.state().civilizations[0].constructions.is_empty());
+
+    game.state_mut().close_popup();
+    game.state_mut().civilizations[0].resources.ressources = 0;
+    game.apply(Action::EndTurn);
+    assert_ne!(game.state().player_turn, 0);
+}
+
+#[test]
+fn tutorials_only_accept_the_action_of_their_current_step() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({ "steps": [
+                { "text": "Build a tower", "expect": "build", "gate": "BUILDING", "target": "tour" },
+                { "text": "End your turn", "expect": "end", "gate": "END_TURN" }
+            ] }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    let step = |game: &Game| game.state().tutorial.as_ref().unwrap().current;
+
+    assert!(game.apply(Action::Hire {
+        unit: Some("mele".to_string()),
+        amount: Some(1),
+    }));
+    let popup = game.state().popup.as_ref().unwrap();
+    assert_eq!(popup.title, "Tutorial");
+    assert!(popup.prompt.ends_with("Expected action: build"));
+    assert!(game.state().civilizations[0].recruitments.is_empty());
+    game.submit_popup_input("");
+
+    game.apply(Action::Build(Some("tour".to_string())));
+    assert_eq!(step(&game), 1);
+    game.apply(Action::EndTurn);
+    assert!(game.state().tutorial.as_ref().unwrap().is_finished());
+    assert!(
+        game.state()
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .starts_with("Tutorial complete!")
+    );
+}
+
+#[test]
+fn foreign_influence_costs_income_and_makes_units_defect() {
+    use clivilization_engine::game::influence::{self, BASE_INFLUENCE};
+    use clivilization_engine::game::turn_rules::projected_income;
+
+    let config = config_with(|c| {
+        let cities = section(c, "cities")["cities"].as_array_mut().unwrap();
+        cities[1]["x"] = 14.into();
+        cities[1]["y"] = 10.into();
+        add_building(c, resource_building("ferme", 10));
+        let mut temple = resource_building("temple", 30);
+        temple["production"]["prod_type"] = "INFLUENCE".into();
+        add_building(c, temple);
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    grant_building(&mut game, 0, "ferme");
+    game.state_mut().civilizations[0].city.units.units = vec![UnitInstance {
+        id_units: "mele".to_string(),
+        nb_units: 10,
+    }];
+    let state = game.state_mut();
+    state.refresh_producers(0);
+    // a rival weaker than the city where it stands exerts no pressure
+    assert_eq!(influence::apply_pressure(state, 0), None);
+
+    grant_building(&mut game, 1, "temple");
+    let state = game.state_mut();
+    let (own, foreign) = influence::pressure(state, 0);
+    assert_eq!(own, BASE_INFLUENCE);
+    assert!(foreign >= 2 * own, "{foreign}");
+    let (resources, income) = (
+        state.civilizations[0].resources.ressources,
+        projected_income(state, 0),
+    );
+    assert!(income > 0);
+    let report = influence::apply_pressure(state, 0).unwrap();
+    assert!(report.ends_with("1 units defected to Noxus"), "{report}");
+    assert_eq!(
+        state.civilizations[0].resources.ressources,
+        resources - income / 2
+    );
+    assert_eq!(state.civilizations[0].city.units.units[0].nb_units, 9);
+    assert_eq!(state.civilizations[1].city.units.units[0].nb_units, 1);
+}
+
+#[test]
+fn storms_hold_back_the_armies_under_them() {
+    use clivilization_engine::game::armies;
+    use clivilization_engine::game::weather::{self, Weather, WeatherMap};
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let state = game.state_mut();
+    let start = state.turn;
+    let (turn, storm) = (start..start + 50)
+        .find_map(|turn| {
+            let weather = WeatherMap::new(&state.map, turn);
+            let width = i32::try_from(state.map.width).unwrap();
+            let height = i32::try_from(state.map.height).unwrap();
+            (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .find(|&(x, y)| weather.at(x, y) == Some(Weather::Storm))
+                .map(|tile| (turn, tile))
+        })
+        .expect("no storm in 50 turns");
+    state.turn = turn;
+    // the weather comes from the seed, the same for every player
+    let other = Game::from_string(CONFIG).unwrap();
+    assert_eq!(
+        WeatherMap::new(&other.state().map, turn).at(storm.0, storm.1),
+        Some(Weather::Storm)
+    );
+
+    let army = |from: (i32, i32)| Travel {
+        attacker: 0,
+        defender: 1,
+        amount: 3,
+        composition: Vec::new(),
+        remaining: 2,
+        total: 2,
+        path: vec![from, (from.0 + 1, from.1), (from.0 + 2, from.1)],
+        step: 0,
+        progress: 0,
+    };
+    let demacia = (8, 10);
+    assert_eq!(
+        WeatherMap::new(&state.map, turn).at(demacia.0, demacia.1),
+        None
+    );
+    state.travels = vec![army(storm), army(demacia)];
+    assert!(weather::forecast(state, 0).contains("held back by a storm on its tile"));
+    armies::advance(state);
+    let moved = |t: &Travel| (t.step, t.progress) != (0, 0);
+    assert!(!moved(&state.travels[0]));
+    assert!(moved(&state.travels[1]));
+}
+
+#[test]
+fn demolished_buildings_free_their_slot_once_confirmed() {
+    use clivilization_engine::game::state::DEMOLISH_REFUND_PERCENT;
+
+    let config = config_with(|c| {
+        let mut farm = resource_building("ferme", 2);
+        farm["cost"] = 10.into();
+        add_building(c, farm);
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    grant_building(&mut game, 0, "ferme");
+    assert!(
+        game.ai_possible_actions(0)
+            .contains(&"demolish ferme".to_string())
+    );
+    let resources = game.state().civilizations[0].resources.ressources;
+    let owned = |game: &Game| game.state().civilizations[0].city.buildings.elements.len();
+
+    assert!(game.apply(Action::Demolish(Some("Ferme".to_string()))));
+    assert_eq!(
+        game.state().popup.as_ref().unwrap().title,
+        "Confirm Demolish"
+    );
+    game.submit_popup_input("Cancel");
+    assert_eq!(owned(&game), 1);
+
+    game.apply(Action::Demolish(Some("ferme".to_string())));
+    game.submit_popup_input("Demolish ferme");
+    assert_eq!(owned(&game), 0);
+    let refund = i32::try_from(10 * DEMOLISH_REFUND_PERCENT / 100).unwrap();
+    assert_eq!(
+        game.state().civilizations[0].resources.ressources,
+        resources + refund
+    );
+}
+
+#[test]
+fn taxes_raise_income_at_the_expense_of_constructions() {
+    use clivilization_engine::game::turn_rules::projected_income;
+
+    let config = config_with(|c| {
+        section(c, "cities")["cities"][1]["policy"] = serde_json::json!({ "tax": 80 });
+        add_building(c, resource_building("ferme", 10));
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    assert_eq!(game.state().civilizations[1].policy.tax, 80);
+    grant_building(&mut game, 0, "ferme");
+    game.state_mut().refresh_producers(0);
+    let neutral = projected_income(game.state(), 0);
+
+    game.apply(Action::Build(Some("tour".to_string())));
+    assert!(!game.apply_action("policy tax 100"));
+    assert_eq!(projected_income(game.state(), 0), neutral * 3 / 2);
+    let remaining = game.state().civilizations[0].constructions[0].remaining;
+    game.state_mut().on_turn_start(0);
+    assert_eq!(
+        game.state().civilizations[0].constructions[0].remaining,
+        remaining
+    );
+    // without taxes, the two turns of the tower are built in one
+    assert_eq!(remaining, 2);
+    game.apply_action("policy tax 0");
+    game.state_mut().on_turn_start(0);
+    let demacia = &game.state().civilizations[0];
+    assert!(demacia.constructions.is_empty());
+    assert!(
+        demacia
+            .city
+            .buildings
+            .elements
+            .iter()
+            .any(|b| b.id_building == "tour")
+    );
+}
+
+#[test]
+fn the_debugger_goes_back_to_the_state_before_any_step() {
+    use clivilization_engine::game::debugger;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    assert!(debugger::command(&mut game, &["steps"]).is_err());
+    game.enable_debugger();
+    let start = game.state().civilizations[0].resources.ressources;
+    game.apply(Action::Build(Some("tour".to_string())));
+    let built = game.state().civilizations[0].resources.ressources;
+    game.apply(Action::Loan(20));
+
+    let steps = debugger::command(&mut game, &["steps"]).unwrap();
+    assert!(steps.starts_with("2 steps recorded\n0: turn"), "{steps}");
+    assert!(steps.ends_with(r#"Action("loan 20")"#), "{steps}");
+    let diff = debugger::command(&mut game, &["diff", "0", "1"]).unwrap();
+    assert!(diff.starts_with("Steps 0 -> 1:"), "{diff}");
+    assert!(diff.contains("tour"), "{diff}");
+    assert!(
+        debugger::command(&mut game, &["diff", "1", "1"])
+            .unwrap()
+            .starts_with("No difference")
+    );
+
+    debugger::command(&mut game, &["jump", "1"]).unwrap();
+    assert_eq!(game.state().civilizations[0].resources.ressources, built);
+    assert!(game.state().civilizations[0].loans.is_empty());
+    debugger::command(&mut game, &["jump", "0"]).unwrap();
+    assert_eq!(game.state().civilizations[0].resources.ressources, start);
+    let error = debugger::command(&mut game, &["jump", "1"]).unwrap_err();
+    assert_eq!(error.to_string(), "no step recorded yet");
+    assert!(debugger::command(&mut game, &["rewind"]).is_err());
+}
+
+#[test]
+fn action_points_limit_the_orders_of_a_turn() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({ "action_points": 2, "block_end_if": [] }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    grant_building(&mut game, 0, "tour");
+    game.state_mut().refresh_producers(0);
+    game.state_mut().civilizations[0].resources.ressources = 100;
+    let points = |game: &Game| game.state().civilizations[0].action_points;
+    assert_eq!(points(&game), 2);
+
+    game.apply_action("build inhibiteur");
+    game.apply_action("hire mele 1");
+    assert_eq!(points(&game), 0);
+    assert!(game.apply_action("hire mele 1"));
+    let popup = game.state().popup.as_ref().unwrap();
+    assert!(
+        popup
+            .prompt
+            .contains("No action point left this turn (2 per turn)")
+    );
+    game.submit_popup_input("");
+    let demacia = &game.state().civilizations[0];
+    assert_eq!(
+        (demacia.constructions.len(), demacia.recruitments.len()),
+        (1, 1)
+    );
+
+    // the points come back at the next turn of the city
+    game.apply_action("end");
+    game.state_mut().on_turn_start(0);
+    assert_eq!(points(&game), 2);
+}
+
+#[test]
+#[cfg(feature = "tui")]
+fn renamed_cities_keep_their_name_in_saves_and_on_the_map() {
+    use clivilization_engine::game::map_view;
+    use clivilization_engine::game::saves::SavedGame;
+    use ratatui::layout::Rect;
+    use ratatui::style::Color;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let rename = |game: &mut Game, city: &str, name: &str| {
+        game.apply(Action::Rename {
+            city: city.to_string(),
+            name: name.to_string(),
+        });
+        game.state().popup.as_ref().map(|p| p.prompt.clone())
+    };
+    assert_eq!(
+        rename(&mut game, "Noxus", "Zaun").as_deref(),
+        Some("You can only rename your own city")
+    );
+    game.submit_popup_input("");
+    assert_eq!(
+        rename(&mut game, "demacia", "NOXUS").as_deref(),
+        Some("Another city is already named NOXUS")
+    );
+    game.submit_popup_input("");
+    assert_eq!(rename(&mut game, "demacia", "Kalamanthis-Ville"), None);
+    let restored = SavedGame::of(&game).restore().unwrap();
+    assert_eq!(restored.state().city_name(0), "Kalamanthis-Ville");
+
+    // zoomed in, long names are cut next to the marker of the city
+    let state = game.state_mut();
+    state.zoom_level = 2;
+    state.camera_x = 0;
+    state.camera_y = 10;
+    let buffer = vec![vec![Color::Green; state.map.width]; state.map.height];
+    let lines = map_view::render_buffer(state, Rect::default(), &buffer, 20, 2);
+    let text: String = lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
+    assert!(text.contains("Kalamanthis…"), "{text}");
+}
+
+#[test]
+fn dynamic_difficulty_helps_the_ais_left_behind_by_the_human() {
+    use clivilization_engine::game::difficulty::{self, MAX_ADJUSTMENT};
+    use clivilization_engine::game::turn_rules::projected_income;
+
+    let config = config_with(|c| add_building(c, resource_building("ferme", 10)));
+    let mut game = Game::from_string(&config).unwrap();
+    grant_building(&mut game, 1, "ferme");
+    game.state_mut().refresh_producers(1);
+    game.state_mut().civilizations[0].city.units.units = vec![UnitInstance {
+        id_units: "mele".to_string(),
+        nb_units: 10,
+    }];
+    let income = projected_income(game.state(), 1);
+    game.enable_dynamic_difficulty(3);
+    let percent = |game: &Game| game.state().difficulty.as_ref().unwrap().percent(1);
+    assert_eq!(percent(&game), MAX_ADJUSTMENT);
+    assert_eq!(
+        projected_income(game.state(), 1),
+        income * (100 + MAX_ADJUSTMENT) / 100
+    );
+    let panel = difficulty::describe(game.state());
+    assert!(
+        panel[1].starts_with("Noxus: puissance 0 contre"),
+        "{panel:?}"
+    );
+    assert!(panel[1].ends_with("production +30%"), "{panel:?}");
+
+    // the AI outgrowing the human is held back, once the interval has passed
+    let state = game.state_mut();
+    state.civilizations[1].city.units.units = vec![UnitInstance {
+        id_units: "casteur".to_string(),
+        nb_units: 20,
+    }];
+    state.turn += 2;
+    difficulty::update(state);
+    assert_eq!(percent(&game), MAX_ADJUSTMENT);
+    let state = game.state_mut();
+    state.turn += 1;
+    difficulty::update(state);
+    assert!(percent(&game) < 0);
+}
+
+#[test]
+fn traits_give_each_civilization_its_own_strengths() {
+    let traits = |extra: &str| {
+        config_with(|c| {
+            add_section(
+                c,
+                serde_json::json!({ "traits": [{
+                    "city": "Demacia", "label": "Bâtisseurs", "building_discount": 50,
+                    "discounted_buildings": { "values": ["inhibiteur"] },
+                    "extra_units": { "units": [{ "id_units": extra, "nb_units": 3 }] }
+                }] }),
+            );
+        })
+    };
+    let Err(error) = Game::from_string(&traits("dragon")) else {
+        panic!("traits of an unknown unit accepted")
+    };
+    assert!(format!("{error:#}").contains("traits of Demacia: unknown unit dragon"));
+
+    let mut game = Game::from_string(&traits("mele")).unwrap();
+    let demacia = &game.state().civilizations[0];
+    assert_eq!(demacia.city.units.units[0].nb_units, 3);
+    assert_eq!(
+        demacia.modifiers.describe(),
+        ["Bâtisseurs", "inhibiteur -50%"]
+    );
+    assert!(game.state().civilizations[1].modifiers.is_empty());
+
+    // the discount only applies to the buildings it names
+    game.state_mut().civilizations[0].resources.ressources = 100;
+    game.apply_action("build inhibiteur");
+    assert_eq!(game.state().civilizations[0].resources.ressources, 98);
+    let mut game = Game::from_string(&traits("mele")).unwrap();
+    game.state_mut().civilizations[0].resources.ressources = 100;
+    game.apply_action("build tour");
+    assert_eq!(game.state().civilizations[0].resources.ressources, 97);
+}
@@ -0,0 +1,59 @@
+//! Configurations shared by the integration tests: `game.json` and the edits made to it.
+
+// each test crate uses only some of the helpers
+#![allow(dead_code)]
+
+use clivilization_engine::game::Game;
+use serde_json::Value;
+
+pub const CONFIG: &str = include_str!("../../game.json");
+
+/// `game.json` with `edit` applied to its JSON.
+pub fn config_with(edit: impl FnOnce(&mut Value)) -> String {
+    let mut config: Value = serde_json::from_str(CONFIG).unwrap();
+    edit(&mut config);
+    config.to_string()
+}
+
+/// Section of `config` holding `key`.
+pub fn section<'a>(config: &'a mut Value, key: &str) -> &'a mut Value {
+    config["sections"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|s| s.get(key).is_some())
+        .unwrap()
+}
+
+/// Add `section` after the sections of `config`.
+pub fn add_section(config: &mut Value, section: Value) {
+    config["sections"].as_array_mut().unwrap().push(section);
+}
+
+/// Definition of a `name` building without prerequisites producing `amount` resources a turn.
+pub fn resource_building(name: &str, amount: u32) -> Value {
+    serde_json::json!({
+        "name": name, "cost": 3, "build_time": 2, "slots": 1,
+        "production": { "prod_type": "RESSOURCE", "amount": amount, "time": 1, "cost": 0 },
+        "prerequisites": { "prereqs": [] }
+    })
+}
+
+/// Add the definition of `building` to the buildings of `config`.
+pub fn add_building(config: &mut Value, building: Value) {
+    section(config, "buildings")["buildings"]
+        .as_array_mut()
+        .unwrap()
+        .push(building);
+}
+
+/// Put a finished `name` building in the city of `civ_index`.
+pub fn grant_building(game: &mut Game, civ_index: usize, name: &str) {
+    let building =
+        serde_json::from_value(serde_json::json!({ "id_building": name, "level": 1 })).unwrap();
+    game.state_mut().civilizations[civ_index]
+        .city
+        .buildings
+        .elements
+        .push(building);
+}
@@ -0,0 +1,116 @@
+//! Victory conditions: the limits of `[victory_conditions]`, the objectives of puzzles and those
+//! registered by programs embedding the engine.
+
+use clivilization_engine::game::Game;
+use clivilization_engine::game::scoring;
+use clivilization_engine::game::state::GameState;
+use clivilization_engine::game::victory::Outcome;
+use common::{CONFIG, add_section, config_with};
+
+mod common;
+
+#[test]
+fn custom_condition_ends_the_game() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let start = game.state().turn;
+    game.add_victory_condition(move |state: &GameState| {
+        (state.turn >= start + 2).then(|| Outcome {
+            winner: Some(0),
+            message: "Time is up".to_string(),
+        })
+    });
+    for _ in 0..10 {
+        if game.state().game_over {
+            break;
+        }
+        game.apply_action("end");
+    }
+    assert!(game.state().game_over);
+    assert_eq!(game.state().winner, Some(0));
+    assert_eq!(game.state().turn, start + 2);
+}
+
+#[test]
+fn turn_and_spending_limits_end_the_game_on_the_best_score() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let start = game.state().turn;
+    game.state_mut().nb_turns = start.cast_unsigned();
+    game.state_mut().civilizations[1].resources.ressources += 50;
+    for _ in 0..10 {
+        if game.state().game_over {
+            break;
+        }
+        game.apply_action("end");
+    }
+    let state = game.state();
+    assert_eq!(state.turn, start + 1);
+    assert!(scoring::base_score(state, 1) > scoring::base_score(state, 0));
+    assert_eq!(state.winner, Some(1));
+    let popup = state.popup.as_ref().unwrap();
+    assert!(popup.prompt.starts_with(&format!(
+        "Turn limit reached ({start} turns): Noxus wins with"
+    )));
+
+    // spending the threshold ends the game at the next turn start, equal scores are a draw
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.state_mut().resources_spent = 10;
+    game.state_mut().civilizations[0].spend(10);
+    game.state_mut().civilizations[1].resources.ressources -= 10;
+    game.apply_action("end");
+    let state = game.state();
+    assert!(state.game_over);
+    assert_eq!(state.winner, None);
+    assert!(
+        state
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .starts_with("Demacia spent 10 resources: draw between")
+    );
+}
+
+#[test]
+fn puzzles_spend_their_budget_and_fail_once_their_turns_run_out() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({
+                "objective": "ELIMINATE", "target": "Noxus", "max_builds": 1, "max_turns": 2
+            }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    game.state_mut().civilizations[0].resources.ressources = 100;
+    game.apply_action("build tour");
+    assert!(game.apply_action("build inhibiteur"));
+    assert!(
+        game.state()
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .contains("Build budget exhausted (1 builds)")
+    );
+    game.submit_popup_input("");
+    assert_eq!(game.state().civilizations[0].constructions.len(), 1);
+    let summary = game.state().puzzle.as_ref().unwrap().summary(game.state());
+    assert_eq!(summary, "Éliminer Noxus, tours 0/2, constructions 1/1");
+
+    for _ in 0..10 {
+        if game.state().game_over {
+            break;
+        }
+        game.apply_action("end");
+    }
+    let state = game.state();
+    assert_eq!(state.winner, None);
+    assert!(
+        state
+            .popup
+            .as_ref()
+            .unwrap()
+            .prompt
+            .starts_with("Puzzle failed: objective not reached within 2 turns")
+    );
+}
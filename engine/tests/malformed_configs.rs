@@ -0,0 +1,312 @@
+//! Regression tests for configurations that used to panic and leave the terminal in raw mode, or
+//! that the DSL editor pushes while they are being typed.
+
+use clivilization_engine::game::state::Travel;
+use clivilization_engine::game::{Game, RandomAi, combat, composition, supply};
+use common::{CONFIG, add_section, config_with, section};
+use serde_json::Value;
+
+mod common;
+
+fn load_error(config: &str) -> String {
+    match Game::from_string(config) {
+        Ok(_) => panic!("configuration should be rejected"),
+        Err(e) => format!("{e:#}"),
+    }
+}
+
+#[test]
+fn city_outside_the_map_is_rejected() {
+    let config = config_with(|c| {
+        section(c, "cities")["cities"][0]["x"] = Value::from(500);
+    });
+    assert!(load_error(&config).contains("outside the 160x40 map"));
+}
+
+#[test]
+fn empty_map_is_rejected() {
+    let config = config_with(|c| {
+        section(c, "map_x")["map_x"] = Value::from(0);
+    });
+    assert!(load_error(&config).contains("invalid map size"));
+}
+
+#[test]
+fn configuration_without_cities_is_rejected() {
+    let config = config_with(|c| {
+        section(c, "cities")["cities"] = Value::Array(Vec::new());
+    });
+    assert!(load_error(&config).contains("no city"));
+}
+
+#[test]
+fn huge_numbers_are_rejected() {
+    let config = config_with(|c| {
+        section(c, "cities")["cities"][0]["units"]["units"] =
+            serde_json::json!([{"id_units": "mele", "nb_units": u32::MAX}]);
+    });
+    assert!(load_error(&config).contains("invalid number"));
+}
+
+#[test]
+fn ai_seeds_take_any_value_of_their_type() {
+    let config = config_with(|c| {
+        section(c, "cities")["cities"][1]["ai_seed"] = Value::from(u32::MAX);
+    });
+    let game = Game::from_string(&config).unwrap();
+    assert_eq!(game.ai_seed(1), Some(u64::from(u32::MAX)));
+
+    let config = config_with(|c| {
+        section(c, "cities")["cities"][1]["ai_seed"] = Value::from(u64::from(u32::MAX) + 1);
+    });
+    assert!(load_error(&config).contains("failed to parse config JSON"));
+}
+
+#[test]
+fn tech_unlocking_an_unknown_building_is_rejected() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({
+                "techs": [{ "name": "forge", "cost": 1, "research_time": 1, "unlocks": { "values": ["moulin"] } }]
+            }),
+        );
+    });
+    assert!(load_error(&config).contains("unlocks unknown building or unit moulin"));
+}
+
+#[test]
+fn city_outside_every_region_is_rejected() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({
+                "regions": [{ "name": "west", "x": 0, "y": 0, "width": 50, "height": 40 }],
+                "crossings": []
+            }),
+        );
+    });
+    assert!(load_error(&config).contains("city Noxus is outside every region"));
+}
+
+#[test]
+fn map_event_outside_the_map_is_rejected() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({
+                "events": [{ "name": "volcano", "turn": 3, "x": 200, "y": 10, "terraform": { "terrain": "MOUNTAIN" } }]
+            }),
+        );
+    });
+    assert!(load_error(&config).contains("map event volcano is outside the 160x40 map"));
+}
+
+#[test]
+fn travel_to_an_unknown_civilization_is_dropped() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.state_mut().travels.push(Travel {
+        attacker: 0,
+        defender: 42,
+        amount: 1,
+        composition: Vec::new(),
+        remaining: 0,
+        total: 1,
+        path: vec![(0, 0)],
+        step: 0,
+        progress: 0,
+    });
+    game.state_mut().on_turn_start(0);
+    assert!(game.state().travels.is_empty());
+}
+
+#[test]
+fn attrition_over_a_hundred_percent_is_capped() {
+    let config = config_with(|c| {
+        section(c, "map_x")["attrition"] = Value::from(1_000_000);
+    });
+    let game = Game::from_string(&config).unwrap();
+    assert_eq!(game.state().attrition, 100);
+    // a large army far from home loses all its units, no more
+    let travel = Travel {
+        attacker: 0,
+        defender: 1,
+        amount: 5_000_000,
+        composition: Vec::new(),
+        remaining: 2,
+        total: 2,
+        path: vec![(150, 35), (151, 35), (152, 35)],
+        step: 0,
+        progress: 0,
+    };
+    assert_eq!(supply::next_losses(game.state(), &travel), 5_000_000);
+}
+
+#[test]
+fn random_players_finish_rounds_without_panicking() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.register_ai(1, Box::new(RandomAi::new()));
+    let start = game.state().turn;
+    for _ in 0..20 {
+        game.apply_action("end");
+        game.run_ai_turn();
+    }
+    assert!(game.state().turn > start || game.state().game_over);
+}
+
+#[test]
+fn products_of_accepted_numbers_do_not_overflow() {
+    let config = config_with(|c| {
+        let cities = &mut section(c, "cities")["cities"];
+        cities[0]["starting_resources"] = Value::from(1_000_000);
+        cities[1]["units"]["units"] = serde_json::json!([{"id_units": "mele", "nb_units": 5000}]);
+        section(c, "units")["units"][0]["attack"] = Value::from(1_000_000);
+        section(c, "units")["units"][0]["upkeep"] = Value::from(1_000_000);
+        let tour = &mut section(c, "buildings")["buildings"][0];
+        tour["build_time"] = Value::from(0);
+        tour["production"] = serde_json::json!({
+            "prod_type": "UNIT", "prod_unit_id": "mele", "amount": 1_000_000, "time": 1_000_000, "cost": 1_000_000
+        });
+        // many buildings producing, storing and costing as much as the DSL allows
+        section(c, "buildings")["buildings"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({
+                "name": "mine", "cost": 1_000_000, "build_time": 0, "slots": 0,
+                "production": { "prod_type": "RESSOURCE", "amount": 1_000_000, "time": 1, "cost": 0 },
+                "storage": 1_000_000, "maintenance": 1_000_000, "defense": 1_000_000,
+                "prerequisites": { "prereqs": [] }
+            }));
+        let mines = vec![serde_json::json!({ "id_building": "mine", "level": 1 }); 5000];
+        let cities = &mut section(c, "cities")["cities"];
+        for city in cities.as_array_mut().unwrap() {
+            city["nb_slots_buildings"] = Value::from(1_000_000);
+            city["nb_slots_units"] = Value::from(1_000_000);
+            city["buildings"]["elements"] = serde_json::json!(mines);
+        }
+        let game = section(c, "map_x");
+        for option in [
+            "storage",
+            "build_queue",
+            "city_stack",
+            "army_stack",
+            "cancel_refund",
+        ] {
+            game[option] = Value::from(1_000_000);
+        }
+        game["capture"] = Value::from("on");
+        add_section(
+            c,
+            serde_json::json!({
+                "traits": [{
+                    "city": "Demacia", "building_discount": 100,
+                    "travel_speed": 100, "income_bonus": 1_000_000
+                }]
+            }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    assert_eq!(game.state().calculate_city_power(1), i32::MAX);
+    game.register_ai(1, Box::new(RandomAi::new()));
+    for _ in 0..20 {
+        for action in [
+            "build tour",
+            "build mine",
+            "hire mele 1000000",
+            "income",
+            "loan 100",
+            "gift Noxus 1000000",
+            "scout Noxus",
+            "simulate attack Noxus 1000000",
+            "attack Noxus 1000000",
+            "cancel build",
+            "demolish mine",
+            "end",
+        ] {
+            game.apply_action(action);
+        }
+        game.run_ai_turn();
+    }
+}
+
+#[test]
+fn large_battles_do_not_overflow() {
+    let config = config_with(|c| {
+        let cities = &mut section(c, "cities")["cities"];
+        for city in cities.as_array_mut().unwrap() {
+            city["units"]["units"] = serde_json::json!([{"id_units": "mele", "nb_units": 5000}]);
+        }
+        let mele = &mut section(c, "units")["units"][0];
+        mele["attack"] = Value::from(1_000_000);
+        mele["defense"] = Value::from(1_000_000);
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    assert_eq!(combat::city_defense(game.state(), 1), i32::MAX);
+    let losses = combat::casualties(i32::MAX, i32::MAX, 5000, 5000);
+    assert_eq!((losses.attacker, losses.defender), (2500, 2500));
+
+    game.apply_action("simulate attack Noxus 5000");
+    game.apply_action("attack Noxus 5000");
+    let army = game.state().travels.last().unwrap().clone();
+    assert_eq!(composition::power(game.state(), &army), i32::MAX);
+    for _ in 0..200 {
+        if game.state().travels.is_empty() {
+            break;
+        }
+        game.apply_action("end");
+        game.run_ai_turn();
+    }
+    assert!(game.state().travels.is_empty());
+}
+
+#[test]
+fn large_incomes_do_not_overflow() {
+    use clivilization_engine::game::turn_rules::projected_income;
+
+    let config = config_with(|c| {
+        section(c, "buildings")["buildings"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({
+                "name": "mine", "cost": 1, "build_time": 1, "slots": 1,
+                "production": { "prod_type": "RESSOURCE", "amount": 1_000_000, "time": 1, "cost": 0 },
+                "prerequisites": { "prereqs": [] }
+            }));
+        let city = &mut section(c, "cities")["cities"][0];
+        city["nb_slots_buildings"] = Value::from(5000);
+        city["buildings"]["elements"] = serde_json::json!(vec![
+            serde_json::json!({ "id_building": "mine", "level": 1 });
+            5000
+        ]);
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    assert_eq!(projected_income(game.state(), 0), i32::MAX);
+    for _ in 0..5 {
+        game.state_mut().on_turn_start(0);
+        game.state_mut().turn += 1;
+    }
+    assert_eq!(game.state().civilizations[0].resources.ressources, i32::MAX);
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn documents_pushed_while_being_typed_leave_the_running_game_untouched() {
+    use clivilization_engine::game::live;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let starting = game.state().civilizations[0].resources.ressources;
+    game.state_mut().civilizations[0].resources.ressources = 99;
+    assert!(live::reload(&mut game, r#"{"sections": [{"map_x": 160"#).is_err());
+    assert!(live::reload(&mut game, r#"{"patch": {"sections": null}}"#).is_err());
+    assert_eq!(game.state().civilizations[0].resources.ressources, 99);
+
+    // a valid document replaces the game and lists what changed
+    let config = config_with(|c| {
+        section(c, "buildings")["buildings"][0]["cost"] = Value::from(7);
+    });
+    let line: Value = serde_json::from_str(&config).unwrap();
+    live::reload(&mut game, &line.to_string()).unwrap();
+    assert_eq!(game.state().buildings[0].cost, 7);
+    assert_eq!(game.state().civilizations[0].resources.ressources, starting);
+    assert_eq!(game.state().config_changes, ["Bâtiment tour: cost 3 -> 7"]);
+}
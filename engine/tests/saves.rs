@@ -0,0 +1,640 @@
+//! Saved games restore the running match exactly.
+
+use clivilization_engine::game::action::Action;
+use clivilization_engine::game::checksum::canonical;
+use clivilization_engine::game::events::GameEvent;
+use clivilization_engine::game::map::Terrain;
+use clivilization_engine::game::saves::{self, SAVE_VERSION, SavedGame, save_path};
+use clivilization_engine::game::{Game, RandomAi};
+use common::{CONFIG, add_section, config_with, section};
+
+mod common;
+
+/// A save of every released format, which later engines must keep loading
+const FIXTURES: [(u32, &str); 1] = [(1, include_str!("fixtures/save_v1.json"))];
+
+#[test]
+fn a_saved_game_is_restored_exactly() {
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.enable_dynamic_difficulty(1);
+    game.apply(Action::Build(Some("tour".to_string())));
+    game.apply(Action::Loan(20));
+    game.apply(Action::EndTurn);
+    game.apply(Action::EndTurn);
+    game.apply(Action::Hire {
+        unit: Some("mele".to_string()),
+        amount: None,
+    });
+
+    let json = serde_json::to_string(&SavedGame::of(&game)).unwrap();
+    let restored = serde_json::from_str::<SavedGame>(&json)
+        .unwrap()
+        .restore()
+        .unwrap();
+
+    assert_eq!(canonical(restored.state()), canonical(game.state()));
+    assert_eq!(restored.state().map.seed, game.state().map.seed);
+}
+
+#[test]
+fn every_saved_field_changes_the_checksum() {
+    use clivilization_engine::ast::{self, PuzzleObjective, UnitInstance};
+    use clivilization_engine::game::checksum::state_checksum;
+    use clivilization_engine::game::conquest::Conquest;
+    use clivilization_engine::game::diplomacy;
+    use clivilization_engine::game::fog::Fog;
+    use clivilization_engine::game::state::GameState;
+    use clivilization_engine::game::surrender::Surrender;
+    use clivilization_engine::game::terraform::TerrainChange;
+    use clivilization_engine::game::tutorial::TutorialState;
+    use clivilization_engine::game::victory::PuzzleState;
+    type Edit = fn(&mut GameState);
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.enable_dynamic_difficulty(3);
+    let state = game.state_mut();
+    state.civilizations[0].city.units.units = vec![UnitInstance {
+        id_units: "mele".to_string(),
+        nb_units: 3,
+    }];
+    state.start_attack(0, 1, None).unwrap();
+    state.fog = Some(Fog::default());
+    state.surrender = Some(Surrender::from_terms(&ast::Surrender {
+        income: None,
+        power: None,
+        turns: 5,
+    }));
+    let rules = ast::Puzzle {
+        max_actions: None,
+        max_builds: None,
+        max_turns: Some(10),
+        objective: PuzzleObjective::SURVIVE,
+        target: None,
+    };
+    state.puzzle = Some(PuzzleState::new(rules, state).unwrap());
+    state.tutorial = Some(TutorialState::new(Vec::new(), state));
+    let base = game.state().clone();
+
+    let edits: [(&str, Edit); 16] = [
+        ("turn", |s| s.turn += 1),
+        ("player", |s| s.player_turn = 1),
+        ("game over", |s| s.game_over = true),
+        ("winner", |s| s.winner = Some(0)),
+        ("seed", |s| s.map = s.map.reseeded("other".to_string())),
+        ("configuration", |s| s.config_hash ^= 1),
+        ("civilizations", |s| s.civilizations[1].morale -= 1),
+        ("diplomacy", |s| {
+            diplomacy::incident(s, 1, 0, 5);
+        }),
+        ("travels", |s| s.travels[0].remaining += 1),
+        ("conquests", |s| {
+            s.pending_conquests.push(Conquest {
+                attacker: 0,
+                defender: 1,
+                attacker_power: 1,
+                defender_power: 0,
+                army: Vec::new(),
+            });
+        }),
+        ("terrain", |s| {
+            s.terrain_changes.push(TerrainChange {
+                x: 1,
+                y: 1,
+                terrain: Terrain::Mountain,
+            });
+        }),
+        ("fog", |s| s.fog = None),
+        ("surrender", |s| s.surrender.as_mut().unwrap().turns += 1),
+        ("puzzle", |s| s.puzzle.as_mut().unwrap().actions_used += 1),
+        ("tutorial", |s| s.tutorial.as_mut().unwrap().current += 1),
+        ("difficulty", |s| {
+            s.difficulty.as_mut().unwrap().interval += 1;
+        }),
+    ];
+    for (field, edit) in edits {
+        let mut state = base.clone();
+        edit(&mut state);
+        assert_ne!(state_checksum(&state), state_checksum(&base), "{field}");
+    }
+    // the event log is not saved, and left out of the checksum with it
+    let mut state = base.clone();
+    state
+        .events
+        .publish(state.turn, GameEvent::Eliminated { civ: 1 });
+    assert_eq!(state_checksum(&state), state_checksum(&base));
+}
+
+#[test]
+fn eliminated_civilizations_stop_scoring_turns_after_a_restore() {
+    use clivilization_engine::game::conquest::{self, Conquest};
+    use clivilization_engine::game::scoring::turns_survived;
+
+    let config = config_with(|c| {
+        add_section(c, serde_json::json!({ "weights": { "per_turn": 10 } }));
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    let razed = game.state().turn;
+    game.state_mut().pending_conquests.push(Conquest {
+        attacker: 0,
+        defender: 1,
+        attacker_power: 3,
+        defender_power: 0,
+        army: Vec::new(),
+    });
+    conquest::resolve(game.state_mut(), "Raze");
+
+    let mut restored = SavedGame::of(&game).restore().unwrap();
+    restored.state_mut().turn = razed + 5;
+    assert_eq!(turns_survived(restored.state(), 1), razed);
+    assert_eq!(turns_survived(restored.state(), 0), razed + 5);
+}
+
+#[test]
+fn play_by_mail_resumes_the_turn_files_handed_over_after_a_conquest() {
+    use clivilization_engine::ast::UnitInstance;
+    use clivilization_engine::game::pbem;
+
+    let config = config_with(|c| {
+        let cities = section(c, "cities")["cities"].as_array_mut().unwrap();
+        // Noxus a few turns of march away, and a third player for the game to go on without it
+        cities[1]["x"] = 14.into();
+        cities[1]["y"] = 10.into();
+        let mut ionia = cities[0].clone();
+        ionia["name"] = "Ionia".into();
+        ionia["x"] = 60.into();
+        ionia["y"] = 30.into();
+        cities.push(ionia);
+        for city in cities.iter_mut() {
+            city["player_type"] = "PLAYER".into();
+        }
+    });
+    let path = std::env::temp_dir().join(format!(
+        "clivilization-pbem-conquest-{}.turn",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let mut game = Game::from_string(&config).unwrap();
+    game.state_mut().civilizations[0].city.units.units = vec![UnitInstance {
+        id_units: "mele".to_string(),
+        nb_units: 20,
+    }];
+    pbem::start(&mut game, path.clone(), "shared secret").unwrap();
+    game.apply(Action::Attack {
+        target: Some("Noxus".to_string()),
+        amount: None,
+        units: Vec::new(),
+    });
+    // each player razes the city it conquered, ends its turn and hands the game over
+    let play = |game: &mut Game| {
+        for _ in 0..3 {
+            game.apply(Action::EndTurn);
+            while let Some(popup) = &game.state().popup {
+                let answer = if popup.title == "Conquest" {
+                    "Raze"
+                } else {
+                    ""
+                };
+                game.submit_popup_input(answer);
+            }
+            if pbem::hand_over(game).unwrap().is_some() {
+                return;
+            }
+        }
+        panic!("the turn was never handed over");
+    };
+    play(&mut game);
+    let mut after_conquest = 0;
+    while after_conquest < 2 {
+        let mut resumed = pbem::resume(&path, "shared secret").unwrap();
+        assert!(resumed.state().turn < 20, "Noxus was never conquered");
+        if resumed.state().civilizations[1].eliminated_at.is_some() {
+            after_conquest += 1;
+        }
+        play(&mut resumed);
+    }
+    assert!(pbem::read(&path, "shared secret").is_ok());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn replays_store_the_inputs_compressed_and_stream_them_back() {
+    use clivilization_engine::game::replay::{
+        self, Input, REPLAY_VERSION, ReplayEvent, ReplayHeader, ReplayRecorder,
+    };
+
+    let path = std::env::temp_dir().join(format!(
+        "clivilization-stream-{}.replay.zst",
+        std::process::id()
+    ));
+    let header = ReplayHeader {
+        version: REPLAY_VERSION,
+        config: Some(CONFIG.to_string()),
+        seed: "Swain".to_string(),
+        ai_seeds: vec![None, Some(7)],
+    };
+    let mut recorder = ReplayRecorder::create_at(path.clone(), &header).unwrap();
+    let mut raw = serde_json::to_string(&header).unwrap().len();
+    for turn in 0..500 {
+        for input in [Input::Action("build tour".to_string()), Input::Step] {
+            let event = ReplayEvent {
+                turn,
+                player: 0,
+                input,
+            };
+            raw += serde_json::to_string(&event).unwrap().len() + 1;
+            recorder.record(&event);
+        }
+    }
+    recorder.finish().unwrap();
+    // flushed at every turn, the stream still shrinks far below its JSON lines
+    let size = usize::try_from(std::fs::metadata(&path).unwrap().len()).unwrap();
+    assert!(size * 4 < raw, "{size} bytes for {raw}");
+
+    let (read, mut events) = replay::open(&path).unwrap();
+    assert_eq!(read.config.as_deref(), Some(CONFIG));
+    assert_eq!(read.ai_seeds, [None, Some(7)]);
+    let first = events.next().unwrap().unwrap();
+    assert!(matches!(first.input, Input::Action(ref a) if a == "build tour"));
+    assert_eq!(events.count(), 999);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn replays_put_back_the_recorded_state_when_the_game_drifted_from_it() {
+    use clivilization_engine::game::replay::{
+        Input, REPLAY_VERSION, ReplayEvent, ReplayHeader, ReplayRecorder,
+    };
+
+    let mut drifted = Game::from_string(CONFIG).unwrap();
+    drifted.state_mut().civilizations[0].resources.ressources = 50;
+    let path = std::env::temp_dir().join(format!(
+        "clivilization-resync-{}.replay.zst",
+        std::process::id()
+    ));
+    let header = ReplayHeader {
+        version: REPLAY_VERSION,
+        config: Some(CONFIG.to_string()),
+        seed: drifted.state().map.seed.clone(),
+        ai_seeds: Vec::new(),
+    };
+    let mut recorder = ReplayRecorder::create_at(path.clone(), &header).unwrap();
+    let snapshot = SavedGame::of_state(drifted.state(), None);
+    for input in [
+        Input::Snapshot(Box::new(snapshot)),
+        Input::Action("build tour".to_string()),
+    ] {
+        recorder.record(&ReplayEvent {
+            turn: drifted.state().turn,
+            player: 0,
+            input,
+        });
+    }
+    recorder.finish().unwrap();
+
+    let replayed = Game::play_replay(&path, |_| {}).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        replayed.state().civilizations[0].resources.ressources,
+        50 - 3
+    );
+    assert_eq!(replayed.state().civilizations[0].constructions.len(), 1);
+}
+
+#[test]
+fn campaigns_unlock_their_scenarios_in_order_and_carry_the_army_over() {
+    use clivilization_engine::game::campaign::{Campaign, CarryOver};
+
+    let dir = std::env::temp_dir().join(format!("clivilization-campaign-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("first.json"), CONFIG).unwrap();
+    std::fs::write(dir.join("second.json"), CONFIG).unwrap();
+    let manifest = serde_json::json!({
+        "name": format!("test campaign {}", std::process::id()),
+        "scenarios": [
+            { "title": "First", "config": "first.json" },
+            { "title": "Second", "config": "second.json" }
+        ]
+    });
+    let manifest_path = dir.join("campaign.json");
+    std::fs::write(&manifest_path, manifest.to_string()).unwrap();
+
+    let mut campaign = Campaign::load(&manifest_path.to_string_lossy()).unwrap();
+    assert_eq!(campaign.next_scenario(), 0);
+    assert!(!campaign.is_unlocked(1));
+    let fresh = campaign.start_scenario(0).unwrap();
+    let starting = fresh.state().civilizations[0].resources.ressources;
+
+    // winning the first scenario unlocks the second, played with what the winner kept
+    campaign.progress.completed[0] = true;
+    campaign.progress.carry_over = Some(CarryOver {
+        resources: 10,
+        units: vec![("mele".to_string(), 3), ("dragon".to_string(), 1)],
+        ..CarryOver::default()
+    });
+    assert!(campaign.is_unlocked(1));
+    assert_eq!(campaign.next_scenario(), 1);
+    let game = campaign.start_scenario(1).unwrap();
+    let demacia = &game.state().civilizations[0];
+    assert_eq!(demacia.resources.ressources, starting + 10);
+    let units: Vec<_> = demacia
+        .city
+        .units
+        .units
+        .iter()
+        .map(|u| (u.id_units.as_str(), u.nb_units))
+        .collect();
+    assert_eq!(units, [("mele", 3)]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn save_names_cannot_leave_the_saves_directory() {
+    assert!(save_path("../preferences").is_err());
+    assert!(save_path("before-attack_2").is_ok());
+}
+
+#[test]
+fn map_events_change_the_terrain_for_good() {
+    let config = config_with(|c| {
+        add_section(
+            c,
+            serde_json::json!({
+                "events": [{
+                    "name": "volcan",
+                    "turn": 3,
+                    "x": 40,
+                    "y": 20,
+                    "terraform": { "terrain": "MOUNTAIN", "radius": 2 }
+                }]
+            }),
+        );
+    });
+    let mut game = Game::from_string(&config).unwrap();
+    // one round: the map changes when the turn of the first civilization starts
+    game.apply(Action::EndTurn);
+    game.apply(Action::EndTurn);
+    assert_eq!(game.state().turn, 3);
+    assert_eq!(game.state().map.tiles[20][40], Terrain::Mountain);
+    assert!(
+        game.state()
+            .events
+            .events_of(0)
+            .any(|(_, e)| matches!(e, GameEvent::Terraformed { .. }))
+    );
+
+    let json = serde_json::to_string(&SavedGame::of(&game)).unwrap();
+    let restored = serde_json::from_str::<SavedGame>(&json)
+        .unwrap()
+        .restore()
+        .unwrap();
+    assert_eq!(restored.state().map.tiles, game.state().map.tiles);
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn daily_challenges_give_everyone_the_same_locked_map_for_the_day() {
+    use clivilization_engine::game::daily::DailyChallenge;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    let challenge = DailyChallenge::for_date("2026-10-17");
+    let mut first = Game::from_string(CONFIG).unwrap();
+    let mut second = Game::from_string(CONFIG).unwrap();
+    challenge.apply(first.state_mut());
+    DailyChallenge::for_date("2026-10-17").apply(second.state_mut());
+    assert_eq!(first.state().map.tiles, second.state().map.tiles);
+    assert_eq!(first.state().nb_turns, challenge.nb_turns);
+    assert!((20..=40).contains(&challenge.nb_turns));
+    assert_ne!(DailyChallenge::for_date("2026-10-18").seed, challenge.seed);
+
+    // the map of the day cannot be reseeded
+    first.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE));
+    assert_eq!(first.state().map.seed, challenge.seed);
+    let entry = challenge.score(first.state());
+    assert_eq!(entry.player, "Demacia");
+    assert!(!entry.won);
+}
+
+#[test]
+fn game_codes_share_the_map_of_a_game() {
+    use clivilization_engine::game::game_code::GameCode;
+
+    let mut game = Game::from_string(CONFIG).unwrap();
+    game.state_mut().map =
+        clivilization_engine::game::map::GameMap::new("shared".to_string(), 120, 30);
+    let code = GameCode::of(game.state()).encode();
+    assert!(
+        code.chars()
+            .all(|c| c == '-' || c.is_ascii_uppercase() || c.is_ascii_digit())
+    );
+
+    let mut other = Game::from_string(CONFIG).unwrap();
+    GameCode::decode(&code.to_lowercase())
+        .unwrap()
+        .apply(other.state_mut())
+        .unwrap();
+    assert_eq!(other.state().map.seed, "shared");
+    assert_eq!(
+        (other.state().map.width, other.state().map.height),
+        (120, 30)
+    );
+    assert_eq!(other.state().map.tiles, game.state().map.tiles);
+
+    // a mistyped character is refused
+    let typo = if code.starts_with('A') { 'B' } else { 'A' };
+    let mistyped = format!("{typo}{}", &code[1..]);
+    assert!(GameCode::decode(&mistyped).is_err());
+}
+
+#[test]
+fn the_game_history_reports_win_rates_per_configuration() {
+    use clivilization_engine::game::history::{self, GameRecord, Participant};
+
+    let record = |config: &str, seed: &str, winner: &str| GameRecord {
+        finished_at: 0,
+        seed: seed.to_string(),
+        config_hash: config.to_string(),
+        participants: ["Demacia", "Noxus"]
+            .into_iter()
+            .map(|name| Participant {
+                name: name.to_string(),
+                player_type: "AI".to_string(),
+                score: if name == winner { 600 } else { 100 },
+            })
+            .collect(),
+        winner: Some(winner.to_string()),
+        turns: 30,
+    };
+    let records = [
+        record("aaaa", "first", "Demacia"),
+        record("aaaa", "second", "Noxus"),
+        record("bbbb", "other", "Noxus"),
+        record("aaaa", "third", "Demacia"),
+    ];
+    let report = history::report(&records, Some("aaaa"), 2);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines[0], "Last 2 of 3 games:");
+    // newest first
+    assert!(lines[1].starts_with("  config aaaa seed 'third': 30 turns, winner Demacia"));
+    assert!(lines[2].contains("seed 'second'"));
+    assert!(lines.contains(&"    Demacia: 2/3 (67%)"));
+    assert!(lines.contains(&"    Noxus: 1/3 (33%)"));
+    assert!(!report.contains("bbbb"));
+    assert_eq!(
+        history::report(&records, Some("cccc"), 2),
+        "No recorded games."
+    );
+
+    // the winner of a finished game scores its survival and its victory
+    let mut game = Game::from_string(CONFIG).unwrap();
+    let before = history::score(game.state(), 0);
+    game.state_mut().civilizations[1].alive = false;
+    game.state_mut().winner = Some(0);
+    game.state_mut().game_over = true;
+    assert_eq!(history::score(game.state(), 0), before + 500);
+}
+
+#[test]
+fn signed_files_are_rejected_once_tampered_with() {
+    use clivilization_engine::game::signing::{sign_file, signature_path, verify_file};
+
+    let path =
+        std::env::temp_dir().join(format!("clivilization-signed-{}.json", std::process::id()));
+    let save = serde_json::to_string(&SavedGame::of(&Game::from_string(CONFIG).unwrap())).unwrap();
+    std::fs::write(&path, &save).unwrap();
+    let error = verify_file(&path, "tournament").unwrap_err();
+    assert!(format!("{error:#}").contains("missing signature"));
+
+    let sig_path = sign_file(&path, "tournament").unwrap();
+    assert_eq!(sig_path, signature_path(&path));
+    assert!(verify_file(&path, "tournament").is_ok());
+    assert!(verify_file(&path, "another key").is_err());
+    std::fs::write(&path, save.replacen("Demacia", "Demacib", 1)).unwrap();
+    let error = verify_file(&path, "tournament").unwrap_err();
+    assert!(format!("{error:#}").contains("was tampered with"));
+    std::fs::write(&sig_path, "not hex").unwrap();
+    let error = verify_file(&path, "tournament").unwrap_err();
+    assert!(format!("{error:#}").contains("malformed signature"));
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&sig_path);
+}
+
+#[test]
+fn saves_of_every_released_format_are_migrated() {
+    for (version, fixture) in FIXTURES {
+        let saved =
+            saves::parse(fixture).unwrap_or_else(|e| panic!("save format {version}: {e:#}"));
+        assert_eq!(saved.version, SAVE_VERSION);
+        let game = saved.restore().unwrap();
+        assert_eq!(game.state().turn, 3);
+        assert_eq!(game.state().travels[0].amount, 2);
+    }
+    // armies of format 1 saves have no composition and fight with one point per unit
+    let game = saves::parse(FIXTURES[0].1).unwrap().restore().unwrap();
+    assert!(game.state().travels[0].composition.is_empty());
+    assert!(
+        game.state()
+            .civilizations
+            .iter()
+            .all(|c| c.waiting.is_empty())
+    );
+
+    let mut newer: serde_json::Value = serde_json::from_str(FIXTURES[0].1).unwrap();
+    newer["version"] = (SAVE_VERSION + 1).into();
+    assert!(saves::parse(&newer.to_string()).is_err());
+}
+
+#[test]
+fn play_by_mail_hands_an_encrypted_turn_file_from_player_to_player() {
+    use clivilization_engine::game::pbem::{self, TurnFile};
+    use clivilization_engine::game::terraform::TerrainChange;
+
+    let config = config_with(|c| {
+        for city in section(c, "cities")["cities"].as_array_mut().unwrap() {
+            city["player_type"] = "PLAYER".into();
+        }
+    });
+    let path = std::env::temp_dir().join(format!("clivilization-pbem-{}.turn", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut game = Game::from_string(&config).unwrap();
+    pbem::start(&mut game, path.clone(), "shared secret").unwrap();
+    game.apply(Action::Loan(20));
+    assert!(pbem::hand_over(&mut game).unwrap().is_none());
+    game.apply(Action::EndTurn);
+    let (written, next) = pbem::hand_over(&mut game).unwrap().unwrap();
+    assert_eq!(written, path);
+    assert_eq!(next, game.state().civilizations[1].city.name);
+    let data = std::fs::read(&path).unwrap();
+    assert!(!String::from_utf8_lossy(&data).contains(&next));
+
+    // the next player continues where the game was handed over
+    let mut resumed = pbem::resume(&path, "shared secret").unwrap();
+    assert_eq!(canonical(resumed.state()), canonical(game.state()));
+    assert!(pbem::resume(&path, "wrong secret").is_err());
+    let mut tampered = data.clone();
+    let middle = tampered.len() / 2;
+    tampered[middle] ^= 1;
+    assert!(pbem::open("shared secret", &tampered).is_err());
+    let (_, keys) = pbem::open("shared secret", &data).unwrap();
+
+    resumed.apply(Action::EndTurn);
+    assert!(pbem::hand_over(&mut resumed).unwrap().is_some());
+    let (file, _, _) = pbem::read(&path, "shared secret").unwrap();
+    assert_eq!(file.chain.len(), 2);
+    assert_eq!(file.chain[0].inputs.len(), 2);
+
+    // a rewritten history breaks the action chain, even sealed with the right key
+    let forge = |edit: &dyn Fn(&mut TurnFile)| {
+        let mut forged: TurnFile = file.clone();
+        edit(&mut forged);
+        std::fs::write(
+            &path,
+            pbem::seal(&keys, &serde_json::to_string(&forged).unwrap()).unwrap(),
+        )
+        .unwrap();
+        let Err(e) = pbem::read(&path, "shared secret") else {
+            panic!("forged turn file accepted")
+        };
+        format!("{e:#}")
+    };
+    let error = forge(&|f| {
+        f.chain[0].inputs.pop();
+    });
+    assert!(error.contains("action chain broken"), "{error}");
+    // and the game handed over must be the one its inputs replay to
+    let error = forge(&|f| f.save.civilizations[1].resources.ressources += 100);
+    assert!(error.contains("does not match its action chain"), "{error}");
+    // down to the fields the checksum of the links leaves to the save
+    let error = forge(&|f| f.save.seed = "forged".to_string());
+    assert!(error.contains("seed differs"), "{error}");
+    let error = forge(&|f| {
+        f.save.terrain_changes.push(TerrainChange {
+            x: 1,
+            y: 1,
+            terrain: Terrain::Mountain,
+        });
+    });
+    assert!(error.contains("terrain_changes differs"), "{error}");
+    let error = forge(&|f| f.save.config = None);
+    assert!(error.contains("config differs"), "{error}");
+
+    // the turns of the AIs replay from their recorded inputs
+    let _ = std::fs::remove_file(&path);
+    let mut game = Game::from_string(CONFIG).unwrap();
+    pbem::start(&mut game, path.clone(), "shared secret").unwrap();
+    game.register_ai(1, Box::new(RandomAi::seeded(7)));
+    for _ in 0..3 {
+        game.apply(Action::EndTurn);
+        game.run_ai_turn();
+    }
+    assert!(pbem::hand_over(&mut game).unwrap().is_some());
+    let mut resumed = pbem::resume(&path, "shared secret").unwrap();
+    assert_eq!(canonical(resumed.state()), canonical(game.state()));
+    resumed.register_ai(1, Box::new(RandomAi::seeded(8)));
+    resumed.apply(Action::EndTurn);
+    resumed.run_ai_turn();
+    assert!(pbem::hand_over(&mut resumed).unwrap().is_some());
+    assert!(pbem::read(&path, "shared secret").is_ok());
+    let _ = std::fs::remove_file(&path);
+}